@@ -0,0 +1,155 @@
+//! Analysis of the degree of the constraints of an AIR.
+//!
+//! The degree of a constraint is its degree as a polynomial in the values of the trace columns at
+//! the local and next rows. Challenges, global values and public inputs are the same in every row,
+//! so they are of degree zero.
+
+use alloc::vec;
+use alloc::vec::Vec;
+
+use super::extension::cubic::CubicParser;
+use super::parser::AirParser;
+use super::AirConstraint;
+use crate::math::prelude::*;
+use crate::polynomial::parser::PolynomialParser;
+
+/// A parser whose variables are the degrees of the expressions they stand for, and which records
+/// the maximal degree of the constraints.
+#[derive(Debug, Clone)]
+pub struct DegreeParser<F> {
+    local_slice: Vec<usize>,
+    next_slice: Vec<usize>,
+    challenge_slice: Vec<usize>,
+    global_slice: Vec<usize>,
+    public_slice: Vec<usize>,
+    degree: usize,
+    _marker: core::marker::PhantomData<F>,
+}
+
+impl<F: Field> DegreeParser<F> {
+    pub fn new(
+        num_columns: usize,
+        num_challenges: usize,
+        num_global_values: usize,
+        num_public_inputs: usize,
+    ) -> Self {
+        Self {
+            local_slice: vec![1; num_columns],
+            next_slice: vec![1; num_columns],
+            challenge_slice: vec![0; num_challenges],
+            global_slice: vec![0; num_global_values],
+            public_slice: vec![0; num_public_inputs],
+            degree: 0,
+            _marker: core::marker::PhantomData,
+        }
+    }
+
+    /// The maximal degree of the constraints parsed so far.
+    pub fn degree(&self) -> usize {
+        self.degree
+    }
+
+    /// The maximal degree of the constraints of `constraint`.
+    pub fn degree_of<C: AirConstraint<Self>>(&mut self, constraint: &C) -> usize {
+        self.degree = 0;
+        constraint.eval(self);
+        self.degree
+    }
+
+    fn record(&mut self, constraint: usize) {
+        self.degree = self.degree.max(constraint);
+    }
+}
+
+impl<F: Field> AirParser for DegreeParser<F> {
+    type Field = F;
+
+    type Var = usize;
+
+    fn local_slice(&self) -> &[Self::Var] {
+        &self.local_slice
+    }
+
+    fn next_slice(&self) -> &[Self::Var] {
+        &self.next_slice
+    }
+
+    fn challenge_slice(&self) -> &[Self::Var] {
+        &self.challenge_slice
+    }
+
+    fn global_slice(&self) -> &[Self::Var] {
+        &self.global_slice
+    }
+
+    fn public_slice(&self) -> &[Self::Var] {
+        &self.public_slice
+    }
+
+    fn constraint(&mut self, constraint: Self::Var) {
+        self.record(constraint);
+    }
+
+    fn constraint_transition(&mut self, constraint: Self::Var) {
+        self.record(constraint);
+    }
+
+    fn constraint_first_row(&mut self, constraint: Self::Var) {
+        self.record(constraint);
+    }
+
+    fn constraint_last_row(&mut self, constraint: Self::Var) {
+        self.record(constraint);
+    }
+
+    fn constant(&mut self, _value: Self::Field) -> Self::Var {
+        0
+    }
+
+    fn add(&mut self, a: Self::Var, b: Self::Var) -> Self::Var {
+        a.max(b)
+    }
+
+    fn sub(&mut self, a: Self::Var, b: Self::Var) -> Self::Var {
+        a.max(b)
+    }
+
+    fn neg(&mut self, a: Self::Var) -> Self::Var {
+        a
+    }
+
+    fn mul(&mut self, a: Self::Var, b: Self::Var) -> Self::Var {
+        a + b
+    }
+}
+
+impl<F: Field> PolynomialParser for DegreeParser<F> {}
+
+impl<F: Field, E: CubicParameters<F>> CubicParser<E> for DegreeParser<F> {}
+
+#[cfg(test)]
+mod tests {
+    use plonky2::field::goldilocks_field::GoldilocksField;
+
+    use super::*;
+
+    #[test]
+    fn test_degree_parser() {
+        type F = GoldilocksField;
+        let mut parser = DegreeParser::<F>::new(3, 1, 1, 1);
+
+        let a = parser.local_slice()[0];
+        let b = parser.next_slice()[1];
+        let challenge = parser.challenge_slice()[0];
+
+        let ab = parser.mul(a, b);
+        let abc = parser.mul(ab, challenge);
+        let sum = parser.add_const(abc, F::ONE);
+        parser.constraint(sum);
+        assert_eq!(parser.degree(), 2);
+
+        let a_cubed = parser.mul(ab, a);
+        parser.constraint_transition(a_cubed);
+        assert_eq!(parser.degree(), 3);
+    }
+}
@@ -1,7 +1,10 @@
 pub mod curta_air;
+pub mod degree;
 pub mod extension;
 pub mod opening;
 pub mod parser;
+pub mod round;
+pub mod usage;
 
 #[cfg(test)]
 pub mod fibonacci;
@@ -0,0 +1,213 @@
+//! Analysis of the rounds of the values read by the constraints of an AIR.
+//!
+//! The trace columns, public inputs and global values are committed in rounds, and the challenges
+//! of a round are squeezed after committing to the values of all the previous rounds. A constraint
+//! reading a challenge must also read a value committed in the round of the challenge or later,
+//! otherwise it only constrains values the prover chose before seeing the challenge.
+
+use alloc::vec::Vec;
+
+use anyhow::{anyhow, Result};
+
+use super::extension::cubic::CubicParser;
+use super::parser::AirParser;
+use super::AirConstraint;
+use crate::math::prelude::*;
+use crate::polynomial::parser::PolynomialParser;
+
+/// The latest rounds of the committed values and of the challenges an expression depends on.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct RoundVar {
+    committed: Option<usize>,
+    challenge: Option<usize>,
+}
+
+impl RoundVar {
+    /// A value committed in `round`.
+    pub const fn committed(round: usize) -> Self {
+        Self {
+            committed: Some(round),
+            challenge: None,
+        }
+    }
+
+    /// A challenge squeezed in `round`.
+    pub const fn challenge(round: usize) -> Self {
+        Self {
+            committed: None,
+            challenge: Some(round),
+        }
+    }
+
+    fn join(self, other: Self) -> Self {
+        Self {
+            committed: self.committed.max(other.committed),
+            challenge: self.challenge.max(other.challenge),
+        }
+    }
+
+    /// Whether the expression reads a challenge but no value committed after it is squeezed.
+    fn is_premature(&self) -> bool {
+        match self.challenge {
+            Some(challenge) => self.committed.map_or(true, |round| round < challenge),
+            None => false,
+        }
+    }
+}
+
+/// A parser whose variables are the rounds of the values an expression depends on, and which
+/// records the constraints reading a challenge before any value committed after it.
+#[derive(Debug, Clone)]
+pub struct RoundParser<F> {
+    local_slice: Vec<RoundVar>,
+    challenge_slice: Vec<RoundVar>,
+    global_slice: Vec<RoundVar>,
+    public_slice: Vec<RoundVar>,
+    premature: Option<RoundVar>,
+    _marker: core::marker::PhantomData<F>,
+}
+
+impl<F: Field> RoundParser<F> {
+    /// A parser for the given commitment rounds of the columns, global values and public inputs,
+    /// and the rounds in which the challenges are squeezed.
+    pub fn new(
+        column_rounds: &[usize],
+        challenge_rounds: &[usize],
+        global_rounds: &[usize],
+        public_rounds: &[usize],
+    ) -> Self {
+        let committed = |rounds: &[usize]| rounds.iter().map(|r| RoundVar::committed(*r)).collect();
+        Self {
+            local_slice: committed(column_rounds),
+            challenge_slice: challenge_rounds
+                .iter()
+                .map(|r| RoundVar::challenge(*r))
+                .collect(),
+            global_slice: committed(global_rounds),
+            public_slice: committed(public_rounds),
+            premature: None,
+            _marker: core::marker::PhantomData,
+        }
+    }
+
+    /// Checks that every constraint of `constraint` reading a challenge also reads a value
+    /// committed in the round of the challenge or later.
+    pub fn check<C: AirConstraint<Self>>(&mut self, constraint: &C) -> Result<()> {
+        self.premature = None;
+        constraint.eval(self);
+        match self.premature.and_then(|var| var.challenge) {
+            Some(round) => Err(anyhow!(
+                "Constraint uses a challenge of round {} but no value committed in round {} or later",
+                round,
+                round
+            )),
+            None => Ok(()),
+        }
+    }
+
+    fn record(&mut self, constraint: RoundVar) {
+        if self.premature.is_none() && constraint.is_premature() {
+            self.premature = Some(constraint);
+        }
+    }
+}
+
+impl<F: Field> AirParser for RoundParser<F> {
+    type Field = F;
+
+    type Var = RoundVar;
+
+    fn local_slice(&self) -> &[Self::Var] {
+        &self.local_slice
+    }
+
+    fn next_slice(&self) -> &[Self::Var] {
+        &self.local_slice
+    }
+
+    fn challenge_slice(&self) -> &[Self::Var] {
+        &self.challenge_slice
+    }
+
+    fn global_slice(&self) -> &[Self::Var] {
+        &self.global_slice
+    }
+
+    fn public_slice(&self) -> &[Self::Var] {
+        &self.public_slice
+    }
+
+    fn constraint(&mut self, constraint: Self::Var) {
+        self.record(constraint);
+    }
+
+    fn constraint_transition(&mut self, constraint: Self::Var) {
+        self.record(constraint);
+    }
+
+    fn constraint_first_row(&mut self, constraint: Self::Var) {
+        self.record(constraint);
+    }
+
+    fn constraint_last_row(&mut self, constraint: Self::Var) {
+        self.record(constraint);
+    }
+
+    fn constant(&mut self, _value: Self::Field) -> Self::Var {
+        RoundVar::default()
+    }
+
+    fn add(&mut self, a: Self::Var, b: Self::Var) -> Self::Var {
+        a.join(b)
+    }
+
+    fn sub(&mut self, a: Self::Var, b: Self::Var) -> Self::Var {
+        a.join(b)
+    }
+
+    fn neg(&mut self, a: Self::Var) -> Self::Var {
+        a
+    }
+
+    fn mul(&mut self, a: Self::Var, b: Self::Var) -> Self::Var {
+        a.join(b)
+    }
+}
+
+impl<F: Field> PolynomialParser for RoundParser<F> {}
+
+impl<F: Field, E: CubicParameters<F>> CubicParser<E> for RoundParser<F> {}
+
+#[cfg(test)]
+mod tests {
+    use plonky2::field::goldilocks_field::GoldilocksField;
+
+    use super::*;
+
+    #[test]
+    fn test_round_parser() {
+        type F = GoldilocksField;
+        let mut parser = RoundParser::<F>::new(&[0, 0, 1], &[1], &[1], &[0]);
+
+        let a = parser.local_slice()[0];
+        let b = parser.next_slice()[1];
+        let digest = parser.local_slice()[2];
+        let beta = parser.challenge_slice()[0];
+
+        // A constraint on a column committed after the challenge is squeezed.
+        let beta_a = parser.mul(beta, a);
+        let value = parser.add(beta_a, b);
+        parser.assert_eq(value, digest);
+        assert!(parser.premature.is_none());
+
+        // A constraint only on columns committed before the challenge is squeezed.
+        parser.assert_eq(beta_a, b);
+        assert_eq!(
+            parser.premature,
+            Some(RoundVar {
+                committed: Some(0),
+                challenge: Some(1)
+            })
+        );
+    }
+}
@@ -0,0 +1,216 @@
+//! Analysis of the columns read by the constraints of an AIR.
+//!
+//! Most columns of a wide trace are only constrained on the local row. Their values at the next
+//! row are never used by the verifier, so they do not need to be opened at `zeta * g`.
+
+use alloc::collections::BTreeSet;
+use alloc::vec;
+use alloc::vec::Vec;
+
+use super::extension::cubic::CubicParser;
+use super::parser::AirParser;
+use super::{RAir, RAirData};
+use crate::math::prelude::*;
+use crate::polynomial::parser::PolynomialParser;
+
+/// A variable of the usage analysis, which only keeps track of the next row columns.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ColumnVar {
+    /// The value of a column at the next row.
+    Next(usize),
+    /// Any other value.
+    Other,
+}
+
+/// A parser which records the next row columns that are used by the constraints.
+///
+/// A column is recorded as soon as its next row value is an operand of an operation or of a
+/// constraint, so the recorded set may be larger than the set of columns the constraints depend
+/// on, but never smaller.
+#[derive(Debug, Clone)]
+pub struct ColumnUsageParser<F> {
+    local_slice: Vec<ColumnVar>,
+    next_slice: Vec<ColumnVar>,
+    challenge_slice: Vec<ColumnVar>,
+    global_slice: Vec<ColumnVar>,
+    public_slice: Vec<ColumnVar>,
+    next_columns: BTreeSet<usize>,
+    _marker: core::marker::PhantomData<F>,
+}
+
+impl<F: Field> ColumnUsageParser<F> {
+    pub fn new<A: RAirData>(air: &A) -> Self {
+        let num_columns = air.num_columns();
+        let num_challenges = air.round_data().iter().map(|d| d.num_challenges).sum();
+        Self {
+            local_slice: vec![ColumnVar::Other; num_columns],
+            next_slice: (0..num_columns).map(ColumnVar::Next).collect(),
+            challenge_slice: vec![ColumnVar::Other; num_challenges],
+            global_slice: vec![ColumnVar::Other; air.num_global_values()],
+            public_slice: vec![ColumnVar::Other; air.num_public_inputs()],
+            next_columns: BTreeSet::new(),
+            _marker: core::marker::PhantomData,
+        }
+    }
+
+    /// The next row columns used by the constraints, in increasing order.
+    pub fn next_columns(&self) -> Vec<usize> {
+        self.next_columns.iter().copied().collect()
+    }
+
+    fn read(&mut self, var: ColumnVar) {
+        if let ColumnVar::Next(column) = var {
+            self.next_columns.insert(column);
+        }
+    }
+}
+
+/// The columns of `air` whose values at the next row are used by its constraints.
+pub fn next_row_columns<F: Field, A>(air: &A) -> Vec<usize>
+where
+    A: RAir<ColumnUsageParser<F>>,
+{
+    let mut parser = ColumnUsageParser::new(air);
+    air.eval(&mut parser);
+    parser.next_columns()
+}
+
+impl<F: Field> AirParser for ColumnUsageParser<F> {
+    type Field = F;
+
+    type Var = ColumnVar;
+
+    fn local_slice(&self) -> &[Self::Var] {
+        &self.local_slice
+    }
+
+    fn next_slice(&self) -> &[Self::Var] {
+        &self.next_slice
+    }
+
+    fn challenge_slice(&self) -> &[Self::Var] {
+        &self.challenge_slice
+    }
+
+    fn global_slice(&self) -> &[Self::Var] {
+        &self.global_slice
+    }
+
+    fn public_slice(&self) -> &[Self::Var] {
+        &self.public_slice
+    }
+
+    fn constraint(&mut self, constraint: Self::Var) {
+        self.read(constraint);
+    }
+
+    fn constraint_transition(&mut self, constraint: Self::Var) {
+        self.read(constraint);
+    }
+
+    fn constraint_first_row(&mut self, constraint: Self::Var) {
+        self.read(constraint);
+    }
+
+    fn constraint_last_row(&mut self, constraint: Self::Var) {
+        self.read(constraint);
+    }
+
+    fn constant(&mut self, _value: Self::Field) -> Self::Var {
+        ColumnVar::Other
+    }
+
+    fn add(&mut self, a: Self::Var, b: Self::Var) -> Self::Var {
+        self.read(a);
+        self.read(b);
+        ColumnVar::Other
+    }
+
+    fn sub(&mut self, a: Self::Var, b: Self::Var) -> Self::Var {
+        self.read(a);
+        self.read(b);
+        ColumnVar::Other
+    }
+
+    fn neg(&mut self, a: Self::Var) -> Self::Var {
+        self.read(a);
+        ColumnVar::Other
+    }
+
+    fn mul(&mut self, a: Self::Var, b: Self::Var) -> Self::Var {
+        self.read(a);
+        self.read(b);
+        ColumnVar::Other
+    }
+}
+
+impl<F: Field> PolynomialParser for ColumnUsageParser<F> {}
+
+impl<F: Field, E: CubicParameters<F>> CubicParser<E> for ColumnUsageParser<F> {}
+
+#[cfg(test)]
+mod tests {
+    use serde::{Deserialize, Serialize};
+
+    use super::*;
+    use crate::chip::builder::tests::*;
+    use crate::chip::builder::AirBuilder;
+    use crate::chip::register::element::ElementRegister;
+    use crate::chip::register::Register;
+    use crate::chip::AirParameters;
+    use crate::plonky2::stark::config::{
+        CurtaPoseidonGoldilocksConfig, PoseidonGoldilocksStarkConfig,
+    };
+    use crate::plonky2::stark::tests::{test_recursive_starky, test_starky};
+    use crate::plonky2::stark::Starky;
+
+    #[derive(Debug, Clone, Serialize, Deserialize)]
+    pub struct UsageTest;
+
+    impl AirParameters for UsageTest {
+        type Field = GoldilocksField;
+        type CubicParams = GoldilocksCubicParameters;
+        type Instruction = EmptyInstruction<GoldilocksField>;
+
+        const NUM_FREE_COLUMNS: usize = 4;
+    }
+
+    #[test]
+    fn test_compressed_openings() {
+        type F = GoldilocksField;
+        type L = UsageTest;
+        type SC = PoseidonGoldilocksStarkConfig;
+        type C = CurtaPoseidonGoldilocksConfig;
+
+        let mut builder = AirBuilder::<L>::new();
+        let counter = builder.alloc::<ElementRegister>();
+        let a = builder.alloc::<ElementRegister>();
+        let b = builder.alloc::<ElementRegister>();
+        let c = builder.alloc::<ElementRegister>();
+        builder.set_to_expression_first_row(&counter, F::ZERO.into());
+        builder.set_to_expression_transition(&counter.next(), counter.expr() + F::ONE);
+        builder.set_to_expression(&a, counter.expr() * F::from_canonical_u8(2));
+        builder.set_to_expression(&b, a.expr() + counter.expr());
+        builder.set_to_expression(&c, b.expr() * a.expr());
+
+        let (air, trace_data) = builder.build();
+
+        // Only the counter is constrained on the next row.
+        let counter_column = counter.register().index();
+        assert_eq!(next_row_columns::<F, _>(&air), vec![counter_column]);
+
+        let num_rows = 1 << 5;
+        let generator = ArithmeticGenerator::<L>::new(trace_data, num_rows);
+        let writer = generator.new_writer();
+        for i in 0..num_rows {
+            writer.write_row_instructions(&generator.air_data, i);
+        }
+
+        let stark = Starky::new(air).with_compressed_openings::<F>();
+        assert_eq!(stark.next_columns(), vec![counter_column]);
+        let config = SC::standard_fast_config(num_rows);
+        let public = writer.public().unwrap().clone();
+        test_starky::<_, _, _, C, 2>(&stark, &config, &generator, &public);
+        test_recursive_starky(stark, config, generator, &public);
+    }
+}
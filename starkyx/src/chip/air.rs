@@ -3,10 +3,13 @@ use super::{AirParameters, Chip};
 use crate::air::parser::AirParser;
 use crate::air::{AirConstraint, RAir, RAirData, RoundDatum};
 
+/// The maximal degree of the constraints of a chip.
+pub const MAX_CONSTRAINT_DEGREE: usize = 3;
+
 impl<L: AirParameters> RAirData for Chip<L> {
     /// The maximal constraint degree
     fn constraint_degree(&self) -> usize {
-        3
+        MAX_CONSTRAINT_DEGREE
     }
 
     /// Columns for each round
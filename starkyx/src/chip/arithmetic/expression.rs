@@ -62,10 +62,45 @@ impl<F: Field> ArithmeticExpression<F> {
         self.expression.registers()
     }
 
+    /// Returns the degree of the expression as a polynomial in the register values.
+    pub fn degree(&self) -> usize {
+        self.expression.degree()
+    }
+
     /// Returns true if any of the registers in the expression is a trace register.
     pub fn is_trace(&self) -> bool {
         !self.registers().iter().all(|reg| !reg.is_trace())
     }
+
+    /// Returns the values of the expression if it is a constant.
+    pub fn as_constant(&self) -> Option<&[F]> {
+        match &self.expression {
+            ArithmeticExpressionSlice::Const(constants) => Some(constants),
+            _ => None,
+        }
+    }
+
+    fn constant_values(&self) -> Option<Vec<F>> {
+        self.as_constant().map(|constants| constants.to_vec())
+    }
+
+    fn is_constant_value(&self, value: F) -> bool {
+        self.as_constant()
+            .map_or(false, |constants| constants.iter().all(|c| *c == value))
+    }
+
+    /// Orders the operands of a commutative operation.
+    ///
+    /// Constants are placed last, and other expressions are ordered by the registers they use, so
+    /// that the representation of an expression does not depend on the order of its operands.
+    fn canonical_pair(a: Self, b: Self) -> (Self, Self) {
+        let key = |e: &Self| (e.as_constant().is_some(), e.registers());
+        if key(&b) < key(&a) {
+            (b, a)
+        } else {
+            (a, b)
+        }
+    }
 }
 
 impl<F: Field> Add for ArithmeticExpression<F> {
@@ -76,12 +111,49 @@ impl<F: Field> Add for ArithmeticExpression<F> {
             self.size, rhs.size,
             "Cannot add arithmetic expressions of different sizes"
         );
+        let size = self.size;
+        let (left, right) = Self::canonical_pair(self, rhs);
+
+        if let Some(b) = right.constant_values() {
+            if let Some(a) = left.constant_values() {
+                return Self::from_constant_vec(
+                    a.iter().zip(b.iter()).map(|(x, y)| *x + *y).collect(),
+                );
+            }
+            if right.is_constant_value(F::ZERO) {
+                return left;
+            }
+            // Fold the constants of `(x + a) + b` into `x + (a + b)`.
+            if let ArithmeticExpressionSlice::Add(x, a) = &left.expression {
+                if let ArithmeticExpressionSlice::Const(a) = a.as_ref() {
+                    let sum = a
+                        .iter()
+                        .zip(b.iter())
+                        .map(|(x, y)| *x + *y)
+                        .collect::<Vec<_>>();
+                    if sum.iter().all(|s| *s == F::ZERO) {
+                        return Self {
+                            expression: x.as_ref().clone(),
+                            size,
+                        };
+                    }
+                    return Self {
+                        expression: ArithmeticExpressionSlice::Add(
+                            x.clone(),
+                            Arc::new(ArithmeticExpressionSlice::Const(sum)),
+                        ),
+                        size,
+                    };
+                }
+            }
+        }
+
         Self {
             expression: ArithmeticExpressionSlice::Add(
-                Arc::new(self.expression),
-                Arc::new(rhs.expression),
+                Arc::new(left.expression),
+                Arc::new(right.expression),
             ),
-            size: self.size,
+            size,
         }
     }
 }
@@ -100,6 +172,12 @@ impl<F: Field> Sub for ArithmeticExpression<F> {
             self.size, rhs.size,
             "Cannot subtract arithmetic expressions of different sizes"
         );
+
+        // Subtracting a constant is represented as adding its negation.
+        if let Some(b) = rhs.constant_values() {
+            return self + b.into_iter().map(|x| -x).collect::<Vec<_>>();
+        }
+
         Self {
             expression: ArithmeticExpressionSlice::Sub(
                 Arc::new(self.expression),
@@ -121,13 +199,7 @@ impl<F: Field> Add<Vec<F>> for ArithmeticExpression<F> {
             rhs.len(),
             self.size
         );
-        Self {
-            expression: ArithmeticExpressionSlice::Add(
-                Arc::new(self.expression),
-                Arc::new(ArithmeticExpressionSlice::Const(rhs)),
-            ),
-            size: self.size,
-        }
+        self + Self::from_constant_vec(rhs)
     }
 }
 
@@ -142,13 +214,7 @@ impl<F: Field> Sub<Vec<F>> for ArithmeticExpression<F> {
             rhs.len(),
             self.size
         );
-        Self {
-            expression: ArithmeticExpressionSlice::Sub(
-                Arc::new(self.expression),
-                Arc::new(ArithmeticExpressionSlice::Const(rhs)),
-            ),
-            size: self.size,
-        }
+        self - Self::from_constant_vec(rhs)
     }
 }
 
@@ -172,8 +238,24 @@ impl<F: Field> Mul<F> for ArithmeticExpression<F> {
     type Output = Self;
 
     fn mul(self, rhs: F) -> Self::Output {
+        if rhs == F::ONE {
+            return self;
+        }
+        if rhs == F::ZERO {
+            return Self::from_constant_vec(vec![F::ZERO; self.size]);
+        }
+        if let Some(values) = self.constant_values() {
+            return Self::from_constant_vec(values.into_iter().map(|x| x * rhs).collect());
+        }
+        // Fold the constants of `(c * x) * d` into `(c * d) * x`.
+        let expression = match self.expression {
+            ArithmeticExpressionSlice::ConstMul(c, x) => {
+                ArithmeticExpressionSlice::ConstMul(c * rhs, x)
+            }
+            expression => ArithmeticExpressionSlice::ConstMul(rhs, Arc::new(expression)),
+        };
         Self {
-            expression: ArithmeticExpressionSlice::ConstMul(rhs, Arc::new(self.expression)),
+            expression,
             size: self.size,
         }
     }
@@ -184,28 +266,120 @@ impl<F: Field> Mul for ArithmeticExpression<F> {
 
     fn mul(self, rhs: Self) -> Self::Output {
         match (self.size, rhs.size) {
-            (1, _) => Self {
-                expression: ArithmeticExpressionSlice::ScalarMul(
-                    Arc::new(self.expression),
-                    Arc::new(rhs.expression),
-                ),
-                size: rhs.size,
-            },
-            (_, 1) => Self {
-                expression: ArithmeticExpressionSlice::ScalarMul(
-                    Arc::new(rhs.expression),
-                    Arc::new(self.expression),
-                ),
-                size: self.size,
-            },
-            (n, m) if n == m => Self {
-                expression: ArithmeticExpressionSlice::Mul(
-                    Arc::new(self.expression),
-                    Arc::new(rhs.expression),
-                ),
-                size: n,
-            },
+            (1, _) | (_, 1) => {
+                let (scalar, expr) = if self.size == 1 && rhs.size == 1 {
+                    Self::canonical_pair(self, rhs)
+                } else if self.size == 1 {
+                    (self, rhs)
+                } else {
+                    (rhs, self)
+                };
+                if let Some(c) = expr.as_constant().filter(|_| expr.size == 1) {
+                    return scalar * c[0];
+                }
+                if let Some(c) = scalar.as_constant() {
+                    return expr * c[0];
+                }
+                let size = expr.size;
+                Self {
+                    expression: ArithmeticExpressionSlice::ScalarMul(
+                        Arc::new(scalar.expression),
+                        Arc::new(expr.expression),
+                    ),
+                    size,
+                }
+            }
+            (n, m) if n == m => {
+                let (left, right) = Self::canonical_pair(self, rhs);
+                if let (Some(a), Some(b)) = (left.as_constant(), right.as_constant()) {
+                    return Self::from_constant_vec(
+                        a.iter().zip(b.iter()).map(|(x, y)| *x * *y).collect(),
+                    );
+                }
+                if right.is_constant_value(F::ZERO) {
+                    return right;
+                }
+                if right.is_constant_value(F::ONE) {
+                    return left;
+                }
+                Self {
+                    expression: ArithmeticExpressionSlice::Mul(
+                        Arc::new(left.expression),
+                        Arc::new(right.expression),
+                    ),
+                    size: n,
+                }
+            }
             _ => panic!("Cannot multiply arithmetic expressions of different sizes"),
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use plonky2::field::goldilocks_field::GoldilocksField;
+    use plonky2::field::types::Sample;
+
+    use super::*;
+
+    type F = GoldilocksField;
+
+    fn input(index: usize, size: usize) -> ArithmeticExpression<F> {
+        ArithmeticExpression {
+            expression: ArithmeticExpressionSlice::from_raw_register(MemorySlice::Local(
+                index, size,
+            )),
+            size,
+        }
+    }
+
+    #[test]
+    fn test_constant_folding() {
+        let a = ArithmeticExpression::from_constant_vec(vec![F::ONE, F::TWO]);
+        let b = ArithmeticExpression::from_constant_vec(vec![F::TWO, F::TWO]);
+
+        let sum = a.clone() + b.clone();
+        assert_eq!(sum.as_constant(), Some(&[F::from_canonical_u8(3); 2][..]));
+        let product = (a.clone() - b.clone()) * b.clone() * F::TWO;
+        assert_eq!(
+            product.as_constant(),
+            Some(&[-F::from_canonical_u8(4), F::ZERO][..])
+        );
+
+        // Constants are folded into the non-constant part of an expression.
+        let x = input(0, 2);
+        let expr = (x.clone() + a.clone()) + b.clone() - a.clone() - b;
+        assert!(matches!(
+            expr.expression,
+            ArithmeticExpressionSlice::Input(_)
+        ));
+        let expr = (x.clone() * F::TWO) * F::from_canonical_u8(3);
+        assert!(matches!(
+            expr.expression,
+            ArithmeticExpressionSlice::ConstMul(c, _) if c == F::from_canonical_u8(6)
+        ));
+        assert_eq!(expr.degree(), 1);
+        assert_eq!((x * F::ZERO).as_constant(), Some(&[F::ZERO; 2][..]));
+    }
+
+    #[test]
+    fn test_canonical_ordering() {
+        let x = input(0, 1);
+        let y = input(1, 1);
+        let c = ArithmeticExpression::from_constant(F::TWO);
+
+        let forward = bincode::serialize(&(x.clone() + y.clone() + c.clone())).unwrap();
+        let backward = bincode::serialize(&(c.clone() + (y.clone() + x.clone()))).unwrap();
+        assert_eq!(forward, backward);
+
+        let forward = bincode::serialize(&(x.clone() * y.clone())).unwrap();
+        let backward = bincode::serialize(&(y.clone() * x.clone())).unwrap();
+        assert_eq!(forward, backward);
+
+        // Reordering does not change the value of the expression.
+        let values = [F::rand(), F::rand()];
+        let expr = (c.clone() - y.clone()) * x.clone() + c * y.clone() + x;
+        let expected = (F::TWO - values[1]) * values[0] + F::TWO * values[1] + values[0];
+        assert_eq!(expr.read_from_slice(&values), vec![expected]);
+    }
+}
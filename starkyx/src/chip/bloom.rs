@@ -0,0 +1,272 @@
+//! Membership checks for Ethereum log blooms.
+//!
+//! A log bloom is a 2048-bit filter stored as 256 big-endian bytes. An item is added to the filter
+//! by setting three bits, whose indices are given by the low 11 bits of each of the first three
+//! pairs of bytes of `keccak256(item)`. Bit `b` of the filter is bit `b % 8` of the byte at index
+//! `255 - b / 8`.
+//!
+//! The gadget stores the bits of the filter in memory, indexed by the bit index `b`. Every
+//! membership query decomposes the relevant bytes of the item hash into bits, computes the three
+//! bit indices and reads the filter at these indices, asserting that all three bits are set. The
+//! hash itself is taken as an input, so that the gadget can be combined with any Keccak chip.
+
+use serde::{Deserialize, Serialize};
+
+use super::arithmetic::expression::ArithmeticExpression;
+use super::builder::AirBuilder;
+use super::memory::pointer::slice::Slice;
+use super::memory::time::Time;
+use super::register::array::ArrayRegister;
+use super::register::bit::BitRegister;
+use super::register::element::ElementRegister;
+use super::register::Register;
+use super::trace::writer::TraceWriter;
+use super::uint::bytes::register::ByteRegister;
+use super::AirParameters;
+use crate::math::prelude::*;
+
+/// The number of bytes in a log bloom.
+pub const BLOOM_BYTE_LENGTH: usize = 256;
+
+/// The number of bits in a log bloom.
+pub const BLOOM_BIT_LENGTH: usize = 8 * BLOOM_BYTE_LENGTH;
+
+/// The number of bits set in the bloom for every item.
+pub const BLOOM_NUM_HASHES: usize = 3;
+
+/// A log bloom filter stored in memory.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BloomFilter {
+    /// The bits of the filter, indexed by bit index.
+    pub bits: ArrayRegister<BitRegister>,
+    /// The number of reads of each bit of the filter.
+    multiplicities: ArrayRegister<ElementRegister>,
+    slice: Slice<BitRegister>,
+    queries: Vec<BloomQuery>,
+}
+
+/// The registers of a membership query of an item in a bloom filter.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BloomQuery {
+    /// The first bytes of the hash of the item.
+    hash: ArrayRegister<ByteRegister>,
+    /// The bit decomposition of the first `2 * BLOOM_NUM_HASHES` bytes of the hash.
+    hash_bits: ArrayRegister<BitRegister>,
+}
+
+impl<L: AirParameters> AirBuilder<L> {
+    /// Allocates a public bloom filter and stores its bits in memory.
+    ///
+    /// The local memory must be initialized before calling this function.
+    pub fn bloom_filter(&mut self) -> BloomFilter {
+        let bits = self.alloc_array_public::<BitRegister>(BLOOM_BIT_LENGTH);
+        let multiplicities = self.alloc_array_global::<ElementRegister>(BLOOM_BIT_LENGTH);
+
+        let slice = self.uninit_slice();
+        for (i, (bit, multiplicity)) in bits.iter().zip(multiplicities.iter()).enumerate() {
+            self.set(
+                &slice.get(i),
+                bit,
+                &Time::zero(),
+                Some(multiplicity),
+                None,
+                None,
+            );
+        }
+
+        BloomFilter {
+            bits,
+            multiplicities,
+            slice,
+            queries: Vec::new(),
+        }
+    }
+
+    /// Asserts that the item whose Keccak hash starts with the bytes `hash` is in the bloom
+    /// `filter`, in every row of the trace.
+    pub fn assert_in_bloom(
+        &mut self,
+        filter: &mut BloomFilter,
+        hash: &ArrayRegister<ByteRegister>,
+    ) {
+        assert!(
+            hash.len() >= 2 * BLOOM_NUM_HASHES,
+            "Expected at least {} bytes of the hash, got {}",
+            2 * BLOOM_NUM_HASHES,
+            hash.len()
+        );
+        assert!(hash.is_trace(), "Hash must be a trace register");
+
+        let hash_bits = self.alloc_array::<BitRegister>(8 * 2 * BLOOM_NUM_HASHES);
+
+        // Constrain the bit decomposition of the hash bytes.
+        for (i, byte) in hash.iter().take(2 * BLOOM_NUM_HASHES).enumerate() {
+            let bits = hash_bits.get_subarray(8 * i..8 * i + 8);
+            let value = bits
+                .iter()
+                .enumerate()
+                .fold(ArithmeticExpression::zero(), |acc, (j, bit)| {
+                    acc + bit.expr() * L::Field::from_canonical_u32(1 << j)
+                });
+            self.assert_expressions_equal(byte.expr(), value);
+        }
+
+        for k in 0..BLOOM_NUM_HASHES {
+            // The bit index is given by the low 3 bits of the first byte and the 8 bits of the
+            // second byte, in big-endian order.
+            let high_bits = hash_bits.get_subarray(16 * k..16 * k + 3);
+            let low_bits = hash_bits.get_subarray(16 * k + 8..16 * k + 16);
+            let index_expr = high_bits
+                .iter()
+                .chain(low_bits.iter())
+                .zip((8..11).chain(0..8))
+                .fold(ArithmeticExpression::zero(), |acc, (bit, j)| {
+                    acc + bit.expr() * L::Field::from_canonical_u32(1 << j)
+                });
+            let index = self.alloc::<ElementRegister>();
+            self.set_to_expression(&index, index_expr);
+
+            let bit = self.get(&filter.slice.get_at(index), &Time::zero(), None, None);
+            self.assert_expressions_equal(bit.expr(), ArithmeticExpression::one());
+        }
+
+        filter.queries.push(BloomQuery {
+            hash: *hash,
+            hash_bits,
+        });
+    }
+}
+
+/// Returns the bits of a bloom filter given as bytes, indexed by bit index.
+pub fn bloom_bits(filter: &[u8; BLOOM_BYTE_LENGTH]) -> Vec<bool> {
+    (0..BLOOM_BIT_LENGTH)
+        .map(|b| (filter[BLOOM_BYTE_LENGTH - 1 - b / 8] >> (b % 8)) & 1 == 1)
+        .collect()
+}
+
+/// Returns the indices of the bits set in the bloom for an item with Keccak hash `hash`.
+pub fn bloom_bit_indices(hash: &[u8]) -> [usize; BLOOM_NUM_HASHES] {
+    core::array::from_fn(|k| {
+        (((hash[2 * k] as usize) << 8) | hash[2 * k + 1] as usize) & (BLOOM_BIT_LENGTH - 1)
+    })
+}
+
+impl BloomFilter {
+    /// Writes the bits of the filter given as bytes.
+    pub fn write_filter<F: Field>(
+        &self,
+        writer: &TraceWriter<F>,
+        filter: &[u8; BLOOM_BYTE_LENGTH],
+    ) {
+        let bits = bloom_bits(filter);
+        writer.write_array(
+            &self.bits,
+            bits.into_iter().map(|b| F::from_canonical_u8(b as u8)),
+            0,
+        );
+    }
+
+    /// Writes the bit decompositions of the hashes of all queries and the number of reads of each
+    /// bit of the filter.
+    ///
+    /// The hash bytes must be written in all rows, and this function must be called before
+    /// writing the global instructions.
+    pub fn write_queries<F: PrimeField64>(&self, writer: &TraceWriter<F>, num_rows: usize) {
+        let mut counts = vec![0usize; BLOOM_BIT_LENGTH];
+        for query in self.queries.iter() {
+            for i in 0..num_rows {
+                let hash = writer
+                    .read_vec(&query.hash.get_subarray(0..2 * BLOOM_NUM_HASHES), i)
+                    .into_iter()
+                    .map(|x| x.as_canonical_u64() as u8)
+                    .collect::<Vec<_>>();
+                let bits = hash
+                    .iter()
+                    .flat_map(|byte| (0..8).map(move |j| F::from_canonical_u8((byte >> j) & 1)));
+                writer.write_array(&query.hash_bits, bits, i);
+
+                for index in bloom_bit_indices(&hash) {
+                    counts[index] += 1;
+                }
+            }
+        }
+
+        writer.write_array(
+            &self.multiplicities,
+            counts.into_iter().map(F::from_canonical_usize),
+            0,
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::{thread_rng, Rng};
+
+    use super::*;
+    use crate::chip::builder::tests::*;
+
+    #[derive(Debug, Clone, Serialize, Deserialize)]
+    pub struct BloomTest;
+
+    impl AirParameters for BloomTest {
+        type Field = GoldilocksField;
+        type CubicParams = GoldilocksCubicParameters;
+        type Instruction = EmptyInstruction<GoldilocksField>;
+
+        const NUM_FREE_COLUMNS: usize = 60;
+        const EXTENDED_COLUMNS: usize = 15;
+    }
+
+    #[test]
+    fn test_bloom_membership() {
+        type F = GoldilocksField;
+        type L = BloomTest;
+        type SC = PoseidonGoldilocksStarkConfig;
+
+        let mut builder = AirBuilder::<L>::new();
+        builder.init_local_memory();
+
+        let mut filter = builder.bloom_filter();
+        let hash = builder.alloc_array::<ByteRegister>(2 * BLOOM_NUM_HASHES);
+        builder.assert_in_bloom(&mut filter, &hash);
+
+        let (air, trace_data) = builder.build();
+        let num_rows = 1 << 10;
+        let generator = ArithmeticGenerator::<L>::new(trace_data, num_rows);
+
+        let mut rng = thread_rng();
+        let items = (0..20)
+            .map(|_| rng.gen::<[u8; 2 * BLOOM_NUM_HASHES]>())
+            .collect::<Vec<_>>();
+
+        let mut bloom = [0u8; BLOOM_BYTE_LENGTH];
+        for item in items.iter() {
+            for b in bloom_bit_indices(item) {
+                bloom[BLOOM_BYTE_LENGTH - 1 - b / 8] |= 1 << (b % 8);
+            }
+        }
+
+        let writer = generator.new_writer();
+        filter.write_filter(&writer, &bloom);
+        for i in 0..num_rows {
+            let item = items[i % items.len()];
+            writer.write_array(&hash, item.map(|x| F::from_canonical_u8(x)), i);
+        }
+        filter.write_queries(&writer, num_rows);
+        writer.write_global_instructions(&generator.air_data);
+        for i in 0..num_rows {
+            writer.write_row_instructions(&generator.air_data, i);
+        }
+
+        let stark = Starky::new(air);
+        let config = SC::standard_fast_config(num_rows);
+
+        let public = writer.public().unwrap().clone();
+        // Generate proof and verify as a stark
+        test_starky(&stark, &config, &generator, &public);
+
+        // Test the recursive proof.
+        test_recursive_starky(stark, config, generator, &public);
+    }
+}
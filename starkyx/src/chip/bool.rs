@@ -124,6 +124,18 @@ impl<F: Field> Instruction<F> for SelectInstruction {
             writer.write_slice(&self.result, &false_value);
         }
     }
+
+    fn inputs(&self) -> Option<Vec<MemorySlice>> {
+        Some(vec![
+            *self.bit.register(),
+            self.true_value,
+            self.false_value,
+        ])
+    }
+
+    fn outputs(&self) -> Option<Vec<MemorySlice>> {
+        Some(vec![self.result])
+    }
 }
 
 // #[cfg(test)]
@@ -0,0 +1,312 @@
+//! Conditional constraint groups.
+//!
+//! A call to `AirBuilder::when` registers the constraints and instructions declared by a closure
+//! multiplied by a selector expression, so that they only apply in the rows where the selector is
+//! non-zero. This replaces the manual multiplication of each constraint by the selector.
+
+use alloc::sync::Arc;
+
+use super::AirBuilder;
+use crate::air::degree::DegreeParser;
+use crate::air::AirConstraint;
+use crate::chip::air::MAX_CONSTRAINT_DEGREE;
+use crate::chip::arithmetic::expression::ArithmeticExpression;
+use crate::chip::arithmetic::ArithmeticConstraint;
+use crate::chip::constraint::Constraint;
+use crate::chip::instruction::set::AirInstruction;
+use crate::chip::AirParameters;
+use crate::math::prelude::*;
+
+impl<L: AirParameters> AirBuilder<L> {
+    /// Registers the constraints and instructions declared in `scope` conditioned on `condition`.
+    ///
+    /// Every constraint declared in the scope is multiplied by `condition`, and every instruction
+    /// is only written in the rows where `condition` is equal to one. Scopes can be nested, in
+    /// which case the conditions are multiplied together.
+    ///
+    /// The degree of the conditioned constraints and instructions must not exceed
+    /// `MAX_CONSTRAINT_DEGREE`, where the degree of an instruction is that of its constraints.
+    ///
+    /// Only arithmetic constraints and instructions can be conditioned. Global constraints and
+    /// instructions declared in the scope are registered unconditionally, and bus, lookup and
+    /// accumulator constraints are not allowed in the scope.
+    pub fn when<T>(
+        &mut self,
+        condition: ArithmeticExpression<L::Field>,
+        scope: impl FnOnce(&mut Self) -> T,
+    ) -> T
+    where
+        L::Instruction: AirConstraint<DegreeParser<L::Field>>,
+    {
+        assert_eq!(condition.size, 1, "Condition must be of size 1");
+
+        let num_constraints = self.constraints.len();
+        let num_instructions = self.instructions.len();
+
+        let output = scope(self);
+
+        let mut parser = DegreeParser::new(
+            L::num_columns(),
+            self.shared_memory.challenge_index(),
+            self.shared_memory.global_index(),
+            self.shared_memory.public_index(),
+        );
+        for instruction in self.instructions[num_instructions..].iter_mut() {
+            *instruction = conditioned_instruction(instruction.clone(), &condition, &mut parser);
+        }
+        for constraint in self.constraints[num_constraints..].iter_mut() {
+            *constraint = match constraint.clone() {
+                Constraint::Instruction(instruction) => Constraint::Instruction(
+                    conditioned_instruction(instruction, &condition, &mut parser),
+                ),
+                Constraint::Arithmetic(constraint) => {
+                    Constraint::Arithmetic(conditioned_arithmetic(constraint, &condition))
+                }
+                _ => panic!("Only arithmetic constraints and instructions can be conditioned"),
+            };
+        }
+
+        output
+    }
+}
+
+/// Multiplies the expression of an arithmetic constraint by `condition`.
+fn conditioned_arithmetic<F: Field>(
+    constraint: ArithmeticConstraint<F>,
+    condition: &ArithmeticExpression<F>,
+) -> ArithmeticConstraint<F> {
+    let apply = |expression: ArithmeticExpression<F>| {
+        let degree = condition.degree() + expression.degree();
+        assert!(
+            degree <= MAX_CONSTRAINT_DEGREE,
+            "Conditioned constraint has degree {}, maximum is {}",
+            degree,
+            MAX_CONSTRAINT_DEGREE
+        );
+        condition.clone() * expression
+    };
+    match constraint {
+        ArithmeticConstraint::First(expression) => ArithmeticConstraint::First(apply(expression)),
+        ArithmeticConstraint::Last(expression) => ArithmeticConstraint::Last(apply(expression)),
+        ArithmeticConstraint::Transition(expression) => {
+            ArithmeticConstraint::Transition(apply(expression))
+        }
+        ArithmeticConstraint::All(expression) => ArithmeticConstraint::All(apply(expression)),
+    }
+}
+
+/// Filters an instruction by `condition`, combining it with any existing filter.
+///
+/// The degree of the instruction is computed by `parser` from its constraints.
+fn conditioned_instruction<F: Field, I: AirConstraint<DegreeParser<F>>>(
+    instruction: AirInstruction<F, I>,
+    condition: &ArithmeticExpression<F>,
+    parser: &mut DegreeParser<F>,
+) -> AirInstruction<F, I> {
+    let (filter, instruction) = match instruction {
+        AirInstruction::Filtered(filter, instruction) => (condition.clone() * filter, instruction),
+        AirInstruction::Watch(..) => return instruction,
+        AirInstruction::CustomInstruction(_)
+        | AirInstruction::BitConstraint(_)
+        | AirInstruction::Assign(_)
+        | AirInstruction::Cycle(_) => (condition.clone(), Arc::new(instruction)),
+        _ => panic!("Only custom, bit, assign and cycle instructions can be conditioned"),
+    };
+
+    let instruction_degree = match instruction.as_ref() {
+        AirInstruction::CustomInstruction(i) => parser.degree_of(i),
+        AirInstruction::BitConstraint(i) => parser.degree_of(i),
+        AirInstruction::Assign(i) => parser.degree_of(i),
+        AirInstruction::Cycle(i) => parser.degree_of(i),
+        _ => unreachable!("Instructions cannot be filtered twice"),
+    };
+    let degree = filter.degree() + instruction_degree;
+    assert!(
+        degree <= MAX_CONSTRAINT_DEGREE,
+        "Conditioned instruction has degree {}, maximum is {}",
+        degree,
+        MAX_CONSTRAINT_DEGREE
+    );
+
+    AirInstruction::Filtered(filter, instruction)
+}
+
+#[cfg(test)]
+mod tests {
+    use serde::{Deserialize, Serialize};
+
+    use super::*;
+    use crate::air::parser::AirParser;
+    use crate::chip::builder::tests::*;
+    use crate::chip::instruction::ConstraintInstruction;
+    use crate::chip::register::bit::BitRegister;
+    use crate::chip::register::element::ElementRegister;
+    use crate::chip::register::Register;
+
+    #[derive(Debug, Clone, Serialize, Deserialize)]
+    pub struct ConditionTest;
+
+    impl AirParameters for ConditionTest {
+        type Field = GoldilocksField;
+        type CubicParams = GoldilocksCubicParameters;
+        type Instruction = EmptyInstruction<GoldilocksField>;
+
+        const NUM_FREE_COLUMNS: usize = 6;
+    }
+
+    /// The constraint `b = a^3`, of degree 3.
+    #[derive(Debug, Clone, Serialize, Deserialize)]
+    pub struct CubeInstruction {
+        a: ElementRegister,
+        b: ElementRegister,
+    }
+
+    impl ConstraintInstruction for CubeInstruction {}
+
+    impl<AP: AirParser> AirConstraint<AP> for CubeInstruction {
+        fn eval(&self, parser: &mut AP) {
+            let a = self.a.eval(parser);
+            let b = self.b.eval(parser);
+            let a_squared = parser.mul(a, a);
+            let a_cubed = parser.mul(a_squared, a);
+            parser.assert_eq(b, a_cubed);
+        }
+    }
+
+    #[derive(Debug, Clone, Serialize, Deserialize)]
+    pub struct CubeConditionTest;
+
+    impl AirParameters for CubeConditionTest {
+        type Field = GoldilocksField;
+        type CubicParams = GoldilocksCubicParameters;
+        type Instruction = CubeInstruction;
+
+        const NUM_FREE_COLUMNS: usize = 3;
+    }
+
+    #[test]
+    fn test_when() {
+        type F = GoldilocksField;
+        type L = ConditionTest;
+        type SC = PoseidonGoldilocksStarkConfig;
+
+        let mut builder = AirBuilder::<L>::new();
+        let selector = builder.alloc::<BitRegister>();
+        let inner_selector = builder.alloc::<BitRegister>();
+        let a = builder.alloc::<ElementRegister>();
+        let b = builder.alloc::<ElementRegister>();
+        let c = builder.alloc::<ElementRegister>();
+        let d = builder.alloc::<ElementRegister>();
+
+        builder.when(selector.expr(), |builder| {
+            builder.assert_equal(&a, &b);
+            builder.set_to_expression(&c, a.expr() * F::TWO);
+            builder.when(inner_selector.expr(), |builder| {
+                builder.set_to_expression(&d, c.expr() + b.expr());
+            });
+        });
+
+        let (air, trace_data) = builder.build();
+        let num_rows = 1 << 8;
+        let generator = ArithmeticGenerator::<L>::new(trace_data, num_rows);
+
+        let writer = generator.new_writer();
+        for i in 0..num_rows {
+            let selector_value = F::from_canonical_usize(i % 2);
+            let inner_value = F::from_canonical_usize((i / 2) % 2);
+            writer.write(&selector, &selector_value, i);
+            writer.write(&inner_selector, &inner_value, i);
+            writer.write(&a, &F::from_canonical_usize(i), i);
+            // The values of `b` only match `a` in the selected rows.
+            let b_value = if i % 2 == 1 { i } else { i + 7 };
+            writer.write(&b, &F::from_canonical_usize(b_value), i);
+            writer.write_row_instructions(&generator.air_data, i);
+
+            if i % 2 == 1 {
+                assert_eq!(writer.read(&c, i), F::from_canonical_usize(2 * i));
+            } else {
+                assert_eq!(writer.read(&c, i), F::ZERO);
+            }
+            if i % 4 == 3 {
+                assert_eq!(writer.read(&d, i), F::from_canonical_usize(3 * i));
+            } else {
+                assert_eq!(writer.read(&d, i), F::ZERO);
+            }
+        }
+
+        let stark = Starky::new(air);
+        let config = SC::standard_fast_config(num_rows);
+
+        // Generate proof and verify as a stark
+        test_starky(&stark, &config, &generator, &[]);
+
+        // Test the recursive proof.
+        test_recursive_starky(stark, config, generator, &[]);
+    }
+
+    #[test]
+    fn test_cube_zero_knowledge() {
+        type F = GoldilocksField;
+        type L = CubeConditionTest;
+        type SC = PoseidonGoldilocksStarkConfig;
+
+        let mut builder = AirBuilder::<L>::new();
+        let a = builder.alloc::<ElementRegister>();
+        let b = builder.alloc::<ElementRegister>();
+        builder.register_instruction(CubeInstruction { a, b });
+
+        let (air, trace_data) = builder.build();
+        let num_rows = 1 << 8;
+        let generator = ArithmeticGenerator::<L>::new(trace_data, num_rows);
+
+        let writer = generator.new_writer();
+        for i in 0..num_rows {
+            let a_value = F::from_canonical_usize(i);
+            writer.write(&a, &a_value, i);
+            writer.write(&b, &a_value.cube(), i);
+            writer.write_row_instructions(&generator.air_data, i);
+        }
+
+        // The blinded trace has degree `2n`, so the quotient of the degree 3 constraints is split
+        // into three chunks of degree `2n`, which requires a rate of at least 4.
+        let stark = Starky::new(air);
+        let config = SC::standard_100_bit_security(num_rows).with_zero_knowledge();
+        assert_eq!(stark.num_quotient_chunks(&config), 3);
+
+        // Generate proof and verify as a stark
+        test_starky(&stark, &config, &generator, &[]);
+
+        // Test the recursive proof.
+        test_recursive_starky(stark, config, generator, &[]);
+    }
+
+    #[test]
+    #[should_panic(expected = "Conditioned constraint has degree")]
+    fn test_when_degree_overflow() {
+        type L = ConditionTest;
+
+        let mut builder = AirBuilder::<L>::new();
+        let selector = builder.alloc::<BitRegister>();
+        let a = builder.alloc::<ElementRegister>();
+
+        builder.when(selector.expr() * selector.expr(), |builder| {
+            builder.assert_expression_zero(a.expr() * a.expr());
+        });
+    }
+
+    #[test]
+    #[should_panic(expected = "Conditioned instruction has degree 4")]
+    fn test_when_instruction_degree() {
+        type L = CubeConditionTest;
+
+        let mut builder = AirBuilder::<L>::new();
+        let selector = builder.alloc::<BitRegister>();
+        let a = builder.alloc::<ElementRegister>();
+        let b = builder.alloc::<ElementRegister>();
+
+        // The instruction is of degree 3, so it can not be conditioned on a selector.
+        builder.when(selector.expr(), |builder| {
+            builder.register_instruction(CubeInstruction { a, b });
+        });
+    }
+}
@@ -1,11 +1,23 @@
+use anyhow::{anyhow, Result};
+
 use super::{AirBuilder, AirParameters};
+use crate::air::round::RoundParser;
+use crate::chip::constraint::Constraint;
 use crate::chip::instruction::set::AirInstruction;
+use crate::chip::instruction::Instruction;
 use crate::chip::register::array::ArrayRegister;
 use crate::chip::register::cell::CellType;
+use crate::chip::register::challenge::ChallengeRegister;
 use crate::chip::register::element::ElementRegister;
 use crate::chip::register::memory::MemorySlice;
 use crate::chip::register::{Register, RegisterSerializable};
 
+/// The round in which the challenges of a chip are squeezed.
+///
+/// The execution trace and the public inputs are committed in round 0, and the extended trace and
+/// the global values are committed in round 1.
+pub const CHALLENGE_ROUND: usize = 1;
+
 impl<L: AirParameters> AirBuilder<L> {
     /// Allocates `size` cells/columns worth of memory and returns it as a `MemorySlice`.
     pub(crate) fn get_local_memory(&mut self, size: usize) -> MemorySlice {
@@ -107,6 +119,17 @@ impl<L: AirParameters> AirBuilder<L> {
         T::from_register(register)
     }
 
+    /// Allocates a challenge together with the round in which it is squeezed.
+    ///
+    /// The challenges are squeezed after committing to the execution trace and the public
+    /// inputs, so they can only be used to compute extended and global columns.
+    pub fn alloc_challenge_register<T: Register>(&mut self) -> ChallengeRegister<T> {
+        let challenge = ChallengeRegister::new(self.alloc_challenge::<T>(), CHALLENGE_ROUND);
+        self.challenge_rounds
+            .push((*challenge.challenge().register(), challenge.round()));
+        challenge
+    }
+
     pub fn alloc_array_challenge<T: Register>(&mut self, length: usize) -> ArrayRegister<T> {
         let size_of = T::size_of() * length;
         let register = self.get_challenge_memory(size_of);
@@ -194,4 +217,110 @@ impl<L: AirParameters> AirBuilder<L> {
             _ => false,
         }
     }
+
+    /// The round in which the memory `slice` is committed, or `None` for challenges.
+    fn commitment_round(slice: &MemorySlice) -> Option<usize> {
+        match slice {
+            MemorySlice::Local(index, _) | MemorySlice::Next(index, _) => {
+                if *index < L::NUM_ARITHMETIC_COLUMNS + L::NUM_FREE_COLUMNS {
+                    Some(0)
+                } else {
+                    Some(1)
+                }
+            }
+            MemorySlice::Public(..) => Some(0),
+            MemorySlice::Global(..) => Some(1),
+            MemorySlice::Challenge(..) => None,
+        }
+    }
+
+    /// The rounds in which the challenges are squeezed, indexed by challenge.
+    ///
+    /// Challenges allocated without a round are squeezed in `CHALLENGE_ROUND`.
+    fn challenge_rounds(&self) -> Vec<usize> {
+        let mut rounds = vec![CHALLENGE_ROUND; self.shared_memory.challenge_index()];
+        for (slice, round) in self.challenge_rounds.iter() {
+            let (start, end) = slice.get_range();
+            rounds[start..end].fill(*round);
+        }
+        rounds
+    }
+
+    /// Checks that no constraint or instruction uses a challenge on values committed before the
+    /// challenge is squeezed.
+    ///
+    /// An instruction may not compute a register committed before the challenges it reads, and a
+    /// constraint reading a challenge must constrain a value committed after it. Otherwise, the
+    /// prover would have to choose the committed values before seeing the challenges, breaking
+    /// either the soundness or the completeness of the Fiat-Shamir transform.
+    pub(crate) fn check_challenge_rounds(&self) -> Result<()> {
+        let challenge_rounds = self.challenge_rounds();
+
+        for instruction in self
+            .instructions
+            .iter()
+            .chain(self.global_instructions.iter())
+        {
+            let (Some(inputs), Some(outputs)) = (instruction.inputs(), instruction.outputs())
+            else {
+                continue;
+            };
+            let Some(challenge_round) = inputs
+                .iter()
+                .filter_map(|slice| match slice {
+                    MemorySlice::Challenge(..) => {
+                        let (start, end) = slice.get_range();
+                        challenge_rounds[start..end].iter().max().copied()
+                    }
+                    _ => None,
+                })
+                .max()
+            else {
+                continue;
+            };
+            for output in outputs.iter() {
+                let round = Self::commitment_round(output)
+                    .ok_or_else(|| anyhow!("Instruction {:?} writes to challenges", instruction))?;
+                if round < challenge_round {
+                    return Err(anyhow!(
+                        "Instruction {:?} uses a challenge of round {} to write a register of round {}",
+                        instruction,
+                        challenge_round,
+                        round
+                    ));
+                }
+            }
+        }
+
+        let column_rounds = (0..L::num_columns())
+            .map(|i| Self::commitment_round(&MemorySlice::Local(i, 1)).unwrap())
+            .collect::<Vec<_>>();
+        let global_rounds = vec![1; self.shared_memory.global_index()];
+        let public_rounds = vec![0; self.shared_memory.public_index()];
+        let mut parser = RoundParser::<L::Field>::new(
+            &column_rounds,
+            &challenge_rounds,
+            &global_rounds,
+            &public_rounds,
+        );
+        // Instructions are checked above from the registers they read and write.
+        for constraint in self
+            .constraints
+            .iter()
+            .chain(self.global_constraints.iter())
+        {
+            let result = match constraint {
+                Constraint::Instruction(_) => Ok(()),
+                Constraint::Arithmetic(constraint) => parser.check(constraint),
+                Constraint::Powers(powers) => parser.check(powers),
+                Constraint::Accumulator(accumulator) => parser.check(accumulator),
+                Constraint::Pointer(accumulator) => parser.check(accumulator),
+                Constraint::BusChannel(channel) => parser.check(channel),
+                Constraint::Bus(bus) => parser.check(bus),
+                Constraint::Lookup(lookup) => parser.check(lookup),
+            };
+            result.map_err(|e| anyhow!("{:?}: {}", constraint, e))?;
+        }
+        Ok(())
+    }
 }
@@ -1,10 +1,13 @@
 pub mod arithmetic;
+pub mod condition;
 pub mod memory;
 pub mod range_check;
 pub mod shared_memory;
 
 use core::cmp::Ordering;
 
+use anyhow::Result;
+
 use self::shared_memory::SharedMemory;
 use super::arithmetic::expression::ArithmeticExpression;
 use super::constraint::Constraint;
@@ -14,6 +17,7 @@ use super::memory::pointer::accumulate::PointerAccumulator;
 use super::register::array::ArrayRegister;
 use super::register::cubic::CubicRegister;
 use super::register::element::ElementRegister;
+use super::register::memory::MemorySlice;
 use super::register::Register;
 use super::table::accumulator::Accumulator;
 use super::table::bus::channel::BusChannel;
@@ -22,8 +26,10 @@ use super::table::lookup::table::LookupTable;
 use super::table::lookup::values::LookupValues;
 use super::table::powers::Powers;
 use super::trace::data::AirTraceData;
+use super::trace::schedule::schedule_instructions;
 use super::{AirParameters, Chip};
 use crate::chip::register::RegisterSerializable;
+use crate::plonky2::stark::outputs::OutputLayout;
 
 #[derive(Debug, Clone)]
 #[allow(clippy::type_complexity)]
@@ -46,6 +52,10 @@ pub struct AirBuilder<L: AirParameters> {
     pub(crate) buses: Vec<Bus<CubicRegister, L::CubicParams>>,
     pub(crate) lookup_values: Vec<LookupValues<L::Field, L::CubicParams>>,
     pub(crate) lookup_tables: Vec<LookupTable<L::Field, L::CubicParams>>,
+    pub(crate) row_bounds: Vec<(String, usize)>,
+    pub(crate) secret_columns: Vec<(usize, usize)>,
+    pub(crate) challenge_rounds: Vec<(MemorySlice, usize)>,
+    pub(crate) output_layout: OutputLayout,
     range_data: Option<(
         LookupTable<L::Field, L::CubicParams>,
         LookupValues<L::Field, L::CubicParams>,
@@ -81,6 +91,10 @@ impl<L: AirParameters> AirBuilder<L> {
             buses: Vec::new(),
             lookup_values: Vec::new(),
             lookup_tables: Vec::new(),
+            row_bounds: Vec::new(),
+            secret_columns: Vec::new(),
+            challenge_rounds: Vec::new(),
+            output_layout: OutputLayout::new(),
             range_data: None,
         }
     }
@@ -193,6 +207,40 @@ impl<L: AirParameters> AirBuilder<L> {
         self.global_constraints.push(constraint.into());
     }
 
+    /// Declares that the gadget `name` needs a trace of at least `num_rows` rows.
+    ///
+    /// The bounds of all gadgets are checked when creating the trace generator, and can be used
+    /// to compute the trace length with `AirTraceData::required_num_rows`.
+    pub fn require_rows(&mut self, name: &str, num_rows: usize) {
+        self.row_bounds.push((name.to_string(), num_rows));
+    }
+
+    /// Marks the trace register `register` as secret, such as a register holding a private key.
+    ///
+    /// The values of secret registers are zeroized by the trace generator once the trace has been
+    /// committed to, and are excluded from the debug output of the generator. This does not hide
+    /// the values from the proof itself, which requires a zero-knowledge configuration.
+    pub fn mark_secret<T: RegisterSerializable>(&mut self, register: &T) {
+        match register.register() {
+            MemorySlice::Local(index, length) | MemorySlice::Next(index, length) => {
+                self.secret_columns.push((*index, *index + *length));
+            }
+            _ => panic!("Only trace registers can be marked as secret"),
+        }
+    }
+
+    /// Names the global register `register` as an output of the chip.
+    ///
+    /// The outputs of a proof can then be read by name with `StarkProof::outputs`.
+    pub fn register_output<T: RegisterSerializable>(&mut self, name: &str, register: &T) {
+        match register.register() {
+            MemorySlice::Global(index, length) => {
+                self.output_layout.insert(name, *index, *index + *length)
+            }
+            _ => panic!("Output {} must be a global register", name),
+        }
+    }
+
     pub fn clock(&mut self) -> ElementRegister {
         let clk = self.alloc::<ElementRegister>();
 
@@ -201,7 +249,16 @@ impl<L: AirParameters> AirBuilder<L> {
         clk
     }
 
-    pub fn build(mut self) -> (Chip<L>, AirTraceData<L>) {
+    pub fn build(self) -> (Chip<L>, AirTraceData<L>) {
+        match self.try_build() {
+            Ok(chip) => chip,
+            Err(e) => panic!("{}", e),
+        }
+    }
+
+    /// Builds the chip, returning an error if a constraint or an instruction uses a challenge to
+    /// constrain or compute values committed before the challenge is squeezed.
+    pub fn try_build(mut self) -> Result<(Chip<L>, AirTraceData<L>)> {
         // Register all bus constraints.
         for i in 0..self.buses.len() {
             self.register_bus_constraint(i);
@@ -272,7 +329,13 @@ impl<L: AirParameters> AirBuilder<L> {
         }
 
         let execution_trace_length = self.local_index;
-        (
+
+        self.check_challenge_rounds()?;
+
+        // Order the instructions according to their register dependencies.
+        let (instructions, instruction_levels) = schedule_instructions(self.instructions);
+        let (global_instructions, _) = schedule_instructions(self.global_instructions);
+        Ok((
             Chip {
                 constraints: self.constraints,
                 global_constraints: self.global_constraints,
@@ -280,14 +343,16 @@ impl<L: AirParameters> AirBuilder<L> {
                 execution_trace_length,
                 num_public_values: self.shared_memory.public_index(),
                 num_global_values: self.shared_memory.global_index(),
+                output_layout: self.output_layout,
             },
             AirTraceData {
                 num_challenges: self.shared_memory.challenge_index(),
                 num_public_inputs: self.shared_memory.public_index(),
                 num_global_values: self.shared_memory.global_index(),
                 execution_trace_length,
-                instructions: self.instructions,
-                global_instructions: self.global_instructions,
+                instructions,
+                global_instructions,
+                instruction_levels,
                 powers: self.powers,
                 accumulators: self.accumulators,
                 pointer_row_accumulators: self.pointer_row_accumulators,
@@ -297,8 +362,10 @@ impl<L: AirParameters> AirBuilder<L> {
                 lookup_values: self.lookup_values,
                 lookup_tables: self.lookup_tables,
                 range_data: self.range_data,
+                row_bounds: self.row_bounds,
+                secret_columns: self.secret_columns,
             },
-        )
+        ))
     }
 }
 
@@ -312,7 +379,7 @@ pub(crate) mod tests {
     use super::*;
     use crate::air::fibonacci::FibonacciAir;
     pub use crate::air::parser::AirParser;
-    pub use crate::air::RAir;
+    pub use crate::air::{RAir, RAirData};
     pub use crate::chip::instruction::empty::EmptyInstruction;
     pub use crate::chip::register::u16::U16Register;
     pub use crate::chip::register::RegisterSerializable;
@@ -320,9 +387,12 @@ pub(crate) mod tests {
     pub use crate::math::goldilocks::cubic::GoldilocksCubicParameters;
     use crate::math::prelude::*;
     pub use crate::maybe_rayon::*;
+    use crate::plonky2::stark::config::CurtaPoseidonGoldilocksConfig;
     pub use crate::plonky2::stark::config::PoseidonGoldilocksStarkConfig;
+    use crate::plonky2::stark::prover::StarkyProver;
     pub(crate) use crate::plonky2::stark::tests::{test_recursive_starky, test_starky};
     pub use crate::plonky2::stark::Starky;
+    use crate::trace::generator::TraceGenerator;
     pub use crate::trace::window_parser::TraceWindowParser;
 
     #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -383,6 +453,29 @@ pub(crate) mod tests {
         }
     }
 
+    #[test]
+    fn test_builder_row_bounds() {
+        type L = FibonacciParameters;
+
+        let mut builder = AirBuilder::<L>::new();
+        let x_0 = builder.alloc::<ElementRegister>();
+        let x_1 = builder.alloc::<ElementRegister>();
+        builder.set_to_expression_transition(&x_0.next(), x_1.expr());
+        builder.set_to_expression_transition(&x_1.next(), x_0.expr() + x_1.expr());
+        builder.require_rows("first", 100);
+        builder.require_rows("second", 300);
+
+        let (_, air_data) = builder.build();
+        assert_eq!(air_data.required_num_rows(), 512);
+        assert!(air_data.check_num_rows(512).is_ok());
+        assert!(air_data.check_num_rows(1 << 10).is_ok());
+        assert!(air_data.check_num_rows(256).is_err());
+        assert!(air_data.check_num_rows(600).is_err());
+
+        let generator = ArithmeticGenerator::<L>::new_with_required_rows(air_data);
+        assert_eq!(generator.num_rows, 512);
+    }
+
     #[test]
     fn test_builder_fibonacci_stark() {
         type F = GoldilocksField;
@@ -431,6 +524,112 @@ pub(crate) mod tests {
         test_recursive_starky(stark, config, generator, &public_inputs);
     }
 
+    #[test]
+    fn test_builder_secret_registers_single_round() {
+        type F = GoldilocksField;
+        type L = FibonacciParameters;
+        type SC = PoseidonGoldilocksStarkConfig;
+
+        let mut builder = AirBuilder::<L>::new();
+        let x_0 = builder.alloc::<ElementRegister>();
+        let x_1 = builder.alloc::<ElementRegister>();
+        builder.set_to_expression_transition(&x_0.next(), x_1.expr());
+        builder.set_to_expression_transition(&x_1.next(), x_0.expr() + x_1.expr());
+        builder.mark_secret(&x_1);
+
+        let (mut air, mut air_data) = builder.build();
+        air.num_public_values = 3;
+        air_data.num_public_inputs = 3;
+        // The chip has no extended columns, so the whole trace is committed in a single round.
+        assert_eq!(air.round_data().len(), 1);
+        let secret_range = x_1.register().get_range();
+        assert_eq!(air_data.secret_columns, vec![secret_range]);
+
+        let num_rows = 1 << 10;
+        let public_inputs = [
+            F::ZERO,
+            F::ONE,
+            FibonacciAir::fibonacci(num_rows - 1, F::ZERO, F::ONE),
+        ];
+
+        let generator = ArithmeticGenerator::<L>::new(air_data, num_rows);
+        let writer = generator.new_writer();
+        writer.write(&x_0, &F::ZERO, 0);
+        writer.write(&x_1, &F::ONE, 0);
+        for i in 0..num_rows {
+            writer.write_row_instructions(&generator.air_data, i);
+        }
+        assert!(format!("{:?}", generator).contains("<redacted>"));
+
+        let stark = Starky::new(air);
+        let config = SC::standard_fast_config(num_rows);
+
+        // The secret register must not be written to disk.
+        assert!(generator.has_secrets());
+        let spill = StarkyProver::<F, CurtaPoseidonGoldilocksConfig, 2>::prove_with_spill(
+            &config,
+            &stark,
+            &generator,
+            &public_inputs,
+            &std::env::temp_dir(),
+        );
+        assert!(spill.is_err());
+
+        test_starky(&stark, &config, &generator, &public_inputs);
+
+        // Once the proof is generated, the secret register is zeroized and the rest of the trace
+        // is left untouched.
+        let trace = generator.trace_clone();
+        let (start, end) = secret_range;
+        assert!(trace
+            .rows()
+            .all(|row| row[start..end].iter().all(|x| *x == F::ZERO)));
+        assert_eq!(trace.row(1)[x_0.register().get_range().0], F::ONE);
+    }
+
+    #[test]
+    fn test_builder_secret_registers_two_rounds() {
+        type F = GoldilocksField;
+        type L = SimpleTestParameters;
+        type SC = PoseidonGoldilocksStarkConfig;
+
+        let mut builder = AirBuilder::<L>::new();
+        let x_0 = builder.alloc::<U16Register>();
+        let secret = builder.alloc::<U16Register>();
+        builder.mark_secret(&secret);
+
+        let clk = builder.clock();
+        let clk_expected = builder.alloc::<ElementRegister>();
+        builder.assert_equal(&clk, &clk_expected);
+
+        let (air, trace_data) = builder.build();
+        // The range check of the `u16` registers is in the extended columns of a second round.
+        assert_eq!(air.round_data().len(), 2);
+        let num_rows = 1 << 16;
+        let generator = ArithmeticGenerator::<L>::new(trace_data, num_rows);
+
+        let writer = generator.new_writer();
+        for i in 0..num_rows {
+            writer.write(&x_0, &F::ONE, i);
+            writer.write(&secret, &F::from_canonical_usize(23), i);
+            writer.write(&clk_expected, &F::from_canonical_usize(i), i);
+            writer.write_row_instructions(&generator.air_data, i);
+        }
+        writer.write_global_instructions(&generator.air_data);
+        let stark = Starky::new(air);
+        let config = SC::standard_fast_config(num_rows);
+
+        let public_inputs = writer.public().unwrap().clone();
+        test_starky(&stark, &config, &generator, &public_inputs);
+
+        let trace = generator.trace_clone();
+        let (start, end) = secret.register().get_range();
+        assert!(trace
+            .rows()
+            .all(|row| row[start..end].iter().all(|x| *x == F::ZERO)));
+        assert_eq!(trace.row(1)[x_0.register().get_range().0], F::ONE);
+    }
+
     #[derive(Debug, Clone, Serialize, Deserialize)]
     pub struct SimpleTestParameters;
 
@@ -472,6 +671,8 @@ pub(crate) mod tests {
         builder.assert_equal(&clk, &clk_expected);
 
         let (air, trace_data) = builder.build();
+        // The range check table takes a row for each `u16` value.
+        assert_eq!(trace_data.required_num_rows(), 1 << 16);
         let num_rows = 1 << 16;
         let generator = ArithmeticGenerator::<L>::new(trace_data, num_rows);
 
@@ -510,7 +711,7 @@ pub(crate) mod tests {
 
         builder.assert_equal(&clk, &clk_expected);
 
-        let num_rows = 1 << 14;
+        let num_rows = 1 << 16;
 
         let (air, trace_data) = builder.build();
         let generator = ArithmeticGenerator::<L>::new(trace_data, num_rows);
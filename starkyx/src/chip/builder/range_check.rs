@@ -10,6 +10,8 @@ use crate::chip::AirParameters;
 
 impl<L: AirParameters> AirBuilder<L> {
     pub(crate) fn arithmetic_range_checks(&mut self) {
+        // The table holds the `2^16` values of a `u16` limb, one per row.
+        self.require_rows("range check", 1 << 16);
         let table = self.alloc::<ElementRegister>();
 
         let one = || -> ArithmeticExpression<L::Field> { ArithmeticExpression::one() };
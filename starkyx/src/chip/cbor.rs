@@ -0,0 +1,376 @@
+//! Parsing of WebAuthn authenticator data and of the COSE public keys it contains.
+//!
+//! The authenticator data of a WebAuthn registration is the concatenation of
+//!
+//! - the 32-byte SHA-256 hash of the relying party identifier,
+//! - a flags byte,
+//! - a 4-byte big-endian signature counter,
+//! - the attested credential data: a 16-byte AAGUID, a 2-byte big-endian credential id length `L`,
+//! the `L`-byte credential id and the credential public key encoded as a CBOR map (a COSE key).
+//!
+//! The gadget supports ES256 credential keys, i.e. P-256 keys, in the canonical CBOR encoding
+//! produced by authenticators:
+//!
+//! ```text
+//! a5                 map of 5 entries
+//!    01 02           kty: EC2
+//!    03 26           alg: ES256 (-7)
+//!    20 01           crv: P-256
+//!    21 58 20 <x>    x: 32-byte string
+//!    22 58 20 <y>    y: 32-byte string
+//! ```
+//!
+//! Since the encoding is canonical, the position of every field is fixed once the credential id
+//! length is known, and the decoder is bounded by the length of the trace registers. The gadget
+//! constrains the CBOR structure and the flags, and returns the big-endian coordinates of the key
+//! so that they can be passed on to a curve gadget verifying the key.
+
+use anyhow::{anyhow, ensure, Result};
+use serde::{Deserialize, Serialize};
+
+use super::arithmetic::expression::ArithmeticExpression;
+use super::builder::AirBuilder;
+use super::register::array::ArrayRegister;
+use super::register::bit::BitRegister;
+use super::register::Register;
+use super::trace::writer::TraceWriter;
+use super::uint::bytes::register::ByteRegister;
+use super::AirParameters;
+use crate::math::prelude::*;
+
+/// The length of the canonical encoding of an ES256 COSE key.
+pub const COSE_ES256_KEY_LENGTH: usize = 77;
+
+/// The bytes preceding the `x` coordinate in the canonical encoding of an ES256 COSE key.
+pub const COSE_ES256_X_PREFIX: [u8; 10] =
+    [0xa5, 0x01, 0x02, 0x03, 0x26, 0x20, 0x01, 0x21, 0x58, 0x20];
+
+/// The bytes preceding the `y` coordinate in the canonical encoding of an ES256 COSE key.
+pub const COSE_ES256_Y_PREFIX: [u8; 3] = [0x22, 0x58, 0x20];
+
+/// The length of the authenticator data preceding the credential id.
+pub const AUTHENTICATOR_DATA_HEADER_LENGTH: usize = 55;
+
+/// The bit of the flags byte indicating that the user is present.
+pub const FLAG_USER_PRESENT: usize = 0;
+
+/// The bit of the flags byte indicating that attested credential data is included.
+pub const FLAG_ATTESTED_CREDENTIAL_DATA: usize = 6;
+
+/// The bit of the flags byte indicating that extension data is included.
+pub const FLAG_EXTENSION_DATA: usize = 7;
+
+/// The coordinates of an ES256 COSE key, as big-endian bytes.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct CoseKey {
+    pub x: ArrayRegister<ByteRegister>,
+    pub y: ArrayRegister<ByteRegister>,
+}
+
+/// The fields of WebAuthn authenticator data with attested credential data.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct AuthenticatorData {
+    pub rp_id_hash: ArrayRegister<ByteRegister>,
+    pub flags: ByteRegister,
+    pub sign_count: ArrayRegister<ByteRegister>,
+    pub aaguid: ArrayRegister<ByteRegister>,
+    pub credential_id: ArrayRegister<ByteRegister>,
+    pub public_key: CoseKey,
+    /// The bit decomposition of the flags byte.
+    flag_bits: ArrayRegister<BitRegister>,
+}
+
+impl<L: AirParameters> AirBuilder<L> {
+    /// Parses the canonical encoding of an ES256 COSE key and returns its coordinates.
+    pub fn cose_es256_key(&mut self, bytes: &ArrayRegister<ByteRegister>) -> CoseKey {
+        assert_eq!(
+            bytes.len(),
+            COSE_ES256_KEY_LENGTH,
+            "Expected {} bytes for an ES256 COSE key",
+            COSE_ES256_KEY_LENGTH
+        );
+
+        let x_start = COSE_ES256_X_PREFIX.len();
+        let y_start = x_start + 32 + COSE_ES256_Y_PREFIX.len();
+
+        self.assert_bytes_equal_constant(&bytes.get_subarray(0..x_start), &COSE_ES256_X_PREFIX);
+        self.assert_bytes_equal_constant(
+            &bytes.get_subarray(x_start + 32..y_start),
+            &COSE_ES256_Y_PREFIX,
+        );
+
+        CoseKey {
+            x: bytes.get_subarray(x_start..x_start + 32),
+            y: bytes.get_subarray(y_start..y_start + 32),
+        }
+    }
+
+    /// Parses WebAuthn authenticator data holding a credential id of `credential_id_len` bytes and
+    /// an ES256 credential key, without extensions.
+    ///
+    /// The flags are constrained to indicate user presence and attested credential data.
+    pub fn webauthn_authenticator_data(
+        &mut self,
+        bytes: &ArrayRegister<ByteRegister>,
+        credential_id_len: usize,
+    ) -> AuthenticatorData {
+        let key_start = AUTHENTICATOR_DATA_HEADER_LENGTH + credential_id_len;
+        assert_eq!(
+            bytes.len(),
+            key_start + COSE_ES256_KEY_LENGTH,
+            "Authenticator data length does not match the credential id length"
+        );
+        assert!(
+            credential_id_len <= u16::MAX as usize,
+            "Credential id length must fit in two bytes"
+        );
+
+        let flags = bytes.get(32);
+        let flag_bits = self.alloc_array::<BitRegister>(8);
+        let flags_value = flag_bits
+            .iter()
+            .enumerate()
+            .fold(ArithmeticExpression::zero(), |acc, (i, bit)| {
+                acc + bit.expr() * L::Field::from_canonical_u32(1 << i)
+            });
+        self.assert_expressions_equal(flags.expr(), flags_value);
+
+        self.assert_expressions_equal(
+            flag_bits.get(FLAG_USER_PRESENT).expr(),
+            ArithmeticExpression::one(),
+        );
+        self.assert_expressions_equal(
+            flag_bits.get(FLAG_ATTESTED_CREDENTIAL_DATA).expr(),
+            ArithmeticExpression::one(),
+        );
+        self.assert_expressions_equal(
+            flag_bits.get(FLAG_EXTENSION_DATA).expr(),
+            ArithmeticExpression::zero(),
+        );
+
+        let credential_id_len_bytes = (credential_id_len as u16).to_be_bytes();
+        self.assert_bytes_equal_constant(&bytes.get_subarray(53..55), &credential_id_len_bytes);
+
+        let public_key = self.cose_es256_key(&bytes.get_subarray(key_start..bytes.len()));
+
+        AuthenticatorData {
+            rp_id_hash: bytes.get_subarray(0..32),
+            flags,
+            sign_count: bytes.get_subarray(33..37),
+            aaguid: bytes.get_subarray(37..53),
+            credential_id: bytes.get_subarray(AUTHENTICATOR_DATA_HEADER_LENGTH..key_start),
+            public_key,
+            flag_bits,
+        }
+    }
+
+    fn assert_bytes_equal_constant(&mut self, bytes: &ArrayRegister<ByteRegister>, values: &[u8]) {
+        for (byte, value) in bytes.iter().zip(values.iter()) {
+            self.assert_expressions_equal(
+                byte.expr(),
+                ArithmeticExpression::from_constant(L::Field::from_canonical_u8(*value)),
+            );
+        }
+    }
+}
+
+impl AuthenticatorData {
+    /// Writes the bit decomposition of the flags byte, which must already be written.
+    pub fn write<F: PrimeField64>(&self, writer: &TraceWriter<F>, row_index: usize) {
+        let flags = writer.read(&self.flags, row_index).as_canonical_u64();
+        writer.write_array(
+            &self.flag_bits,
+            (0..8).map(|i| F::from_canonical_u64((flags >> i) & 1)),
+            row_index,
+        );
+    }
+}
+
+/// Decodes a COSE key encoded as a CBOR map and returns the coordinates of an ES256 key.
+///
+/// Unlike the gadget, the decoder accepts the entries of the map in any order. It only supports
+/// the small integer keys and values of the COSE EC2 key parameters.
+pub fn decode_cose_es256_key(bytes: &[u8]) -> Result<([u8; 32], [u8; 32])> {
+    let mut reader = CborReader { bytes, position: 0 };
+    let (major_type, num_entries) = reader.read_header()?;
+    ensure!(major_type == 5, "COSE key must be a CBOR map");
+
+    let (mut kty, mut alg, mut crv, mut x, mut y) = (None, None, None, None, None);
+    for _ in 0..num_entries {
+        let label = reader.read_int()?;
+        match label {
+            1 => kty = Some(reader.read_int()?),
+            3 => alg = Some(reader.read_int()?),
+            -1 => crv = Some(reader.read_int()?),
+            -2 => x = Some(reader.read_coordinate()?),
+            -3 => y = Some(reader.read_coordinate()?),
+            _ => return Err(anyhow!("Unsupported COSE key parameter {}", label)),
+        }
+    }
+    ensure!(
+        reader.position == bytes.len(),
+        "Trailing bytes after COSE key"
+    );
+    ensure!(kty == Some(2), "COSE key type is not EC2");
+    ensure!(alg == Some(-7), "COSE key algorithm is not ES256");
+    ensure!(crv == Some(1), "COSE key curve is not P-256");
+
+    Ok((
+        x.ok_or_else(|| anyhow!("Missing x coordinate"))?,
+        y.ok_or_else(|| anyhow!("Missing y coordinate"))?,
+    ))
+}
+
+/// Encodes the coordinates of an ES256 key in the canonical COSE encoding.
+pub fn encode_cose_es256_key(x: &[u8; 32], y: &[u8; 32]) -> [u8; COSE_ES256_KEY_LENGTH] {
+    let mut bytes = [0u8; COSE_ES256_KEY_LENGTH];
+    let y_start = COSE_ES256_X_PREFIX.len() + 32;
+    bytes[..COSE_ES256_X_PREFIX.len()].copy_from_slice(&COSE_ES256_X_PREFIX);
+    bytes[COSE_ES256_X_PREFIX.len()..y_start].copy_from_slice(x);
+    bytes[y_start..y_start + COSE_ES256_Y_PREFIX.len()].copy_from_slice(&COSE_ES256_Y_PREFIX);
+    bytes[y_start + COSE_ES256_Y_PREFIX.len()..].copy_from_slice(y);
+    bytes
+}
+
+struct CborReader<'a> {
+    bytes: &'a [u8],
+    position: usize,
+}
+
+impl<'a> CborReader<'a> {
+    fn read_bytes(&mut self, len: usize) -> Result<&'a [u8]> {
+        ensure!(
+            self.position + len <= self.bytes.len(),
+            "Unexpected end of CBOR data"
+        );
+        let bytes = &self.bytes[self.position..self.position + len];
+        self.position += len;
+        Ok(bytes)
+    }
+
+    /// Reads the header of a data item and returns its major type and argument.
+    fn read_header(&mut self) -> Result<(u8, u64)> {
+        let initial = self.read_bytes(1)?[0];
+        let major_type = initial >> 5;
+        let argument = match initial & 0x1f {
+            info @ 0..=23 => info as u64,
+            24 => self.read_bytes(1)?[0] as u64,
+            25 => u16::from_be_bytes(self.read_bytes(2)?.try_into()?) as u64,
+            26 => u32::from_be_bytes(self.read_bytes(4)?.try_into()?) as u64,
+            27 => u64::from_be_bytes(self.read_bytes(8)?.try_into()?),
+            info => return Err(anyhow!("Unsupported CBOR additional information {}", info)),
+        };
+        Ok((major_type, argument))
+    }
+
+    fn read_int(&mut self) -> Result<i64> {
+        let (major_type, argument) = self.read_header()?;
+        ensure!(argument <= i64::MAX as u64, "CBOR integer out of range");
+        match major_type {
+            0 => Ok(argument as i64),
+            1 => Ok(-1 - argument as i64),
+            _ => Err(anyhow!(
+                "Expected a CBOR integer, got major type {}",
+                major_type
+            )),
+        }
+    }
+
+    fn read_coordinate(&mut self) -> Result<[u8; 32]> {
+        let (major_type, len) = self.read_header()?;
+        ensure!(major_type == 2, "Expected a CBOR byte string");
+        ensure!(
+            len == 32,
+            "Expected a 32-byte coordinate, got {} bytes",
+            len
+        );
+        Ok(self.read_bytes(32)?.try_into()?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::{thread_rng, Rng};
+
+    use super::*;
+    use crate::chip::builder::tests::*;
+
+    #[derive(Debug, Clone, Serialize, Deserialize)]
+    pub struct WebAuthnTest;
+
+    impl AirParameters for WebAuthnTest {
+        type Field = GoldilocksField;
+        type CubicParams = GoldilocksCubicParameters;
+        type Instruction = EmptyInstruction<GoldilocksField>;
+
+        const NUM_FREE_COLUMNS: usize = 156;
+    }
+
+    #[test]
+    fn test_decode_cose_key() {
+        let mut rng = thread_rng();
+        let x = rng.gen::<[u8; 32]>();
+        let y = rng.gen::<[u8; 32]>();
+
+        let bytes = encode_cose_es256_key(&x, &y);
+        assert_eq!(decode_cose_es256_key(&bytes).unwrap(), (x, y));
+
+        // Reordering the entries of the map gives the same key.
+        let mut reordered = vec![0xa5, 0x22, 0x58, 0x20];
+        reordered.extend_from_slice(&y);
+        reordered.extend_from_slice(&[0x21, 0x58, 0x20]);
+        reordered.extend_from_slice(&x);
+        reordered.extend_from_slice(&[0x20, 0x01, 0x03, 0x26, 0x01, 0x02]);
+        assert_eq!(decode_cose_es256_key(&reordered).unwrap(), (x, y));
+
+        assert!(decode_cose_es256_key(&bytes[..COSE_ES256_KEY_LENGTH - 1]).is_err());
+    }
+
+    #[test]
+    fn test_webauthn_authenticator_data() {
+        type F = GoldilocksField;
+        type L = WebAuthnTest;
+        type SC = PoseidonGoldilocksStarkConfig;
+
+        let credential_id_len = 16;
+        let len = AUTHENTICATOR_DATA_HEADER_LENGTH + credential_id_len + COSE_ES256_KEY_LENGTH;
+
+        let mut builder = AirBuilder::<L>::new();
+        let bytes = builder.alloc_array::<ByteRegister>(len);
+        let auth_data = builder.webauthn_authenticator_data(&bytes, credential_id_len);
+
+        let (air, trace_data) = builder.build();
+        let num_rows = 1 << 9;
+        let generator = ArithmeticGenerator::<L>::new(trace_data, num_rows);
+
+        let mut rng = thread_rng();
+        let writer = generator.new_writer();
+        for i in 0..num_rows {
+            let x = rng.gen::<[u8; 32]>();
+            let y = rng.gen::<[u8; 32]>();
+
+            let mut data = rng.gen::<[u8; 32]>().to_vec();
+            data.push(0x41 | (rng.gen::<u8>() & 0x3e));
+            data.extend_from_slice(&rng.gen::<[u8; 4]>());
+            data.extend_from_slice(&rng.gen::<[u8; 16]>());
+            data.extend_from_slice(&(credential_id_len as u16).to_be_bytes());
+            data.extend((0..credential_id_len).map(|_| rng.gen::<u8>()));
+            data.extend_from_slice(&encode_cose_es256_key(&x, &y));
+
+            writer.write_array(&bytes, data.iter().map(|b| F::from_canonical_u8(*b)), i);
+            auth_data.write(&writer, i);
+
+            let key = writer.read_vec(&auth_data.public_key.x, i);
+            assert_eq!(key, x.map(F::from_canonical_u8).to_vec());
+        }
+
+        let stark = Starky::new(air);
+        let config = SC::standard_fast_config(num_rows);
+
+        // Generate proof and verify as a stark
+        test_starky(&stark, &config, &generator, &[]);
+
+        // Test the recursive proof.
+        test_recursive_starky(stark, config, generator, &[]);
+    }
+}
@@ -0,0 +1,171 @@
+use serde::{Deserialize, Serialize};
+
+use super::is_zero::Bls12381FpIsZeroInstruction;
+use super::params::{Bls12381, Bls12381BaseField};
+use super::sgn0::Bls12381FpSgn0Instruction;
+use super::sqrt::Bls12381FpSqrtInstruction;
+use crate::air::AirConstraint;
+use crate::chip::ec::scalar::LimbBitInstruction;
+use crate::chip::ec::ECInstruction;
+use crate::chip::field::add::FpAddInstruction;
+use crate::chip::field::den::FpDenInstruction;
+use crate::chip::field::div::FpDivInstruction;
+use crate::chip::field::inner_product::FpInnerProductInstruction;
+use crate::chip::field::instruction::FromFieldInstruction;
+use crate::chip::field::mul::FpMulInstruction;
+use crate::chip::field::mul_const::FpMulConstInstruction;
+use crate::chip::field::sub::FpSubInstruction;
+use crate::chip::instruction::Instruction;
+use crate::chip::trace::writer::{AirWriter, TraceWriter};
+use crate::math::field::PrimeField64;
+use crate::polynomial::parser::PolynomialParser;
+
+/// The instructions needed by the BLS12-381 base field gadgets, on top of the field operations.
+pub trait Bls12381FpInstructions:
+    FromFieldInstruction<Bls12381BaseField>
+    + From<Bls12381FpSqrtInstruction>
+    + From<Bls12381FpSgn0Instruction>
+    + From<Bls12381FpIsZeroInstruction>
+{
+}
+
+impl<
+        T: FromFieldInstruction<Bls12381BaseField>
+            + From<Bls12381FpSqrtInstruction>
+            + From<Bls12381FpSgn0Instruction>
+            + From<Bls12381FpIsZeroInstruction>,
+    > Bls12381FpInstructions for T
+{
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(bound = "")]
+pub enum Bls12381FpInstruction {
+    EC(ECInstruction<Bls12381>),
+    Sqrt(Bls12381FpSqrtInstruction),
+    Sgn0(Bls12381FpSgn0Instruction),
+    IsZero(Bls12381FpIsZeroInstruction),
+}
+
+impl FromFieldInstruction<Bls12381BaseField> for Bls12381FpInstruction {}
+
+impl From<Bls12381FpSqrtInstruction> for Bls12381FpInstruction {
+    fn from(i: Bls12381FpSqrtInstruction) -> Self {
+        Self::Sqrt(i)
+    }
+}
+
+impl From<Bls12381FpSgn0Instruction> for Bls12381FpInstruction {
+    fn from(i: Bls12381FpSgn0Instruction) -> Self {
+        Self::Sgn0(i)
+    }
+}
+
+impl From<Bls12381FpIsZeroInstruction> for Bls12381FpInstruction {
+    fn from(i: Bls12381FpIsZeroInstruction) -> Self {
+        Self::IsZero(i)
+    }
+}
+
+impl<AP: PolynomialParser> AirConstraint<AP> for Bls12381FpInstruction {
+    fn eval(&self, parser: &mut AP) {
+        match self {
+            Bls12381FpInstruction::EC(instruction) => {
+                AirConstraint::<AP>::eval(instruction, parser)
+            }
+            Bls12381FpInstruction::Sqrt(instruction) => {
+                AirConstraint::<AP>::eval(instruction, parser)
+            }
+            Bls12381FpInstruction::Sgn0(instruction) => {
+                AirConstraint::<AP>::eval(instruction, parser)
+            }
+            Bls12381FpInstruction::IsZero(instruction) => {
+                AirConstraint::<AP>::eval(instruction, parser)
+            }
+        }
+    }
+}
+
+impl<F: PrimeField64> Instruction<F> for Bls12381FpInstruction {
+    fn write(&self, writer: &TraceWriter<F>, row_index: usize) {
+        match self {
+            Bls12381FpInstruction::EC(instruction) => {
+                Instruction::<F>::write(instruction, writer, row_index)
+            }
+            Bls12381FpInstruction::Sqrt(instruction) => {
+                Instruction::<F>::write(instruction, writer, row_index)
+            }
+            Bls12381FpInstruction::Sgn0(instruction) => {
+                Instruction::<F>::write(instruction, writer, row_index)
+            }
+            Bls12381FpInstruction::IsZero(instruction) => {
+                Instruction::<F>::write(instruction, writer, row_index)
+            }
+        }
+    }
+
+    fn write_to_air(&self, writer: &mut impl AirWriter<Field = F>) {
+        match self {
+            Bls12381FpInstruction::EC(instruction) => {
+                Instruction::<F>::write_to_air(instruction, writer)
+            }
+            Bls12381FpInstruction::Sqrt(instruction) => {
+                Instruction::<F>::write_to_air(instruction, writer)
+            }
+            Bls12381FpInstruction::Sgn0(instruction) => {
+                Instruction::<F>::write_to_air(instruction, writer)
+            }
+            Bls12381FpInstruction::IsZero(instruction) => {
+                Instruction::<F>::write_to_air(instruction, writer)
+            }
+        }
+    }
+}
+
+impl From<LimbBitInstruction> for Bls12381FpInstruction {
+    fn from(i: LimbBitInstruction) -> Self {
+        Self::EC(i.into())
+    }
+}
+
+impl From<FpAddInstruction<Bls12381BaseField>> for Bls12381FpInstruction {
+    fn from(i: FpAddInstruction<Bls12381BaseField>) -> Self {
+        Self::EC(i.into())
+    }
+}
+
+impl From<FpMulInstruction<Bls12381BaseField>> for Bls12381FpInstruction {
+    fn from(i: FpMulInstruction<Bls12381BaseField>) -> Self {
+        Self::EC(i.into())
+    }
+}
+
+impl From<FpSubInstruction<Bls12381BaseField>> for Bls12381FpInstruction {
+    fn from(i: FpSubInstruction<Bls12381BaseField>) -> Self {
+        Self::EC(i.into())
+    }
+}
+
+impl From<FpDivInstruction<Bls12381BaseField>> for Bls12381FpInstruction {
+    fn from(i: FpDivInstruction<Bls12381BaseField>) -> Self {
+        Self::EC(i.into())
+    }
+}
+
+impl From<FpDenInstruction<Bls12381BaseField>> for Bls12381FpInstruction {
+    fn from(i: FpDenInstruction<Bls12381BaseField>) -> Self {
+        Self::EC(i.into())
+    }
+}
+
+impl From<FpInnerProductInstruction<Bls12381BaseField>> for Bls12381FpInstruction {
+    fn from(i: FpInnerProductInstruction<Bls12381BaseField>) -> Self {
+        Self::EC(i.into())
+    }
+}
+
+impl From<FpMulConstInstruction<Bls12381BaseField>> for Bls12381FpInstruction {
+    fn from(i: FpMulConstInstruction<Bls12381BaseField>) -> Self {
+        Self::EC(i.into())
+    }
+}
@@ -0,0 +1,164 @@
+use num::{BigUint, Zero};
+use serde::{Deserialize, Serialize};
+
+use super::params::Bls12381BaseField;
+use crate::air::AirConstraint;
+use crate::chip::builder::AirBuilder;
+use crate::chip::field::mul::FpMulInstruction;
+use crate::chip::field::parameters::FieldParameters;
+use crate::chip::field::register::FieldRegister;
+use crate::chip::instruction::Instruction;
+use crate::chip::register::array::ArrayRegister;
+use crate::chip::register::bit::BitRegister;
+use crate::chip::register::u16::U16Register;
+use crate::chip::register::{Register, RegisterSerializable};
+use crate::chip::trace::writer::{AirWriter, TraceWriter};
+use crate::chip::utils::digits_to_biguint;
+use crate::chip::AirParameters;
+use crate::math::prelude::*;
+use crate::polynomial::parser::PolynomialParser;
+use crate::polynomial::{to_u16_le_limbs_polynomial, Polynomial};
+
+/// Fp zero test. Computes the bit `is_zero`, which is set if and only if `a = 0`.
+///
+/// This is done by witnessing the inverse of `a`, or zero if `a = 0`, and constraining that
+/// `a * inverse = 1 - is_zero` and that `is_zero * a = 0` limb by limb.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[serde(bound = "")]
+pub struct Bls12381FpIsZeroInstruction {
+    is_zero: BitRegister,
+    /// a `FpMulInstruction` to compute `a * inverse = product`.
+    product: FpMulInstruction<Bls12381BaseField>,
+}
+
+impl<L: AirParameters> AirBuilder<L> {
+    /// Given a field element `a`, returns a bit which is set if and only if `a` is zero.
+    ///
+    /// The input must be in canonical form, as are the results of the field operations.
+    pub fn bls12_381_is_zero(&mut self, a: &FieldRegister<Bls12381BaseField>) -> BitRegister
+    where
+        L::Instruction: From<Bls12381FpIsZeroInstruction>,
+    {
+        let is_trace = a.is_trace();
+
+        let is_zero: BitRegister;
+        let inverse: FieldRegister<Bls12381BaseField>;
+        let product: FieldRegister<Bls12381BaseField>;
+        let product_carry: FieldRegister<Bls12381BaseField>;
+        let product_witness_low: ArrayRegister<U16Register>;
+        let product_witness_high: ArrayRegister<U16Register>;
+
+        if is_trace {
+            is_zero = self.alloc::<BitRegister>();
+            inverse = self.alloc::<FieldRegister<Bls12381BaseField>>();
+            product = self.alloc::<FieldRegister<Bls12381BaseField>>();
+            product_carry = self.alloc::<FieldRegister<Bls12381BaseField>>();
+            product_witness_low =
+                self.alloc_array::<U16Register>(Bls12381BaseField::NB_WITNESS_LIMBS);
+            product_witness_high =
+                self.alloc_array::<U16Register>(Bls12381BaseField::NB_WITNESS_LIMBS);
+        } else {
+            is_zero = self.alloc_public::<BitRegister>();
+            inverse = self.alloc_public::<FieldRegister<Bls12381BaseField>>();
+            product = self.alloc_public::<FieldRegister<Bls12381BaseField>>();
+            product_carry = self.alloc_public::<FieldRegister<Bls12381BaseField>>();
+            product_witness_low =
+                self.alloc_array_public::<U16Register>(Bls12381BaseField::NB_WITNESS_LIMBS);
+            product_witness_high =
+                self.alloc_array_public::<U16Register>(Bls12381BaseField::NB_WITNESS_LIMBS);
+        }
+
+        // check that a * inverse == product
+        let product = FpMulInstruction {
+            a: *a,
+            b: inverse,
+            result: product,
+            carry: product_carry,
+            witness_low: product_witness_low,
+            witness_high: product_witness_high,
+        };
+
+        let instr = Bls12381FpIsZeroInstruction { is_zero, product };
+
+        if is_trace {
+            self.register_instruction(instr);
+        } else {
+            self.register_global_instruction(instr);
+        }
+
+        is_zero
+    }
+}
+
+impl<AP: PolynomialParser> AirConstraint<AP> for Bls12381FpIsZeroInstruction {
+    fn eval(&self, parser: &mut AP) {
+        // Assert that a * inverse == product
+        self.product.eval(parser);
+
+        // Assert that `product = 1 - is_zero`, limb by limb.
+        let is_zero = self.is_zero.eval(parser);
+        let one = parser.one();
+        let not_is_zero = parser.sub(one, is_zero);
+        let product = self.product.result.eval(parser);
+        for (i, limb) in product.coefficients.into_iter().enumerate() {
+            match i {
+                0 => parser.assert_eq(limb, not_is_zero),
+                _ => parser.constraint(limb),
+            }
+        }
+
+        // Assert that `is_zero * a = 0`, limb by limb.
+        let a = self.product.a.eval(parser);
+        for limb in a.coefficients {
+            let constraint = parser.mul(is_zero, limb);
+            parser.constraint(constraint);
+        }
+    }
+}
+
+impl Bls12381FpIsZeroInstruction {
+    /// Returns the values of `is_zero` and of the inverse.
+    fn witness<F: PrimeField64>(p_a: Polynomial<F>) -> (F, Polynomial<F>) {
+        let a_digits = p_a
+            .coefficients
+            .iter()
+            .map(|x| x.as_canonical_u64() as u16)
+            .collect::<Vec<_>>();
+        let a = digits_to_biguint(&a_digits);
+
+        let modulus = Bls12381BaseField::modulus();
+        let a = a % &modulus;
+        let (is_zero, inverse) = match a.is_zero() {
+            true => (F::ONE, BigUint::zero()),
+            false => (
+                F::ZERO,
+                a.modpow(&(&modulus - BigUint::from(2u32)), &modulus),
+            ),
+        };
+        let p_inverse = to_u16_le_limbs_polynomial::<F, Bls12381BaseField>(&inverse);
+
+        (is_zero, p_inverse)
+    }
+}
+
+impl<F: PrimeField64> Instruction<F> for Bls12381FpIsZeroInstruction {
+    fn write(&self, writer: &TraceWriter<F>, row_index: usize) {
+        let p_a = writer.read(&self.product.a, row_index);
+        let (is_zero, p_inverse) = Self::witness(p_a);
+
+        writer.write(&self.is_zero, &is_zero, row_index);
+        writer.write(&self.product.b, &p_inverse, row_index);
+
+        self.product.write(writer, row_index);
+    }
+
+    fn write_to_air(&self, writer: &mut impl AirWriter<Field = F>) {
+        let p_a = writer.read(&self.product.a);
+        let (is_zero, p_inverse) = Self::witness(p_a);
+
+        writer.write(&self.is_zero, &is_zero);
+        writer.write(&self.product.b, &p_inverse);
+
+        self.product.write_to_air(writer);
+    }
+}
@@ -0,0 +1,355 @@
+//! The map from the base field to BLS12-381 G1 of the suite `BLS12381G1_XMD:SHA-256_SSWU_RO_` of
+//! RFC 9380.
+//!
+//! A field element is first sent to the isogenous curve `E'` by the simplified SWU map, and then
+//! to the curve by the 11-isogeny of section E.2 of RFC 9380. The cofactor of the result is
+//! cleared by a multiplication with `h_eff`.
+
+use num::{BigUint, Num, Zero};
+
+use super::instruction::Bls12381FpInstructions;
+use super::params::{Bls12381, Bls12381BaseField, Bls12381Iso, Bls12381IsoParameters};
+use super::sgn0::sgn0;
+use super::sqrt::sqrt;
+use crate::chip::builder::AirBuilder;
+use crate::chip::ec::point::{AffinePoint, AffinePointRegister};
+use crate::chip::ec::weierstrass::WeierstrassParameters;
+use crate::chip::field::parameters::{FieldParameters, MAX_NB_LIMBS};
+use crate::chip::field::register::FieldRegister;
+use crate::chip::register::bit::BitRegister;
+use crate::chip::register::{Register, RegisterSerializable};
+use crate::chip::AirParameters;
+use crate::math::prelude::*;
+
+/// The non-square `Z` of the simplified SWU map for `E'`.
+pub const SSWU_Z: u16 = 11;
+
+/// The scalar `h_eff` that clears the cofactor of a point of the curve.
+pub const H_EFF: u64 = 0xd201000000010001;
+
+// The coefficients of the 11-isogeny map from `E'` to the curve, from the constant term up. The
+// leading coefficients of the denominators are equal to one and are omitted.
+const ISO_X_NUM: [&str; 12] = [
+    "11a05f2b1e833340b809101dd99815856b303e88a2d7005ff2627b56cdb4e2c85610c2d5f2e62d6eaeac1662734649b7",
+    "17294ed3e943ab2f0588bab22147a81c7c17e75b2f6a8417f565e33c70d1e86b4838f2a6f318c356e834eef1b3cb83bb",
+    "0d54005db97678ec1d1048c5d10a9a1bce032473295983e56878e501ec68e25c958c3e3d2a09729fe0179f9dac9edcb0",
+    "1778e7166fcc6db74e0609d307e55412d7f5e4656a8dbf25f1b33289f1b330835336e25ce3107193c5b388641d9b6861",
+    "0e99726a3199f4436642b4b3e4118e5499db995a1257fb3f086eeb65982fac18985a286f301e77c451154ce9ac8895d9",
+    "1630c3250d7313ff01d1201bf7a74ab5db3cb17dd952799b9ed3ab9097e68f90a0870d2dcae73d19cd13c1c66f652983",
+    "0d6ed6553fe44d296a3726c38ae652bfb11586264f0f8ce19008e218f9c86b2a8da25128c1052ecaddd7f225a139ed84",
+    "17b81e7701abdbe2e8743884d1117e53356de5ab275b4db1a682c62ef0f2753339b7c8f8c8f475af9ccb5618e3f0c88e",
+    "080d3cf1f9a78fc47b90b33563be990dc43b756ce79f5574a2c596c928c5d1de4fa295f296b74e956d71986a8497e317",
+    "169b1f8e1bcfa7c42e0c37515d138f22dd2ecb803a0c5c99676314baf4bb1b7fa3190b2edc0327797f241067be390c9e",
+    "10321da079ce07e272d8ec09d2565b0dfa7dccdde6787f96d50af36003b14866f69b771f8c285decca67df3f1605fb7b",
+    "06e08c248e260e70bd1e962381edee3d31d79d7e22c837bc23c0bf1bc24c6b68c24b1b80b64d391fa9c8ba2e8ba2d229",
+];
+
+const ISO_X_DEN: [&str; 10] = [
+    "08ca8d548cff19ae18b2e62f4bd3fa6f01d5ef4ba35b48ba9c9588617fc8ac62b558d681be343df8993cf9fa40d21b1c",
+    "12561a5deb559c4348b4711298e536367041e8ca0cf0800c0126c2588c48bf5713daa8846cb026e9e5c8276ec82b3bff",
+    "0b2962fe57a3225e8137e629bff2991f6f89416f5a718cd1fca64e00b11aceacd6a3d0967c94fedcfcc239ba5cb83e19",
+    "03425581a58ae2fec83aafef7c40eb545b08243f16b1655154cca8abc28d6fd04976d5243eecf5c4130de8938dc62cd8",
+    "13a8e162022914a80a6f1d5f43e7a07dffdfc759a12062bb8d6b44e833b306da9bd29ba81f35781d539d395b3532a21e",
+    "0e7355f8e4e667b955390f7f0506c6e9395735e9ce9cad4d0a43bcef24b8982f7400d24bc4228f11c02df9a29f6304a5",
+    "0772caacf16936190f3e0c63e0596721570f5799af53a1894e2e073062aede9cea73b3538f0de06cec2574496ee84a3a",
+    "14a7ac2a9d64a8b230b3f5b074cf01996e7f63c21bca68a81996e1cdf9822c580fa5b9489d11e2d311f7d99bbdcc5a5e",
+    "0a10ecf6ada54f825e920b3dafc7a3cce07f8d1d7161366b74100da67f39883503826692abba43704776ec3a79a1d641",
+    "095fc13ab9e92ad4476d6e3eb3a56680f682b4ee96f7d03776df533978f31c1593174e4b4b7865002d6384d168ecdd0a",
+];
+
+const ISO_Y_NUM: [&str; 16] = [
+    "090d97c81ba24ee0259d1f094980dcfa11ad138e48a869522b52af6c956543d3cd0c7aee9b3ba3c2be9845719707bb33",
+    "134996a104ee5811d51036d776fb46831223e96c254f383d0f906343eb67ad34d6c56711962fa8bfe097e75a2e41c696",
+    "00cc786baa966e66f4a384c86a3b49942552e2d658a31ce2c344be4b91400da7d26d521628b00523b8dfe240c72de1f6",
+    "01f86376e8981c217898751ad8746757d42aa7b90eeb791c09e4a3ec03251cf9de405aba9ec61deca6355c77b0e5f4cb",
+    "08cc03fdefe0ff135caf4fe2a21529c4195536fbe3ce50b879833fd221351adc2ee7f8dc099040a841b6daecf2e8fedb",
+    "16603fca40634b6a2211e11db8f0a6a074a7d0d4afadb7bd76505c3d3ad5544e203f6326c95a807299b23ab13633a5f0",
+    "04ab0b9bcfac1bbcb2c977d027796b3ce75bb8ca2be184cb5231413c4d634f3747a87ac2460f415ec961f8855fe9d6f2",
+    "0987c8d5333ab86fde9926bd2ca6c674170a05bfe3bdd81ffd038da6c26c842642f64550fedfe935a15e4ca31870fb29",
+    "09fc4018bd96684be88c9e221e4da1bb8f3abd16679dc26c1e8b6e6a1f20cabe69d65201c78607a360370e577bdba587",
+    "0e1bba7a1186bdb5223abde7ada14a23c42a0ca7915af6fe06985e7ed1e4d43b9b3f7055dd4eba6f2bafaaebca731c30",
+    "19713e47937cd1be0dfd0b8f1d43fb93cd2fcbcb6caf493fd1183e416389e61031bf3a5cce3fbafce813711ad011c132",
+    "18b46a908f36f6deb918c143fed2edcc523559b8aaf0c2462e6bfe7f911f643249d9cdf41b44d606ce07c8a4d0074d8e",
+    "0b182cac101b9399d155096004f53f447aa7b12a3426b08ec02710e807b4633f06c851c1919211f20d4c04f00b971ef8",
+    "0245a394ad1eca9b72fc00ae7be315dc757b3b080d4c158013e6632d3c40659cc6cf90ad1c232a6442d9d3f5db980133",
+    "05c129645e44cf1102a159f748c4a3fc5e673d81d7e86568d9ab0f5d396a7ce46ba1049b6579afb7866b1e715475224b",
+    "15e6be4e990f03ce4ea50b3b42df2eb5cb181d8f84965a3957add4fa95af01b2b665027efec01c7704b456be69c8b604",
+];
+
+const ISO_Y_DEN: [&str; 15] = [
+    "16112c4c3a9c98b252181140fad0eae9601a6de578980be6eec3232b5be72e7a07f3688ef60c206d01479253b03663c1",
+    "1962d75c2381201e1a0cbd6c43c348b885c84ff731c4d59ca4a10356f453e01f78a4260763529e3532f6102c2e49a03d",
+    "058df3306640da276faaae7d6e8eb15778c4855551ae7f310c35a5dd279cd2eca6757cd636f96f891e2538b53dbf67f2",
+    "16b7d288798e5395f20d23bf89edb4d1d115c5dbddbcd30e123da489e726af41727364f2c28297ada8d26d98445f5416",
+    "0be0e079545f43e4b00cc912f8228ddcc6d19c9f0f69bbb0542eda0fc9dec916a20b15dc0fd2ededda39142311a5001d",
+    "08d9e5297186db2d9fb266eaac783182b70152c65550d881c5ecd87b6f0f5a6449f38db9dfa9cce202c6477faaf9b7ac",
+    "166007c08a99db2fc3ba8734ace9824b5eecfdfa8d0cf8ef5dd365bc400a0051d5fa9c01a58b1fb93d1a1399126a775c",
+    "16a3ef08be3ea7ea03bcddfabba6ff6ee5a4375efa1f4fd7feb34fd206357132b920f5b00801dee460ee415a15812ed9",
+    "1866c8ed336c61231a1be54fd1d74cc4f9fb0ce4c6af5920abc5750c4bf39b4852cfe2f7bb9248836b233d9d55535d4a",
+    "167a55cda70a6e1cea820597d94a84903216f763e13d87bb5308592e7ea7d4fbc7385ea3d529b35e346ef48bb8913f55",
+    "04d2f259eea405bd48f010a01ad2911d9c6dd039bb61a6290e591b36e636a5c871a5c29f4f83060400f8b49cba8f6aa8",
+    "0accbb67481d033ff5852c1e48c50c477f94ff8aefce42d28c0f9a88cea7913516f968986f7ebbea9684b529e2561092",
+    "0ad6b9514c767fe3c3613144b45f1496543346d98adf02267d5ceef9a00d9b8693000763e3b90ac11e99b138573345cc",
+    "02660400eb2e4f3b628bdd0d53cd76f2bf565b94e72927c1cb748df27942480e420517bd8714cc80d1fadc1326ed06f7",
+    "0e0fa1d816ddc03e6b24255e0d7819c171c40f65e273b853324efcd6356caa205ca2f570f13497804415473a1d634b8f",
+];
+
+/// The coefficients of a polynomial of the isogeny map, from the constant term up, with the
+/// leading coefficient one appended if the polynomial is `monic`.
+fn iso_coefficients(coefficients: &[&str], monic: bool) -> Vec<BigUint> {
+    let mut coefficients = coefficients
+        .iter()
+        .map(|c| BigUint::from_str_radix(c, 16).unwrap())
+        .collect::<Vec<_>>();
+    if monic {
+        coefficients.push(BigUint::from(1u32));
+    }
+    coefficients
+}
+
+impl<L: AirParameters> AirBuilder<L> {
+    /// Maps the field element `u` to a point of `E'` by the simplified SWU map, following the
+    /// straight-line description of section 6.6.2 of RFC 9380.
+    ///
+    /// The input must be in canonical form, as are the results of the field operations.
+    pub fn bls12_381_map_to_curve_simple_swu(
+        &mut self,
+        u: &FieldRegister<Bls12381BaseField>,
+    ) -> AffinePointRegister<Bls12381Iso>
+    where
+        L::Instruction: Bls12381FpInstructions,
+    {
+        let mut z_limbs = [0u16; MAX_NB_LIMBS];
+        z_limbs[0] = SSWU_Z;
+        let a_limbs = Bls12381IsoParameters::A;
+        let b_limbs = Bls12381IsoParameters::B;
+
+        let zero = self.fp_zero::<Bls12381BaseField>();
+        let one = self.fp_one::<Bls12381BaseField>();
+        let z = self.fp_constant::<Bls12381BaseField>(&BigUint::from(SSWU_Z));
+        let b = self.fp_constant::<Bls12381BaseField>(&Bls12381IsoParameters::b_int());
+
+        // tv1 = Z * u^2 and tv2 = tv1^2 + tv1.
+        let u_sq = self.fp_mul(u, u);
+        let tv1 = self.fp_mul_const(&u_sq, z_limbs);
+        let tv1_sq = self.fp_mul(&tv1, &tv1);
+        let tv2 = self.fp_add(&tv1_sq, &tv1);
+
+        // x1 = B * (tv2 + 1) / (A * (-tv2)), or B / (A * Z) in the exceptional case tv2 = 0.
+        let tv2_plus_one = self.fp_add(&tv2, &one);
+        let numerator = self.fp_mul_const(&tv2_plus_one, b_limbs);
+        let neg_tv2 = self.fp_sub(&zero, &tv2);
+        let is_exceptional = self.bls12_381_is_zero(&tv2);
+        let tv4 = self.select(&is_exceptional, &z, &neg_tv2);
+        let denominator = self.fp_mul_const(&tv4, a_limbs);
+        let x1 = self.fp_div(&numerator, &denominator);
+
+        // gx1 = x1^3 + A * x1 + B.
+        let x1_sq = self.fp_mul(&x1, &x1);
+        let x1_cube = self.fp_mul(&x1_sq, &x1);
+        let a_x1 = self.fp_mul_const(&x1, a_limbs);
+        let gx1 = self.fp_add(&x1_cube, &a_x1);
+        let gx1 = self.fp_add(&gx1, &b);
+
+        // If gx1 is not a square, then x2 = tv1 * x1 and gx2 = tv1^3 * gx1, so that
+        // y2 = tv1 * u * sqrt(Z * gx1) is a square root of gx2.
+        let z_gx1 = self.fp_mul_const(&gx1, z_limbs);
+        let (is_square, root) = self.bls12_381_sqrt(&gx1, &z_gx1);
+        let x2 = self.fp_mul(&tv1, &x1);
+        let tv1_u = self.fp_mul(&tv1, u);
+        let y2 = self.fp_mul(&tv1_u, &root);
+        let x = self.select(&is_square, &x1, &x2);
+        let y = self.select(&is_square, &root, &y2);
+
+        // Fix the sign of y so that sgn0(y) = sgn0(u).
+        let sign_u = self.bls12_381_sgn0(u);
+        let sign_y = self.bls12_381_sgn0(&y);
+        let flip = self.bls12_381_xor_bits(&sign_u, &sign_y);
+        let neg_y = self.fp_sub(&zero, &y);
+        let y = self.select(&flip, &neg_y, &y);
+
+        AffinePointRegister::new(x, y)
+    }
+
+    /// Maps a point of `E'` to the curve by the 11-isogeny.
+    ///
+    /// The denominators of the map vanish only at the points of the kernel of the isogeny, which
+    /// the simplified SWU map reaches with negligible probability.
+    pub fn bls12_381_iso_map(
+        &mut self,
+        point: &AffinePointRegister<Bls12381Iso>,
+    ) -> AffinePointRegister<Bls12381>
+    where
+        L::Instruction: Bls12381FpInstructions,
+    {
+        let x_num = self.bls12_381_polynomial(&iso_coefficients(&ISO_X_NUM, false), &point.x);
+        let x_den = self.bls12_381_polynomial(&iso_coefficients(&ISO_X_DEN, true), &point.x);
+        let y_num = self.bls12_381_polynomial(&iso_coefficients(&ISO_Y_NUM, false), &point.x);
+        let y_den = self.bls12_381_polynomial(&iso_coefficients(&ISO_Y_DEN, true), &point.x);
+
+        let x = self.fp_div(&x_num, &x_den);
+        let y_ratio = self.fp_div(&y_num, &y_den);
+        let y = self.fp_mul(&point.y, &y_ratio);
+
+        AffinePointRegister::new(x, y)
+    }
+
+    /// Evaluates the polynomial with the given coefficients, from the constant term up, at `x` by
+    /// Horner's rule.
+    fn bls12_381_polynomial(
+        &mut self,
+        coefficients: &[BigUint],
+        x: &FieldRegister<Bls12381BaseField>,
+    ) -> FieldRegister<Bls12381BaseField>
+    where
+        L::Instruction: Bls12381FpInstructions,
+    {
+        let (leading, rest) = coefficients.split_last().unwrap();
+        let mut acc = self.fp_constant::<Bls12381BaseField>(leading);
+        for coefficient in rest.iter().rev() {
+            let constant = self.fp_constant::<Bls12381BaseField>(coefficient);
+            let product = self.fp_mul(&acc, x);
+            acc = self.fp_add(&product, &constant);
+        }
+        acc
+    }
+
+    /// Returns the exclusive or of two bits.
+    fn bls12_381_xor_bits(&mut self, a: &BitRegister, b: &BitRegister) -> BitRegister {
+        let xor = a.expr() + b.expr() - a.expr() * b.expr() * L::Field::from_canonical_u8(2);
+        if a.is_trace() || b.is_trace() {
+            let result = self.alloc::<BitRegister>();
+            self.set_to_expression(&result, xor);
+            result
+        } else {
+            let result = self.alloc_public::<BitRegister>();
+            self.set_to_expression_public(&result, xor);
+            result
+        }
+    }
+}
+
+/// The inverse of a non-zero element of the base field.
+fn inverse(a: &BigUint) -> BigUint {
+    let modulus = Bls12381BaseField::modulus();
+    a.modpow(&(&modulus - BigUint::from(2u32)), &modulus)
+}
+
+/// Maps the field element `u` to a point of `E'` by the simplified SWU map.
+pub fn map_to_curve_simple_swu(u: &BigUint) -> AffinePoint<Bls12381Iso> {
+    let modulus = Bls12381BaseField::modulus();
+    let a = Bls12381IsoParameters::a_int();
+    let b = Bls12381IsoParameters::b_int();
+    let z = BigUint::from(SSWU_Z);
+    let u = u % &modulus;
+
+    let tv1 = &z * &u * &u % &modulus;
+    let tv2 = (&tv1 * &tv1 + &tv1) % &modulus;
+    let (numerator, denominator) = match tv2.is_zero() {
+        true => (b.clone(), &a * &z % &modulus),
+        false => (
+            &b * (&tv2 + 1u32) % &modulus,
+            &a * (&modulus - &tv2) % &modulus,
+        ),
+    };
+    let x1 = numerator * inverse(&denominator) % &modulus;
+    let gx1 = (&x1 * &x1 * &x1 + &a * &x1 + &b) % &modulus;
+
+    let (x, y) = match sqrt(&gx1) {
+        Some(y1) => (x1, y1),
+        None => {
+            let x2 = &tv1 * &x1 % &modulus;
+            let gx2 = (&x2 * &x2 * &x2 + &a * &x2 + &b) % &modulus;
+            let y2 = sqrt(&gx2).expect("gx2 must be a square if gx1 is not");
+            (x2, y2)
+        }
+    };
+
+    let y = match sgn0(&u) == sgn0(&y) {
+        true => y,
+        false => (&modulus - &y) % &modulus,
+    };
+
+    AffinePoint::new(x, y)
+}
+
+/// Maps a point of `E'` to the curve by the 11-isogeny.
+pub fn iso_map(point: &AffinePoint<Bls12381Iso>) -> AffinePoint<Bls12381> {
+    let modulus = Bls12381BaseField::modulus();
+    let evaluate = |coefficients: Vec<BigUint>| {
+        coefficients
+            .iter()
+            .rev()
+            .fold(BigUint::zero(), |acc, c| (acc * &point.x + c) % &modulus)
+    };
+
+    let x_num = evaluate(iso_coefficients(&ISO_X_NUM, false));
+    let x_den = evaluate(iso_coefficients(&ISO_X_DEN, true));
+    let y_num = evaluate(iso_coefficients(&ISO_Y_NUM, false));
+    let y_den = evaluate(iso_coefficients(&ISO_Y_DEN, true));
+
+    let x = x_num * inverse(&x_den) % &modulus;
+    let y = &point.y * y_num % &modulus * inverse(&y_den) % &modulus;
+
+    AffinePoint::new(x, y)
+}
+
+/// Clears the cofactor of a point of the curve by a multiplication with `h_eff`.
+pub fn clear_cofactor(point: &AffinePoint<Bls12381>) -> AffinePoint<Bls12381> {
+    point.sw_scalar_mul(&BigUint::from(H_EFF))
+}
+
+/// Maps two field elements to a point of G1, as the `hash_to_curve` function of RFC 9380 does
+/// with the output of `hash_to_field`.
+///
+/// The two points of `E'` are added before the isogeny map, which is a group homomorphism.
+pub fn map_to_g1(u0: &BigUint, u1: &BigUint) -> AffinePoint<Bls12381> {
+    let q0 = map_to_curve_simple_swu(u0);
+    let q1 = map_to_curve_simple_swu(u1);
+    clear_cofactor(&iso_map(&q0.sw_add(&q1)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn from_hex(s: &str) -> BigUint {
+        BigUint::from_str_radix(s, 16).unwrap()
+    }
+
+    #[test]
+    fn test_bls12_381_map_to_g1() {
+        // The test vectors of section J.9.1 of RFC 9380 for the messages "" and "abc".
+        let test_vectors = [
+            (
+                "0ba14bd907ad64a016293ee7c2d276b8eae71f25a4b941eece7b0d89f17f75cb3ae5438a614fb61d6835ad59f29c564f",
+                "019b9bd7979f12657976de2884c7cce192b82c177c80e0ec604436a7f538d231552f0d96d9f7babe5fa3b19b3ff25ac9",
+                "11a3cce7e1d90975990066b2f2643b9540fa40d6137780df4e753a8054d07580db3b7f1f03396333d4a359d1fe3766fe",
+                "160003aaf1632b13396dbad518effa00fff532f604de1a7fc2082ff4cb0afa2d63b2c32da1bef2bf6c5ca62dc6b72f9c",
+                "052926add2207b76ca4fa57a8734416c8dc95e24501772c814278700eed6d1e4e8cf62d9c09db0fac349612b759e79a1",
+                "08ba738453bfed09cb546dbb0783dbb3a5f1f566ed67bb6be0e8c67e2e81a4cc68ee29813bb7994998f3eae0c9c6a265",
+            ),
+            (
+                "0d921c33f2bad966478a03ca35d05719bdf92d347557ea166e5bba579eea9b83e9afa5c088573c2281410369fbd32951",
+                "003574a00b109ada2f26a37a91f9d1e740dffd8d69ec0c35e1e9f4652c7dba61123e9dd2e76c655d956e2b3462611139",
+                "125435adce8e1cbd1c803e7123f45392dc6e326d292499c2c45c5865985fd74fe8f042ecdeeec5ecac80680d04317d80",
+                "11def93719829ecda3b46aa8c31fc3ac9c34b428982b898369608e4f042babee6c77ab9218aad5c87ba785481eff8ae4",
+                "03567bc5ef9c690c2ab2ecdf6a96ef1c139cc0b2f284dca0a9a7943388a49a3aee664ba5379a7655d3c68900be2f6903",
+                "0b9c15f3fe6e5cf4211f346271d7b01c8f3b28be689c8429c85b67af215533311f0b8dfaaa154fa6b88176c229f2885d",
+            ),
+        ];
+
+        for (u0, u1, q0_x, q1_x, p_x, p_y) in test_vectors {
+            let (u0, u1) = (from_hex(u0), from_hex(u1));
+            let q0 = iso_map(&map_to_curve_simple_swu(&u0));
+            let q1 = iso_map(&map_to_curve_simple_swu(&u1));
+            assert_eq!(q0.x, from_hex(q0_x));
+            assert_eq!(q1.x, from_hex(q1_x));
+
+            let p = map_to_g1(&u0, &u1);
+            assert_eq!(p.x, from_hex(p_x));
+            assert_eq!(p.y, from_hex(p_y));
+            assert_eq!(clear_cofactor(&q0.sw_add(&q1)), p);
+        }
+    }
+}
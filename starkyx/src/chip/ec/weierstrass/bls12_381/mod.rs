@@ -0,0 +1,6 @@
+pub mod instruction;
+pub mod is_zero;
+pub mod map;
+pub mod params;
+pub mod sgn0;
+pub mod sqrt;
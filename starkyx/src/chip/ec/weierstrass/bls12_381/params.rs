@@ -0,0 +1,172 @@
+use num::{BigUint, Num, Zero};
+use serde::{Deserialize, Serialize};
+
+use crate::chip::ec::weierstrass::{SWCurve, WeierstrassParameters};
+use crate::chip::ec::EllipticCurveParameters;
+use crate::chip::field::parameters::{FieldParameters, MAX_NB_LIMBS};
+use crate::math::bigint::bls12_381::Bls12381Scalar;
+use crate::math::field::PrimeField;
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+/// BLS12-381 G1 curve parameter
+pub struct Bls12381Parameters;
+
+pub type Bls12381 = SWCurve<Bls12381Parameters>;
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+/// Parameters of the curve `E'` which is 11-isogenous to BLS12-381 G1, as in section 8.8.1 of
+/// RFC 9380. The simplified SWU map sends field elements to `E'`, whose `a` is non-zero.
+pub struct Bls12381IsoParameters;
+
+pub type Bls12381Iso = SWCurve<Bls12381IsoParameters>;
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Serialize, Deserialize)]
+/// BLS12-381 base field parameter
+pub struct Bls12381BaseField;
+
+impl FieldParameters for Bls12381BaseField {
+    const NB_BITS_PER_LIMB: usize = 16;
+
+    const NB_LIMBS: usize = 24;
+
+    const NB_WITNESS_LIMBS: usize = 2 * Self::NB_LIMBS - 2;
+
+    // Base field modulus:
+    //  0x1a0111ea397fe69a4b1ba7b6434bacd764774b84f38512bf6730d2a0f6b0f6241eabfffeb153ffffb9feffffffffaaab
+    const MODULUS: [u16; MAX_NB_LIMBS] = [
+        43691, 65535, 65535, 47614, 65535, 45395, 65534, 7851, 63012, 63152, 53920, 26416, 4799,
+        62341, 19332, 25719, 44247, 17227, 42934, 19227, 59034, 14719, 4586, 6657, 0, 0, 0, 0, 0,
+        0, 0, 0,
+    ];
+
+    const WITNESS_OFFSET: usize = 1usize << 22;
+}
+
+impl EllipticCurveParameters for Bls12381Parameters {
+    type BaseField = Bls12381BaseField;
+}
+
+impl WeierstrassParameters for Bls12381Parameters {
+    const A: [u16; MAX_NB_LIMBS] = [
+        0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
+        0, 0,
+    ];
+
+    const B: [u16; MAX_NB_LIMBS] = [
+        4, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
+        0, 0,
+    ];
+
+    fn generator() -> (BigUint, BigUint) {
+        let x = BigUint::from_str_radix(
+            "17f1d3a73197d7942695638c4fa9ac0fc3688c4f9774b905a14e3a3f171bac586c55e83ff97a1aeffb3af00adb22c6bb",
+            16,
+        )
+        .unwrap();
+        let y = BigUint::from_str_radix(
+            "08b3f481e3aaa0f1a09e30ed741d8ae4fcf5e095d5d00af600db18cb2c04b3edd03cc744a2888ae40caa232946c5e7e1",
+            16,
+        )
+        .unwrap();
+        (x, y)
+    }
+
+    fn prime_group_order() -> BigUint {
+        Bls12381Scalar::modulus()
+    }
+
+    fn a_int() -> BigUint {
+        BigUint::zero()
+    }
+
+    fn b_int() -> BigUint {
+        BigUint::from(4u32)
+    }
+
+    /// The scalars are reduced modulo the order of the subgroup, which has 255 bits.
+    fn nb_scalar_bits() -> usize {
+        256
+    }
+}
+
+impl EllipticCurveParameters for Bls12381IsoParameters {
+    type BaseField = Bls12381BaseField;
+}
+
+impl WeierstrassParameters for Bls12381IsoParameters {
+    const A: [u16; MAX_NB_LIMBS] = [
+        19485, 11608, 10248, 23796, 63871, 41184, 28557, 39059, 33196, 61400, 38938, 55528, 26280,
+        33774, 38995, 45290, 18818, 51565, 14850, 15721, 59715, 41912, 18072, 20, 0, 0, 0, 0, 0, 0,
+        0, 0,
+    ];
+
+    const B: [u16; MAX_NB_LIMBS] = [
+        11232, 36375, 18665, 53708, 60069, 12652, 8538, 23075, 24309, 52979, 49487, 41145, 16496,
+        62031, 49648, 8214, 60987, 30014, 4840, 395, 32816, 4456, 37005, 4834, 0, 0, 0, 0, 0, 0, 0,
+        0,
+    ];
+
+    /// A point of order `r` of `E'`, which is the image of `1` by the simplified SWU map
+    /// multiplied by the cofactor of `E'`.
+    fn generator() -> (BigUint, BigUint) {
+        let x = BigUint::from_str_radix(
+            "195ff424a4c3bab19984db4d176abba1ca12a82586a644f035b1c9c559d2f250af0329814f1ee1a8052e750d518dd5c5",
+            16,
+        )
+        .unwrap();
+        let y = BigUint::from_str_radix(
+            "0570ce7b1d0b6552259bdb2db2adb080b157b2d18f2751eadc1a38d677b0943ee01b3a6e7595fd50f27a3c06cd4ece50",
+            16,
+        )
+        .unwrap();
+        (x, y)
+    }
+
+    /// The curves `E'` and BLS12-381 G1 are isogenous, so they have the same order.
+    fn prime_group_order() -> BigUint {
+        Bls12381Scalar::modulus()
+    }
+
+    fn nb_scalar_bits() -> usize {
+        256
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use num::One;
+
+    use super::*;
+    use crate::chip::ec::point::AffinePoint;
+
+    fn is_on_curve<E: WeierstrassParameters>(point: &AffinePoint<SWCurve<E>>) -> bool {
+        let p = E::BaseField::modulus();
+        let lhs = &point.y * &point.y % &p;
+        let rhs = (&point.x * &point.x * &point.x + E::a_int() * &point.x + E::b_int()) % &p;
+        lhs == rhs
+    }
+
+    #[test]
+    fn test_bls12_381_parameters() {
+        let p = BigUint::from_str_radix(
+            "1a0111ea397fe69a4b1ba7b6434bacd764774b84f38512bf6730d2a0f6b0f6241eabfffeb153ffffb9feffffffffaaab",
+            16,
+        )
+        .unwrap();
+        assert_eq!(Bls12381BaseField::modulus(), p);
+
+        // The generators are on their curves and of order `r`, so that `(r - 1) * G = -G`.
+        let order_minus_one = Bls12381Scalar::modulus() - BigUint::one();
+        let generator = Bls12381::generator();
+        assert!(is_on_curve(&generator));
+        let neg_generator = generator.sw_scalar_mul(&order_minus_one);
+        assert_eq!(neg_generator.x, generator.x);
+        assert_eq!(neg_generator.y, &p - &generator.y);
+
+        let iso_generator = Bls12381Iso::generator();
+        assert!(is_on_curve(&iso_generator));
+        let neg_iso_generator = iso_generator.sw_scalar_mul(&order_minus_one);
+        assert_eq!(neg_iso_generator.x, iso_generator.x);
+        assert_eq!(neg_iso_generator.y, &p - &iso_generator.y);
+    }
+}
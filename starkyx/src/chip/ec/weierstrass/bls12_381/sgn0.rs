@@ -0,0 +1,204 @@
+use num::BigUint;
+use serde::{Deserialize, Serialize};
+
+use super::params::Bls12381BaseField;
+use crate::air::AirConstraint;
+use crate::chip::builder::AirBuilder;
+use crate::chip::field::instruction::FromFieldInstruction;
+use crate::chip::field::parameters::FieldParameters;
+use crate::chip::field::register::FieldRegister;
+use crate::chip::instruction::Instruction;
+use crate::chip::register::array::ArrayRegister;
+use crate::chip::register::bit::BitRegister;
+use crate::chip::register::u16::U16Register;
+use crate::chip::register::{Register, RegisterSerializable};
+use crate::chip::trace::writer::{AirWriter, TraceWriter};
+use crate::chip::utils::digits_to_biguint;
+use crate::chip::AirParameters;
+use crate::math::prelude::*;
+use crate::polynomial::parser::PolynomialParser;
+use crate::polynomial::{to_u16_le_limbs_polynomial, Polynomial};
+
+/// The sign of an Fp element, as defined by the `sgn0` function of RFC 9380, which is the parity
+/// of its canonical representative.
+///
+/// This is done by witnessing the canonical representative `canonical` together with its
+/// complement `complement = p - 1 - canonical`, whose limbs are range checked. The sum is
+/// constrained limb by limb with carry bits, which proves that `canonical < p`. The sign is the
+/// lowest bit of the first limb of `canonical`, which is witnessed with the rest of the limb.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[serde(bound = "")]
+pub struct Bls12381FpSgn0Instruction {
+    a: FieldRegister<Bls12381BaseField>,
+    canonical: FieldRegister<Bls12381BaseField>,
+    complement: FieldRegister<Bls12381BaseField>,
+    carries: ArrayRegister<BitRegister>,
+    half_limb: U16Register,
+    sign: BitRegister,
+}
+
+impl<L: AirParameters> AirBuilder<L> {
+    /// Returns the sign of the field element `a`, which is the parity of its canonical
+    /// representative.
+    pub fn bls12_381_sgn0(&mut self, a: &FieldRegister<Bls12381BaseField>) -> BitRegister
+    where
+        L::Instruction: FromFieldInstruction<Bls12381BaseField> + From<Bls12381FpSgn0Instruction>,
+    {
+        let is_trace = a.is_trace();
+        let num_limbs = Bls12381BaseField::NB_LIMBS;
+
+        let canonical: FieldRegister<Bls12381BaseField>;
+        let complement: FieldRegister<Bls12381BaseField>;
+        let carries: ArrayRegister<BitRegister>;
+        let half_limb: U16Register;
+        let sign: BitRegister;
+        if is_trace {
+            canonical = self.alloc::<FieldRegister<Bls12381BaseField>>();
+            complement = self.alloc::<FieldRegister<Bls12381BaseField>>();
+            carries = self.alloc_array::<BitRegister>(num_limbs - 1);
+            half_limb = self.alloc::<U16Register>();
+            sign = self.alloc::<BitRegister>();
+        } else {
+            canonical = self.alloc_public::<FieldRegister<Bls12381BaseField>>();
+            complement = self.alloc_public::<FieldRegister<Bls12381BaseField>>();
+            carries = self.alloc_array_public::<BitRegister>(num_limbs - 1);
+            half_limb = self.alloc_public::<U16Register>();
+            sign = self.alloc_public::<BitRegister>();
+        }
+
+        let instr = Bls12381FpSgn0Instruction {
+            a: *a,
+            canonical,
+            complement,
+            carries,
+            half_limb,
+            sign,
+        };
+        if is_trace {
+            self.register_instruction(instr);
+        } else {
+            self.register_global_instruction(instr);
+        }
+
+        // Assert that `a - canonical = 0` as an integer, so that `canonical` is equal to `a`
+        // modulo p.
+        let difference = self.fp_sub(a, &canonical);
+        self.assert_expression_zero(difference.expr());
+
+        sign
+    }
+}
+
+impl<AP: PolynomialParser> AirConstraint<AP> for Bls12381FpSgn0Instruction {
+    fn eval(&self, parser: &mut AP) {
+        // Assert that `canonical + complement = p - 1` as integers, by constraining for each limb
+        // `canonical_i + complement_i + carry_in = (p - 1)_i + 2^16 * carry_out`. As all limbs are
+        // range checked, this proves that `canonical < p`.
+        let canonical = self.canonical.eval(parser).coefficients;
+        let complement = self.complement.eval(parser).coefficients;
+        let carries = self.carries.eval_vec(parser);
+        let num_limbs = canonical.len();
+        let p_minus_one = p_minus_one_limbs();
+        let limb_base = AP::Field::from_canonical_u32(1 << 16);
+        for (i, (canonical_limb, complement_limb)) in
+            canonical.iter().zip(complement.iter()).enumerate()
+        {
+            let mut lhs = parser.add(*canonical_limb, *complement_limb);
+            if i > 0 {
+                lhs = parser.add(lhs, carries[i - 1]);
+            }
+            let mut rhs = parser.constant(AP::Field::from_canonical_u16(p_minus_one[i]));
+            if i < num_limbs - 1 {
+                let carry_out = parser.mul_const(carries[i], limb_base);
+                rhs = parser.add(rhs, carry_out);
+            }
+            parser.assert_eq(lhs, rhs);
+        }
+
+        // Assert that `canonical_0 = 2 * half_limb + sign`. The range check of `half_limb` and of
+        // `canonical_0` imply that `half_limb < 2^15`.
+        let half_limb = self.half_limb.eval(parser);
+        let sign = self.sign.eval(parser);
+        let two_half_limb = parser.mul_const(half_limb, AP::Field::from_canonical_u8(2));
+        let first_limb = parser.add(two_half_limb, sign);
+        parser.assert_eq(canonical[0], first_limb);
+    }
+}
+
+/// The limbs of `p - 1`, where `p` is the modulus of the base field.
+fn p_minus_one_limbs() -> Vec<u16> {
+    let mut limbs = Bls12381BaseField::MODULUS[..Bls12381BaseField::NB_LIMBS].to_vec();
+    // The modulus is odd, so there is no borrow.
+    limbs[0] -= 1;
+    limbs
+}
+
+impl Bls12381FpSgn0Instruction {
+    /// Returns the values of `canonical`, `complement`, `carries`, `half_limb` and `sign`.
+    #[allow(clippy::type_complexity)]
+    fn witness<F: PrimeField64>(
+        p_a: Polynomial<F>,
+    ) -> (Polynomial<F>, Polynomial<F>, Vec<F>, F, F) {
+        let a_digits = p_a
+            .coefficients
+            .iter()
+            .map(|x| x.as_canonical_u64() as u16)
+            .collect::<Vec<_>>();
+        let a = digits_to_biguint(&a_digits);
+
+        let modulus = Bls12381BaseField::modulus();
+        let canonical = a % &modulus;
+        let complement = &modulus - 1u32 - &canonical;
+        let p_canonical = to_u16_le_limbs_polynomial::<F, Bls12381BaseField>(&canonical);
+        let p_complement = to_u16_le_limbs_polynomial::<F, Bls12381BaseField>(&complement);
+
+        let mut carries = Vec::with_capacity(Bls12381BaseField::NB_LIMBS - 1);
+        let mut carry = 0u64;
+        for ((c, d), m) in p_canonical
+            .coefficients
+            .iter()
+            .zip(p_complement.coefficients.iter())
+            .zip(p_minus_one_limbs())
+            .take(Bls12381BaseField::NB_LIMBS - 1)
+        {
+            let sum = c.as_canonical_u64() + d.as_canonical_u64() + carry;
+            carry = (sum - m as u64) >> 16;
+            carries.push(F::from_canonical_u64(carry));
+        }
+
+        let first_limb = p_canonical.coefficients[0].as_canonical_u64();
+        let half_limb = F::from_canonical_u64(first_limb >> 1);
+        let sign = F::from_canonical_u64(first_limb & 1);
+
+        (p_canonical, p_complement, carries, half_limb, sign)
+    }
+}
+
+impl<F: PrimeField64> Instruction<F> for Bls12381FpSgn0Instruction {
+    fn write(&self, writer: &TraceWriter<F>, row_index: usize) {
+        let p_a = writer.read(&self.a, row_index);
+        let (p_canonical, p_complement, carries, half_limb, sign) = Self::witness(p_a);
+
+        writer.write(&self.canonical, &p_canonical, row_index);
+        writer.write(&self.complement, &p_complement, row_index);
+        writer.write_array(&self.carries, carries, row_index);
+        writer.write(&self.half_limb, &half_limb, row_index);
+        writer.write(&self.sign, &sign, row_index);
+    }
+
+    fn write_to_air(&self, writer: &mut impl AirWriter<Field = F>) {
+        let p_a = writer.read(&self.a);
+        let (p_canonical, p_complement, carries, half_limb, sign) = Self::witness(p_a);
+
+        writer.write(&self.canonical, &p_canonical);
+        writer.write(&self.complement, &p_complement);
+        writer.write_array(&self.carries, carries);
+        writer.write(&self.half_limb, &half_limb);
+        writer.write(&self.sign, &sign);
+    }
+}
+
+/// The sign of `a`, which is the parity of its canonical representative.
+pub fn sgn0(a: &BigUint) -> bool {
+    (a % Bls12381BaseField::modulus()).bit(0)
+}
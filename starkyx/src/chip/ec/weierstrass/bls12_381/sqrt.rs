@@ -0,0 +1,235 @@
+use num::{BigUint, One, Zero};
+use serde::{Deserialize, Serialize};
+
+use super::params::Bls12381BaseField;
+use crate::air::AirConstraint;
+use crate::chip::builder::AirBuilder;
+use crate::chip::field::mul::FpMulInstruction;
+use crate::chip::field::parameters::FieldParameters;
+use crate::chip::field::register::FieldRegister;
+use crate::chip::instruction::Instruction;
+use crate::chip::register::array::ArrayRegister;
+use crate::chip::register::bit::BitRegister;
+use crate::chip::register::u16::U16Register;
+use crate::chip::register::{Register, RegisterSerializable};
+use crate::chip::trace::writer::{AirWriter, TraceWriter};
+use crate::chip::utils::digits_to_biguint;
+use crate::chip::AirParameters;
+use crate::math::prelude::*;
+use crate::polynomial::parser::PolynomialParser;
+use crate::polynomial::{to_u16_le_limbs_polynomial, Polynomial};
+
+/// Fp square root of `a`, or of `z * a` if `a` is not a square, for a non-square `z`.
+///
+/// This is done by witnessing whether `a` is a square, selecting `target` among `a` and `z * a`
+/// accordingly, and witnessing the square root of `target` with the constraint
+/// `result * result == target`. As `z` is not a square, exactly one of `a` and `z * a` is a square
+/// unless `a` is zero.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[serde(bound = "")]
+pub struct Bls12381FpSqrtInstruction {
+    a: FieldRegister<Bls12381BaseField>,
+    z_a: FieldRegister<Bls12381BaseField>,
+    is_square: BitRegister,
+    /// a `FpMulInstruction` to compute `result * result = target`.
+    square: FpMulInstruction<Bls12381BaseField>,
+}
+
+impl<L: AirParameters> AirBuilder<L> {
+    /// Given a field element `a` and the product `z_a = z * a` for a non-square `z`, returns a
+    /// pair `(is_square, r)` where `r` is a square root of `a` if `is_square` is set and a square
+    /// root of `z * a` otherwise.
+    ///
+    /// The inputs must be in canonical form, as are the results of the field operations.
+    pub fn bls12_381_sqrt(
+        &mut self,
+        a: &FieldRegister<Bls12381BaseField>,
+        z_a: &FieldRegister<Bls12381BaseField>,
+    ) -> (BitRegister, FieldRegister<Bls12381BaseField>)
+    where
+        L::Instruction: From<Bls12381FpSqrtInstruction>,
+    {
+        let is_trace = a.is_trace() || z_a.is_trace();
+
+        let is_square: BitRegister;
+        let result: FieldRegister<Bls12381BaseField>;
+        let target: FieldRegister<Bls12381BaseField>;
+        let square_carry: FieldRegister<Bls12381BaseField>;
+        let square_witness_low: ArrayRegister<U16Register>;
+        let square_witness_high: ArrayRegister<U16Register>;
+
+        if is_trace {
+            is_square = self.alloc::<BitRegister>();
+            result = self.alloc::<FieldRegister<Bls12381BaseField>>();
+            target = self.alloc::<FieldRegister<Bls12381BaseField>>();
+            square_carry = self.alloc::<FieldRegister<Bls12381BaseField>>();
+            square_witness_low =
+                self.alloc_array::<U16Register>(Bls12381BaseField::NB_WITNESS_LIMBS);
+            square_witness_high =
+                self.alloc_array::<U16Register>(Bls12381BaseField::NB_WITNESS_LIMBS);
+        } else {
+            is_square = self.alloc_public::<BitRegister>();
+            result = self.alloc_public::<FieldRegister<Bls12381BaseField>>();
+            target = self.alloc_public::<FieldRegister<Bls12381BaseField>>();
+            square_carry = self.alloc_public::<FieldRegister<Bls12381BaseField>>();
+            square_witness_low =
+                self.alloc_array_public::<U16Register>(Bls12381BaseField::NB_WITNESS_LIMBS);
+            square_witness_high =
+                self.alloc_array_public::<U16Register>(Bls12381BaseField::NB_WITNESS_LIMBS);
+        }
+
+        // check that result * result == target
+        let square = FpMulInstruction {
+            a: result,
+            b: result,
+            result: target,
+            carry: square_carry,
+            witness_low: square_witness_low,
+            witness_high: square_witness_high,
+        };
+
+        let instr = Bls12381FpSqrtInstruction {
+            a: *a,
+            z_a: *z_a,
+            is_square,
+            square,
+        };
+
+        if is_trace {
+            self.register_instruction(instr);
+        } else {
+            self.register_global_instruction(instr);
+        }
+
+        (is_square, result)
+    }
+}
+
+impl<AP: PolynomialParser> AirConstraint<AP> for Bls12381FpSqrtInstruction {
+    fn eval(&self, parser: &mut AP) {
+        // Assert that result * result == target
+        self.square.eval(parser);
+
+        // Assert that `target = is_square * a + (1 - is_square) * z_a`, limb by limb.
+        let is_square = self.is_square.eval(parser);
+        let a = self.a.eval(parser);
+        let z_a = self.z_a.eval(parser);
+        let target = self.square.result.eval(parser);
+        for ((a_limb, z_a_limb), target_limb) in a
+            .coefficients
+            .into_iter()
+            .zip(z_a.coefficients)
+            .zip(target.coefficients)
+        {
+            let diff = parser.sub(a_limb, z_a_limb);
+            let selected = parser.mul(is_square, diff);
+            let selected = parser.add(selected, z_a_limb);
+            parser.assert_eq(target_limb, selected);
+        }
+    }
+}
+
+impl Bls12381FpSqrtInstruction {
+    /// Returns the values of `is_square`, `target` and of the square root.
+    fn witness<F: PrimeField64>(
+        p_a: Polynomial<F>,
+        p_z_a: Polynomial<F>,
+    ) -> (F, Polynomial<F>, Polynomial<F>) {
+        let a_digits = p_a
+            .coefficients
+            .iter()
+            .map(|x| x.as_canonical_u64() as u16)
+            .collect::<Vec<_>>();
+        let a = digits_to_biguint(&a_digits);
+
+        let (is_square, p_target) = match is_square(&a) {
+            true => (F::ONE, p_a),
+            false => (F::ZERO, p_z_a),
+        };
+        let target_digits = p_target
+            .coefficients
+            .iter()
+            .map(|x| x.as_canonical_u64() as u16)
+            .collect::<Vec<_>>();
+        let target = digits_to_biguint(&target_digits);
+
+        let root = sqrt(&target).expect("Either a or z * a must be a square");
+        let p_root = to_u16_le_limbs_polynomial::<F, Bls12381BaseField>(&root);
+
+        (is_square, p_target, p_root)
+    }
+}
+
+impl<F: PrimeField64> Instruction<F> for Bls12381FpSqrtInstruction {
+    fn write(&self, writer: &TraceWriter<F>, row_index: usize) {
+        let p_a = writer.read(&self.a, row_index);
+        let p_z_a = writer.read(&self.z_a, row_index);
+
+        let (is_square, p_target, p_root) = Self::witness(p_a, p_z_a);
+
+        writer.write(&self.is_square, &is_square, row_index);
+        writer.write(&self.square.result, &p_target, row_index);
+        writer.write(&self.square.a, &p_root, row_index);
+
+        self.square.write(writer, row_index);
+    }
+
+    fn write_to_air(&self, writer: &mut impl AirWriter<Field = F>) {
+        let p_a = writer.read(&self.a);
+        let p_z_a = writer.read(&self.z_a);
+
+        let (is_square, p_target, p_root) = Self::witness(p_a, p_z_a);
+
+        writer.write(&self.is_square, &is_square);
+        writer.write(&self.square.result, &p_target);
+        writer.write(&self.square.a, &p_root);
+
+        self.square.write_to_air(writer);
+    }
+}
+
+/// Returns `true` if `a` is a square in the base field, including zero.
+pub fn is_square(a: &BigUint) -> bool {
+    let modulus = Bls12381BaseField::modulus();
+    let a = a % &modulus;
+    // Euler's criterion.
+    let legendre = a.modpow(&((&modulus - BigUint::one()) >> 1), &modulus);
+    legendre.is_zero() || legendre.is_one()
+}
+
+/// The square root of `a` in the base field, if `a` is a square.
+pub fn sqrt(a: &BigUint) -> Option<BigUint> {
+    // As the modulus is `3 mod 4`, the square root of a square `a` is `a^((p + 1) / 4)`.
+    let modulus = Bls12381BaseField::modulus();
+    let a = a % &modulus;
+    let root = a.modpow(&((&modulus + BigUint::one()) >> 2), &modulus);
+    (&root * &root % &modulus == a).then_some(root)
+}
+
+#[cfg(test)]
+mod tests {
+    use num::bigint::RandBigInt;
+    use rand::thread_rng;
+
+    use super::*;
+
+    #[test]
+    fn test_bls12_381_sqrt() {
+        let modulus = Bls12381BaseField::modulus();
+        let z = BigUint::from(11u32);
+        assert!(!is_square(&z));
+        assert!(is_square(&BigUint::zero()));
+
+        let mut rng = thread_rng();
+        for _ in 0..10 {
+            let a = rng.gen_biguint_below(&modulus);
+            let root = sqrt(&(&a * &a)).unwrap();
+            assert!(root == a || root == &modulus - &a);
+
+            // Exactly one of `a` and `z * a` is a square.
+            let z_a = &z * &a % &modulus;
+            assert_ne!(is_square(&a), is_square(&z_a));
+            assert_eq!(sqrt(&a).is_some(), is_square(&a));
+        }
+    }
+}
@@ -1,9 +1,11 @@
-use num::{BigUint, Num, Zero};
+use num::{BigUint, Zero};
 use serde::{Deserialize, Serialize};
 
 use super::{SWCurve, WeierstrassParameters};
 use crate::chip::ec::EllipticCurveParameters;
 use crate::chip::field::parameters::FieldParameters;
+use crate::math::bigint::bn254::Bn254Scalar;
+use crate::math::field::PrimeField;
 
 #[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
 /// Bn254 curve parameter
@@ -53,11 +55,7 @@ impl WeierstrassParameters for Bn254Parameters {
     }
 
     fn prime_group_order() -> num::BigUint {
-        BigUint::from_str_radix(
-            "21888242871839275222246405745257275088548364400416034343698204186575808495617",
-            10,
-        )
-        .unwrap()
+        Bn254Scalar::modulus()
     }
 
     fn a_int() -> BigUint {
@@ -9,6 +9,7 @@ use crate::chip::field::parameters::{FieldParameters, MAX_NB_LIMBS};
 use crate::chip::AirParameters;
 
 pub mod biguint_operations;
+pub mod bls12_381;
 pub mod bn254;
 pub mod group;
 pub mod slope;
@@ -72,6 +73,10 @@ impl<E: WeierstrassParameters> EllipticCurve for SWCurve<E> {
         let modulus = E::BaseField::modulus();
         AffinePoint::new(p.x.clone(), modulus - &p.y)
     }
+
+    fn nb_scalar_bits() -> usize {
+        E::nb_scalar_bits()
+    }
 }
 
 impl<E: WeierstrassParameters> SWCurve<E> {
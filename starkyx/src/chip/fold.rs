@@ -0,0 +1,196 @@
+//! Fold-style computations over a committed list.
+//!
+//! A fold proves that `result = step(...step(step(initial, list[0]), list[1])..., list[n - 1])`
+//! for a step function given by the caller. Each row of the trace applies the step function once:
+//!
+//! - The running state is kept in a register which holds `initial` in the first row and the
+//! output of the step function of the previous row in every other row.
+//! - The list element of each row is fed through a bus. The list is a public or global array, and
+//! the bus matches the pairs `(i, list[i])` with the pairs `(clk, element)` of the trace, so that
+//! row `i` processes `list[i]`.
+//! - The output of the step function in the last row is the result of the fold.
+//!
+//! The list must have as many elements as the trace has rows.
+
+use serde::{Deserialize, Serialize};
+
+use super::arithmetic::expression::ArithmeticExpression;
+use super::builder::AirBuilder;
+use super::register::array::ArrayRegister;
+use super::register::cubic::CubicRegister;
+use super::register::element::ElementRegister;
+use super::register::Register;
+use super::trace::writer::TraceWriter;
+use super::AirParameters;
+use crate::math::prelude::*;
+
+/// The registers of a fold over a list.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Fold<S, T> {
+    /// The list of elements.
+    pub list: ArrayRegister<T>,
+    /// The element of the list processed in the current row.
+    pub element: T,
+    /// The state before applying the step function in the current row.
+    pub state: S,
+    /// The state after applying the step function in the current row.
+    pub next_state: S,
+    /// The initial state.
+    pub initial: S,
+    /// The final state, after processing all elements of the list.
+    pub result: S,
+}
+
+impl<L: AirParameters> AirBuilder<L> {
+    /// Folds the `list` starting from the state `initial`, where the step function `step` takes the
+    /// current state and list element and returns the next state.
+    ///
+    /// The step function is called once to register the constraints and instructions of a single
+    /// row. The registers it returns must be written by its instructions. The list and the initial
+    /// state must be public or global registers, and the result is a public register.
+    pub fn fold<S: Register, T: Register>(
+        &mut self,
+        list: &ArrayRegister<T>,
+        initial: &S,
+        step: impl FnOnce(&mut Self, &S, &T) -> S,
+    ) -> Fold<S, T> {
+        assert!(
+            !list.is_trace() && !initial.is_trace(),
+            "The list and the initial state must be public or global registers"
+        );
+
+        self.require_rows("fold", list.len());
+
+        let state = self.alloc::<S>();
+        let element = self.alloc::<T>();
+        let next_state = step(self, &state, &element);
+        let result = self.alloc_public::<S>();
+
+        // The running state starts at `initial`, follows the step function and ends at `result`.
+        self.assert_equal_first_row(&state, initial);
+        self.assert_equal_transition(&state.next(), &next_state);
+        self.assert_equal_last_row(&next_state, &result);
+
+        // Feed the elements of the list to the rows in order.
+        let clk = self.clock();
+        let challenges = self.alloc_array_challenge::<CubicRegister>(1 + T::size_of());
+        let row_digest = self.accumulate_expressions(&challenges, &[clk.expr(), element.expr()]);
+
+        let mut bus = self.new_bus();
+        let channel_idx = bus.new_channel(self);
+        self.output_from_bus(channel_idx, row_digest);
+        for (i, value) in list.iter().enumerate() {
+            let index = ArithmeticExpression::from_constant(L::Field::from_canonical_usize(i));
+            let digest = self.accumulate_public_expressions(&challenges, &[index, value.expr()]);
+            bus.insert_global_value(&digest);
+        }
+        self.constrain_bus(bus);
+
+        Fold {
+            list: *list,
+            element,
+            state,
+            next_state,
+            initial: *initial,
+            result,
+        }
+    }
+}
+
+impl<S: Register, T: Register> Fold<S, T> {
+    /// Writes the list element and the state of the row `row_index`.
+    ///
+    /// The list and the initial state must already be written. This function must be called for
+    /// each row before writing the instructions of the row, and after writing the instructions of
+    /// the previous row.
+    pub fn write_row<F: Field>(&self, writer: &TraceWriter<F>, row_index: usize) {
+        let element = writer.read(&self.list.get(row_index), 0);
+        writer.write(&self.element, &element, row_index);
+
+        let state = if row_index == 0 {
+            writer.read(&self.initial, 0)
+        } else {
+            writer.read(&self.next_state, row_index - 1)
+        };
+        writer.write(&self.state, &state, row_index);
+    }
+
+    /// Writes the result of the fold, once all rows are written.
+    pub fn write_result<F: Field>(&self, writer: &TraceWriter<F>, num_rows: usize) {
+        let result = writer.read(&self.next_state, num_rows - 1);
+        writer.write(&self.result, &result, 0);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use plonky2::field::types::Sample;
+
+    use super::*;
+    use crate::chip::builder::tests::*;
+
+    #[derive(Debug, Clone, Serialize, Deserialize)]
+    pub struct FoldTest;
+
+    impl AirParameters for FoldTest {
+        type Field = GoldilocksField;
+        type CubicParams = GoldilocksCubicParameters;
+        type Instruction = EmptyInstruction<GoldilocksField>;
+
+        const NUM_FREE_COLUMNS: usize = 4;
+        const EXTENDED_COLUMNS: usize = 6;
+    }
+
+    #[test]
+    fn test_fold() {
+        type F = GoldilocksField;
+        type L = FoldTest;
+        type SC = PoseidonGoldilocksStarkConfig;
+
+        let num_rows = 1 << 8;
+
+        let mut builder = AirBuilder::<L>::new();
+        let list = builder.alloc_array_public::<ElementRegister>(num_rows);
+        let initial = builder.alloc_public::<ElementRegister>();
+        // Horner evaluation of the list as a polynomial at 3.
+        let fold = builder.fold(&list, &initial, |builder, state, element| {
+            let next = builder.alloc::<ElementRegister>();
+            builder.set_to_expression(
+                &next,
+                state.expr() * F::from_canonical_u8(3) + element.expr(),
+            );
+            next
+        });
+
+        let (air, trace_data) = builder.build();
+        let generator = ArithmeticGenerator::<L>::new(trace_data, num_rows);
+
+        let values = (0..num_rows).map(|_| F::rand()).collect::<Vec<_>>();
+        let initial_value = F::rand();
+
+        let writer = generator.new_writer();
+        writer.write_array(&list, values.iter().copied(), 0);
+        writer.write(&initial, &initial_value, 0);
+        writer.write_global_instructions(&generator.air_data);
+        for i in 0..num_rows {
+            fold.write_row(&writer, i);
+            writer.write_row_instructions(&generator.air_data, i);
+        }
+        fold.write_result(&writer, num_rows);
+
+        let expected = values
+            .iter()
+            .fold(initial_value, |acc, x| acc * F::from_canonical_u8(3) + *x);
+        assert_eq!(writer.read(&fold.result, 0), expected);
+
+        let stark = Starky::new(air);
+        let config = SC::standard_fast_config(num_rows);
+
+        let public = writer.public().unwrap().clone();
+        // Generate proof and verify as a stark
+        test_starky(&stark, &config, &generator, &public);
+
+        // Test the recursive proof.
+        test_recursive_starky(stark, config, generator, &public);
+    }
+}
@@ -119,4 +119,12 @@ impl<F: Field> Instruction<F> for AssignInstruction<F> {
             _ => {}
         }
     }
+
+    fn inputs(&self) -> Option<Vec<MemorySlice>> {
+        Some(self.source.registers())
+    }
+
+    fn outputs(&self) -> Option<Vec<MemorySlice>> {
+        Some(vec![self.target])
+    }
 }
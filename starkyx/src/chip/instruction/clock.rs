@@ -4,6 +4,7 @@ use super::Instruction;
 use crate::air::parser::AirParser;
 use crate::air::AirConstraint;
 use crate::chip::register::element::ElementRegister;
+use crate::chip::register::memory::MemorySlice;
 use crate::chip::register::{Register, RegisterSerializable};
 use crate::chip::trace::writer::{AirWriter, TraceWriter};
 use crate::math::prelude::*;
@@ -36,4 +37,12 @@ impl<F: Field> Instruction<F> for ClockInstruction {
         let value = F::from_canonical_usize(writer.row_index().unwrap());
         writer.write(&self.clk, &value);
     }
+
+    fn inputs(&self) -> Option<Vec<MemorySlice>> {
+        Some(Vec::new())
+    }
+
+    fn outputs(&self) -> Option<Vec<MemorySlice>> {
+        Some(vec![*self.clk.register()])
+    }
 }
@@ -9,6 +9,7 @@ use crate::chip::builder::AirBuilder;
 use crate::chip::register::array::ArrayRegister;
 use crate::chip::register::bit::BitRegister;
 use crate::chip::register::element::ElementRegister;
+use crate::chip::register::memory::MemorySlice;
 use crate::chip::register::{Register, RegisterSerializable};
 use crate::chip::trace::writer::{AirWriter, TraceWriter};
 use crate::chip::AirParameters;
@@ -48,6 +49,7 @@ impl Loop {
 
 impl<L: AirParameters> AirBuilder<L> {
     pub fn cycle(&mut self, length_log: usize) -> Cycle<L::Field> {
+        self.require_rows("cycle", 1 << length_log);
         let start_bit = self.alloc::<BitRegister>();
         let end_bit = self.alloc::<BitRegister>();
         let element = self.alloc::<ElementRegister>();
@@ -232,6 +234,20 @@ impl<F: Field> Instruction<F> for Cycle<F> {
             writer.write(&self.end_bit_witness, &(element - gen_inverse).inverse());
         }
     }
+
+    fn inputs(&self) -> Option<Vec<MemorySlice>> {
+        Some(Vec::new())
+    }
+
+    fn outputs(&self) -> Option<Vec<MemorySlice>> {
+        Some(vec![
+            *self.element.register(),
+            *self.start_bit.register(),
+            *self.end_bit.register(),
+            *self.start_bit_witness.register(),
+            *self.end_bit_witness.register(),
+        ])
+    }
 }
 
 impl<AP: AirParser<Field = F>, F: Field> AirConstraint<AP> for ProcessIdInstruction {
@@ -258,6 +274,14 @@ impl<F: Field> Instruction<F> for ProcessIdInstruction {
         let process_id = F::from_canonical_usize(row_index / self.size);
         writer.write(&self.process_id, &process_id);
     }
+
+    fn inputs(&self) -> Option<Vec<MemorySlice>> {
+        Some(Vec::new())
+    }
+
+    fn outputs(&self) -> Option<Vec<MemorySlice>> {
+        Some(vec![*self.process_id.register()])
+    }
 }
 
 #[cfg(test)]
@@ -2,6 +2,7 @@ use core::fmt::Debug;
 
 use serde::{Deserialize, Serialize};
 
+use super::register::memory::MemorySlice;
 use super::trace::writer::AirWriter;
 use crate::chip::trace::writer::TraceWriter;
 use crate::math::prelude::*;
@@ -22,6 +23,19 @@ pub trait Instruction<F: Field>:
     #[allow(unused_variables)]
     // Writes the instruction to a general AirWriter.
     fn write_to_air(&self, writer: &mut impl AirWriter<Field = F>);
+
+    /// The registers read by the instruction, or `None` if they are not known.
+    ///
+    /// Instructions with known inputs and outputs can be reordered according to their data
+    /// dependencies when writing the trace.
+    fn inputs(&self) -> Option<Vec<MemorySlice>> {
+        None
+    }
+
+    /// The registers written by the instruction, or `None` if they are not known.
+    fn outputs(&self) -> Option<Vec<MemorySlice>> {
+        None
+    }
 }
 
 /// An instruction that only consists of constraints
@@ -34,4 +48,12 @@ impl<F: Field, C: ConstraintInstruction> Instruction<F> for C {
     fn write(&self, _writer: &TraceWriter<F>, _row_index: usize) {}
 
     fn write_to_air(&self, _writer: &mut impl AirWriter<Field = F>) {}
+
+    fn inputs(&self) -> Option<Vec<MemorySlice>> {
+        Some(Vec::new())
+    }
+
+    fn outputs(&self) -> Option<Vec<MemorySlice>> {
+        Some(Vec::new())
+    }
 }
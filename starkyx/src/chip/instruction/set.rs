@@ -16,6 +16,7 @@ use crate::chip::memory::instruction::MemoryInstruction;
 use crate::chip::register::array::ArrayRegister;
 use crate::chip::register::element::ElementRegister;
 use crate::chip::register::memory::MemorySlice;
+use crate::chip::register::RegisterSerializable;
 use crate::chip::trace::writer::TraceWriter;
 use crate::math::prelude::*;
 
@@ -118,6 +119,41 @@ impl<F: Field, I: Instruction<F>> Instruction<F> for AirInstruction<F, I> {
             }
         }
     }
+
+    fn inputs(&self) -> Option<Vec<MemorySlice>> {
+        match self {
+            AirInstruction::CustomInstruction(i) => i.inputs(),
+            AirInstruction::BitConstraint(i) => Instruction::<F>::inputs(i),
+            AirInstruction::Select(i) => Instruction::<F>::inputs(i),
+            AirInstruction::Assign(i) => Instruction::<F>::inputs(i),
+            AirInstruction::Cycle(i) => Instruction::<F>::inputs(i),
+            AirInstruction::Clock(i) => Instruction::<F>::inputs(i),
+            AirInstruction::ProcessId(i) => Instruction::<F>::inputs(i),
+            AirInstruction::Filtered(expression, i) => {
+                let mut inputs = expression.registers();
+                inputs.extend(i.inputs()?);
+                Some(inputs)
+            }
+            // Memory instructions depend on the state of the memory.
+            AirInstruction::Mem(_) => None,
+            AirInstruction::Watch(_, register) => Some(vec![*register.register()]),
+        }
+    }
+
+    fn outputs(&self) -> Option<Vec<MemorySlice>> {
+        match self {
+            AirInstruction::CustomInstruction(i) => i.outputs(),
+            AirInstruction::BitConstraint(i) => Instruction::<F>::outputs(i),
+            AirInstruction::Select(i) => Instruction::<F>::outputs(i),
+            AirInstruction::Assign(i) => Instruction::<F>::outputs(i),
+            AirInstruction::Cycle(i) => Instruction::<F>::outputs(i),
+            AirInstruction::Clock(i) => Instruction::<F>::outputs(i),
+            AirInstruction::ProcessId(i) => Instruction::<F>::outputs(i),
+            AirInstruction::Filtered(_, i) => i.outputs(),
+            AirInstruction::Mem(_) => None,
+            AirInstruction::Watch(_, _) => Some(Vec::new()),
+        }
+    }
 }
 
 impl<F, I> From<I> for AirInstruction<F, I> {
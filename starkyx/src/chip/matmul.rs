@@ -0,0 +1,261 @@
+//! A chip for quantized matrix multiplication.
+//!
+//! The chip proves `C = A * B` where `A` is an `m x n` matrix and `B` is a small `n x k` matrix,
+//! both with entries quantized to unsigned 16-bit integers. The layout places one row of `A` in
+//! each row of the trace, while `B` is kept in the same columns of every row and constrained to be
+//! constant along the trace. Each trace row then computes the `k` dot products of the row of `A`
+//! with the columns of `B`. For a matrix-vector product, take `k = 1`.
+//!
+//! All entries of `A`, `B` and `C` are range checked by allocating them as `U16Register`s. A dot
+//! product of `n` pairs of 16-bit values is less than `n * 2^32`, so for `n <= 2^16` every output
+//! fits in three 16-bit limbs and the computation does not overflow the Goldilocks field.
+
+use serde::{Deserialize, Serialize};
+
+use super::builder::AirBuilder;
+use super::instruction::Instruction;
+use super::register::array::ArrayRegister;
+use super::register::u16::U16Register;
+use super::register::RegisterSerializable;
+use super::trace::writer::{AirWriter, TraceWriter};
+use super::AirParameters;
+use crate::air::parser::AirParser;
+use crate::air::AirConstraint;
+use crate::math::prelude::*;
+
+/// The number of 16-bit limbs used to represent the result of a dot product.
+pub const DOT_PRODUCT_LIMBS: usize = 3;
+
+/// The maximal length of vectors supported by the dot product instruction.
+pub const MAX_DOT_PRODUCT_LEN: usize = 1 << 16;
+
+/// An instruction computing the dot product of two vectors of 16-bit values.
+///
+/// The result is written as little-endian 16-bit limbs.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct DotProductInstruction {
+    a: ArrayRegister<U16Register>,
+    b: ArrayRegister<U16Register>,
+    pub result: ArrayRegister<U16Register>,
+}
+
+/// The registers of a quantized matrix multiplication `C = A * B`.
+///
+/// Each trace row holds a row of `A` and the corresponding row of `C`, while all rows hold the
+/// same copy of `B`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct QuantizedMatMul {
+    /// A row of the left-hand matrix `A`.
+    pub lhs_row: ArrayRegister<U16Register>,
+    /// The columns of the right-hand matrix `B`.
+    pub rhs_columns: Vec<ArrayRegister<U16Register>>,
+    /// The entries of the corresponding row of `C`, each as little-endian 16-bit limbs.
+    pub output: Vec<ArrayRegister<U16Register>>,
+}
+
+impl<L: AirParameters> AirBuilder<L> {
+    /// Computes the dot product of `a` and `b` and returns the result as 16-bit limbs.
+    pub fn u16_dot_product(
+        &mut self,
+        a: &ArrayRegister<U16Register>,
+        b: &ArrayRegister<U16Register>,
+    ) -> ArrayRegister<U16Register>
+    where
+        L::Instruction: From<DotProductInstruction>,
+    {
+        assert_eq!(a.len(), b.len(), "Vectors must have the same length");
+        assert!(
+            a.len() <= MAX_DOT_PRODUCT_LEN,
+            "Vectors of length {} exceed the maximal length {}",
+            a.len(),
+            MAX_DOT_PRODUCT_LEN
+        );
+        assert!(
+            a.is_trace() && b.is_trace(),
+            "Vectors must be trace registers"
+        );
+
+        let result = self.alloc_array::<U16Register>(DOT_PRODUCT_LIMBS);
+        let instr = DotProductInstruction {
+            a: *a,
+            b: *b,
+            result,
+        };
+        self.register_instruction(instr);
+        result
+    }
+
+    /// Allocates the registers and constraints for the product of an `m x inner_dim` matrix `A`
+    /// with an `inner_dim x num_columns` matrix `B`, with one row of `A` per trace row.
+    ///
+    /// The registers of `B` are constrained to be equal in all rows of the trace.
+    pub fn quantized_mat_mul(&mut self, inner_dim: usize, num_columns: usize) -> QuantizedMatMul
+    where
+        L::Instruction: From<DotProductInstruction>,
+    {
+        let lhs_row = self.alloc_array::<U16Register>(inner_dim);
+        let rhs_columns = (0..num_columns)
+            .map(|_| self.alloc_array::<U16Register>(inner_dim))
+            .collect::<Vec<_>>();
+
+        for column in rhs_columns.iter() {
+            self.assert_expressions_equal_transition(column.expr(), column.next().expr());
+        }
+
+        let output = rhs_columns
+            .iter()
+            .map(|column| self.u16_dot_product(&lhs_row, column))
+            .collect();
+
+        QuantizedMatMul {
+            lhs_row,
+            rhs_columns,
+            output,
+        }
+    }
+}
+
+/// Computes the dot product of two vectors of 16-bit values as little-endian 16-bit limbs.
+pub fn u16_dot_product_limbs(a: &[u64], b: &[u64]) -> [u64; DOT_PRODUCT_LIMBS] {
+    debug_assert!(a.len() <= MAX_DOT_PRODUCT_LEN);
+    let value = a.iter().zip(b.iter()).map(|(x, y)| x * y).sum::<u64>();
+    core::array::from_fn(|i| (value >> (16 * i)) & 0xFFFF)
+}
+
+impl<AP: AirParser> AirConstraint<AP> for DotProductInstruction {
+    fn eval(&self, parser: &mut AP) {
+        let a = self.a.eval_vec(parser);
+        let b = self.b.eval_vec(parser);
+        let limbs = self.result.eval_vec(parser);
+
+        let products = a
+            .iter()
+            .zip(b.iter())
+            .map(|(x, y)| parser.mul(*x, *y))
+            .collect::<Vec<_>>();
+        let dot_product = parser.sum(&products);
+
+        let base = AP::Field::from_canonical_u32(1 << 16);
+        let mut result = parser.zero();
+        for limb in limbs.iter().rev() {
+            let shifted = parser.mul_const(result, base);
+            result = parser.add(shifted, *limb);
+        }
+
+        parser.assert_eq(dot_product, result);
+    }
+}
+
+impl<F: PrimeField64> Instruction<F> for DotProductInstruction {
+    fn write(&self, writer: &TraceWriter<F>, row_index: usize) {
+        let a = writer
+            .read_vec(&self.a, row_index)
+            .iter()
+            .map(|x| x.as_canonical_u64())
+            .collect::<Vec<_>>();
+        let b = writer
+            .read_vec(&self.b, row_index)
+            .iter()
+            .map(|x| x.as_canonical_u64())
+            .collect::<Vec<_>>();
+
+        let limbs = u16_dot_product_limbs(&a, &b).map(F::from_canonical_u64);
+        writer.write_array(&self.result, limbs, row_index);
+    }
+
+    fn write_to_air(&self, writer: &mut impl AirWriter<Field = F>) {
+        let a = writer
+            .read_vec(&self.a)
+            .iter()
+            .map(|x| x.as_canonical_u64())
+            .collect::<Vec<_>>();
+        let b = writer
+            .read_vec(&self.b)
+            .iter()
+            .map(|x| x.as_canonical_u64())
+            .collect::<Vec<_>>();
+
+        let limbs = u16_dot_product_limbs(&a, &b).map(F::from_canonical_u64);
+        writer.write_array(&self.result, limbs);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::{thread_rng, Rng};
+
+    use super::*;
+    use crate::chip::builder::tests::*;
+
+    #[derive(Debug, Clone, Serialize, Deserialize)]
+    pub struct MatMulTest;
+
+    impl AirParameters for MatMulTest {
+        type Field = GoldilocksField;
+        type CubicParams = GoldilocksCubicParameters;
+        type Instruction = DotProductInstruction;
+
+        const NUM_ARITHMETIC_COLUMNS: usize = 18;
+        const NUM_FREE_COLUMNS: usize = 2;
+        const EXTENDED_COLUMNS: usize = 36;
+    }
+
+    #[test]
+    fn test_quantized_mat_mul() {
+        type F = GoldilocksField;
+        type L = MatMulTest;
+        type SC = PoseidonGoldilocksStarkConfig;
+
+        let inner_dim = 4;
+        let num_columns = 2;
+
+        let mut builder = AirBuilder::<L>::new();
+        let mat_mul = builder.quantized_mat_mul(inner_dim, num_columns);
+
+        let (air, trace_data) = builder.build();
+        let num_rows = 1 << 16;
+        let generator = ArithmeticGenerator::<L>::new(trace_data, num_rows);
+
+        let mut rng = thread_rng();
+        let rhs = (0..num_columns)
+            .map(|_| (0..inner_dim).map(|_| rng.gen::<u16>()).collect::<Vec<_>>())
+            .collect::<Vec<_>>();
+
+        let writer = generator.new_writer();
+        for i in 0..num_rows {
+            let lhs_row = (0..inner_dim).map(|_| rng.gen::<u16>()).collect::<Vec<_>>();
+            writer.write_array(
+                &mat_mul.lhs_row,
+                lhs_row.iter().map(|x| F::from_canonical_u16(*x)),
+                i,
+            );
+            for (column, values) in mat_mul.rhs_columns.iter().zip(rhs.iter()) {
+                writer.write_array(column, values.iter().map(|x| F::from_canonical_u16(*x)), i);
+            }
+            writer.write_row_instructions(&generator.air_data, i);
+
+            for (output, column) in mat_mul.output.iter().zip(rhs.iter()) {
+                let expected = lhs_row
+                    .iter()
+                    .zip(column.iter())
+                    .map(|(x, y)| *x as u64 * *y as u64)
+                    .sum::<u64>();
+                let limbs = writer.read_vec(output, i);
+                let value = limbs
+                    .iter()
+                    .rev()
+                    .fold(0u64, |acc, x| (acc << 16) + x.as_canonical_u64());
+                assert_eq!(value, expected);
+            }
+        }
+
+        let stark = Starky::new(air);
+        let config = SC::standard_fast_config(num_rows);
+
+        // Generate proof and verify as a stark
+        test_starky(&stark, &config, &generator, &[]);
+
+        // Test the recursive proof.
+        test_recursive_starky(stark, config, generator, &[]);
+    }
+}
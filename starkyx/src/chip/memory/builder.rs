@@ -60,7 +60,23 @@ impl<L: AirParameters> AirBuilder<L> {
         last_write: &Time<L::Field>,
     ) {
         let digest = value.compress(self, ptr.raw, last_write, &ptr.challenges);
-        self.output_from_memory_bus(digest)
+        self.output_from_memory_bus(digest, None)
+    }
+
+    /// Frees the memory at location `ptr` with value `value` and write time given by `time`,
+    /// counted with multiplicity given by the value of `multiplicity`.
+    ///
+    /// This allows freeing a trace register in some rows only, such as a value which is the same
+    /// in all rows and is freed from the first one.
+    pub fn free_with_multiplicity<V: MemoryValue>(
+        &mut self,
+        ptr: &Pointer<V>,
+        value: V,
+        last_write: &Time<L::Field>,
+        multiplicity: ElementRegister,
+    ) {
+        let digest = value.compress(self, ptr.raw, last_write, &ptr.challenges);
+        self.output_from_memory_bus(digest, Some(multiplicity))
     }
 
     /// Initializes a slice with initial `values` and write time given by `time`.
@@ -111,11 +127,19 @@ impl<L: AirParameters> AirBuilder<L> {
         }
     }
 
-    fn output_from_memory_bus(&mut self, digest: CubicRegister) {
-        match digest.register() {
-            MemorySlice::Local(_, _) => self.output_from_bus(0, digest),
-            MemorySlice::Public(_, _) => self.buses[0].output_global_value(&digest),
-            MemorySlice::Global(_, _) => self.buses[0].output_global_value(&digest),
+    fn output_from_memory_bus(
+        &mut self,
+        digest: CubicRegister,
+        multiplicity: Option<ElementRegister>,
+    ) {
+        match (digest.register(), multiplicity) {
+            (MemorySlice::Local(_, _), None) => self.output_from_bus(0, digest),
+            (MemorySlice::Public(_, _), None) => self.buses[0].output_global_value(&digest),
+            (MemorySlice::Global(_, _), None) => self.buses[0].output_global_value(&digest),
+            (MemorySlice::Local(_, _), Some(m)) => {
+                self.output_from_bus_with_multiplicity(0, digest, m)
+            }
+            (_, Some(_)) => panic!("Expected local register with a multiplicity"),
             _ => panic!("Expected local, public, or global register"),
         }
     }
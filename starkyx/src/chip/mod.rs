@@ -4,18 +4,26 @@ use serde::{Deserialize, Serialize};
 use self::constraint::Constraint;
 use self::instruction::Instruction;
 use crate::math::prelude::*;
+use crate::plonky2::stark::outputs::OutputLayout;
 use crate::plonky2::stark::Starky;
 
 pub mod air;
 pub mod arithmetic;
+pub mod bloom;
 pub mod bool;
 pub mod builder;
+pub mod cbor;
 pub mod constraint;
+#[cfg(feature = "ecc")]
 pub mod ec;
+#[cfg(feature = "bigint")]
 pub mod field;
+pub mod fold;
 pub mod instruction;
+pub mod matmul;
 pub mod memory;
 pub mod register;
+pub mod sort;
 pub mod table;
 pub mod trace;
 pub mod uint;
@@ -65,10 +73,15 @@ pub struct Chip<L: AirParameters> {
     pub num_challenges: usize,
     pub num_public_values: usize,
     pub num_global_values: usize,
+    /// The named outputs of the chip in the global values.
+    #[serde(default)]
+    pub output_layout: OutputLayout,
 }
 
 impl<L: AirParameters> Starky<Chip<L>> {
+    /// A `Starky` for the chip, with the output layout of the chip.
     pub fn from_chip(chip: Chip<L>) -> Self {
-        Self::new(chip)
+        let output_layout = chip.output_layout.clone();
+        Self::new(chip).with_output_layout(output_layout)
     }
 }
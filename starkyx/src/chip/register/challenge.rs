@@ -0,0 +1,123 @@
+use serde::{Deserialize, Serialize};
+
+use super::memory::MemorySlice;
+use super::Register;
+use crate::chip::arithmetic::expression::ArithmeticExpression;
+use crate::math::prelude::*;
+
+/// A challenge register together with the round in which the challenge is squeezed.
+///
+/// The challenges of round `r` are squeezed after committing to the columns of all the rounds
+/// before `r`, so they can only be used to compute the values of columns committed in round `r`
+/// or later.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ChallengeRegister<T> {
+    challenge: T,
+    round: usize,
+}
+
+impl<T: Register> ChallengeRegister<T> {
+    pub(crate) fn new(challenge: T, round: usize) -> Self {
+        assert!(
+            matches!(challenge.register(), MemorySlice::Challenge(..)),
+            "A challenge register must be allocated in the challenge memory"
+        );
+        Self { challenge, round }
+    }
+
+    /// The underlying challenge register.
+    pub const fn challenge(&self) -> &T {
+        &self.challenge
+    }
+
+    /// The round in which the challenge is squeezed.
+    pub const fn round(&self) -> usize {
+        self.round
+    }
+
+    /// Whether the challenge can be used to compute the columns committed in `round`.
+    pub const fn is_available_in(&self, round: usize) -> bool {
+        self.round <= round
+    }
+
+    pub fn expr<F: Field>(&self) -> ArithmeticExpression<F> {
+        self.challenge.expr()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde::{Deserialize, Serialize};
+
+    use super::*;
+    use crate::chip::builder::tests::*;
+    use crate::chip::builder::AirBuilder;
+    use crate::chip::register::element::ElementRegister;
+    use crate::chip::AirParameters;
+
+    #[derive(Debug, Clone, Serialize, Deserialize)]
+    pub struct ChallengeTest;
+
+    impl AirParameters for ChallengeTest {
+        type Field = GoldilocksField;
+        type CubicParams = GoldilocksCubicParameters;
+        type Instruction = EmptyInstruction<GoldilocksField>;
+
+        const NUM_FREE_COLUMNS: usize = 2;
+        const EXTENDED_COLUMNS: usize = 1;
+    }
+
+    #[test]
+    fn test_challenge_rounds() {
+        type L = ChallengeTest;
+
+        let mut builder = AirBuilder::<L>::new();
+        let a = builder.alloc::<ElementRegister>();
+        let b = builder.alloc::<ElementRegister>();
+        let beta = builder.alloc_challenge_register::<ElementRegister>();
+        assert_eq!(beta.round(), 1);
+        assert!(!beta.is_available_in(0));
+        assert!(beta.is_available_in(1));
+
+        // Extended columns are committed after the challenge is squeezed.
+        let digest = builder.alloc_array_extended::<ElementRegister>(1).get(0);
+        builder.set_to_expression(&digest, beta.expr() * a.expr() + b.expr());
+        builder.build();
+    }
+
+    #[test]
+    #[should_panic(expected = "uses a challenge of round 1")]
+    fn test_challenge_in_execution_trace() {
+        type L = ChallengeTest;
+
+        let mut builder = AirBuilder::<L>::new();
+        let a = builder.alloc::<ElementRegister>();
+        let beta = builder.alloc_challenge_register::<ElementRegister>();
+        let b = builder.alloc::<ElementRegister>();
+
+        // The execution trace is committed before the challenge is squeezed.
+        builder.set_to_expression(&b, beta.expr() * a.expr());
+        builder.build();
+    }
+
+    #[test]
+    fn test_challenge_in_constraint() {
+        type L = ChallengeTest;
+
+        let mut builder = AirBuilder::<L>::new();
+        let a = builder.alloc::<ElementRegister>();
+        let b = builder.alloc::<ElementRegister>();
+        let beta = builder.alloc_challenge_register::<ElementRegister>();
+        let digest = builder.alloc_array_extended::<ElementRegister>(1).get(0);
+
+        // Constraints reading the challenge and an extended column are accepted.
+        let mut accepted = builder.clone();
+        accepted.assert_expressions_equal(beta.expr() * a.expr() + b.expr(), digest.expr());
+        assert!(accepted.try_build().is_ok());
+
+        // Constraints reading the challenge and only execution trace columns are rejected.
+        builder.assert_expressions_equal(beta.expr() * a.expr(), b.expr());
+        let err = builder.try_build().unwrap_err();
+        assert!(err.to_string().contains("uses a challenge of round 1"));
+    }
+}
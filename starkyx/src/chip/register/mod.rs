@@ -12,6 +12,7 @@ use crate::math::prelude::*;
 pub mod array;
 pub mod bit;
 pub mod cell;
+pub mod challenge;
 pub mod cubic;
 pub mod element;
 pub mod memory;
@@ -0,0 +1,181 @@
+//! A gadget proving that a list of trace rows is a sorted permutation of another.
+//!
+//! Each row of the input holds a 16-bit key and a tuple of values. The gadget allocates a copy of
+//! these registers holding the same rows sorted by key, and constrains it in two parts:
+//!
+//! - The sorted rows are a permutation of the input rows. Every row is compressed to a single
+//! extension element using random challenges, and the compressed rows are sent through a bus
+//! taking the input rows as inputs and the sorted rows as outputs.
+//! - Adjacent sorted keys are in non-decreasing order. The difference `key_next - key` is written to
+//! a `U16Register`, so the range check guarantees that it is a small non-negative integer.
+//!
+//! Since the sorted keys are in the range of the input keys, which are 16-bit values, the
+//! differences can not wrap around the field modulus.
+
+use serde::{Deserialize, Serialize};
+
+use super::arithmetic::expression::ArithmeticExpression;
+use super::builder::AirBuilder;
+use super::register::cubic::CubicRegister;
+use super::register::element::ElementRegister;
+use super::register::u16::U16Register;
+use super::register::Register;
+use super::trace::writer::TraceWriter;
+use super::AirParameters;
+use crate::math::prelude::*;
+
+/// The registers of a sorted permutation of trace rows.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SortedRows {
+    /// The key of the input row.
+    pub key: U16Register,
+    /// The values of the input row.
+    pub values: Vec<ElementRegister>,
+    /// The key of the sorted row.
+    pub sorted_key: U16Register,
+    /// The values of the sorted row.
+    pub sorted_values: Vec<ElementRegister>,
+    /// The difference between the next sorted key and the current one.
+    difference: U16Register,
+}
+
+impl<L: AirParameters> AirBuilder<L> {
+    /// Allocates registers for the trace rows `(key, values)` sorted by `key`, and constrains them
+    /// to be a permutation of the input rows in non-decreasing order of keys.
+    ///
+    /// The rows are sorted with respect to the whole trace, so the input registers must be written
+    /// in all rows before calling `SortedRows::write_sorted`.
+    pub fn sort_by_key(&mut self, key: &U16Register, values: &[ElementRegister]) -> SortedRows {
+        let sorted_key = self.alloc::<U16Register>();
+        let sorted_values = values
+            .iter()
+            .map(|_| self.alloc::<ElementRegister>())
+            .collect::<Vec<_>>();
+        let difference = self.alloc::<U16Register>();
+
+        // The sorted keys are in non-decreasing order.
+        self.assert_expressions_equal_transition(
+            sorted_key.next().expr() - sorted_key.expr(),
+            difference.expr(),
+        );
+
+        // The sorted rows are a permutation of the input rows.
+        let challenges = self.alloc_array_challenge::<CubicRegister>(1 + values.len());
+        let row_expressions = |key: &U16Register, values: &[ElementRegister]| {
+            core::iter::once(key.expr())
+                .chain(values.iter().map(|v| v.expr()))
+                .collect::<Vec<ArithmeticExpression<L::Field>>>()
+        };
+        let input_digest = self.accumulate_expressions(&challenges, &row_expressions(key, values));
+        let sorted_digest =
+            self.accumulate_expressions(&challenges, &row_expressions(&sorted_key, &sorted_values));
+
+        let mut bus = self.new_bus();
+        let channel_idx = bus.new_channel(self);
+        self.input_to_bus(channel_idx, input_digest);
+        self.output_from_bus(channel_idx, sorted_digest);
+        self.constrain_bus(bus);
+
+        SortedRows {
+            key: *key,
+            values: values.to_vec(),
+            sorted_key,
+            sorted_values,
+            difference,
+        }
+    }
+}
+
+impl SortedRows {
+    /// Reads the input rows from the trace and writes them sorted by key.
+    ///
+    /// The sort is stable, so rows with equal keys keep their relative order.
+    pub fn write_sorted<F: PrimeField64>(&self, writer: &TraceWriter<F>, num_rows: usize) {
+        let mut rows = (0..num_rows)
+            .map(|i| {
+                let key = writer.read(&self.key, i);
+                let values = self
+                    .values
+                    .iter()
+                    .map(|v| writer.read(v, i))
+                    .collect::<Vec<_>>();
+                (key, values)
+            })
+            .collect::<Vec<_>>();
+        rows.sort_by_key(|(key, _)| key.as_canonical_u64());
+
+        for (i, (key, values)) in rows.iter().enumerate() {
+            writer.write(&self.sorted_key, key, i);
+            for (register, value) in self.sorted_values.iter().zip(values.iter()) {
+                writer.write(register, value, i);
+            }
+            let difference = rows.get(i + 1).map_or(F::ZERO, |(next, _)| *next - *key);
+            writer.write(&self.difference, &difference, i);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::{thread_rng, Rng};
+
+    use super::*;
+    use crate::chip::builder::tests::*;
+
+    #[derive(Debug, Clone, Serialize, Deserialize)]
+    pub struct SortTest;
+
+    impl AirParameters for SortTest {
+        type Field = GoldilocksField;
+        type CubicParams = GoldilocksCubicParameters;
+        type Instruction = EmptyInstruction<GoldilocksField>;
+
+        const NUM_ARITHMETIC_COLUMNS: usize = 3;
+        const NUM_FREE_COLUMNS: usize = 4;
+        const EXTENDED_COLUMNS: usize = 24;
+    }
+
+    #[test]
+    fn test_sort_by_key() {
+        type F = GoldilocksField;
+        type L = SortTest;
+        type SC = PoseidonGoldilocksStarkConfig;
+
+        let mut builder = AirBuilder::<L>::new();
+        let key = builder.alloc::<U16Register>();
+        let value = builder.alloc::<ElementRegister>();
+        let sorted = builder.sort_by_key(&key, &[value]);
+
+        let (air, trace_data) = builder.build();
+        let num_rows = 1 << 16;
+        let generator = ArithmeticGenerator::<L>::new(trace_data, num_rows);
+
+        let mut rng = thread_rng();
+        let writer = generator.new_writer();
+        for i in 0..num_rows {
+            // Use a small range of keys to get many repeated values.
+            let k = rng.gen_range(0..1000u16);
+            writer.write(&key, &F::from_canonical_u16(k), i);
+            writer.write(&value, &F::from_canonical_usize(i), i);
+        }
+        sorted.write_sorted(&writer, num_rows);
+        for i in 0..num_rows {
+            writer.write_row_instructions(&generator.air_data, i);
+        }
+
+        for i in 1..num_rows {
+            let prev = writer.read(&sorted.sorted_key, i - 1).as_canonical_u64();
+            let curr = writer.read(&sorted.sorted_key, i).as_canonical_u64();
+            assert!(prev <= curr);
+        }
+
+        let stark = Starky::new(air);
+        let config = SC::standard_fast_config(num_rows);
+
+        // Generate proof and verify as a stark
+        test_starky(&stark, &config, &generator, &[]);
+
+        // Test the recursive proof.
+        test_recursive_starky(stark, config, generator, &[]);
+    }
+}
@@ -1,3 +1,6 @@
+use core::ops::Range;
+
+use anyhow::{ensure, Result};
 use serde::{Deserialize, Serialize};
 
 use super::writer::{AirWriter, TraceWriter};
@@ -21,6 +24,9 @@ pub struct AirTraceData<L: AirParameters> {
     pub execution_trace_length: usize,
     pub instructions: Vec<AirInstruction<L::Field, L::Instruction>>,
     pub global_instructions: Vec<AirInstruction<L::Field, L::Instruction>>,
+    /// The ranges of `instructions` that are independent of each other, in dependency order.
+    #[serde(default)]
+    pub instruction_levels: Vec<Range<usize>>,
     pub powers: Vec<Powers<L::Field, L::CubicParams>>,
     pub accumulators: Vec<Accumulator<L::Field, L::CubicParams>>,
     pub pointer_row_accumulators: Vec<PointerAccumulator<L::Field, L::CubicParams>>,
@@ -33,9 +39,44 @@ pub struct AirTraceData<L: AirParameters> {
         LookupTable<L::Field, L::CubicParams>,
         LookupValues<L::Field, L::CubicParams>,
     )>,
+    /// The minimal number of rows declared by each gadget of the chip.
+    #[serde(default)]
+    pub row_bounds: Vec<(String, usize)>,
+    /// The column ranges of the registers marked as secret.
+    #[serde(default)]
+    pub secret_columns: Vec<(usize, usize)>,
 }
 
 impl<L: AirParameters> AirTraceData<L> {
+    /// The smallest power of two satisfying the row bounds of all gadgets.
+    pub fn required_num_rows(&self) -> usize {
+        self.row_bounds
+            .iter()
+            .map(|(_, num_rows)| *num_rows)
+            .max()
+            .unwrap_or(1)
+            .next_power_of_two()
+    }
+
+    /// Checks that a trace of `num_rows` rows satisfies the row bounds of all gadgets.
+    pub fn check_num_rows(&self, num_rows: usize) -> Result<()> {
+        ensure!(
+            num_rows.is_power_of_two(),
+            "Number of rows must be a power of two, got {}",
+            num_rows
+        );
+        for (name, bound) in self.row_bounds.iter() {
+            ensure!(
+                num_rows >= *bound,
+                "Gadget {} requires at least {} rows, got {}",
+                name,
+                bound,
+                num_rows
+            );
+        }
+        Ok(())
+    }
+
     #[inline]
     pub fn write_trace_instructions(&self, writer: &mut impl AirWriter<Field = L::Field>) {
         for instruction in self.instructions.iter() {
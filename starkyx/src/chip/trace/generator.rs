@@ -1,4 +1,5 @@
 use alloc::sync::Arc;
+use core::fmt::{self, Debug};
 
 use anyhow::{Error, Result};
 use serde::{Deserialize, Serialize};
@@ -14,7 +15,7 @@ use crate::maybe_rayon::*;
 use crate::trace::generator::TraceGenerator;
 use crate::trace::AirTrace;
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Clone, Serialize, Deserialize)]
 #[serde(bound = "")]
 pub struct ArithmeticGenerator<L: AirParameters> {
     pub writer: TraceWriter<L::Field>,
@@ -42,7 +43,13 @@ impl<L: AirParameters> ArithmeticGenerator<L> {
         *public = public_new;
     }
 
+    /// Creates a generator for a trace of `num_rows` rows.
+    ///
+    /// Panics if `num_rows` does not satisfy the row bounds declared by the gadgets of the chip.
     pub fn new(air_data: AirTraceData<L>, num_rows: usize) -> Self {
+        if let Err(e) = air_data.check_num_rows(num_rows) {
+            panic!("{}", e);
+        }
         let num_public_inputs = air_data.num_public_inputs;
         let num_global_values = air_data.num_global_values;
         Self {
@@ -58,6 +65,12 @@ impl<L: AirParameters> ArithmeticGenerator<L> {
         }
     }
 
+    /// Creates a generator for the shortest trace satisfying the row bounds of the chip.
+    pub fn new_with_required_rows(air_data: AirTraceData<L>) -> Self {
+        let num_rows = air_data.required_num_rows();
+        Self::new(air_data, num_rows)
+    }
+
     #[inline]
     pub fn range_fn(element: L::Field) -> usize {
         element.as_canonical_u64() as usize
@@ -76,6 +89,22 @@ impl<L: AirParameters> ArithmeticGenerator<L> {
     }
 }
 
+impl<L: AirParameters> Debug for ArithmeticGenerator<L> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut debug = f.debug_struct("ArithmeticGenerator");
+        // The trace is omitted if it contains secret registers.
+        if self.air_data.secret_columns.is_empty() {
+            debug.field("writer", &self.writer);
+        } else {
+            debug.field("writer", &"<redacted>");
+        }
+        debug
+            .field("air_data", &self.air_data)
+            .field("num_rows", &self.num_rows)
+            .finish()
+    }
+}
+
 impl<L: AirParameters> TraceGenerator<L::Field, Chip<L>> for ArithmeticGenerator<L> {
     type Error = Error;
 
@@ -126,7 +155,7 @@ impl<L: AirParameters> TraceGenerator<L::Field, Chip<L>> for ArithmeticGenerator
                     );
                 }
 
-                let trace = self.trace_clone();
+                let trace = self.writer.read_trace().unwrap();
                 let execution_trace_values = trace
                     .rows_par()
                     .flat_map(|row| row[..air.execution_trace_length].to_vec())
@@ -145,11 +174,12 @@ impl<L: AirParameters> TraceGenerator<L::Field, Chip<L>> for ArithmeticGenerator
 
                 self.air_data.write_extended_trace(&writer);
 
-                let trace = self.trace_clone();
+                let trace = self.writer.read_trace().unwrap();
                 let extended_trace_values = trace
                     .rows_par()
                     .flat_map(|row| row[air.execution_trace_length..].to_vec())
                     .collect::<Vec<_>>();
+                drop(trace);
 
                 let new_global = self.writer.0.global.read().unwrap();
                 let (id_0, id_1) = (0, air.num_global_values);
@@ -163,4 +193,18 @@ impl<L: AirParameters> TraceGenerator<L::Field, Chip<L>> for ArithmeticGenerator
             _ => unreachable!("Chip air IOP only has two rounds"),
         }
     }
+
+    /// Overwrites the values of the secret registers in the trace with zeros.
+    ///
+    /// A generator of a chip with secret registers can therefore only be used for a single proof.
+    fn zeroize_secrets(&self) {
+        self.writer
+            .write_trace()
+            .unwrap()
+            .zeroize_columns(&self.air_data.secret_columns);
+    }
+
+    fn has_secrets(&self) -> bool {
+        !self.air_data.secret_columns.is_empty()
+    }
 }
@@ -2,5 +2,7 @@
 //!
 
 pub mod data;
+#[cfg(feature = "prover")]
 pub mod generator;
+pub mod schedule;
 pub mod writer;
@@ -0,0 +1,234 @@
+//! Scheduling of instructions according to their data dependencies.
+//!
+//! An instruction depends on the instructions writing the registers it reads, so it must be
+//! written after them. Instructions are grouped into levels, such that every instruction only
+//! depends on instructions of earlier levels. The instructions of a level are independent of each
+//! other and can be written in any order, or in parallel.
+//!
+//! Instructions that do not declare their inputs and outputs keep their position relative to all
+//! other instructions.
+
+use core::ops::Range;
+use std::collections::HashMap;
+
+use log::warn;
+
+use crate::chip::instruction::Instruction;
+use crate::chip::register::memory::MemorySlice;
+use crate::math::prelude::*;
+
+/// A single column of a memory slice, identified by the kind of memory and the column index.
+type Cell = (u8, usize);
+
+fn cells(slice: &MemorySlice) -> impl Iterator<Item = Cell> {
+    let kind = match slice {
+        MemorySlice::Local(..) => 0,
+        MemorySlice::Next(..) => 1,
+        MemorySlice::Public(..) => 2,
+        MemorySlice::Global(..) => 3,
+        MemorySlice::Challenge(..) => 4,
+    };
+    let (start, end) = slice.get_range();
+    (start..end).map(move |i| (kind, i))
+}
+
+/// Groups the indices of `instructions` into levels of independent instructions, listed in the
+/// order in which they must be written.
+///
+/// Returns `None` if the dependencies between the instructions contain a cycle.
+pub fn dependency_levels<F: Field, I: Instruction<F>>(
+    instructions: &[I],
+) -> Option<Vec<Vec<usize>>> {
+    let mut levels = Vec::new();
+    let mut segment_start = 0;
+    for (i, instruction) in instructions.iter().enumerate() {
+        if instruction.inputs().is_none() || instruction.outputs().is_none() {
+            levels.extend(segment_levels(instructions, segment_start..i)?);
+            levels.push(vec![i]);
+            segment_start = i + 1;
+        }
+    }
+    levels.extend(segment_levels(
+        instructions,
+        segment_start..instructions.len(),
+    )?);
+    Some(levels)
+}
+
+/// Reorders `instructions` by dependency levels, returning the ranges of the levels in the
+/// reordered list.
+///
+/// If the dependencies contain a cycle, the instructions are kept in their original order and
+/// every instruction forms its own level.
+pub fn schedule_instructions<F: Field, I: Instruction<F>>(
+    instructions: Vec<I>,
+) -> (Vec<I>, Vec<Range<usize>>) {
+    let Some(levels) = dependency_levels(&instructions) else {
+        warn!("Cyclic instruction dependencies, keeping the registration order");
+        let ranges = (0..instructions.len()).map(|i| i..i + 1).collect();
+        return (instructions, ranges);
+    };
+
+    let mut ranges = Vec::with_capacity(levels.len());
+    let mut order = Vec::with_capacity(instructions.len());
+    for level in levels {
+        ranges.push(order.len()..order.len() + level.len());
+        order.extend(level);
+    }
+
+    let mut instructions = instructions.into_iter().map(Some).collect::<Vec<_>>();
+    let scheduled = order
+        .into_iter()
+        .map(|i| instructions[i].take().unwrap())
+        .collect();
+    (scheduled, ranges)
+}
+
+/// Computes the dependency levels of the instructions in `range`, all of which have known inputs
+/// and outputs.
+fn segment_levels<F: Field, I: Instruction<F>>(
+    instructions: &[I],
+    range: Range<usize>,
+) -> Option<Vec<Vec<usize>>> {
+    let segment = &instructions[range.clone()];
+    let n = segment.len();
+
+    let mut writers = HashMap::<Cell, Vec<usize>>::new();
+    for (i, instruction) in segment.iter().enumerate() {
+        for cell in instruction.outputs().unwrap().iter().flat_map(cells) {
+            let cell_writers = writers.entry(cell).or_default();
+            if cell_writers.last() != Some(&i) {
+                cell_writers.push(i);
+            }
+        }
+    }
+
+    let mut dependents = vec![Vec::new(); n];
+    let mut num_dependencies = vec![0usize; n];
+    let mut add_edge = |from: usize, to: usize| {
+        if from != to {
+            dependents[from].push(to);
+            num_dependencies[to] += 1;
+        }
+    };
+    // Instructions writing the same register keep their relative order.
+    for cell_writers in writers.values() {
+        for pair in cell_writers.windows(2) {
+            add_edge(pair[0], pair[1]);
+        }
+    }
+    // Instructions are written after the instructions writing their inputs.
+    for (i, instruction) in segment.iter().enumerate() {
+        for cell in instruction.inputs().unwrap().iter().flat_map(cells) {
+            for &writer in writers.get(&cell).into_iter().flatten() {
+                add_edge(writer, i);
+            }
+        }
+    }
+
+    let mut levels = Vec::new();
+    let mut current = (0..n)
+        .filter(|i| num_dependencies[*i] == 0)
+        .collect::<Vec<_>>();
+    let mut num_scheduled = 0;
+    while !current.is_empty() {
+        num_scheduled += current.len();
+        let mut next = Vec::new();
+        for &i in current.iter() {
+            for &j in dependents[i].iter() {
+                num_dependencies[j] -= 1;
+                if num_dependencies[j] == 0 {
+                    next.push(j);
+                }
+            }
+        }
+        next.sort_unstable();
+        levels.push(current.iter().map(|i| range.start + i).collect());
+        current = next;
+    }
+
+    (num_scheduled == n).then_some(levels)
+}
+
+#[cfg(test)]
+mod tests {
+    use serde::{Deserialize, Serialize};
+
+    use super::*;
+    use crate::chip::builder::tests::*;
+    use crate::chip::builder::AirBuilder;
+    use crate::chip::register::element::ElementRegister;
+    use crate::chip::register::Register;
+    use crate::chip::AirParameters;
+
+    #[derive(Debug, Clone, Serialize, Deserialize)]
+    pub struct ScheduleTest;
+
+    impl AirParameters for ScheduleTest {
+        type Field = GoldilocksField;
+        type CubicParams = GoldilocksCubicParameters;
+        type Instruction = EmptyInstruction<GoldilocksField>;
+
+        const NUM_FREE_COLUMNS: usize = 4;
+    }
+
+    #[test]
+    fn test_out_of_order_instructions() {
+        type F = GoldilocksField;
+        type L = ScheduleTest;
+        type SC = PoseidonGoldilocksStarkConfig;
+
+        let mut builder = AirBuilder::<L>::new();
+        let a = builder.alloc::<ElementRegister>();
+        let b = builder.alloc::<ElementRegister>();
+        let c = builder.alloc::<ElementRegister>();
+        let d = builder.alloc::<ElementRegister>();
+
+        // The instructions are registered in the reverse order of their dependencies.
+        builder.set_to_expression(&d, c.expr() * b.expr());
+        builder.set_to_expression(&c, b.expr() + a.expr());
+        builder.set_to_expression(&b, a.expr() * F::TWO);
+
+        let (air, trace_data) = builder.build();
+        assert_eq!(trace_data.instruction_levels, vec![0..1, 1..2, 2..3]);
+
+        let num_rows = 1 << 5;
+        let generator = ArithmeticGenerator::<L>::new(trace_data, num_rows);
+        let writer = generator.new_writer();
+        for i in 0..num_rows {
+            writer.write(&a, &F::from_canonical_usize(i), i);
+            writer.write_row_instructions_parallel(&generator.air_data, i);
+            let b_value = F::from_canonical_usize(2 * i);
+            let c_value = F::from_canonical_usize(3 * i);
+            assert_eq!(writer.read(&d, i), b_value * c_value);
+        }
+
+        let stark = Starky::new(air);
+        let config = SC::standard_fast_config(num_rows);
+        test_starky(&stark, &config, &generator, &[]);
+    }
+
+    #[test]
+    fn test_dependency_levels() {
+        type F = GoldilocksField;
+        type L = ScheduleTest;
+
+        let mut builder = AirBuilder::<L>::new();
+        let a = builder.alloc::<ElementRegister>();
+        let b = builder.alloc::<ElementRegister>();
+        let c = builder.alloc::<ElementRegister>();
+        let d = builder.alloc::<ElementRegister>();
+
+        builder.set_to_expression(&c, a.expr() + b.expr());
+        builder.set_to_expression(&a, F::ONE.into());
+        builder.set_to_expression(&b, F::TWO.into());
+        builder.set_to_expression(&d, c.expr());
+
+        let levels = dependency_levels(&builder.instructions).unwrap();
+        assert_eq!(levels, vec![vec![1, 2], vec![0], vec![3]]);
+
+        // A cycle between two instructions.
+        builder.set_to_expression(&a, d.expr());
+        assert!(dependency_levels(&builder.instructions).is_none());
+    }
+}
@@ -8,6 +8,7 @@ use anyhow::{anyhow, Result};
 use serde::{Deserialize, Serialize};
 
 use super::data::AirTraceData;
+use crate::air::RAir;
 use crate::chip::arithmetic::expression::ArithmeticExpression;
 use crate::chip::instruction::Instruction;
 use crate::chip::memory::map::MemoryMap;
@@ -18,6 +19,8 @@ use crate::chip::register::{Register, RegisterSerializable};
 use crate::chip::table::log_derivative::entry::{LogEntry, LogEntryValue};
 use crate::chip::AirParameters;
 use crate::math::prelude::*;
+use crate::maybe_rayon::*;
+use crate::trace::spot_check::{ConstraintFailure, SpotCheckParser};
 use crate::trace::window::TraceWindow;
 use crate::trace::window_parser::TraceWindowParser;
 use crate::trace::AirTrace;
@@ -318,6 +321,24 @@ impl<F: Field> TraceWriter<F> {
         expression.eval(&mut parser)
     }
 
+    /// Evaluates the constraints of `air` on the given `rows` of the written trace.
+    ///
+    /// See `AirTrace::check_rows`.
+    pub fn check_rows<A>(
+        &self,
+        air: &A,
+        rows: impl IntoIterator<Item = usize>,
+    ) -> core::result::Result<(), Vec<ConstraintFailure<F>>>
+    where
+        A: for<'a> RAir<SpotCheckParser<'a, F>>,
+    {
+        let trace = self.0.trace.read().unwrap();
+        let global_values = self.global.read().unwrap();
+        let challenges = self.0.challenges.read().unwrap();
+        let public_inputs = self.0.public.read().unwrap();
+        trace.check_rows(air, rows, &challenges, &global_values, &public_inputs)
+    }
+
     /// Evaluates the log derivative entry `LogEntry` at the given row index.
     #[inline]
     pub fn read_log_entry<T: EvalCubic>(
@@ -433,6 +454,23 @@ impl<F: Field> TraceWriter<F> {
         }
     }
 
+    /// Writes the instructions of a row, writing the instructions of each dependency level in
+    /// parallel.
+    pub fn write_row_instructions_parallel<L: AirParameters<Field = F>>(
+        &self,
+        air_data: &AirTraceData<L>,
+        row_index: usize,
+    ) {
+        if air_data.instruction_levels.is_empty() {
+            return self.write_row_instructions(air_data, row_index);
+        }
+        for level in air_data.instruction_levels.iter() {
+            air_data.instructions[level.clone()]
+                .par_iter()
+                .for_each(|instruction| self.write_instruction(instruction, row_index));
+        }
+    }
+
     #[inline]
     pub fn write_global_instructions<L: AirParameters<Field = F>>(
         &self,
@@ -166,6 +166,7 @@ mod tests {
     use super::*;
     pub use crate::chip::builder::tests::*;
     use crate::chip::register::Register;
+    use crate::chip::uint::bytes::operations::sbox::AES_SBOX;
     use crate::chip::uint::bytes::operations::value::ByteOperation;
     use crate::chip::uint::bytes::register::ByteRegister;
 
@@ -178,8 +179,8 @@ mod tests {
 
         type Instruction = ByteInstructionSet;
 
-        const NUM_FREE_COLUMNS: usize = 195;
-        const EXTENDED_COLUMNS: usize = 453;
+        const NUM_FREE_COLUMNS: usize = 217;
+        const EXTENDED_COLUMNS: usize = 504;
         const NUM_ARITHMETIC_COLUMNS: usize = 0;
     }
 
@@ -201,6 +202,7 @@ mod tests {
         let mut and_expected_vec = Vec::new();
         let mut xor_expected_vec = Vec::new();
         let mut not_expected_vec = Vec::new();
+        let mut sbox_expected_vec = Vec::new();
         let mut shr_expected_vec = Vec::new();
         let mut shr_const_expected_vec = Vec::new();
         let mut rot_expected_vec = Vec::new();
@@ -237,6 +239,13 @@ mod tests {
             builder.assert_equal(&a_not, &not_expected);
             not_expected_vec.push(not_expected);
 
+            let a_sbox = builder.alloc::<ByteRegister>();
+            let sbox = ByteOperation::Sbox(a, a_sbox);
+            builder.set_byte_operation(&sbox, &mut operations);
+            let sbox_expected = builder.alloc::<ByteRegister>();
+            builder.assert_equal(&a_sbox, &sbox_expected);
+            sbox_expected_vec.push(sbox_expected);
+
             let a_shr_b = builder.alloc::<ByteRegister>();
             let shr = ByteOperation::Shr(a, b, a_shr_b);
             builder.set_byte_operation(&shr, &mut operations);
@@ -290,6 +299,9 @@ mod tests {
         let b_not = builder.alloc_public::<ByteRegister>();
         let not = ByteOperation::Not(b_pub, b_not);
         builder.set_public_inputs_byte_operation(&not, &mut operations);
+        let a_sbox = builder.alloc_public::<ByteRegister>();
+        let sbox = ByteOperation::Sbox(a_pub, a_sbox);
+        builder.set_public_inputs_byte_operation(&sbox, &mut operations);
 
         let byte_mult_data = builder.register_byte_lookup(&mut byte_table, operations);
         builder.constraint_byte_lookup_table(&byte_table);
@@ -319,6 +331,10 @@ mod tests {
         );
         a_not.assign_to_raw_slice(&mut public_write, &F::from_canonical_u8(!a_pub_val));
         b_not.assign_to_raw_slice(&mut public_write, &F::from_canonical_u8(!b_pub_val));
+        a_sbox.assign_to_raw_slice(
+            &mut public_write,
+            &F::from_canonical_u8(AES_SBOX[a_pub_val as usize]),
+        );
         drop(public_write);
 
         byte_table.write_table_entries(&writer);
@@ -333,6 +349,11 @@ mod tests {
                 writer.write(&and_expected_vec[k], &F::from_canonical_u8(a_v & b_v), i);
                 writer.write(&xor_expected_vec[k], &F::from_canonical_u8(a_v ^ b_v), i);
                 writer.write(&not_expected_vec[k], &F::from_canonical_u8(!a_v), i);
+                writer.write(
+                    &sbox_expected_vec[k],
+                    &F::from_canonical_u8(AES_SBOX[a_v as usize]),
+                    i,
+                );
                 writer.write(
                     &shr_expected_vec[k],
                     &F::from_canonical_u8(a_v >> (b_v & 0x7)),
@@ -8,14 +8,17 @@ use crate::chip::register::element::ElementRegister;
 use crate::chip::trace::writer::TraceWriter;
 use crate::chip::uint::bytes::operations::value::ByteOperation;
 use crate::chip::uint::bytes::operations::{
-    NUM_BIT_OPPS, OPCODE_AND, OPCODE_INDICES, OPCODE_NOT, OPCODE_RANGE, OPCODE_ROT, OPCODE_SHR,
-    OPCODE_SHR_CARRY, OPCODE_XOR,
+    NUM_BIT_OPPS, OPCODE_AND, OPCODE_INDICES, OPCODE_NOT, OPCODE_RANGE, OPCODE_ROT, OPCODE_SBOX,
+    OPCODE_SHR, OPCODE_SHR_CARRY, OPCODE_XOR,
 };
 use crate::chip::uint::bytes::register::ByteRegister;
 use crate::math::prelude::*;
 use crate::maybe_rayon::*;
 use crate::trace::AirTrace;
 
+/// The number of entries of the byte lookup table, one for each pair of bytes.
+pub const NUM_TABLE_ENTRIES: usize = 1 << 16;
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct MultiplicityData {
     pub multiplicities: ArrayRegister<ElementRegister>,
@@ -31,10 +34,24 @@ pub struct ByteMultiplicityData {
 }
 
 impl MultiplicityData {
+    /// The multiplicity data of a table whose segments are given by the multiplicity columns,
+    /// `NUM_BIT_OPPS + 1` for each segment.
+    ///
+    /// The entries are keyed by their index in the table, where the entry at `row` of the
+    /// segment `j` has index `j * num_rows + row`.
     pub fn new(multiplicities: ArrayRegister<ElementRegister>) -> Self {
+        assert_eq!(
+            multiplicities.len() % (NUM_BIT_OPPS + 1),
+            0,
+            "Invalid number of multiplicity columns"
+        );
+        let num_segments = multiplicities.len() / (NUM_BIT_OPPS + 1);
+        let num_rows = NUM_TABLE_ENTRIES / num_segments;
         let mut operations_multipcitiy_dict = HashMap::new();
         let mut operations_dict = HashMap::new();
-        for (row_index, (a, b)) in (0..=u8::MAX).cartesian_product(0..=u8::MAX).enumerate() {
+        for (entry_index, (a, b)) in (0..=u8::MAX).cartesian_product(0..=u8::MAX).enumerate() {
+            let row_index = entry_index % num_rows;
+            let col_offset = (entry_index / num_rows) * (NUM_BIT_OPPS + 1);
             let mut operations = Vec::with_capacity(NUM_BIT_OPPS + 1);
             for (op_index, opcode) in OPCODE_INDICES.into_iter().enumerate() {
                 let operation = match opcode {
@@ -44,13 +61,14 @@ impl MultiplicityData {
                     OPCODE_SHR_CARRY => ByteOperation::shr_full(a, b),
                     OPCODE_ROT => ByteOperation::rot(a, b),
                     OPCODE_NOT => ByteOperation::not(a),
+                    OPCODE_SBOX => ByteOperation::sbox(a),
                     OPCODE_RANGE => ByteOperation::range(a),
                     _ => unreachable!("Invalid opcode: {}", opcode),
                 };
-                operations_multipcitiy_dict.insert(operation, (row_index, op_index));
+                operations_multipcitiy_dict.insert(operation, (row_index, col_offset + op_index));
                 operations.push(operation);
             }
-            operations_dict.insert(row_index, operations);
+            operations_dict.insert(entry_index, operations);
         }
         Self {
             multiplicities,
@@ -67,6 +85,15 @@ impl MultiplicityData {
     pub fn multiplicities(&self) -> ArrayRegister<ElementRegister> {
         self.multiplicities
     }
+
+    pub fn num_segments(&self) -> usize {
+        self.multiplicities.len() / (NUM_BIT_OPPS + 1)
+    }
+
+    /// The number of rows of each segment of the table.
+    pub fn num_rows(&self) -> usize {
+        NUM_TABLE_ENTRIES / self.num_segments()
+    }
 }
 
 impl ByteMultiplicityData {
@@ -83,7 +110,9 @@ impl ByteMultiplicityData {
     }
 
     pub fn get_multiplicities<F: PrimeField64>(&self, writer: &TraceWriter<F>) -> AirTrace<F> {
-        let mut multiplicities_trace = AirTrace::new_with_value(NUM_BIT_OPPS + 1, 1 << 16, 0u32);
+        let width = self.data.multiplicities.len();
+        let height = self.data.num_rows();
+        let mut multiplicities_trace = AirTrace::new_with_value(width, height, 0u32);
 
         // Count the multiplicities in the trace
         let num_rows = writer.height;
@@ -91,8 +120,8 @@ impl ByteMultiplicityData {
             for op in self.trace_operations.iter() {
                 let op_value = op.read_from_writer(writer, i);
                 let (row_index, col_index) = self.data.operations_multipcitiy_dict[&op_value];
-                assert!(col_index < NUM_BIT_OPPS + 1);
-                assert!(row_index < 1 << 16);
+                assert!(col_index < width);
+                assert!(row_index < height);
                 multiplicities_trace.row_mut(row_index)[col_index] += 1;
             }
         }
@@ -102,8 +131,8 @@ impl ByteMultiplicityData {
         for op in self.public_operations.iter() {
             let op_value = op.read_from_slice(&public_slice);
             let (row_index, col_index) = self.data.operations_multipcitiy_dict[&op_value];
-            assert!(col_index < NUM_BIT_OPPS + 1);
-            assert!(row_index < 1 << 16);
+            assert!(col_index < width);
+            assert!(row_index < height);
             multiplicities_trace.row_mut(row_index)[col_index] += 1;
         }
 
@@ -113,7 +142,7 @@ impl ByteMultiplicityData {
                 .into_par_iter()
                 .map(F::from_canonical_u32)
                 .collect(),
-            NUM_BIT_OPPS + 1,
+            width,
         )
     }
 
@@ -1,7 +1,7 @@
 use serde::{Deserialize, Serialize};
 
 use super::super::operations::NUM_BIT_OPPS;
-use super::multiplicity_data::MultiplicityData;
+use super::multiplicity_data::{MultiplicityData, NUM_TABLE_ENTRIES};
 use super::ByteInstructionSet;
 use crate::chip::builder::AirBuilder;
 use crate::chip::register::array::ArrayRegister;
@@ -13,17 +13,20 @@ use crate::chip::trace::writer::TraceWriter;
 use crate::chip::uint::bytes::decode::ByteDecodeInstruction;
 use crate::chip::uint::bytes::operations::value::ByteOperation;
 use crate::chip::uint::bytes::operations::{
-    OPCODE_AND, OPCODE_INDICES, OPCODE_NOT, OPCODE_RANGE, OPCODE_ROT, OPCODE_SHR, OPCODE_SHR_CARRY,
-    OPCODE_XOR,
+    OPCODE_AND, OPCODE_INDICES, OPCODE_NOT, OPCODE_RANGE, OPCODE_ROT, OPCODE_SBOX, OPCODE_SHR,
+    OPCODE_SHR_CARRY, OPCODE_XOR,
 };
 use crate::chip::uint::bytes::register::ByteRegister;
 use crate::chip::AirParameters;
 use crate::math::prelude::*;
 use crate::maybe_rayon::*;
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct ByteLogLookupTable<F, E> {
-    pub challenges: ArrayRegister<CubicRegister>,
+/// The columns of one segment of the byte lookup table.
+///
+/// Each row of a segment holds an entry `(a, b)` together with the results of all the byte
+/// operations on it.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct ByteTableSegment {
     pub a: ByteRegister,
     pub b: ByteRegister,
     pub a_and_b: ByteRegister,
@@ -32,6 +35,17 @@ pub struct ByteLogLookupTable<F, E> {
     pub a_shr_carry_b: ByteRegister,
     pub a_rot_b: ByteRegister,
     pub a_not: ByteRegister,
+    pub a_sbox: ByteRegister,
+}
+
+/// A lookup table of all the byte operations.
+///
+/// The `2^16` entries of the table are split among `segments`, which are placed side by side in
+/// the trace, so that a table of `k` segments takes `2^16 / k` rows.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ByteLogLookupTable<F, E> {
+    pub challenges: ArrayRegister<CubicRegister>,
+    pub segments: Vec<ByteTableSegment>,
     pub multiplicity_data: MultiplicityData,
     pub digests: Vec<CubicRegister>,
     pub lookup: LogLookupTable<CubicRegister, F, E>,
@@ -42,36 +56,75 @@ impl<L: AirParameters> AirBuilder<L> {
     where
         L::Instruction: From<ByteInstructionSet> + From<ByteDecodeInstruction>,
     {
-        let multiplicities = self.alloc_array::<ElementRegister>(NUM_BIT_OPPS + 1);
+        self.new_segmented_byte_lookup_table(1)
+    }
 
-        let a = self.alloc::<ByteRegister>();
-        let b = self.alloc::<ByteRegister>();
+    /// A byte lookup table whose entries are split into `num_segments` segments of
+    /// `2^16 / num_segments` rows each.
+    pub fn new_segmented_byte_lookup_table(
+        &mut self,
+        num_segments: usize,
+    ) -> ByteLogLookupTable<L::Field, L::CubicParams>
+    where
+        L::Instruction: From<ByteInstructionSet> + From<ByteDecodeInstruction>,
+    {
+        assert!(
+            num_segments.is_power_of_two() && num_segments <= NUM_TABLE_ENTRIES,
+            "Invalid number of byte table segments: {}",
+            num_segments
+        );
+        self.require_rows("byte lookup table", NUM_TABLE_ENTRIES / num_segments);
+        let multiplicities = self.alloc_array::<ElementRegister>((NUM_BIT_OPPS + 1) * num_segments);
 
-        let a_and_b = self.alloc::<ByteRegister>();
-        let a_xor_b = self.alloc::<ByteRegister>();
-        let a_shr_b = self.alloc::<ByteRegister>();
-        let a_shr_carry_b = self.alloc::<ByteRegister>();
-        let a_rot_b = self.alloc::<ByteRegister>();
-        let a_not = self.alloc::<ByteRegister>();
+        let segments = (0..num_segments)
+            .map(|_| ByteTableSegment {
+                a: self.alloc::<ByteRegister>(),
+                b: self.alloc::<ByteRegister>(),
+                a_and_b: self.alloc::<ByteRegister>(),
+                a_xor_b: self.alloc::<ByteRegister>(),
+                a_shr_b: self.alloc::<ByteRegister>(),
+                a_shr_carry_b: self.alloc::<ByteRegister>(),
+                a_rot_b: self.alloc::<ByteRegister>(),
+                a_not: self.alloc::<ByteRegister>(),
+                a_sbox: self.alloc::<ByteRegister>(),
+            })
+            .collect::<Vec<_>>();
 
         let multiplicity_data = MultiplicityData::new(multiplicities);
 
         // Accumulate entries for the lookup table
         let challenges = self.challenge_powers(5);
 
-        let digests = OPCODE_INDICES
-            .into_iter()
-            .map(|op| {
-                let operation = match op {
+        let operations = segments
+            .iter()
+            .flat_map(|segment| {
+                let ByteTableSegment {
+                    a,
+                    b,
+                    a_and_b,
+                    a_xor_b,
+                    a_shr_b,
+                    a_shr_carry_b,
+                    a_rot_b,
+                    a_not,
+                    a_sbox,
+                } = *segment;
+                OPCODE_INDICES.into_iter().map(move |op| match op {
                     OPCODE_AND => ByteOperation::And(a, b, a_and_b),
                     OPCODE_XOR => ByteOperation::Xor(a, b, a_xor_b),
                     OPCODE_SHR => ByteOperation::Shr(a, b, a_shr_b),
                     OPCODE_SHR_CARRY => ByteOperation::ShrFull(a, b, a_shr_b, a_shr_carry_b),
                     OPCODE_ROT => ByteOperation::Rot(a, b, a_rot_b),
                     OPCODE_NOT => ByteOperation::Not(a, a_not),
+                    OPCODE_SBOX => ByteOperation::Sbox(a, a_sbox),
                     OPCODE_RANGE => ByteOperation::Range(a),
                     _ => unreachable!("Invalid opcode: {}", op),
-                };
+                })
+            })
+            .collect::<Vec<_>>();
+        let digests = operations
+            .into_iter()
+            .map(|operation| {
                 let values = operation.expressions();
                 self.accumulate_expressions(&challenges, &values)
             })
@@ -81,14 +134,7 @@ impl<L: AirParameters> AirBuilder<L> {
 
         ByteLogLookupTable {
             challenges,
-            a,
-            b,
-            a_and_b,
-            a_xor_b,
-            a_shr_b,
-            a_shr_carry_b,
-            a_rot_b,
-            a_not,
+            segments,
             multiplicity_data,
             digests,
             lookup,
@@ -100,47 +146,64 @@ impl<F: PrimeField64, E: CubicParameters<F>> ByteLogLookupTable<F, E> {
     pub fn multiplicities(&self) -> ArrayRegister<ElementRegister> {
         self.multiplicity_data.multiplicities
     }
+    pub fn num_segments(&self) -> usize {
+        self.segments.len()
+    }
+
+    /// The number of rows of the table.
+    pub fn num_rows(&self) -> usize {
+        self.multiplicity_data.num_rows()
+    }
+
     pub fn write_table_entries(&self, writer: &TraceWriter<F>) {
         let operations_dict = &self.multiplicity_data.operations_dict;
+        let num_rows = self.num_rows();
         // Write the lookup table entries
         writer
             .write_trace()
             .unwrap()
             .rows_par_mut()
+            .take(num_rows)
             .enumerate()
             .for_each(|(i, row)| {
-                for operation in operations_dict[&i].iter() {
-                    let as_field = |&x| F::from_canonical_u8(x);
-                    match operation {
-                        ByteOperation::And(a, b, c) => {
-                            // Write field values
-                            self.a.assign_to_raw_slice(row, &as_field(a));
-                            self.b.assign_to_raw_slice(row, &as_field(b));
-                            self.a_and_b.assign_to_raw_slice(row, &as_field(c));
-                        }
-                        ByteOperation::Xor(_, _, c) => {
-                            // Write field values
-                            self.a_xor_b.assign_to_raw_slice(row, &as_field(c));
-                        }
-                        ByteOperation::Not(_, c) => {
-                            // Write field values
-                            self.a_not.assign_to_raw_slice(row, &as_field(c));
-                        }
-                        ByteOperation::Shr(_, _, c) => {
-                            // Write field value
-                            self.a_shr_b.assign_to_raw_slice(row, &as_field(c));
-                        }
-                        ByteOperation::ShrFull(_, _, r, c) => {
-                            // Write field value
-                            self.a_shr_b.assign_to_raw_slice(row, &as_field(r));
-                            self.a_shr_carry_b.assign_to_raw_slice(row, &as_field(c));
-                        }
-                        ByteOperation::Rot(_, _, c) => {
-                            // Write field value
-                            self.a_rot_b.assign_to_raw_slice(row, &as_field(c));
+                for (j, segment) in self.segments.iter().enumerate() {
+                    for operation in operations_dict[&(j * num_rows + i)].iter() {
+                        let as_field = |&x| F::from_canonical_u8(x);
+                        match operation {
+                            ByteOperation::And(a, b, c) => {
+                                // Write field values
+                                segment.a.assign_to_raw_slice(row, &as_field(a));
+                                segment.b.assign_to_raw_slice(row, &as_field(b));
+                                segment.a_and_b.assign_to_raw_slice(row, &as_field(c));
+                            }
+                            ByteOperation::Xor(_, _, c) => {
+                                // Write field values
+                                segment.a_xor_b.assign_to_raw_slice(row, &as_field(c));
+                            }
+                            ByteOperation::Not(_, c) => {
+                                // Write field values
+                                segment.a_not.assign_to_raw_slice(row, &as_field(c));
+                            }
+                            ByteOperation::Sbox(_, c) => {
+                                // Write field values
+                                segment.a_sbox.assign_to_raw_slice(row, &as_field(c));
+                            }
+                            ByteOperation::Shr(_, _, c) => {
+                                // Write field value
+                                segment.a_shr_b.assign_to_raw_slice(row, &as_field(c));
+                            }
+                            ByteOperation::ShrFull(_, _, r, c) => {
+                                // Write field value
+                                segment.a_shr_b.assign_to_raw_slice(row, &as_field(r));
+                                segment.a_shr_carry_b.assign_to_raw_slice(row, &as_field(c));
+                            }
+                            ByteOperation::Rot(_, _, c) => {
+                                // Write field value
+                                segment.a_rot_b.assign_to_raw_slice(row, &as_field(c));
+                            }
+                            ByteOperation::Range(_) => {}
+                            _ => unreachable!("const parameter operations are not supported"),
                         }
-                        ByteOperation::Range(_) => {}
-                        _ => unreachable!("const parameter operations are not supported"),
                     }
                 }
             });
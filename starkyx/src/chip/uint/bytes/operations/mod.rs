@@ -8,6 +8,7 @@ use crate::chip::builder::AirBuilder;
 use crate::chip::AirParameters;
 
 pub mod instruction;
+pub mod sbox;
 pub mod value;
 
 pub const OPCODE_AND: u8 = 101;
@@ -17,8 +18,9 @@ pub const OPCODE_ROT: u8 = 104;
 pub const OPCODE_NOT: u8 = 105;
 pub const OPCODE_RANGE: u8 = 106;
 pub const OPCODE_SHR_CARRY: u8 = 107;
+pub const OPCODE_SBOX: u8 = 108;
 
-pub const NUM_BIT_OPPS: usize = 6;
+pub const NUM_BIT_OPPS: usize = 7;
 
 pub const OPCODE_INDICES: [u8; NUM_BIT_OPPS + 1] = [
     OPCODE_AND,
@@ -28,6 +30,7 @@ pub const OPCODE_INDICES: [u8; NUM_BIT_OPPS + 1] = [
     OPCODE_ROT,
     OPCODE_NOT,
     OPCODE_RANGE,
+    OPCODE_SBOX,
 ];
 
 impl<L: AirParameters> AirBuilder<L> {
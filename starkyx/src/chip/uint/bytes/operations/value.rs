@@ -1,7 +1,9 @@
 use serde::{Deserialize, Serialize};
 
+use super::sbox::AES_SBOX;
 use super::{
-    OPCODE_AND, OPCODE_NOT, OPCODE_RANGE, OPCODE_ROT, OPCODE_SHR, OPCODE_SHR_CARRY, OPCODE_XOR,
+    OPCODE_AND, OPCODE_NOT, OPCODE_RANGE, OPCODE_ROT, OPCODE_SBOX, OPCODE_SHR, OPCODE_SHR_CARRY,
+    OPCODE_XOR,
 };
 use crate::air::parser::AirParser;
 use crate::air::AirConstraint;
@@ -27,6 +29,7 @@ pub enum ByteOperation<T> {
     RotConst(T, u8, T),
     Rot(T, T, T),
     Not(T, T),
+    Sbox(T, T),
     Range(T),
 }
 
@@ -113,6 +116,13 @@ impl ByteOperation<ByteRegister> {
                 res.expr(),
                 F::ZERO.into(),
             ],
+            ByteOperation::Sbox(a, res) => [
+                opcode.into(),
+                a.expr(),
+                F::ZERO.into(),
+                res.expr(),
+                F::ZERO.into(),
+            ],
             ByteOperation::Range(a) => [
                 opcode.into(),
                 a.expr(),
@@ -294,6 +304,12 @@ impl ByteOperation<ByteRegister> {
                 writer.write(b, &as_field(b_val), row_index);
                 ByteOperation::Not(a_val, b_val)
             }
+            ByteOperation::Sbox(a, b) => {
+                let a_val = from_field(writer.read(a, row_index));
+                let b_val = AES_SBOX[a_val as usize];
+                writer.write(b, &as_field(b_val), row_index);
+                ByteOperation::Sbox(a_val, b_val)
+            }
             ByteOperation::Range(a) => {
                 let a_val = from_field(writer.read(a, row_index));
                 ByteOperation::Range(a_val)
@@ -394,6 +410,12 @@ impl ByteOperation<ByteRegister> {
                 writer.write(b, &as_field(b_val));
                 ByteOperation::Not(a_val, b_val)
             }
+            ByteOperation::Sbox(a, b) => {
+                let a_val = from_field(writer.read(a));
+                let b_val = AES_SBOX[a_val as usize];
+                writer.write(b, &as_field(b_val));
+                ByteOperation::Sbox(a_val, b_val)
+            }
             ByteOperation::Range(a) => {
                 let a_val = from_field(writer.read(a));
                 ByteOperation::Range(a_val)
@@ -460,6 +482,11 @@ impl ByteOperation<ByteRegister> {
                 let b_val = from_field(writer.read(b, row_index));
                 ByteOperation::Not(a_val, b_val)
             }
+            ByteOperation::Sbox(a, b) => {
+                let a_val = from_field(writer.read(a, row_index));
+                let b_val = from_field(writer.read(b, row_index));
+                ByteOperation::Sbox(a_val, b_val)
+            }
             ByteOperation::Range(a) => {
                 let a_val = from_field(writer.read(a, row_index));
                 ByteOperation::Range(a_val)
@@ -522,6 +549,11 @@ impl ByteOperation<ByteRegister> {
                 let b_val = from_field(b.read_from_slice(slice));
                 ByteOperation::Not(a_val, b_val)
             }
+            ByteOperation::Sbox(a, b) => {
+                let a_val = from_field(a.read_from_slice(slice));
+                let b_val = from_field(b.read_from_slice(slice));
+                ByteOperation::Sbox(a_val, b_val)
+            }
             ByteOperation::Range(a) => {
                 let a_val = from_field(a.read_from_slice(slice));
                 ByteOperation::Range(a_val)
@@ -542,6 +574,7 @@ impl<T> ByteOperation<T> {
             ByteOperation::Rot(_, _, _) => OPCODE_ROT,
             ByteOperation::RotConst(_, _, _) => OPCODE_ROT,
             ByteOperation::Not(_, _) => OPCODE_NOT,
+            ByteOperation::Sbox(_, _) => OPCODE_SBOX,
             ByteOperation::Range(_) => OPCODE_RANGE,
         }
     }
@@ -586,6 +619,10 @@ impl ByteOperation<u8> {
         ByteOperation::Not(a, !a)
     }
 
+    pub fn sbox(a: u8) -> Self {
+        ByteOperation::Sbox(a, AES_SBOX[a as usize])
+    }
+
     pub fn range(a: u8) -> Self {
         ByteOperation::Range(a)
     }
@@ -604,6 +641,7 @@ impl ByteOperation<u8> {
             ByteOperation::Rot(a, b, result) => u32::from_le_bytes([opcode, *a, *b, *result]),
             ByteOperation::RotConst(a, b, c) => u32::from_le_bytes([opcode, *a, *b, *c]),
             ByteOperation::Not(a, b) => u32::from_le_bytes([opcode, *a, *b, 0]),
+            ByteOperation::Sbox(a, b) => u32::from_le_bytes([opcode, *a, *b, 0]),
             ByteOperation::Range(a) => u32::from_le_bytes([opcode, *a, 0, 0]),
             _ => unimplemented!(),
         }
@@ -637,6 +675,7 @@ impl ByteOperation<u8> {
                 ByteOperation::RotConst(as_field(a), *b, as_field(c))
             }
             ByteOperation::Not(a, b) => ByteOperation::Not(as_field(a), as_field(b)),
+            ByteOperation::Sbox(a, b) => ByteOperation::Sbox(as_field(a), as_field(b)),
             ByteOperation::Range(a) => ByteOperation::Range(as_field(a)),
         }
     }
@@ -720,6 +759,11 @@ impl<L: AirParameters> AirBuilder<L> {
                 let result = self.alloc_public::<ByteRegister>();
                 ByteOperation::Not(a, result)
             }
+            ByteOperation::Sbox(_, _) => {
+                let a = self.alloc_public::<ByteRegister>();
+                let result = self.alloc_public::<ByteRegister>();
+                ByteOperation::Sbox(a, result)
+            }
             ByteOperation::Range(_) => {
                 let a = self.alloc_public::<ByteRegister>();
                 ByteOperation::Range(a)
@@ -2,3 +2,4 @@ pub mod bytes;
 pub mod operations;
 pub mod register;
 pub mod util;
+pub mod wide;
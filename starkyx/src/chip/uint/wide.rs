@@ -0,0 +1,454 @@
+//! 128-bit arithmetic on pairs of 64-bit words.
+//!
+//! A Goldilocks element can not hold a 64-bit word, so a 128-bit value is represented by its two
+//! 64-bit words `(lo, hi)`, each as four little-endian 16-bit limbs. The eight limbs are allocated
+//! as `U16Register`s and are range checked by the builder.
+//!
+//! The constraints propagate carries between 32-bit chunks of the limbs, so that every equation
+//! holds over the integers without wrapping around the field modulus:
+//!
+//! - `U128Add` computes `a + b = result + carry * 2^128` with a carry bit for every chunk.
+//! - `U64WideningMul` computes the full 128-bit product of two 64-bit words. The carries of the
+//! schoolbook columns are less than `2^19` and are split into two 16-bit limbs each.
+//!
+//! The functions `u128_add_limbs` and `u64_widening_mul_limbs` compute the same values on the
+//! host, and are used by the instructions to write the trace.
+
+use serde::{Deserialize, Serialize};
+
+use crate::air::parser::AirParser;
+use crate::air::AirConstraint;
+use crate::chip::builder::AirBuilder;
+use crate::chip::instruction::Instruction;
+use crate::chip::register::array::ArrayRegister;
+use crate::chip::register::bit::BitRegister;
+use crate::chip::register::u16::U16Register;
+use crate::chip::register::Register;
+use crate::chip::trace::writer::{AirWriter, TraceWriter};
+use crate::chip::AirParameters;
+use crate::math::prelude::*;
+
+/// The number of 16-bit limbs of a 64-bit word.
+pub const U64_LIMBS: usize = 4;
+
+/// The number of 16-bit limbs of a 128-bit value.
+pub const U128_LIMBS: usize = 8;
+
+/// The number of 32-bit chunks of a 128-bit value, each with its own carry.
+const U128_CHUNKS: usize = U128_LIMBS / 2;
+
+/// The number of 16-bit limbs of the carries of a widening multiplication.
+///
+/// The product is less than `2^128`, so there is no carry out of the last chunk, and each of the
+/// other carries takes two limbs.
+pub const WIDENING_MUL_CARRY_LIMBS: usize = 2 * (U128_CHUNKS - 1);
+
+/// An instruction computing the sum of two 128-bit values, with a carry out.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct U128Add {
+    a: ArrayRegister<U16Register>,
+    b: ArrayRegister<U16Register>,
+    pub result: ArrayRegister<U16Register>,
+    /// The carries out of the 32-bit chunks, the last of which is the carry out of the sum.
+    carries: ArrayRegister<BitRegister>,
+}
+
+/// An instruction computing the 128-bit product of two 64-bit words.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct U64WideningMul {
+    a: ArrayRegister<U16Register>,
+    b: ArrayRegister<U16Register>,
+    pub result: ArrayRegister<U16Register>,
+    /// The carries out of the 32-bit chunks of the product, as pairs of 16-bit limbs.
+    carries: ArrayRegister<U16Register>,
+}
+
+impl U128Add {
+    /// The carry out of the sum.
+    pub fn carry(&self) -> BitRegister {
+        self.carries.get(U128_CHUNKS - 1)
+    }
+}
+
+impl<L: AirParameters> AirBuilder<L> {
+    /// Allocates a 128-bit value as eight 16-bit limbs.
+    pub fn alloc_u128(&mut self) -> ArrayRegister<U16Register> {
+        self.alloc_array::<U16Register>(U128_LIMBS)
+    }
+
+    /// Allocates a 64-bit word as four 16-bit limbs.
+    pub fn alloc_u64_limbs(&mut self) -> ArrayRegister<U16Register> {
+        self.alloc_array::<U16Register>(U64_LIMBS)
+    }
+
+    /// Computes `a + b` modulo `2^128` and returns the sum together with the carry out.
+    pub fn carrying_add_u128(
+        &mut self,
+        a: &ArrayRegister<U16Register>,
+        b: &ArrayRegister<U16Register>,
+    ) -> (ArrayRegister<U16Register>, BitRegister)
+    where
+        L::Instruction: From<U128Add>,
+    {
+        assert_eq!(a.len(), U128_LIMBS, "Expected {} limbs", U128_LIMBS);
+        assert_eq!(b.len(), U128_LIMBS, "Expected {} limbs", U128_LIMBS);
+
+        let instr = U128Add {
+            a: *a,
+            b: *b,
+            result: self.alloc_u128(),
+            carries: self.alloc_array::<BitRegister>(U128_CHUNKS),
+        };
+        self.register_instruction(instr);
+        (instr.result, instr.carry())
+    }
+
+    /// Computes the full 128-bit product of the 64-bit words `a` and `b`.
+    pub fn widening_mul_u64(
+        &mut self,
+        a: &ArrayRegister<U16Register>,
+        b: &ArrayRegister<U16Register>,
+    ) -> ArrayRegister<U16Register>
+    where
+        L::Instruction: From<U64WideningMul>,
+    {
+        assert_eq!(a.len(), U64_LIMBS, "Expected {} limbs", U64_LIMBS);
+        assert_eq!(b.len(), U64_LIMBS, "Expected {} limbs", U64_LIMBS);
+
+        let instr = U64WideningMul {
+            a: *a,
+            b: *b,
+            result: self.alloc_u128(),
+            carries: self.alloc_array::<U16Register>(WIDENING_MUL_CARRY_LIMBS),
+        };
+        self.register_instruction(instr);
+        instr.result
+    }
+}
+
+/// The little-endian 16-bit limbs of `value`.
+pub fn u128_to_limbs(value: u128) -> [u64; U128_LIMBS] {
+    core::array::from_fn(|i| ((value >> (16 * i)) & 0xFFFF) as u64)
+}
+
+/// The value of the little-endian 16-bit `limbs`.
+pub fn limbs_to_u128(limbs: &[u64]) -> u128 {
+    debug_assert!(limbs.len() <= U128_LIMBS);
+    limbs
+        .iter()
+        .rev()
+        .fold(0u128, |acc, limb| (acc << 16) | *limb as u128)
+}
+
+/// The limbs of `a + b` modulo `2^128` and the carries out of its 32-bit chunks.
+pub fn u128_add_limbs(a: u128, b: u128) -> ([u64; U128_LIMBS], [u64; U128_CHUNKS]) {
+    let (sum, _) = a.overflowing_add(b);
+    let carries = core::array::from_fn(|k| {
+        // The carry out of the chunk `k` is the carry out of the sum of the low `32 * (k + 1)` bits.
+        let bits = 32 * (k + 1);
+        let mask = u128::MAX >> (128 - bits);
+        let low_sum = (a & mask).checked_add(b & mask);
+        match low_sum {
+            Some(low_sum) => (low_sum >> bits) as u64,
+            None => 1,
+        }
+    });
+    (u128_to_limbs(sum), carries)
+}
+
+/// The limbs of the product of the 64-bit words `a` and `b` and the limbs of the carries out of
+/// its 32-bit chunks.
+pub fn u64_widening_mul_limbs(
+    a: u64,
+    b: u64,
+) -> ([u64; U128_LIMBS], [u64; WIDENING_MUL_CARRY_LIMBS]) {
+    let a_limbs = u128_to_limbs(a as u128);
+    let b_limbs = u128_to_limbs(b as u128);
+    let result = u128_to_limbs(a as u128 * b as u128);
+
+    // The schoolbook columns, each less than `4 * 2^32`.
+    let mut columns = [0u64; U128_LIMBS];
+    for (i, a_limb) in a_limbs.iter().take(U64_LIMBS).enumerate() {
+        for (j, b_limb) in b_limbs.iter().take(U64_LIMBS).enumerate() {
+            columns[i + j] += a_limb * b_limb;
+        }
+    }
+
+    let mut carries = [0u64; WIDENING_MUL_CARRY_LIMBS];
+    let mut carry = 0u64;
+    for k in 0..U128_CHUNKS - 1 {
+        let chunk = columns[2 * k] + (columns[2 * k + 1] << 16) + carry;
+        let value = result[2 * k] + (result[2 * k + 1] << 16);
+        debug_assert_eq!((chunk - value) & 0xFFFF_FFFF, 0);
+        carry = (chunk - value) >> 32;
+        carries[2 * k] = carry & 0xFFFF;
+        carries[2 * k + 1] = carry >> 16;
+    }
+    (result, carries)
+}
+
+/// The value `limbs[0] + limbs[1] * 2^16` of a pair of limbs.
+fn chunk_value<AP: AirParser>(parser: &mut AP, limbs: &[AP::Var]) -> AP::Var {
+    let shifted = parser.mul_const(limbs[1], AP::Field::from_canonical_u32(1 << 16));
+    parser.add(limbs[0], shifted)
+}
+
+impl<AP: AirParser> AirConstraint<AP> for U128Add {
+    fn eval(&self, parser: &mut AP) {
+        let a = self.a.eval_vec(parser);
+        let b = self.b.eval_vec(parser);
+        let result = self.result.eval_vec(parser);
+        let carries = self.carries.eval_vec(parser);
+
+        let two_32 = AP::Field::from_canonical_u64(1 << 32);
+        let mut carry_in = parser.zero();
+        for (k, carry_out) in carries.into_iter().enumerate() {
+            let chunk = 2 * k..2 * k + 2;
+            let a_chunk = chunk_value(parser, &a[chunk.clone()]);
+            let b_chunk = chunk_value(parser, &b[chunk.clone()]);
+            let result_chunk = chunk_value(parser, &result[chunk]);
+
+            // `a + b + carry_in = result + carry_out * 2^32` on every chunk.
+            let sum = parser.add(a_chunk, b_chunk);
+            let lhs = parser.add(sum, carry_in);
+            let carry_shifted = parser.mul_const(carry_out, two_32);
+            let rhs = parser.add(result_chunk, carry_shifted);
+            parser.assert_eq(lhs, rhs);
+
+            carry_in = carry_out;
+        }
+    }
+}
+
+impl<AP: AirParser> AirConstraint<AP> for U64WideningMul {
+    fn eval(&self, parser: &mut AP) {
+        let a = self.a.eval_vec(parser);
+        let b = self.b.eval_vec(parser);
+        let result = self.result.eval_vec(parser);
+        let carries = self.carries.eval_vec(parser);
+
+        let mut columns = vec![parser.zero(); U128_LIMBS];
+        for (i, a_limb) in a.iter().enumerate() {
+            for (j, b_limb) in b.iter().enumerate() {
+                let product = parser.mul(*a_limb, *b_limb);
+                columns[i + j] = parser.add(columns[i + j], product);
+            }
+        }
+
+        // The equations are of values less than `2^51`, so they hold over the integers.
+        let two_32 = AP::Field::from_canonical_u64(1 << 32);
+        let mut carry_in = parser.zero();
+        for k in 0..U128_CHUNKS {
+            let chunk = 2 * k..2 * k + 2;
+            let column_chunk = chunk_value(parser, &columns[chunk.clone()]);
+            let result_chunk = chunk_value(parser, &result[chunk]);
+
+            let lhs = parser.add(column_chunk, carry_in);
+            let rhs = if k < U128_CHUNKS - 1 {
+                let carry_out = chunk_value(parser, &carries[2 * k..2 * k + 2]);
+                let carry_shifted = parser.mul_const(carry_out, two_32);
+                carry_in = carry_out;
+                parser.add(result_chunk, carry_shifted)
+            } else {
+                result_chunk
+            };
+            parser.assert_eq(lhs, rhs);
+        }
+    }
+}
+
+fn read_u128<F: PrimeField64>(limbs: &[F]) -> u128 {
+    limbs_to_u128(
+        &limbs
+            .iter()
+            .map(|x| x.as_canonical_u64())
+            .collect::<Vec<_>>(),
+    )
+}
+
+impl<F: PrimeField64> Instruction<F> for U128Add {
+    fn write(&self, writer: &TraceWriter<F>, row_index: usize) {
+        let a = read_u128(&writer.read_vec(&self.a, row_index));
+        let b = read_u128(&writer.read_vec(&self.b, row_index));
+
+        let (result, carries) = u128_add_limbs(a, b);
+        writer.write_array(&self.result, result.map(F::from_canonical_u64), row_index);
+        writer.write_array(&self.carries, carries.map(F::from_canonical_u64), row_index);
+    }
+
+    fn write_to_air(&self, writer: &mut impl AirWriter<Field = F>) {
+        let a = read_u128(&writer.read_vec(&self.a));
+        let b = read_u128(&writer.read_vec(&self.b));
+
+        let (result, carries) = u128_add_limbs(a, b);
+        writer.write_array(&self.result, result.map(F::from_canonical_u64));
+        writer.write_array(&self.carries, carries.map(F::from_canonical_u64));
+    }
+}
+
+impl<F: PrimeField64> Instruction<F> for U64WideningMul {
+    fn write(&self, writer: &TraceWriter<F>, row_index: usize) {
+        let a = read_u128(&writer.read_vec(&self.a, row_index)) as u64;
+        let b = read_u128(&writer.read_vec(&self.b, row_index)) as u64;
+
+        let (result, carries) = u64_widening_mul_limbs(a, b);
+        writer.write_array(&self.result, result.map(F::from_canonical_u64), row_index);
+        writer.write_array(&self.carries, carries.map(F::from_canonical_u64), row_index);
+    }
+
+    fn write_to_air(&self, writer: &mut impl AirWriter<Field = F>) {
+        let a = read_u128(&writer.read_vec(&self.a)) as u64;
+        let b = read_u128(&writer.read_vec(&self.b)) as u64;
+
+        let (result, carries) = u64_widening_mul_limbs(a, b);
+        writer.write_array(&self.result, result.map(F::from_canonical_u64));
+        writer.write_array(&self.carries, carries.map(F::from_canonical_u64));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::{thread_rng, Rng};
+
+    use super::*;
+    use crate::chip::builder::tests::*;
+
+    #[derive(Debug, Clone, Serialize, Deserialize)]
+    pub struct U128AddTest;
+
+    impl AirParameters for U128AddTest {
+        type Field = GoldilocksField;
+        type CubicParams = GoldilocksCubicParameters;
+        type Instruction = U128Add;
+
+        const NUM_ARITHMETIC_COLUMNS: usize = 24;
+        const NUM_FREE_COLUMNS: usize = 6;
+        const EXTENDED_COLUMNS: usize = 45;
+    }
+
+    #[derive(Debug, Clone, Serialize, Deserialize)]
+    pub struct U64WideningMulTest;
+
+    impl AirParameters for U64WideningMulTest {
+        type Field = GoldilocksField;
+        type CubicParams = GoldilocksCubicParameters;
+        type Instruction = U64WideningMul;
+
+        const NUM_ARITHMETIC_COLUMNS: usize = 22;
+        const NUM_FREE_COLUMNS: usize = 2;
+        const EXTENDED_COLUMNS: usize = 42;
+    }
+
+    fn write_u128(
+        writer: &TraceWriter<GoldilocksField>,
+        register: &ArrayRegister<U16Register>,
+        value: u128,
+        row_index: usize,
+    ) {
+        let limbs = u128_to_limbs(value).map(GoldilocksField::from_canonical_u64);
+        writer.write_array(register, &limbs[..register.len()], row_index);
+    }
+
+    #[test]
+    fn test_host_arithmetic() {
+        let mut rng = thread_rng();
+        let edge = [0, 1, u64::MAX as u128, u128::MAX, 1 << 127, (1 << 96) - 1];
+        let values = edge
+            .into_iter()
+            .chain((0..100).map(|_| rng.gen::<u128>()))
+            .collect::<Vec<_>>();
+
+        for &a in values.iter() {
+            assert_eq!(limbs_to_u128(&u128_to_limbs(a)), a);
+            for &b in values.iter().take(20) {
+                let (sum, carries) = u128_add_limbs(a, b);
+                let (expected, carry) = a.overflowing_add(b);
+                assert_eq!(limbs_to_u128(&sum), expected);
+                assert_eq!(carries[U128_CHUNKS - 1], carry as u64);
+
+                let (a, b) = (a as u64, b as u64);
+                let (product, carries) = u64_widening_mul_limbs(a, b);
+                assert_eq!(limbs_to_u128(&product), a as u128 * b as u128);
+                assert!(carries.iter().all(|c| *c < 1 << 16));
+            }
+        }
+    }
+
+    #[test]
+    fn test_u128_add() {
+        type F = GoldilocksField;
+        type L = U128AddTest;
+        type SC = PoseidonGoldilocksStarkConfig;
+
+        let mut builder = AirBuilder::<L>::new();
+        let a = builder.alloc_u128();
+        let b = builder.alloc_u128();
+        let (result, carry) = builder.carrying_add_u128(&a, &b);
+
+        let (air, trace_data) = builder.build();
+        let num_rows = 1 << 16;
+        let generator = ArithmeticGenerator::<L>::new(trace_data, num_rows);
+
+        let mut rng = thread_rng();
+        let writer = generator.new_writer();
+        for i in 0..num_rows {
+            let (a_val, b_val) = (rng.gen::<u128>(), rng.gen::<u128>());
+            write_u128(&writer, &a, a_val, i);
+            write_u128(&writer, &b, b_val, i);
+            writer.write_row_instructions(&generator.air_data, i);
+
+            let (expected, expected_carry) = a_val.overflowing_add(b_val);
+            assert_eq!(read_u128::<F>(&writer.read_vec(&result, i)), expected);
+            assert_eq!(
+                writer.read(&carry, i),
+                F::from_canonical_u8(expected_carry as u8)
+            );
+        }
+
+        let stark = Starky::new(air);
+        let config = SC::standard_fast_config(num_rows);
+
+        // Generate proof and verify as a stark
+        test_starky(&stark, &config, &generator, &[]);
+
+        // Test the recursive proof.
+        test_recursive_starky(stark, config, generator, &[]);
+    }
+
+    #[test]
+    fn test_u64_widening_mul() {
+        type F = GoldilocksField;
+        type L = U64WideningMulTest;
+        type SC = PoseidonGoldilocksStarkConfig;
+
+        let mut builder = AirBuilder::<L>::new();
+        let a = builder.alloc_u64_limbs();
+        let b = builder.alloc_u64_limbs();
+        let product = builder.widening_mul_u64(&a, &b);
+
+        let (air, trace_data) = builder.build();
+        let num_rows = 1 << 16;
+        let generator = ArithmeticGenerator::<L>::new(trace_data, num_rows);
+
+        let mut rng = thread_rng();
+        let writer = generator.new_writer();
+        for i in 0..num_rows {
+            let (a_val, b_val) = (rng.gen::<u64>(), rng.gen::<u64>());
+            write_u128(&writer, &a, a_val as u128, i);
+            write_u128(&writer, &b, b_val as u128, i);
+            writer.write_row_instructions(&generator.air_data, i);
+
+            let expected = a_val as u128 * b_val as u128;
+            assert_eq!(read_u128::<F>(&writer.read_vec(&product, i)), expected);
+        }
+
+        let stark = Starky::new(air);
+        let config = SC::standard_fast_config(num_rows);
+
+        // Generate proof and verify as a stark
+        test_starky(&stark, &config, &generator, &[]);
+
+        // Test the recursive proof.
+        test_recursive_starky(stark, config, generator, &[]);
+    }
+}
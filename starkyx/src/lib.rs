@@ -5,11 +5,14 @@
 extern crate alloc;
 
 pub mod air;
+#[cfg(feature = "std")]
 pub mod chip;
+#[cfg(feature = "std")]
 pub mod machine;
 pub mod math;
 pub mod maybe_rayon;
 pub mod polynomial;
+#[cfg(feature = "std")]
 pub mod trace;
 pub mod utils;
 
@@ -19,16 +22,27 @@ pub mod plonky2;
 pub mod prelude {
     pub use crate::air::parser::AirParser;
     pub use crate::air::AirConstraint;
+    #[cfg(feature = "std")]
     pub use crate::chip::instruction::empty::EmptyInstruction;
+    #[cfg(feature = "std")]
     pub use crate::chip::trace::writer::data::AirWriterData;
+    #[cfg(feature = "std")]
     pub use crate::chip::trace::writer::AirWriter;
+    #[cfg(feature = "std")]
     pub use crate::chip::AirParameters;
+    #[cfg(feature = "std")]
     pub use crate::machine::builder::Builder;
+    #[cfg(feature = "std")]
     pub use crate::machine::bytes::builder::BytesBuilder;
+    #[cfg(feature = "std")]
     pub use crate::machine::bytes::stark::ByteStark;
+    #[cfg(feature = "std")]
     pub use crate::machine::emulated::builder::EmulatedBuilder;
+    #[cfg(feature = "std")]
     pub use crate::machine::emulated::stark::EmulatedStark;
+    #[cfg(feature = "std")]
     pub use crate::machine::stark::builder::StarkBuilder;
+    #[cfg(feature = "std")]
     pub use crate::machine::stark::Stark;
     pub use crate::math::prelude::*;
     pub use crate::maybe_rayon::*;
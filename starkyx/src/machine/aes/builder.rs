@@ -0,0 +1,460 @@
+use serde::{Deserialize, Serialize};
+
+use super::{shift_rows_index, AES128_NUM_ROUNDS, AES_BLOCK_BYTES, RCON};
+use crate::chip::arithmetic::expression::ArithmeticExpression;
+use crate::chip::register::array::ArrayRegister;
+use crate::chip::register::{Register, RegisterSerializable};
+use crate::chip::uint::bytes::operations::value::ByteOperation;
+use crate::chip::uint::bytes::register::ByteRegister;
+use crate::chip::uint::operations::instruction::UintInstructions;
+use crate::chip::AirParameters;
+use crate::machine::builder::Builder;
+use crate::machine::bytes::builder::BytesBuilder;
+use crate::math::prelude::*;
+
+/// The round keys of AES-128, each a register of 16 bytes, with the key as the first round key.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AES128KeyScheduleRegister {
+    pub round_keys: Vec<ArrayRegister<ByteRegister>>,
+}
+
+/// The registers of a block of a message encrypted in CTR mode.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct AES128CTRRegister {
+    /// The last four bytes of the counter block, whose value must be written in each row.
+    pub counter: ArrayRegister<ByteRegister>,
+    /// The ciphertext of the block.
+    pub ciphertext: ArrayRegister<ByteRegister>,
+}
+
+pub trait AES128Builder: Builder {
+    /// Computes the round keys of `key`, a register of 16 bytes.
+    ///
+    /// If the key is in the trace, the key and the round keys are marked as secret.
+    fn aes128_key_schedule(
+        &mut self,
+        key: &ArrayRegister<ByteRegister>,
+    ) -> AES128KeyScheduleRegister;
+
+    /// Encrypts `block` with the round keys of `key_schedule`, in every row of the trace.
+    fn aes128_encrypt(
+        &mut self,
+        key_schedule: &AES128KeyScheduleRegister,
+        block: &ArrayRegister<ByteRegister>,
+    ) -> ArrayRegister<ByteRegister>;
+
+    /// Encrypts the blocks of a message in CTR mode, one block in each row of the trace.
+    ///
+    /// The key is constrained to be the same in all rows. The counter block of the row `i` is
+    /// `initial_counter`, a public register of 16 bytes, with its last four bytes incremented by
+    /// `i` as a big-endian integer, which must not overflow. These bytes are returned in the
+    /// counter register, whose value must be written by the prover, see
+    /// [`super::aes128_ctr_counter_block`]. The keystream of rows past the end of the message
+    /// can be ignored, as well as the last bytes of the keystream of a partial last block.
+    fn aes128_ctr(
+        &mut self,
+        key_schedule: &AES128KeyScheduleRegister,
+        initial_counter: &ArrayRegister<ByteRegister>,
+        plaintext: &ArrayRegister<ByteRegister>,
+    ) -> AES128CTRRegister;
+
+    /// Encrypts the blocks of a message in CBC mode, one block in each row of the trace.
+    ///
+    /// The key is constrained to be the same in all rows. The block of the first row is chained
+    /// with the public initialization vector `iv`, and the block of each other row with the
+    /// ciphertext of the previous row.
+    fn aes128_cbc(
+        &mut self,
+        key_schedule: &AES128KeyScheduleRegister,
+        iv: &ArrayRegister<ByteRegister>,
+        plaintext: &ArrayRegister<ByteRegister>,
+    ) -> ArrayRegister<ByteRegister>;
+}
+
+impl<L: AirParameters> AES128Builder for BytesBuilder<L>
+where
+    L::Instruction: UintInstructions,
+{
+    fn aes128_key_schedule(
+        &mut self,
+        key: &ArrayRegister<ByteRegister>,
+    ) -> AES128KeyScheduleRegister {
+        assert_eq!(key.len(), AES_BLOCK_BYTES);
+        let mut round_keys = vec![*key];
+        for rcon in RCON {
+            let prev = *round_keys.last().unwrap();
+            // The last word of the previous round key, rotated and substituted.
+            let mut word = [13, 14, 15, 12].map(|i| sbox(self, &prev.get(i)));
+            let rcon = self.constant::<ByteRegister>(&L::Field::from_canonical_u8(rcon));
+            word[0] = xor(self, &word[0], &rcon);
+
+            let round_key = self.alloc_array::<ByteRegister>(AES_BLOCK_BYTES);
+            for i in 0..AES_BLOCK_BYTES {
+                set_xor(self, &word[i % 4], &prev.get(i), &round_key.get(i));
+                word[i % 4] = round_key.get(i);
+            }
+            round_keys.push(round_key);
+        }
+
+        if key.is_trace() {
+            for register in round_keys.iter() {
+                self.api.mark_secret(register);
+            }
+        }
+        AES128KeyScheduleRegister { round_keys }
+    }
+
+    fn aes128_encrypt(
+        &mut self,
+        key_schedule: &AES128KeyScheduleRegister,
+        block: &ArrayRegister<ByteRegister>,
+    ) -> ArrayRegister<ByteRegister> {
+        assert_eq!(block.len(), AES_BLOCK_BYTES);
+        let ciphertext = self.alloc_array::<ByteRegister>(AES_BLOCK_BYTES);
+        encrypt_block(
+            self,
+            key_schedule,
+            core::array::from_fn(|i| block.get(i)),
+            &ciphertext,
+        );
+        ciphertext
+    }
+
+    fn aes128_ctr(
+        &mut self,
+        key_schedule: &AES128KeyScheduleRegister,
+        initial_counter: &ArrayRegister<ByteRegister>,
+        plaintext: &ArrayRegister<ByteRegister>,
+    ) -> AES128CTRRegister {
+        assert_eq!(initial_counter.len(), AES_BLOCK_BYTES);
+        assert_eq!(plaintext.len(), AES_BLOCK_BYTES);
+        assert!(
+            !initial_counter.is_trace(),
+            "The initial counter block must be public"
+        );
+        assert_constant_key(self, key_schedule);
+
+        // The counter of the row `i` is the initial counter plus `i`.
+        let counter = self.alloc_array::<ByteRegister>(4);
+        let be_value = |bytes: ArrayRegister<ByteRegister>| {
+            bytes
+                .iter()
+                .fold(ArithmeticExpression::<L::Field>::zero(), |acc, byte| {
+                    acc * L::Field::from_canonical_u16(256) + byte.expr()
+                })
+        };
+        let clk = self.clk();
+        self.assert_expressions_equal(
+            be_value(counter),
+            be_value(initial_counter.get_subarray(12..16)) + clk.expr(),
+        );
+
+        let counter_block = core::array::from_fn(|i| match i < 12 {
+            true => initial_counter.get(i),
+            false => counter.get(i - 12),
+        });
+        let keystream = self.alloc_array::<ByteRegister>(AES_BLOCK_BYTES);
+        encrypt_block(self, key_schedule, counter_block, &keystream);
+
+        let ciphertext = self.alloc_array::<ByteRegister>(AES_BLOCK_BYTES);
+        for i in 0..AES_BLOCK_BYTES {
+            set_xor(
+                self,
+                &plaintext.get(i),
+                &keystream.get(i),
+                &ciphertext.get(i),
+            );
+        }
+
+        AES128CTRRegister {
+            counter,
+            ciphertext,
+        }
+    }
+
+    fn aes128_cbc(
+        &mut self,
+        key_schedule: &AES128KeyScheduleRegister,
+        iv: &ArrayRegister<ByteRegister>,
+        plaintext: &ArrayRegister<ByteRegister>,
+    ) -> ArrayRegister<ByteRegister> {
+        assert_eq!(iv.len(), AES_BLOCK_BYTES);
+        assert_eq!(plaintext.len(), AES_BLOCK_BYTES);
+        assert!(!iv.is_trace(), "The initialization vector must be public");
+        assert_constant_key(self, key_schedule);
+
+        // The chaining value, set to the initialization vector in the first row and to the
+        // ciphertext of the previous row in the others.
+        let chain = self.alloc_array::<ByteRegister>(AES_BLOCK_BYTES);
+        for (byte, iv_byte) in chain.iter().zip(iv.iter()) {
+            self.set_first_row(&byte, &iv_byte);
+        }
+
+        let block = core::array::from_fn(|i| xor(self, &plaintext.get(i), &chain.get(i)));
+        let ciphertext = self.alloc_array::<ByteRegister>(AES_BLOCK_BYTES);
+        encrypt_block(self, key_schedule, block, &ciphertext);
+
+        for (byte, ciphertext_byte) in chain.iter().zip(ciphertext.iter()) {
+            self.set_next(&byte, &ciphertext_byte);
+        }
+        ciphertext
+    }
+}
+
+/// Asserts that the key of `key_schedule` is the same in all rows of the trace.
+fn assert_constant_key<L: AirParameters>(
+    builder: &mut BytesBuilder<L>,
+    key_schedule: &AES128KeyScheduleRegister,
+) where
+    L::Instruction: UintInstructions,
+{
+    let key = key_schedule.round_keys[0];
+    if key.is_trace() {
+        for byte in key.iter() {
+            builder.assert_equal_transition(&byte.next(), &byte);
+        }
+    }
+}
+
+/// Encrypts `block` and writes the result to `ciphertext`.
+fn encrypt_block<L: AirParameters>(
+    builder: &mut BytesBuilder<L>,
+    key_schedule: &AES128KeyScheduleRegister,
+    block: [ByteRegister; AES_BLOCK_BYTES],
+    ciphertext: &ArrayRegister<ByteRegister>,
+) where
+    L::Instruction: UintInstructions,
+{
+    let round_keys = &key_schedule.round_keys;
+    assert_eq!(round_keys.len(), AES128_NUM_ROUNDS + 1);
+
+    let mut state = core::array::from_fn(|i| xor(builder, &block[i], &round_keys[0].get(i)));
+    for round_key in round_keys[1..AES128_NUM_ROUNDS].iter() {
+        let shifted = sub_bytes_shift_rows(builder, &state);
+        let mixed = mix_columns(builder, &shifted);
+        state = core::array::from_fn(|i| xor(builder, &mixed[i], &round_key.get(i)));
+    }
+
+    // The last round has no `MixColumns`, and its result is written to the ciphertext.
+    let shifted = sub_bytes_shift_rows(builder, &state);
+    let round_key = round_keys[AES128_NUM_ROUNDS];
+    for (i, byte) in shifted.iter().enumerate() {
+        set_xor(builder, byte, &round_key.get(i), &ciphertext.get(i));
+    }
+}
+
+/// The `SubBytes` step, followed by `ShiftRows` which only permutes the registers.
+fn sub_bytes_shift_rows<L: AirParameters>(
+    builder: &mut BytesBuilder<L>,
+    state: &[ByteRegister; AES_BLOCK_BYTES],
+) -> [ByteRegister; AES_BLOCK_BYTES]
+where
+    L::Instruction: UintInstructions,
+{
+    let substituted = state.map(|byte| sbox(builder, &byte));
+    core::array::from_fn(|i| substituted[shift_rows_index(i)])
+}
+
+/// The `MixColumns` step, where the byte `i` of a column `a` becomes
+/// `a_i ^ t ^ xtime(a_i ^ a_(i + 1))`, for `t` the xor of all bytes of the column.
+fn mix_columns<L: AirParameters>(
+    builder: &mut BytesBuilder<L>,
+    state: &[ByteRegister; AES_BLOCK_BYTES],
+) -> [ByteRegister; AES_BLOCK_BYTES]
+where
+    L::Instruction: UintInstructions,
+{
+    let mut mixed = Vec::with_capacity(AES_BLOCK_BYTES);
+    for column in state.chunks_exact(4) {
+        let t_01 = xor(builder, &column[0], &column[1]);
+        let t_23 = xor(builder, &column[2], &column[3]);
+        let t = xor(builder, &t_01, &t_23);
+        for i in 0..4 {
+            let sum = xor(builder, &column[i], &column[(i + 1) % 4]);
+            let doubled = xtime(builder, &sum);
+            let partial = xor(builder, &column[i], &t);
+            mixed.push(xor(builder, &partial, &doubled));
+        }
+    }
+    mixed.try_into().unwrap()
+}
+
+/// The multiplication by `x` in `GF(2^8)`.
+///
+/// The byte is split into its top bit and its lower seven bits with a shift, so that the shift
+/// to the left is twice the lower bits, which is then reduced by `0x1b` if the top bit is set.
+fn xtime<L: AirParameters>(builder: &mut BytesBuilder<L>, a: &ByteRegister) -> ByteRegister
+where
+    L::Instruction: UintInstructions,
+{
+    let top_bit = builder.alloc::<ByteRegister>();
+    let low_bits = builder.alloc::<ByteRegister>();
+    let shr = ByteOperation::ShrCarry(*a, 7, top_bit, low_bits);
+    builder
+        .api
+        .set_byte_operation(&shr, &mut builder.operations);
+
+    let shifted = builder.expression::<ByteRegister>(low_bits.expr() * L::Field::TWO);
+    let reduction =
+        builder.expression::<ByteRegister>(top_bit.expr() * L::Field::from_canonical_u8(0x1b));
+    xor(builder, &shifted, &reduction)
+}
+
+fn sbox<L: AirParameters>(builder: &mut BytesBuilder<L>, a: &ByteRegister) -> ByteRegister
+where
+    L::Instruction: UintInstructions,
+{
+    let result = builder.alloc::<ByteRegister>();
+    let sbox = ByteOperation::Sbox(*a, result);
+    builder
+        .api
+        .set_byte_operation(&sbox, &mut builder.operations);
+    result
+}
+
+fn xor<L: AirParameters>(
+    builder: &mut BytesBuilder<L>,
+    a: &ByteRegister,
+    b: &ByteRegister,
+) -> ByteRegister
+where
+    L::Instruction: UintInstructions,
+{
+    let result = builder.alloc::<ByteRegister>();
+    set_xor(builder, a, b, &result);
+    result
+}
+
+fn set_xor<L: AirParameters>(
+    builder: &mut BytesBuilder<L>,
+    a: &ByteRegister,
+    b: &ByteRegister,
+    result: &ByteRegister,
+) where
+    L::Instruction: UintInstructions,
+{
+    let xor = ByteOperation::Xor(*a, *b, *result);
+    builder
+        .api
+        .set_byte_operation(&xor, &mut builder.operations);
+}
+
+#[cfg(test)]
+mod tests {
+    use plonky2::field::goldilocks_field::GoldilocksField;
+    use plonky2::timed;
+    use plonky2::util::timing::TimingTree;
+    use rand::{thread_rng, Rng};
+
+    use super::*;
+    use crate::chip::uint::operations::instruction::UintInstruction;
+    use crate::machine::aes::{aes128_cbc_encrypt, aes128_ctr, aes128_ctr_counter_block};
+    use crate::math::goldilocks::cubic::GoldilocksCubicParameters;
+    use crate::plonky2::stark::config::CurtaPoseidonGoldilocksConfig;
+    use crate::prelude::{AirWriter, AirWriterData};
+
+    #[derive(Clone, Debug, Serialize, Deserialize)]
+    pub struct AES128Test;
+
+    impl AirParameters for AES128Test {
+        type Field = GoldilocksField;
+        type CubicParams = GoldilocksCubicParameters;
+        type Instruction = UintInstruction;
+
+        const NUM_FREE_COLUMNS: usize = 3503;
+        const EXTENDED_COLUMNS: usize = 11571;
+    }
+
+    fn from_hex(s: &str) -> [u8; AES_BLOCK_BYTES] {
+        hex::decode(s).unwrap().try_into().unwrap()
+    }
+
+    fn to_field_bytes(bytes: &[u8]) -> Vec<GoldilocksField> {
+        bytes
+            .iter()
+            .map(|byte| GoldilocksField::from_canonical_u8(*byte))
+            .collect()
+    }
+
+    #[test]
+    fn test_aes128_ctr_cbc() {
+        type C = CurtaPoseidonGoldilocksConfig;
+
+        let _ = env_logger::builder().is_test(true).try_init();
+        let mut timing = TimingTree::new("test_aes128_ctr_cbc", log::Level::Debug);
+
+        let key = from_hex("2b7e151628aed2a6abf7158809cf4f3c");
+        let initial_counter = from_hex("f0f1f2f3f4f5f6f7f8f9fafbfcfdfeff");
+        let iv = from_hex("000102030405060708090a0b0c0d0e0f");
+
+        // Build the stark, with a message encrypted in both modes.
+        let mut builder = BytesBuilder::<AES128Test>::new();
+        let key_register = builder.alloc_array::<ByteRegister>(AES_BLOCK_BYTES);
+        let initial_counter_register = builder.alloc_array_public::<ByteRegister>(AES_BLOCK_BYTES);
+        let iv_register = builder.alloc_array_public::<ByteRegister>(AES_BLOCK_BYTES);
+        let ctr_plaintext = builder.alloc_array::<ByteRegister>(AES_BLOCK_BYTES);
+        let cbc_plaintext = builder.alloc_array::<ByteRegister>(AES_BLOCK_BYTES);
+
+        let key_schedule = builder.aes128_key_schedule(&key_register);
+        let ctr = builder.aes128_ctr(&key_schedule, &initial_counter_register, &ctr_plaintext);
+        let cbc_ciphertext = builder.aes128_cbc(&key_schedule, &iv_register, &cbc_plaintext);
+
+        let num_rows = 1 << 5;
+        let stark = builder.build::<C, 2>(num_rows);
+
+        // Write trace.
+        let mut rng = thread_rng();
+        let blocks = (0..num_rows)
+            .map(|_| rng.gen::<[u8; AES_BLOCK_BYTES]>())
+            .collect::<Vec<_>>();
+
+        let mut writer_data = AirWriterData::new(&stark.air_data, num_rows);
+        let mut writer = writer_data.public_writer();
+        writer.write_array(&initial_counter_register, to_field_bytes(&initial_counter));
+        writer.write_array(&iv_register, to_field_bytes(&iv));
+
+        let mut ctr_values = Vec::with_capacity(num_rows * AES_BLOCK_BYTES);
+        let mut cbc_values = Vec::with_capacity(num_rows);
+        timed!(timing, "write input", {
+            stark.air_data.write_global_instructions(&mut writer);
+
+            for mut chunk in writer_data.chunks(num_rows) {
+                for (i, block) in blocks.iter().enumerate() {
+                    let mut writer = chunk.window_writer(i);
+                    writer.write_array(&key_register, to_field_bytes(&key));
+                    let counter_block = aes128_ctr_counter_block(&initial_counter, i);
+                    writer.write_array(&ctr.counter, to_field_bytes(&counter_block[12..]));
+                    writer.write_array(&ctr_plaintext, to_field_bytes(block));
+                    writer.write_array(&cbc_plaintext, to_field_bytes(block));
+                    stark.air_data.write_trace_instructions(&mut writer);
+
+                    let as_byte = |value: GoldilocksField| value.as_canonical_u64() as u8;
+                    ctr_values.extend(writer.read_vec(&ctr.ciphertext).into_iter().map(as_byte));
+                    cbc_values.push(
+                        writer
+                            .read_array::<_, AES_BLOCK_BYTES>(&cbc_ciphertext)
+                            .map(as_byte),
+                    );
+                }
+            }
+        });
+
+        let (trace, public) = (writer_data.trace, writer_data.public);
+
+        let proof = timed!(
+            timing,
+            "generate stark proof",
+            stark.prove(&trace, &public, &mut timing).unwrap()
+        );
+
+        stark.verify(proof, &public).unwrap();
+
+        assert_eq!(
+            ctr_values,
+            aes128_ctr(&key, &initial_counter, &blocks.concat())
+        );
+        assert_eq!(cbc_values, aes128_cbc_encrypt(&key, &iv, &blocks));
+
+        timing.print();
+    }
+}
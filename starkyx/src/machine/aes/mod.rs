@@ -0,0 +1,219 @@
+//! The AES-128 block cipher of FIPS-197, with the CTR and CBC modes of SP 800-38A.
+//!
+//! The gadgets of [`builder::AES128Builder`] encrypt one block in each row of the trace, so that
+//! the rows of a trace are the blocks of a message. The S-box is a byte operation of the lookup
+//! table, see [`AES_SBOX`], and the rest of the cipher is made of byte xors, with the
+//! multiplication by `x` of `MixColumns` given by a shift of the byte.
+
+use crate::chip::uint::bytes::operations::sbox::AES_SBOX;
+
+pub mod builder;
+
+/// The number of bytes of an AES block.
+pub const AES_BLOCK_BYTES: usize = 16;
+/// The number of rounds of AES-128.
+pub const AES128_NUM_ROUNDS: usize = 10;
+
+/// The round constants of the AES-128 key schedule.
+const RCON: [u8; AES128_NUM_ROUNDS] = [0x01, 0x02, 0x04, 0x08, 0x10, 0x20, 0x40, 0x80, 0x1b, 0x36];
+
+/// The multiplication by `x` in `GF(2^8)`.
+fn xtime(a: u8) -> u8 {
+    (a << 1) ^ ((a >> 7) * 0x1b)
+}
+
+/// The round keys of AES-128, with the key itself as the first round key.
+pub fn aes128_round_keys(
+    key: &[u8; AES_BLOCK_BYTES],
+) -> [[u8; AES_BLOCK_BYTES]; AES128_NUM_ROUNDS + 1] {
+    let mut round_keys = vec![*key];
+    for rcon in RCON {
+        let prev = *round_keys.last().unwrap();
+        let mut word = [prev[13], prev[14], prev[15], prev[12]].map(|b| AES_SBOX[b as usize]);
+        word[0] ^= rcon;
+        let mut round_key = [0u8; AES_BLOCK_BYTES];
+        for (i, (byte, prev_byte)) in round_key.iter_mut().zip(prev).enumerate() {
+            word[i % 4] ^= prev_byte;
+            *byte = word[i % 4];
+        }
+        round_keys.push(round_key);
+    }
+    round_keys.try_into().unwrap()
+}
+
+/// Encrypts a block with AES-128.
+///
+/// The block is the state of the cipher in column-major order, as in FIPS-197.
+pub fn aes128_encrypt_block(
+    key: &[u8; AES_BLOCK_BYTES],
+    block: &[u8; AES_BLOCK_BYTES],
+) -> [u8; AES_BLOCK_BYTES] {
+    let round_keys = aes128_round_keys(key);
+    let mut state = core::array::from_fn(|i| block[i] ^ round_keys[0][i]);
+    for (round, round_key) in round_keys.iter().enumerate().skip(1) {
+        // SubBytes and ShiftRows.
+        let shifted: [u8; AES_BLOCK_BYTES] =
+            core::array::from_fn(|i| AES_SBOX[state[shift_rows_index(i)] as usize]);
+        // MixColumns, except in the last round.
+        let mixed = if round < AES128_NUM_ROUNDS {
+            core::array::from_fn(|i| {
+                let column = &shifted[i - i % 4..i - i % 4 + 4];
+                let t = column[0] ^ column[1] ^ column[2] ^ column[3];
+                column[i % 4] ^ t ^ xtime(column[i % 4] ^ column[(i + 1) % 4])
+            })
+        } else {
+            shifted
+        };
+        // AddRoundKey.
+        state = core::array::from_fn(|i| mixed[i] ^ round_key[i]);
+    }
+    state
+}
+
+/// The index of the state before `ShiftRows` of the byte at index `i` after it.
+pub(crate) const fn shift_rows_index(i: usize) -> usize {
+    let (row, column) = (i % 4, i / 4);
+    row + 4 * ((column + row) % 4)
+}
+
+/// The counter block of index `i`, whose last 32 bits are those of `initial_counter` incremented
+/// by `i` modulo `2^32`, as a big-endian integer.
+pub fn aes128_ctr_counter_block(
+    initial_counter: &[u8; AES_BLOCK_BYTES],
+    i: usize,
+) -> [u8; AES_BLOCK_BYTES] {
+    let mut counter = *initial_counter;
+    let value = u32::from_be_bytes(initial_counter[12..].try_into().unwrap());
+    counter[12..].copy_from_slice(&value.wrapping_add(i as u32).to_be_bytes());
+    counter
+}
+
+/// Encrypts `message` with AES-128 in CTR mode, starting from the counter block
+/// `initial_counter`.
+pub fn aes128_ctr(
+    key: &[u8; AES_BLOCK_BYTES],
+    initial_counter: &[u8; AES_BLOCK_BYTES],
+    message: &[u8],
+) -> Vec<u8> {
+    message
+        .chunks(AES_BLOCK_BYTES)
+        .enumerate()
+        .flat_map(|(i, chunk)| {
+            let keystream =
+                aes128_encrypt_block(key, &aes128_ctr_counter_block(initial_counter, i));
+            chunk
+                .iter()
+                .zip(keystream)
+                .map(|(byte, key_byte)| byte ^ key_byte)
+                .collect::<Vec<_>>()
+        })
+        .collect()
+}
+
+/// Encrypts the blocks of a message with AES-128 in CBC mode, with initialization vector `iv`.
+pub fn aes128_cbc_encrypt(
+    key: &[u8; AES_BLOCK_BYTES],
+    iv: &[u8; AES_BLOCK_BYTES],
+    blocks: &[[u8; AES_BLOCK_BYTES]],
+) -> Vec<[u8; AES_BLOCK_BYTES]> {
+    let mut chain = *iv;
+    blocks
+        .iter()
+        .map(|block| {
+            chain = aes128_encrypt_block(key, &core::array::from_fn(|i| block[i] ^ chain[i]));
+            chain
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn from_hex(s: &str) -> [u8; AES_BLOCK_BYTES] {
+        hex::decode(s).unwrap().try_into().unwrap()
+    }
+
+    /// The plaintext of the examples of SP 800-38A, appendix F.
+    const SP800_38A_PLAINTEXT: [&str; 4] = [
+        "6bc1bee22e409f96e93d7e117393172a",
+        "ae2d8a571e03ac9c9eb76fac45af8e51",
+        "30c81c46a35ce411e5fbc1191a0a52ef",
+        "f69f2445df4f9b17ad2b417be66c3710",
+    ];
+    const SP800_38A_KEY: &str = "2b7e151628aed2a6abf7158809cf4f3c";
+
+    #[test]
+    fn test_aes128_encrypt_block() {
+        // The examples of FIPS-197, appendices B and C.1.
+        let test_vectors = [
+            (
+                "2b7e151628aed2a6abf7158809cf4f3c",
+                "3243f6a8885a308d313198a2e0370734",
+                "3925841d02dc09fbdc118597196a0b32",
+            ),
+            (
+                "000102030405060708090a0b0c0d0e0f",
+                "00112233445566778899aabbccddeeff",
+                "69c4e0d86a7b0430d8cdb78070b4c55a",
+            ),
+        ];
+        for (key, plaintext, ciphertext) in test_vectors {
+            let result = aes128_encrypt_block(&from_hex(key), &from_hex(plaintext));
+            assert_eq!(hex::encode(result), ciphertext);
+        }
+
+        let round_keys = aes128_round_keys(&from_hex(SP800_38A_KEY));
+        assert_eq!(
+            hex::encode(round_keys[AES128_NUM_ROUNDS]),
+            "d014f9a8c9ee2589e13f0cc8b6630ca6"
+        );
+    }
+
+    #[test]
+    fn test_aes128_ctr() {
+        // The example F.5.1 of SP 800-38A.
+        let key = from_hex(SP800_38A_KEY);
+        let initial_counter = from_hex("f0f1f2f3f4f5f6f7f8f9fafbfcfdfeff");
+        let message = hex::decode(SP800_38A_PLAINTEXT.concat()).unwrap();
+        assert_eq!(
+            hex::encode(aes128_ctr(&key, &initial_counter, &message)),
+            [
+                "874d6191b620e3261bef6864990db6ce",
+                "9806f66b7970fdff8617187bb9fffdff",
+                "5ae4df3edbd5d35e5b4f09020db03eab",
+                "1e031dda2fbe03d1792170a0f3009cee",
+            ]
+            .concat()
+        );
+        assert_eq!(
+            hex::encode(aes128_ctr_counter_block(&initial_counter, 1)),
+            "f0f1f2f3f4f5f6f7f8f9fafbfcfdff00"
+        );
+
+        // A partial last block is encrypted with the first bytes of its keystream.
+        let ciphertext = aes128_ctr(&key, &initial_counter, &message[..20]);
+        assert_eq!(
+            hex::encode(ciphertext),
+            "874d6191b620e3261bef6864990db6ce9806f66b"
+        );
+    }
+
+    #[test]
+    fn test_aes128_cbc_encrypt() {
+        // The example F.2.1 of SP 800-38A.
+        let key = from_hex(SP800_38A_KEY);
+        let iv = from_hex("000102030405060708090a0b0c0d0e0f");
+        let blocks = SP800_38A_PLAINTEXT.map(from_hex);
+        let ciphertext = aes128_cbc_encrypt(&key, &iv, &blocks);
+        assert_eq!(
+            ciphertext.iter().map(hex::encode).collect::<Vec<_>>(),
+            [
+                "7649abac8119b246cee98e9b12e9197d",
+                "5086cb9b507219ee95db113a917678b2",
+                "73bed6b8e3c1743b7116e69e22229516",
+                "3ff1caa1681fac09120eca307586e1a7",
+            ]
+        );
+    }
+}
@@ -0,0 +1,519 @@
+use anyhow::Result;
+use num::BigUint;
+use serde::{Deserialize, Serialize};
+
+use super::u256::{range_check, u256_to_bytes, U256Add, U256MulAdd, U256_BYTES};
+use super::{
+    bits, block_hash, hash_value, inner_digest, target, work, HEADER_BYTES, MAX_EXPONENT,
+    MIN_EXPONENT,
+};
+use crate::chip::register::array::ArrayRegister;
+use crate::chip::register::bit::BitRegister;
+use crate::chip::register::element::ElementRegister;
+use crate::chip::register::Register;
+use crate::chip::trace::writer::AirWriter;
+use crate::chip::uint::bytes::register::ByteRegister;
+use crate::chip::uint::operations::instruction::UintInstructions;
+use crate::chip::uint::register::{ByteArrayRegister, U32Register};
+use crate::chip::uint::util::u32_to_le_field_bytes;
+use crate::chip::AirParameters;
+use crate::machine::builder::Builder;
+use crate::machine::bytes::builder::BytesBuilder;
+use crate::machine::hash::sha::algorithm::SHAPure;
+use crate::machine::hash::sha::builder::SHABuilder;
+use crate::machine::hash::sha::outputs::SHADigestSchedule;
+use crate::machine::hash::sha::sha256::register::SHA256DigestRegister;
+use crate::machine::hash::sha::sha256::SHA256;
+use crate::math::prelude::*;
+use crate::plonky2::stark::outputs::ProofOutputs;
+
+/// The number of chunks hashed for each header, two for the header and one for its digest.
+pub const CHUNKS_PER_HEADER: usize = 3;
+
+/// The registers of a header of a chain.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BitcoinHeaderRegister {
+    /// The two chunks of the padded header, whose first 20 words are the header as big-endian
+    /// words.
+    pub chunks: [ArrayRegister<U32Register>; 2],
+    /// The digest of the header.
+    pub inner_digest: SHA256DigestRegister,
+    /// The hash of the block, which is the digest of `inner_digest`.
+    pub hash: SHA256DigestRegister,
+    /// The target encoded by the field `bits`, as little-endian bytes.
+    pub target: ArrayRegister<ByteRegister>,
+    /// The work of the block, `2^256 / (target + 1)`, as little-endian bytes.
+    pub work: ArrayRegister<ByteRegister>,
+    /// The work of the chain up to this block, as little-endian bytes.
+    pub chain_work: ArrayRegister<ByteRegister>,
+    witness: HeaderWitness,
+}
+
+/// The values of a header written by the prover to check its target and its work.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct HeaderWitness {
+    /// The bits telling which of the exponents `MIN_EXPONENT..=MAX_EXPONENT` is that of `bits`.
+    exponent_bits: ArrayRegister<BitRegister>,
+    /// `target - hash`, and the sum `hash + (target - hash) = target`.
+    hash_gap: ArrayRegister<ByteRegister>,
+    hash_le_target: U256Add,
+    /// `target + 1`, and the sum `target + 1 = target_plus_one`.
+    target_plus_one: ArrayRegister<ByteRegister>,
+    increment: U256Add,
+    /// The remainder `r` of the division `2^256 = work * (target + 1) + r`.
+    remainder: ArrayRegister<ByteRegister>,
+    division: U256MulAdd,
+    /// `target - r`, and the sum `r + (target - r) = target`.
+    remainder_gap: ArrayRegister<ByteRegister>,
+    remainder_le_target: U256Add,
+    /// The sum of the work of the chain up to the previous block and the work of the block.
+    chain_work_sum: Option<U256Add>,
+}
+
+/// A chain of headers, with the work of the chain after each header as an output.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BitcoinHeaderChain {
+    pub headers: Vec<BitcoinHeaderRegister>,
+    /// The works of the chain after each header, as global values named as an output.
+    pub chain_work: ArrayRegister<ByteArrayRegister<U256_BYTES>>,
+}
+
+pub trait BitcoinHeaderBuilder: Builder {
+    /// Verifies a chain of `num_headers` headers and exposes its work as the output `name`.
+    ///
+    /// The hash of each header is its double SHA-256, computed by a single instance of the
+    /// SHA-256 chip. The hash of the previous block held by each header after the first is set
+    /// to the hash of the previous header, and each hash must not exceed the target of its
+    /// header, whose work is added to the work of the chain.
+    ///
+    /// The headers and all the values checking them are public and must be written before the
+    /// global instructions, as given by [`BitcoinHeaderChain::write`]. The trace takes
+    /// `CHUNKS_PER_HEADER * 64` rows for each header, rounded up to a power of two.
+    fn bitcoin_header_chain(&mut self, name: &str, num_headers: usize) -> BitcoinHeaderChain;
+}
+
+impl<L: AirParameters> BitcoinHeaderBuilder for BytesBuilder<L>
+where
+    L::Instruction: UintInstructions,
+{
+    fn bitcoin_header_chain(&mut self, name: &str, num_headers: usize) -> BitcoinHeaderChain {
+        assert!(num_headers > 0, "The chain has no headers");
+        let padding = SHA256::pad(&[0u8; HEADER_BYTES]);
+        let header_padding = self.constant_array::<U32Register>(
+            &padding[HEADER_BYTES / 4..]
+                .iter()
+                .map(|word| u32_to_le_field_bytes(*word))
+                .collect::<Vec<_>>(),
+        );
+        let digest_padding = self.constant_array::<U32Register>(
+            &SHA256::pad(&[0u8; 32])[8..]
+                .iter()
+                .map(|word| u32_to_le_field_bytes(*word))
+                .collect::<Vec<_>>(),
+        );
+
+        // The chunks of the header and of its digest, for each header.
+        let mut padded_chunks = Vec::with_capacity(CHUNKS_PER_HEADER * num_headers);
+        let mut header_chunks = Vec::with_capacity(num_headers);
+        let mut digest_chunks = Vec::with_capacity(num_headers);
+        for _ in 0..num_headers {
+            let chunks = [
+                self.alloc_array_public::<U32Register>(16),
+                self.alloc_array_public::<U32Register>(16),
+            ];
+            for (word, padding) in chunks[1]
+                .get_subarray(HEADER_BYTES / 4 - 16..16)
+                .iter()
+                .zip(header_padding.iter())
+            {
+                self.api().set_to_expression_public(&word, padding.expr());
+            }
+            let digest_chunk = self.alloc_array_public::<U32Register>(16);
+            for (word, padding) in digest_chunk
+                .get_subarray(8..16)
+                .iter()
+                .zip(digest_padding.iter())
+            {
+                self.api().set_to_expression_public(&word, padding.expr());
+            }
+            padded_chunks.extend(chunks);
+            padded_chunks.push(digest_chunk);
+            header_chunks.push(chunks);
+            digest_chunks.push(digest_chunk);
+        }
+
+        let schedule = header_schedule(num_headers);
+        let to_field = |bits: Vec<bool>| {
+            bits.into_iter()
+                .map(|bit| Self::Field::from_canonical_usize(bit as usize))
+                .collect::<Vec<_>>()
+        };
+        let end_bits = self.constant_array::<BitRegister>(&to_field(schedule.end_bits()));
+        let digest_bits = self.constant_array::<BitRegister>(&to_field(schedule.digest_bits()));
+        let digest_indices = self.constant_array::<ElementRegister>(
+            &schedule
+                .digest_indices()
+                .into_iter()
+                .map(Self::Field::from_canonical_usize)
+                .collect::<Vec<_>>(),
+        );
+        let digests =
+            self.sha::<SHA256, 64>(&padded_chunks, &end_bits, &digest_bits, digest_indices);
+
+        // The constants `1` and `2^256`.
+        let one = self.constant_array::<ByteRegister>(
+            &(0..U256_BYTES)
+                .map(|k| Self::Field::from_canonical_usize((k == 0) as usize))
+                .collect::<Vec<_>>(),
+        );
+        let two_256 = self.constant_array::<ByteRegister>(
+            &(0..2 * U256_BYTES)
+                .map(|k| Self::Field::from_canonical_usize((k == U256_BYTES) as usize))
+                .collect::<Vec<_>>(),
+        );
+        let one = one.iter().collect::<Vec<_>>();
+        let two_256 = two_256.iter().collect::<Vec<_>>();
+
+        let mut headers: Vec<BitcoinHeaderRegister> = Vec::with_capacity(num_headers);
+        for ((chunks, digest_chunk), digests) in header_chunks
+            .into_iter()
+            .zip(digest_chunks)
+            .zip(digests.chunks_exact(2))
+        {
+            let (inner_digest, hash) = (digests[0], digests[1]);
+            for (word, value) in digest_chunk.iter().zip(inner_digest.iter()) {
+                self.api().set_to_expression_public(&word, value.expr());
+            }
+
+            // The header extends the previous one.
+            if let Some(previous) = headers.last() {
+                for (word, value) in chunks[0]
+                    .get_subarray(1..9)
+                    .iter()
+                    .zip(previous.hash.iter())
+                {
+                    self.api().set_to_expression_public(&word, value.expr());
+                }
+            }
+
+            // The word of `bits` holds the exponent and then the mantissa from its most
+            // significant byte, in little-endian order.
+            let bits_bytes = chunks[1].get(2).to_le_bytes();
+            let exponent = bits_bytes.get(0);
+            let mantissa = [bits_bytes.get(3), bits_bytes.get(2), bits_bytes.get(1)];
+            range_check(self, &mantissa);
+            let double_mantissa_msb = self.alloc_public::<ByteRegister>();
+            self.api().set_to_expression_public(
+                &double_mantissa_msb,
+                mantissa[2].expr() * Self::Field::from_canonical_u32(2),
+            );
+            range_check(self, &[double_mantissa_msb]);
+
+            // The target is the mantissa shifted by the exponent, given by its bits.
+            let exponents = MIN_EXPONENT..=MAX_EXPONENT;
+            let exponent_bits = self.alloc_array_public::<BitRegister>(exponents.clone().count());
+            let mut bit_sum = ArithmeticExpression::zero();
+            let mut exponent_sum = ArithmeticExpression::zero();
+            for (bit, e) in exponent_bits.iter().zip(exponents.clone()) {
+                self.assert_expression_zero(bit.expr() * bit.not_expr());
+                bit_sum = bit_sum + bit.expr();
+                exponent_sum = exponent_sum + bit.expr() * Self::Field::from_canonical_u32(e);
+            }
+            self.assert_expression_zero(bit_sum - Self::Field::ONE);
+            self.assert_expressions_equal(exponent_sum, exponent.expr());
+            let target = self.alloc_array_public::<ByteRegister>(U256_BYTES);
+            for (k, byte) in target.iter().enumerate() {
+                let value = exponent_bits
+                    .iter()
+                    .zip(exponents.clone())
+                    .filter_map(|(bit, e)| {
+                        let shift = (e - MIN_EXPONENT) as usize;
+                        (shift..shift + 3)
+                            .contains(&k)
+                            .then(|| bit.expr() * mantissa[k - shift].expr())
+                    })
+                    .fold(ArithmeticExpression::zero(), |acc, x| acc + x);
+                self.api().set_to_expression_public(&byte, value);
+            }
+            let target_bytes = target.iter().collect::<Vec<_>>();
+
+            // The hash, as a little-endian integer, does not exceed the target.
+            let hash_bytes = (0..U256_BYTES)
+                .map(|k| hash.get(k / 4).to_le_bytes().get(3 - k % 4))
+                .collect::<Vec<_>>();
+            let hash_gap = self.alloc_array_public::<ByteRegister>(U256_BYTES);
+            let hash_gap_bytes = hash_gap.iter().collect::<Vec<_>>();
+            range_check(self, &hash_gap_bytes);
+            let hash_le_target = U256Add::new(self, &hash_bytes, &hash_gap_bytes, &target_bytes);
+
+            // The work is the quotient of `2^256` by `target + 1`.
+            let target_plus_one = self.alloc_array_public::<ByteRegister>(U256_BYTES);
+            let target_plus_one_bytes = target_plus_one.iter().collect::<Vec<_>>();
+            range_check(self, &target_plus_one_bytes);
+            let increment = U256Add::new(self, &target_bytes, &one, &target_plus_one_bytes);
+            let [work, remainder, remainder_gap] =
+                [(); 3].map(|_| self.alloc_array_public::<ByteRegister>(U256_BYTES));
+            let [work_bytes, remainder_bytes, remainder_gap_bytes] =
+                [work, remainder, remainder_gap]
+                    .map(|register| register.iter().collect::<Vec<_>>());
+            for bytes in [&work_bytes, &remainder_bytes, &remainder_gap_bytes] {
+                range_check(self, bytes);
+            }
+            let division = U256MulAdd::new(
+                self,
+                &work_bytes,
+                &target_plus_one_bytes,
+                &remainder_bytes,
+                &two_256,
+            );
+            let remainder_le_target =
+                U256Add::new(self, &remainder_bytes, &remainder_gap_bytes, &target_bytes);
+
+            // The work of the chain is the sum of the works of its blocks.
+            let (chain_work, chain_work_sum) = match headers.last() {
+                None => (work, None),
+                Some(previous) => {
+                    let chain_work = self.alloc_array_public::<ByteRegister>(U256_BYTES);
+                    let chain_work_bytes = chain_work.iter().collect::<Vec<_>>();
+                    range_check(self, &chain_work_bytes);
+                    let sum = U256Add::new(
+                        self,
+                        &previous.chain_work.iter().collect::<Vec<_>>(),
+                        &work_bytes,
+                        &chain_work_bytes,
+                    );
+                    (chain_work, Some(sum))
+                }
+            };
+
+            headers.push(BitcoinHeaderRegister {
+                chunks,
+                inner_digest,
+                hash,
+                target,
+                work,
+                chain_work,
+                witness: HeaderWitness {
+                    exponent_bits,
+                    hash_gap,
+                    hash_le_target,
+                    target_plus_one,
+                    increment,
+                    remainder,
+                    division,
+                    remainder_gap,
+                    remainder_le_target,
+                    chain_work_sum,
+                },
+            });
+        }
+
+        let chain_work = self
+            .api()
+            .alloc_array_global::<ByteArrayRegister<U256_BYTES>>(num_headers);
+        for (output, header) in chain_work.iter().zip(headers.iter()) {
+            self.api()
+                .set_to_expression_public(&output, header.chain_work.expr());
+        }
+        self.api().register_output(name, &chain_work);
+
+        BitcoinHeaderChain {
+            headers,
+            chain_work,
+        }
+    }
+}
+
+impl BitcoinHeaderChain {
+    /// The number of rows of the trace.
+    pub fn num_rows(&self) -> usize {
+        header_schedule(self.headers.len()).num_rows(64)
+    }
+
+    /// Writes the headers, their digests and the values checking their targets and their works.
+    ///
+    /// The values are those of a valid chain, see [`super::verify_chain`]. The proof fails for
+    /// headers which do not form a chain or whose hashes exceed their targets.
+    pub fn write<W: AirWriter>(&self, writer: &mut W, headers: &[[u8; HEADER_BYTES]]) {
+        assert_eq!(headers.len(), self.headers.len());
+        let write_bytes = |writer: &mut W, register: &ArrayRegister<ByteRegister>, value: &[u8]| {
+            writer.write_array(
+                register,
+                value.iter().map(|byte| W::Field::from_canonical_u8(*byte)),
+            )
+        };
+        let one = u256_to_bytes(&BigUint::from(1u32));
+
+        let mut chain_work = BigUint::from(0u32);
+        for (register, header) in self.headers.iter().zip(headers.iter()) {
+            let witness = &register.witness;
+            let padded_header = SHA256::pad(header);
+            for (chunk, values) in register.chunks.iter().zip(padded_header.chunks_exact(16)) {
+                writer.write_array(
+                    chunk,
+                    values.iter().map(|word| u32_to_le_field_bytes(*word)),
+                );
+            }
+            writer.write_array(
+                &register.inner_digest.as_array(),
+                inner_digest(header).map(u32_to_le_field_bytes),
+            );
+            let hash = block_hash(header);
+            writer.write_array(&register.hash.as_array(), hash.map(u32_to_le_field_bytes));
+
+            let bits = bits(header);
+            let exponent = bits >> 24;
+            for (bit, e) in witness
+                .exponent_bits
+                .iter()
+                .zip(MIN_EXPONENT..=MAX_EXPONENT)
+            {
+                writer.write(
+                    &bit,
+                    &W::Field::from_canonical_usize((e == exponent) as usize),
+                );
+            }
+
+            let target = target(bits);
+            let hash_value = hash_value(&hash);
+            let hash_gap = u256_to_bytes(&(&target - &hash_value));
+            write_bytes(writer, &witness.hash_gap, &hash_gap);
+            witness
+                .hash_le_target
+                .write_carries(writer, &u256_to_bytes(&hash_value), &hash_gap);
+
+            let target_bytes = u256_to_bytes(&target);
+            let target_plus_one = u256_to_bytes(&(&target + 1u32));
+            write_bytes(writer, &witness.target_plus_one, &target_plus_one);
+            witness.increment.write_carries(writer, &target_bytes, &one);
+
+            let work = work(&target);
+            let remainder = (BigUint::from(1u32) << 256) - &work * (&target + 1u32);
+            let (work_bytes, remainder_bytes) = (u256_to_bytes(&work), u256_to_bytes(&remainder));
+            write_bytes(writer, &register.work, &work_bytes);
+            write_bytes(writer, &witness.remainder, &remainder_bytes);
+            witness
+                .division
+                .write_carries(writer, &work_bytes, &target_plus_one, &remainder_bytes);
+            let remainder_gap = u256_to_bytes(&(&target - &remainder));
+            write_bytes(writer, &witness.remainder_gap, &remainder_gap);
+            witness
+                .remainder_le_target
+                .write_carries(writer, &remainder_bytes, &remainder_gap);
+
+            if let Some(sum) = witness.chain_work_sum.as_ref() {
+                sum.write_carries(writer, &u256_to_bytes(&chain_work), &work_bytes);
+            }
+            chain_work += work;
+            write_bytes(writer, &register.chain_work, &u256_to_bytes(&chain_work));
+        }
+    }
+}
+
+/// The schedule of the messages hashed for a chain of headers, the header and its digest for
+/// each header.
+fn header_schedule(num_headers: usize) -> SHADigestSchedule {
+    SHADigestSchedule::new([2, 1].repeat(num_headers))
+}
+
+/// Reads the works of the chain after each header from the output `name` of a proof.
+pub fn read_chain_work<F: PrimeField64>(
+    outputs: &ProofOutputs<F>,
+    name: &str,
+) -> Result<Vec<BigUint>> {
+    Ok(outputs
+        .values::<ByteArrayRegister<U256_BYTES>>(name)?
+        .iter()
+        .map(|bytes| {
+            BigUint::from_bytes_le(
+                &bytes
+                    .iter()
+                    .map(|byte| byte.as_canonical_u64() as u8)
+                    .collect::<Vec<_>>(),
+            )
+        })
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use plonky2::field::goldilocks_field::GoldilocksField;
+    use plonky2::timed;
+    use plonky2::util::timing::TimingTree;
+
+    use super::*;
+    use crate::chip::uint::operations::instruction::UintInstruction;
+    use crate::machine::bitcoin::tests::headers;
+    use crate::machine::bitcoin::verify_chain;
+    use crate::math::goldilocks::cubic::GoldilocksCubicParameters;
+    use crate::plonky2::stark::config::CurtaPoseidonGoldilocksConfig;
+    use crate::plonky2::stark::outputs::AirOutputs;
+    use crate::prelude::AirWriterData;
+
+    #[derive(Clone, Debug, Serialize, Deserialize)]
+    pub struct BitcoinHeaderTest;
+
+    impl AirParameters for BitcoinHeaderTest {
+        type Field = GoldilocksField;
+        type CubicParams = GoldilocksCubicParameters;
+        type Instruction = UintInstruction;
+
+        const NUM_FREE_COLUMNS: usize = 418;
+        const EXTENDED_COLUMNS: usize = 912;
+    }
+
+    #[test]
+    fn test_bitcoin_header_chain() {
+        type C = CurtaPoseidonGoldilocksConfig;
+
+        let _ = env_logger::builder().is_test(true).try_init();
+        let mut timing = TimingTree::new("test_bitcoin_header_chain", log::Level::Debug);
+
+        let headers = headers();
+
+        // Build the stark.
+        let mut builder = BytesBuilder::<BitcoinHeaderTest>::new();
+        let chain = builder.bitcoin_header_chain("chain_work", headers.len());
+
+        let num_rows = chain.num_rows();
+        assert_eq!(num_rows, 1 << 10);
+        let stark = builder.build::<C, 2>(num_rows);
+
+        // Write trace.
+        let mut writer_data = AirWriterData::new(&stark.air_data, num_rows);
+        let mut writer = writer_data.public_writer();
+        chain.write(&mut writer, &headers);
+
+        timed!(timing, "write input", {
+            stark.air_data.write_global_instructions(&mut writer);
+
+            for mut chunk in writer_data.chunks(num_rows) {
+                for i in 0..num_rows {
+                    let mut writer = chunk.window_writer(i);
+                    stark.air_data.write_trace_instructions(&mut writer);
+                }
+            }
+        });
+
+        let (trace, public) = (writer_data.trace, writer_data.public);
+
+        let proof = timed!(
+            timing,
+            "generate stark proof",
+            stark.prove(&trace, &public, &mut timing).unwrap()
+        );
+        stark.verify(proof.clone(), &public).unwrap();
+
+        // The verifier reads the work of the chain after each header.
+        struct ChainWork(Vec<BigUint>);
+        impl AirOutputs<GoldilocksField> for ChainWork {
+            fn from_outputs(outputs: &ProofOutputs<GoldilocksField>) -> Result<Self> {
+                read_chain_work(outputs, "chain_work").map(ChainWork)
+            }
+        }
+        let ChainWork(chain_work) = proof.outputs::<ChainWork, _>(&stark.stark).unwrap();
+        assert_eq!(chain_work, verify_chain(&headers).unwrap());
+
+        timing.print();
+    }
+}
@@ -0,0 +1,190 @@
+//! Verification of chains of Bitcoin block headers with the SHA-256 chip.
+//!
+//! A header is 80 bytes long, and the hash of its block is the double SHA-256 of the header. The
+//! header of a block holds the hash of the previous block in its bytes `4..36`, and the field
+//! `bits` in its bytes `72..76`, a compact encoding of the target which the hash must not exceed
+//! as a little-endian integer. The target of `bits` is `mantissa * 256^(exponent - 3)`, where the
+//! exponent is the last byte of `bits` and the mantissa is given by the other three, whose sign
+//! bit must be zero. The work of a block is `2^256 / (target + 1)`, the expected number of hashes
+//! needed to find a header meeting the target, and the work of a chain is the sum of the works of
+//! its blocks.
+//!
+//! [`builder::BitcoinHeaderBuilder`] proves these relations for a chain of headers, and exposes
+//! the work of the chain after each header as an output of the proof. Only exponents in
+//! `3..=32` are supported, which are those of every target of the network.
+
+use anyhow::{ensure, Result};
+use num::{BigUint, One, Zero};
+
+use crate::machine::hash::sha::algorithm::SHAPure;
+use crate::machine::hash::sha::sha256::{INITIAL_HASH, SHA256};
+
+pub mod builder;
+pub mod u256;
+
+/// The number of bytes of a header.
+pub const HEADER_BYTES: usize = 80;
+
+/// The range of exponents of the encoding of a target.
+pub(crate) const MIN_EXPONENT: u32 = 3;
+pub(crate) const MAX_EXPONENT: u32 = 32;
+
+/// The SHA-256 digest of `msg` as eight big-endian words.
+fn sha256(msg: &[u8]) -> [u32; 8] {
+    SHA256::pad(msg)
+        .chunks_exact(16)
+        .fold(INITIAL_HASH, |state, chunk| SHA256::compress(state, chunk))
+}
+
+/// The bytes of a digest given by its words.
+pub fn digest_bytes(digest: &[u32; 8]) -> [u8; 32] {
+    let bytes = digest
+        .iter()
+        .flat_map(|word| word.to_be_bytes())
+        .collect::<Vec<_>>();
+    bytes.try_into().unwrap()
+}
+
+/// The SHA-256 digest of the header, which is the message of the second hash.
+pub fn inner_digest(header: &[u8; HEADER_BYTES]) -> [u32; 8] {
+    sha256(header)
+}
+
+/// The hash of the block of the header, as the words of the digest of the second hash.
+pub fn block_hash(header: &[u8; HEADER_BYTES]) -> [u32; 8] {
+    sha256(&digest_bytes(&inner_digest(header)))
+}
+
+/// The value of a hash as a little-endian integer, which is compared to the target.
+pub fn hash_value(hash: &[u32; 8]) -> BigUint {
+    BigUint::from_bytes_le(&digest_bytes(hash))
+}
+
+/// The hash of the previous block held by the header, as the words of a digest.
+pub fn previous_hash(header: &[u8; HEADER_BYTES]) -> [u32; 8] {
+    core::array::from_fn(|i| u32::from_be_bytes(header[4 + 4 * i..8 + 4 * i].try_into().unwrap()))
+}
+
+/// The field `bits` of the header.
+pub fn bits(header: &[u8; HEADER_BYTES]) -> u32 {
+    u32::from_le_bytes(header[72..76].try_into().unwrap())
+}
+
+/// The target encoded by `bits`.
+///
+/// Panics if the exponent is not supported or if the sign bit of the mantissa is set.
+pub fn target(bits: u32) -> BigUint {
+    let exponent = bits >> 24;
+    let mantissa = bits & 0xffffff;
+    assert!(
+        (MIN_EXPONENT..=MAX_EXPONENT).contains(&exponent),
+        "Unsupported exponent {}",
+        exponent
+    );
+    assert!(mantissa < 0x800000, "The target {:#x} is negative", bits);
+    BigUint::from(mantissa) << (8 * (exponent - MIN_EXPONENT))
+}
+
+/// The work of a block with the given target, `2^256 / (target + 1)`.
+pub fn work(target: &BigUint) -> BigUint {
+    (BigUint::one() << 256) / (target + BigUint::one())
+}
+
+/// Checks that the headers form a chain whose hashes meet their targets, and returns the work of
+/// the chain after each header.
+pub fn verify_chain(headers: &[[u8; HEADER_BYTES]]) -> Result<Vec<BigUint>> {
+    let mut chain_work = BigUint::zero();
+    let mut previous: Option<[u32; 8]> = None;
+    headers
+        .iter()
+        .enumerate()
+        .map(|(i, header)| {
+            if let Some(hash) = previous {
+                ensure!(
+                    previous_hash(header) == hash,
+                    "Header {} does not extend the previous one",
+                    i
+                );
+            }
+            let hash = block_hash(header);
+            let target = target(bits(header));
+            ensure!(
+                hash_value(&hash) <= target,
+                "The hash of header {} exceeds its target",
+                i
+            );
+            chain_work += work(&target);
+            previous = Some(hash);
+            Ok(chain_work.clone())
+        })
+        .collect()
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+
+    /// The headers of the first three blocks of the network.
+    pub(crate) const HEADERS: [&str; 3] = [
+        "0100000000000000000000000000000000000000000000000000000000000000000000003ba3edfd7a7b12b27ac72c3e67768f617fc81bc3888a51323a9fb8aa4b1e5e4a29ab5f49ffff001d1dac2b7c",
+        "010000006fe28c0ab6f1b372c1a6a246ae63f74f931e8365e15a089c68d6190000000000982051fd1e4ba744bbbe680e1fee14677ba1a3c3540bf7b1cdb606e857233e0e61bc6649ffff001d01e36299",
+        "010000004860eb18bf1b1620e37e9490fc8a427514416fd75159ab86688e9a8300000000d5fdcc541e25de1c7a5addedf24858b8bb665c9f36ef744ee42c316022c90f9bb0bc6649ffff001d08d2bd61",
+    ];
+
+    pub(crate) fn headers() -> Vec<[u8; HEADER_BYTES]> {
+        HEADERS
+            .iter()
+            .map(|header| hex::decode(header).unwrap().try_into().unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn test_block_hash() {
+        // Hashes are displayed as big-endian integers, in the reverse order of their bytes.
+        let expected = [
+            "000000000019d6689c085ae165831e934ff763ae46a2a6c172b3f1b60a8ce26f",
+            "00000000839a8e6886ab5951d76f411475428afc90947ee320161bbf18eb6048",
+            "000000006a625f06636b8bb6ac7b960a8d03705d1ace08b1a19da3fdcc99ddbd",
+        ];
+        for (header, expected) in headers().iter().zip(expected) {
+            let mut bytes = digest_bytes(&block_hash(header));
+            bytes.reverse();
+            assert_eq!(hex::encode(bytes), expected);
+        }
+    }
+
+    #[test]
+    fn test_target_and_work() {
+        let target = target(0x1d00ffff);
+        assert_eq!(target, BigUint::from(0xffffu32) << 208);
+        assert_eq!(work(&target), BigUint::from(0x100010001u64));
+
+        // The smallest exponent does not shift the mantissa.
+        assert_eq!(super::target(0x03123456), BigUint::from(0x123456u32));
+        assert_eq!(super::target(0x207fffff), BigUint::from(0x7fffffu32) << 232);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_negative_target() {
+        target(0x1d800000);
+    }
+
+    #[test]
+    fn test_verify_chain() {
+        let headers = headers();
+        let chain_work = verify_chain(&headers).unwrap();
+        assert_eq!(
+            chain_work,
+            [0x100010001u64, 0x200020002, 0x300030003].map(BigUint::from)
+        );
+
+        // The headers do not form a chain in another order.
+        assert!(verify_chain(&[headers[0], headers[2]]).is_err());
+
+        // A header whose nonce is changed does not meet its target.
+        let mut header = headers[1];
+        header[79] ^= 1;
+        assert!(verify_chain(&[headers[0], header]).is_err());
+    }
+}
@@ -0,0 +1,185 @@
+//! Arithmetic of 256-bit integers held by public registers.
+//!
+//! An integer is given by its 32 little-endian bytes, which need not be contiguous, such as the
+//! bytes of a digest read as a little-endian integer. The constraints are global and relate the
+//! bytes of the operands through the carries between them, so that every equation holds over the
+//! integers. A comparison `a <= b` is the sum `a + (b - a) = b` without overflow.
+//!
+//! The gadgets range check their carries, while the bytes of the operands are range checked by the
+//! caller, with [`range_check`], unless they are known to be bytes. The carries are written by the
+//! prover from the values of the operands, as they may be computed by global instructions.
+
+use num::BigUint;
+use serde::{Deserialize, Serialize};
+
+use crate::chip::arithmetic::expression::ArithmeticExpression;
+use crate::chip::register::array::ArrayRegister;
+use crate::chip::register::bit::BitRegister;
+use crate::chip::register::Register;
+use crate::chip::trace::writer::AirWriter;
+use crate::chip::uint::bytes::operations::value::ByteOperation;
+use crate::chip::uint::bytes::register::ByteRegister;
+use crate::chip::uint::operations::instruction::UintInstructions;
+use crate::chip::AirParameters;
+use crate::machine::builder::Builder;
+use crate::machine::bytes::builder::BytesBuilder;
+use crate::math::prelude::*;
+
+/// The number of bytes of a 256-bit integer.
+pub const U256_BYTES: usize = 32;
+
+/// Range checks public bytes with the byte lookup table.
+pub fn range_check<L: AirParameters>(builder: &mut BytesBuilder<L>, bytes: &[ByteRegister])
+where
+    L::Instruction: UintInstructions,
+{
+    for byte in bytes {
+        builder.api.set_public_inputs_byte_operation(
+            &ByteOperation::Range(*byte),
+            &mut builder.operations,
+        );
+    }
+}
+
+/// The sum `a + b = sum` of 256-bit integers, which does not overflow.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct U256Add {
+    /// The carries out of the bytes, except the last one which is zero.
+    carries: ArrayRegister<BitRegister>,
+}
+
+impl U256Add {
+    pub fn new<L: AirParameters>(
+        builder: &mut BytesBuilder<L>,
+        a: &[ByteRegister],
+        b: &[ByteRegister],
+        sum: &[ByteRegister],
+    ) -> Self
+    where
+        L::Instruction: UintInstructions,
+    {
+        for operand in [a, b, sum] {
+            assert_eq!(operand.len(), U256_BYTES);
+        }
+        let carries = builder.alloc_array_public::<BitRegister>(U256_BYTES - 1);
+        for carry in carries.iter() {
+            builder.assert_expression_zero(carry.expr() * carry.not_expr());
+        }
+
+        // `a_k + b_k + carry_in = sum_k + carry_out * 2^8` on every byte.
+        let two_8 = L::Field::from_canonical_u32(1 << 8);
+        for (k, ((a_k, b_k), sum_k)) in a.iter().zip(b).zip(sum).enumerate() {
+            let mut lhs = a_k.expr() + b_k.expr();
+            if k > 0 {
+                lhs = lhs + carries.get(k - 1).expr();
+            }
+            let mut rhs = sum_k.expr();
+            if k < U256_BYTES - 1 {
+                rhs = rhs + carries.get(k).expr() * two_8;
+            }
+            builder.assert_expressions_equal(lhs, rhs);
+        }
+
+        Self { carries }
+    }
+
+    /// Writes the carries of the sum of the values `a` and `b`.
+    pub fn write_carries<W: AirWriter>(&self, writer: &mut W, a: &[u8; 32], b: &[u8; 32]) {
+        let mut carry = 0u32;
+        for (k, register) in self.carries.iter().enumerate() {
+            carry = (a[k] as u32 + b[k] as u32 + carry) >> 8;
+            writer.write(&register, &W::Field::from_canonical_u32(carry));
+        }
+    }
+}
+
+/// The product `a * b + c = result` of 256-bit integers `a`, `b`, `c` and a 512-bit `result`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct U256MulAdd {
+    /// The carries out of the columns of the product, except the last one which is zero, as
+    /// pairs of bytes.
+    carries: ArrayRegister<ByteRegister>,
+}
+
+impl U256MulAdd {
+    pub fn new<L: AirParameters>(
+        builder: &mut BytesBuilder<L>,
+        a: &[ByteRegister],
+        b: &[ByteRegister],
+        c: &[ByteRegister],
+        result: &[ByteRegister],
+    ) -> Self
+    where
+        L::Instruction: UintInstructions,
+    {
+        for operand in [a, b, c] {
+            assert_eq!(operand.len(), U256_BYTES);
+        }
+        assert_eq!(result.len(), 2 * U256_BYTES);
+
+        // The columns of the product are less than `2^21` and the carries less than `2^14`, so
+        // each carry takes two bytes and the equations hold over the integers.
+        let carries = builder.alloc_array_public::<ByteRegister>(2 * (2 * U256_BYTES - 1));
+        range_check(builder, &carries.iter().collect::<Vec<_>>());
+        let carry = |k: usize| {
+            carries.get(2 * k).expr()
+                + carries.get(2 * k + 1).expr() * L::Field::from_canonical_u32(1 << 8)
+        };
+
+        let two_8 = L::Field::from_canonical_u32(1 << 8);
+        for (k, result_k) in result.iter().enumerate() {
+            let mut lhs = (k.saturating_sub(U256_BYTES - 1)..=k.min(U256_BYTES - 1))
+                .map(|i| a[i].expr() * b[k - i].expr())
+                .reduce(|acc, x| acc + x)
+                .unwrap_or_else(ArithmeticExpression::zero);
+            if let Some(c_k) = c.get(k) {
+                lhs = lhs + c_k.expr();
+            }
+            if k > 0 {
+                lhs = lhs + carry(k - 1);
+            }
+            let mut rhs = result_k.expr();
+            if k < 2 * U256_BYTES - 1 {
+                rhs = rhs + carry(k) * two_8;
+            }
+            builder.assert_expressions_equal(lhs, rhs);
+        }
+
+        Self { carries }
+    }
+
+    /// Writes the carries of the product of the values `a` and `b` plus `c`.
+    pub fn write_carries<W: AirWriter>(
+        &self,
+        writer: &mut W,
+        a: &[u8; 32],
+        b: &[u8; 32],
+        c: &[u8; 32],
+    ) {
+        let mut carry = 0u64;
+        for k in 0..2 * U256_BYTES - 1 {
+            let column = (k.saturating_sub(U256_BYTES - 1)..=k.min(U256_BYTES - 1))
+                .map(|i| a[i] as u64 * b[k - i] as u64)
+                .sum::<u64>()
+                + c.get(k).map_or(0, |c_k| *c_k as u64)
+                + carry;
+            carry = column >> 8;
+            writer.write(
+                &self.carries.get(2 * k),
+                &W::Field::from_canonical_u64(carry & 0xff),
+            );
+            writer.write(
+                &self.carries.get(2 * k + 1),
+                &W::Field::from_canonical_u64(carry >> 8),
+            );
+        }
+    }
+}
+
+/// The 32 little-endian bytes of an integer less than `2^256`.
+pub fn u256_to_bytes(value: &BigUint) -> [u8; 32] {
+    let mut bytes = value.to_bytes_le();
+    assert!(bytes.len() <= U256_BYTES, "The value exceeds 256 bits");
+    bytes.resize(U256_BYTES, 0);
+    bytes.try_into().unwrap()
+}
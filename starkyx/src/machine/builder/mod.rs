@@ -1,6 +1,7 @@
 use self::ops::{Adc, Add, And, Div, Double, Mul, Neg, Not, One, Or, Shl, Shr, Sub, Xor, Zero};
 use crate::chip::arithmetic::expression::ArithmeticExpression;
 use crate::chip::builder::AirBuilder;
+#[cfg(feature = "ecc")]
 use crate::chip::ec::scalar::LimbBitInstruction;
 use crate::chip::instruction::cycle::Cycle;
 use crate::chip::instruction::Instruction;
@@ -133,6 +134,19 @@ pub trait Builder: Sized {
         self.api().free(ptr, value, last_write)
     }
 
+    /// Frees the memory at location `ptr` with last write time given by `last_write_ts`, counted
+    /// with multiplicity given by the value of `multiplicity`.
+    fn free_with_multiplicity<V: MemoryValue>(
+        &mut self,
+        ptr: &Pointer<V>,
+        value: V,
+        last_write: &Time<Self::Field>,
+        multiplicity: ElementRegister,
+    ) {
+        self.api()
+            .free_with_multiplicity(ptr, value, last_write, multiplicity)
+    }
+
     /// Prints out a log message (using the log::debug! macro) with the value and multiplicity
     /// of the memory slot.
     ///
@@ -425,6 +439,7 @@ pub trait Builder: Sized {
         self.api().process_id(size, end_bit)
     }
 
+    #[cfg(feature = "ecc")]
     fn bit_decomposition(
         &mut self,
         limb: ElementRegister,
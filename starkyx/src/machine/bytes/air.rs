@@ -18,25 +18,35 @@ use crate::plonky2::stark::Starky;
 use crate::prelude::AirParser;
 use crate::trace::AirTrace;
 
+/// The parameters of a byte lookup table split into `S` segments.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
-pub struct ByteParameters<F, E>(pub PhantomData<(F, E)>);
+pub struct ByteParameters<F, E, const S: usize = 1>(pub PhantomData<(F, E)>);
 
-impl<F: PrimeField64, E: CubicParameters<F>> AirParameters for ByteParameters<F, E> {
+impl<F: PrimeField64, E: CubicParameters<F>, const S: usize> AirParameters
+    for ByteParameters<F, E, S>
+{
     type Field = F;
     type CubicParams = E;
 
     type Instruction = UintInstruction;
 
     const NUM_ARITHMETIC_COLUMNS: usize = 0;
-    const NUM_FREE_COLUMNS: usize = 15;
-    const EXTENDED_COLUMNS: usize = 45;
+    const NUM_FREE_COLUMNS: usize = 17 * S;
+    const EXTENDED_COLUMNS: usize = 48 * S + 3;
+}
+
+impl<F: PrimeField64, E: CubicParameters<F>, const S: usize> ByteParameters<F, E, S> {
+    /// The number of multiplicity columns of the table.
+    pub const NUM_MULTIPLICITY_COLUMNS: usize = (NUM_BIT_OPPS + 1) * S;
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(bound = "")]
-pub struct ByteAir<F: PrimeField64, E: CubicParameters<F>>(pub(crate) Chip<ByteParameters<F, E>>);
+pub struct ByteAir<F: PrimeField64, E: CubicParameters<F>, const S: usize = 1>(
+    pub(crate) Chip<ByteParameters<F, E, S>>,
+);
 
-impl<F: PrimeField64, E: CubicParameters<F>> RAirData for ByteAir<F, E> {
+impl<F: PrimeField64, E: CubicParameters<F>, const S: usize> RAirData for ByteAir<F, E, S> {
     fn width(&self) -> usize {
         self.0.width()
     }
@@ -46,14 +56,15 @@ impl<F: PrimeField64, E: CubicParameters<F>> RAirData for ByteAir<F, E> {
     }
 
     fn round_data(&self) -> Vec<RoundDatum> {
-        let total = ByteParameters::<F, E>::num_columns();
+        let total = ByteParameters::<F, E, S>::num_columns();
+        let num_multiplicities = ByteParameters::<F, E, S>::NUM_MULTIPLICITY_COLUMNS;
         let execution_trace_length = self.0.execution_trace_length;
         let extended_trace_length = total - execution_trace_length;
 
         vec![
-            RoundDatum::new(NUM_BIT_OPPS + 1, (0, 0), 0),
+            RoundDatum::new(num_multiplicities, (0, 0), 0),
             RoundDatum::new(
-                execution_trace_length - (NUM_BIT_OPPS + 1),
+                execution_trace_length - num_multiplicities,
                 (0, 0),
                 self.0.num_challenges,
             ),
@@ -66,9 +77,10 @@ impl<F: PrimeField64, E: CubicParameters<F>> RAirData for ByteAir<F, E> {
     }
 }
 
-impl<AP: AirParser, F: PrimeField64, E: CubicParameters<F>> RAir<AP> for ByteAir<F, E>
+impl<AP: AirParser, F: PrimeField64, E: CubicParameters<F>, const S: usize> RAir<AP>
+    for ByteAir<F, E, S>
 where
-    Chip<ByteParameters<F, E>>: RAir<AP>,
+    Chip<ByteParameters<F, E, S>>: RAir<AP>,
 {
     fn eval(&self, parser: &mut AP) {
         self.0.eval(parser)
@@ -79,26 +91,27 @@ where
     }
 }
 
-pub fn get_preprocessed_byte_trace<F, E, C, const D: usize>(
+pub fn get_preprocessed_byte_trace<F, E, C, const D: usize, const S: usize>(
     lookup_writer: &TraceWriter<F>,
     lookup_config: &StarkyConfig<C, D>,
-    lookup_stark: &Starky<ByteAir<F, E>>,
+    lookup_stark: &Starky<ByteAir<F, E, S>>,
 ) -> PolynomialBatch<F, C::GenericConfig, D>
 where
     F: RichField + Extendable<D>,
     E: CubicParameters<F>,
     C: CurtaConfig<D, F = F>,
 {
+    let num_multiplicities = ByteParameters::<F, E, S>::NUM_MULTIPLICITY_COLUMNS;
     let lookup_execution_trace_values = lookup_writer
         .read_trace()
         .unwrap()
         .rows_par()
-        .flat_map(|row| row[(NUM_BIT_OPPS + 1)..lookup_stark.air.0.execution_trace_length].to_vec())
+        .flat_map(|row| row[num_multiplicities..lookup_stark.air.0.execution_trace_length].to_vec())
         .collect::<Vec<_>>();
 
     let lookup_execution_trace = AirTrace {
         values: lookup_execution_trace_values,
-        width: (lookup_stark.air.0.execution_trace_length - (NUM_BIT_OPPS + 1)),
+        width: (lookup_stark.air.0.execution_trace_length - num_multiplicities),
     };
 
     lookup_config.commit(&lookup_execution_trace, &mut TimingTree::default())
@@ -1,3 +1,4 @@
+use log::warn;
 use plonky2::field::extension::Extendable;
 use plonky2::hash::hash_types::RichField;
 
@@ -7,13 +8,22 @@ use crate::chip::builder::AirBuilder;
 use crate::chip::register::element::ElementRegister;
 use crate::chip::trace::writer::TraceWriter;
 use crate::chip::uint::bytes::lookup_table::builder_operations::ByteLookupOperations;
+use crate::chip::uint::bytes::lookup_table::multiplicity_data::NUM_TABLE_ENTRIES;
 use crate::chip::uint::operations::instruction::UintInstructions;
 use crate::chip::AirParameters;
 use crate::machine::builder::Builder;
 use crate::plonky2::stark::config::{CurtaConfig, StarkyConfig};
 use crate::plonky2::stark::Starky;
 
-pub(crate) const NUM_LOOKUP_ROWS: usize = 1 << 16;
+/// The number of segments of the byte lookup table for which the lookup STARK has as many rows
+/// as a trace of `num_rows` rows, or a single segment if the trace has at least `2^16` rows.
+pub const fn lookup_segments(num_rows: usize) -> usize {
+    if num_rows >= NUM_TABLE_ENTRIES {
+        1
+    } else {
+        NUM_TABLE_ENTRIES / num_rows
+    }
+}
 
 pub struct BytesBuilder<L: AirParameters> {
     pub api: AirBuilder<L>,
@@ -51,6 +61,24 @@ where
         }
     }
 
+    /// The number of segments of the byte lookup table suited to a trace of `num_rows` rows.
+    ///
+    /// A chip without byte operations only needs a single segment. Otherwise, the table is split
+    /// so that the lookup STARK is no longer than the trace, as given by `lookup_segments`.
+    pub fn num_lookup_segments(&self, num_rows: usize) -> usize {
+        if self.operations.trace_operations.is_empty() {
+            1
+        } else {
+            lookup_segments(num_rows)
+        }
+    }
+
+    /// Builds the STARK with a byte lookup table of a single segment.
+    ///
+    /// The number of segments fixes the columns of the lookup STARK, which are constants of its
+    /// `AirParameters`, so it can not be chosen at runtime. When the byte operations of a trace of
+    /// `num_rows` rows call for more segments, a warning is logged with the number to pass to
+    /// `build_with_lookup_segments`.
     pub fn build<C: CurtaConfig<D, F = L::Field>, const D: usize>(
         self,
         num_rows: usize,
@@ -58,6 +86,34 @@ where
     where
         L::Field: RichField + Extendable<D>,
     {
+        self.build_with_lookup_segments::<C, D, 1>(num_rows)
+    }
+
+    /// Builds the STARK with a byte lookup table split into `S` segments, so that the lookup
+    /// STARK has `2^16 / S` rows.
+    ///
+    /// A single segment is best when the trace has at least `2^16` rows. For shorter traces,
+    /// `S = lookup_segments(num_rows)` gives a lookup STARK of the same length as the trace.
+    /// The segment count suited to the byte operations of the chip is `num_lookup_segments`.
+    pub fn build_with_lookup_segments<
+        C: CurtaConfig<D, F = L::Field>,
+        const D: usize,
+        const S: usize,
+    >(
+        self,
+        num_rows: usize,
+    ) -> ByteStark<L, C, D, S>
+    where
+        L::Field: RichField + Extendable<D>,
+    {
+        let num_lookup_rows = NUM_TABLE_ENTRIES / S;
+        let num_segments = self.num_lookup_segments(num_rows);
+        if num_segments != S {
+            warn!(
+                "Building the byte lookup table with {} segments, {} are suited to a trace of {} rows",
+                S, num_segments, num_rows
+            );
+        }
         let BytesBuilder {
             mut api,
             operations,
@@ -65,30 +121,33 @@ where
         } = self;
         let shared_memory = api.shared_memory.clone();
         let mut lookup_builder =
-            AirBuilder::<ByteParameters<L::Field, L::CubicParams>>::init(shared_memory);
+            AirBuilder::<ByteParameters<L::Field, L::CubicParams, S>>::init(shared_memory);
 
-        let mut lookup_table = lookup_builder.new_byte_lookup_table();
+        let mut lookup_table = lookup_builder.new_segmented_byte_lookup_table(S);
         let multiplicity_data = api.register_byte_lookup(&mut lookup_table, operations);
         lookup_builder.constraint_byte_lookup_table(&lookup_table);
 
         let config = StarkyConfig::<C, D>::standard_fast_config(num_rows);
         let (air, trace_data) = api.build();
-        let stark = Starky::new(air);
+        if let Err(e) = trace_data.check_num_rows(num_rows) {
+            panic!("{}", e);
+        }
+        let stark = Starky::from_chip(air);
 
-        let lookup_config = StarkyConfig::<C, D>::standard_fast_config(NUM_LOOKUP_ROWS);
+        let lookup_config = StarkyConfig::<C, D>::standard_fast_config(num_lookup_rows);
         let (lookup_air, lookup_trace_data) = lookup_builder.build();
         let lookup_stark = Starky::new(ByteAir(lookup_air));
 
         // Get the commitment to the preprocessed byte trace.
-        let lookup_writer = TraceWriter::new(&lookup_trace_data, NUM_LOOKUP_ROWS);
+        let lookup_writer = TraceWriter::new(&lookup_trace_data, num_lookup_rows);
         // Write lookup table values
         lookup_table.write_table_entries(&lookup_writer);
-        for i in 0..NUM_LOOKUP_ROWS {
+        for i in 0..num_lookup_rows {
             lookup_writer.write_row_instructions(&lookup_trace_data, i);
         }
         // Generate the preprocesswed trace commitment
         let lookup_preprocessed_commitment =
-            get_preprocessed_byte_trace::<L::Field, L::CubicParams, C, D>(
+            get_preprocessed_byte_trace::<L::Field, L::CubicParams, C, D, S>(
                 &lookup_writer,
                 &lookup_config,
                 &lookup_stark,
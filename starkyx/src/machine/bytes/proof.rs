@@ -1,11 +1,14 @@
+use anyhow::Result;
 use plonky2::field::extension::Extendable;
 use plonky2::hash::hash_types::RichField;
 use plonky2::iop::target::Target;
 
 use crate::plonky2::stark::config::CurtaConfig;
+use crate::plonky2::stark::outputs::{AirOutputs, ProofOutputs};
 use crate::plonky2::stark::proof::{
     AirProof, AirProofTarget, StarkProofChallenges, StarkProofChallengesTarget,
 };
+use crate::plonky2::stark::Starky;
 
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct ByteStarkProof<F: RichField + Extendable<D>, C: CurtaConfig<D, F = F>, const D: usize> {
@@ -14,6 +17,18 @@ pub struct ByteStarkProof<F: RichField + Extendable<D>, C: CurtaConfig<D, F = F>
     pub global_values: Vec<F>,
 }
 
+impl<F: RichField + Extendable<D>, C: CurtaConfig<D, F = F>, const D: usize>
+    ByteStarkProof<F, C, D>
+{
+    /// Reads the outputs of the proof of the main STARK `stark` from the global values.
+    pub fn outputs<O: AirOutputs<F>, A>(&self, stark: &Starky<A>) -> Result<O> {
+        O::from_outputs(&ProofOutputs::new(
+            &stark.output_layout,
+            &self.global_values,
+        ))
+    }
+}
+
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct ByteStarkProofTarget<const D: usize> {
     pub main_proof: AirProofTarget<D>,
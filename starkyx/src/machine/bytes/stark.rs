@@ -1,40 +1,56 @@
+#[cfg(feature = "prover")]
+use anyhow::ensure;
 use anyhow::Result;
 use plonky2::field::extension::Extendable;
-use plonky2::fri::oracle::PolynomialBatch;
 use plonky2::hash::hash_types::RichField;
 use plonky2::hash::merkle_tree::MerkleCap;
-use plonky2::iop::challenger::{Challenger, RecursiveChallenger};
+#[cfg(feature = "prover")]
+use plonky2::iop::challenger::Challenger;
 use plonky2::iop::target::Target;
 use plonky2::iop::witness::WitnessWrite;
 use plonky2::plonk::circuit_builder::CircuitBuilder;
+use plonky2::plonk::config::AlgebraicHasher;
+#[cfg(feature = "prover")]
 use plonky2::timed;
+#[cfg(feature = "prover")]
 use plonky2::util::timing::TimingTree;
 use serde::{Deserialize, Serialize};
 
-use super::air::{get_preprocessed_byte_trace, ByteAir, ByteParameters};
+#[cfg(feature = "prover")]
+use super::air::get_preprocessed_byte_trace;
+use super::air::{ByteAir, ByteParameters};
 use super::proof::{
     ByteStarkChallenges, ByteStarkChallengesTarget, ByteStarkProof, ByteStarkProofTarget,
 };
 use crate::chip::trace::data::AirTraceData;
+#[cfg(feature = "prover")]
 use crate::chip::trace::writer::{InnerWriterData, TraceWriter};
-use crate::chip::uint::bytes::lookup_table::multiplicity_data::ByteMultiplicityData;
+use crate::chip::uint::bytes::lookup_table::multiplicity_data::{
+    ByteMultiplicityData, NUM_TABLE_ENTRIES,
+};
 use crate::chip::uint::bytes::lookup_table::table::ByteLogLookupTable;
-use crate::chip::uint::bytes::operations::NUM_BIT_OPPS;
 use crate::chip::{AirParameters, Chip};
-use crate::machine::bytes::builder::NUM_LOOKUP_ROWS;
+#[cfg(feature = "prover")]
 use crate::maybe_rayon::*;
 use crate::plonky2::stark::config::{CurtaConfig, StarkyConfig};
+#[cfg(feature = "prover")]
 use crate::plonky2::stark::prover::{AirCommitment, StarkyProver};
+#[cfg(feature = "prover")]
+use crate::plonky2::stark::table::CommittedTable;
 use crate::plonky2::stark::verifier::{
-    add_virtual_air_proof, set_air_proof_target, StarkyVerifier,
+    add_virtual_air_proof, set_air_proof_target, StarkyVerifier, VerificationError,
 };
 use crate::plonky2::stark::Starky;
 use crate::plonky2::Plonky2Air;
+#[cfg(feature = "prover")]
+use crate::plonky2::ProverAir;
+#[cfg(feature = "prover")]
 use crate::trace::AirTrace;
 
+/// A STARK with a byte lookup table of `S` segments in a separate lookup STARK.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(bound = "")]
-pub struct ByteStark<L: AirParameters, C, const D: usize>
+pub struct ByteStark<L: AirParameters, C, const D: usize, const S: usize = 1>
 where
     L::Field: RichField,
     C: CurtaConfig<D, F = L::Field>,
@@ -45,12 +61,12 @@ where
     pub byte_trace_cap: MerkleCap<L::Field, C::Hasher>,
     pub(crate) multiplicity_data: ByteMultiplicityData,
     pub(crate) lookup_config: StarkyConfig<C, D>,
-    pub(crate) lookup_stark: Starky<ByteAir<L::Field, L::CubicParams>>,
-    pub(crate) lookup_air_data: AirTraceData<ByteParameters<L::Field, L::CubicParams>>,
+    pub(crate) lookup_stark: Starky<ByteAir<L::Field, L::CubicParams, S>>,
+    pub(crate) lookup_air_data: AirTraceData<ByteParameters<L::Field, L::CubicParams, S>>,
     pub(crate) lookup_table: ByteLogLookupTable<L::Field, L::CubicParams>,
 }
 
-impl<L: AirParameters, C, const D: usize> ByteStark<L, C, D>
+impl<L: AirParameters, C, const D: usize, const S: usize> ByteStark<L, C, D, S>
 where
     L::Field: RichField + Extendable<D>,
     C: CurtaConfig<D, F = L::Field, FE = <L::Field as Extendable<D>>::Extension>,
@@ -64,21 +80,36 @@ where
         &self.config
     }
 
-    pub const fn lookup_stark(&self) -> &Starky<ByteAir<L::Field, L::CubicParams>> {
+    pub const fn lookup_stark(&self) -> &Starky<ByteAir<L::Field, L::CubicParams, S>> {
         &self.lookup_stark
     }
 
+    /// The number of rows of the lookup STARK.
+    pub const fn lookup_num_rows(&self) -> usize {
+        NUM_TABLE_ENTRIES / S
+    }
+
     pub const fn lookup_config(&self) -> &StarkyConfig<C, D> {
         &self.lookup_config
     }
 
-    fn get_preprocessed_byte_trace(
-        &self,
-        lookup_writer: &TraceWriter<L::Field>,
-    ) -> PolynomialBatch<L::Field, C::GenericConfig, D> {
-        get_preprocessed_byte_trace(lookup_writer, &self.lookup_config, &self.lookup_stark)
+    /// Commits to the byte lookup table, so that it can be shared by many proofs with
+    /// `prove_with_lookup_table`.
+    #[cfg(feature = "prover")]
+    pub fn commit_lookup_table(&self) -> CommittedTable<L::Field, C, D> {
+        let lookup_writer = TraceWriter::new(&self.lookup_air_data, self.lookup_num_rows());
+        self.lookup_table.write_table_entries(&lookup_writer);
+        for i in 0..self.lookup_num_rows() {
+            lookup_writer.write_row_instructions(&self.lookup_air_data, i);
+        }
+        CommittedTable::from_batch(get_preprocessed_byte_trace(
+            &lookup_writer,
+            &self.lookup_config,
+            &self.lookup_stark,
+        ))
     }
 
+    #[cfg(feature = "prover")]
     fn generate_execution_traces(
         &self,
         execution_trace: &AirTrace<L::Field>,
@@ -86,7 +117,7 @@ where
     ) -> (TraceWriter<L::Field>, TraceWriter<L::Field>) {
         // Initialize writers.
         let main_writer = TraceWriter::new(&self.air_data, execution_trace.height());
-        let lookup_writer = TraceWriter::new(&self.lookup_air_data, NUM_LOOKUP_ROWS);
+        let lookup_writer = TraceWriter::new(&self.lookup_air_data, self.lookup_num_rows());
 
         // Insert execution trace and into main writer.
         let execution_trace_length = self.stark.air.execution_trace_length;
@@ -111,7 +142,7 @@ where
 
         // Write lookup table values
         self.lookup_table.write_table_entries(&lookup_writer);
-        for i in 0..NUM_LOOKUP_ROWS {
+        for i in 0..self.lookup_num_rows() {
             lookup_writer.write_row_instructions(&self.lookup_air_data, i);
         }
         // Write multiplicities
@@ -122,6 +153,7 @@ where
         (main_writer, lookup_writer)
     }
 
+    #[cfg(feature = "prover")]
     fn generate_extended_traces(
         &self,
         main_writer: &TraceWriter<L::Field>,
@@ -147,8 +179,10 @@ where
             .copy_from_slice(&main_writer.global.read().unwrap());
     }
 
+    #[cfg(feature = "prover")]
     fn generate_trace(
         &self,
+        table: &CommittedTable<L::Field, C, D>,
         execution_trace: &AirTrace<L::Field>,
         public_values: &[L::Field],
         challenger: &mut Challenger<L::Field, C::Hasher>,
@@ -172,22 +206,20 @@ where
             width: self.stark.air.execution_trace_length,
         };
 
-        let lookup_preprocessed_commitment = timed!(
-            timing,
-            "Preprocess lookup trace",
-            self.get_preprocessed_byte_trace(&lookup_writer)
-        );
+        let lookup_preprocessed_commitment = table.batch();
 
+        let num_multiplicities =
+            ByteParameters::<L::Field, L::CubicParams, S>::NUM_MULTIPLICITY_COLUMNS;
         let lookup_multiplicity_trace_values = lookup_writer
             .read_trace()
             .unwrap()
             .rows_par()
-            .flat_map(|row| row[0..NUM_BIT_OPPS + 1].to_vec())
+            .flat_map(|row| row[0..num_multiplicities].to_vec())
             .collect::<Vec<_>>();
 
         let lookup_multiplicity_trace = AirTrace {
             values: lookup_multiplicity_trace_values,
-            width: NUM_BIT_OPPS + 1,
+            width: num_multiplicities,
         };
 
         // Commit to execution traces
@@ -262,7 +294,7 @@ where
             .collect::<Vec<_>>();
         let lookup_extended_trace = AirTrace {
             values: lookup_extended_trace_values,
-            width: ByteParameters::<L::Field, L::CubicParams>::num_columns()
+            width: ByteParameters::<L::Field, L::CubicParams, S>::num_columns()
                 - self.lookup_stark.air.0.execution_trace_length,
         };
         let lookup_extended_commitment = timed!(
@@ -298,20 +330,52 @@ where
         )
     }
 
+    #[cfg(feature = "prover")]
     pub fn prove(
         &self,
         execution_trace: &AirTrace<L::Field>,
         public_values: &[L::Field],
         timing: &mut TimingTree,
-    ) -> Result<ByteStarkProof<L::Field, C, D>> {
+    ) -> Result<ByteStarkProof<L::Field, C, D>>
+    where
+        Chip<L>: ProverAir<L::Field, C, D>,
+    {
+        let table = timed!(timing, "Commit to lookup table", self.commit_lookup_table());
+        self.prove_with_lookup_table(&table, execution_trace, public_values, timing)
+    }
+
+    /// Proves the execution trace, reusing the commitment `table` to the byte lookup table instead
+    /// of committing to it again.
+    #[cfg(feature = "prover")]
+    pub fn prove_with_lookup_table(
+        &self,
+        table: &CommittedTable<L::Field, C, D>,
+        execution_trace: &AirTrace<L::Field>,
+        public_values: &[L::Field],
+        timing: &mut TimingTree,
+    ) -> Result<ByteStarkProof<L::Field, C, D>>
+    where
+        Chip<L>: ProverAir<L::Field, C, D>,
+    {
+        ensure!(
+            table.cap() == &self.byte_trace_cap,
+            "The committed table is not the byte lookup table of the STARK"
+        );
+
         // Initialize challenger.
-        let mut challenger = Challenger::new();
+        let mut challenger = self.config.challenger();
 
         // Generate stark commitment.
         let (main_air_commitment, lookup_air_commitment) = timed!(
             timing,
             "Generate stark trace",
-            self.generate_trace(execution_trace, public_values, &mut challenger, timing)
+            self.generate_trace(
+                table,
+                execution_trace,
+                public_values,
+                &mut challenger,
+                timing
+            )
         );
 
         // Generate individual stark proofs.
@@ -353,7 +417,7 @@ where
         public_values: &[L::Field],
     ) -> ByteStarkChallenges<L::Field, D> {
         // Initialize challenger.
-        let mut challenger = Challenger::<L::Field, C::Hasher>::new();
+        let mut challenger = self.config.challenger();
 
         // Observe public values.
         challenger.observe_elements(public_values);
@@ -377,13 +441,13 @@ where
         // Get all challenges.
         let main_challenges = proof.main_proof.get_iop_challenges(
             &self.config,
-            self.config.degree_bits,
+            self.config.fri_degree_bits(),
             challenges.clone(),
             &mut challenger,
         );
         let lookup_challenges = proof.lookup_proof.get_iop_challenges(
             &self.lookup_config,
-            self.lookup_config.degree_bits,
+            self.lookup_config.fri_degree_bits(),
             challenges,
             &mut challenger,
         );
@@ -411,7 +475,9 @@ where
         } = proof;
 
         // Verify that the byte lookup table matches the preprocessed value.
-        assert_eq!(lookup_proof.trace_caps[1], self.byte_trace_cap);
+        if lookup_proof.trace_caps[1] != self.byte_trace_cap {
+            return Err(VerificationError::PreprocessedCapMismatch.into());
+        }
 
         // Verify the main AIR proof.
         StarkyVerifier::verify_with_challenges(
@@ -430,7 +496,8 @@ where
             public_values,
             &global_values,
             lookup_challenges,
-        )
+        )?;
+        Ok(())
     }
 
     pub fn add_virtual_proof_with_pis_target(
@@ -461,7 +528,7 @@ where
         public_values: &[Target],
     ) -> ByteStarkChallengesTarget<D> {
         // Initialize challenger.
-        let mut challenger = RecursiveChallenger::<L::Field, C::InnerHasher, D>::new(builder);
+        let mut challenger = self.config.recursive_challenger(builder);
 
         // Observe public values.
         challenger.observe_elements(public_values);
@@ -507,7 +574,9 @@ where
         builder: &mut CircuitBuilder<L::Field, D>,
         proof: &ByteStarkProofTarget<D>,
         public_values: &[Target],
-    ) {
+    ) where
+        C::Hasher: AlgebraicHasher<L::Field>,
+    {
         let challenges = self.get_challenges_target(builder, proof, public_values);
         let ByteStarkProofTarget {
             main_proof,
@@ -545,7 +614,10 @@ where
         witness: &mut W,
         proof_tagret: &ByteStarkProofTarget<D>,
         proof: ByteStarkProof<L::Field, C, D>,
-    ) -> Result<()> {
+    ) -> Result<()>
+    where
+        C::Hasher: AlgebraicHasher<L::Field>,
+    {
         let ByteStarkProofTarget {
             main_proof,
             lookup_proof,
@@ -575,7 +647,7 @@ mod tests {
     use crate::chip::uint::register::U32Register;
     use crate::chip::uint::util::u32_to_le_field_bytes;
     use crate::machine::builder::Builder;
-    use crate::machine::bytes::builder::BytesBuilder;
+    use crate::machine::bytes::builder::{lookup_segments, BytesBuilder};
     use crate::math::goldilocks::cubic::GoldilocksCubicParameters;
     use crate::math::prelude::*;
     use crate::plonky2::stark::config::CurtaPoseidonGoldilocksConfig;
@@ -655,6 +727,117 @@ mod tests {
         timing.print();
     }
 
+    #[test]
+    fn test_byte_multi_stark_segmented_table() {
+        type L = ByteTest;
+        type C = CurtaPoseidonGoldilocksConfig;
+
+        let _ = env_logger::builder().is_test(true).try_init();
+
+        let mut timing =
+            TimingTree::new("test_byte_multi_stark_segmented_table", log::Level::Debug);
+
+        let mut builder = BytesBuilder::<L>::new();
+
+        let a = builder.alloc::<U32Register>();
+        let b = builder.alloc::<U32Register>();
+        let _ = builder.and(&a, &b);
+
+        const NUM_ROWS: usize = 1 << 12;
+        assert_eq!(
+            builder.num_lookup_segments(NUM_ROWS),
+            lookup_segments(NUM_ROWS)
+        );
+        let stark =
+            builder.build_with_lookup_segments::<C, 2, { lookup_segments(NUM_ROWS) }>(NUM_ROWS);
+        assert_eq!(stark.lookup_num_rows(), NUM_ROWS);
+
+        let writer = TraceWriter::new(&stark.air_data, NUM_ROWS);
+
+        let mut rng = rand::thread_rng();
+        for i in 0..NUM_ROWS {
+            let a_val = rng.gen::<u32>();
+            let b_val = rng.gen::<u32>();
+            writer.write(&a, &u32_to_le_field_bytes(a_val), i);
+            writer.write(&b, &u32_to_le_field_bytes(b_val), i);
+            writer.write_row_instructions(&stark.air_data, i);
+        }
+
+        let InnerWriterData { trace, public, .. } = writer.into_inner().unwrap();
+        let proof = stark.prove(&trace, &public, &mut timing).unwrap();
+
+        stark.verify(proof, &public).unwrap();
+
+        timing.print();
+    }
+
+    #[test]
+    fn test_byte_multi_stark_shared_lookup_table() {
+        type L = ByteTest;
+        type C = CurtaPoseidonGoldilocksConfig;
+
+        let _ = env_logger::builder().is_test(true).try_init();
+
+        let mut timing = TimingTree::new(
+            "test_byte_multi_stark_shared_lookup_table",
+            log::Level::Debug,
+        );
+
+        let mut builder = BytesBuilder::<L>::new();
+
+        let a = builder.alloc::<U32Register>();
+        let b = builder.alloc::<U32Register>();
+        let _ = builder.and(&a, &b);
+
+        let num_rows = 1 << 5;
+        let stark = builder.build::<C, 2>(num_rows);
+
+        // The table is committed to once and shared by all the proofs.
+        let table = stark.commit_lookup_table();
+        assert_eq!(table.cap(), &stark.byte_trace_cap);
+
+        let mut rng = rand::thread_rng();
+        let mut proofs = vec![];
+        for _ in 0..3 {
+            let writer = TraceWriter::new(&stark.air_data, num_rows);
+            for i in 0..num_rows {
+                writer.write(&a, &u32_to_le_field_bytes(rng.gen::<u32>()), i);
+                writer.write(&b, &u32_to_le_field_bytes(rng.gen::<u32>()), i);
+                writer.write_row_instructions(&stark.air_data, i);
+            }
+            let InnerWriterData { trace, public, .. } = writer.into_inner().unwrap();
+            let proof = stark
+                .prove_with_lookup_table(&table, &trace, &public, &mut timing)
+                .unwrap();
+            assert_eq!(proof.lookup_proof.trace_caps[1], stark.byte_trace_cap);
+            stark.verify(proof.clone(), &public).unwrap();
+            proofs.push((proof, public));
+        }
+
+        // A proof with a different table is rejected.
+        let (mut proof, public) = proofs.pop().unwrap();
+        proof.lookup_proof.trace_caps[1] = proof.lookup_proof.trace_caps[0].clone();
+        let error = stark.verify(proof, &public).unwrap_err();
+        assert_eq!(
+            error.downcast_ref::<VerificationError>(),
+            Some(&VerificationError::PreprocessedCapMismatch)
+        );
+
+        // A table of another STARK can not be used.
+        let mut other_builder = BytesBuilder::<L>::new();
+        let a = other_builder.alloc::<U32Register>();
+        let b = other_builder.alloc::<U32Register>();
+        let _ = other_builder.xor(&a, &b);
+        let other_stark =
+            other_builder.build_with_lookup_segments::<C, 2, { lookup_segments(1 << 12) }>(1 << 12);
+        let other_table = other_stark.commit_lookup_table();
+        let writer = TraceWriter::new(&stark.air_data, num_rows);
+        let InnerWriterData { trace, public, .. } = writer.into_inner().unwrap();
+        assert!(stark
+            .prove_with_lookup_table(&other_table, &trace, &public, &mut timing)
+            .is_err());
+    }
+
     #[derive(Debug, Clone, Serialize, Deserialize)]
     pub struct ByteMemTest;
 
@@ -0,0 +1,62 @@
+use plonky2::field::goldilocks_field::GoldilocksField;
+
+use super::pure::{mds_layer, round_constants};
+use super::{is_full_round, NUM_ROUNDS, WIDTH};
+use crate::chip::arithmetic::expression::ArithmeticExpression;
+use crate::chip::register::array::ArrayRegister;
+use crate::chip::register::element::ElementRegister;
+use crate::chip::register::Register;
+use crate::machine::builder::Builder;
+use crate::math::prelude::*;
+
+/// Computes the Poseidon permutation of plonky2 of `input` in the current row.
+///
+/// The S-box `x^7` is computed with two registers of degree 3, `x^3` and `x^7 = (x^3)^2 * x`, and
+/// the state is committed after every partial round so that the constraints of the next rounds
+/// only refer to the committed registers. The input expressions must have degree 1.
+pub fn permutation<B: Builder<Field = GoldilocksField>>(
+    builder: &mut B,
+    input: &[ArithmeticExpression<GoldilocksField>],
+) -> ArrayRegister<ElementRegister> {
+    assert_eq!(input.len(), WIDTH);
+    let mut state = input.to_vec();
+    for round in 0..NUM_ROUNDS {
+        let constants = round_constants(round);
+        if is_full_round(round) {
+            let sbox_state = state
+                .iter()
+                .zip(constants.iter())
+                .map(|(x, &c)| {
+                    sbox(builder, x.clone() + GoldilocksField::from_canonical_u64(c)).expr()
+                })
+                .collect::<Vec<_>>();
+            state = mds_layer(&sbox_state);
+        } else {
+            let mut sbox_state = state
+                .iter()
+                .zip(constants.iter())
+                .map(|(x, &c)| x.clone() + GoldilocksField::from_canonical_u64(c))
+                .collect::<Vec<_>>();
+            sbox_state[0] = sbox(builder, sbox_state[0].clone()).expr();
+            let next_state = builder.alloc_array::<ElementRegister>(WIDTH);
+            for (register, expression) in next_state.iter().zip(mds_layer(&sbox_state)) {
+                builder.set_to_expression(&register, expression);
+            }
+            state = next_state.iter().map(|register| register.expr()).collect();
+        }
+    }
+
+    let output = builder.alloc_array::<ElementRegister>(WIDTH);
+    for (register, expression) in output.iter().zip(state) {
+        builder.set_to_expression(&register, expression);
+    }
+    output
+}
+
+fn sbox<B: Builder<Field = GoldilocksField>>(
+    builder: &mut B,
+    x: ArithmeticExpression<GoldilocksField>,
+) -> ElementRegister {
+    let x_3 = builder.expression::<ElementRegister>(x.clone() * x.clone() * x.clone());
+    builder.expression(x_3.expr() * x_3.expr() * x)
+}
@@ -0,0 +1,334 @@
+use plonky2::field::goldilocks_field::GoldilocksField;
+use plonky2::util::log2_ceil;
+
+use super::air::permutation;
+use super::pure::{ChallengerOp, DuplexSchedule};
+use super::{RATE, WIDTH};
+use crate::chip::memory::time::Time;
+use crate::chip::register::bit::BitRegister;
+use crate::chip::register::element::ElementRegister;
+use crate::chip::register::Register;
+use crate::chip::trace::writer::AirWriter;
+use crate::machine::builder::Builder;
+use crate::machine::hash::poseidon2::air::load_row_value;
+use crate::math::prelude::*;
+
+/// An element observed by the challenger.
+#[derive(Debug, Clone, Copy)]
+enum Observation {
+    Public(ElementRegister),
+    /// A constant, whose value is known before the global instructions are written.
+    Constant(ElementRegister, GoldilocksField),
+}
+
+impl Observation {
+    fn register(&self) -> ElementRegister {
+        match self {
+            Observation::Public(register) | Observation::Constant(register, _) => *register,
+        }
+    }
+}
+
+/// A challenger whose challenges are those of the plonky2 `Challenger` observing the same
+/// elements, see [`super`].
+///
+/// The operations are recorded by [`AirChallenger::observe_element`] and
+/// [`AirChallenger::get_challenge`], and the duplexings of the sponge are computed by
+/// [`AirChallenger::finalize`], one in each row of a trace of `AirChallenger::num_rows` rows. The
+/// observed elements must be public, and the challenges are public registers, which can be used
+/// in the constraints of the trace. They are written by [`AirChallenger::write_challenges`] once
+/// the observed elements are written, before the global instructions.
+#[derive(Debug, Clone, Default)]
+pub struct AirChallenger {
+    ops: Vec<ChallengerOp>,
+    observations: Vec<Observation>,
+    challenges: Vec<ElementRegister>,
+}
+
+impl AirChallenger {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Observes the public element `element`.
+    pub fn observe_element(&mut self, element: ElementRegister) {
+        assert!(!element.is_trace(), "The observed elements must be public");
+        self.ops.push(ChallengerOp::Observe);
+        self.observations.push(Observation::Public(element));
+    }
+
+    pub fn observe_elements(&mut self, elements: impl IntoIterator<Item = ElementRegister>) {
+        for element in elements {
+            self.observe_element(element);
+        }
+    }
+
+    /// Observes the constant `value`, such as a domain separator.
+    pub fn observe_constant<B: Builder<Field = GoldilocksField>>(
+        &mut self,
+        builder: &mut B,
+        value: GoldilocksField,
+    ) {
+        let register = builder.constant(&value);
+        self.ops.push(ChallengerOp::Observe);
+        self.observations
+            .push(Observation::Constant(register, value));
+    }
+
+    /// Returns a public register holding the next challenge.
+    pub fn get_challenge<B: Builder<Field = GoldilocksField>>(
+        &mut self,
+        builder: &mut B,
+    ) -> ElementRegister {
+        let challenge = builder.alloc_public();
+        self.ops.push(ChallengerOp::Challenge);
+        self.challenges.push(challenge);
+        challenge
+    }
+
+    pub fn get_n_challenges<B: Builder<Field = GoldilocksField>>(
+        &mut self,
+        builder: &mut B,
+        n: usize,
+    ) -> Vec<ElementRegister> {
+        (0..n).map(|_| self.get_challenge(builder)).collect()
+    }
+
+    /// The duplexings of the operations recorded so far.
+    pub fn schedule(&self) -> DuplexSchedule {
+        DuplexSchedule::new(&self.ops)
+    }
+
+    /// The number of rows of the trace, which must be the length of the trace of the AIR.
+    pub fn num_rows(&self) -> usize {
+        1 << log2_ceil(self.schedule().num_duplexings())
+    }
+
+    /// Constrains the challenges to be the elements squeezed from the sponge.
+    ///
+    /// The row of index `i` computes the duplexing of index `i`. Its state is the state after the
+    /// permutation of the previous row, whose first elements are overwritten by the observed
+    /// elements of the duplexing, which are loaded from memory along with the bits telling which
+    /// elements are overwritten. The elements of the permuted state which are challenges are
+    /// stored in memory, at the time of the row, and the challenges are freed from there. The rows
+    /// after the last duplexing permute the state without inputs, and their outputs are ignored.
+    ///
+    /// This must be called once, after the last operation of the challenger.
+    pub fn finalize<B: Builder<Field = GoldilocksField>>(&self, builder: &mut B) {
+        let schedule = self.schedule();
+        assert!(
+            schedule.num_duplexings() > 0,
+            "The challenger has no challenges"
+        );
+        let num_rows = self.num_rows();
+        let clk = builder.clk();
+
+        let zero = builder.constant::<ElementRegister>(&GoldilocksField::ZERO);
+        let one = builder.constant::<ElementRegister>(&GoldilocksField::ONE);
+        let zero_bit = builder.constant::<BitRegister>(&GoldilocksField::ZERO);
+        let one_bit = builder.constant::<BitRegister>(&GoldilocksField::ONE);
+
+        // Load the observed elements of the current row and the bits of the overwritten elements.
+        let mut observations = self.observations.iter().map(|o| o.register());
+        let row_inputs = schedule
+            .num_inputs
+            .iter()
+            .map(|num_inputs| observations.by_ref().take(*num_inputs).collect::<Vec<_>>())
+            .collect::<Vec<_>>();
+        let (inputs, overwrite_bits): (Vec<_>, Vec<_>) = (0..RATE)
+            .map(|i| {
+                let values = row_inputs
+                    .iter()
+                    .map(|inputs| inputs.get(i).copied().unwrap_or(zero))
+                    .collect::<Vec<_>>();
+                let bits = row_inputs
+                    .iter()
+                    .map(|inputs| match i < inputs.len() {
+                        true => one_bit,
+                        false => zero_bit,
+                    })
+                    .collect::<Vec<_>>();
+                (
+                    load_row_value(builder, &values, zero, num_rows, clk),
+                    load_row_value(builder, &bits, zero_bit, num_rows, clk),
+                )
+            })
+            .unzip();
+
+        // The state after the permutation of the previous row, which is zero in the first row.
+        let previous = builder.alloc_array::<ElementRegister>(WIDTH);
+        for register in previous.iter() {
+            builder.set_to_expression_first_row(&register, GoldilocksField::ZERO.into());
+        }
+        let rate = builder.alloc_array::<ElementRegister>(RATE);
+        for (((register, input), bit), prev) in rate
+            .iter()
+            .zip(inputs.iter())
+            .zip(overwrite_bits.iter())
+            .zip(previous.iter())
+        {
+            builder.set_to_expression(
+                &register,
+                bit.expr() * input.expr() + bit.not_expr() * prev.expr(),
+            );
+        }
+        let state = rate
+            .iter()
+            .chain(previous.iter().skip(RATE))
+            .map(|register| register.expr())
+            .collect::<Vec<_>>();
+        let output = permutation(builder, &state);
+        for (register, output_i) in previous.iter().zip(output.iter()) {
+            builder.set_to_expression_transition(&register.next(), output_i.expr());
+        }
+
+        // Store the challenges of the current row and free them at the row of their duplexing.
+        let mut is_challenge = vec![[false; RATE]; schedule.num_duplexings()];
+        for (duplexing, position) in schedule.challenges.iter() {
+            is_challenge[*duplexing][*position] = true;
+        }
+        let challenge_ptr = builder.uninit_slice();
+        for (i, element) in output.iter().take(RATE).enumerate() {
+            let multiplicities = is_challenge
+                .iter()
+                .map(|row| match row[i] {
+                    true => one,
+                    false => zero,
+                })
+                .collect::<Vec<_>>();
+            let multiplicity = load_row_value(builder, &multiplicities, zero, num_rows, clk);
+            builder.store(
+                &challenge_ptr.get(i),
+                element,
+                &Time::from_element(clk),
+                Some(multiplicity),
+                None,
+                None,
+            );
+        }
+        for (challenge, (duplexing, position)) in
+            self.challenges.iter().zip(schedule.challenges.iter())
+        {
+            builder.free(
+                &challenge_ptr.get(*position),
+                *challenge,
+                &Time::constant(*duplexing),
+            );
+        }
+    }
+
+    /// Writes the values of the challenges, computed from the values of the observed elements.
+    pub fn write_challenges<W: AirWriter<Field = GoldilocksField>>(&self, writer: &mut W) {
+        let observations = self
+            .observations
+            .iter()
+            .map(|observation| match observation {
+                Observation::Public(register) => writer.read(register),
+                Observation::Constant(_, value) => *value,
+            })
+            .collect::<Vec<_>>();
+        let values = self.schedule().challenge_values(&observations);
+        for (challenge, value) in self.challenges.iter().zip(values) {
+            writer.write(challenge, &value);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use plonky2::hash::poseidon::PoseidonHash;
+    use plonky2::iop::challenger::Challenger;
+    use plonky2::util::timing::TimingTree;
+    use serde::{Deserialize, Serialize};
+
+    use super::*;
+    use crate::chip::AirParameters;
+    use crate::machine::stark::builder::StarkBuilder;
+    use crate::math::goldilocks::cubic::GoldilocksCubicParameters;
+    use crate::plonky2::stark::config::CurtaPoseidonGoldilocksConfig;
+    use crate::prelude::{AirWriterData, EmptyInstruction};
+
+    #[derive(Clone, Debug, Serialize, Deserialize)]
+    pub struct ChallengerTest;
+
+    impl AirParameters for ChallengerTest {
+        type Field = GoldilocksField;
+        type CubicParams = GoldilocksCubicParameters;
+        type Instruction = EmptyInstruction<GoldilocksField>;
+
+        const NUM_FREE_COLUMNS: usize = 557;
+        const EXTENDED_COLUMNS: usize = 162;
+    }
+
+    #[test]
+    fn test_air_challenger() {
+        type F = GoldilocksField;
+        type C = CurtaPoseidonGoldilocksConfig;
+
+        let _ = env_logger::builder().is_test(true).try_init();
+        let mut timing = TimingTree::new("test_air_challenger", log::Level::Debug);
+
+        // Rounds of a transcript, each observing a domain separator and some public elements,
+        // and squeezing some challenges.
+        let num_rounds = 16;
+        let mut builder = StarkBuilder::<ChallengerTest>::new();
+        let mut challenger = AirChallenger::new();
+        let mut elements = Vec::new();
+        let mut challenges = Vec::new();
+        for round in 0..num_rounds {
+            challenger.observe_constant(&mut builder, F::from_canonical_usize(round));
+            let round_elements = builder.alloc_array_public::<ElementRegister>(round % 10 + 1);
+            challenger.observe_elements(round_elements.iter());
+            elements.push(round_elements);
+            challenges.push(challenger.get_n_challenges(&mut builder, 3 * (round % 4) + 1));
+        }
+        challenger.finalize(&mut builder);
+
+        let num_rows = challenger.num_rows();
+        let stark = builder.build::<C, 2>(num_rows);
+
+        // Write trace.
+        let mut writer_data = AirWriterData::new(&stark.air_data, num_rows);
+        let mut writer = writer_data.public_writer();
+
+        let element_values = elements
+            .iter()
+            .map(|round_elements| {
+                let values = (0..round_elements.len())
+                    .map(|_| F::rand())
+                    .collect::<Vec<_>>();
+                writer.write_array(round_elements, &values);
+                values
+            })
+            .collect::<Vec<_>>();
+        challenger.write_challenges(&mut writer);
+
+        // The challenges are those of the plonky2 challenger.
+        let mut plonky2_challenger = Challenger::<F, PoseidonHash>::new();
+        for (round, (values, round_challenges)) in
+            element_values.iter().zip(challenges.iter()).enumerate()
+        {
+            plonky2_challenger.observe_element(F::from_canonical_usize(round));
+            plonky2_challenger.observe_elements(values);
+            let expected = plonky2_challenger.get_n_challenges(round_challenges.len());
+            let values = round_challenges
+                .iter()
+                .map(|challenge| writer.read(challenge))
+                .collect::<Vec<_>>();
+            assert_eq!(values, expected);
+        }
+
+        stark.air_data.write_global_instructions(&mut writer);
+        for mut chunk in writer_data.chunks(num_rows) {
+            for i in 0..num_rows {
+                let mut writer = chunk.window_writer(i);
+                stark.air_data.write_trace_instructions(&mut writer);
+            }
+        }
+
+        let (trace, public) = (writer_data.trace, writer_data.public);
+
+        let proof = stark.prove(&trace, &public, &mut timing).unwrap();
+        stark.verify(proof, &public).unwrap();
+    }
+}
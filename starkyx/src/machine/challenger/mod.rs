@@ -0,0 +1,33 @@
+//! A challenger in the AIR, with the duplex sponge of the plonky2 `Challenger`.
+//!
+//! The plonky2 challenger absorbs the observed elements in the overwrite mode of a sponge over the
+//! Poseidon permutation of width 12, and squeezes challenges from the first 8 elements of the
+//! state, last one first. Observing an element discards the buffered challenges, and a challenge
+//! after new observations, or after the buffered challenges are exhausted, permutes the state
+//! again. The sequence of duplexings of a challenger is thus fixed by the order of its
+//! operations, see [`pure::DuplexSchedule`].
+//!
+//! [`builder::AirChallenger`] records the operations of a challenger while building an AIR and
+//! computes one duplexing in each row of the trace, so that protocols within the trace, such as
+//! the Fiat-Shamir transform of a sub-argument, derive the same challenges as a plonky2 verifier
+//! observing the same elements.
+
+pub mod air;
+pub mod builder;
+pub mod pure;
+
+/// The number of elements of the state.
+pub const WIDTH: usize = 12;
+/// The number of elements of the state overwritten by the observed elements, which are also the
+/// elements squeezed as challenges.
+pub const RATE: usize = 8;
+
+/// The number of full rounds, half of which come before the partial rounds.
+pub const NUM_FULL_ROUNDS: usize = 8;
+pub const NUM_PARTIAL_ROUNDS: usize = 22;
+const NUM_ROUNDS: usize = NUM_FULL_ROUNDS + NUM_PARTIAL_ROUNDS;
+
+/// Whether the round of index `round` is a full round.
+const fn is_full_round(round: usize) -> bool {
+    round < NUM_FULL_ROUNDS / 2 || round >= NUM_FULL_ROUNDS / 2 + NUM_PARTIAL_ROUNDS
+}
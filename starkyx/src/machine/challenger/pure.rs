@@ -0,0 +1,218 @@
+use core::ops::{Add, Mul};
+
+use plonky2::field::goldilocks_field::GoldilocksField;
+use plonky2::hash::poseidon::{Poseidon, ALL_ROUND_CONSTANTS};
+
+use super::{is_full_round, NUM_ROUNDS, RATE, WIDTH};
+use crate::math::prelude::*;
+
+/// The round constants of the round of index `round`.
+pub(crate) fn round_constants(round: usize) -> &'static [u64] {
+    &ALL_ROUND_CONSTANTS[WIDTH * round..WIDTH * (round + 1)]
+}
+
+/// The Poseidon permutation of plonky2 over the Goldilocks field.
+pub fn permute(state: [GoldilocksField; WIDTH]) -> [GoldilocksField; WIDTH] {
+    let mut state = state.to_vec();
+    for round in 0..NUM_ROUNDS {
+        for (x, &c) in state.iter_mut().zip(round_constants(round)) {
+            *x += GoldilocksField::from_canonical_u64(c);
+        }
+        if is_full_round(round) {
+            state.iter_mut().for_each(|x| *x = sbox(*x));
+        } else {
+            state[0] = sbox(state[0]);
+        }
+        state = mds_layer(&state);
+    }
+    state.try_into().unwrap()
+}
+
+fn sbox(x: GoldilocksField) -> GoldilocksField {
+    let x3 = x * x * x;
+    x3 * x3 * x
+}
+
+/// The MDS layer, given by the circulant matrix `MDS_MATRIX_CIRC` plus the diagonal matrix
+/// `MDS_MATRIX_DIAG`.
+///
+/// The layer is generic over the values, so that it applies both to field elements and to
+/// arithmetic expressions.
+pub fn mds_layer<T: Clone + Add<Output = T> + Mul<GoldilocksField, Output = T>>(
+    state: &[T],
+) -> Vec<T> {
+    assert_eq!(state.len(), WIDTH);
+    let circ = <GoldilocksField as Poseidon>::MDS_MATRIX_CIRC;
+    let diag = <GoldilocksField as Poseidon>::MDS_MATRIX_DIAG;
+    (0..WIDTH)
+        .map(|r| {
+            (0..WIDTH)
+                .map(|i| (state[(i + r) % WIDTH].clone(), circ[i]))
+                .chain([(state[r].clone(), diag[r])])
+                .filter(|(_, m)| *m != 0)
+                .map(|(x, m)| match m {
+                    1 => x,
+                    _ => x * GoldilocksField::from_canonical_u64(m),
+                })
+                .reduce(|acc, x| acc + x)
+                .unwrap()
+        })
+        .collect()
+}
+
+/// An operation of the challenger.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ChallengerOp {
+    /// Observes the next element.
+    Observe,
+    /// Squeezes the next challenge.
+    Challenge,
+}
+
+/// The duplexings of the sponge of the plonky2 `Challenger` for a sequence of operations.
+///
+/// Each duplexing overwrites the beginning of the state with the observed elements buffered since
+/// the previous one, and permutes the state. Observations after the last challenge are never
+/// absorbed, as they do not affect any challenge.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DuplexSchedule {
+    /// The number of observed elements absorbed by each duplexing.
+    pub num_inputs: Vec<usize>,
+    /// The index of the duplexing and the position in the state of each challenge.
+    pub challenges: Vec<(usize, usize)>,
+}
+
+impl DuplexSchedule {
+    pub fn new(ops: &[ChallengerOp]) -> Self {
+        let mut num_inputs = Vec::new();
+        let mut challenges = Vec::new();
+        let mut num_buffered_inputs = 0;
+        let mut num_buffered_outputs = 0;
+        for op in ops {
+            match op {
+                ChallengerOp::Observe => {
+                    num_buffered_outputs = 0;
+                    num_buffered_inputs += 1;
+                    if num_buffered_inputs == RATE {
+                        num_inputs.push(num_buffered_inputs);
+                        num_buffered_inputs = 0;
+                        num_buffered_outputs = RATE;
+                    }
+                }
+                ChallengerOp::Challenge => {
+                    if num_buffered_inputs > 0 || num_buffered_outputs == 0 {
+                        num_inputs.push(num_buffered_inputs);
+                        num_buffered_inputs = 0;
+                        num_buffered_outputs = RATE;
+                    }
+                    // The outputs are popped from the end of the buffer.
+                    num_buffered_outputs -= 1;
+                    challenges.push((num_inputs.len() - 1, num_buffered_outputs));
+                }
+            }
+        }
+        Self {
+            num_inputs,
+            challenges,
+        }
+    }
+
+    pub fn num_duplexings(&self) -> usize {
+        self.num_inputs.len()
+    }
+
+    /// The number of observed elements absorbed by the duplexings.
+    pub fn num_absorbed(&self) -> usize {
+        self.num_inputs.iter().sum()
+    }
+
+    /// The states after each duplexing, given the observed elements in order.
+    pub fn states(&self, observations: &[GoldilocksField]) -> Vec<[GoldilocksField; WIDTH]> {
+        assert!(
+            observations.len() >= self.num_absorbed(),
+            "{} elements are absorbed, but only {} are given",
+            self.num_absorbed(),
+            observations.len()
+        );
+        let mut observations = observations.iter();
+        let mut state = [GoldilocksField::ZERO; WIDTH];
+        self.num_inputs
+            .iter()
+            .map(|num_inputs| {
+                for (x, observation) in state.iter_mut().zip(&mut observations).take(*num_inputs) {
+                    *x = *observation;
+                }
+                state = permute(state);
+                state
+            })
+            .collect()
+    }
+
+    /// The values of the challenges, given the observed elements in order.
+    pub fn challenge_values(&self, observations: &[GoldilocksField]) -> Vec<GoldilocksField> {
+        let states = self.states(observations);
+        self.challenges
+            .iter()
+            .map(|(duplexing, position)| states[*duplexing][*position])
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use plonky2::hash::poseidon::PoseidonHash;
+    use plonky2::iop::challenger::Challenger;
+
+    use super::*;
+
+    #[test]
+    fn test_poseidon_permutation() {
+        type F = GoldilocksField;
+        for _ in 0..4 {
+            let state: [F; WIDTH] = core::array::from_fn(|_| F::rand());
+            assert_eq!(permute(state), F::poseidon(state));
+        }
+    }
+
+    #[test]
+    fn test_duplex_schedule() {
+        type F = GoldilocksField;
+        use ChallengerOp::*;
+
+        // Challenges after a few observations, after a full rate of observations, more
+        // challenges than the rate, and observations which are never absorbed.
+        let ops = [
+            vec![Observe; 3],
+            vec![Challenge; 2],
+            vec![Observe; RATE],
+            vec![Challenge; RATE + 2],
+            vec![Observe; 11],
+            vec![Challenge],
+            vec![Observe; 2],
+        ]
+        .concat();
+        let schedule = DuplexSchedule::new(&ops);
+        assert_eq!(schedule.num_inputs, vec![3, RATE, 0, RATE, 3]);
+        assert_eq!(schedule.challenges[..2], [(0, 7), (0, 6)]);
+        assert_eq!(schedule.challenges[2..4], [(1, 7), (1, 6)]);
+        assert_eq!(schedule.challenges[10..12], [(2, 7), (2, 6)]);
+        assert_eq!(schedule.challenges[12], (4, 7));
+
+        let observations = (0..ops.iter().filter(|op| **op == Observe).count())
+            .map(|_| F::rand())
+            .collect::<Vec<_>>();
+        let challenges = schedule.challenge_values(&observations);
+
+        // Compare with the challenger of plonky2.
+        let mut challenger = Challenger::<F, PoseidonHash>::new();
+        let mut observations = observations.iter();
+        let mut expected = Vec::new();
+        for op in ops {
+            match op {
+                Observe => challenger.observe_element(*observations.next().unwrap()),
+                Challenge => expected.push(challenger.get_challenge()),
+            }
+        }
+        assert_eq!(challenges, expected);
+    }
+}
@@ -123,6 +123,8 @@ pub trait EllipticCurveBuilder<E: EllipticCurveAir<Self::Parameters>>: Builder {
         let degree_log = log2_ceil(num_ops * nb_scalar_bits);
         assert!(degree_log < 31, "AIR degree is too large");
         debug!("AIR degree after padding: {}", 1 << degree_log);
+        self.api()
+            .require_rows("scalar multiplication", 1 << degree_log);
         let num_dummy_ops = (1 << degree_log) / nb_scalar_bits - num_ops;
 
         // Insert dummy entries where necessary.
@@ -191,24 +193,158 @@ pub trait EllipticCurveBuilder<E: EllipticCurveAir<Self::Parameters>>: Builder {
         );
     }
 
+    /// Computes `results[i] = scalars[i] * point` for a fixed base `point`.
+    ///
+    /// The scalar multiplications are laid out in `lanes` columns of accumulators which share a
+    /// single doubling chain of `point`, so that each row performs one doubling for `lanes` scalar
+    /// multiplications. Compared to `scalar_mul_batch`, this divides the number of rows by `lanes`
+    /// and saves a doubling per scalar multiplication in all but one of the lanes.
+    ///
+    /// The doubling chain is reset to `point` at the start of every cycle, so the trace rows of
+    /// this gadget must be written in order, from the first row.
+    fn fixed_base_scalar_mul_batch<J, K>(
+        &mut self,
+        point: &AffinePointRegister<E>,
+        scalars: J,
+        results: K,
+        lanes: usize,
+    ) where
+        J: IntoIterator,
+        K: IntoIterator,
+        J::Item: Borrow<ECScalarRegister<E>>,
+        K::Item: Borrow<AffinePointRegister<E>>,
+        Self::Instruction: ECInstructions<E>,
+    {
+        assert!(lanes > 0, "The number of lanes must be positive");
+        let nb_scalar_bits = E::nb_scalar_bits();
+        let nb_bits_log = nb_scalar_bits.ilog2();
+        assert_eq!(
+            1 << nb_bits_log,
+            nb_scalar_bits,
+            "Scalar size must be a power of 2"
+        );
+        assert!(nb_bits_log > 5, "Scalar size must be at least 32 bits");
+        let nb_limbs = nb_scalar_bits / 32;
+
+        let cycle_32_size = self.constant(&Self::Field::from_canonical_u32(32));
+        let cycle = self.cycle(nb_bits_log as usize);
+        let cycle_32 = self.cycle(5);
+
+        let x_ptrs = (0..lanes)
+            .map(|_| self.uninit_slice::<FieldRegister<E::BaseField>>())
+            .collect::<Vec<_>>();
+        let y_ptrs = (0..lanes)
+            .map(|_| self.uninit_slice::<FieldRegister<E::BaseField>>())
+            .collect::<Vec<_>>();
+        let limb_ptrs = (0..lanes)
+            .map(|_| self.uninit_slice::<ElementRegister>())
+            .collect::<Vec<_>>();
+        let zero = Time::zero();
+
+        // The `i`-th scalar multiplication is computed in the lane `i % lanes` during the cycle
+        // `i / lanes`.
+        let num_ops = scalars
+            .into_iter()
+            .zip_eq(results)
+            .enumerate()
+            .map(|(i, (scalar, result))| {
+                let scalar = scalar.borrow();
+                let result = result.borrow();
+                let (cycle_index, lane) = (i / lanes, i % lanes);
+
+                for (j, limb) in scalar.limbs.iter().enumerate() {
+                    self.store(
+                        &limb_ptrs[lane].get(cycle_index * nb_limbs + j),
+                        limb,
+                        &zero,
+                        Some(cycle_32_size),
+                        None,
+                        None,
+                    );
+                }
+
+                self.free(&x_ptrs[lane].get(cycle_index), result.x, &zero);
+                self.free(&y_ptrs[lane].get(cycle_index), result.y, &zero);
+            })
+            .count();
+
+        let num_cycles = num_ops.div_ceil(lanes);
+        debug!("AIR degree before padding: {}", num_cycles * nb_scalar_bits);
+        let degree_log = log2_ceil(num_cycles * nb_scalar_bits);
+        assert!(degree_log < 31, "AIR degree is too large");
+        debug!("AIR degree after padding: {}", 1 << degree_log);
+        self.api()
+            .require_rows("fixed-base scalar multiplication", 1 << degree_log);
+        let num_cycles = (1 << degree_log) / nb_scalar_bits;
+
+        // Fill the remaining entries with `1 * point = point`.
+        let mut one_scalar_limbs = vec![Self::Field::ONE];
+        one_scalar_limbs.resize(nb_limbs, Self::Field::ZERO);
+        let one_limbs = self.constant_array::<ElementRegister>(&one_scalar_limbs);
+        for i in num_ops..(num_cycles * lanes) {
+            let (cycle_index, lane) = (i / lanes, i % lanes);
+            for (j, limb) in one_limbs.iter().enumerate() {
+                self.store(
+                    &limb_ptrs[lane].get(cycle_index * nb_limbs + j),
+                    limb,
+                    &zero,
+                    Some(cycle_32_size),
+                    None,
+                    None,
+                );
+            }
+
+            self.free(&x_ptrs[lane].get(cycle_index), point.x, &zero);
+            self.free(&y_ptrs[lane].get(cycle_index), point.y, &zero);
+        }
+
+        // The shared doubling chain, which is equal to `2^k * point` at the `k`-th row of each
+        // cycle.
+        let temp = self.alloc_ec_point();
+        self.set_to_expression_first_row(&temp.x, point.x.expr());
+        self.set_to_expression_first_row(&temp.y, point.y.expr());
+        let temp_next = self.double(&temp);
+        self.select_next_ec_point(cycle.end_bit, point, &temp_next, &temp);
+
+        let process_id = self.process_id(nb_scalar_bits, cycle.end_bit);
+        let process_id_u32 = self.process_id(32, cycle_32.end_bit);
+        let end_flag = Some(cycle.end_bit.as_element());
+
+        for lane in 0..lanes {
+            // Load the scalar limb of the lane and decompose it to bits.
+            let limb = self.load(&limb_ptrs[lane].get_at(process_id_u32), &zero, None, None);
+            let scalar_bit = self.bit_decomposition(limb, cycle_32.start_bit, cycle_32.end_bit);
+
+            let result_next = self.accumulate_scalar_bit(
+                scalar_bit,
+                &temp,
+                &temp_next,
+                cycle.start_bit,
+                cycle.end_bit,
+            );
+            self.store(
+                &x_ptrs[lane].get_at(process_id),
+                result_next.x,
+                &zero,
+                end_flag,
+                None,
+                None,
+            );
+            self.store(
+                &y_ptrs[lane].get_at(process_id),
+                result_next.y,
+                &zero,
+                end_flag,
+                None,
+                None,
+            );
+        }
+    }
+
     fn double_and_add(&mut self, data: &DoubleAddData<E>) -> AffinePointRegister<E>
     where
         Self::Instruction: ECInstructions<E>,
     {
-        // Keep track of whether res is the identity, which is the point at infinity for some
-        // curves.
-        //
-        // The value starts by being '0' at the begining of each cycle, and set to '1' once the
-        // scalar bit is different from zero.
-        let is_res_valid = self.alloc::<BitRegister>();
-        let scalar_bit = data.bit;
-        let end_bit = data.end_bit;
-        let start_bit = data.start_bit;
-        self.set_to_expression_first_row(&is_res_valid, Self::Field::ZERO.into());
-        let next_res_valid =
-            self.expression(is_res_valid.expr() + scalar_bit.expr() * is_res_valid.not_expr());
-        self.select_next(end_bit, &start_bit, &next_res_valid, &is_res_valid);
-
         // Load temp.
         let process_id = data.process_id;
         let temp_x_ptr = data.temp_x_ptr.get_at(process_id);
@@ -238,14 +374,41 @@ pub trait EllipticCurveBuilder<E: EllipticCurveAir<Self::Parameters>>: Builder {
             None,
         );
 
+        self.accumulate_scalar_bit(data.bit, &temp, &temp_next, data.start_bit, data.end_bit)
+    }
+
+    /// Adds `temp` to an intermediate result of a double-and-add cycle if `scalar_bit` is set,
+    /// where `temp_next` is the double of `temp`, and returns the next value of the result.
+    fn accumulate_scalar_bit(
+        &mut self,
+        scalar_bit: BitRegister,
+        temp: &AffinePointRegister<E>,
+        temp_next: &AffinePointRegister<E>,
+        start_bit: BitRegister,
+        end_bit: BitRegister,
+    ) -> AffinePointRegister<E>
+    where
+        Self::Instruction: ECInstructions<E>,
+    {
+        // Keep track of whether res is the identity, which is the point at infinity for some
+        // curves.
+        //
+        // The value starts by being '0' at the begining of each cycle, and set to '1' once the
+        // scalar bit is different from zero.
+        let is_res_valid = self.alloc::<BitRegister>();
+        self.set_to_expression_first_row(&is_res_valid, Self::Field::ZERO.into());
+        let next_res_valid =
+            self.expression(is_res_valid.expr() + scalar_bit.expr() * is_res_valid.not_expr());
+        self.select_next(end_bit, &start_bit, &next_res_valid, &is_res_valid);
+
         // Allocate the intermeddiate result.
         let result = self.alloc_ec_point();
 
         // Calculate res_next = res + temp if scalar_bit is 1, otherwise res_next = res.
-        let addend = self.select_ec_point(is_res_valid, &result, &temp_next);
-        let sum = self.add(&temp, &addend);
+        let addend = self.select_ec_point(is_res_valid, &result, temp_next);
+        let sum = self.add(temp, &addend);
 
-        let res_plus_temp = self.select_ec_point(is_res_valid, &sum, &temp);
+        let res_plus_temp = self.select_ec_point(is_res_valid, &sum, temp);
         let result_next = self.select_ec_point(scalar_bit, &res_plus_temp, &result);
 
         let zero_field = self.zero::<FieldRegister<E::BaseField>>();
@@ -303,6 +466,20 @@ mod tests {
         const EXTENDED_COLUMNS: usize = 2502;
     }
 
+    #[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+    struct Ed25519FixedBaseScalarMulTest;
+
+    impl AirParameters for Ed25519FixedBaseScalarMulTest {
+        type Field = GoldilocksField;
+        type CubicParams = GoldilocksCubicParameters;
+
+        type Instruction = ECInstruction<Ed25519>;
+
+        const NUM_ARITHMETIC_COLUMNS: usize = 3264;
+        const NUM_FREE_COLUMNS: usize = 64;
+        const EXTENDED_COLUMNS: usize = 5004;
+    }
+
     #[test]
     fn test_ec_scalar_mul() {
         type F = GoldilocksField;
@@ -417,4 +594,75 @@ mod tests {
 
         timing.print();
     }
+
+    #[test]
+    fn test_ec_fixed_base_scalar_mul() {
+        type F = GoldilocksField;
+        type L = Ed25519FixedBaseScalarMulTest;
+        type C = CurtaPoseidonGoldilocksConfig;
+        type E = Ed25519;
+
+        let _ = env_logger::builder().is_test(true).try_init();
+
+        let mut timing = TimingTree::new("Ed25519 fixed-base scalar mul", log::Level::Debug);
+
+        let mut builder = EmulatedBuilder::<L>::new();
+
+        let num_ops = 5;
+        let lanes = 2;
+
+        let point = builder.alloc_public_ec_point();
+
+        let scalars = (0..num_ops)
+            .map(|_| builder.alloc_array_public::<ElementRegister>(8))
+            .map(ECScalarRegister::<E>::new)
+            .collect::<Vec<_>>();
+
+        let results = (0..num_ops)
+            .map(|_| builder.alloc_public_ec_point())
+            .collect::<Vec<_>>();
+
+        builder.fixed_base_scalar_mul_batch(&point, &scalars, &results, lanes);
+
+        let degree_log = log2_ceil(num_ops.div_ceil(lanes) * 256);
+        let num_rows = 1 << degree_log;
+        let stark = builder.build::<C, 2>(num_rows);
+
+        let order = E::prime_group_order();
+        let mut rng = thread_rng();
+        let base = E::ec_generator() * rng.gen_biguint(256);
+
+        let mut writer_data = AirWriterData::new(&stark.air_data, num_rows);
+
+        let mut writer = writer_data.public_writer();
+        writer.write_ec_point(&point, &base);
+        for (scalar_reg, result_reg) in scalars.iter().zip(results.iter()) {
+            let scalar = rng.gen_biguint(256) % &order;
+            writer.write_ec_point(result_reg, &(&base * &scalar));
+
+            let mut limb_values = scalar.to_u32_digits();
+            limb_values.resize(8, 0);
+            for (limb_reg, limb) in scalar_reg.limbs.iter().zip_eq(limb_values) {
+                writer.write(&limb_reg, &F::from_canonical_u32(limb));
+            }
+        }
+
+        stark.air_data.write_global_instructions(&mut writer);
+
+        // The doubling chain is reset to the base point at the start of each cycle, so the rows
+        // are written in order.
+        for mut chunk in writer_data.chunks(num_rows) {
+            for i in 0..num_rows {
+                let mut writer = chunk.window_writer(i);
+                stark.air_data.write_trace_instructions(&mut writer);
+            }
+        }
+
+        let (trace, public) = (writer_data.trace, writer_data.public);
+
+        let proof = stark.prove(&trace, &public, &mut timing).unwrap();
+        stark.verify(proof, &public).unwrap();
+
+        timing.print();
+    }
 }
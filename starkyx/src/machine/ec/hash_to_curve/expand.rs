@@ -0,0 +1,293 @@
+//! The `expand_message_xmd` function of RFC 9380 with the SHA-256 chip.
+//!
+//! The digest `b_0` is the hash of `Z_pad || msg || I2OSP(len_in_bytes, 2) || I2OSP(0, 1) ||
+//! DST_prime`, and the uniform bytes are the concatenation of the digests `b_1, ..., b_ell`, where
+//! `b_i` is the hash of `strxor(b_0, b_(i - 1)) || I2OSP(i, 1) || DST_prime`, with `b_0` in place
+//! of the `strxor` for `i = 1`. The first 32 bytes of the messages of `b_1, ..., b_ell` are
+//! computed from the previous digests by public byte operations, and all the bytes which depend
+//! only on the domain separation tag are constants.
+
+use serde::{Deserialize, Serialize};
+
+use super::{num_uniform_digests, uniform_digest_padding};
+use crate::chip::register::array::ArrayRegister;
+use crate::chip::register::bit::BitRegister;
+use crate::chip::register::element::ElementRegister;
+use crate::chip::register::Register;
+use crate::chip::uint::bytes::operations::value::ByteOperation;
+use crate::chip::uint::operations::instruction::UintInstructions;
+use crate::chip::uint::register::U32Register;
+use crate::chip::uint::util::u32_to_le_field_bytes;
+use crate::chip::AirParameters;
+use crate::machine::builder::Builder;
+use crate::machine::bytes::builder::BytesBuilder;
+use crate::machine::hash::sha::builder::SHABuilder;
+use crate::machine::hash::sha::sha256::register::SHA256DigestRegister;
+use crate::machine::hash::sha::sha256::SHA256;
+use crate::math::prelude::*;
+
+/// The digests of an `expand_message_xmd` computation.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExpandMessageXMDRegister {
+    /// The digest `b_0` of the message.
+    pub b_0: SHA256DigestRegister,
+    /// The digests `b_1, ..., b_ell`, whose concatenation starts with the uniform bytes.
+    pub uniform_digests: Vec<SHA256DigestRegister>,
+}
+
+pub trait ExpandMessageBuilder: Builder {
+    /// Computes `expand_message_xmd` of messages with the domain separation tag `dst`.
+    ///
+    /// The message of each index is given by the padded chunks of the hash of `b_0` after the
+    /// chunk of zeros `Z_pad`, see [`super::expand_message_chunks`].
+    ///
+    /// Returns the digests of each message, which are public and must be written before the
+    /// global instructions, as given by [`super::expand_message_xmd_digests`].
+    fn expand_message_xmd(
+        &mut self,
+        message_chunks: &[Vec<ArrayRegister<U32Register>>],
+        dst: &[u8],
+        len_in_bytes: usize,
+    ) -> Vec<ExpandMessageXMDRegister>;
+}
+
+impl<L: AirParameters> ExpandMessageBuilder for BytesBuilder<L>
+where
+    L::Instruction: UintInstructions,
+{
+    fn expand_message_xmd(
+        &mut self,
+        message_chunks: &[Vec<ArrayRegister<U32Register>>],
+        dst: &[u8],
+        len_in_bytes: usize,
+    ) -> Vec<ExpandMessageXMDRegister> {
+        let ell = num_uniform_digests(len_in_bytes);
+        let zero_chunk = self.constant_array::<U32Register>(&[u32_to_le_field_bytes(0); 16]);
+        let paddings = (1..=ell)
+            .map(|i| uniform_digest_padding(dst, i))
+            .collect::<Vec<_>>();
+
+        // The chunks of the hashes of `b_0, b_1, ..., b_ell` of each message, in that order.
+        let mut padded_chunks = Vec::new();
+        let mut end_bit_values = Vec::new();
+        let mut prefixes = Vec::new();
+        for chunks in message_chunks.iter() {
+            padded_chunks.push(zero_chunk);
+            padded_chunks.extend(chunks.iter().copied());
+            end_bit_values.extend((0..=chunks.len()).map(|i| i == chunks.len()));
+
+            // The first 32 bytes of the message of each `b_i` are public and set after the
+            // digests are written, the rest of the padded message is constant.
+            let mut message_prefixes = Vec::with_capacity(ell);
+            for padding in paddings.iter() {
+                let first_chunk = self.alloc_array_public::<U32Register>(16);
+                for (word, value) in first_chunk
+                    .get_subarray(8..16)
+                    .iter()
+                    .zip(padding[8..16].iter())
+                {
+                    let value = self.constant::<U32Register>(&u32_to_le_field_bytes(*value));
+                    self.api.set_to_expression_public(&word, value.expr());
+                }
+                padded_chunks.push(first_chunk);
+                for chunk in padding[16..].chunks_exact(16) {
+                    let chunk = self.constant_array::<U32Register>(
+                        &chunk
+                            .iter()
+                            .map(|word| u32_to_le_field_bytes(*word))
+                            .collect::<Vec<_>>(),
+                    );
+                    padded_chunks.push(chunk);
+                }
+                let num_chunks = padding.len() / 16;
+                end_bit_values.extend((0..num_chunks).map(|i| i == num_chunks - 1));
+                message_prefixes.push(first_chunk.get_subarray(0..8));
+            }
+            prefixes.push(message_prefixes);
+        }
+
+        let end_bits = self.constant_array::<BitRegister>(
+            &end_bit_values
+                .iter()
+                .map(|bit| Self::Field::from_canonical_usize(*bit as usize))
+                .collect::<Vec<_>>(),
+        );
+        let digest_indices = self.constant_array::<ElementRegister>(
+            &end_bit_values
+                .iter()
+                .enumerate()
+                .filter(|(_, bit)| **bit)
+                .map(|(i, _)| Self::Field::from_canonical_usize(i))
+                .collect::<Vec<_>>(),
+        );
+        let digests = self.sha::<SHA256, 64>(&padded_chunks, &end_bits, &end_bits, digest_indices);
+
+        digests
+            .chunks_exact(ell + 1)
+            .zip(prefixes)
+            .map(|(digests, message_prefixes)| {
+                let b_0 = digests[0];
+                for (i, prefix) in message_prefixes.iter().enumerate() {
+                    if i == 0 {
+                        // The message of `b_1` starts with `b_0`.
+                        for (word, value) in prefix.iter().zip(b_0.iter()) {
+                            self.api.set_to_expression_public(&word, value.expr());
+                        }
+                        continue;
+                    }
+                    // The message of `b_(i + 1)` starts with `strxor(b_0, b_i)`.
+                    for ((word, b_0_word), b_i_word) in
+                        prefix.iter().zip(b_0.iter()).zip(digests[i].iter())
+                    {
+                        for ((byte, b_0_byte), b_i_byte) in word
+                            .to_le_bytes()
+                            .iter()
+                            .zip(b_0_word.to_le_bytes().iter())
+                            .zip(b_i_word.to_le_bytes().iter())
+                        {
+                            let xor = ByteOperation::Xor(b_0_byte, b_i_byte, byte);
+                            self.api
+                                .set_public_inputs_byte_operation(&xor, &mut self.operations);
+                        }
+                    }
+                }
+                ExpandMessageXMDRegister {
+                    b_0,
+                    uniform_digests: digests[1..].to_vec(),
+                }
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use plonky2::field::goldilocks_field::GoldilocksField;
+    use plonky2::timed;
+    use plonky2::util::log2_ceil;
+    use plonky2::util::timing::TimingTree;
+
+    use super::*;
+    use crate::chip::uint::operations::instruction::UintInstruction;
+    use crate::machine::ec::hash_to_curve::{
+        expand_message_chunks, expand_message_xmd, expand_message_xmd_digests, UNIFORM_BYTES,
+    };
+    use crate::math::goldilocks::cubic::GoldilocksCubicParameters;
+    use crate::plonky2::stark::config::CurtaPoseidonGoldilocksConfig;
+    use crate::prelude::{AirWriter, AirWriterData};
+
+    #[derive(Clone, Debug, Serialize, Deserialize)]
+    pub struct ExpandMessageTest;
+
+    impl AirParameters for ExpandMessageTest {
+        type Field = GoldilocksField;
+        type CubicParams = GoldilocksCubicParameters;
+        type Instruction = UintInstruction;
+
+        const NUM_FREE_COLUMNS: usize = 418;
+        const EXTENDED_COLUMNS: usize = 912;
+    }
+
+    fn to_field_bytes(digest: [u32; 8]) -> [GoldilocksField; 32] {
+        let bytes = digest
+            .iter()
+            .flat_map(|word| u32_to_le_field_bytes::<GoldilocksField>(*word))
+            .collect::<Vec<_>>();
+        bytes.try_into().unwrap()
+    }
+
+    #[test]
+    fn test_expand_message_xmd_air() {
+        type C = CurtaPoseidonGoldilocksConfig;
+
+        let _ = env_logger::builder().is_test(true).try_init();
+        let mut timing = TimingTree::new("test_expand_message_xmd_air", log::Level::Debug);
+
+        let dst = b"QUUX-V01-CS02-with-BLS12381G1_XMD:SHA-256_SSWU_RO_";
+        let messages: [&[u8]; 2] = [b"", b"abc"];
+
+        // Build the stark.
+        let mut builder = BytesBuilder::<ExpandMessageTest>::new();
+        let message_chunk_values = messages
+            .iter()
+            .map(|msg| expand_message_chunks(msg, dst, UNIFORM_BYTES))
+            .collect::<Vec<_>>();
+        let message_chunks = message_chunk_values
+            .iter()
+            .map(|values| {
+                (0..values.len() / 16)
+                    .map(|_| builder.alloc_array_public::<U32Register>(16))
+                    .collect::<Vec<_>>()
+            })
+            .collect::<Vec<_>>();
+        let expanded = builder.expand_message_xmd(&message_chunks, dst, UNIFORM_BYTES);
+
+        let num_chunks = padded_num_chunks(&message_chunks, dst);
+        let num_rows = 1 << log2_ceil(64 * num_chunks);
+        let stark = builder.build::<C, 2>(num_rows);
+
+        // Write trace.
+        let mut writer_data = AirWriterData::new(&stark.air_data, num_rows);
+        let mut writer = writer_data.public_writer();
+
+        for ((msg, (chunks, values)), digests) in messages
+            .iter()
+            .zip(message_chunks.iter().zip(message_chunk_values.iter()))
+            .zip(expanded.iter())
+        {
+            for (register, chunk) in chunks.iter().zip(values.chunks_exact(16)) {
+                writer.write_array(
+                    register,
+                    chunk.iter().map(|word| u32_to_le_field_bytes(*word)),
+                );
+            }
+            let (b_0, uniform_digests) = expand_message_xmd_digests(msg, dst, UNIFORM_BYTES);
+            writer.write(&digests.b_0, &to_field_bytes(b_0));
+            for (register, value) in digests.uniform_digests.iter().zip(uniform_digests.iter()) {
+                writer.write(register, &to_field_bytes(*value));
+            }
+
+            let uniform_bytes = uniform_digests
+                .iter()
+                .flatten()
+                .flat_map(|word| word.to_be_bytes())
+                .collect::<Vec<_>>();
+            assert_eq!(uniform_bytes, expand_message_xmd(msg, dst, UNIFORM_BYTES));
+        }
+
+        timed!(timing, "write input", {
+            stark.air_data.write_global_instructions(&mut writer);
+
+            for mut chunk in writer_data.chunks(num_rows) {
+                for i in 0..num_rows {
+                    let mut writer = chunk.window_writer(i);
+                    stark.air_data.write_trace_instructions(&mut writer);
+                }
+            }
+        });
+
+        let (trace, public) = (writer_data.trace, writer_data.public);
+
+        let proof = timed!(
+            timing,
+            "generate stark proof",
+            stark.prove(&trace, &public, &mut timing).unwrap()
+        );
+
+        stark.verify(proof, &public).unwrap();
+
+        timing.print();
+    }
+
+    /// The total number of chunks hashed by the expansion of the messages.
+    fn padded_num_chunks(message_chunks: &[Vec<ArrayRegister<U32Register>>], dst: &[u8]) -> usize {
+        let ell = num_uniform_digests(UNIFORM_BYTES);
+        let digest_chunks = (1..=ell)
+            .map(|i| uniform_digest_padding(dst, i).len() / 16)
+            .sum::<usize>();
+        message_chunks
+            .iter()
+            .map(|chunks| 1 + chunks.len() + digest_chunks)
+            .sum()
+    }
+}
@@ -0,0 +1,203 @@
+//! The map from uniform bytes to BLS12-381 G1.
+//!
+//! Each 64-byte half of the uniform bytes is given by the two 32-byte digests it is made of, as
+//! public field registers, and reduced to a field element `u = hi * 2^256 + lo`. The two field
+//! elements of a message are mapped to `E'` by the simplified SWU map, and their sum is mapped to
+//! the curve by the 11-isogeny, which is a group homomorphism. All of this is computed on public
+//! values. The cofactor is then cleared by a scalar multiplication in the trace, see
+//! [`EllipticCurveBuilder::scalar_mul_batch`].
+//!
+//! The addition on `E'` assumes that the two points are neither equal nor opposite, which only
+//! fails with negligible probability for uniform bytes.
+
+use serde::{Deserialize, Serialize};
+
+use crate::chip::ec::point::AffinePointRegister;
+use crate::chip::ec::scalar::{ECScalarRegister, LimbBitInstruction};
+use crate::chip::ec::weierstrass::bls12_381::instruction::Bls12381FpInstructions;
+use crate::chip::ec::weierstrass::bls12_381::map::H_EFF;
+use crate::chip::ec::weierstrass::bls12_381::params::{
+    Bls12381, Bls12381BaseField, Bls12381IsoParameters,
+};
+use crate::chip::field::parameters::{FieldParameters, MAX_NB_LIMBS};
+use crate::chip::field::register::FieldRegister;
+use crate::chip::register::element::ElementRegister;
+use crate::machine::builder::Builder;
+use crate::machine::ec::builder::EllipticCurveBuilder;
+use crate::math::prelude::*;
+
+/// The uniform bytes of a message, as the four 32-byte digests `b_1, ..., b_4` of
+/// `expand_message_xmd`, each read as a big-endian integer.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct UniformBytesRegister {
+    pub digests: [FieldRegister<Bls12381BaseField>; 4],
+}
+
+pub trait MapToG1Builder: Builder {
+    /// Allocates the public registers of the uniform bytes of a message.
+    fn alloc_uniform_bytes(&mut self) -> UniformBytesRegister {
+        UniformBytesRegister {
+            digests: core::array::from_fn(|_| self.alloc_public()),
+        }
+    }
+
+    /// Maps the uniform bytes of messages to points of G1.
+    ///
+    /// The cofactors are cleared by [`EllipticCurveBuilder::scalar_mul_batch`], which takes the
+    /// trace of the builder, and whose results are returned. They are public and must be written
+    /// before the global instructions, as given by [`super::hash_to_curve`].
+    fn bls12_381_map_to_g1(
+        &mut self,
+        uniform_bytes: &[UniformBytesRegister],
+    ) -> Vec<AffinePointRegister<Bls12381>>
+    where
+        Self::Instruction: Bls12381FpInstructions + From<LimbBitInstruction>,
+    {
+        // The constant `2^256`, which is smaller than the modulus.
+        let mut shift = [0u16; MAX_NB_LIMBS];
+        shift[256 / Bls12381BaseField::NB_BITS_PER_LIMB] = 1;
+
+        let points = uniform_bytes
+            .iter()
+            .map(|bytes| {
+                let [u0, u1] = [0, 1].map(|i| {
+                    let hi = self.api().fp_mul_const(&bytes.digests[2 * i], shift);
+                    self.api().fp_add(&hi, &bytes.digests[2 * i + 1])
+                });
+                let q0 = self.api().bls12_381_map_to_curve_simple_swu(&u0);
+                let q1 = self.api().bls12_381_map_to_curve_simple_swu(&u1);
+                let q = self.api().sw_add::<Bls12381IsoParameters>(&q0, &q1);
+                self.api().bls12_381_iso_map(&q)
+            })
+            .collect::<Vec<_>>();
+
+        let h_eff_limbs = [H_EFF as u32, (H_EFF >> 32) as u32, 0, 0, 0, 0, 0, 0];
+        let h_eff = self
+            .constant_array::<ElementRegister>(&h_eff_limbs.map(Self::Field::from_canonical_u32));
+        let scalars = vec![ECScalarRegister::<Bls12381>::new(h_eff); points.len()];
+        let results: Vec<AffinePointRegister<Bls12381>> = points
+            .iter()
+            .map(|_| self.alloc_public_ec_point())
+            .collect::<Vec<_>>();
+        self.scalar_mul_batch(&points, &scalars, &results);
+
+        results
+    }
+}
+
+impl<B: Builder> MapToG1Builder for B {}
+
+/// The values of the registers of the uniform bytes, given by the digests `b_1, ..., b_4` of
+/// `expand_message_xmd` as big-endian words.
+///
+/// The verifier of the map checks that these values are the digests of the expansion.
+pub fn uniform_bytes_limbs<F: Field>(uniform_digests: &[[u32; 8]]) -> [Vec<F>; 4] {
+    assert_eq!(uniform_digests.len(), 4);
+    core::array::from_fn(|i| {
+        let mut limbs = uniform_digests[i]
+            .iter()
+            .rev()
+            .flat_map(|word| [*word as u16, (*word >> 16) as u16])
+            .map(F::from_canonical_u16)
+            .collect::<Vec<_>>();
+        limbs.resize(Bls12381BaseField::NB_LIMBS, F::ZERO);
+        limbs
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use plonky2::field::goldilocks_field::GoldilocksField;
+    use plonky2::timed;
+    use plonky2::util::log2_ceil;
+    use plonky2::util::timing::TimingTree;
+
+    use super::*;
+    use crate::chip::ec::gadget::EllipticCurveAirWriter;
+    use crate::chip::ec::weierstrass::bls12_381::instruction::Bls12381FpInstruction;
+    use crate::chip::ec::EllipticCurve;
+    use crate::chip::trace::writer::data::AirWriterData;
+    use crate::chip::trace::writer::AirWriter;
+    use crate::chip::AirParameters;
+    use crate::machine::ec::hash_to_curve::{expand_message_xmd_digests, hash_to_curve};
+    use crate::machine::emulated::builder::EmulatedBuilder;
+    use crate::math::goldilocks::cubic::GoldilocksCubicParameters;
+    use crate::maybe_rayon::*;
+    use crate::plonky2::stark::config::CurtaPoseidonGoldilocksConfig;
+    use crate::polynomial::Polynomial;
+
+    #[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+    struct MapToG1Test;
+
+    impl AirParameters for MapToG1Test {
+        type Field = GoldilocksField;
+        type CubicParams = GoldilocksCubicParameters;
+
+        type Instruction = Bls12381FpInstruction;
+
+        const NUM_ARITHMETIC_COLUMNS: usize = 2448;
+        const NUM_FREE_COLUMNS: usize = 19;
+        const EXTENDED_COLUMNS: usize = 3753;
+    }
+
+    #[test]
+    fn test_bls12_381_map_to_g1() {
+        type F = GoldilocksField;
+        type L = MapToG1Test;
+        type C = CurtaPoseidonGoldilocksConfig;
+
+        let _ = env_logger::builder().is_test(true).try_init();
+        let mut timing = TimingTree::new("BLS12-381 map to G1", log::Level::Debug);
+
+        let dst = b"QUUX-V01-CS02-with-BLS12381G1_XMD:SHA-256_SSWU_RO_";
+        let messages: [&[u8]; 2] = [b"", b"abc"];
+
+        let mut builder = EmulatedBuilder::<L>::new();
+        let uniform_bytes = messages
+            .iter()
+            .map(|_| builder.alloc_uniform_bytes())
+            .collect::<Vec<_>>();
+        let results = builder.bls12_381_map_to_g1(&uniform_bytes);
+
+        let num_rows = 1 << log2_ceil(messages.len() * Bls12381::nb_scalar_bits());
+        let stark = builder.build::<C, 2>(num_rows);
+
+        let mut writer_data = AirWriterData::new(&stark.air_data, num_rows);
+        let mut writer = writer_data.public_writer();
+        for ((msg, bytes), result) in messages
+            .iter()
+            .zip(uniform_bytes.iter())
+            .zip(results.iter())
+        {
+            let (_, uniform_digests) = expand_message_xmd_digests(msg, dst, 128);
+            let limbs = uniform_bytes_limbs::<F>(&uniform_digests);
+            for (register, value) in bytes.digests.iter().zip(limbs) {
+                writer.write(register, &Polynomial::from_coefficients(value));
+            }
+            writer.write_ec_point(result, &hash_to_curve(msg, dst));
+        }
+
+        timed!(timing, "write input", {
+            stark.air_data.write_global_instructions(&mut writer);
+
+            writer_data.chunks_par(256).for_each(|mut chunk| {
+                for i in 0..256 {
+                    let mut writer = chunk.window_writer(i);
+                    stark.air_data.write_trace_instructions(&mut writer);
+                }
+            });
+        });
+
+        let (trace, public) = (writer_data.trace, writer_data.public);
+
+        let proof = timed!(
+            timing,
+            "generate stark proof",
+            stark.prove(&trace, &public, &mut timing).unwrap()
+        );
+
+        stark.verify(proof, &public).unwrap();
+
+        timing.print();
+    }
+}
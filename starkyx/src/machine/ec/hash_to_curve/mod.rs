@@ -0,0 +1,191 @@
+//! Hashing to BLS12-381 G1 with the suite `BLS12381G1_XMD:SHA-256_SSWU_RO_` of RFC 9380.
+//!
+//! A message is hashed to a point of G1 in two steps, each proven by its own STARK:
+//!
+//! - [`expand::ExpandMessageBuilder`] computes the uniform bytes of `expand_message_xmd` with the
+//!   SHA-256 chip.
+//! - [`map::MapToG1Builder`] reduces the uniform bytes to two field elements, maps them to the
+//!   curve by the simplified SWU map and the 11-isogeny, and clears the cofactor.
+//!
+//! The digests of the first proof and the uniform bytes of the second are public inputs, and the
+//! verifier links the two proofs by checking that they agree, see [`map::uniform_bytes_limbs`].
+
+use num::BigUint;
+
+use crate::chip::ec::point::AffinePoint;
+use crate::chip::ec::weierstrass::bls12_381::map::map_to_g1;
+use crate::chip::ec::weierstrass::bls12_381::params::{Bls12381, Bls12381BaseField};
+use crate::chip::field::parameters::FieldParameters;
+use crate::machine::hash::sha::algorithm::SHAPure;
+use crate::machine::hash::sha::sha256::SHA256;
+
+pub mod expand;
+pub mod map;
+
+/// The number of uniform bytes hashed to two field elements, `2 * L` for `L = 64`.
+pub const UNIFORM_BYTES: usize = 128;
+
+/// The number of bytes of a SHA-256 chunk, and of the block of zeros `Z_pad`.
+const BLOCK_BYTES: usize = 64;
+/// The number of bytes of a SHA-256 digest.
+const DIGEST_BYTES: usize = 32;
+
+/// The domain separation tag followed by its length.
+fn dst_prime(dst: &[u8]) -> Vec<u8> {
+    assert!(dst.len() <= 255, "The DST must be at most 255 bytes long");
+    let mut dst_prime = dst.to_vec();
+    dst_prime.push(dst.len() as u8);
+    dst_prime
+}
+
+/// The number `ell` of digests `b_1, ..., b_ell` of an output of `len_in_bytes` bytes.
+pub fn num_uniform_digests(len_in_bytes: usize) -> usize {
+    let ell = len_in_bytes.div_ceil(DIGEST_BYTES);
+    assert!(ell <= 255 && len_in_bytes <= u16::MAX as usize);
+    ell
+}
+
+/// The padded chunks of `msg_prime = Z_pad || msg || I2OSP(len_in_bytes, 2) || I2OSP(0, 1) ||
+/// DST_prime`, whose hash is `b_0`, after the chunk of zeros `Z_pad`.
+pub fn expand_message_chunks(msg: &[u8], dst: &[u8], len_in_bytes: usize) -> Vec<u32> {
+    let mut msg_prime = vec![0u8; BLOCK_BYTES];
+    msg_prime.extend_from_slice(msg);
+    msg_prime.extend_from_slice(&(len_in_bytes as u16).to_be_bytes());
+    msg_prime.push(0);
+    msg_prime.extend(dst_prime(dst));
+    SHA256::pad(&msg_prime)[16..].to_vec()
+}
+
+/// The padded message hashed for `b_i`, with zeros in place of its first 32 bytes, which are
+/// `b_0` for `i = 1` and `strxor(b_0, b_(i - 1))` otherwise.
+fn uniform_digest_padding(dst: &[u8], i: usize) -> Vec<u32> {
+    let mut msg = vec![0u8; DIGEST_BYTES];
+    msg.push(i as u8);
+    msg.extend(dst_prime(dst));
+    SHA256::pad(&msg)
+}
+
+/// The state after the compression of the chunks of `padded_msg` from the initial hash.
+fn sha256_chunks<I: IntoIterator<Item = u32>>(padded_msg: I) -> [u32; 8] {
+    let padded_msg = padded_msg.into_iter().collect::<Vec<_>>();
+    padded_msg
+        .chunks_exact(16)
+        .fold(SHA256::INITIAL_HASH, |state, chunk| {
+            SHA256::process(state, &SHA256::pre_process(chunk))
+        })
+}
+
+/// The digests `b_0` and `b_1, ..., b_ell` of `expand_message_xmd`.
+pub fn expand_message_xmd_digests(
+    msg: &[u8],
+    dst: &[u8],
+    len_in_bytes: usize,
+) -> ([u32; 8], Vec<[u32; 8]>) {
+    let ell = num_uniform_digests(len_in_bytes);
+    let b_0 = sha256_chunks(core::iter::repeat(0).take(16).chain(expand_message_chunks(
+        msg,
+        dst,
+        len_in_bytes,
+    )));
+
+    let mut uniform_digests: Vec<[u32; 8]> = Vec::with_capacity(ell);
+    for i in 1..=ell {
+        let prefix = match uniform_digests.last() {
+            None => b_0,
+            Some(b_prev) => core::array::from_fn(|j| b_0[j] ^ b_prev[j]),
+        };
+        let padding = uniform_digest_padding(dst, i);
+        uniform_digests.push(sha256_chunks(
+            prefix.into_iter().chain(padding[8..].to_vec()),
+        ));
+    }
+
+    (b_0, uniform_digests)
+}
+
+/// The `expand_message_xmd` function of RFC 9380 with SHA-256.
+pub fn expand_message_xmd(msg: &[u8], dst: &[u8], len_in_bytes: usize) -> Vec<u8> {
+    let (_, uniform_digests) = expand_message_xmd_digests(msg, dst, len_in_bytes);
+    let mut uniform_bytes = uniform_digests
+        .iter()
+        .flatten()
+        .flat_map(|word| word.to_be_bytes())
+        .collect::<Vec<_>>();
+    uniform_bytes.truncate(len_in_bytes);
+    uniform_bytes
+}
+
+/// The two field elements of the `hash_to_field` function of RFC 9380.
+pub fn hash_to_field(msg: &[u8], dst: &[u8]) -> [BigUint; 2] {
+    let uniform_bytes = expand_message_xmd(msg, dst, UNIFORM_BYTES);
+    let modulus = Bls12381BaseField::modulus();
+    core::array::from_fn(|i| {
+        let len = UNIFORM_BYTES / 2;
+        BigUint::from_bytes_be(&uniform_bytes[i * len..(i + 1) * len]) % &modulus
+    })
+}
+
+/// The `hash_to_curve` function of RFC 9380.
+pub fn hash_to_curve(msg: &[u8], dst: &[u8]) -> AffinePoint<Bls12381> {
+    let [u0, u1] = hash_to_field(msg, dst);
+    map_to_g1(&u0, &u1)
+}
+
+#[cfg(test)]
+mod tests {
+    use num::Num;
+
+    use super::*;
+
+    const DST: &[u8] = b"QUUX-V01-CS02-with-BLS12381G1_XMD:SHA-256_SSWU_RO_";
+
+    fn from_hex(s: &str) -> BigUint {
+        BigUint::from_str_radix(s, 16).unwrap()
+    }
+
+    #[test]
+    fn test_expand_message_xmd() {
+        // The test vectors of section K.1 of RFC 9380.
+        let dst = b"QUUX-V01-CS02-with-expander-SHA256-128";
+        assert_eq!(
+            hex::encode(expand_message_xmd(b"", dst, 0x20)),
+            "68a985b87eb6b46952128911f2a4412bbc302a9d759667f87f7a21d803f07235"
+        );
+        assert_eq!(
+            hex::encode(expand_message_xmd(b"abc", dst, 0x20)),
+            "d8ccab23b5985ccea865c6c97b6e5b8350e794e603b4b97902f53a8a0d605615"
+        );
+    }
+
+    #[test]
+    fn test_hash_to_curve() {
+        // The test vectors of section J.9.1 of RFC 9380.
+        let [u0, u1] = hash_to_field(b"", DST);
+        assert_eq!(
+            u0,
+            from_hex("0ba14bd907ad64a016293ee7c2d276b8eae71f25a4b941eece7b0d89f17f75cb3ae5438a614fb61d6835ad59f29c564f")
+        );
+        assert_eq!(
+            u1,
+            from_hex("019b9bd7979f12657976de2884c7cce192b82c177c80e0ec604436a7f538d231552f0d96d9f7babe5fa3b19b3ff25ac9")
+        );
+
+        let test_vectors: [(&[u8], &str, &str); 2] = [
+            (
+                b"",
+                "052926add2207b76ca4fa57a8734416c8dc95e24501772c814278700eed6d1e4e8cf62d9c09db0fac349612b759e79a1",
+                "08ba738453bfed09cb546dbb0783dbb3a5f1f566ed67bb6be0e8c67e2e81a4cc68ee29813bb7994998f3eae0c9c6a265",
+            ),
+            (
+                b"abc",
+                "03567bc5ef9c690c2ab2ecdf6a96ef1c139cc0b2f284dca0a9a7943388a49a3aee664ba5379a7655d3c68900be2f6903",
+                "0b9c15f3fe6e5cf4211f346271d7b01c8f3b28be689c8429c85b67af215533311f0b8dfaaa154fa6b88176c229f2885d",
+            ),
+        ];
+        for (msg, x, y) in test_vectors {
+            let point = hash_to_curve(msg, DST);
+            assert_eq!(point.x, from_hex(x));
+            assert_eq!(point.y, from_hex(y));
+        }
+    }
+}
@@ -1,2 +1,4 @@
 pub mod builder;
+#[cfg(feature = "sha")]
+pub mod hash_to_curve;
 pub mod scalar_mul;
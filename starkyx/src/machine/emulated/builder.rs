@@ -69,7 +69,10 @@ impl<L: AirParameters> EmulatedBuilder<L> {
 
         let config = StarkyConfig::<C, D>::standard_fast_config(num_rows);
         let (air, trace_data) = api.build();
-        let stark = Starky::new(air);
+        if let Err(e) = trace_data.check_num_rows(num_rows) {
+            panic!("{}", e);
+        }
+        let stark = Starky::from_chip(air);
 
         let lookup_config = StarkyConfig::<C, D>::standard_fast_config(NUM_LOOKUP_ROWS);
         let (lookup_air, lookup_trace_data) = lookup_builder.build();
@@ -1,14 +1,19 @@
 use anyhow::Result;
 use plonky2::field::extension::Extendable;
 use plonky2::hash::hash_types::RichField;
-use plonky2::iop::challenger::{Challenger, RecursiveChallenger};
+#[cfg(feature = "prover")]
+use plonky2::iop::challenger::Challenger;
 use plonky2::iop::target::Target;
 use plonky2::iop::witness::WitnessWrite;
 use plonky2::plonk::circuit_builder::CircuitBuilder;
+use plonky2::plonk::config::AlgebraicHasher;
+#[cfg(feature = "prover")]
 use plonky2::timed;
+#[cfg(feature = "prover")]
 use plonky2::util::timing::TimingTree;
 use serde::{Deserialize, Serialize};
 
+#[cfg(feature = "prover")]
 use super::builder::NUM_LOOKUP_ROWS;
 use super::proof::{
     EmulatedStarkChallenges, EmulatedStarkChallengesTarget, EmulatedStarkProof,
@@ -19,17 +24,23 @@ use crate::chip::register::array::ArrayRegister;
 use crate::chip::register::element::ElementRegister;
 use crate::chip::table::lookup::values::LogLookupValues;
 use crate::chip::trace::data::AirTraceData;
+#[cfg(feature = "prover")]
 use crate::chip::trace::writer::{InnerWriterData, TraceWriter};
 use crate::chip::{AirParameters, Chip};
 use crate::math::prelude::*;
+#[cfg(feature = "prover")]
 use crate::maybe_rayon::*;
 use crate::plonky2::stark::config::{CurtaConfig, StarkyConfig};
+#[cfg(feature = "prover")]
 use crate::plonky2::stark::prover::{AirCommitment, StarkyProver};
 use crate::plonky2::stark::verifier::{
     add_virtual_air_proof, set_air_proof_target, StarkyVerifier,
 };
 use crate::plonky2::stark::Starky;
 use crate::plonky2::Plonky2Air;
+#[cfg(feature = "prover")]
+use crate::plonky2::ProverAir;
+#[cfg(feature = "prover")]
 use crate::trace::AirTrace;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -73,6 +84,7 @@ where
         (element.as_canonical_u64() as usize, 0)
     }
 
+    #[cfg(feature = "prover")]
     fn generate_execution_traces(
         &self,
         execution_trace: &AirTrace<L::Field>,
@@ -124,6 +136,7 @@ where
         (main_writer, lookup_writer)
     }
 
+    #[cfg(feature = "prover")]
     fn generate_extended_traces(
         &self,
         main_writer: &TraceWriter<L::Field>,
@@ -149,6 +162,7 @@ where
             .copy_from_slice(&lookup_writer.global.read().unwrap());
     }
 
+    #[cfg(feature = "prover")]
     fn generate_trace(
         &self,
         execution_trace: &AirTrace<L::Field>,
@@ -288,14 +302,18 @@ where
         )
     }
 
+    #[cfg(feature = "prover")]
     pub fn prove(
         &self,
         execution_trace: &AirTrace<L::Field>,
         public_values: &[L::Field],
         timing: &mut TimingTree,
-    ) -> Result<EmulatedStarkProof<L::Field, C, D>> {
+    ) -> Result<EmulatedStarkProof<L::Field, C, D>>
+    where
+        Chip<L>: ProverAir<L::Field, C, D>,
+    {
         // Initialize challenger.
-        let mut challenger = Challenger::new();
+        let mut challenger = self.config.challenger();
 
         // Generate stark commitment.
         let (main_air_commitment, lookup_air_commitment) = timed!(
@@ -343,7 +361,7 @@ where
         public_values: &[L::Field],
     ) -> EmulatedStarkChallenges<L::Field, D> {
         // Initialize challenger.
-        let mut challenger = Challenger::<L::Field, C::Hasher>::new();
+        let mut challenger = self.config.challenger();
 
         // Observe public values.
         challenger.observe_elements(public_values);
@@ -364,13 +382,13 @@ where
         // Get all challenges.
         let main_challenges = proof.main_proof.get_iop_challenges(
             &self.config,
-            self.config.degree_bits,
+            self.config.fri_degree_bits(),
             challenges.clone(),
             &mut challenger,
         );
         let lookup_challenges = proof.lookup_proof.get_iop_challenges(
             &self.lookup_config,
-            self.lookup_config.degree_bits,
+            self.lookup_config.fri_degree_bits(),
             challenges,
             &mut challenger,
         );
@@ -412,7 +430,8 @@ where
             public_values,
             &global_values,
             lookup_challenges,
-        )
+        )?;
+        Ok(())
     }
 
     pub fn add_virtual_proof_with_pis_target(
@@ -443,7 +462,7 @@ where
         public_values: &[Target],
     ) -> EmulatedStarkChallengesTarget<D> {
         // Initialize challenger.
-        let mut challenger = RecursiveChallenger::<L::Field, C::InnerHasher, D>::new(builder);
+        let mut challenger = self.config.recursive_challenger(builder);
 
         // Observe public values.
         challenger.observe_elements(public_values);
@@ -486,7 +505,9 @@ where
         builder: &mut CircuitBuilder<L::Field, D>,
         proof: &EmulatedStarkProofTarget<D>,
         public_values: &[Target],
-    ) {
+    ) where
+        C::Hasher: AlgebraicHasher<L::Field>,
+    {
         let challenges = self.get_challenges_target(builder, proof, public_values);
         let EmulatedStarkProofTarget {
             main_proof,
@@ -520,7 +541,10 @@ where
         witness: &mut W,
         proof_tagret: &EmulatedStarkProofTarget<D>,
         proof: EmulatedStarkProof<L::Field, C, D>,
-    ) -> Result<()> {
+    ) -> Result<()>
+    where
+        C::Hasher: AlgebraicHasher<L::Field>,
+    {
         let EmulatedStarkProofTarget {
             main_proof,
             lookup_proof,
@@ -534,7 +558,7 @@ where
     }
 }
 
-#[cfg(test)]
+#[cfg(all(test, feature = "bigint"))]
 mod tests {
     use num::bigint::RandBigInt;
     use plonky2::field::goldilocks_field::GoldilocksField;
@@ -0,0 +1,218 @@
+//! Batches of messages of different lengths hashed in one table.
+//!
+//! The chunks of the messages are placed one after the other, so that each message takes as many
+//! chunks as it needs, and the table is padded with dummy chunks up to a power of two rows. The
+//! table also records the message of each row, and the outputs of a message are found by its
+//! index in the batch.
+
+use core::ops::Range;
+
+use plonky2::util::log2_ceil;
+
+use crate::chip::memory::time::Time;
+use crate::chip::register::element::ElementRegister;
+use crate::machine::builder::Builder;
+use crate::math::prelude::*;
+
+/// The layout of the chunks of a batch of messages.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BatchSchedule {
+    /// The number of chunks of each message.
+    chunk_counts: Vec<usize>,
+    /// The number of outputs of each message, which are the states after its last chunks.
+    output_counts: Vec<usize>,
+}
+
+impl BatchSchedule {
+    /// A schedule of messages with the given numbers of chunks and one output each.
+    pub fn new(chunk_counts: Vec<usize>) -> Self {
+        let output_counts = vec![1; chunk_counts.len()];
+        Self::with_outputs(chunk_counts, output_counts)
+    }
+
+    /// A schedule of messages with the given numbers of chunks and of outputs, such as sponges
+    /// squeezing several blocks.
+    pub fn with_outputs(chunk_counts: Vec<usize>, output_counts: Vec<usize>) -> Self {
+        assert_eq!(chunk_counts.len(), output_counts.len());
+        assert!(
+            !chunk_counts.is_empty(),
+            "A batch needs at least one message"
+        );
+        for (num_chunks, num_outputs) in chunk_counts.iter().zip(output_counts.iter()) {
+            assert!(*num_outputs > 0 && num_outputs <= num_chunks);
+        }
+        Self {
+            chunk_counts,
+            output_counts,
+        }
+    }
+
+    pub fn num_messages(&self) -> usize {
+        self.chunk_counts.len()
+    }
+
+    pub fn num_chunks(&self) -> usize {
+        self.chunk_counts.iter().sum()
+    }
+
+    /// The number of rows of a table whose chunks take `cycle_length` rows.
+    pub fn num_rows(&self, cycle_length: usize) -> usize {
+        1 << log2_ceil(cycle_length * self.num_chunks())
+    }
+
+    /// The indices of the chunks of the message `message`.
+    pub fn chunk_range(&self, message: usize) -> Range<usize> {
+        let start = self.chunk_counts[..message].iter().sum();
+        start..start + self.chunk_counts[message]
+    }
+
+    /// The indices of the outputs of the message `message` among the outputs of the batch.
+    pub fn output_range(&self, message: usize) -> Range<usize> {
+        let start = self.output_counts[..message].iter().sum();
+        start..start + self.output_counts[message]
+    }
+
+    /// The end bit of each chunk, which is set at the last chunk of each message.
+    pub fn end_bits(&self) -> Vec<bool> {
+        self.chunk_counts
+            .iter()
+            .flat_map(|num_chunks| (0..*num_chunks).map(move |i| i == num_chunks - 1))
+            .collect()
+    }
+
+    /// The output bit of each chunk, which is set at the last chunks of each message.
+    pub fn output_bits(&self) -> Vec<bool> {
+        self.chunk_counts
+            .iter()
+            .zip(self.output_counts.iter())
+            .flat_map(|(num_chunks, num_outputs)| {
+                (0..*num_chunks).map(move |i| i >= num_chunks - num_outputs)
+            })
+            .collect()
+    }
+
+    /// The indices of the chunks whose output bits are set.
+    pub fn output_indices(&self) -> Vec<usize> {
+        self.output_bits()
+            .into_iter()
+            .enumerate()
+            .filter(|(_, bit)| *bit)
+            .map(|(i, _)| i)
+            .collect()
+    }
+
+    /// The index of the message of each chunk.
+    pub fn chunk_message_ids(&self) -> Vec<usize> {
+        self.chunk_counts
+            .iter()
+            .enumerate()
+            .flat_map(|(message, num_chunks)| core::iter::repeat(message).take(*num_chunks))
+            .collect()
+    }
+
+    /// The index of the message of each row of a table whose chunks take `cycle_length` rows,
+    /// which is the number of messages in the rows of the dummy chunks.
+    pub fn row_message_ids(&self, cycle_length: usize) -> Vec<usize> {
+        let chunk_message_ids = self.chunk_message_ids();
+        (0..self.num_rows(cycle_length))
+            .map(|row| {
+                chunk_message_ids
+                    .get(row / cycle_length)
+                    .copied()
+                    .unwrap_or(self.num_messages())
+            })
+            .collect()
+    }
+
+    /// Loads the index of the message of the current row, given the index `chunk_id` of the chunk
+    /// of the row, see [`BatchSchedule::row_message_ids`].
+    pub fn load_message_id<B: Builder>(
+        &self,
+        builder: &mut B,
+        chunk_id: ElementRegister,
+        cycle_length: usize,
+    ) -> ElementRegister {
+        // Every chunk is read in each of its rows, and the table ends in the middle of the last
+        // dummy chunk.
+        let num_rows = self.num_rows(cycle_length);
+        let num_chunks = num_rows / cycle_length + 1;
+        let cycle_length_element = builder.constant(&B::Field::from_canonical_usize(cycle_length));
+        let last_length =
+            builder.constant(&B::Field::from_canonical_usize(num_rows % cycle_length));
+        let chunk_message_ids = self.chunk_message_ids();
+        let message_ids = builder.uninit_slice();
+        for i in 0..num_chunks {
+            let message_id = chunk_message_ids
+                .get(i)
+                .copied()
+                .unwrap_or(self.num_messages());
+            let message_id = builder.constant(&B::Field::from_canonical_usize(message_id));
+            let multiplicity = match i < num_chunks - 1 {
+                true => cycle_length_element,
+                false => last_length,
+            };
+            builder.store(
+                &message_ids.get(i),
+                message_id,
+                &Time::zero(),
+                Some(multiplicity),
+                None,
+                None,
+            );
+        }
+        builder.load(&message_ids.get_at(chunk_id), &Time::zero(), None, None)
+    }
+}
+
+/// The outputs of a batch of messages, with the index of the message of each row.
+#[derive(Debug, Clone)]
+pub struct HashBatch<D> {
+    pub schedule: BatchSchedule,
+    /// The outputs of all messages, in order.
+    pub outputs: Vec<D>,
+    /// The index of the message of the current row, see [`BatchSchedule::row_message_ids`].
+    pub message_id: ElementRegister,
+}
+
+impl<D: Copy> HashBatch<D> {
+    /// The outputs of the message `message`.
+    pub fn outputs(&self, message: usize) -> &[D] {
+        &self.outputs[self.schedule.output_range(message)]
+    }
+
+    /// The digest of the message `message`, which is its first output.
+    pub fn digest(&self, message: usize) -> D {
+        self.outputs(message)[0]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_batch_schedule() {
+        let schedule = BatchSchedule::with_outputs(vec![1, 3, 2], vec![1, 1, 2]);
+        assert_eq!(schedule.num_chunks(), 6);
+        assert_eq!(schedule.num_rows(64), 512);
+        assert_eq!(schedule.num_rows(24), 256);
+        assert_eq!(schedule.chunk_range(1), 1..4);
+        assert_eq!(schedule.output_range(2), 2..4);
+        assert_eq!(
+            schedule.end_bits(),
+            vec![true, false, false, true, false, true]
+        );
+        assert_eq!(
+            schedule.output_bits(),
+            vec![true, false, false, true, true, true]
+        );
+        assert_eq!(schedule.output_indices(), vec![0, 3, 4, 5]);
+        assert_eq!(schedule.chunk_message_ids(), vec![0, 1, 1, 1, 2, 2]);
+
+        let row_message_ids = schedule.row_message_ids(64);
+        assert_eq!(row_message_ids[63], 0);
+        assert_eq!(row_message_ids[64], 1);
+        assert_eq!(row_message_ids[6 * 64 - 1], 2);
+        assert_eq!(row_message_ids[6 * 64], 3);
+    }
+}
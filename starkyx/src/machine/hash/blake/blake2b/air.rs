@@ -2,8 +2,9 @@ use log::debug;
 use plonky2::util::log2_ceil;
 
 use super::data::{BLAKE2BConstNums, BLAKE2BConsts, BLAKE2BData};
+use super::params::{BLAKE2BParams, MAX_AIR_DIGEST_LENGTH};
 use super::register::BLAKE2BDigestRegister;
-use super::{BLAKE2B, COMPRESS_LENGTH, IV, STATE_SIZE};
+use super::{BLAKE2B, COMPRESS_LENGTH, STATE_SIZE};
 use crate::chip::memory::instruction::MemorySliceIndex;
 use crate::chip::memory::pointer::slice::Slice;
 use crate::chip::memory::time::Time;
@@ -64,11 +65,30 @@ pub trait BLAKEAir<B: Builder>: HashIntConversion<B> + HashDigest<B> {
         num_messages: &ElementRegister,
     ) -> Vec<Self::DigestRegister>;
 
+    /// Computes the BLAKE2b hashes of messages with the parameters `params`, which are shared by
+    /// all messages.
+    ///
+    /// The digests are the first 32 bytes of the final states, of which the first
+    /// `params.digest_length` bytes are the hash. The chunks of a keyed hash start with the key
+    /// block, see [`BLAKE2BParams::pad`].
+    #[allow(clippy::too_many_arguments)]
+    fn blake2b_with_params(
+        builder: &mut B,
+        params: &BLAKE2BParams,
+        padded_chunks: &[ArrayRegister<Self::IntRegister>],
+        t_values: &ArrayRegister<Self::IntRegister>,
+        end_bits: &ArrayRegister<BitRegister>,
+        digest_bits: &ArrayRegister<BitRegister>,
+        digest_indices: &ArrayRegister<ElementRegister>,
+        num_messages: &ElementRegister,
+    ) -> Vec<Self::DigestRegister>;
+
     fn blake2b_const_nums(builder: &mut B) -> BLAKE2BConstNums;
 
     #[allow(clippy::too_many_arguments)]
     fn blake2b_const(
         builder: &mut B,
+        params: &BLAKE2BParams,
         num_rows_element: &ElementRegister,
         num_messages_element: &ElementRegister,
         num_real_compresses: usize,
@@ -105,8 +125,10 @@ pub trait BLAKEAir<B: Builder>: HashIntConversion<B> + HashDigest<B> {
         num_dummy_rows: usize,
     ) -> BLAKE2BMemory;
 
+    #[allow(clippy::too_many_arguments)]
     fn blake2b_data(
         builder: &mut B,
+        params: &BLAKE2BParams,
         padded_chunks: &[ArrayRegister<Self::IntRegister>],
         t_values: &ArrayRegister<Self::IntRegister>,
         end_bits: &ArrayRegister<BitRegister>,
@@ -181,8 +203,36 @@ where
         digest_indices: &ArrayRegister<ElementRegister>,
         num_messages: &ElementRegister,
     ) -> Vec<Self::DigestRegister> {
+        Self::blake2b_with_params(
+            builder,
+            &BLAKE2BParams::default(),
+            padded_chunks,
+            t_values,
+            end_bits,
+            digest_bits,
+            digest_indices,
+            num_messages,
+        )
+    }
+
+    fn blake2b_with_params(
+        builder: &mut BytesBuilder<L>,
+        params: &BLAKE2BParams,
+        padded_chunks: &[ArrayRegister<Self::IntRegister>],
+        t_values: &ArrayRegister<Self::IntRegister>,
+        end_bits: &ArrayRegister<BitRegister>,
+        digest_bits: &ArrayRegister<BitRegister>,
+        digest_indices: &ArrayRegister<ElementRegister>,
+        num_messages: &ElementRegister,
+    ) -> Vec<Self::DigestRegister> {
+        assert!(
+            params.digest_length <= MAX_AIR_DIGEST_LENGTH,
+            "Digests of more than {} bytes are not supported",
+            MAX_AIR_DIGEST_LENGTH
+        );
         let data = Self::blake2b_data(
             builder,
+            params,
             padded_chunks,
             t_values,
             end_bits,
@@ -248,6 +298,7 @@ where
     #[allow(clippy::too_many_arguments)]
     fn blake2b_const(
         builder: &mut BytesBuilder<L>,
+        params: &BLAKE2BParams,
         num_rows_element: &ElementRegister,
         num_messages_element: &ElementRegister,
         num_real_compresses: usize,
@@ -272,7 +323,9 @@ where
             builder.constant(&L::Field::from_canonical_u64(FIRST_COMPRESS_H_READ_TS));
 
         let iv_values = builder.constant_array::<Self::IntRegister>(
-            &IV.map(&<Self as HashIntConversion<BytesBuilder<L>>>::int_to_field_value),
+            &params
+                .initial_state()
+                .map(&<Self as HashIntConversion<BytesBuilder<L>>>::int_to_field_value),
         );
         let iv: Slice<crate::chip::uint::register::ByteArrayRegister<8>> = builder.uninit_slice();
         for (i, value) in iv_values.iter().enumerate() {
@@ -797,6 +850,7 @@ where
 
     fn blake2b_data(
         builder: &mut BytesBuilder<L>,
+        params: &BLAKE2BParams,
         padded_chunks: &[ArrayRegister<Self::IntRegister>],
         t_values: &ArrayRegister<Self::IntRegister>,
         end_bits: &ArrayRegister<BitRegister>,
@@ -813,6 +867,7 @@ where
         let degree_log = log2_ceil(num_real_compresses * 96);
         assert!(degree_log < 31, "AIR degree is too large");
         debug!("AIR degree after padding: {}", 1 << degree_log);
+        builder.api().require_rows("BLAKE2b", 1 << degree_log);
 
         let num_dummy_compresses = (1 << degree_log) / COMPRESS_LENGTH + 1 - num_real_compresses;
         let length_last_compress = (1 << degree_log) % COMPRESS_LENGTH;
@@ -841,6 +896,7 @@ where
         // create the consts data
         let consts = Self::blake2b_const(
             builder,
+            params,
             &num_rows_element,
             num_messages_element,
             num_real_compresses,
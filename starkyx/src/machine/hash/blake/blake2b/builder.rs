@@ -1,4 +1,5 @@
 use super::air::BLAKEAir;
+use super::params::BLAKE2BParams;
 use crate::chip::register::array::ArrayRegister;
 use crate::chip::register::bit::BitRegister;
 use crate::chip::register::element::ElementRegister;
@@ -24,6 +25,30 @@ pub trait BlakeBuilder: Builder {
             num_messages,
         )
     }
+
+    /// Computes BLAKE2b hashes with the parameters `params`, see [`BLAKEAir::blake2b_with_params`].
+    #[allow(clippy::too_many_arguments)]
+    fn blake2b_with_params<B: BLAKEAir<Self>>(
+        &mut self,
+        params: &BLAKE2BParams,
+        padded_chunks: &[ArrayRegister<B::IntRegister>],
+        t_values: &ArrayRegister<B::IntRegister>,
+        end_bits: &ArrayRegister<BitRegister>,
+        digest_bits: &ArrayRegister<BitRegister>,
+        digest_indices: &ArrayRegister<ElementRegister>,
+        num_messages: &ElementRegister,
+    ) -> Vec<B::DigestRegister> {
+        B::blake2b_with_params(
+            self,
+            params,
+            padded_chunks,
+            t_values,
+            end_bits,
+            digest_bits,
+            digest_indices,
+            num_messages,
+        )
+    }
 }
 
 impl<B: Builder> BlakeBuilder for B {}
@@ -41,18 +66,21 @@ pub mod test_utils {
     use plonky2::plonk::circuit_builder::CircuitBuilder;
     use plonky2::plonk::circuit_data::CircuitConfig;
     use plonky2::timed;
+    use plonky2::util::log2_ceil;
     use plonky2::util::timing::TimingTree;
     use serde::{Deserialize, Serialize};
 
     use super::*;
     use crate::chip::uint::operations::instruction::UintInstruction;
+    use crate::chip::uint::register::U64Register;
     use crate::chip::uint::util::u64_to_le_field_bytes;
     use crate::chip::AirParameters;
     use crate::machine;
     use crate::machine::bytes::builder::BytesBuilder;
+    use crate::machine::hash::blake::blake2b::padding::{message_mask, BLAKE2BPaddingBuilder};
     use crate::machine::hash::blake::blake2b::pure::BLAKE2BPure;
     use crate::machine::hash::blake::blake2b::utils::BLAKE2BUtil;
-    use crate::machine::hash::blake::blake2b::{BLAKE2B, IV};
+    use crate::machine::hash::blake::blake2b::{BLAKE2B, COMPRESS_LENGTH, IV};
     use crate::machine::hash::HashDigest;
     use crate::math::goldilocks::cubic::GoldilocksCubicParameters;
     use crate::math::prelude::*;
@@ -277,4 +305,131 @@ pub mod test_utils {
 
         timing.print();
     }
+
+    #[test]
+    pub fn test_blake2b_with_params() {
+        type F = GoldilocksField;
+        type C = CurtaPoseidonGoldilocksConfig;
+
+        let _ = env_logger::builder().is_test(true).try_init();
+        let mut timing = TimingTree::new("test_blake2b_with_params", log::Level::Debug);
+
+        // Keyed hashes with a salt, a personalization and a digest of 20 bytes, each padded to
+        // the same number of chunks.
+        let key = b"secret key";
+        let params = BLAKE2BParams {
+            digest_length: 20,
+            key_length: key.len(),
+            salt: *b"0123456789abcdef",
+            personalization: *b"personalization!",
+        };
+        let long_msg = (0..200).collect::<Vec<u8>>();
+        let msgs: [(&[u8], &str); 3] = [
+            (b"abc", "c9f60e3a5a82fdc6948de04ecf9c946c7690d9a5"),
+            (&long_msg, "1a3ba9d668549e6f152f494bfdd181878e454daf"),
+            (b"", "cca4f157f093ddade066f0aebf049ad1bf662019"),
+        ];
+        let num_chunks = 3;
+        let num_compresses = num_chunks * msgs.len();
+        let num_rows = 1 << log2_ceil(COMPRESS_LENGTH * num_compresses);
+
+        // Build the stark.
+        let mut builder = BytesBuilder::<BLAKE2BTest>::new();
+        let padded_chunks = (0..num_compresses)
+            .map(|_| builder.alloc_array_public::<U64Register>(16))
+            .collect::<Vec<_>>();
+        let t_values = builder.alloc_array_public::<U64Register>(num_compresses);
+        let end_bits = builder.alloc_array_public::<BitRegister>(num_compresses);
+        let digest_bits = builder.alloc_array_public::<BitRegister>(num_compresses);
+        let digest_indices = builder.alloc_array_public(msgs.len());
+        let masks = (0..msgs.len())
+            .map(|i| {
+                let range = num_chunks * i..num_chunks * (i + 1);
+                let mask = builder.alloc_array_public::<BitRegister>(num_chunks * 128);
+                builder.blake2b_assert_padding(
+                    &padded_chunks[range.clone()],
+                    &t_values.get_subarray(range.clone()),
+                    &digest_bits.get_subarray(range),
+                    &mask,
+                );
+                mask
+            })
+            .collect::<Vec<_>>();
+        let num_messages = builder.constant(&F::from_canonical_usize(msgs.len()));
+        let digests = builder.blake2b_with_params::<BLAKE2B>(
+            &params,
+            &padded_chunks,
+            &t_values,
+            &end_bits,
+            &digest_bits,
+            &digest_indices,
+            &num_messages,
+        );
+
+        let stark = builder.build::<C, 2>(num_rows);
+
+        // Write trace.
+        let mut writer_data = AirWriterData::new(&stark.air_data, num_rows);
+        let mut writer = writer_data.public_writer();
+
+        for (i, ((msg, expected), digest)) in msgs.iter().zip(digests.iter()).enumerate() {
+            let data_length = params.data(key, msg).len();
+            let padded_data = params.pad(key, msg, num_chunks as u64);
+            let digest_chunk = BLAKE2BParams::digest_chunk(data_length);
+            for (j, (chunk, t)) in padded_data
+                .chunks_exact(128)
+                .zip(BLAKE2BParams::t_values(data_length, num_chunks))
+                .enumerate()
+            {
+                let c = num_chunks * i + j;
+                writer.write_array(
+                    &padded_chunks[c],
+                    chunk
+                        .chunks_exact(8)
+                        .map(|bytes| u64::from_le_bytes(bytes.try_into().unwrap()))
+                        .map(u64_to_le_field_bytes),
+                );
+                writer.write(&t_values.get(c), &u64_to_le_field_bytes(t));
+                writer.write(
+                    &end_bits.get(c),
+                    &F::from_canonical_usize((j == num_chunks - 1) as usize),
+                );
+                writer.write(
+                    &digest_bits.get(c),
+                    &F::from_canonical_usize((j == digest_chunk) as usize),
+                );
+            }
+            writer.write(
+                &digest_indices.get(i),
+                &F::from_canonical_usize(num_chunks * i + digest_chunk),
+            );
+            writer.write_array(
+                &masks[i],
+                message_mask(data_length, num_chunks)
+                    .into_iter()
+                    .map(|bit| F::from_canonical_usize(bit as usize)),
+            );
+
+            let state = params.final_state(key, msg);
+            assert_eq!(hex::encode(params.hash(key, msg)), *expected);
+            writer.write_array(
+                &digest.as_array(),
+                state[..4].iter().map(|word| u64_to_le_field_bytes(*word)),
+            );
+        }
+
+        stark.air_data.write_global_instructions(&mut writer);
+
+        for mut chunk in writer_data.chunks(num_rows) {
+            for i in 0..num_rows {
+                let mut writer = chunk.window_writer(i);
+                stark.air_data.write_trace_instructions(&mut writer);
+            }
+        }
+
+        let (trace, public) = (writer_data.trace, writer_data.public);
+
+        let proof = stark.prove(&trace, &public, &mut timing).unwrap();
+        stark.verify(proof, &public).unwrap();
+    }
 }
@@ -3,6 +3,8 @@ use serde::{Deserialize, Serialize};
 pub mod air;
 pub mod builder;
 pub mod data;
+pub mod padding;
+pub mod params;
 pub mod pure;
 pub mod register;
 pub mod utils;
@@ -28,10 +30,10 @@ pub const IV: [u64; STATE_SIZE] = [
     0x5be0cd19137e2179,
 ];
 
-// Note that for this blake2b implementation, we don't support a key input and
-// we assume that the output is 32 bytes
-// So that means the initial hash entry to be
-// 0x6a09e667f3bcc908 xor 0x01010020
+// The IV of BLAKE2b, which is xored with the parameter block to get the initial hash. For an
+// unkeyed hash with a 32 bytes output, the first entry of the initial hash is
+// 0x6a09e667f3bcc908 xor 0x01010020, as in `IV`. Other parameters are given by
+// `params::BLAKE2BParams`.
 const COMPRESS_IV: [u64; STATE_SIZE] = [
     0x6a09e667f3bcc908,
     0xbb67ae8584caa73b,
@@ -0,0 +1,242 @@
+use super::params::CHUNK_BYTES;
+use crate::chip::arithmetic::expression::ArithmeticExpression;
+use crate::chip::register::array::ArrayRegister;
+use crate::chip::register::bit::BitRegister;
+use crate::chip::register::element::ElementRegister;
+use crate::chip::register::Register;
+use crate::chip::uint::register::U64Register;
+use crate::machine::builder::Builder;
+use crate::math::prelude::*;
+
+/// The mask of the bytes of data of `length` bytes padded to `num_chunks` chunks, whose `p`-th
+/// value is `true` iff `p < length`.
+///
+/// The data of a keyed hash starts with the key block, see
+/// [`BLAKE2BParams::data`](super::params::BLAKE2BParams::data).
+pub fn message_mask(length: usize, num_chunks: usize) -> Vec<bool> {
+    let mask_len = num_chunks * CHUNK_BYTES;
+    assert!(
+        length <= mask_len,
+        "Data of {} bytes does not fit in {} chunks",
+        length,
+        num_chunks
+    );
+    (0..mask_len).map(|p| p < length).collect()
+}
+
+pub trait BLAKE2BPaddingBuilder: Builder {
+    /// Constrains `padded_chunks` and `t_values` to be the padding of data of variable length,
+    /// and returns its length in bytes.
+    ///
+    /// The length is given by `message_mask`, whose `p`-th bit is set iff the byte `p` of the
+    /// chunks belongs to the data, see [`message_mask`]. The data is followed by zeros up to the
+    /// end of the chunks, and the digest chunk, whose bit is set in `digest_bits`, is the last
+    /// chunk holding data, or the first chunk for empty data. The number of bytes compressed is
+    /// the length of the data at the digest chunk, and `128 * (i + 1)` at any other chunk `i`, as
+    /// in [`BLAKE2BParams::t_values`](super::params::BLAKE2BParams::t_values).
+    ///
+    /// All registers are public and the constraints are global constraints, so the same circuit
+    /// accepts data of any length up to the number of chunks.
+    fn blake2b_assert_padding(
+        &mut self,
+        padded_chunks: &[ArrayRegister<U64Register>],
+        t_values: &ArrayRegister<U64Register>,
+        digest_bits: &ArrayRegister<BitRegister>,
+        message_mask: &ArrayRegister<BitRegister>,
+    ) -> ElementRegister {
+        let num_chunks = padded_chunks.len();
+        assert_eq!(t_values.len(), num_chunks);
+        assert_eq!(digest_bits.len(), num_chunks);
+        assert_eq!(message_mask.len(), num_chunks * CHUNK_BYTES);
+
+        // The mask extended by zeros after its end.
+        let mask = |p: usize| -> ArithmeticExpression<Self::Field> {
+            if p < message_mask.len() {
+                message_mask.get(p).expr()
+            } else {
+                ArithmeticExpression::zero()
+            }
+        };
+
+        // The mask is a sequence of ones followed by zeros.
+        for (p, bit) in message_mask.iter().enumerate() {
+            self.assert_expression_zero(bit.expr() * bit.not_expr());
+            if p > 0 {
+                self.assert_expression_zero(bit.expr() * message_mask.get(p - 1).not_expr());
+            }
+        }
+        let length = self.public_expression::<ElementRegister>(
+            message_mask
+                .iter()
+                .fold(ArithmeticExpression::zero(), |acc, bit| acc + bit.expr()),
+        );
+
+        for (c, (chunk, digest_bit)) in padded_chunks.iter().zip(digest_bits.iter()).enumerate() {
+            assert_eq!(chunk.len(), 16);
+            let start = c * CHUNK_BYTES;
+
+            // The digest chunk is the one where `128 * c < length <= 128 * (c + 1)`, or the first
+            // chunk if the length is zero.
+            let holds_data = match c {
+                0 => ArithmeticExpression::one(),
+                _ => mask(start),
+            };
+            self.assert_expressions_equal(
+                digest_bit.expr(),
+                holds_data - mask(start + CHUNK_BYTES),
+            );
+
+            // The words are little-endian, as are their bytes.
+            for (i, word) in chunk.iter().enumerate() {
+                for (k, byte) in word.to_le_bytes().iter().enumerate() {
+                    let p = start + 8 * i + k;
+                    self.assert_expression_zero(
+                        (ArithmeticExpression::one() - mask(p)) * byte.expr(),
+                    );
+                }
+            }
+
+            // The number of bytes compressed is less than `2^32`, so that its four high bytes
+            // are zero.
+            let t_bytes = t_values.get(c).to_le_bytes();
+            for byte in t_bytes.iter().skip(4) {
+                self.assert_expression_zero(byte.expr());
+            }
+            let t = t_bytes
+                .iter()
+                .take(4)
+                .enumerate()
+                .fold(ArithmeticExpression::zero(), |acc, (j, byte)| {
+                    acc + byte.expr() * Self::Field::from_canonical_u32(1 << (8 * j))
+                });
+            let chunk_end = Self::Field::from_canonical_usize(start + CHUNK_BYTES);
+            self.assert_expressions_equal(
+                t,
+                digest_bit.expr() * length.expr() + digest_bit.not_expr() * chunk_end,
+            );
+        }
+
+        length
+    }
+}
+
+impl<B: Builder> BLAKE2BPaddingBuilder for B {}
+
+#[cfg(test)]
+mod tests {
+    use plonky2::field::goldilocks_field::GoldilocksField;
+    use plonky2::util::timing::TimingTree;
+    use serde::{Deserialize, Serialize};
+
+    use super::*;
+    use crate::chip::uint::util::u64_to_le_field_bytes;
+    use crate::chip::AirParameters;
+    use crate::machine::hash::blake::blake2b::params::BLAKE2BParams;
+    use crate::machine::stark::builder::StarkBuilder;
+    use crate::math::goldilocks::cubic::GoldilocksCubicParameters;
+    use crate::plonky2::stark::config::CurtaPoseidonGoldilocksConfig;
+    use crate::prelude::{AirWriter, AirWriterData, EmptyInstruction};
+
+    #[derive(Clone, Debug, Serialize, Deserialize)]
+    pub struct BLAKE2BPaddingTest;
+
+    impl AirParameters for BLAKE2BPaddingTest {
+        type Field = GoldilocksField;
+        type CubicParams = GoldilocksCubicParameters;
+        type Instruction = EmptyInstruction<GoldilocksField>;
+
+        const NUM_FREE_COLUMNS: usize = 1;
+        const EXTENDED_COLUMNS: usize = 3;
+    }
+
+    #[test]
+    fn test_blake2b_padding() {
+        type F = GoldilocksField;
+        type L = BLAKE2BPaddingTest;
+        type C = CurtaPoseidonGoldilocksConfig;
+
+        let _ = env_logger::builder().is_test(true).try_init();
+        let mut timing = TimingTree::new("test_blake2b_padding", log::Level::Debug);
+
+        // Lengths at the boundaries of the chunks, all padded to the same number of chunks.
+        let num_chunks = 3;
+        let lengths = [0, 1, 127, 128, 129, 256, 257, 384];
+
+        // Build the stark.
+        let mut builder = StarkBuilder::<L>::new();
+        let messages = lengths
+            .iter()
+            .map(|_| {
+                let padded_chunks = (0..num_chunks)
+                    .map(|_| builder.alloc_array_public::<U64Register>(16))
+                    .collect::<Vec<_>>();
+                let t_values = builder.alloc_array_public::<U64Register>(num_chunks);
+                let digest_bits = builder.alloc_array_public::<BitRegister>(num_chunks);
+                let mask = builder.alloc_array_public::<BitRegister>(num_chunks * CHUNK_BYTES);
+                let length =
+                    builder.blake2b_assert_padding(&padded_chunks, &t_values, &digest_bits, &mask);
+                (padded_chunks, t_values, digest_bits, mask, length)
+            })
+            .collect::<Vec<_>>();
+
+        let num_rows = 1 << 5;
+        let stark = builder.build::<C, 2>(num_rows);
+
+        // Write trace.
+        let mut writer_data = AirWriterData::new(&stark.air_data, num_rows);
+        let mut writer = writer_data.public_writer();
+
+        let params = BLAKE2BParams::default();
+        for (&len, (padded_chunks, t_values, digest_bits, mask, _)) in
+            lengths.iter().zip(messages.iter())
+        {
+            let msg = (1..=len).map(|i| i as u8).collect::<Vec<_>>();
+            let padded_msg = params.pad(&[], &msg, num_chunks as u64);
+            for (register, chunk) in padded_chunks.iter().zip(padded_msg.chunks_exact(128)) {
+                writer.write_array(
+                    register,
+                    chunk
+                        .chunks_exact(8)
+                        .map(|bytes| bytes.iter().map(|b| F::from_canonical_u8(*b)))
+                        .map(|bytes| bytes.collect::<Vec<_>>().try_into().unwrap()),
+                );
+            }
+            writer.write_array(
+                t_values,
+                BLAKE2BParams::t_values(len, num_chunks)
+                    .into_iter()
+                    .map(u64_to_le_field_bytes),
+            );
+            let digest_chunk = BLAKE2BParams::digest_chunk(len);
+            for i in 0..num_chunks {
+                writer.write(
+                    &digest_bits.get(i),
+                    &F::from_canonical_usize((i == digest_chunk) as usize),
+                );
+            }
+            writer.write_array(
+                mask,
+                message_mask(len, num_chunks)
+                    .into_iter()
+                    .map(|bit| F::from_canonical_usize(bit as usize)),
+            );
+        }
+
+        stark.air_data.write_global_instructions(&mut writer);
+        for (&len, (.., length)) in lengths.iter().zip(messages.iter()) {
+            assert_eq!(writer.read(length), F::from_canonical_usize(len));
+        }
+
+        for mut chunk in writer_data.chunks(num_rows) {
+            for i in 0..num_rows {
+                let mut writer = chunk.window_writer(i);
+                stark.air_data.write_trace_instructions(&mut writer);
+            }
+        }
+
+        let (trace, public) = (writer_data.trace, writer_data.public);
+
+        let proof = stark.prove(&trace, &public, &mut timing).unwrap();
+        stark.verify(proof, &public).unwrap();
+    }
+}
@@ -0,0 +1,189 @@
+use serde::{Deserialize, Serialize};
+
+use super::pure::BLAKE2BPure;
+use super::utils::BLAKE2BUtil;
+use super::{BLAKE2B, COMPRESS_IV, STATE_SIZE};
+
+/// The number of bytes of a chunk, and of the key block.
+pub(crate) const CHUNK_BYTES: usize = 128;
+/// The largest digest length, in bytes, of the BLAKE2b AIR, whose digests are the first four
+/// words of the state.
+pub const MAX_AIR_DIGEST_LENGTH: usize = 32;
+
+/// The parameter block of BLAKE2b, as in RFC 7693 and the BLAKE2 specification, for sequential
+/// hashing.
+///
+/// The parameters are xored into the initial hash. A keyed hash also hashes the key, padded with
+/// zeros to a chunk, before the message, see [`BLAKE2BParams::pad`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BLAKE2BParams {
+    /// The length of the digest in bytes, between 1 and 64.
+    pub digest_length: usize,
+    /// The length of the key in bytes, at most 64, or zero for an unkeyed hash.
+    pub key_length: usize,
+    pub salt: [u8; 16],
+    pub personalization: [u8; 16],
+}
+
+impl Default for BLAKE2BParams {
+    /// The parameters of an unkeyed hash with a digest of 32 bytes, which are those of the
+    /// BLAKE2b AIR without parameters.
+    fn default() -> Self {
+        Self {
+            digest_length: 32,
+            key_length: 0,
+            salt: [0; 16],
+            personalization: [0; 16],
+        }
+    }
+}
+
+impl BLAKE2BParams {
+    /// The initial hash, which is the IV xored with the parameter block.
+    pub fn initial_state(&self) -> [u64; STATE_SIZE] {
+        assert!((1..=64).contains(&self.digest_length));
+        assert!(self.key_length <= 64);
+        let mut parameter_block = [0u64; STATE_SIZE];
+        // The digest and key lengths, with a fanout and a maximal depth of 1.
+        parameter_block[0] =
+            self.digest_length as u64 | (self.key_length as u64) << 8 | 1 << 16 | 1 << 24;
+        for (i, bytes) in self
+            .salt
+            .chunks_exact(8)
+            .chain(self.personalization.chunks_exact(8))
+            .enumerate()
+        {
+            parameter_block[4 + i] = u64::from_le_bytes(bytes.try_into().unwrap());
+        }
+        core::array::from_fn(|i| COMPRESS_IV[i] ^ parameter_block[i])
+    }
+
+    /// The data of a message, which is prefixed by the key block for a keyed hash.
+    pub fn data(&self, key: &[u8], msg: &[u8]) -> Vec<u8> {
+        assert_eq!(key.len(), self.key_length);
+        let mut data = Vec::new();
+        if !key.is_empty() {
+            data.extend_from_slice(key);
+            data.resize(CHUNK_BYTES, 0);
+        }
+        data.extend_from_slice(msg);
+        data
+    }
+
+    /// Pads the data of a message with zeros to `max_chunk_size` chunks.
+    pub fn pad(&self, key: &[u8], msg: &[u8], max_chunk_size: u64) -> Vec<u8> {
+        BLAKE2BUtil::pad(&self.data(key, msg), max_chunk_size)
+    }
+
+    /// The index of the last chunk of data of `data_length` bytes, whose state is the digest.
+    pub fn digest_chunk(data_length: usize) -> usize {
+        data_length.saturating_sub(1) / CHUNK_BYTES
+    }
+
+    /// The number of bytes compressed after each of `num_chunks` chunks of data of
+    /// `data_length` bytes, which is the length of the data at the digest chunk.
+    pub fn t_values(data_length: usize, num_chunks: usize) -> Vec<u64> {
+        let digest_chunk = Self::digest_chunk(data_length);
+        (0..num_chunks)
+            .map(|i| match i == digest_chunk {
+                true => data_length as u64,
+                false => ((i + 1) * CHUNK_BYTES) as u64,
+            })
+            .collect()
+    }
+
+    /// The final state of the hash of `msg` with the key `key`.
+    pub fn final_state(&self, key: &[u8], msg: &[u8]) -> [u64; STATE_SIZE] {
+        let data = self.data(key, msg);
+        let num_chunks = Self::digest_chunk(data.len()) + 1;
+        let padded_data = BLAKE2BUtil::pad(&data, num_chunks as u64);
+        let mut state = self.initial_state();
+        for (i, (chunk, t)) in padded_data
+            .chunks_exact(CHUNK_BYTES)
+            .zip(Self::t_values(data.len(), num_chunks))
+            .enumerate()
+        {
+            BLAKE2B::compress(chunk, &mut state, t, i == num_chunks - 1);
+        }
+        state
+    }
+
+    /// The hash of `msg` with the key `key`, of `self.digest_length` bytes.
+    pub fn hash(&self, key: &[u8], msg: &[u8]) -> Vec<u8> {
+        self.final_state(key, msg)
+            .iter()
+            .flat_map(|word| word.to_le_bytes())
+            .take(self.digest_length)
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::machine::hash::blake::blake2b::IV;
+
+    #[test]
+    fn test_blake2b_params_default() {
+        assert_eq!(BLAKE2BParams::default().initial_state(), IV);
+    }
+
+    #[test]
+    fn test_blake2b_params_hash() {
+        // BLAKE2b-512 of "abc", from Appendix A of RFC 7693.
+        let params = BLAKE2BParams {
+            digest_length: 64,
+            ..Default::default()
+        };
+        assert_eq!(
+            hex::encode(params.hash(&[], b"abc")),
+            "ba80a53f981c4d0d6a2797b69f12f6e94c212f14685ac4b74b12bb6fdbffa2d1\
+             7d87c5392aab792dc252d5de4533cc9518d38aa8dbf1925ab92386edd4009923"
+        );
+
+        // Keyed hashes from the known answer tests of the reference implementation.
+        let key = (0..64).collect::<Vec<u8>>();
+        let params = BLAKE2BParams {
+            digest_length: 64,
+            key_length: 64,
+            ..Default::default()
+        };
+        assert_eq!(
+            hex::encode(params.hash(&key, &[])),
+            "10ebb67700b1868efb4417987acf4690ae9d972fb7a590c2f02871799aaa4786\
+             b5e996e8f0f4eb981fc214b005f42d2ff4233499391653df7aefcbc13fc51568"
+        );
+        assert_eq!(
+            hex::encode(params.hash(&key, &(0..255).collect::<Vec<u8>>())),
+            "142709d62e28fcccd0af97fad0f8465b971e82201dc51070faa0372aa43e9248\
+             4be1c1e73ba10906d5d1853db6a4106e0a7bf9800d373d6dee2d46d62ef2a461"
+        );
+
+        // A personalized hash, as used by Zcash for transaction digests.
+        let params = BLAKE2BParams {
+            personalization: *b"ZcashPrevoutHash",
+            ..Default::default()
+        };
+        assert_eq!(
+            hex::encode(params.hash(&[], &[])),
+            "d53a633bbecf82fe9e9484d8a0e727c73bb9e68c96e72dec30144f6a84afa136"
+        );
+
+        // All parameters together.
+        let key = b"secret key";
+        let params = BLAKE2BParams {
+            digest_length: 20,
+            key_length: key.len(),
+            salt: *b"0123456789abcdef",
+            personalization: *b"personalization!",
+        };
+        assert_eq!(
+            hex::encode(params.hash(key, b"abc")),
+            "c9f60e3a5a82fdc6948de04ecf9c946c7690d9a5"
+        );
+        assert_eq!(
+            hex::encode(params.hash(key, &(0..200).collect::<Vec<u8>>())),
+            "1a3ba9d668549e6f152f494bfdd181878e454daf"
+        );
+    }
+}
@@ -0,0 +1,340 @@
+use log::debug;
+use plonky2::util::log2_ceil;
+
+use super::register::BLAKE3DigestRegister;
+use super::{
+    BLAKE3, CYCLE_LENGTH, IV, MSG_ARRAY_SIZE, MSG_PERMUTATION, NUM_PARAMETERS, STATE_SIZE,
+    V_INDICES, WORK_VECTOR_SIZE,
+};
+use crate::chip::memory::time::Time;
+use crate::chip::memory::value::MemoryValue;
+use crate::chip::register::array::ArrayRegister;
+use crate::chip::register::bit::BitRegister;
+use crate::chip::register::element::ElementRegister;
+use crate::chip::register::Register;
+use crate::chip::uint::operations::instruction::UintInstructions;
+use crate::chip::uint::register::U32Register;
+use crate::chip::uint::util::{u32_from_le_field_bytes, u32_to_le_field_bytes};
+use crate::chip::AirParameters;
+use crate::machine::builder::Builder;
+use crate::machine::bytes::builder::BytesBuilder;
+use crate::machine::hash::{HashDigest, HashIntConversion, HashInteger};
+use crate::math::prelude::*;
+
+impl<B: Builder> HashInteger<B> for BLAKE3 {
+    type Value = <U32Register as Register>::Value<B::Field>;
+    type IntRegister = U32Register;
+}
+
+impl<B: Builder> HashIntConversion<B> for BLAKE3 {
+    fn int_to_field_value(int: Self::Integer) -> Self::Value {
+        u32_to_le_field_bytes(int)
+    }
+
+    fn field_value_to_int(value: &Self::Value) -> Self::Integer {
+        u32_from_le_field_bytes(value)
+    }
+}
+
+impl<B: Builder> HashDigest<B> for BLAKE3 {
+    type DigestRegister = BLAKE3DigestRegister;
+}
+
+/// BLAKE3 AIR implementation.
+///
+/// Each compression takes a cycle of `CYCLE_LENGTH` rows. The rows `0..7` of the cycle compute
+/// the seven rounds of the compression function, and the output chaining value is read off the
+/// work vector in the last row. The message words and the work vector are carried from one row to
+/// the next, and the message is permuted between rounds.
+pub trait BLAKE3Air<B: Builder>: HashIntConversion<B> + HashDigest<B> {
+    /// Computes the given compressions and returns their output chaining values at the indices
+    /// `digest_indices`.
+    ///
+    /// The `i`-th compression takes the block `blocks[i]` and the parameter words
+    /// `parameters[i] = [counter_low, counter_high, block_len, flags]`. Its chaining value is the
+    /// output of the previous compression if `chain_bits[i]` is set, as for the blocks of a chunk
+    /// after the first one, and `keys[i]` otherwise, which is the IV for unkeyed hashing. The
+    /// outputs of the compressions whose `digest_bits` are set are the returned digests, in order.
+    ///
+    /// Parent compressions are linked to their children by constraining their blocks to equal the
+    /// digests of the children.
+    #[allow(clippy::too_many_arguments)]
+    fn blake3(
+        builder: &mut B,
+        blocks: &[ArrayRegister<Self::IntRegister>],
+        keys: &[ArrayRegister<Self::IntRegister>],
+        parameters: &[ArrayRegister<Self::IntRegister>],
+        chain_bits: &ArrayRegister<BitRegister>,
+        digest_bits: &ArrayRegister<BitRegister>,
+        digest_indices: &ArrayRegister<ElementRegister>,
+    ) -> Vec<Self::DigestRegister>;
+
+    /// A round of the compression function, applied to the work vector `v` with the message `m`.
+    fn blake3_round(
+        builder: &mut B,
+        v: &[Self::IntRegister],
+        m: &[Self::IntRegister],
+    ) -> Vec<Self::IntRegister>;
+
+    fn blake3_g(
+        builder: &mut B,
+        v_a: &Self::IntRegister,
+        v_b: &Self::IntRegister,
+        v_c: &Self::IntRegister,
+        v_d: &Self::IntRegister,
+        x: &Self::IntRegister,
+        y: &Self::IntRegister,
+    ) -> (
+        Self::IntRegister,
+        Self::IntRegister,
+        Self::IntRegister,
+        Self::IntRegister,
+    );
+}
+
+impl<L: AirParameters> BLAKE3Air<BytesBuilder<L>> for BLAKE3
+where
+    L::Instruction: UintInstructions,
+{
+    fn blake3(
+        builder: &mut BytesBuilder<L>,
+        blocks: &[ArrayRegister<Self::IntRegister>],
+        keys: &[ArrayRegister<Self::IntRegister>],
+        parameters: &[ArrayRegister<Self::IntRegister>],
+        chain_bits: &ArrayRegister<BitRegister>,
+        digest_bits: &ArrayRegister<BitRegister>,
+        digest_indices: &ArrayRegister<ElementRegister>,
+    ) -> Vec<Self::DigestRegister> {
+        let num_real_compressions = blocks.len();
+        assert_eq!(keys.len(), num_real_compressions);
+        assert_eq!(parameters.len(), num_real_compressions);
+        assert_eq!(chain_bits.len(), num_real_compressions);
+        assert_eq!(digest_bits.len(), num_real_compressions);
+        debug!(
+            "AIR degree before padding: {}",
+            num_real_compressions * CYCLE_LENGTH
+        );
+        let degree_log = log2_ceil(num_real_compressions * CYCLE_LENGTH);
+        assert!(degree_log < 31, "AIR degree is too large");
+        debug!("AIR degree after padding: {}", 1 << degree_log);
+        builder.api().require_rows("BLAKE3", 1 << degree_log);
+        // The number of rows is a multiple of the cycle length, the compressions after the real
+        // ones are dummy compressions of zero values whose outputs are ignored.
+        let num_compressions = (1 << degree_log) / CYCLE_LENGTH;
+
+        let cycle = builder.cycle(3);
+        let process_id = builder.process_id(CYCLE_LENGTH, cycle.end_bit);
+        let reg_cycle_length = builder.constant(&L::Field::from_canonical_usize(CYCLE_LENGTH));
+
+        let zero_word = builder.constant::<U32Register>(&u32_to_le_field_bytes(0));
+        let zero_bit = builder.constant::<BitRegister>(&L::Field::ZERO);
+        let iv_values = IV[..4]
+            .iter()
+            .map(|&word| u32_to_le_field_bytes(word))
+            .collect::<Vec<_>>();
+        let iv = builder.constant_array::<U32Register>(&iv_values);
+
+        // Load the inputs of the current compression.
+        let load_words =
+            |builder: &mut BytesBuilder<L>, arrays: &[ArrayRegister<U32Register>], len: usize| {
+                (0..len)
+                    .map(|j| {
+                        let values = arrays.iter().map(|array| array.get(j)).collect::<Vec<_>>();
+                        load_compression_value(
+                            builder,
+                            &values,
+                            zero_word,
+                            num_compressions,
+                            reg_cycle_length,
+                            process_id,
+                        )
+                    })
+                    .collect::<Vec<_>>()
+            };
+        let block = load_words(builder, blocks, MSG_ARRAY_SIZE);
+        let key = load_words(builder, keys, STATE_SIZE);
+        let params = load_words(builder, parameters, NUM_PARAMETERS);
+        let chain_bit = load_compression_value(
+            builder,
+            &chain_bits.iter().collect::<Vec<_>>(),
+            zero_bit,
+            num_compressions,
+            reg_cycle_length,
+            process_id,
+        );
+        let digest_bit = load_compression_value(
+            builder,
+            &digest_bits.iter().collect::<Vec<_>>(),
+            zero_bit,
+            num_compressions,
+            reg_cycle_length,
+            process_id,
+        );
+
+        // The work vector, message and output carried over from the previous row.
+        let v = builder.alloc_array::<U32Register>(WORK_VECTOR_SIZE);
+        let m = builder.alloc_array::<U32Register>(MSG_ARRAY_SIZE);
+        let prev_output = builder.alloc_array::<U32Register>(STATE_SIZE);
+        for reg in v.iter().chain(m.iter()).chain(prev_output.iter()) {
+            builder.set_to_expression_first_row(&reg, zero_word.expr());
+        }
+
+        // At the start of a compression, initialize the work vector and the message.
+        let cv = (0..STATE_SIZE)
+            .map(|i| builder.select(chain_bit, &prev_output.get(i), &key[i]))
+            .collect::<Vec<_>>();
+        let v_init = cv
+            .into_iter()
+            .chain(iv.iter())
+            .chain(params)
+            .collect::<Vec<_>>();
+        let v_in = v_init
+            .iter()
+            .zip(v.iter())
+            .map(|(init, v_i)| builder.select(cycle.start_bit, init, &v_i))
+            .collect::<Vec<_>>();
+        let m_in = block
+            .iter()
+            .zip(m.iter())
+            .map(|(block_i, m_i)| builder.select(cycle.start_bit, block_i, &m_i))
+            .collect::<Vec<_>>();
+
+        let v_out = Self::blake3_round(builder, &v_in, &m_in);
+
+        // The output chaining value, which is correct in the last row of the cycle.
+        let output = (0..STATE_SIZE)
+            .map(|i| builder.xor(&v_in[i], &v_in[i + 8]))
+            .collect::<Vec<_>>();
+
+        for (v_i, v_out_i) in v.iter().zip(v_out.iter()) {
+            builder.set_to_expression_transition(&v_i.next(), v_out_i.expr());
+        }
+        for (m_i, &j) in m.iter().zip(MSG_PERMUTATION.iter()) {
+            builder.set_to_expression_transition(&m_i.next(), m_in[j].expr());
+        }
+        for (prev_output_i, output_i) in prev_output.iter().zip(output.iter()) {
+            builder.set_to_expression_transition(&prev_output_i.next(), output_i.expr());
+        }
+
+        // Store the outputs of the digest compressions and free them at the digest indices.
+        let digests = (0..digest_indices.len())
+            .map(|_| builder.alloc_public::<BLAKE3DigestRegister>())
+            .collect::<Vec<_>>();
+        let state_ptr = builder.uninit_slice();
+        for (index, digest) in digest_indices.iter().zip(digests.iter()) {
+            for (j, word) in digest.iter().enumerate() {
+                builder.free(&state_ptr.get(j), word, &Time::from_element(index));
+            }
+        }
+        let flag = builder.expression(cycle.end_bit.expr() * digest_bit.expr());
+        for (j, word) in output.into_iter().enumerate() {
+            builder.store(
+                &state_ptr.get(j),
+                word,
+                &Time::from_element(process_id),
+                Some(flag),
+                None,
+                None,
+            );
+        }
+
+        digests
+    }
+
+    fn blake3_round(
+        builder: &mut BytesBuilder<L>,
+        v: &[Self::IntRegister],
+        m: &[Self::IntRegister],
+    ) -> Vec<Self::IntRegister> {
+        let mut v = v.to_vec();
+        for (i, &[a, b, c, d]) in V_INDICES.iter().enumerate() {
+            let (v_a, v_b, v_c, v_d) = Self::blake3_g(
+                builder,
+                &v[a],
+                &v[b],
+                &v[c],
+                &v[d],
+                &m[2 * i],
+                &m[2 * i + 1],
+            );
+            v[a] = v_a;
+            v[b] = v_b;
+            v[c] = v_c;
+            v[d] = v_d;
+        }
+        v
+    }
+
+    fn blake3_g(
+        builder: &mut BytesBuilder<L>,
+        v_a: &Self::IntRegister,
+        v_b: &Self::IntRegister,
+        v_c: &Self::IntRegister,
+        v_d: &Self::IntRegister,
+        x: &Self::IntRegister,
+        y: &Self::IntRegister,
+    ) -> (
+        Self::IntRegister,
+        Self::IntRegister,
+        Self::IntRegister,
+        Self::IntRegister,
+    ) {
+        let mut a = builder.add(*v_a, *v_b);
+        a = builder.add(a, *x);
+        let mut d = builder.xor(v_d, &a);
+        d = builder.rotate_right(d, 16);
+        let mut c = builder.add(*v_c, d);
+        let mut b = builder.xor(v_b, &c);
+        b = builder.rotate_right(b, 12);
+
+        a = builder.add(a, b);
+        a = builder.add(a, *y);
+        d = builder.xor(&d, &a);
+        d = builder.rotate_right(d, 8);
+        c = builder.add(c, d);
+        b = builder.xor(&b, &c);
+        b = builder.rotate_right(b, 7);
+
+        (a, b, c, d)
+    }
+}
+
+/// Stores the values of the compressions in a slice, padded with `dummy` values for the dummy
+/// compressions, and loads the value of the current compression.
+///
+/// Each value is stored with multiplicity `cycle_length`, since it is read in every row of its
+/// compression.
+fn load_compression_value<L: AirParameters, V: MemoryValue>(
+    builder: &mut BytesBuilder<L>,
+    values: &[V],
+    dummy: V,
+    num_compressions: usize,
+    cycle_length: ElementRegister,
+    process_id: ElementRegister,
+) -> V
+where
+    L::Instruction: UintInstructions,
+{
+    let slice = builder.uninit_slice();
+    for (i, value) in values.iter().enumerate() {
+        builder.store(
+            &slice.get(i),
+            *value,
+            &Time::zero(),
+            Some(cycle_length),
+            None,
+            None,
+        );
+    }
+    for i in values.len()..num_compressions {
+        builder.store(
+            &slice.get(i),
+            dummy,
+            &Time::zero(),
+            Some(cycle_length),
+            None,
+            None,
+        );
+    }
+    builder.load(&slice.get_at(process_id), &Time::zero(), None, None)
+}
@@ -0,0 +1,266 @@
+use super::air::BLAKE3Air;
+use crate::chip::register::array::ArrayRegister;
+use crate::chip::register::bit::BitRegister;
+use crate::chip::register::element::ElementRegister;
+use crate::prelude::Builder;
+
+pub trait BLAKE3Builder: Builder {
+    /// Computes BLAKE3 compressions, see [`BLAKE3Air::blake3`].
+    #[allow(clippy::too_many_arguments)]
+    fn blake3<B: BLAKE3Air<Self>>(
+        &mut self,
+        blocks: &[ArrayRegister<B::IntRegister>],
+        keys: &[ArrayRegister<B::IntRegister>],
+        parameters: &[ArrayRegister<B::IntRegister>],
+        chain_bits: &ArrayRegister<BitRegister>,
+        digest_bits: &ArrayRegister<BitRegister>,
+        digest_indices: &ArrayRegister<ElementRegister>,
+    ) -> Vec<B::DigestRegister> {
+        B::blake3(
+            self,
+            blocks,
+            keys,
+            parameters,
+            chain_bits,
+            digest_bits,
+            digest_indices,
+        )
+    }
+}
+
+impl<B: Builder> BLAKE3Builder for B {}
+
+#[cfg(test)]
+mod tests {
+    use itertools::Itertools;
+    use plonky2::field::goldilocks_field::GoldilocksField;
+    use plonky2::iop::witness::{PartialWitness, WitnessWrite};
+    use plonky2::plonk::circuit_builder::CircuitBuilder;
+    use plonky2::plonk::circuit_data::CircuitConfig;
+    use plonky2::timed;
+    use plonky2::util::log2_ceil;
+    use plonky2::util::timing::TimingTree;
+    use serde::{Deserialize, Serialize};
+
+    use super::*;
+    use crate::chip::uint::operations::instruction::UintInstruction;
+    use crate::chip::uint::register::U32Register;
+    use crate::chip::uint::util::{u32_from_le_field_bytes, u32_to_le_field_bytes};
+    use crate::chip::AirParameters;
+    use crate::machine::bytes::builder::BytesBuilder;
+    use crate::machine::hash::blake::blake3::pure::{key_words, BLAKE3Pure};
+    use crate::machine::hash::blake::blake3::{BLAKE3, CHUNK_END, CYCLE_LENGTH, KEY_LEN};
+    use crate::math::goldilocks::cubic::GoldilocksCubicParameters;
+    use crate::math::prelude::*;
+    use crate::plonky2::stark::config::{CurtaConfig, CurtaPoseidonGoldilocksConfig};
+    use crate::prelude::{AirWriter, AirWriterData};
+
+    #[derive(Clone, Debug, Serialize, Deserialize)]
+    pub struct BLAKE3Test;
+
+    impl AirParameters for BLAKE3Test {
+        type Field = GoldilocksField;
+        type CubicParams = GoldilocksCubicParameters;
+        type Instruction = UintInstruction;
+
+        const NUM_FREE_COLUMNS: usize = 1240;
+        const EXTENDED_COLUMNS: usize = 2364;
+    }
+
+    #[test]
+    fn test_blake3() {
+        type C = CurtaPoseidonGoldilocksConfig;
+        type Config = <C as CurtaConfig<2>>::GenericConfig;
+
+        let _ = env_logger::builder().is_test(true).try_init();
+        let mut timing = TimingTree::new("test_blake3", log::Level::Debug);
+
+        let key = b"whats the Elvish word for friend";
+        let input = (0..1025).map(|i| (i % 251) as u8).collect::<Vec<_>>();
+        // A single block, two chained blocks, and two chunks merged by a parent compression.
+        let messages: [(Option<&[u8; KEY_LEN]>, &[u8], &str); 3] = [
+            (
+                None,
+                &[],
+                "af1349b9f5f9a1a6a0404dea36dcc9499bcb25c9adc112b7cc9a93cae41f3262",
+            ),
+            (
+                Some(key),
+                &input[..65],
+                "c0a4edefa2d2accb9277c371ac12fcdbb52988a86edc54f0716e1591b4326e72",
+            ),
+            (
+                None,
+                &input,
+                "d00278ae47eb27b34faecf67b4fe263f82d5412916c1ffd97c8cb7fb814b8444",
+            ),
+        ];
+
+        // The compressions of all messages, with the indices of the children of parents shifted
+        // to the position of the message.
+        let mut compressions = Vec::new();
+        let mut message_keys = Vec::new();
+        let mut roots = Vec::new();
+        for (key, msg, _) in messages.iter() {
+            let offset = compressions.len();
+            for mut compression in BLAKE3::compressions(*key, msg) {
+                compression.children = compression
+                    .children
+                    .map(|(left, right)| (left + offset, right + offset));
+                compressions.push(compression);
+                message_keys.push(key_words(*key).0);
+            }
+            roots.push(compressions.len() - 1);
+        }
+        let is_digest =
+            |i: usize| compressions[i].flags & CHUNK_END != 0 || compressions[i].children.is_some();
+        let num_compressions = compressions.len();
+        let digest_positions = (0..num_compressions)
+            .scan(0, |position, i| {
+                let current = *position;
+                *position += is_digest(i) as usize;
+                Some(current)
+            })
+            .collect::<Vec<_>>();
+        let num_digests = (0..num_compressions).filter(|&i| is_digest(i)).count();
+
+        // Build the stark.
+        let mut builder = BytesBuilder::<BLAKE3Test>::new();
+        let blocks = (0..num_compressions)
+            .map(|_| builder.alloc_array_public::<U32Register>(16))
+            .collect::<Vec<_>>();
+        let keys = (0..num_compressions)
+            .map(|_| builder.alloc_array_public::<U32Register>(8))
+            .collect::<Vec<_>>();
+        let parameters = (0..num_compressions)
+            .map(|_| builder.alloc_array_public::<U32Register>(4))
+            .collect::<Vec<_>>();
+        let chain_bits = builder.alloc_array_public::<BitRegister>(num_compressions);
+        let digest_bits = builder.alloc_array_public::<BitRegister>(num_compressions);
+        let digest_indices = builder.alloc_array_public::<ElementRegister>(num_digests);
+        let digests = builder.blake3::<BLAKE3>(
+            &blocks,
+            &keys,
+            &parameters,
+            &chain_bits,
+            &digest_bits,
+            &digest_indices,
+        );
+
+        // Link the parents to the chaining values of their children.
+        for (block, compression) in blocks.iter().zip(compressions.iter()) {
+            if let Some((left, right)) = compression.children {
+                let children = digests[digest_positions[left]]
+                    .iter()
+                    .chain(digests[digest_positions[right]].iter());
+                for (word, child_word) in block.iter().zip_eq(children) {
+                    builder.assert_equal(&word, &child_word);
+                }
+            }
+        }
+
+        let num_rows = 1 << log2_ceil(CYCLE_LENGTH * num_compressions);
+        let stark = builder.build::<C, 2>(num_rows);
+
+        // Build the recursive circuit.
+        let config_rec = CircuitConfig::standard_recursion_config();
+        let mut recursive_builder = CircuitBuilder::<GoldilocksField, 2>::new(config_rec);
+
+        let (proof_target, public_input) =
+            stark.add_virtual_proof_with_pis_target(&mut recursive_builder);
+        stark.verify_circuit(&mut recursive_builder, &proof_target, &public_input);
+
+        let rec_data = recursive_builder.build::<Config>();
+
+        // Write trace.
+        let mut writer_data = AirWriterData::new(&stark.air_data, num_rows);
+        let mut writer = writer_data.public_writer();
+
+        for (i, compression) in compressions.iter().enumerate() {
+            writer.write_array(
+                &blocks[i],
+                compression
+                    .block
+                    .map(u32_to_le_field_bytes::<GoldilocksField>),
+            );
+            writer.write_array(
+                &keys[i],
+                message_keys[i].map(u32_to_le_field_bytes::<GoldilocksField>),
+            );
+            writer.write_array(
+                &parameters[i],
+                compression
+                    .parameters()
+                    .map(u32_to_le_field_bytes::<GoldilocksField>),
+            );
+            writer.write(
+                &chain_bits.get(i),
+                &GoldilocksField::from_canonical_usize(compression.chained as usize),
+            );
+            writer.write(
+                &digest_bits.get(i),
+                &GoldilocksField::from_canonical_usize(is_digest(i) as usize),
+            );
+            if is_digest(i) {
+                let position = digest_positions[i];
+                writer.write(
+                    &digest_indices.get(position),
+                    &GoldilocksField::from_canonical_usize(i),
+                );
+                writer.write_array(
+                    &digests[position].as_array(),
+                    compression
+                        .output
+                        .map(u32_to_le_field_bytes::<GoldilocksField>),
+                );
+            }
+        }
+
+        timed!(timing, "write input", {
+            stark.air_data.write_global_instructions(&mut writer);
+
+            for mut chunk in writer_data.chunks(num_rows) {
+                for i in 0..num_rows {
+                    let mut writer = chunk.window_writer(i);
+                    stark.air_data.write_trace_instructions(&mut writer);
+                }
+            }
+        });
+
+        // Compare the expected hashes with the digests of the roots.
+        let writer = writer_data.public_writer();
+        for (root, (_, _, expected)) in roots.into_iter().zip_eq(messages.iter()) {
+            let digest = writer
+                .read_array::<_, 8>(&digests[digest_positions[root]].as_array())
+                .map(|word| u32_from_le_field_bytes(&word));
+            let digest_bytes = digest.iter().flat_map(|w| w.to_le_bytes()).collect_vec();
+            assert_eq!(hex::encode(digest_bytes), *expected);
+        }
+
+        let (trace, public) = (writer_data.trace, writer_data.public);
+
+        let proof = timed!(
+            timing,
+            "generate stark proof",
+            stark.prove(&trace, &public, &mut timing).unwrap()
+        );
+
+        stark.verify(proof.clone(), &public).unwrap();
+
+        let mut pw = PartialWitness::new();
+
+        pw.set_target_arr(&public_input, &public).unwrap();
+        stark
+            .set_proof_target(&mut pw, &proof_target, proof)
+            .unwrap();
+
+        let rec_proof = timed!(
+            timing,
+            "generate recursive proof",
+            rec_data.prove(pw).unwrap()
+        );
+        rec_data.verify(rec_proof).unwrap();
+
+        timing.print();
+    }
+}
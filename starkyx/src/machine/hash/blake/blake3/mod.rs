@@ -0,0 +1,56 @@
+//! The BLAKE3 hash function.
+//!
+//! BLAKE3 splits the message into chunks of 1024 bytes, each made of up to 16 blocks of 64 bytes.
+//! The blocks of a chunk are compressed in sequence, chaining the output of each compression into
+//! the next, and the chaining values of the chunks are then merged pairwise by parent compressions
+//! into a binary tree. All compressions take the key as their initial chaining value, which is the
+//! IV for unkeyed hashing.
+
+use serde::{Deserialize, Serialize};
+
+pub mod air;
+pub mod builder;
+pub mod pure;
+pub mod register;
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct BLAKE3;
+
+/// The number of rounds of the compression function.
+const NUM_ROUNDS: usize = 7;
+/// The number of rows of a compression in the AIR, one per round and one for the output.
+const CYCLE_LENGTH: usize = 8;
+const MSG_ARRAY_SIZE: usize = 16;
+const STATE_SIZE: usize = 8;
+const WORK_VECTOR_SIZE: usize = 16;
+/// The number of parameter words of a compression: the counter, the block length and the flags.
+const NUM_PARAMETERS: usize = 4;
+
+pub const BLOCK_LEN: usize = 64;
+pub const CHUNK_LEN: usize = 1024;
+pub const KEY_LEN: usize = 32;
+
+pub const IV: [u32; STATE_SIZE] = [
+    0x6a09e667, 0xbb67ae85, 0x3c6ef372, 0xa54ff53a, 0x510e527f, 0x9b05688c, 0x1f83d9ab, 0x5be0cd19,
+];
+
+const MSG_PERMUTATION: [usize; MSG_ARRAY_SIZE] =
+    [2, 6, 3, 10, 7, 0, 4, 13, 1, 11, 12, 5, 9, 14, 15, 8];
+
+/// The `a, b, c, d` indices of the work vector mixed by each of the `G` functions of a round.
+const V_INDICES: [[usize; 4]; 8] = [
+    [0, 4, 8, 12],
+    [1, 5, 9, 13],
+    [2, 6, 10, 14],
+    [3, 7, 11, 15],
+    [0, 5, 10, 15],
+    [1, 6, 11, 12],
+    [2, 7, 8, 13],
+    [3, 4, 9, 14],
+];
+
+pub const CHUNK_START: u32 = 1 << 0;
+pub const CHUNK_END: u32 = 1 << 1;
+pub const PARENT: u32 = 1 << 2;
+pub const ROOT: u32 = 1 << 3;
+pub const KEYED_HASH: u32 = 1 << 4;
@@ -0,0 +1,356 @@
+use super::{
+    BLAKE3, BLOCK_LEN, CHUNK_END, CHUNK_LEN, CHUNK_START, IV, KEYED_HASH, KEY_LEN, MSG_ARRAY_SIZE,
+    MSG_PERMUTATION, NUM_ROUNDS, PARENT, ROOT, STATE_SIZE, V_INDICES, WORK_VECTOR_SIZE,
+};
+use crate::machine::hash::HashPureInteger;
+
+impl HashPureInteger for BLAKE3 {
+    type Integer = u32;
+}
+
+/// A single call to the compression function in the computation of a hash.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BLAKE3Compression {
+    pub block: [u32; MSG_ARRAY_SIZE],
+    pub counter: u64,
+    pub block_len: u32,
+    pub flags: u32,
+    /// Whether the chaining value is the output of the previous compression, rather than the key.
+    pub chained: bool,
+    /// For parent compressions, the indices of the compressions whose outputs are the block.
+    pub children: Option<(usize, usize)>,
+    /// The chaining value output by the compression.
+    pub output: [u32; STATE_SIZE],
+}
+
+impl BLAKE3Compression {
+    /// The parameter words of the compression: the counter, the block length and the flags.
+    pub fn parameters(&self) -> [u32; 4] {
+        [
+            self.counter as u32,
+            (self.counter >> 32) as u32,
+            self.block_len,
+            self.flags,
+        ]
+    }
+}
+
+pub trait BLAKE3Pure: HashPureInteger {
+    /// The compression function, returning the full 16-word output.
+    ///
+    /// The first 8 words are the output chaining value, the rest is only used to extend the
+    /// output beyond 32 bytes.
+    fn compress(
+        cv: &[Self::Integer; STATE_SIZE],
+        block: &[Self::Integer; MSG_ARRAY_SIZE],
+        counter: u64,
+        block_len: u32,
+        flags: u32,
+    ) -> [Self::Integer; WORK_VECTOR_SIZE];
+
+    fn g(
+        v: &mut [Self::Integer; WORK_VECTOR_SIZE],
+        indices: [usize; 4],
+        x: Self::Integer,
+        y: Self::Integer,
+    );
+
+    /// The sequence of compressions computing the hash of `msg`, keyed by `key` if given.
+    ///
+    /// The compressions of the blocks of each chunk are consecutive, followed by the parent
+    /// compressions in post-order, so that the last compression is the root.
+    fn compressions(key: Option<&[u8; KEY_LEN]>, msg: &[u8]) -> Vec<BLAKE3Compression>;
+
+    /// The 32-byte hash of `msg`.
+    fn hash(msg: &[u8]) -> [Self::Integer; STATE_SIZE];
+
+    /// The 32-byte keyed hash of `msg`.
+    fn keyed_hash(key: &[u8; KEY_LEN], msg: &[u8]) -> [Self::Integer; STATE_SIZE];
+}
+
+/// The key as words, or the IV for unkeyed hashing, and the corresponding domain flags.
+pub fn key_words(key: Option<&[u8; KEY_LEN]>) -> ([u32; STATE_SIZE], u32) {
+    match key {
+        Some(key) => (le_words(key), KEYED_HASH),
+        None => (IV, 0),
+    }
+}
+
+/// Reads little-endian words from `bytes`, padding the last word with zeros.
+fn le_words<const N: usize>(bytes: &[u8]) -> [u32; N] {
+    let mut words = [0u32; N];
+    for (word, chunk) in words.iter_mut().zip(bytes.chunks(4)) {
+        let mut word_bytes = [0u8; 4];
+        word_bytes[..chunk.len()].copy_from_slice(chunk);
+        *word = u32::from_le_bytes(word_bytes);
+    }
+    words
+}
+
+impl BLAKE3Pure for BLAKE3 {
+    fn compress(
+        cv: &[Self::Integer; STATE_SIZE],
+        block: &[Self::Integer; MSG_ARRAY_SIZE],
+        counter: u64,
+        block_len: u32,
+        flags: u32,
+    ) -> [Self::Integer; WORK_VECTOR_SIZE] {
+        let mut v = [0u32; WORK_VECTOR_SIZE];
+        v[..8].copy_from_slice(cv);
+        v[8..12].copy_from_slice(&IV[..4]);
+        v[12] = counter as u32;
+        v[13] = (counter >> 32) as u32;
+        v[14] = block_len;
+        v[15] = flags;
+
+        let mut m = *block;
+        for round in 0..NUM_ROUNDS {
+            for (i, indices) in V_INDICES.iter().enumerate() {
+                Self::g(&mut v, *indices, m[2 * i], m[2 * i + 1]);
+            }
+            if round < NUM_ROUNDS - 1 {
+                m = core::array::from_fn(|i| m[MSG_PERMUTATION[i]]);
+            }
+        }
+
+        for i in 0..8 {
+            v[i] ^= v[i + 8];
+            v[i + 8] ^= cv[i];
+        }
+        v
+    }
+
+    fn g(
+        v: &mut [Self::Integer; WORK_VECTOR_SIZE],
+        indices: [usize; 4],
+        x: Self::Integer,
+        y: Self::Integer,
+    ) {
+        let [a, b, c, d] = indices;
+        v[a] = v[a].wrapping_add(v[b]).wrapping_add(x);
+        v[d] = (v[d] ^ v[a]).rotate_right(16);
+        v[c] = v[c].wrapping_add(v[d]);
+        v[b] = (v[b] ^ v[c]).rotate_right(12);
+        v[a] = v[a].wrapping_add(v[b]).wrapping_add(y);
+        v[d] = (v[d] ^ v[a]).rotate_right(8);
+        v[c] = v[c].wrapping_add(v[d]);
+        v[b] = (v[b] ^ v[c]).rotate_right(7);
+    }
+
+    fn compressions(key: Option<&[u8; KEY_LEN]>, msg: &[u8]) -> Vec<BLAKE3Compression> {
+        let (key, domain_flag) = key_words(key);
+        let chunks = split(msg, CHUNK_LEN);
+        let is_root = chunks.len() == 1;
+
+        let mut compressions = Vec::new();
+        let mut chunk_outputs = Vec::with_capacity(chunks.len());
+        for (counter, chunk) in chunks.into_iter().enumerate() {
+            let blocks = split(chunk, BLOCK_LEN);
+            let num_blocks = blocks.len();
+            let mut cv = key;
+            for (j, block_bytes) in blocks.into_iter().enumerate() {
+                let mut flags = domain_flag;
+                if j == 0 {
+                    flags |= CHUNK_START;
+                }
+                if j == num_blocks - 1 {
+                    flags |= CHUNK_END;
+                    if is_root {
+                        flags |= ROOT;
+                    }
+                }
+                let block = le_words(block_bytes);
+                let block_len = block_bytes.len() as u32;
+                let output = Self::compress(&cv, &block, counter as u64, block_len, flags);
+                cv = output[..8].try_into().unwrap();
+                compressions.push(BLAKE3Compression {
+                    block,
+                    counter: counter as u64,
+                    block_len,
+                    flags,
+                    chained: j > 0,
+                    children: None,
+                    output: cv,
+                });
+            }
+            chunk_outputs.push(compressions.len() - 1);
+        }
+
+        if !is_root {
+            merge(&mut compressions, &chunk_outputs, &key, domain_flag, true);
+        }
+        compressions
+    }
+
+    fn hash(msg: &[u8]) -> [Self::Integer; STATE_SIZE] {
+        Self::compressions(None, msg).last().unwrap().output
+    }
+
+    fn keyed_hash(key: &[u8; KEY_LEN], msg: &[u8]) -> [Self::Integer; STATE_SIZE] {
+        Self::compressions(Some(key), msg).last().unwrap().output
+    }
+}
+
+/// Splits `bytes` into pieces of `len` bytes, the last one possibly shorter. An empty input is
+/// a single empty piece.
+fn split(bytes: &[u8], len: usize) -> Vec<&[u8]> {
+    if bytes.is_empty() {
+        return vec![bytes];
+    }
+    bytes.chunks(len).collect()
+}
+
+/// Merges the chaining values output by the compressions `nodes` into a subtree, whose left
+/// subtree is the largest complete one, and returns the index of its root compression.
+fn merge(
+    compressions: &mut Vec<BLAKE3Compression>,
+    nodes: &[usize],
+    key: &[u32; STATE_SIZE],
+    domain_flag: u32,
+    is_root: bool,
+) -> usize {
+    if nodes.len() == 1 {
+        return nodes[0];
+    }
+    let mut left_len = 1;
+    while 2 * left_len < nodes.len() {
+        left_len *= 2;
+    }
+    let left = merge(compressions, &nodes[..left_len], key, domain_flag, false);
+    let right = merge(compressions, &nodes[left_len..], key, domain_flag, false);
+
+    let mut block = [0u32; MSG_ARRAY_SIZE];
+    block[..8].copy_from_slice(&compressions[left].output);
+    block[8..].copy_from_slice(&compressions[right].output);
+    let mut flags = domain_flag | PARENT;
+    if is_root {
+        flags |= ROOT;
+    }
+    let output = BLAKE3::compress(key, &block, 0, BLOCK_LEN as u32, flags);
+    compressions.push(BLAKE3Compression {
+        block,
+        counter: 0,
+        block_len: BLOCK_LEN as u32,
+        flags,
+        chained: false,
+        children: Some((left, right)),
+        output: output[..8].try_into().unwrap(),
+    });
+    compressions.len() - 1
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const KEY: &[u8; KEY_LEN] = b"whats the Elvish word for friend";
+
+    fn input(len: usize) -> Vec<u8> {
+        (0..len).map(|i| (i % 251) as u8).collect()
+    }
+
+    fn to_hex(digest: [u32; STATE_SIZE]) -> String {
+        hex::encode(
+            digest
+                .iter()
+                .flat_map(|w| w.to_le_bytes())
+                .collect::<Vec<_>>(),
+        )
+    }
+
+    #[test]
+    fn test_blake3_hash() {
+        let test_vectors = [
+            (
+                0,
+                "af1349b9f5f9a1a6a0404dea36dcc9499bcb25c9adc112b7cc9a93cae41f3262",
+                "92b2b75604ed3c761f9d6f62392c8a9227ad0ea3f09573e783f1498a4ed60d26",
+            ),
+            (
+                1,
+                "2d3adedff11b61f14c886e35afa036736dcd87a74d27b5c1510225d0f592e213",
+                "6d7878dfff2f485635d39013278ae14f1454b8c0a3a2d34bc1ab38228a80c95b",
+            ),
+            (
+                3,
+                "e1be4d7a8ab5560aa4199eea339849ba8e293d55ca0a81006726d184519e647f",
+                "39e67b76b5a007d4921969779fe666da67b5213b096084ab674742f0d5ec62b9",
+            ),
+            (
+                64,
+                "4eed7141ea4a5cd4b788606bd23f46e212af9cacebacdc7d1f4c6dc7f2511b98",
+                "ba8ced36f327700d213f120b1a207a3b8c04330528586f414d09f2f7d9ccb7e6",
+            ),
+            (
+                65,
+                "de1e5fa0be70df6d2be8fffd0e99ceaa8eb6e8c93a63f2d8d1c30ecb6b263dee",
+                "c0a4edefa2d2accb9277c371ac12fcdbb52988a86edc54f0716e1591b4326e72",
+            ),
+            (
+                1023,
+                "10108970eeda3eb932baac1428c7a2163b0e924c9a9e25b35bba72b28f70bd11",
+                "c951ecdf03288d0fcc96ee3413563d8a6d3589547f2c2fb36d9786470f1b9d6e",
+            ),
+            (
+                1024,
+                "42214739f095a406f3fc83deb889744ac00df831c10daa55189b5d121c855af7",
+                "75c46f6f3d9eb4f55ecaaee480db732e6c2105546f1e675003687c31719c7ba4",
+            ),
+            (
+                1025,
+                "d00278ae47eb27b34faecf67b4fe263f82d5412916c1ffd97c8cb7fb814b8444",
+                "357dc55de0c7e382c900fd6e320acc04146be01db6a8ce7210b7189bd664ea69",
+            ),
+            (
+                2048,
+                "e776b6028c7cd22a4d0ba182a8bf62205d2ef576467e838ed6f2529b85fba24a",
+                "879cf1fa2ea0e79126cb1063617a05b6ad9d0b696d0d757cf053439f60a99dd1",
+            ),
+            (
+                2049,
+                "5f4d72f40d7a5f82b15ca2b2e44b1de3c2ef86c426c95c1af0b6879522563030",
+                "9f29700902f7c86e514ddc4df1e3049f258b2472b6dd5267f61bf13983b78dd5",
+            ),
+        ];
+
+        for (len, hash, keyed_hash) in test_vectors {
+            let msg = input(len);
+            assert_eq!(to_hex(BLAKE3::hash(&msg)), hash, "length {len}");
+            assert_eq!(
+                to_hex(BLAKE3::keyed_hash(KEY, &msg)),
+                keyed_hash,
+                "length {len}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_blake3_compressions() {
+        // Three chunks: the left subtree holds the first two, so the parents are in the order
+        // (c0, c1), (p01, c2).
+        let compressions = BLAKE3::compressions(None, &input(2049));
+        let chunk_ends = compressions
+            .iter()
+            .enumerate()
+            .filter(|(_, c)| c.flags & CHUNK_END != 0)
+            .map(|(i, _)| i)
+            .collect::<Vec<_>>();
+        assert_eq!(chunk_ends, vec![15, 31, 32]);
+        assert_eq!(compressions.len(), 35);
+        assert_eq!(compressions[33].children, Some((15, 31)));
+        assert_eq!(compressions[34].children, Some((33, 32)));
+        assert_eq!(compressions[34].flags, PARENT | ROOT);
+
+        for (i, compression) in compressions.iter().enumerate() {
+            assert_eq!(
+                compression.chained,
+                compression.flags & CHUNK_START == 0 && compression.children.is_none()
+            );
+            if let Some((left, right)) = compression.children {
+                assert!(left < i && right < i);
+                assert_eq!(compression.block[..8], compressions[left].output);
+                assert_eq!(compression.block[8..], compressions[right].output);
+            }
+        }
+    }
+}
@@ -0,0 +1,406 @@
+use log::debug;
+use plonky2::util::log2_ceil;
+
+use super::{
+    pi_index, Keccak, CYCLE_LENGTH, MAX_RATE_LANES, NUM_ROUNDS, ROTATIONS, ROUND_CONSTANTS,
+    STATE_SIZE,
+};
+use crate::chip::arithmetic::expression::ArithmeticExpression;
+use crate::chip::memory::time::Time;
+use crate::chip::memory::value::MemoryValue;
+use crate::chip::register::array::ArrayRegister;
+use crate::chip::register::bit::BitRegister;
+use crate::chip::register::element::ElementRegister;
+use crate::chip::register::Register;
+use crate::chip::uint::operations::instruction::UintInstructions;
+use crate::chip::uint::register::U64Register;
+use crate::chip::uint::util::{u64_from_le_field_bytes, u64_to_le_field_bytes};
+use crate::chip::AirParameters;
+use crate::machine::builder::Builder;
+use crate::machine::bytes::builder::BytesBuilder;
+use crate::machine::hash::batch::{BatchSchedule, HashBatch};
+use crate::machine::hash::{HashIntConversion, HashInteger};
+use crate::math::prelude::*;
+
+impl<B: Builder> HashInteger<B> for Keccak {
+    type Value = <U64Register as Register>::Value<B::Field>;
+    type IntRegister = U64Register;
+}
+
+impl<B: Builder> HashIntConversion<B> for Keccak {
+    fn int_to_field_value(int: Self::Integer) -> Self::Value {
+        u64_to_le_field_bytes(int)
+    }
+
+    fn field_value_to_int(value: &Self::Value) -> Self::Integer {
+        u64_from_le_field_bytes(value)
+    }
+}
+
+/// Keccak AIR implementation.
+///
+/// Each permutation takes a cycle of `CYCLE_LENGTH` rows, one per round. The block of the
+/// permutation is xored into the state in the first row of the cycle, where the state is the
+/// output of the previous permutation of the sponge, or zero for the first permutation. The round
+/// constants and the blocks are read from memory.
+pub trait KeccakAir<B: Builder>: HashIntConversion<B> {
+    /// Applies the permutations of sponges absorbing `blocks`, and returns the first
+    /// `output_lanes` lanes of the states at the indices `output_indices`.
+    ///
+    /// The blocks of a sponge are consecutive and the last one has its bit in `end_bits` set,
+    /// after which the state is reset to zero. A block has at most `MAX_RATE_LANES` lanes, which
+    /// are xored into the first lanes of the state, so that sponges of different rates can share
+    /// the chip. The states after the permutations whose `output_bits` are set are returned, in
+    /// order, and the output indices are the indices of these permutations.
+    ///
+    /// The blocks and the bits of a message are given by [`KeccakSponge::blocks`], where the
+    /// outputs are the states after the last [`KeccakSponge::num_squeezes`] permutations.
+    ///
+    /// [`KeccakSponge::blocks`]: super::pure::KeccakSponge::blocks
+    /// [`KeccakSponge::num_squeezes`]: super::pure::KeccakSponge::num_squeezes
+    fn keccak_sponge(
+        builder: &mut B,
+        blocks: &[ArrayRegister<Self::IntRegister>],
+        end_bits: &ArrayRegister<BitRegister>,
+        output_bits: &ArrayRegister<BitRegister>,
+        output_indices: &ArrayRegister<ElementRegister>,
+        output_lanes: usize,
+    ) -> Vec<ArrayRegister<Self::IntRegister>>;
+
+    /// Applies the permutations of a batch of sponges, each absorbing the blocks of its message,
+    /// and returns the outputs of the messages, see [`HashBatch`].
+    ///
+    /// The blocks of a message are given by [`KeccakSponge::blocks`], and the outputs of a
+    /// message are the first `output_lanes` lanes of the states after its last `num_squeezes`
+    /// permutations. The messages take as many permutations as they need, and the rows of the
+    /// table record the index of their message.
+    ///
+    /// [`KeccakSponge::blocks`]: super::pure::KeccakSponge::blocks
+    fn keccak_sponge_batch(
+        builder: &mut B,
+        messages: &[Vec<ArrayRegister<Self::IntRegister>>],
+        num_squeezes: &[usize],
+        output_lanes: usize,
+    ) -> HashBatch<ArrayRegister<Self::IntRegister>>;
+
+    /// A round of the permutation, returning the next state.
+    fn keccak_round(
+        builder: &mut B,
+        state: &[Self::IntRegister],
+        round_constant: Self::IntRegister,
+    ) -> Vec<Self::IntRegister>;
+}
+
+impl<L: AirParameters> KeccakAir<BytesBuilder<L>> for Keccak
+where
+    L::Instruction: UintInstructions,
+{
+    fn keccak_sponge(
+        builder: &mut BytesBuilder<L>,
+        blocks: &[ArrayRegister<Self::IntRegister>],
+        end_bits: &ArrayRegister<BitRegister>,
+        output_bits: &ArrayRegister<BitRegister>,
+        output_indices: &ArrayRegister<ElementRegister>,
+        output_lanes: usize,
+    ) -> Vec<ArrayRegister<Self::IntRegister>> {
+        let (outputs, _) = keccak_sponge_table(
+            builder,
+            blocks,
+            end_bits,
+            output_bits,
+            output_indices,
+            output_lanes,
+        );
+        outputs
+    }
+
+    fn keccak_sponge_batch(
+        builder: &mut BytesBuilder<L>,
+        messages: &[Vec<ArrayRegister<Self::IntRegister>>],
+        num_squeezes: &[usize],
+        output_lanes: usize,
+    ) -> HashBatch<ArrayRegister<Self::IntRegister>> {
+        let schedule = BatchSchedule::with_outputs(
+            messages.iter().map(|blocks| blocks.len()).collect(),
+            num_squeezes.to_vec(),
+        );
+        let blocks = messages.concat();
+        let to_field = |bits: Vec<bool>| {
+            bits.into_iter()
+                .map(|bit| L::Field::from_canonical_usize(bit as usize))
+                .collect::<Vec<_>>()
+        };
+        let end_bits = builder.constant_array::<BitRegister>(&to_field(schedule.end_bits()));
+        let output_bits = builder.constant_array::<BitRegister>(&to_field(schedule.output_bits()));
+        let output_indices = builder.constant_array::<ElementRegister>(
+            &schedule
+                .output_indices()
+                .into_iter()
+                .map(L::Field::from_canonical_usize)
+                .collect::<Vec<_>>(),
+        );
+
+        let (outputs, process_id) = keccak_sponge_table(
+            builder,
+            &blocks,
+            &end_bits,
+            &output_bits,
+            &output_indices,
+            output_lanes,
+        );
+        let message_id = schedule.load_message_id(builder, process_id, CYCLE_LENGTH);
+
+        HashBatch {
+            schedule,
+            outputs,
+            message_id,
+        }
+    }
+
+    fn keccak_round(
+        builder: &mut BytesBuilder<L>,
+        state: &[Self::IntRegister],
+        round_constant: Self::IntRegister,
+    ) -> Vec<Self::IntRegister> {
+        assert_eq!(state.len(), STATE_SIZE);
+
+        // Theta.
+        let c = (0..5)
+            .map(|x| {
+                let mut c_x = builder.xor(&state[x], &state[x + 5]);
+                for y in 2..5 {
+                    c_x = builder.xor(&c_x, &state[x + 5 * y]);
+                }
+                c_x
+            })
+            .collect::<Vec<_>>();
+        let d = (0..5)
+            .map(|x| {
+                let c_rotated = builder.rotate_right(c[(x + 1) % 5], 63);
+                builder.xor(&c[(x + 4) % 5], &c_rotated)
+            })
+            .collect::<Vec<_>>();
+        let a = state
+            .iter()
+            .enumerate()
+            .map(|(i, lane)| builder.xor(lane, &d[i % 5]))
+            .collect::<Vec<_>>();
+
+        // Rho and pi.
+        let mut b = a.clone();
+        for (i, lane) in a.into_iter().enumerate() {
+            b[pi_index(i)] = match ROTATIONS[i] {
+                0 => lane,
+                rotation => builder.rotate_right(lane, 64 - rotation as usize),
+            };
+        }
+
+        // Chi, where the complement of a lane is linear in its bytes.
+        let ones =
+            ArithmeticExpression::from_constant_vec(vec![L::Field::from_canonical_u8(u8::MAX,); 8]);
+        let not_b = b
+            .iter()
+            .map(|lane| builder.expression::<U64Register>(ones.clone() - lane.expr()))
+            .collect::<Vec<_>>();
+        let mut next_state = (0..STATE_SIZE)
+            .map(|i| {
+                let (x, y) = (i % 5, i / 5);
+                let and = builder.and(&not_b[(x + 1) % 5 + 5 * y], &b[(x + 2) % 5 + 5 * y]);
+                builder.xor(&b[i], &and)
+            })
+            .collect::<Vec<_>>();
+
+        // Iota.
+        next_state[0] = builder.xor(&next_state[0], &round_constant);
+
+        next_state
+    }
+}
+
+/// Lays out the permutations of the sponges absorbing `blocks`, see
+/// [`KeccakAir::keccak_sponge`], and returns the outputs with the index of the permutation of the
+/// current row.
+fn keccak_sponge_table<L: AirParameters>(
+    builder: &mut BytesBuilder<L>,
+    blocks: &[ArrayRegister<U64Register>],
+    end_bits: &ArrayRegister<BitRegister>,
+    output_bits: &ArrayRegister<BitRegister>,
+    output_indices: &ArrayRegister<ElementRegister>,
+    output_lanes: usize,
+) -> (Vec<ArrayRegister<U64Register>>, ElementRegister)
+where
+    L::Instruction: UintInstructions,
+{
+    let num_real_permutations = blocks.len();
+    assert_eq!(end_bits.len(), num_real_permutations);
+    assert_eq!(output_bits.len(), num_real_permutations);
+    assert!(output_lanes > 0 && output_lanes <= STATE_SIZE);
+    debug!(
+        "AIR degree before padding: {}",
+        num_real_permutations * CYCLE_LENGTH
+    );
+    let degree_log = log2_ceil(num_real_permutations * CYCLE_LENGTH);
+    assert!(degree_log < 31, "AIR degree is too large");
+    debug!("AIR degree after padding: {}", 1 << degree_log);
+    builder.api().require_rows("Keccak", 1 << degree_log);
+    // The permutations after the real ones are dummy permutations of zero blocks whose
+    // outputs are ignored. The number of rows is a power of two and not a multiple of
+    // `CYCLE_LENGTH`, so the last dummy permutation is cut short.
+    let num_rows = 1 << degree_log;
+    let num_permutations = num_rows / CYCLE_LENGTH + 1;
+    let length_last_permutation = num_rows % CYCLE_LENGTH;
+
+    // The cycle is made of three segments of eight rows, of which only the bit of the current
+    // segment is set.
+    let cycle_8 = builder.cycle(3);
+    let segment_bits = builder.alloc_array::<BitRegister>(3);
+    for (segment, bit) in segment_bits.iter().enumerate() {
+        let previous = segment_bits.get((segment + 2) % 3);
+        builder.set_to_expression_first_row(
+            &bit,
+            L::Field::from_canonical_usize((segment == 0) as usize).into(),
+        );
+        builder.set_to_expression_transition(
+            &bit.next(),
+            cycle_8.end_bit.expr() * previous.expr() + cycle_8.end_bit.not_expr() * bit.expr(),
+        );
+    }
+    let cycle_start_bit =
+        builder.expression::<BitRegister>(cycle_8.start_bit.expr() * segment_bits.get(0).expr());
+    let cycle_end_bit =
+        builder.expression::<BitRegister>(cycle_8.end_bit.expr() * segment_bits.get(2).expr());
+    let process_id = builder.process_id(CYCLE_LENGTH, cycle_end_bit);
+    let clk = builder.clk();
+    let round = builder.expression::<ElementRegister>(
+        clk.expr() - process_id.expr() * L::Field::from_canonical_usize(CYCLE_LENGTH),
+    );
+
+    // Load the round constant, which is read once per permutation, except for the rounds
+    // after the end of the last permutation.
+    let all_permutations = builder.constant(&L::Field::from_canonical_usize(num_permutations));
+    let all_but_last = builder.constant(&L::Field::from_canonical_usize(num_permutations - 1));
+    let round_multiplicities = (0..NUM_ROUNDS)
+        .map(|r| match r < length_last_permutation {
+            true => all_permutations,
+            false => all_but_last,
+        })
+        .collect::<Vec<_>>();
+    let round_constants =
+        ROUND_CONSTANTS.map(|rc| builder.constant::<U64Register>(&u64_to_le_field_bytes(rc)));
+    let round_constant = load_value(builder, &round_constants, &round_multiplicities, round);
+
+    // Load the block, the end bit and the output bit of the current permutation, which are
+    // read in each of its rows.
+    let cycle_length = builder.constant(&L::Field::from_canonical_usize(CYCLE_LENGTH));
+    let last_length = builder.constant(&L::Field::from_canonical_usize(length_last_permutation));
+    let permutation_multiplicities = (0..num_permutations)
+        .map(|p| match p < num_permutations - 1 {
+            true => cycle_length,
+            false => last_length,
+        })
+        .collect::<Vec<_>>();
+    let zero_lane = builder.constant::<U64Register>(&u64_to_le_field_bytes(0));
+    let block_lanes = (0..MAX_RATE_LANES)
+        .map(|j| {
+            let values = (0..num_permutations)
+                .map(|p| match blocks.get(p) {
+                    Some(block) => {
+                        assert!(block.len() <= MAX_RATE_LANES);
+                        match j < block.len() {
+                            true => block.get(j),
+                            false => zero_lane,
+                        }
+                    }
+                    None => zero_lane,
+                })
+                .collect::<Vec<_>>();
+            load_value(builder, &values, &permutation_multiplicities, process_id)
+        })
+        .collect::<Vec<_>>();
+    let zero_bit = builder.constant::<BitRegister>(&L::Field::ZERO);
+    let load_bits = |builder: &mut BytesBuilder<L>, bits: &ArrayRegister<BitRegister>| {
+        let values = bits
+            .iter()
+            .chain((num_real_permutations..num_permutations).map(|_| zero_bit))
+            .collect::<Vec<_>>();
+        load_value(builder, &values, &permutation_multiplicities, process_id)
+    };
+    let end_bit = load_bits(builder, end_bits);
+    let output_bit = load_bits(builder, output_bits);
+
+    // The state, which is zero in the first row and absorbs the block in the first row of
+    // each cycle.
+    let state = builder.alloc_array::<U64Register>(STATE_SIZE);
+    let zero =
+        ArithmeticExpression::from_constant_vec(u64_to_le_field_bytes::<L::Field>(0).to_vec());
+    for lane in state.iter() {
+        builder.set_to_expression_first_row(&lane, zero.clone());
+    }
+    let input = state
+        .iter()
+        .enumerate()
+        .map(|(j, lane)| match block_lanes.get(j) {
+            Some(block_lane) => {
+                let absorbed =
+                    builder.expression::<U64Register>(cycle_start_bit.expr() * block_lane.expr());
+                builder.xor(&lane, &absorbed)
+            }
+            None => lane,
+        })
+        .collect::<Vec<_>>();
+
+    let output = Keccak::keccak_round(builder, &input, round_constant);
+
+    // Set the next state to the output of the round, unless it is the end of a sponge.
+    let reset_bit = builder.expression::<BitRegister>(cycle_end_bit.expr() * end_bit.expr());
+    for (lane, output_lane) in state.iter().zip(output.iter()) {
+        builder
+            .set_to_expression_transition(&lane.next(), output_lane.expr() * reset_bit.not_expr());
+    }
+
+    // Store the outputs of the permutations and free them at the output indices.
+    let outputs = (0..output_indices.len())
+        .map(|_| builder.alloc_array_public::<U64Register>(output_lanes))
+        .collect::<Vec<_>>();
+    let state_ptr = builder.uninit_slice();
+    for (index, lanes) in output_indices.iter().zip(outputs.iter()) {
+        for (j, lane) in lanes.iter().enumerate() {
+            builder.free(&state_ptr.get(j), lane, &Time::from_element(index));
+        }
+    }
+    let flag = builder.expression(cycle_end_bit.expr() * output_bit.expr());
+    for (j, lane) in output.into_iter().take(output_lanes).enumerate() {
+        builder.store(
+            &state_ptr.get(j),
+            lane,
+            &Time::from_element(process_id),
+            Some(flag),
+            None,
+            None,
+        );
+    }
+
+    (outputs, process_id)
+}
+
+/// Stores `values` in a slice with the given multiplicities, and loads the value at `index`.
+fn load_value<B: Builder, V: MemoryValue>(
+    builder: &mut B,
+    values: &[V],
+    multiplicities: &[ElementRegister],
+    index: ElementRegister,
+) -> V {
+    assert_eq!(values.len(), multiplicities.len());
+    let slice = builder.uninit_slice();
+    for (i, (value, multiplicity)) in values.iter().zip(multiplicities.iter()).enumerate() {
+        builder.store(
+            &slice.get(i),
+            *value,
+            &Time::zero(),
+            Some(*multiplicity),
+            None,
+            None,
+        );
+    }
+    builder.load(&slice.get_at(index), &Time::zero(), None, None)
+}
@@ -0,0 +1,407 @@
+use super::air::KeccakAir;
+use crate::chip::register::array::ArrayRegister;
+use crate::chip::register::bit::BitRegister;
+use crate::chip::register::element::ElementRegister;
+use crate::machine::builder::Builder;
+use crate::machine::hash::batch::HashBatch;
+
+pub trait KeccakBuilder: Builder {
+    /// Computes Keccak sponges, see [`KeccakAir::keccak_sponge`].
+    fn keccak_sponge<K: KeccakAir<Self>>(
+        &mut self,
+        blocks: &[ArrayRegister<K::IntRegister>],
+        end_bits: &ArrayRegister<BitRegister>,
+        output_bits: &ArrayRegister<BitRegister>,
+        output_indices: &ArrayRegister<ElementRegister>,
+        output_lanes: usize,
+    ) -> Vec<ArrayRegister<K::IntRegister>> {
+        K::keccak_sponge(
+            self,
+            blocks,
+            end_bits,
+            output_bits,
+            output_indices,
+            output_lanes,
+        )
+    }
+
+    /// Computes Keccak sponges of a batch of messages, see [`KeccakAir::keccak_sponge_batch`].
+    fn keccak_sponge_batch<K: KeccakAir<Self>>(
+        &mut self,
+        messages: &[Vec<ArrayRegister<K::IntRegister>>],
+        num_squeezes: &[usize],
+        output_lanes: usize,
+    ) -> HashBatch<ArrayRegister<K::IntRegister>> {
+        K::keccak_sponge_batch(self, messages, num_squeezes, output_lanes)
+    }
+}
+
+impl<B: Builder> KeccakBuilder for B {}
+
+#[cfg(test)]
+mod tests {
+    use itertools::Itertools;
+    use plonky2::field::goldilocks_field::GoldilocksField;
+    use plonky2::iop::witness::{PartialWitness, WitnessWrite};
+    use plonky2::plonk::circuit_builder::CircuitBuilder;
+    use plonky2::plonk::circuit_data::CircuitConfig;
+    use plonky2::timed;
+    use plonky2::util::log2_ceil;
+    use plonky2::util::timing::TimingTree;
+    use serde::{Deserialize, Serialize};
+
+    use super::*;
+    use crate::chip::uint::operations::instruction::UintInstruction;
+    use crate::chip::uint::register::U64Register;
+    use crate::chip::uint::util::{u64_from_le_field_bytes, u64_to_le_field_bytes};
+    use crate::chip::AirParameters;
+    use crate::machine::bytes::builder::BytesBuilder;
+    use crate::machine::hash::keccak::pure::{keccak_f, KeccakSponge};
+    use crate::machine::hash::keccak::{Keccak, CYCLE_LENGTH, MAX_RATE_LANES, STATE_SIZE};
+    use crate::math::goldilocks::cubic::GoldilocksCubicParameters;
+    use crate::math::prelude::*;
+    use crate::plonky2::stark::config::{CurtaConfig, CurtaPoseidonGoldilocksConfig};
+    use crate::prelude::{AirWriter, AirWriterData};
+
+    #[derive(Clone, Debug, Serialize, Deserialize)]
+    pub struct KeccakTest;
+
+    impl AirParameters for KeccakTest {
+        type Field = GoldilocksField;
+        type CubicParams = GoldilocksCubicParameters;
+        type Instruction = UintInstruction;
+
+        const NUM_FREE_COLUMNS: usize = 2448;
+        const EXTENDED_COLUMNS: usize = 5682;
+    }
+
+    #[test]
+    fn test_keccak_sponges() {
+        type C = CurtaPoseidonGoldilocksConfig;
+        type Config = <C as CurtaConfig<2>>::GenericConfig;
+
+        let _ = env_logger::builder().is_test(true).try_init();
+        let mut timing = TimingTree::new("test_keccak_sponges", log::Level::Debug);
+
+        // Sponges of all rates in the same table, with two absorptions for the long message and
+        // two squeezes for the long SHAKE128 output.
+        let msg_a3 = vec![0xa3; 200];
+        let messages: [(KeccakSponge, &[u8], &str); 5] = [
+            (
+                KeccakSponge::sha3_256(),
+                b"",
+                "a7ffc6f8bf1ed76651c14756a061d662f580ff4de43b49fa82d80a4b80f8434a",
+            ),
+            (
+                KeccakSponge::sha3_512(),
+                b"abc",
+                "b751850b1a57168a5693cd924b6b096e08f621827444f70d884f5d0240d2712e\
+                 10e116e9192af3c91a7ec57647e3934057340b4cf408d5a56592f8274eec53f0",
+            ),
+            (
+                KeccakSponge::keccak256(),
+                b"",
+                "c5d2460186f7233c927e7db2dcc703c0e500b653ca82273b7bfad8045d85a470",
+            ),
+            (
+                KeccakSponge::shake256(64),
+                &msg_a3,
+                "cd8a920ed141aa0407a22d59288652e9d9f1a7ee0c1e7c1ca699424da84a904d\
+                 2d700caae7396ece96604440577da4f3aa22aeb8857f961c4cd8e06f0ae6610b",
+            ),
+            (
+                KeccakSponge::shake128(200),
+                b"abc",
+                "5881092dd818bf5cf8a3ddb793fbcba74097d5c526a6d35f97b83351940f2cc8\
+                 44c50af32acd3f2cdd066568706f509bc1bdde58295dae3f891a9a0fca578378\
+                 9a41f8611214ce612394df286a62d1a2252aa94db9c538956c717dc2bed4f232\
+                 a0294c857c730aa16067ac1062f1201fb0d377cfb9cde4c63599b27f3462bba4\
+                 a0ed296c801f9ff7f57302bb3076ee145f97a32ae68e76ab66c48d51675bd49a\
+                 cc29082f5647584e6aa01b3f5af057805f973ff8ecb8b226ac32ada6f01c1fcd\
+                 4818cb006aa5b4cd",
+            ),
+        ];
+
+        // The blocks of all sponges, with their end and output bits.
+        let mut block_values = Vec::new();
+        let mut end_bit_values = Vec::new();
+        let mut output_bit_values = Vec::new();
+        for (sponge, msg, _) in messages.iter() {
+            let blocks = sponge.blocks(msg);
+            let num_blocks = blocks.len();
+            for (i, block) in blocks.into_iter().enumerate() {
+                block_values.push(block);
+                end_bit_values.push(i == num_blocks - 1);
+                output_bit_values.push(i >= num_blocks - sponge.num_squeezes());
+            }
+        }
+        let num_permutations = block_values.len();
+        let num_outputs = output_bit_values.iter().filter(|bit| **bit).count();
+
+        // Build the stark.
+        let mut builder = BytesBuilder::<KeccakTest>::new();
+        let blocks = block_values
+            .iter()
+            .map(|block| builder.alloc_array_public::<U64Register>(block.len()))
+            .collect_vec();
+        let end_bits = builder.alloc_array_public::<BitRegister>(num_permutations);
+        let output_bits = builder.alloc_array_public::<BitRegister>(num_permutations);
+        let output_indices = builder.alloc_array_public::<ElementRegister>(num_outputs);
+        let outputs = builder.keccak_sponge::<Keccak>(
+            &blocks,
+            &end_bits,
+            &output_bits,
+            &output_indices,
+            MAX_RATE_LANES,
+        );
+
+        let num_rows = 1 << log2_ceil(CYCLE_LENGTH * num_permutations);
+        let stark = builder.build::<C, 2>(num_rows);
+
+        // Build the recursive circuit.
+        let config_rec = CircuitConfig::standard_recursion_config();
+        let mut recursive_builder = CircuitBuilder::<GoldilocksField, 2>::new(config_rec);
+
+        let (proof_target, public_input) =
+            stark.add_virtual_proof_with_pis_target(&mut recursive_builder);
+        stark.verify_circuit(&mut recursive_builder, &proof_target, &public_input);
+
+        let rec_data = recursive_builder.build::<Config>();
+
+        // Write trace.
+        let mut writer_data = AirWriterData::new(&stark.air_data, num_rows);
+        let mut writer = writer_data.public_writer();
+
+        let mut state = [0u64; STATE_SIZE];
+        let mut outputs_iter = outputs.iter();
+        let mut output_indices_iter = output_indices.iter();
+        for (i, (block, register)) in block_values.iter().zip_eq(blocks.iter()).enumerate() {
+            writer.write_array(
+                register,
+                block
+                    .iter()
+                    .map(|lane| u64_to_le_field_bytes::<GoldilocksField>(*lane)),
+            );
+            for (lane, word) in state.iter_mut().zip(block.iter()) {
+                *lane ^= word;
+            }
+            keccak_f(&mut state);
+            if output_bit_values[i] {
+                writer.write(
+                    &output_indices_iter.next().unwrap(),
+                    &GoldilocksField::from_canonical_usize(i),
+                );
+                writer.write_array(
+                    outputs_iter.next().unwrap(),
+                    state[..MAX_RATE_LANES]
+                        .iter()
+                        .map(|lane| u64_to_le_field_bytes::<GoldilocksField>(*lane)),
+                );
+            }
+            if end_bit_values[i] {
+                state = [0u64; STATE_SIZE];
+            }
+            writer.write(
+                &end_bits.get(i),
+                &GoldilocksField::from_canonical_usize(end_bit_values[i] as usize),
+            );
+            writer.write(
+                &output_bits.get(i),
+                &GoldilocksField::from_canonical_usize(output_bit_values[i] as usize),
+            );
+        }
+
+        timed!(timing, "write input", {
+            stark.air_data.write_global_instructions(&mut writer);
+
+            for mut chunk in writer_data.chunks(num_rows) {
+                for i in 0..num_rows {
+                    let mut writer = chunk.window_writer(i);
+                    stark.air_data.write_trace_instructions(&mut writer);
+                }
+            }
+        });
+
+        // Compare the expected hashes with the outputs.
+        let writer = writer_data.public_writer();
+        let mut outputs_iter = outputs.iter();
+        for (sponge, _, expected) in messages.iter() {
+            let states = (0..sponge.num_squeezes())
+                .map(|_| {
+                    writer
+                        .read_array::<_, MAX_RATE_LANES>(outputs_iter.next().unwrap())
+                        .map(|lane| u64_from_le_field_bytes(&lane))
+                        .to_vec()
+                })
+                .collect_vec();
+            assert_eq!(hex::encode(sponge.output(states)), *expected);
+        }
+
+        let (trace, public) = (writer_data.trace, writer_data.public);
+
+        let proof = timed!(
+            timing,
+            "generate stark proof",
+            stark.prove(&trace, &public, &mut timing).unwrap()
+        );
+
+        stark.verify(proof.clone(), &public).unwrap();
+
+        let mut pw = PartialWitness::new();
+
+        pw.set_target_arr(&public_input, &public).unwrap();
+        stark
+            .set_proof_target(&mut pw, &proof_target, proof)
+            .unwrap();
+
+        let rec_proof = timed!(
+            timing,
+            "generate recursive proof",
+            rec_data.prove(pw).unwrap()
+        );
+        rec_data.verify(rec_proof).unwrap();
+
+        timing.print();
+    }
+
+    #[derive(Clone, Debug, Serialize, Deserialize)]
+    pub struct KeccakBatchTest;
+
+    impl AirParameters for KeccakBatchTest {
+        type Field = GoldilocksField;
+        type CubicParams = GoldilocksCubicParameters;
+        type Instruction = UintInstruction;
+
+        const NUM_FREE_COLUMNS: usize = 2449;
+        const EXTENDED_COLUMNS: usize = 5688;
+    }
+
+    #[test]
+    fn test_keccak_sponge_batch() {
+        type F = GoldilocksField;
+        type C = CurtaPoseidonGoldilocksConfig;
+
+        let _ = env_logger::builder().is_test(true).try_init();
+        let mut timing = TimingTree::new("test_keccak_sponge_batch", log::Level::Debug);
+
+        // Messages of one, two and two permutations, the last one squeezing twice.
+        let msg_a3 = vec![0xa3; 200];
+        let messages: [(KeccakSponge, &[u8], &str); 3] = [
+            (
+                KeccakSponge::sha3_256(),
+                b"",
+                "a7ffc6f8bf1ed76651c14756a061d662f580ff4de43b49fa82d80a4b80f8434a",
+            ),
+            (
+                KeccakSponge::shake256(64),
+                &msg_a3,
+                "cd8a920ed141aa0407a22d59288652e9d9f1a7ee0c1e7c1ca699424da84a904d\
+                 2d700caae7396ece96604440577da4f3aa22aeb8857f961c4cd8e06f0ae6610b",
+            ),
+            (
+                KeccakSponge::shake128(200),
+                b"abc",
+                "5881092dd818bf5cf8a3ddb793fbcba74097d5c526a6d35f97b83351940f2cc8\
+                 44c50af32acd3f2cdd066568706f509bc1bdde58295dae3f891a9a0fca578378\
+                 9a41f8611214ce612394df286a62d1a2252aa94db9c538956c717dc2bed4f232\
+                 a0294c857c730aa16067ac1062f1201fb0d377cfb9cde4c63599b27f3462bba4\
+                 a0ed296c801f9ff7f57302bb3076ee145f97a32ae68e76ab66c48d51675bd49a\
+                 cc29082f5647584e6aa01b3f5af057805f973ff8ecb8b226ac32ada6f01c1fcd\
+                 4818cb006aa5b4cd",
+            ),
+        ];
+        let block_values = messages
+            .iter()
+            .map(|(sponge, msg, _)| sponge.blocks(msg))
+            .collect_vec();
+
+        // Build the stark.
+        let mut builder = BytesBuilder::<KeccakBatchTest>::new();
+        let blocks = block_values
+            .iter()
+            .map(|blocks| {
+                blocks
+                    .iter()
+                    .map(|block| builder.alloc_array_public::<U64Register>(block.len()))
+                    .collect_vec()
+            })
+            .collect_vec();
+        let num_squeezes = messages
+            .iter()
+            .map(|(sponge, ..)| sponge.num_squeezes())
+            .collect_vec();
+        let batch = builder.keccak_sponge_batch::<Keccak>(&blocks, &num_squeezes, MAX_RATE_LANES);
+
+        let num_rows = batch.schedule.num_rows(CYCLE_LENGTH);
+        assert_eq!(num_rows, 1 << 7);
+        let stark = builder.build::<C, 2>(num_rows);
+
+        // Write trace.
+        let mut writer_data = AirWriterData::new(&stark.air_data, num_rows);
+        let mut writer = writer_data.public_writer();
+
+        for (i, (values, registers)) in block_values.iter().zip_eq(blocks.iter()).enumerate() {
+            let mut state = [0u64; STATE_SIZE];
+            let mut states = Vec::new();
+            for (block, register) in values.iter().zip_eq(registers.iter()) {
+                writer.write_array(
+                    register,
+                    block.iter().map(|lane| u64_to_le_field_bytes::<F>(*lane)),
+                );
+                for (lane, word) in state.iter_mut().zip(block.iter()) {
+                    *lane ^= word;
+                }
+                keccak_f(&mut state);
+                states.push(state);
+            }
+            let outputs = batch.outputs(i);
+            for (output, state) in outputs
+                .iter()
+                .zip(states[states.len() - outputs.len()..].iter())
+            {
+                writer.write_array(
+                    output,
+                    state[..MAX_RATE_LANES]
+                        .iter()
+                        .map(|lane| u64_to_le_field_bytes::<F>(*lane)),
+                );
+            }
+        }
+
+        stark.air_data.write_global_instructions(&mut writer);
+
+        // Each row holds the index of its message.
+        let row_message_ids = batch.schedule.row_message_ids(CYCLE_LENGTH);
+        for mut chunk in writer_data.chunks(num_rows) {
+            for (i, message_id) in row_message_ids.iter().enumerate() {
+                let mut writer = chunk.window_writer(i);
+                stark.air_data.write_trace_instructions(&mut writer);
+                assert_eq!(
+                    writer.read(&batch.message_id),
+                    F::from_canonical_usize(*message_id)
+                );
+            }
+        }
+
+        // Compare the expected hashes with the outputs.
+        let writer = writer_data.public_writer();
+        for (i, (sponge, _, expected)) in messages.iter().enumerate() {
+            let states = batch
+                .outputs(i)
+                .iter()
+                .map(|output| {
+                    writer
+                        .read_array::<_, MAX_RATE_LANES>(output)
+                        .map(|lane| u64_from_le_field_bytes(&lane))
+                        .to_vec()
+                })
+                .collect_vec();
+            assert_eq!(hex::encode(sponge.output(states)), *expected);
+        }
+
+        let (trace, public) = (writer_data.trace, writer_data.public);
+
+        let proof = stark.prove(&trace, &public, &mut timing).unwrap();
+        stark.verify(proof, &public).unwrap();
+    }
+}
@@ -0,0 +1,66 @@
+//! The Keccak-f[1600] permutation and the sponges built on it.
+//!
+//! The state is made of 25 lanes of 64 bits, where the lane `(x, y)` is at index `x + 5 * y`. A
+//! sponge absorbs the padded message by blocks of `rate` bytes, which are xored into the first
+//! lanes of the state before each permutation, and squeezes its output from the same lanes. The
+//! SHA-3 hashes and the SHAKE extendable-output functions differ by their rate, their domain
+//! separation byte and their output length, see [`pure::KeccakSponge`].
+
+use serde::{Deserialize, Serialize};
+
+pub mod air;
+pub mod builder;
+pub mod pure;
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct Keccak;
+
+/// The number of rounds of the permutation.
+const NUM_ROUNDS: usize = 24;
+/// The number of rows of a permutation in the AIR, one per round.
+pub(crate) const CYCLE_LENGTH: usize = NUM_ROUNDS;
+pub const STATE_SIZE: usize = 25;
+/// The largest rate in lanes, that of SHAKE128.
+pub const MAX_RATE_LANES: usize = 21;
+
+pub(crate) const ROUND_CONSTANTS: [u64; NUM_ROUNDS] = [
+    0x0000000000000001,
+    0x0000000000008082,
+    0x800000000000808a,
+    0x8000000080008000,
+    0x000000000000808b,
+    0x0000000080000001,
+    0x8000000080008081,
+    0x8000000000008009,
+    0x000000000000008a,
+    0x0000000000000088,
+    0x0000000080008009,
+    0x000000008000000a,
+    0x000000008000808b,
+    0x800000000000008b,
+    0x8000000000008089,
+    0x8000000000008003,
+    0x8000000000008002,
+    0x8000000000000080,
+    0x000000000000800a,
+    0x800000008000000a,
+    0x8000000080008081,
+    0x8000000000008080,
+    0x0000000080000001,
+    0x8000000080008008,
+];
+
+/// The left rotations of the lanes in the `rho` step.
+pub(crate) const ROTATIONS: [u32; STATE_SIZE] = [
+    0, 1, 62, 28, 27, //
+    36, 44, 6, 55, 20, //
+    3, 10, 43, 25, 39, //
+    41, 45, 15, 21, 8, //
+    18, 2, 61, 56, 14,
+];
+
+/// The index of the lane to which the lane `(x, y)` is moved by the `pi` step, `(y, 2x + 3y)`.
+pub(crate) const fn pi_index(i: usize) -> usize {
+    let (x, y) = (i % 5, i / 5);
+    y + 5 * ((2 * x + 3 * y) % 5)
+}
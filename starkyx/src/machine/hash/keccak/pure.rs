@@ -0,0 +1,266 @@
+use core::array::from_fn;
+
+use serde::{Deserialize, Serialize};
+
+use super::{pi_index, Keccak, MAX_RATE_LANES, ROTATIONS, ROUND_CONSTANTS, STATE_SIZE};
+use crate::machine::hash::HashPureInteger;
+
+impl HashPureInteger for Keccak {
+    type Integer = u64;
+}
+
+/// The Keccak-f[1600] permutation.
+pub fn keccak_f(state: &mut [u64; STATE_SIZE]) {
+    for round_constant in ROUND_CONSTANTS {
+        keccak_round(state, round_constant);
+    }
+}
+
+/// A round of the permutation, with the steps `theta`, `rho`, `pi`, `chi` and `iota`.
+fn keccak_round(state: &mut [u64; STATE_SIZE], round_constant: u64) {
+    let c: [u64; 5] = from_fn(|x| (0..5).fold(0, |acc, y| acc ^ state[x + 5 * y]));
+    for (i, lane) in state.iter_mut().enumerate() {
+        let x = i % 5;
+        *lane ^= c[(x + 4) % 5] ^ c[(x + 1) % 5].rotate_left(1);
+    }
+
+    let mut b = [0u64; STATE_SIZE];
+    for (i, lane) in state.iter().enumerate() {
+        b[pi_index(i)] = lane.rotate_left(ROTATIONS[i]);
+    }
+
+    for (i, lane) in state.iter_mut().enumerate() {
+        let (x, y) = (i % 5, i / 5);
+        *lane = b[i] ^ (!b[(x + 1) % 5 + 5 * y] & b[(x + 2) % 5 + 5 * y]);
+    }
+
+    state[0] ^= round_constant;
+}
+
+/// The parameters of a sponge over Keccak-f[1600].
+///
+/// The message is padded with the domain separation byte, followed by zeros and a final bit, to a
+/// multiple of the rate. After the absorption of the padded message, an output of `output_len`
+/// bytes is squeezed by blocks of `rate` bytes, with a permutation between two blocks.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct KeccakSponge {
+    /// The number of bytes absorbed or squeezed by a permutation.
+    pub rate: usize,
+    /// The domain separation byte, which includes the first bit of the padding.
+    pub domain: u8,
+    /// The number of bytes of the output.
+    pub output_len: usize,
+}
+
+impl KeccakSponge {
+    pub fn new(rate: usize, domain: u8, output_len: usize) -> Self {
+        assert!(
+            rate > 0 && rate % 8 == 0 && rate <= 8 * MAX_RATE_LANES,
+            "The rate must be a positive number of lanes, at most {}",
+            MAX_RATE_LANES
+        );
+        assert!(output_len > 0, "The output must not be empty");
+        Self {
+            rate,
+            domain,
+            output_len,
+        }
+    }
+
+    pub fn sha3_256() -> Self {
+        Self::new(136, 0x06, 32)
+    }
+
+    pub fn sha3_512() -> Self {
+        Self::new(72, 0x06, 64)
+    }
+
+    /// The original Keccak padding with a 256-bit output, as used by Ethereum.
+    pub fn keccak256() -> Self {
+        Self::new(136, 0x01, 32)
+    }
+
+    pub fn shake128(output_len: usize) -> Self {
+        Self::new(168, 0x1f, output_len)
+    }
+
+    pub fn shake256(output_len: usize) -> Self {
+        Self::new(136, 0x1f, output_len)
+    }
+
+    /// The number of lanes of a block.
+    pub fn rate_lanes(&self) -> usize {
+        self.rate / 8
+    }
+
+    /// Pads `msg` to a multiple of the rate, as little-endian lanes.
+    pub fn pad(&self, msg: &[u8]) -> Vec<u64> {
+        let mut padded_msg = msg.to_vec();
+        padded_msg.push(self.domain);
+        padded_msg.resize(self.num_absorbs(msg) * self.rate, 0);
+        *padded_msg.last_mut().unwrap() |= 0x80;
+
+        padded_msg
+            .chunks_exact(8)
+            .map(|slice| u64::from_le_bytes(slice.try_into().unwrap()))
+            .collect()
+    }
+
+    /// The number of blocks of the padded message.
+    pub fn num_absorbs(&self, msg: &[u8]) -> usize {
+        msg.len() / self.rate + 1
+    }
+
+    /// The number of blocks of the output.
+    pub fn num_squeezes(&self) -> usize {
+        (self.output_len + self.rate - 1) / self.rate
+    }
+
+    /// The blocks xored into the state before each permutation, which are the blocks of the padded
+    /// message followed by a zero block for each squeeze after the first.
+    ///
+    /// The outputs are the states after the last `num_squeezes` permutations.
+    pub fn blocks(&self, msg: &[u8]) -> Vec<Vec<u64>> {
+        let mut blocks = self
+            .pad(msg)
+            .chunks_exact(self.rate_lanes())
+            .map(|block| block.to_vec())
+            .collect::<Vec<_>>();
+        blocks.extend((1..self.num_squeezes()).map(|_| vec![0; self.rate_lanes()]));
+        blocks
+    }
+
+    /// The output from the first `rate` bytes of the output states, given by their first lanes.
+    pub fn output<I: IntoIterator<Item = Vec<u64>>>(&self, states: I) -> Vec<u8> {
+        let mut output = states
+            .into_iter()
+            .flat_map(|state| state[..self.rate_lanes()].to_vec())
+            .flat_map(|lane| lane.to_le_bytes())
+            .collect::<Vec<_>>();
+        assert!(output.len() >= self.output_len, "Not enough output states");
+        output.truncate(self.output_len);
+        output
+    }
+
+    /// The hash of `msg`, of `output_len` bytes.
+    pub fn hash(&self, msg: &[u8]) -> Vec<u8> {
+        let blocks = self.blocks(msg);
+        let num_absorbs = self.num_absorbs(msg);
+        let states = blocks
+            .iter()
+            .scan([0u64; STATE_SIZE], |state, block| {
+                for (lane, word) in state.iter_mut().zip(block.iter()) {
+                    *lane ^= word;
+                }
+                keccak_f(state);
+                Some(state.to_vec())
+            })
+            .skip(num_absorbs - 1)
+            .collect::<Vec<_>>();
+        self.output(states)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_keccak_sponge() {
+        let msg_a3 = vec![0xa3; 200];
+        let msg_135 = (0..135).collect::<Vec<u8>>();
+        let msg_136 = (0..136).collect::<Vec<u8>>();
+        let test_vectors: [(KeccakSponge, &[u8], &str); 13] = [
+            (
+                KeccakSponge::sha3_256(),
+                b"",
+                "a7ffc6f8bf1ed76651c14756a061d662f580ff4de43b49fa82d80a4b80f8434a",
+            ),
+            (
+                KeccakSponge::sha3_256(),
+                b"abc",
+                "3a985da74fe225b2045c172d6bd390bd855f086e3e9d525b46bfe24511431532",
+            ),
+            (
+                KeccakSponge::sha3_256(),
+                &msg_a3,
+                "79f38adec5c20307a98ef76e8324afbfd46cfd81b22e3973c65fa1bd9de31787",
+            ),
+            (
+                KeccakSponge::sha3_256(),
+                &msg_135,
+                "fded8fd9d6551c601eeb3b7c6bc5e5cfd8aad1d015b7e9aaa9c9b9475231d5e2",
+            ),
+            (
+                KeccakSponge::sha3_512(),
+                b"",
+                "a69f73cca23a9ac5c8b567dc185a756e97c982164fe25859e0d1dcc1475c80a6\
+                 15b2123af1f5f94c11e3e9402c3ac558f500199d95b6d3e301758586281dcd26",
+            ),
+            (
+                KeccakSponge::sha3_512(),
+                b"abc",
+                "b751850b1a57168a5693cd924b6b096e08f621827444f70d884f5d0240d2712e\
+                 10e116e9192af3c91a7ec57647e3934057340b4cf408d5a56592f8274eec53f0",
+            ),
+            (
+                KeccakSponge::sha3_512(),
+                &msg_a3,
+                "e76dfad22084a8b1467fcf2ffa58361bec7628edf5f3fdc0e4805dc48caeeca8\
+                 1b7c13c30adf52a3659584739a2df46be589c51ca1a4a8416df6545a1ce8ba00",
+            ),
+            (
+                KeccakSponge::keccak256(),
+                b"",
+                "c5d2460186f7233c927e7db2dcc703c0e500b653ca82273b7bfad8045d85a470",
+            ),
+            (
+                KeccakSponge::shake128(32),
+                b"",
+                "7f9c2ba4e88f827d616045507605853ed73b8093f6efbc88eb1a6eacfa66ef26",
+            ),
+            (
+                KeccakSponge::shake128(32),
+                &msg_a3,
+                "131ab8d2b594946b9c81333f9bb6e0ce75c3b93104fa3469d3917457385da037",
+            ),
+            (
+                KeccakSponge::shake256(64),
+                b"",
+                "46b9dd2b0ba88d13233b3feb743eeb243fcd52ea62b81b82b50c27646ed5762f\
+                 d75dc4ddd8c0f200cb05019d67b592f6fc821c49479ab48640292eacb3b7c4be",
+            ),
+            (
+                KeccakSponge::shake256(64),
+                &msg_a3,
+                "cd8a920ed141aa0407a22d59288652e9d9f1a7ee0c1e7c1ca699424da84a904d\
+                 2d700caae7396ece96604440577da4f3aa22aeb8857f961c4cd8e06f0ae6610b",
+            ),
+            (
+                KeccakSponge::shake256(16),
+                &msg_136,
+                "b7ff4073b3f5a8eabd6e17705ca7f676",
+            ),
+        ];
+
+        for (sponge, msg, digest) in test_vectors {
+            assert_eq!(hex::encode(sponge.hash(msg)), digest);
+        }
+    }
+
+    #[test]
+    fn test_shake128_long_output() {
+        // An output of 200 bytes takes two squeezes.
+        let sponge = KeccakSponge::shake128(200);
+        assert_eq!(sponge.num_squeezes(), 2);
+        let output = sponge.hash(b"abc");
+        assert_eq!(
+            hex::encode(&output[..32]),
+            "5881092dd818bf5cf8a3ddb793fbcba74097d5c526a6d35f97b83351940f2cc8"
+        );
+        assert_eq!(
+            hex::encode(&output[180..]),
+            "ecb8b226ac32ada6f01c1fcd4818cb006aa5b4cd"
+        );
+    }
+}
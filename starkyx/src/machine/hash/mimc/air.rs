@@ -0,0 +1,171 @@
+use log::debug;
+use plonky2::util::log2_ceil;
+
+use super::pure::{cube, GMiMC, MiMCFeistel, MiMCPure};
+use crate::chip::arithmetic::expression::ArithmeticExpression;
+use crate::chip::memory::time::Time;
+use crate::chip::register::array::ArrayRegister;
+use crate::chip::register::bit::BitRegister;
+use crate::chip::register::element::ElementRegister;
+use crate::chip::register::Register;
+use crate::machine::builder::Builder;
+use crate::machine::hash::poseidon2::air::load_row_value;
+use crate::math::prelude::*;
+
+/// MiMC AIR implementation.
+///
+/// A permutation takes a single row, with one register of degree 3 for the cube of each round.
+/// The other elements of the state are sums of the input and of these registers, so they are
+/// kept as expressions and only the output is committed.
+pub trait MiMCAir<B: Builder>: MiMCPure<B::Field> {
+    /// Computes the permutation of `input` in the current row. The input expressions must have
+    /// degree 1.
+    fn permutation(
+        &self,
+        builder: &mut B,
+        input: &[ArithmeticExpression<B::Field>],
+    ) -> ArrayRegister<ElementRegister>;
+
+    /// Hashes messages with the sponge construction and returns their digests at the indices
+    /// `digest_indices`.
+    ///
+    /// Each of `chunks` is a chunk of `self.rate()` elements absorbed by one permutation, in the
+    /// row of the same index. The chunks of a message are consecutive and the last one has its
+    /// bit in `end_bits` set, after which the capacity is reset to zero for the next message. The
+    /// digest indices are the indices of the last chunks of the messages, see
+    /// [`MiMCPure::hash_no_pad`].
+    fn sponge(
+        &self,
+        builder: &mut B,
+        chunks: &[ArrayRegister<ElementRegister>],
+        end_bits: &ArrayRegister<BitRegister>,
+        digest_indices: &ArrayRegister<ElementRegister>,
+    ) -> Vec<ArrayRegister<ElementRegister>> {
+        let (rate, digest_length) = (self.rate(), self.digest_length());
+        let num_real_permutations = chunks.len();
+        assert_eq!(end_bits.len(), num_real_permutations);
+        debug!("AIR degree before padding: {}", num_real_permutations);
+        let degree_log = log2_ceil(num_real_permutations);
+        assert!(degree_log < 31, "AIR degree is too large");
+        debug!("AIR degree after padding: {}", 1 << degree_log);
+        builder.api().require_rows("MiMC", 1 << degree_log);
+        // The permutations after the real ones are dummy permutations of zero chunks whose
+        // outputs are ignored.
+        let num_permutations = 1 << degree_log;
+        let clk = builder.clk();
+
+        // Load the chunk and the end bit of the current row.
+        let zero = builder.constant::<ElementRegister>(&B::Field::ZERO);
+        let zero_bit = builder.constant::<BitRegister>(&B::Field::ZERO);
+        let chunk = (0..rate)
+            .map(|j| {
+                let values = chunks
+                    .iter()
+                    .map(|chunk| {
+                        assert_eq!(chunk.len(), rate);
+                        chunk.get(j)
+                    })
+                    .collect::<Vec<_>>();
+                load_row_value(builder, &values, zero, num_permutations, clk)
+            })
+            .collect::<Vec<_>>();
+        let end_bit = load_row_value(
+            builder,
+            &end_bits.iter().collect::<Vec<_>>(),
+            zero_bit,
+            num_permutations,
+            clk,
+        );
+
+        // The capacity is carried over from the previous permutation of the same message.
+        let capacity = builder.alloc_array::<ElementRegister>(self.width() - rate);
+        for register in capacity.iter() {
+            builder.set_to_expression_first_row(&register, B::Field::ZERO.into());
+        }
+        let input = chunk
+            .iter()
+            .map(|register| register.expr())
+            .chain(capacity.iter().map(|register| register.expr()))
+            .collect::<Vec<_>>();
+        let output = self.permutation(builder, &input);
+        for (register, output_i) in capacity.iter().zip(output.iter().skip(rate)) {
+            builder.set_to_expression_transition(
+                &register.next(),
+                output_i.expr() * end_bit.not_expr(),
+            );
+        }
+
+        // Store the digests of the messages and free them at the digest indices.
+        let digests = (0..digest_indices.len())
+            .map(|_| builder.alloc_array_public::<ElementRegister>(digest_length))
+            .collect::<Vec<_>>();
+        let digest_ptr = builder.uninit_slice();
+        for (index, digest) in digest_indices.iter().zip(digests.iter()) {
+            for (j, element) in digest.iter().enumerate() {
+                builder.free(&digest_ptr.get(j), element, &Time::from_element(index));
+            }
+        }
+        let flag = builder.expression(end_bit.expr());
+        for (j, element) in output.iter().take(digest_length).enumerate() {
+            builder.store(
+                &digest_ptr.get(j),
+                element,
+                &Time::from_element(clk),
+                Some(flag),
+                None,
+                None,
+            );
+        }
+
+        digests
+    }
+}
+
+/// Commits the output of a permutation to registers.
+fn commit_output<B: Builder>(
+    builder: &mut B,
+    state: Vec<ArithmeticExpression<B::Field>>,
+) -> ArrayRegister<ElementRegister> {
+    let output = builder.alloc_array::<ElementRegister>(state.len());
+    for (register, expression) in output.iter().zip(state) {
+        builder.set_to_expression(&register, expression);
+    }
+    output
+}
+
+impl<B: Builder> MiMCAir<B> for MiMCFeistel<B::Field> {
+    fn permutation(
+        &self,
+        builder: &mut B,
+        input: &[ArithmeticExpression<B::Field>],
+    ) -> ArrayRegister<ElementRegister> {
+        assert_eq!(input.len(), 2);
+        let (mut left, mut right) = (input[0].clone(), input[1].clone());
+        for &c in self.round_constants.iter() {
+            let f = builder.expression::<ElementRegister>(cube(left.clone() + c));
+            (left, right) = (right + f.expr(), left);
+        }
+        commit_output(builder, vec![left, right])
+    }
+}
+
+impl<B: Builder> MiMCAir<B> for GMiMC<B::Field> {
+    fn permutation(
+        &self,
+        builder: &mut B,
+        input: &[ArithmeticExpression<B::Field>],
+    ) -> ArrayRegister<ElementRegister> {
+        assert_eq!(input.len(), self.width);
+        let mut state = input.to_vec();
+        for (round, &c) in self.round_constants.iter().enumerate() {
+            let active = round % self.width;
+            let f = builder.expression::<ElementRegister>(cube(state[active].clone() + c));
+            for (i, x) in state.iter_mut().enumerate() {
+                if i != active {
+                    *x = x.clone() + f.expr();
+                }
+            }
+        }
+        commit_output(builder, state)
+    }
+}
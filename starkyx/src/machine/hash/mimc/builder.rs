@@ -0,0 +1,196 @@
+use super::air::MiMCAir;
+use crate::chip::register::array::ArrayRegister;
+use crate::chip::register::bit::BitRegister;
+use crate::chip::register::element::ElementRegister;
+use crate::chip::register::Register;
+use crate::machine::builder::Builder;
+
+pub trait MiMCBuilder: Builder {
+    /// Computes the permutation `mimc` of `input` in the current row.
+    fn mimc<P: MiMCAir<Self>>(
+        &mut self,
+        mimc: &P,
+        input: &ArrayRegister<ElementRegister>,
+    ) -> ArrayRegister<ElementRegister> {
+        let input = input.iter().map(|x| x.expr()).collect::<Vec<_>>();
+        mimc.permutation(self, &input)
+    }
+
+    /// Hashes messages with the sponge of `mimc`, see [`MiMCAir::sponge`].
+    fn mimc_sponge<P: MiMCAir<Self>>(
+        &mut self,
+        mimc: &P,
+        chunks: &[ArrayRegister<ElementRegister>],
+        end_bits: &ArrayRegister<BitRegister>,
+        digest_indices: &ArrayRegister<ElementRegister>,
+    ) -> Vec<ArrayRegister<ElementRegister>> {
+        mimc.sponge(self, chunks, end_bits, digest_indices)
+    }
+}
+
+impl<B: Builder> MiMCBuilder for B {}
+
+#[cfg(test)]
+mod tests {
+    use plonky2::field::goldilocks_field::GoldilocksField;
+    use plonky2::iop::witness::{PartialWitness, WitnessWrite};
+    use plonky2::plonk::circuit_builder::CircuitBuilder;
+    use plonky2::plonk::circuit_data::CircuitConfig;
+    use plonky2::timed;
+    use plonky2::util::log2_ceil;
+    use plonky2::util::timing::TimingTree;
+    use serde::{Deserialize, Serialize};
+
+    use super::*;
+    use crate::chip::{AirParameters, Chip};
+    use crate::machine::hash::mimc::pure::{GMiMC, MiMCFeistel};
+    use crate::machine::stark::builder::StarkBuilder;
+    use crate::math::goldilocks::cubic::GoldilocksCubicParameters;
+    use crate::math::prelude::*;
+    use crate::plonky2::stark::config::{CurtaConfig, CurtaPoseidonGoldilocksConfig};
+    use crate::plonky2::{Plonky2Air, ProverAir};
+    use crate::prelude::{AirWriter, AirWriterData, EmptyInstruction};
+
+    #[derive(Clone, Debug, Serialize, Deserialize)]
+    pub struct MiMCFeistelTest;
+
+    impl AirParameters for MiMCFeistelTest {
+        type Field = GoldilocksField;
+        type CubicParams = GoldilocksCubicParameters;
+        type Instruction = EmptyInstruction<GoldilocksField>;
+
+        const NUM_FREE_COLUMNS: usize = 89;
+        const EXTENDED_COLUMNS: usize = 15;
+    }
+
+    #[derive(Clone, Debug, Serialize, Deserialize)]
+    pub struct GMiMCTest;
+
+    impl AirParameters for GMiMCTest {
+        type Field = GoldilocksField;
+        type CubicParams = GoldilocksCubicParameters;
+        type Instruction = EmptyInstruction<GoldilocksField>;
+
+        const NUM_FREE_COLUMNS: usize = 128;
+        const EXTENDED_COLUMNS: usize = 66;
+    }
+
+    fn test_mimc_sponge<L, P>(mimc: P)
+    where
+        L: AirParameters<Field = GoldilocksField, CubicParams = GoldilocksCubicParameters>,
+        P: MiMCAir<StarkBuilder<L>>,
+        Chip<L>: Plonky2Air<GoldilocksField, 2>,
+        Chip<L>: ProverAir<GoldilocksField, CurtaPoseidonGoldilocksConfig, 2>,
+    {
+        type C = CurtaPoseidonGoldilocksConfig;
+        type Config = <C as CurtaConfig<2>>::GenericConfig;
+
+        let _ = env_logger::builder().is_test(true).try_init();
+        let mut timing = TimingTree::new("test_mimc_sponge", log::Level::Debug);
+
+        // Messages of 1 to 8 chunks.
+        let rate = mimc.rate();
+        let messages = (1..=8)
+            .map(|num_chunks| {
+                (0..num_chunks * rate)
+                    .map(|_| GoldilocksField::rand())
+                    .collect::<Vec<_>>()
+            })
+            .collect::<Vec<_>>();
+        let num_chunks = messages.iter().map(|msg| msg.len() / rate).sum::<usize>();
+
+        // Build the stark.
+        let mut builder = StarkBuilder::<L>::new();
+        let chunks = (0..num_chunks)
+            .map(|_| builder.alloc_array_public::<ElementRegister>(rate))
+            .collect::<Vec<_>>();
+        let end_bits = builder.alloc_array_public::<BitRegister>(num_chunks);
+        let digest_indices = builder.alloc_array_public::<ElementRegister>(messages.len());
+        let digests = builder.mimc_sponge(&mimc, &chunks, &end_bits, &digest_indices);
+
+        let num_rows = 1 << log2_ceil(num_chunks);
+        let stark = builder.build::<C, 2>(num_rows);
+
+        // Build the recursive circuit.
+        let config_rec = CircuitConfig::standard_recursion_config();
+        let mut recursive_builder = CircuitBuilder::<GoldilocksField, 2>::new(config_rec);
+
+        let (proof_target, public_input) =
+            stark.add_virtual_proof_with_pis_target(&mut recursive_builder);
+        stark.verify_circuit(&mut recursive_builder, &proof_target, &public_input);
+
+        let rec_data = recursive_builder.build::<Config>();
+
+        // Write trace.
+        let mut writer_data = AirWriterData::new(&stark.air_data, num_rows);
+        let mut writer = writer_data.public_writer();
+
+        let mut chunk_registers = chunks.iter().enumerate();
+        for (position, (msg, digest)) in messages.iter().zip(digests.iter()).enumerate() {
+            let msg_chunks = msg.chunks_exact(rate);
+            let num_msg_chunks = msg_chunks.len();
+            for (j, (chunk, (i, register))) in msg_chunks.zip(&mut chunk_registers).enumerate() {
+                let is_end = j == num_msg_chunks - 1;
+                writer.write_array(register, chunk);
+                writer.write(
+                    &end_bits.get(i),
+                    &GoldilocksField::from_canonical_usize(is_end as usize),
+                );
+                if is_end {
+                    writer.write(
+                        &digest_indices.get(position),
+                        &GoldilocksField::from_canonical_usize(i),
+                    );
+                }
+            }
+            writer.write_array(digest, mimc.hash_no_pad(msg));
+        }
+
+        timed!(timing, "write input", {
+            stark.air_data.write_global_instructions(&mut writer);
+
+            for mut chunk in writer_data.chunks(num_rows) {
+                for i in 0..num_rows {
+                    let mut writer = chunk.window_writer(i);
+                    stark.air_data.write_trace_instructions(&mut writer);
+                }
+            }
+        });
+
+        let (trace, public) = (writer_data.trace, writer_data.public);
+
+        let proof = timed!(
+            timing,
+            "generate stark proof",
+            stark.prove(&trace, &public, &mut timing).unwrap()
+        );
+
+        stark.verify(proof.clone(), &public).unwrap();
+
+        let mut pw = PartialWitness::new();
+
+        pw.set_target_arr(&public_input, &public).unwrap();
+        stark
+            .set_proof_target(&mut pw, &proof_target, proof)
+            .unwrap();
+
+        let rec_proof = timed!(
+            timing,
+            "generate recursive proof",
+            rec_data.prove(pw).unwrap()
+        );
+        rec_data.verify(rec_proof).unwrap();
+
+        timing.print();
+    }
+
+    #[test]
+    fn test_mimc_feistel_sponge() {
+        test_mimc_sponge::<MiMCFeistelTest, _>(MiMCFeistel::new());
+    }
+
+    #[test]
+    fn test_gmimc_sponge() {
+        test_mimc_sponge::<GMiMCTest, _>(GMiMC::new(12, 4));
+    }
+}
@@ -0,0 +1,81 @@
+//! Round constants and numbers of rounds of the instances over each field.
+//!
+//! The round constants of an instance are the 8-byte little-endian words of the SHAKE256 output
+//! of a seed naming the instance and the order of the field, reduced modulo the order. The
+//! numbers of rounds are those given by the interpolation bounds of the MiMC and GMiMC papers for
+//! the cube, which depend on the size of the field.
+
+use crate::machine::hash::keccak::pure::KeccakSponge;
+use crate::math::prelude::*;
+
+/// The first `num_rounds` round constants of the instance named `name` over `F`.
+pub fn round_constants<F: PrimeField64>(name: &str, num_rounds: usize) -> Vec<F> {
+    let order = F::order();
+    let seed = format!("{}_{}", name, order);
+    KeccakSponge::shake256(8 * num_rounds)
+        .hash(seed.as_bytes())
+        .chunks_exact(8)
+        .map(|word| F::from_canonical_u64(u64::from_le_bytes(word.try_into().unwrap()) % order))
+        .collect()
+}
+
+/// The number of cubings after which the degree of the permutation exceeds the order of `F`,
+/// that is the least `r` such that `3^r >= |F|`.
+pub fn num_cubings<F: PrimeField64>() -> usize {
+    let order = F::order() as u128;
+    let mut degree = 1u128;
+    let mut num_rounds = 0;
+    while degree < order {
+        degree *= 3;
+        num_rounds += 1;
+    }
+    num_rounds
+}
+
+/// The number of rounds of MiMC-2p/p, twice the number of rounds of MiMC-p/p.
+pub fn mimc_feistel_rounds<F: PrimeField64>() -> usize {
+    2 * num_cubings::<F>()
+}
+
+/// The number of rounds of GMiMC-erf of width `width`.
+///
+/// This is the number of cubings plus `5 * width` rounds, which gives the 101 rounds of the
+/// instance of width 12 over the Goldilocks field.
+pub fn gmimc_rounds<F: PrimeField64>(width: usize) -> usize {
+    num_cubings::<F>() + 5 * width
+}
+
+#[cfg(test)]
+mod tests {
+    use plonky2::field::goldilocks_field::GoldilocksField;
+
+    use super::*;
+    use crate::math::mersenne31::Mersenne31Field;
+
+    #[test]
+    fn test_number_of_rounds() {
+        assert_eq!(num_cubings::<GoldilocksField>(), 41);
+        assert_eq!(mimc_feistel_rounds::<GoldilocksField>(), 82);
+        assert_eq!(gmimc_rounds::<GoldilocksField>(12), 101);
+        assert_eq!(num_cubings::<Mersenne31Field>(), 20);
+    }
+
+    #[test]
+    fn test_round_constants_per_field() {
+        let constants = round_constants::<GoldilocksField>("gmimc", 4);
+        assert_eq!(constants.len(), 4);
+        assert_eq!(constants, round_constants::<GoldilocksField>("gmimc", 4));
+        assert_ne!(constants, round_constants::<GoldilocksField>("mimc", 4));
+
+        // The constants over another field come from another seed.
+        let goldilocks = round_constants::<GoldilocksField>("mimc", 4)
+            .iter()
+            .map(|c| c.as_canonical_u64() % Mersenne31Field::order())
+            .collect::<Vec<_>>();
+        let mersenne = round_constants::<Mersenne31Field>("mimc", 4)
+            .iter()
+            .map(|c| c.as_canonical_u64())
+            .collect::<Vec<_>>();
+        assert_ne!(goldilocks, mersenne);
+    }
+}
@@ -0,0 +1,20 @@
+//! MiMC and GMiMC permutations over prime fields.
+//!
+//! Both permutations are Feistel networks whose round function is the cube `(x + c)^3` of a
+//! single element, so that a round costs a single column of degree 3 in the AIR. As the round
+//! function of a Feistel network need not be invertible, the cube is used over every field.
+//!
+//! - [`pure::MiMCFeistel`] is MiMC-2p/p, a balanced Feistel network on two elements, used as a sponge
+//!   with a rate of one element, as in the `MiMCSponge` of circomlib.
+//! - [`pure::GMiMC`] is GMiMC-erf, an unbalanced Feistel network in which the round function of one
+//!   element is added to all the others, used as a sponge of any width and capacity.
+//!
+//! The round constants are generated for each field by [`constants::round_constants`], so that
+//! the same instance is defined over any of the fields of the crate. These permutations are
+//! much cheaper than Poseidon and Poseidon2 in the AIR, and are meant for protocols in which
+//! both sides agree to use them.
+
+pub mod air;
+pub mod builder;
+pub mod constants;
+pub mod pure;
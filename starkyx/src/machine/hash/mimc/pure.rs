@@ -0,0 +1,229 @@
+use core::fmt::Debug;
+use core::ops::Mul;
+
+use super::constants::{gmimc_rounds, mimc_feistel_rounds, round_constants};
+use crate::math::prelude::*;
+
+/// Pure MiMC implementation.
+///
+/// An interface for a MiMC permutation and its sponge as Rust functions operating on field
+/// elements.
+pub trait MiMCPure<F: PrimeField64>: Debug + Clone + Send + Sync + 'static {
+    /// The number of elements of the state.
+    fn width(&self) -> usize;
+
+    /// The number of elements absorbed by each permutation of the sponge.
+    fn rate(&self) -> usize;
+
+    /// The number of elements of a digest, which are the first elements of the final state.
+    fn digest_length(&self) -> usize;
+
+    fn permute(&self, state: &[F]) -> Vec<F>;
+
+    /// Hashes the input with the sponge construction, without padding.
+    ///
+    /// Each chunk of `self.rate()` elements overwrites the beginning of the state before the
+    /// state is permuted, and the digest is the beginning of the final state. The length of the
+    /// input must be a non-zero multiple of the rate.
+    fn hash_no_pad(&self, input: &[F]) -> Vec<F> {
+        let rate = self.rate();
+        assert!(
+            !input.is_empty() && input.len() % rate == 0,
+            "The input length {} is not a non-zero multiple of the rate {}",
+            input.len(),
+            rate
+        );
+        let mut state = vec![F::ZERO; self.width()];
+        for chunk in input.chunks_exact(rate) {
+            state[..rate].copy_from_slice(chunk);
+            state = self.permute(&state);
+        }
+        state.truncate(self.digest_length());
+        state
+    }
+}
+
+/// The MiMC-2p/p permutation, a Feistel network on two elements `(x_L, x_R)`.
+///
+/// Each round maps `(x_L, x_R)` to `(x_R + (x_L + c)^3, x_L)`. As a sponge, the first element is
+/// the rate and the second one the capacity.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MiMCFeistel<F> {
+    pub round_constants: Vec<F>,
+}
+
+/// The GMiMC-erf permutation, an unbalanced Feistel network on `width` elements.
+///
+/// The round of index `r` cubes the element of index `r % width` plus the round constant, and
+/// adds the cube to all other elements. As a sponge, the first `width - capacity` elements are
+/// the rate, and a digest has `capacity` elements.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GMiMC<F> {
+    pub width: usize,
+    pub capacity: usize,
+    pub round_constants: Vec<F>,
+}
+
+impl<F: PrimeField64> MiMCFeistel<F> {
+    /// The instance over `F`, with the rounds and round constants of [`super::constants`].
+    pub fn new() -> Self {
+        Self {
+            round_constants: round_constants("mimc_feistel", mimc_feistel_rounds::<F>()),
+        }
+    }
+}
+
+impl<F: PrimeField64> Default for MiMCFeistel<F> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<F: PrimeField64> GMiMC<F> {
+    /// The instance of width `width` over `F`, with a sponge capacity of `capacity` elements.
+    pub fn new(width: usize, capacity: usize) -> Self {
+        assert!(width >= 2, "The width must be at least 2");
+        assert!(
+            capacity > 0 && 2 * capacity <= width,
+            "The capacity {} must be positive and at most the rate",
+            capacity
+        );
+        Self {
+            width,
+            capacity,
+            round_constants: round_constants(&format!("gmimc_{}", width), gmimc_rounds::<F>(width)),
+        }
+    }
+}
+
+pub(crate) fn cube<T: Clone + Mul<Output = T>>(x: T) -> T {
+    x.clone() * x.clone() * x
+}
+
+impl<F: PrimeField64> MiMCPure<F> for MiMCFeistel<F> {
+    fn width(&self) -> usize {
+        2
+    }
+
+    fn rate(&self) -> usize {
+        1
+    }
+
+    fn digest_length(&self) -> usize {
+        1
+    }
+
+    fn permute(&self, state: &[F]) -> Vec<F> {
+        assert_eq!(state.len(), 2);
+        let (mut left, mut right) = (state[0], state[1]);
+        for &c in self.round_constants.iter() {
+            (left, right) = (right + cube(left + c), left);
+        }
+        vec![left, right]
+    }
+}
+
+impl<F: PrimeField64> MiMCPure<F> for GMiMC<F> {
+    fn width(&self) -> usize {
+        self.width
+    }
+
+    fn rate(&self) -> usize {
+        self.width - self.capacity
+    }
+
+    fn digest_length(&self) -> usize {
+        self.capacity
+    }
+
+    fn permute(&self, state: &[F]) -> Vec<F> {
+        assert_eq!(state.len(), self.width);
+        let mut state = state.to_vec();
+        for (round, &c) in self.round_constants.iter().enumerate() {
+            let active = round % self.width;
+            let f = cube(state[active] + c);
+            for (i, x) in state.iter_mut().enumerate() {
+                if i != active {
+                    *x += f;
+                }
+            }
+        }
+        state
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use plonky2::field::goldilocks_field::GoldilocksField;
+
+    use super::*;
+    use crate::math::mersenne31::Mersenne31Field;
+
+    /// Inverts MiMC-2p/p by running the rounds backwards.
+    fn mimc_feistel_inverse<F: PrimeField64>(mimc: &MiMCFeistel<F>, state: &[F]) -> Vec<F> {
+        let (mut left, mut right) = (state[0], state[1]);
+        for &c in mimc.round_constants.iter().rev() {
+            (left, right) = (right, left - cube(right + c));
+        }
+        vec![left, right]
+    }
+
+    /// Inverts GMiMC-erf by running the rounds backwards.
+    fn gmimc_inverse<F: PrimeField64>(gmimc: &GMiMC<F>, state: &[F]) -> Vec<F> {
+        let mut state = state.to_vec();
+        for (round, &c) in gmimc.round_constants.iter().enumerate().rev() {
+            let active = round % gmimc.width;
+            let f = cube(state[active] + c);
+            for (i, x) in state.iter_mut().enumerate() {
+                if i != active {
+                    *x -= f;
+                }
+            }
+        }
+        state
+    }
+
+    fn test_permutations<F: PrimeField64 + Sample>() {
+        let mimc = MiMCFeistel::<F>::new();
+        let state = vec![F::rand(), F::rand()];
+        let output = mimc.permute(&state);
+        assert_ne!(output, state);
+        assert_eq!(mimc_feistel_inverse(&mimc, &output), state);
+
+        let gmimc = GMiMC::<F>::new(12, 4);
+        let state = (0..12).map(|_| F::rand()).collect::<Vec<_>>();
+        let output = gmimc.permute(&state);
+        assert_ne!(output, state);
+        assert_eq!(gmimc_inverse(&gmimc, &output), state);
+    }
+
+    #[test]
+    fn test_mimc_permutations() {
+        test_permutations::<GoldilocksField>();
+        test_permutations::<Mersenne31Field>();
+    }
+
+    #[test]
+    fn test_mimc_sponge() {
+        type F = GoldilocksField;
+        let input = (0..16).map(|_| F::rand()).collect::<Vec<_>>();
+
+        // With a rate of 8, the input is absorbed by two permutations.
+        let gmimc = GMiMC::<F>::new(12, 4);
+        let mut state = vec![F::ZERO; 12];
+        state[..8].copy_from_slice(&input[..8]);
+        state = gmimc.permute(&state);
+        state[..8].copy_from_slice(&input[8..]);
+        state = gmimc.permute(&state);
+        assert_eq!(gmimc.hash_no_pad(&input), state[..4]);
+
+        // With a rate of 1, each element is absorbed by its own permutation.
+        let mimc = MiMCFeistel::<F>::new();
+        let mut state = vec![F::ZERO; 2];
+        for &x in input.iter() {
+            state[0] = x;
+            state = mimc.permute(&state);
+        }
+        assert_eq!(mimc.hash_no_pad(&input), state[..1]);
+    }
+}
@@ -7,7 +7,20 @@ use crate::chip::memory::value::MemoryValue;
 use crate::chip::register::array::ArrayRegister;
 use crate::chip::register::Register;
 
+pub mod batch;
+#[cfg(feature = "blake")]
 pub mod blake;
+#[cfg(feature = "keccak")]
+pub mod keccak;
+#[cfg(feature = "mimc")]
+pub mod mimc;
+#[cfg(feature = "poseidon")]
+pub mod poseidon;
+#[cfg(feature = "poseidon2")]
+pub mod poseidon2;
+#[cfg(feature = "ripemd160")]
+pub mod ripemd160;
+#[cfg(feature = "sha")]
 pub mod sha;
 
 pub trait HashPureInteger {
@@ -0,0 +1,268 @@
+use core::array::from_fn;
+
+use log::debug;
+use plonky2::util::log2_ceil;
+
+use super::{
+    is_full_round, Bn254ScalarField, PoseidonBn254, CYCLE_LENGTH, NUM_ROUNDS, RATE, WIDTH,
+};
+use crate::chip::field::instruction::FromFieldInstruction;
+use crate::chip::field::register::FieldRegister;
+use crate::chip::memory::time::Time;
+use crate::chip::memory::value::MemoryValue;
+use crate::chip::register::bit::BitRegister;
+use crate::chip::register::element::ElementRegister;
+use crate::chip::register::Register;
+use crate::machine::builder::Builder;
+use crate::math::bigint::bn254::Bn254Scalar;
+use crate::math::prelude::*;
+
+/// Poseidon AIR implementation over the scalar field of BN254.
+///
+/// Each permutation takes a cycle of `CYCLE_LENGTH` rows, one per round. A row applies the S-box
+/// and the MDS matrix to the state, followed by the addition of the round constants of the next
+/// round, so that the input of a permutation is given with the constants of the first round
+/// already added. The round constants, the kind of round and the inputs are read from memory.
+pub trait PoseidonBn254Air<B: Builder>
+where
+    B::Instruction: FromFieldInstruction<Bn254ScalarField>,
+{
+    /// Hashes each pair of `inputs` and returns the digests, see [`PoseidonBn254::hash`].
+    ///
+    /// The inputs must be reduced modulo the order of the scalar field.
+    fn hash_pairs(
+        builder: &mut B,
+        inputs: &[[FieldRegister<Bn254ScalarField>; RATE]],
+    ) -> Vec<FieldRegister<Bn254ScalarField>>;
+
+    /// A round of the permutation without its round constants, followed by the addition of
+    /// `next_round_constants`, where the S-box is applied to the whole state if `full_round_bit`
+    /// is set and to the first element otherwise.
+    fn poseidon_round(
+        builder: &mut B,
+        state: &[FieldRegister<Bn254ScalarField>; WIDTH],
+        full_round_bit: BitRegister,
+        next_round_constants: &[FieldRegister<Bn254ScalarField>; WIDTH],
+    ) -> [FieldRegister<Bn254ScalarField>; WIDTH];
+}
+
+impl<B: Builder> PoseidonBn254Air<B> for PoseidonBn254
+where
+    B::Instruction: FromFieldInstruction<Bn254ScalarField>,
+{
+    fn hash_pairs(
+        builder: &mut B,
+        inputs: &[[FieldRegister<Bn254ScalarField>; RATE]],
+    ) -> Vec<FieldRegister<Bn254ScalarField>> {
+        let num_real_permutations = inputs.len();
+        debug!(
+            "AIR degree before padding: {}",
+            num_real_permutations * CYCLE_LENGTH
+        );
+        let degree_log = log2_ceil(num_real_permutations * CYCLE_LENGTH);
+        assert!(degree_log < 31, "AIR degree is too large");
+        debug!("AIR degree after padding: {}", 1 << degree_log);
+        builder.api().require_rows("Poseidon", 1 << degree_log);
+        // The permutations after the real ones are dummy permutations of zero inputs whose
+        // outputs are ignored. The number of rows is a power of two and not a multiple of
+        // `CYCLE_LENGTH`, so the last dummy permutation is cut short.
+        let num_rows = 1 << degree_log;
+        let num_permutations = num_rows / CYCLE_LENGTH + 1;
+        let length_last_permutation = num_rows % CYCLE_LENGTH;
+
+        // The round counter, which is reset after the last round of a permutation.
+        let round = builder.alloc::<ElementRegister>();
+        builder.set_to_expression_first_row(&round, B::Field::ZERO.into());
+
+        // Load the data of the current round, which is read once per permutation, except for the
+        // rounds after the end of the last permutation.
+        let all_permutations = builder.constant(&B::Field::from_canonical_usize(num_permutations));
+        let all_but_last = builder.constant(&B::Field::from_canonical_usize(num_permutations - 1));
+        let round_multiplicities = (0..NUM_ROUNDS)
+            .map(|r| match r < length_last_permutation {
+                true => all_permutations,
+                false => all_but_last,
+            })
+            .collect::<Vec<_>>();
+        let zero_bit = builder.constant::<BitRegister>(&B::Field::ZERO);
+        let one_bit = builder.constant::<BitRegister>(&B::Field::ONE);
+        let bit_values = |f: &dyn Fn(usize) -> bool| {
+            (0..NUM_ROUNDS)
+                .map(|r| match f(r) {
+                    true => one_bit,
+                    false => zero_bit,
+                })
+                .collect::<Vec<_>>()
+        };
+        let start_bit_values = bit_values(&|r| r == 0);
+        let end_bit_values = bit_values(&|r| r == NUM_ROUNDS - 1);
+        let full_round_bit_values = bit_values(&is_full_round);
+        let start_bit = load_value(builder, &start_bit_values, &round_multiplicities, round);
+        let end_bit = load_value(builder, &end_bit_values, &round_multiplicities, round);
+        let full_round_bit = load_value(
+            builder,
+            &full_round_bit_values,
+            &round_multiplicities,
+            round,
+        );
+        builder.set_to_expression_transition(
+            &round.next(),
+            (round.expr() + B::Field::ONE) * end_bit.not_expr(),
+        );
+        let process_id = builder.process_id(CYCLE_LENGTH, end_bit);
+
+        // The constants of the next round, which are zero after the last round.
+        let mut round_constant_values = (1..NUM_ROUNDS)
+            .map(|r| PoseidonBn254::round_constants(r).map(|c| fp_constant(builder, c)))
+            .collect::<Vec<_>>();
+        round_constant_values.push([(); WIDTH].map(|_| fp_constant(builder, Bn254Scalar::ZERO)));
+        let next_round_constants: [_; WIDTH] = from_fn(|i| {
+            let values = round_constant_values
+                .iter()
+                .map(|constants| constants[i])
+                .collect::<Vec<_>>();
+            load_value(builder, &values, &round_multiplicities, round)
+        });
+
+        // Load the input of the current permutation, which is read in each of its rows, with the
+        // constants of the first round added.
+        let cycle_length = builder.constant(&B::Field::from_canonical_usize(CYCLE_LENGTH));
+        let last_length =
+            builder.constant(&B::Field::from_canonical_usize(length_last_permutation));
+        let permutation_multiplicities = (0..num_permutations)
+            .map(|p| match p < num_permutations - 1 {
+                true => cycle_length,
+                false => last_length,
+            })
+            .collect::<Vec<_>>();
+        let first_round_constants =
+            PoseidonBn254::round_constants(0).map(|c| fp_constant(builder, c));
+        let input_values = inputs
+            .iter()
+            .map(|[a, b]| {
+                [
+                    first_round_constants[0],
+                    builder.api().fp_add(a, &first_round_constants[1]),
+                    builder.api().fp_add(b, &first_round_constants[2]),
+                ]
+            })
+            .chain((num_real_permutations..num_permutations).map(|_| first_round_constants))
+            .collect::<Vec<_>>();
+        let input: [_; WIDTH] = from_fn(|i| {
+            let values = input_values
+                .iter()
+                .map(|input| input[i])
+                .collect::<Vec<_>>();
+            load_value(builder, &values, &permutation_multiplicities, process_id)
+        });
+        let real_bit_values = (0..num_permutations)
+            .map(|p| match p < num_real_permutations {
+                true => one_bit,
+                false => zero_bit,
+            })
+            .collect::<Vec<_>>();
+        let real_bit = load_value(
+            builder,
+            &real_bit_values,
+            &permutation_multiplicities,
+            process_id,
+        );
+
+        // The state, which is replaced by the input in the first row of each cycle.
+        let state: [FieldRegister<Bn254ScalarField>; WIDTH] = from_fn(|_| builder.alloc());
+        let zero = fp_constant(builder, Bn254Scalar::ZERO);
+        for x in state.iter() {
+            builder.set_to_expression_first_row(x, zero.expr());
+        }
+        let round_input: [_; WIDTH] = from_fn(|i| builder.select(start_bit, &input[i], &state[i]));
+
+        let output =
+            Self::poseidon_round(builder, &round_input, full_round_bit, &next_round_constants);
+        for (x, output_x) in state.iter().zip(output.iter()) {
+            builder.set_to_expression_transition(&x.next(), output_x.expr());
+        }
+
+        // Store the digests of the real permutations and free them at their indices.
+        let digests = (0..num_real_permutations)
+            .map(|_| builder.alloc_public::<FieldRegister<Bn254ScalarField>>())
+            .collect::<Vec<_>>();
+        let digest_ptr = builder.uninit();
+        for (i, digest) in digests.iter().enumerate() {
+            let index = builder.constant(&B::Field::from_canonical_usize(i));
+            builder.free(&digest_ptr, *digest, &Time::from_element(index));
+        }
+        let flag = builder.expression(end_bit.expr() * real_bit.expr());
+        builder.store(
+            &digest_ptr,
+            output[0],
+            &Time::from_element(process_id),
+            Some(flag),
+            None,
+            None,
+        );
+
+        digests
+    }
+
+    fn poseidon_round(
+        builder: &mut B,
+        state: &[FieldRegister<Bn254ScalarField>; WIDTH],
+        full_round_bit: BitRegister,
+        next_round_constants: &[FieldRegister<Bn254ScalarField>; WIDTH],
+    ) -> [FieldRegister<Bn254ScalarField>; WIDTH] {
+        // The S-box, whose output is only used for the first element in partial rounds.
+        let sbox: [_; WIDTH] = from_fn(|i| {
+            let x = state[i];
+            let x2 = builder.api().fp_mul(&x, &x);
+            let x4 = builder.api().fp_mul(&x2, &x2);
+            builder.api().fp_mul(&x4, &x)
+        });
+        let sbox_state: [_; WIDTH] = from_fn(|i| match i {
+            0 => sbox[0],
+            _ => builder.select(full_round_bit, &sbox[i], &state[i]),
+        });
+
+        // The MDS matrix and the next round constants, as inner products with a last term of one.
+        let one = fp_constant(builder, Bn254Scalar::ONE);
+        let mds_matrix = PoseidonBn254::mds_matrix();
+        from_fn(|i| {
+            let row = mds_matrix[i]
+                .iter()
+                .map(|m| fp_constant(builder, *m))
+                .chain([one])
+                .collect::<Vec<_>>();
+            let terms = sbox_state
+                .iter()
+                .copied()
+                .chain([next_round_constants[i]])
+                .collect::<Vec<_>>();
+            builder.api().fp_inner_product(&row, &terms)
+        })
+    }
+}
+
+fn fp_constant<B: Builder>(builder: &mut B, value: Bn254Scalar) -> FieldRegister<Bn254ScalarField> {
+    builder.api().fp_constant(&value.to_biguint())
+}
+
+/// Stores `values` in a slice with the given multiplicities, and loads the value at `index`.
+fn load_value<B: Builder, V: MemoryValue>(
+    builder: &mut B,
+    values: &[V],
+    multiplicities: &[ElementRegister],
+    index: ElementRegister,
+) -> V {
+    assert_eq!(values.len(), multiplicities.len());
+    let slice = builder.uninit_slice();
+    for (i, (value, multiplicity)) in values.iter().zip(multiplicities.iter()).enumerate() {
+        builder.store(
+            &slice.get(i),
+            *value,
+            &Time::zero(),
+            Some(*multiplicity),
+            None,
+            None,
+        );
+    }
+    builder.load(&slice.get_at(index), &Time::zero(), None, None)
+}
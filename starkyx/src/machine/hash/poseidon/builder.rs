@@ -0,0 +1,161 @@
+use super::air::PoseidonBn254Air;
+use super::{Bn254ScalarField, PoseidonBn254, RATE};
+use crate::chip::field::instruction::FromFieldInstruction;
+use crate::chip::field::register::FieldRegister;
+use crate::machine::builder::Builder;
+
+pub trait PoseidonBn254Builder: Builder
+where
+    Self::Instruction: FromFieldInstruction<Bn254ScalarField>,
+{
+    /// Hashes each pair of `inputs` with the Poseidon hash of circomlib, see
+    /// [`PoseidonBn254Air::hash_pairs`].
+    fn poseidon_bn254(
+        &mut self,
+        inputs: &[[FieldRegister<Bn254ScalarField>; RATE]],
+    ) -> Vec<FieldRegister<Bn254ScalarField>> {
+        PoseidonBn254::hash_pairs(self, inputs)
+    }
+}
+
+impl<B: Builder> PoseidonBn254Builder for B where
+    B::Instruction: FromFieldInstruction<Bn254ScalarField>
+{
+}
+
+#[cfg(test)]
+mod tests {
+    use plonky2::field::goldilocks_field::GoldilocksField;
+    use plonky2::iop::witness::{PartialWitness, WitnessWrite};
+    use plonky2::plonk::circuit_builder::CircuitBuilder;
+    use plonky2::plonk::circuit_data::CircuitConfig;
+    use plonky2::timed;
+    use plonky2::util::log2_ceil;
+    use plonky2::util::timing::TimingTree;
+    use rand::thread_rng;
+    use serde::{Deserialize, Serialize};
+
+    use super::*;
+    use crate::chip::field::instruction::FpInstruction;
+    use crate::chip::AirParameters;
+    use crate::machine::emulated::builder::EmulatedBuilder;
+    use crate::machine::hash::poseidon::CYCLE_LENGTH;
+    use crate::math::bigint::bn254::Bn254Scalar;
+    use crate::math::goldilocks::cubic::GoldilocksCubicParameters;
+    use crate::math::prelude::*;
+    use crate::plonky2::stark::config::{CurtaConfig, CurtaPoseidonGoldilocksConfig};
+    use crate::polynomial::to_u16_le_limbs_polynomial;
+    use crate::prelude::{AirWriter, AirWriterData};
+
+    #[derive(Clone, Debug, Serialize, Deserialize)]
+    pub struct PoseidonBn254Test;
+
+    impl AirParameters for PoseidonBn254Test {
+        type Field = GoldilocksField;
+        type CubicParams = GoldilocksCubicParameters;
+        type Instruction = FpInstruction<Bn254ScalarField>;
+
+        const NUM_ARITHMETIC_COLUMNS: usize = 1328;
+        const NUM_FREE_COLUMNS: usize = 10;
+        const EXTENDED_COLUMNS: usize = 2100;
+    }
+
+    #[test]
+    fn test_poseidon_bn254() {
+        type F = GoldilocksField;
+        type C = CurtaPoseidonGoldilocksConfig;
+        type Config = <C as CurtaConfig<2>>::GenericConfig;
+
+        let _ = env_logger::builder().is_test(true).try_init();
+        let mut timing = TimingTree::new("test_poseidon_bn254", log::Level::Debug);
+
+        // The inputs of circomlib's test vector, followed by random inputs.
+        let mut rng = thread_rng();
+        let input_values = [[
+            Bn254Scalar::from_canonical_u64(1),
+            Bn254Scalar::from_canonical_u64(2),
+        ]]
+        .into_iter()
+        .chain((0..4).map(|_| [Bn254Scalar::sample(&mut rng), Bn254Scalar::sample(&mut rng)]))
+        .collect::<Vec<_>>();
+        let num_hashes = input_values.len();
+
+        // Build the stark.
+        let mut builder = EmulatedBuilder::<PoseidonBn254Test>::new();
+        let inputs = (0..num_hashes)
+            .map(|_| [builder.alloc_public(), builder.alloc_public()])
+            .collect::<Vec<_>>();
+        let digests = builder.poseidon_bn254(&inputs);
+
+        let num_rows = 1 << log2_ceil(CYCLE_LENGTH * num_hashes);
+        let stark = builder.build::<C, 2>(num_rows);
+
+        // Build the recursive circuit.
+        let config_rec = CircuitConfig::standard_recursion_config();
+        let mut recursive_builder = CircuitBuilder::<GoldilocksField, 2>::new(config_rec);
+
+        let (proof_target, public_input) =
+            stark.add_virtual_proof_with_pis_target(&mut recursive_builder);
+        stark.verify_circuit(&mut recursive_builder, &proof_target, &public_input);
+
+        let rec_data = recursive_builder.build::<Config>();
+
+        // Write trace.
+        let mut writer_data = AirWriterData::new(&stark.air_data, num_rows);
+        let mut writer = writer_data.public_writer();
+
+        let to_limbs =
+            |x: Bn254Scalar| to_u16_le_limbs_polynomial::<F, Bn254ScalarField>(&x.to_biguint());
+        for ((values, registers), digest) in
+            input_values.iter().zip(inputs.iter()).zip(digests.iter())
+        {
+            for (value, register) in values.iter().zip(registers.iter()) {
+                writer.write(register, &to_limbs(*value));
+            }
+            writer.write(digest, &to_limbs(PoseidonBn254::hash(*values)));
+        }
+        assert_eq!(
+            PoseidonBn254::hash(input_values[0])
+                .to_biguint()
+                .to_str_radix(16),
+            "115cc0f5e7d690413df64c6b9662e9cf2a3617f2743245519e19607a4417189a"
+        );
+
+        timed!(timing, "write input", {
+            stark.air_data.write_global_instructions(&mut writer);
+
+            for mut chunk in writer_data.chunks(num_rows) {
+                for i in 0..num_rows {
+                    let mut writer = chunk.window_writer(i);
+                    stark.air_data.write_trace_instructions(&mut writer);
+                }
+            }
+        });
+
+        let (trace, public) = (writer_data.trace, writer_data.public);
+
+        let proof = timed!(
+            timing,
+            "generate stark proof",
+            stark.prove(&trace, &public, &mut timing).unwrap()
+        );
+
+        stark.verify(proof.clone(), &public).unwrap();
+
+        let mut pw = PartialWitness::new();
+
+        pw.set_target_arr(&public_input, &public).unwrap();
+        stark
+            .set_proof_target(&mut pw, &proof_target, proof)
+            .unwrap();
+
+        let rec_proof = timed!(
+            timing,
+            "generate recursive proof",
+            rec_data.prove(pw).unwrap()
+        );
+        rec_data.verify(rec_proof).unwrap();
+
+        timing.print();
+    }
+}
@@ -0,0 +1,67 @@
+//! The original Poseidon permutation over the scalar field of BN254.
+//!
+//! This is the instance of width 3 used by circomlib and by the Poseidon contracts and gadgets of
+//! the Ethereum ecosystem, so that a digest computed in a STARK can be recomputed cheaply by a
+//! Groth16 or PLONK wrapper over BN254, or by an EVM contract. A permutation applies 4 full
+//! rounds, 57 partial rounds and 4 full rounds, where a round adds the round constants, applies
+//! the S-box `x^5` to the whole state in full rounds or to the first element in partial rounds,
+//! and multiplies the state by the MDS matrix.
+//!
+//! The hash of two elements `a` and `b` is the first element of the permutation of `[0, a, b]`.
+//!
+//! The elements of the scalar field are not native to the STARK, so the AIR represents them with
+//! [`FieldRegister`]s over [`Bn254ScalarField`] and uses the non-native field instructions.
+//!
+//! [`FieldRegister`]: crate::chip::field::register::FieldRegister
+
+use serde::{Deserialize, Serialize};
+
+use crate::chip::field::parameters::{FieldParameters, MAX_NB_LIMBS};
+
+pub mod air;
+pub mod builder;
+pub mod constants;
+pub mod pure;
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct PoseidonBn254;
+
+/// The parameters of the scalar field of BN254 for the non-native field instructions.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Bn254ScalarField;
+
+impl FieldParameters for Bn254ScalarField {
+    const NB_BITS_PER_LIMB: usize = 16;
+
+    const NB_LIMBS: usize = 16;
+
+    const NB_WITNESS_LIMBS: usize = 2 * Self::NB_LIMBS - 2;
+
+    // Scalar field modulus:
+    //  21888242871839275222246405745257275088548364400416034343698204186575808495617
+    const MODULUS: [u16; MAX_NB_LIMBS] = [
+        1, 61440, 62867, 17377, 28817, 31161, 59464, 10291, 22621, 33153, 17846, 47184, 41001,
+        57649, 20082, 12388, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
+    ];
+
+    // The linear layer is an inner product of four terms, whose witness is larger than that of a
+    // single multiplication.
+    const WITNESS_OFFSET: usize = 1usize << 22;
+}
+
+/// The number of elements of the state.
+pub const WIDTH: usize = 3;
+/// The number of input elements of a hash, which are absorbed after the zero capacity element.
+pub const RATE: usize = WIDTH - 1;
+
+/// The number of full rounds, half of which come before the partial rounds.
+pub const NUM_FULL_ROUNDS: usize = 8;
+pub const NUM_PARTIAL_ROUNDS: usize = 57;
+const NUM_ROUNDS: usize = NUM_FULL_ROUNDS + NUM_PARTIAL_ROUNDS;
+/// The number of rows of a permutation in the AIR, one per round.
+const CYCLE_LENGTH: usize = NUM_ROUNDS;
+
+/// Whether the round of index `round` is a full round.
+const fn is_full_round(round: usize) -> bool {
+    round < NUM_FULL_ROUNDS / 2 || round >= NUM_FULL_ROUNDS / 2 + NUM_PARTIAL_ROUNDS
+}
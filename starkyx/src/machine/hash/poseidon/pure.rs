@@ -0,0 +1,94 @@
+use core::array::from_fn;
+
+use super::constants::{MDS_MATRIX, ROUND_CONSTANTS};
+use super::{is_full_round, PoseidonBn254, NUM_ROUNDS, RATE, WIDTH};
+use crate::math::bigint::bn254::Bn254Scalar;
+use crate::math::prelude::*;
+
+impl PoseidonBn254 {
+    /// The round constants added to the state at the beginning of the round `round`.
+    pub fn round_constants(round: usize) -> [Bn254Scalar; WIDTH] {
+        from_fn(|i| Bn254Scalar::from_canonical_limbs(ROUND_CONSTANTS[WIDTH * round + i]))
+    }
+
+    pub fn mds_matrix() -> [[Bn254Scalar; WIDTH]; WIDTH] {
+        MDS_MATRIX.map(|row| row.map(Bn254Scalar::from_canonical_limbs))
+    }
+
+    pub fn permute(state: [Bn254Scalar; WIDTH]) -> [Bn254Scalar; WIDTH] {
+        let mds_matrix = Self::mds_matrix();
+        let mut state = state;
+        for round in 0..NUM_ROUNDS {
+            for (x, c) in state.iter_mut().zip(Self::round_constants(round)) {
+                *x += c;
+            }
+            match is_full_round(round) {
+                true => state = state.map(sbox),
+                false => state[0] = sbox(state[0]),
+            }
+            state = from_fn(|i| {
+                mds_matrix[i]
+                    .iter()
+                    .zip(state.iter())
+                    .map(|(m, x)| *m * *x)
+                    .sum()
+            });
+        }
+        state
+    }
+
+    /// Hashes `inputs` into the first element of the permutation of `[0, inputs[0], inputs[1]]`,
+    /// which is the Poseidon hash of circomlib with two inputs.
+    pub fn hash(inputs: [Bn254Scalar; RATE]) -> Bn254Scalar {
+        let state = [Bn254Scalar::ZERO, inputs[0], inputs[1]];
+        Self::permute(state)[0]
+    }
+}
+
+fn sbox(x: Bn254Scalar) -> Bn254Scalar {
+    let x2 = x * x;
+    x2 * x2 * x
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn from_hex(hex: &str) -> Bn254Scalar {
+        let bytes: [u8; 32] = hex::decode(hex).unwrap().try_into().unwrap();
+        Bn254Scalar::try_from_bytes_be(&bytes).unwrap()
+    }
+
+    #[test]
+    fn test_poseidon_bn254_hash() {
+        // Test vectors of circomlib's `poseidon` with two inputs.
+        let test_vectors = [
+            (
+                [0u64, 0],
+                "2098f5fb9e239eab3ceac3f27b81e481dc3124d55ffed523a839ee8446b64864",
+            ),
+            (
+                [1, 2],
+                "115cc0f5e7d690413df64c6b9662e9cf2a3617f2743245519e19607a4417189a",
+            ),
+        ];
+
+        for (inputs, digest) in test_vectors {
+            let inputs = inputs.map(Bn254Scalar::from_canonical_u64);
+            assert_eq!(PoseidonBn254::hash(inputs), from_hex(digest));
+        }
+    }
+
+    #[test]
+    fn test_poseidon_bn254_constants() {
+        // The first round constant and the first entry of the MDS matrix.
+        assert_eq!(
+            PoseidonBn254::round_constants(0)[0],
+            from_hex("0ee9a592ba9a9518d05986d656f40c2114c4993c11bb29938d21d47304cd8e6e")
+        );
+        assert_eq!(
+            PoseidonBn254::mds_matrix()[0][0],
+            from_hex("109b7f411ba0e4c9b2b70caf5c36a7b194be7c11ad24378bfedb68592ba8118b")
+        );
+    }
+}
@@ -0,0 +1,201 @@
+use log::debug;
+use plonky2::field::goldilocks_field::GoldilocksField;
+use plonky2::util::log2_ceil;
+
+use super::pure::{external_linear_layer, internal_linear_layer, Poseidon2Pure};
+use super::{
+    Poseidon2Goldilocks12, Poseidon2Goldilocks8, CAPACITY, DIGEST_LENGTH, NUM_EXTERNAL_ROUNDS,
+};
+use crate::chip::arithmetic::expression::ArithmeticExpression;
+use crate::chip::memory::time::Time;
+use crate::chip::memory::value::MemoryValue;
+use crate::chip::register::array::ArrayRegister;
+use crate::chip::register::bit::BitRegister;
+use crate::chip::register::element::ElementRegister;
+use crate::chip::register::Register;
+use crate::machine::builder::Builder;
+use crate::math::prelude::*;
+
+/// Poseidon2 AIR implementation.
+///
+/// A permutation takes a single row. The S-box `x^7` is computed with two registers of degree 3,
+/// `x^3` and `x^7 = (x^3)^2 * x`, and the state is committed after every internal round so that
+/// the constraints of the next rounds only refer to the committed registers.
+pub trait Poseidon2Air<B: Builder<Field = GoldilocksField>, const WIDTH: usize>:
+    Poseidon2Pure<WIDTH>
+{
+    /// Computes the permutation of `input` in the current row.
+    fn permutation(
+        builder: &mut B,
+        input: &[ArithmeticExpression<GoldilocksField>],
+    ) -> ArrayRegister<ElementRegister> {
+        assert_eq!(input.len(), WIDTH);
+        let (first_rounds, last_rounds) =
+            Self::EXTERNAL_ROUND_CONSTANTS.split_at(NUM_EXTERNAL_ROUNDS / 2);
+
+        let mut state = external_linear_layer::<GoldilocksField, _>(input);
+        for round_constants in first_rounds {
+            state = Self::external_round(builder, &state, round_constants);
+        }
+        for round_constant in Self::INTERNAL_ROUND_CONSTANTS {
+            let x = Self::sbox(
+                builder,
+                state[0].clone() + GoldilocksField::from_canonical_u64(round_constant),
+            );
+            state[0] = x.expr();
+            let next_state = builder.alloc_array::<ElementRegister>(WIDTH);
+            let layer =
+                internal_linear_layer::<GoldilocksField, _>(&state, &Self::INTERNAL_DIAGONAL);
+            for (register, expression) in next_state.iter().zip(layer) {
+                builder.set_to_expression(&register, expression);
+            }
+            state = next_state.iter().map(|register| register.expr()).collect();
+        }
+        for round_constants in last_rounds {
+            state = Self::external_round(builder, &state, round_constants);
+        }
+
+        let output = builder.alloc_array::<ElementRegister>(WIDTH);
+        for (register, expression) in output.iter().zip(state) {
+            builder.set_to_expression(&register, expression);
+        }
+        output
+    }
+
+    /// Adds the round constants and applies the S-box to the whole state, followed by the
+    /// external linear layer.
+    fn external_round(
+        builder: &mut B,
+        state: &[ArithmeticExpression<GoldilocksField>],
+        round_constants: &[u64],
+    ) -> Vec<ArithmeticExpression<GoldilocksField>> {
+        let state = state
+            .iter()
+            .zip(round_constants.iter())
+            .map(|(x, &c)| {
+                Self::sbox(builder, x.clone() + GoldilocksField::from_canonical_u64(c)).expr()
+            })
+            .collect::<Vec<_>>();
+        external_linear_layer::<GoldilocksField, _>(&state)
+    }
+
+    fn sbox(builder: &mut B, x: ArithmeticExpression<GoldilocksField>) -> ElementRegister {
+        let x_3 = builder.expression::<ElementRegister>(x.clone() * x.clone() * x.clone());
+        builder.expression(x_3.expr() * x_3.expr() * x)
+    }
+
+    /// Hashes messages with the sponge construction and returns their digests at the indices
+    /// `digest_indices`.
+    ///
+    /// Each of `chunks` is a chunk of `Self::RATE` elements absorbed by one permutation, in the
+    /// row of the same index. The chunks of a message are consecutive and the last one has its
+    /// bit in `end_bits` set, after which the capacity is reset to zero for the next message. The
+    /// digest indices are the indices of the last chunks of the messages, see
+    /// [`Poseidon2Pure::hash_no_pad`].
+    fn sponge(
+        builder: &mut B,
+        chunks: &[ArrayRegister<ElementRegister>],
+        end_bits: &ArrayRegister<BitRegister>,
+        digest_indices: &ArrayRegister<ElementRegister>,
+    ) -> Vec<ArrayRegister<ElementRegister>> {
+        let num_real_permutations = chunks.len();
+        assert_eq!(end_bits.len(), num_real_permutations);
+        debug!("AIR degree before padding: {}", num_real_permutations);
+        let degree_log = log2_ceil(num_real_permutations);
+        assert!(degree_log < 31, "AIR degree is too large");
+        debug!("AIR degree after padding: {}", 1 << degree_log);
+        builder.api().require_rows("Poseidon2", 1 << degree_log);
+        // The permutations after the real ones are dummy permutations of zero chunks whose
+        // outputs are ignored.
+        let num_permutations = 1 << degree_log;
+        let clk = builder.clk();
+
+        // Load the chunk and the end bit of the current row.
+        let zero = builder.constant::<ElementRegister>(&GoldilocksField::ZERO);
+        let zero_bit = builder.constant::<BitRegister>(&GoldilocksField::ZERO);
+        let chunk = (0..Self::RATE)
+            .map(|j| {
+                let values = chunks
+                    .iter()
+                    .map(|chunk| {
+                        assert_eq!(chunk.len(), Self::RATE);
+                        chunk.get(j)
+                    })
+                    .collect::<Vec<_>>();
+                load_row_value(builder, &values, zero, num_permutations, clk)
+            })
+            .collect::<Vec<_>>();
+        let end_bit = load_row_value(
+            builder,
+            &end_bits.iter().collect::<Vec<_>>(),
+            zero_bit,
+            num_permutations,
+            clk,
+        );
+
+        // The capacity is carried over from the previous permutation of the same message.
+        let capacity = builder.alloc_array::<ElementRegister>(CAPACITY);
+        for register in capacity.iter() {
+            builder.set_to_expression_first_row(&register, GoldilocksField::ZERO.into());
+        }
+        let input = chunk
+            .iter()
+            .map(|register| register.expr())
+            .chain(capacity.iter().map(|register| register.expr()))
+            .collect::<Vec<_>>();
+        let output = Self::permutation(builder, &input);
+        for (register, output_i) in capacity.iter().zip(output.iter().skip(Self::RATE)) {
+            builder.set_to_expression_transition(
+                &register.next(),
+                output_i.expr() * end_bit.not_expr(),
+            );
+        }
+
+        // Store the digests of the messages and free them at the digest indices.
+        let digests = (0..digest_indices.len())
+            .map(|_| builder.alloc_array_public::<ElementRegister>(DIGEST_LENGTH))
+            .collect::<Vec<_>>();
+        let digest_ptr = builder.uninit_slice();
+        for (index, digest) in digest_indices.iter().zip(digests.iter()) {
+            for (j, element) in digest.iter().enumerate() {
+                builder.free(&digest_ptr.get(j), element, &Time::from_element(index));
+            }
+        }
+        let flag = builder.expression(end_bit.expr());
+        for (j, element) in output.iter().take(DIGEST_LENGTH).enumerate() {
+            builder.store(
+                &digest_ptr.get(j),
+                element,
+                &Time::from_element(clk),
+                Some(flag),
+                None,
+                None,
+            );
+        }
+
+        digests
+    }
+}
+
+impl<B: Builder<Field = GoldilocksField>> Poseidon2Air<B, 8> for Poseidon2Goldilocks8 {}
+
+impl<B: Builder<Field = GoldilocksField>> Poseidon2Air<B, 12> for Poseidon2Goldilocks12 {}
+
+/// Stores the values of the rows in a slice, padded with `dummy` values for the dummy rows, and
+/// loads the value of the current row.
+pub(crate) fn load_row_value<B: Builder, V: MemoryValue>(
+    builder: &mut B,
+    values: &[V],
+    dummy: V,
+    num_rows: usize,
+    clk: ElementRegister,
+) -> V {
+    let slice = builder.uninit_slice();
+    for (i, value) in values.iter().enumerate() {
+        builder.store(&slice.get(i), *value, &Time::zero(), None, None, None);
+    }
+    for i in values.len()..num_rows {
+        builder.store(&slice.get(i), dummy, &Time::zero(), None, None, None);
+    }
+    builder.load(&slice.get_at(clk), &Time::zero(), None, None)
+}
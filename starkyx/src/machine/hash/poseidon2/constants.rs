@@ -0,0 +1,286 @@
+//! Round constants and internal matrices of the Goldilocks instances.
+//!
+//! The round constants are sampled with the Grain LFSR of the Poseidon paper, initialized with
+//! the parameters of each instance: a prime field with 64-bit elements, the `x^7` S-box, the width,
+//! 8 external rounds and 22 internal rounds. The stream yields one row of `WIDTH` constants per
+//! round, of which the internal rounds only use the first element.
+//!
+//! The internal matrices are `1 + diag(INTERNAL_DIAGONAL_*)`, where `1` is the all-ones matrix.
+//! The minimal polynomials of their powers up to `2 * WIDTH` are irreducible, so that the internal
+//! rounds have no invariant subspaces.
+
+use super::{NUM_EXTERNAL_ROUNDS, NUM_INTERNAL_ROUNDS};
+
+pub(crate) const EXTERNAL_ROUND_CONSTANTS_8: [[u64; 8]; NUM_EXTERNAL_ROUNDS] = [
+    [
+        0xdd5743e7f2a5a5d9,
+        0xcb3a864e58ada44b,
+        0xffa2449ed32f8cdc,
+        0x42025f65d6bd13ee,
+        0x7889175e25506323,
+        0x34b98bb03d24b737,
+        0xbdcc535ecc4faa2a,
+        0x5b20ad869fc0d033,
+    ],
+    [
+        0xf1dda5b9259dfcb4,
+        0x27515210be112d59,
+        0x4227d1718c766c3f,
+        0x26d333161a5bd794,
+        0x49b938957bf4b026,
+        0x4a56b5938b213669,
+        0x1120426b48c8353d,
+        0x6b323c3f10a56cad,
+    ],
+    [
+        0xce57d6245ddca6b2,
+        0xb1fc8d402bba1eb1,
+        0xb5c5096ca959bd04,
+        0x6db55cd306d31f7f,
+        0xc49d293a81cb9641,
+        0x1ce55a4fe979719f,
+        0xa92e60a9d178a4d1,
+        0x002cc64973bcfd8c,
+    ],
+    [
+        0xcea721cce82fb11b,
+        0xe5b55eb8098ece81,
+        0x4e30525c6f1ddd66,
+        0x43c6702827070987,
+        0xaca68430a7b5762a,
+        0x3674238634df9c93,
+        0x88cee1c825e33433,
+        0xde99ae8d74b57176,
+    ],
+    [
+        0xfb1d6bf0ca43221b,
+        0x97b0a1b01d6a2955,
+        0x08c60bd622952b30,
+        0x43f2be0f9e24147c,
+        0xfa7268b7d3730f5d,
+        0x43a6c419a23983bb,
+        0xcd77c1f7b29b113c,
+        0xcfa43c9db8eec29f,
+    ],
+    [
+        0xcaaa95a6c7365dec,
+        0x0a91193f798f3be0,
+        0x1104497652735dc6,
+        0x35aecb93663b515e,
+        0x8dbc9916065aa858,
+        0xada8f7a0266579ed,
+        0x524dee7bec1ea789,
+        0xa93aee9dd5af9521,
+    ],
+    [
+        0x9d1f1b54750d707e,
+        0x7c9feab87096d5dc,
+        0xa2e1fb19f9d4261b,
+        0xb714deb448de6346,
+        0x225d1f0d011c5403,
+        0x1549b7f1d28cedc0,
+        0xaef3e46f97d43942,
+        0x6dfc7ffe0b38bf08,
+    ],
+    [
+        0x7de853fdc542b663,
+        0xa68ecc96610657b2,
+        0xe88bb5428af289b1,
+        0xd7cfa1504c5569f5,
+        0x78a9aad0d642d30a,
+        0xd68315f2353dce52,
+        0x46e56300f86fcfd5,
+        0x323d95332b145fd6,
+    ],
+];
+
+pub(crate) const INTERNAL_ROUND_CONSTANTS_8: [u64; NUM_INTERNAL_ROUNDS] = [
+    0x488897d85ff51f56,
+    0x56ccb62574aaa918,
+    0x14a0c2e1d45f03cd,
+    0xfdb25aef2c5bae3b,
+    0xb3cb23eced349ae4,
+    0xceb0735bf00b2c5f,
+    0xb1f6b8eee9adb940,
+    0x85ffc27171439d9d,
+    0x46fa6a6450dd4735,
+    0xcc535945b7dbf0f7,
+    0xe40cd4f6c5609a27,
+    0x287db8630da89c8b,
+    0xe839452eb4b8a5e1,
+    0x8b7b05225c4e7dad,
+    0xc17f55037cf00de9,
+    0xe01dd653daf15809,
+    0x49d45382e0f21d4a,
+    0x42cca18ebeb265c8,
+    0xed12a2276dfa1553,
+    0x89e779214737c0b7,
+    0x854aee2dc1924137,
+    0x49884bf25f4ef15d,
+];
+
+pub(crate) const INTERNAL_DIAGONAL_8: [u64; 8] = [
+    0xa98811a1fed4e3a5,
+    0x1cc48b54f377e2a0,
+    0xe40cd4f6c5609a26,
+    0x11de79ebca97a4a3,
+    0x9177c73d8b7e929c,
+    0x2a6fe8085797e791,
+    0x3de6e93329f8d5ad,
+    0x3f7af9125da962fe,
+];
+
+pub(crate) const EXTERNAL_ROUND_CONSTANTS_12: [[u64; 12]; NUM_EXTERNAL_ROUNDS] = [
+    [
+        0x13dcf33aba214f46,
+        0x30b3b654a1da6d83,
+        0x1fc634ada6159b56,
+        0x937459964dc03466,
+        0xedd2ef2ca7949924,
+        0xede9affde0e22f68,
+        0x8515b9d6bac9282d,
+        0x6b5c07b4e9e900d8,
+        0x1ec66368838c8a08,
+        0x9042367d80d1fbab,
+        0x400283564a3c3799,
+        0x4a00be0466bca75e,
+    ],
+    [
+        0x7913beee58e3817f,
+        0xf545e88532237d90,
+        0x22f8cb8736042005,
+        0x6f04990e247a2623,
+        0xfe22e87ba37c38cd,
+        0xd20e32c85ffe2815,
+        0x117227674048fe73,
+        0x4e9fb7ea98a6b145,
+        0xe0866c232b8af08b,
+        0x00bbc77916884964,
+        0x7031c0fb990d7116,
+        0x240a9e87cf35108f,
+    ],
+    [
+        0x2e6363a5a12244b3,
+        0x5e1c3787d1b5011c,
+        0x4132660e2a196e8b,
+        0x3a013b648d3d4327,
+        0xf79839f49888ea43,
+        0xfe85658ebafe1439,
+        0xb6889825a14240bd,
+        0x578453605541382b,
+        0x4508cda8f6b63ce9,
+        0x9c3ef35848684c91,
+        0x0812bde23c87178c,
+        0xfe49638f7f722c14,
+    ],
+    [
+        0x8e3f688ce885cbf5,
+        0xb8e110acf746a87d,
+        0xb4b2e8973a6dabef,
+        0x9e714c5da3d462ec,
+        0x6438f9033d3d0c15,
+        0x24312f7cf1a27199,
+        0x23f843bb47acbf71,
+        0x9183f11a34be9f01,
+        0x839062fbb9d45dbf,
+        0x24b56e7e6c2e43fa,
+        0xe1683da61c962a72,
+        0xa95c63971a19bfa7,
+    ],
+    [
+        0x9271d450fc9b4117,
+        0xcffeea06b6e3aac1,
+        0xfa4a44c748d1cd8e,
+        0xe64db01ba569b469,
+        0xd31005160e4045fe,
+        0x39e0fa013e025f79,
+        0xe243be574196a956,
+        0x205b2a681e3d2642,
+        0x79cae5ad93486bab,
+        0xfdf567844e32c295,
+        0x331679589bfb7189,
+        0xaf06ee32297b89c2,
+    ],
+    [
+        0xa6bcae311e498491,
+        0x9d16f52c96ac8b3e,
+        0x48a674b59393fa35,
+        0x0f9e65da3fde3796,
+        0x1e098310fc84578c,
+        0x559ae5fab1ae8dad,
+        0x56bd4d624078881d,
+        0xfd8bbbf8fbe817b5,
+        0x82d30695c44df534,
+        0x3ec0a97bc41127c5,
+        0x1eb8b64adaa22078,
+        0x82c45e418d60c983,
+    ],
+    [
+        0xb092280f484d55bf,
+        0xcd317c9537697939,
+        0xd3be2e352feb79f3,
+        0xca6d866539a390e5,
+        0xb5efb1a494e55ee6,
+        0xfa9013ac89756e9e,
+        0xaeb88efd1e981242,
+        0x13ee477cdab6e0dc,
+        0xce7df902c40da2d3,
+        0xf3fbaf0d4e6f5f34,
+        0xf96354ada6785f38,
+        0x13b5692812406886,
+    ],
+    [
+        0xf03cae030a0f4418,
+        0x7d3172887aa98e1a,
+        0x8a2c2644f2faf7b9,
+        0x80d721abee696d00,
+        0x27c8b903a4d68267,
+        0xaf0b7b12f90291b8,
+        0x00acd08cfdff3817,
+        0x4659ee496c634328,
+        0xf5b25c10730dbff1,
+        0xdde3a153297329c2,
+        0x50c0b70d6910a44b,
+        0x23c7426af725a6a0,
+    ],
+];
+
+pub(crate) const INTERNAL_ROUND_CONSTANTS_12: [u64; NUM_INTERNAL_ROUNDS] = [
+    0x4adf842aa75d4316,
+    0x3f36b9fe72ad4e5f,
+    0x9717f025e7daf6a5,
+    0xac4bb7c627cf7c13,
+    0x047d766678f13875,
+    0xbfce13201f3f7e6b,
+    0x70971fc4e6f85305,
+    0xe2a6e06e61fcec9c,
+    0xdf58134c134491c2,
+    0x1c4bd1e816050a7e,
+    0xf8a6cd02e92cdb0b,
+    0x4c0f5fc6c0dda3d1,
+    0x0a4a11d794be40a2,
+    0x6d3fbd3b4a9f1de6,
+    0x0d0c371c5b35b850,
+    0x2cff3000be1fcd0a,
+    0xd5ef60d6f76a42fa,
+    0x942069f5d6eece7e,
+    0x8b62a5551e9a9797,
+    0x4f88cdcdfb791921,
+    0xab21b42e0f642307,
+    0x587fa39990b62800,
+];
+
+pub(crate) const INTERNAL_DIAGONAL_12: [u64; 12] = [
+    0xc3b6c08e23ba9300,
+    0xd84b5de94a324fb6,
+    0x0d0c371c5b35b84f,
+    0x7964f570e7188037,
+    0x5daf18bbd996604b,
+    0x6743bc47b9595257,
+    0x5528b9362c59bb70,
+    0xac45e25b7127b68b,
+    0xa2077d7dfbb606b5,
+    0xf3faac6faee378ae,
+    0x0c6388b51545e883,
+    0xd27dbb6944917b60,
+];
@@ -0,0 +1,35 @@
+//! The Poseidon2 permutation over the Goldilocks field.
+//!
+//! Poseidon2 is an arithmetization-oriented permutation, so the AIR operates directly on field
+//! elements and needs no bit decomposition. A permutation applies the external linear layer,
+//! half of the external (full) rounds, the internal (partial) rounds, and the remaining external
+//! rounds. External rounds apply the S-box `x^7` to the whole state, followed by the external
+//! linear layer, and internal rounds apply it to the first element only, followed by the cheaper
+//! internal linear layer.
+//!
+//! The instances of widths 8 and 12 are used as sponges with a capacity of 4 elements, in the
+//! overwrite mode of the plonky2 hashes.
+
+use serde::{Deserialize, Serialize};
+
+pub mod air;
+pub mod builder;
+pub mod constants;
+pub mod pure;
+
+/// The Poseidon2 instance of width 8, with a rate of 4 elements.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct Poseidon2Goldilocks8;
+
+/// The Poseidon2 instance of width 12, with a rate of 8 elements.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct Poseidon2Goldilocks12;
+
+/// The number of external rounds, half of which come before the internal rounds.
+pub const NUM_EXTERNAL_ROUNDS: usize = 8;
+pub const NUM_INTERNAL_ROUNDS: usize = 22;
+
+/// The number of state elements which are not overwritten by the input of the sponge.
+pub const CAPACITY: usize = 4;
+/// The number of elements of a digest, which are the first elements of the final state.
+pub const DIGEST_LENGTH: usize = 4;
@@ -0,0 +1,256 @@
+use core::fmt::Debug;
+use core::ops::{Add, Mul};
+
+use plonky2::field::goldilocks_field::GoldilocksField;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+
+use super::constants::{
+    EXTERNAL_ROUND_CONSTANTS_12, EXTERNAL_ROUND_CONSTANTS_8, INTERNAL_DIAGONAL_12,
+    INTERNAL_DIAGONAL_8, INTERNAL_ROUND_CONSTANTS_12, INTERNAL_ROUND_CONSTANTS_8,
+};
+use super::{
+    Poseidon2Goldilocks12, Poseidon2Goldilocks8, CAPACITY, DIGEST_LENGTH, NUM_EXTERNAL_ROUNDS,
+    NUM_INTERNAL_ROUNDS,
+};
+use crate::math::prelude::*;
+
+/// The 4x4 MDS matrix from which the external linear layer is built.
+const M4: [[u64; 4]; 4] = [[5, 7, 1, 3], [4, 6, 1, 1], [1, 3, 5, 7], [1, 1, 4, 6]];
+
+/// Pure Poseidon2 implementation.
+///
+/// An interface for the Poseidon2 permutation and sponge as Rust functions operating on field
+/// elements.
+pub trait Poseidon2Pure<const WIDTH: usize>:
+    Debug + Clone + 'static + Serialize + DeserializeOwned + Send + Sync
+{
+    const EXTERNAL_ROUND_CONSTANTS: [[u64; WIDTH]; NUM_EXTERNAL_ROUNDS];
+    const INTERNAL_ROUND_CONSTANTS: [u64; NUM_INTERNAL_ROUNDS];
+    /// The diagonal `d` of the internal matrix `1 + diag(d)`.
+    const INTERNAL_DIAGONAL: [u64; WIDTH];
+    /// The number of elements absorbed by each permutation of the sponge.
+    const RATE: usize = WIDTH - CAPACITY;
+
+    fn permute(state: [GoldilocksField; WIDTH]) -> [GoldilocksField; WIDTH] {
+        let (first_rounds, last_rounds) =
+            Self::EXTERNAL_ROUND_CONSTANTS.split_at(NUM_EXTERNAL_ROUNDS / 2);
+
+        let mut state = external_linear_layer::<GoldilocksField, _>(&state);
+        for round_constants in first_rounds {
+            state = external_round(&state, round_constants);
+        }
+        for round_constant in Self::INTERNAL_ROUND_CONSTANTS {
+            state[0] = sbox(state[0] + GoldilocksField::from_canonical_u64(round_constant));
+            state = internal_linear_layer::<GoldilocksField, _>(&state, &Self::INTERNAL_DIAGONAL);
+        }
+        for round_constants in last_rounds {
+            state = external_round(&state, round_constants);
+        }
+
+        state.try_into().unwrap()
+    }
+
+    /// Hashes the input with the sponge construction, without padding.
+    ///
+    /// Each chunk of `Self::RATE` elements overwrites the beginning of the state before the
+    /// state is permuted, and the digest is the beginning of the final state. The length of the
+    /// input must be a non-zero multiple of `Self::RATE`.
+    fn hash_no_pad(input: &[GoldilocksField]) -> [GoldilocksField; DIGEST_LENGTH] {
+        assert!(
+            !input.is_empty() && input.len() % Self::RATE == 0,
+            "The input length {} is not a non-zero multiple of the rate {}",
+            input.len(),
+            Self::RATE
+        );
+        let mut state = [GoldilocksField::ZERO; WIDTH];
+        for chunk in input.chunks_exact(Self::RATE) {
+            state[..Self::RATE].copy_from_slice(chunk);
+            state = Self::permute(state);
+        }
+        state[..DIGEST_LENGTH].try_into().unwrap()
+    }
+
+    /// Hashes two digests into one, as in the nodes of a Merkle tree.
+    fn two_to_one(
+        left: [GoldilocksField; DIGEST_LENGTH],
+        right: [GoldilocksField; DIGEST_LENGTH],
+    ) -> [GoldilocksField; DIGEST_LENGTH] {
+        let input = left.into_iter().chain(right).collect::<Vec<_>>();
+        Self::hash_no_pad(&input)
+    }
+}
+
+impl Poseidon2Pure<8> for Poseidon2Goldilocks8 {
+    const EXTERNAL_ROUND_CONSTANTS: [[u64; 8]; NUM_EXTERNAL_ROUNDS] = EXTERNAL_ROUND_CONSTANTS_8;
+    const INTERNAL_ROUND_CONSTANTS: [u64; NUM_INTERNAL_ROUNDS] = INTERNAL_ROUND_CONSTANTS_8;
+    const INTERNAL_DIAGONAL: [u64; 8] = INTERNAL_DIAGONAL_8;
+}
+
+impl Poseidon2Pure<12> for Poseidon2Goldilocks12 {
+    const EXTERNAL_ROUND_CONSTANTS: [[u64; 12]; NUM_EXTERNAL_ROUNDS] = EXTERNAL_ROUND_CONSTANTS_12;
+    const INTERNAL_ROUND_CONSTANTS: [u64; NUM_INTERNAL_ROUNDS] = INTERNAL_ROUND_CONSTANTS_12;
+    const INTERNAL_DIAGONAL: [u64; 12] = INTERNAL_DIAGONAL_12;
+}
+
+fn sbox(x: GoldilocksField) -> GoldilocksField {
+    let x3 = x * x * x;
+    x3 * x3 * x
+}
+
+fn external_round(state: &[GoldilocksField], round_constants: &[u64]) -> Vec<GoldilocksField> {
+    let state = state
+        .iter()
+        .zip(round_constants.iter())
+        .map(|(&x, &c)| sbox(x + GoldilocksField::from_canonical_u64(c)))
+        .collect::<Vec<_>>();
+    external_linear_layer::<GoldilocksField, _>(&state)
+}
+
+/// Multiplies `x` by a small constant, skipping the multiplication by one.
+fn scale<F: Field, T: Clone + Mul<F, Output = T>>(x: &T, constant: u64) -> T {
+    match constant {
+        1 => x.clone(),
+        _ => x.clone() * F::from_canonical_u64(constant),
+    }
+}
+
+fn sum<T: Clone + Add<Output = T>>(values: impl IntoIterator<Item = T>) -> T {
+    values.into_iter().reduce(|acc, x| acc + x).unwrap()
+}
+
+/// The external linear layer, given by the matrix whose `4x4` blocks are `2 * M4` on the
+/// diagonal and `M4` elsewhere.
+///
+/// It is computed by applying `M4` to each chunk of 4 elements and adding to each element the
+/// sum of the elements of the same index in all chunks. The layer is generic over the values, so
+/// that it applies both to field elements and to arithmetic expressions.
+pub fn external_linear_layer<F: Field, T: Clone + Add<Output = T> + Mul<F, Output = T>>(
+    state: &[T],
+) -> Vec<T> {
+    assert_eq!(state.len() % 4, 0, "The width must be a multiple of 4");
+    let chunks = state
+        .chunks_exact(4)
+        .flat_map(|chunk| {
+            M4.iter().map(|row| {
+                sum(row
+                    .iter()
+                    .zip(chunk.iter())
+                    .map(|(&m, x)| scale::<F, T>(x, m)))
+            })
+        })
+        .collect::<Vec<_>>();
+    let sums = (0..4)
+        .map(|i| sum(chunks.iter().skip(i).step_by(4).cloned()))
+        .collect::<Vec<_>>();
+    chunks
+        .iter()
+        .enumerate()
+        .map(|(k, y)| y.clone() + sums[k % 4].clone())
+        .collect()
+}
+
+/// The internal linear layer, given by the matrix `1 + diag(diagonal)`, where `1` is the
+/// all-ones matrix.
+pub fn internal_linear_layer<F: Field, T: Clone + Add<Output = T> + Mul<F, Output = T>>(
+    state: &[T],
+    diagonal: &[u64],
+) -> Vec<T> {
+    assert_eq!(state.len(), diagonal.len());
+    let state_sum = sum(state.iter().cloned());
+    state
+        .iter()
+        .zip(diagonal.iter())
+        .map(|(x, &d)| scale::<F, T>(x, d) + state_sum.clone())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn permutation_of_range<P: Poseidon2Pure<WIDTH>, const WIDTH: usize>() -> [u64; WIDTH] {
+        let state = core::array::from_fn(GoldilocksField::from_canonical_usize);
+        P::permute(state).map(|x| x.as_canonical_u64())
+    }
+
+    #[test]
+    fn test_poseidon2_permutation() {
+        assert_eq!(
+            permutation_of_range::<Poseidon2Goldilocks8, 8>(),
+            [
+                0xf019d83b44a1b6bc,
+                0x08cd8462266e2756,
+                0x76b7c3f722d1c69c,
+                0x2eef5c767f054422,
+                0x5fa7e1e57333be8a,
+                0x1ef3495c03193c77,
+                0x53a7d68af3ec2aa2,
+                0xa425b47d55febd5d,
+            ]
+        );
+        assert_eq!(
+            permutation_of_range::<Poseidon2Goldilocks12, 12>(),
+            [
+                0x7dad28df76f00a0c,
+                0x7eeee15a719b2c83,
+                0x9cbeeaab2a21779f,
+                0x55d01e3390da190f,
+                0xc1b07fdd2b1f52d5,
+                0x0eddb45a904bb791,
+                0x5d4a5a83ec7044eb,
+                0xea94a554cd4cb8da,
+                0x05b264ec4aed4aa6,
+                0x3f142c01fb5938d9,
+                0x5b7ce4cb9689b006,
+                0x5a899329ab43d2bb,
+            ]
+        );
+    }
+
+    #[test]
+    fn test_linear_layers() {
+        type F = GoldilocksField;
+        let width = 12;
+        let state = (0..width).map(|_| F::rand()).collect::<Vec<_>>();
+
+        // Compare with the products by the explicit matrices.
+        let external = external_linear_layer::<F, _>(&state);
+        let internal = internal_linear_layer::<F, _>(&state, &INTERNAL_DIAGONAL_12);
+        for i in 0..width {
+            let mut expected_external = F::ZERO;
+            let mut expected_internal = F::ZERO;
+            for (j, &x) in state.iter().enumerate() {
+                let block_factor = if i / 4 == j / 4 { 2 } else { 1 };
+                let m_ij = M4[i % 4][j % 4] * block_factor;
+                expected_external += F::from_canonical_u64(m_ij) * x;
+                let d_ij = if i == j { INTERNAL_DIAGONAL_12[i] } else { 0 };
+                expected_internal += (F::ONE + F::from_canonical_u64(d_ij)) * x;
+            }
+            assert_eq!(external[i], expected_external);
+            assert_eq!(internal[i], expected_internal);
+        }
+    }
+
+    #[test]
+    fn test_poseidon2_sponge() {
+        type F = GoldilocksField;
+        let left = [1, 2, 3, 4].map(F::from_canonical_u64);
+        let right = [5, 6, 7, 8].map(F::from_canonical_u64);
+
+        // With a rate of 4, the two digests are absorbed by two permutations.
+        let mut state = [F::ZERO; 8];
+        state[..4].copy_from_slice(&left);
+        state = Poseidon2Goldilocks8::permute(state);
+        state[..4].copy_from_slice(&right);
+        state = Poseidon2Goldilocks8::permute(state);
+        assert_eq!(Poseidon2Goldilocks8::two_to_one(left, right), state[..4]);
+
+        // With a rate of 8, they are absorbed by a single permutation.
+        let mut state = [F::ZERO; 12];
+        state[..4].copy_from_slice(&left);
+        state[4..8].copy_from_slice(&right);
+        state = Poseidon2Goldilocks12::permute(state);
+        assert_eq!(Poseidon2Goldilocks12::two_to_one(left, right), state[..4]);
+    }
+}
@@ -0,0 +1,476 @@
+use log::debug;
+use plonky2::util::log2_ceil;
+
+use super::register::RIPEMD160DigestRegister;
+use super::{
+    CYCLE_LENGTH, INITIAL_HASH, K_LEFT, K_RIGHT, MSG_ARRAY_SIZE, NUM_ROTATION_BITS, NUM_ROUNDS,
+    RIPEMD160, R_LEFT, R_RIGHT, STATE_SIZE, S_LEFT, S_RIGHT,
+};
+use crate::chip::arithmetic::expression::ArithmeticExpression;
+use crate::chip::memory::time::Time;
+use crate::chip::memory::value::MemoryValue;
+use crate::chip::register::array::ArrayRegister;
+use crate::chip::register::bit::BitRegister;
+use crate::chip::register::element::ElementRegister;
+use crate::chip::register::Register;
+use crate::chip::uint::operations::instruction::UintInstructions;
+use crate::chip::uint::register::U32Register;
+use crate::chip::uint::util::{u32_from_le_field_bytes, u32_to_le_field_bytes};
+use crate::chip::AirParameters;
+use crate::machine::builder::Builder;
+use crate::machine::bytes::builder::BytesBuilder;
+use crate::machine::hash::{HashDigest, HashIntConversion, HashInteger};
+use crate::math::prelude::*;
+
+impl<B: Builder> HashInteger<B> for RIPEMD160 {
+    type Value = <U32Register as Register>::Value<B::Field>;
+    type IntRegister = U32Register;
+}
+
+impl<B: Builder> HashIntConversion<B> for RIPEMD160 {
+    fn int_to_field_value(int: Self::Integer) -> Self::Value {
+        u32_to_le_field_bytes(int)
+    }
+
+    fn field_value_to_int(value: &Self::Value) -> Self::Integer {
+        u32_from_le_field_bytes(value)
+    }
+}
+
+impl<B: Builder> HashDigest<B> for RIPEMD160 {
+    type DigestRegister = RIPEMD160DigestRegister;
+}
+
+/// RIPEMD-160 AIR implementation.
+///
+/// Each compression takes a cycle of `CYCLE_LENGTH` rows, one for each step of the two lines.
+/// Since the steps differ from one row to the next, the values they depend on are selected in
+/// each row: the boolean functions and the round constants by bits tracking the round, which are
+/// shifted every 16 rows, the message words by indices read from memory, and the rotations by
+/// the bits of their amounts, also read from memory, which choose among rotations by 1, 2, 4 and
+/// 8 bits.
+pub trait RIPEMD160Air<B: Builder>: HashIntConversion<B> + HashDigest<B> {
+    /// Computes the compressions of `padded_chunks` and returns the states at the indices
+    /// `digest_indices`.
+    ///
+    /// The chunks of a message are consecutive and the last one has its bit in `end_bits` set,
+    /// after which the state is reset to the initial hash. The states after the chunks whose
+    /// `digest_bits` are set are the returned digests, in order, and the digest indices are the
+    /// indices of these chunks.
+    ///
+    /// The trace has `CYCLE_LENGTH * padded_chunks.len()` rows rounded up to a power of two, which
+    /// other chips of the same trace must agree with, such as the SHA-256 chip for `HASH160`.
+    fn ripemd160(
+        builder: &mut B,
+        padded_chunks: &[ArrayRegister<Self::IntRegister>],
+        end_bits: &ArrayRegister<BitRegister>,
+        digest_bits: &ArrayRegister<BitRegister>,
+        digest_indices: &ArrayRegister<ElementRegister>,
+    ) -> Vec<Self::DigestRegister>;
+
+    /// A step of a line, returning the next working variables.
+    fn ripemd160_step(
+        builder: &mut B,
+        vars: &[Self::IntRegister],
+        f: Self::IntRegister,
+        x: Self::IntRegister,
+        k: Self::IntRegister,
+        rotation_bits: &[BitRegister],
+    ) -> Vec<Self::IntRegister>;
+
+    /// The boolean functions of the five rounds applied to `x, y, z`, in the order of the left
+    /// line.
+    fn ripemd160_functions(
+        builder: &mut B,
+        x: &Self::IntRegister,
+        y: &Self::IntRegister,
+        z: &Self::IntRegister,
+    ) -> [ArithmeticExpression<B::Field>; NUM_ROUNDS];
+
+    /// Rotates `value` to the left by the amount whose bits are `rotation_bits`.
+    fn ripemd160_rotate_left(
+        builder: &mut B,
+        value: Self::IntRegister,
+        rotation_bits: &[BitRegister],
+    ) -> Self::IntRegister;
+}
+
+impl<L: AirParameters> RIPEMD160Air<BytesBuilder<L>> for RIPEMD160
+where
+    L::Instruction: UintInstructions,
+{
+    fn ripemd160(
+        builder: &mut BytesBuilder<L>,
+        padded_chunks: &[ArrayRegister<Self::IntRegister>],
+        end_bits: &ArrayRegister<BitRegister>,
+        digest_bits: &ArrayRegister<BitRegister>,
+        digest_indices: &ArrayRegister<ElementRegister>,
+    ) -> Vec<Self::DigestRegister> {
+        let num_real_compressions = padded_chunks.len();
+        assert_eq!(end_bits.len(), num_real_compressions);
+        assert_eq!(digest_bits.len(), num_real_compressions);
+        debug!(
+            "AIR degree before padding: {}",
+            num_real_compressions * CYCLE_LENGTH
+        );
+        let degree_log = log2_ceil(num_real_compressions * CYCLE_LENGTH);
+        assert!(degree_log < 31, "AIR degree is too large");
+        debug!("AIR degree after padding: {}", 1 << degree_log);
+        builder.api().require_rows("RIPEMD-160", 1 << degree_log);
+        // The compressions after the real ones are dummy compressions of zero chunks whose
+        // outputs are ignored. The number of rows is a power of two and not a multiple of
+        // `CYCLE_LENGTH`, so the last dummy compression is cut short.
+        let num_rows = 1 << degree_log;
+        let num_compressions = num_rows / CYCLE_LENGTH + 1;
+        let length_last_compression = num_rows % CYCLE_LENGTH;
+
+        // The round bits, of which only the bit of the current round is set.
+        let cycle_16 = builder.cycle(4);
+        let round_bits = builder.alloc_array::<BitRegister>(NUM_ROUNDS);
+        for (round, bit) in round_bits.iter().enumerate() {
+            let previous = round_bits.get((round + NUM_ROUNDS - 1) % NUM_ROUNDS);
+            builder.set_to_expression_first_row(
+                &bit,
+                L::Field::from_canonical_usize((round == 0) as usize).into(),
+            );
+            builder.set_to_expression_transition(
+                &bit.next(),
+                cycle_16.end_bit.expr() * previous.expr()
+                    + cycle_16.end_bit.not_expr() * bit.expr(),
+            );
+        }
+        let cycle_end_bit = builder.expression::<BitRegister>(
+            cycle_16.end_bit.expr() * round_bits.get(NUM_ROUNDS - 1).expr(),
+        );
+        let process_id = builder.process_id(CYCLE_LENGTH, cycle_end_bit);
+        let clk = builder.clk();
+        let step = builder.expression::<ElementRegister>(
+            clk.expr() - process_id.expr() * L::Field::from_canonical_usize(CYCLE_LENGTH),
+        );
+
+        // The constants of each step are read once per compression, except for the steps after
+        // the end of the last compression.
+        let all_compressions = builder.constant(&L::Field::from_canonical_usize(num_compressions));
+        let all_but_last = builder.constant(&L::Field::from_canonical_usize(num_compressions - 1));
+        let step_multiplicities = (0..CYCLE_LENGTH)
+            .map(|j| match j < length_last_compression {
+                true => all_compressions,
+                false => all_but_last,
+            })
+            .collect::<Vec<_>>();
+
+        // Load the message indices and the rotation bits of the current step.
+        let load_indices = |builder: &mut BytesBuilder<L>, indices: &[usize]| {
+            let values = indices
+                .iter()
+                .map(|&i| builder.constant(&L::Field::from_canonical_usize(i)))
+                .collect::<Vec<ElementRegister>>();
+            load_value(builder, &values, &step_multiplicities, step)
+        };
+        let r_left = load_indices(builder, &R_LEFT);
+        let r_right = load_indices(builder, &R_RIGHT);
+        let load_rotation_bits = |builder: &mut BytesBuilder<L>, rotations: &[u32]| {
+            (0..NUM_ROTATION_BITS)
+                .map(|k| {
+                    let values = rotations
+                        .iter()
+                        .map(|s| builder.constant(&L::Field::from_canonical_u32((s >> k) & 1)))
+                        .collect::<Vec<BitRegister>>();
+                    load_value(builder, &values, &step_multiplicities, step)
+                })
+                .collect::<Vec<_>>()
+        };
+        let rotation_bits_left = load_rotation_bits(builder, &S_LEFT);
+        let rotation_bits_right = load_rotation_bits(builder, &S_RIGHT);
+
+        // Load the end bit and the digest bit of the current compression, which are read in each
+        // of its rows.
+        let cycle_length = builder.constant(&L::Field::from_canonical_usize(CYCLE_LENGTH));
+        let last_length =
+            builder.constant(&L::Field::from_canonical_usize(length_last_compression));
+        let compression_multiplicities = (0..num_compressions)
+            .map(|c| match c < num_compressions - 1 {
+                true => cycle_length,
+                false => last_length,
+            })
+            .collect::<Vec<_>>();
+        let zero_bit = builder.constant::<BitRegister>(&L::Field::ZERO);
+        let load_bits = |builder: &mut BytesBuilder<L>, bits: &ArrayRegister<BitRegister>| {
+            let values = bits
+                .iter()
+                .chain((num_real_compressions..num_compressions).map(|_| zero_bit))
+                .collect::<Vec<_>>();
+            load_value(builder, &values, &compression_multiplicities, process_id)
+        };
+        let end_bit = load_bits(builder, end_bits);
+        let digest_bit = load_bits(builder, digest_bits);
+
+        // Store the words of the chunks, each read once per line and round, and load the words
+        // of the current step.
+        let zero_word = builder.constant::<U32Register>(&u32_to_le_field_bytes(0));
+        let full_reads = builder.constant(&L::Field::from_canonical_usize(2 * NUM_ROUNDS));
+        let message = builder.uninit_slice::<U32Register>();
+        for c in 0..num_compressions {
+            let num_steps = match c < num_compressions - 1 {
+                true => CYCLE_LENGTH,
+                false => length_last_compression,
+            };
+            for i in 0..MSG_ARRAY_SIZE {
+                let num_reads = R_LEFT[..num_steps]
+                    .iter()
+                    .chain(R_RIGHT[..num_steps].iter())
+                    .filter(|&&r| r == i)
+                    .count();
+                let multiplicity = match num_reads {
+                    0 => continue,
+                    n if n == 2 * NUM_ROUNDS => full_reads,
+                    n => builder.constant(&L::Field::from_canonical_usize(n)),
+                };
+                let word = padded_chunks.get(c).map_or(zero_word, |chunk| {
+                    assert_eq!(chunk.len(), MSG_ARRAY_SIZE);
+                    chunk.get(i)
+                });
+                builder.store(
+                    &message.get(MSG_ARRAY_SIZE * c + i),
+                    word,
+                    &Time::zero(),
+                    Some(multiplicity),
+                    None,
+                    None,
+                );
+            }
+        }
+        let load_word = |builder: &mut BytesBuilder<L>, r: ElementRegister| {
+            let index = builder.expression(
+                process_id.expr() * L::Field::from_canonical_usize(MSG_ARRAY_SIZE) + r.expr(),
+            );
+            builder.load(&message.get_at(index), &Time::zero(), None, None)
+        };
+        let x_left = load_word(builder, r_left);
+        let x_right = load_word(builder, r_right);
+
+        // The working variables of the two lines and the state, set to the initial hash in the
+        // first row.
+        let initial_hash = builder.constant_array::<U32Register>(
+            &INITIAL_HASH.map(<Self as HashIntConversion<BytesBuilder<L>>>::int_to_field_value),
+        );
+        let left = builder.alloc_array::<U32Register>(STATE_SIZE);
+        let right = builder.alloc_array::<U32Register>(STATE_SIZE);
+        let state = builder.alloc_array::<U32Register>(STATE_SIZE);
+        for array in [left, right, state] {
+            for (register, init) in array.iter().zip(initial_hash.iter()) {
+                builder.set_to_expression_first_row(&register, init.expr());
+            }
+        }
+
+        // Select the boolean functions and the round constants of the current round, where the
+        // right line uses the functions in the reverse order.
+        let select = |builder: &mut BytesBuilder<L>, values: &[ArithmeticExpression<L::Field>]| {
+            let expression = round_bits.iter().zip(values.iter()).fold(
+                ArithmeticExpression::from_constant_vec(vec![L::Field::ZERO; 4]),
+                |acc, (bit, value)| acc + bit.expr() * value.clone(),
+            );
+            builder.expression::<U32Register>(expression)
+        };
+        let round_constants = |constants: &[u32]| {
+            constants
+                .iter()
+                .map(|&k| {
+                    ArithmeticExpression::from_constant_vec(u32_to_le_field_bytes(k).to_vec())
+                })
+                .collect::<Vec<_>>()
+        };
+
+        let left_vars = left.iter().collect::<Vec<_>>();
+        let mut functions =
+            Self::ripemd160_functions(builder, &left_vars[1], &left_vars[2], &left_vars[3]);
+        let f_left = select(builder, &functions);
+        let k_left = select(builder, &round_constants(&K_LEFT));
+        let left_next = Self::ripemd160_step(
+            builder,
+            &left_vars,
+            f_left,
+            x_left,
+            k_left,
+            &rotation_bits_left,
+        );
+
+        let right_vars = right.iter().collect::<Vec<_>>();
+        functions =
+            Self::ripemd160_functions(builder, &right_vars[1], &right_vars[2], &right_vars[3]);
+        functions.reverse();
+        let f_right = select(builder, &functions);
+        let k_right = select(builder, &round_constants(&K_RIGHT));
+        let right_next = Self::ripemd160_step(
+            builder,
+            &right_vars,
+            f_right,
+            x_right,
+            k_right,
+            &rotation_bits_right,
+        );
+
+        // The next state, which is correct in the last row of the cycle.
+        let state_next = (0..STATE_SIZE)
+            .map(|i| {
+                let h = state.get((i + 1) % STATE_SIZE);
+                let sum = builder.add(h, left_next[(i + 2) % STATE_SIZE]);
+                builder.add(sum, right_next[(i + 3) % STATE_SIZE])
+            })
+            .collect::<Vec<_>>();
+
+        // At the end of a compression, set the working variables and the state to the next state,
+        // or to the initial hash at the end of a message. Otherwise, the working variables take
+        // the values of the step and the state is unchanged.
+        let state_vars = state.iter().collect::<Vec<_>>();
+        for (vars, vars_next) in [
+            (left, &left_next),
+            (right, &right_next),
+            (state, &state_vars),
+        ] {
+            let bit = cycle_end_bit;
+            for (((var, var_next), h_next), init) in vars
+                .iter()
+                .zip(vars_next.iter())
+                .zip(state_next.iter())
+                .zip(initial_hash.iter())
+            {
+                builder.set_to_expression_transition(
+                    &var.next(),
+                    var_next.expr() * bit.not_expr()
+                        + (h_next.expr() * end_bit.not_expr() + init.expr() * end_bit.expr())
+                            * bit.expr(),
+                );
+            }
+        }
+
+        // Store the next states of the digest compressions and free them at the digest indices.
+        let digests = (0..digest_indices.len())
+            .map(|_| builder.alloc_public::<RIPEMD160DigestRegister>())
+            .collect::<Vec<_>>();
+        let state_ptr = builder.uninit_slice();
+        for (index, digest) in digest_indices.iter().zip(digests.iter()) {
+            for (j, word) in digest.iter().enumerate() {
+                builder.free(&state_ptr.get(j), word, &Time::from_element(index));
+            }
+        }
+        let flag = builder.expression(cycle_end_bit.expr() * digest_bit.expr());
+        for (j, word) in state_next.into_iter().enumerate() {
+            builder.store(
+                &state_ptr.get(j),
+                word,
+                &Time::from_element(process_id),
+                Some(flag),
+                None,
+                None,
+            );
+        }
+
+        digests
+    }
+
+    fn ripemd160_step(
+        builder: &mut BytesBuilder<L>,
+        vars: &[Self::IntRegister],
+        f: Self::IntRegister,
+        x: Self::IntRegister,
+        k: Self::IntRegister,
+        rotation_bits: &[BitRegister],
+    ) -> Vec<Self::IntRegister> {
+        let [a, b, c, d, e]: [Self::IntRegister; STATE_SIZE] = vars.try_into().unwrap();
+        let mut t = builder.add(a, f);
+        t = builder.add(t, x);
+        t = builder.add(t, k);
+        t = Self::ripemd160_rotate_left(builder, t, rotation_bits);
+        t = builder.add(t, e);
+        let c_rotated = builder.rotate_right(c, 32 - 10);
+
+        vec![e, t, b, c_rotated, d]
+    }
+
+    fn ripemd160_functions(
+        builder: &mut BytesBuilder<L>,
+        x: &Self::IntRegister,
+        y: &Self::IntRegister,
+        z: &Self::IntRegister,
+    ) -> [ArithmeticExpression<L::Field>; NUM_ROUNDS] {
+        // The complement of a word is linear in its bytes.
+        let not = |word: Self::IntRegister| {
+            ArithmeticExpression::from_constant_vec(vec![L::Field::from_canonical_u8(u8::MAX); 4])
+                - word.expr()
+        };
+
+        let x_xor_y = builder.xor(x, y);
+        let y_xor_z = builder.xor(y, z);
+
+        // `x ^ y ^ z`.
+        let f_1 = builder.xor(&x_xor_y, z);
+        // `(x & y) | (!x & z) = z ^ (x & (y ^ z))`.
+        let x_and_y_xor_z = builder.and(x, &y_xor_z);
+        let f_2 = builder.xor(z, &x_and_y_xor_z);
+        // `(x | !y) ^ z = !((!x & y) ^ z)`, where `!x & y = (x ^ y) & y`.
+        let not_x_and_y = builder.and(&x_xor_y, y);
+        let not_f_3 = builder.xor(&not_x_and_y, z);
+        // `(x & z) | (y & !z) = y ^ (z & (x ^ y))`.
+        let z_and_x_xor_y = builder.and(z, &x_xor_y);
+        let f_4 = builder.xor(y, &z_and_x_xor_y);
+        // `x ^ (y | !z) = !(x ^ (!y & z))`, where `!y & z = (y ^ z) & z`.
+        let not_y_and_z = builder.and(&y_xor_z, z);
+        let not_f_5 = builder.xor(x, &not_y_and_z);
+
+        [
+            f_1.expr(),
+            f_2.expr(),
+            not(not_f_3),
+            f_4.expr(),
+            not(not_f_5),
+        ]
+    }
+
+    fn ripemd160_rotate_left(
+        builder: &mut BytesBuilder<L>,
+        value: Self::IntRegister,
+        rotation_bits: &[BitRegister],
+    ) -> Self::IntRegister {
+        assert_eq!(rotation_bits.len(), NUM_ROTATION_BITS);
+        let mut value = value;
+        for (k, bit) in rotation_bits.iter().enumerate().take(NUM_ROTATION_BITS - 1) {
+            let rotated = builder.rotate_right(value, 32 - (1 << k));
+            value = builder.select(*bit, &rotated, &value);
+        }
+
+        // A rotation by 8 bits moves the bytes, so it is selected byte by byte.
+        let bit = rotation_bits[NUM_ROTATION_BITS - 1];
+        let result = builder.alloc::<U32Register>();
+        let bytes = value.to_le_bytes();
+        for (i, byte) in result.to_le_bytes().iter().enumerate() {
+            builder.set_to_expression(
+                &byte,
+                bit.expr() * bytes.get((i + 3) % 4).expr() + bit.not_expr() * bytes.get(i).expr(),
+            );
+        }
+        result
+    }
+}
+
+/// Stores `values` in a slice with the given multiplicities, and loads the value at `index`.
+fn load_value<B: Builder, V: MemoryValue>(
+    builder: &mut B,
+    values: &[V],
+    multiplicities: &[ElementRegister],
+    index: ElementRegister,
+) -> V {
+    assert_eq!(values.len(), multiplicities.len());
+    let slice = builder.uninit_slice();
+    for (i, (value, multiplicity)) in values.iter().zip(multiplicities.iter()).enumerate() {
+        builder.store(
+            &slice.get(i),
+            *value,
+            &Time::zero(),
+            Some(*multiplicity),
+            None,
+            None,
+        );
+    }
+    builder.load(&slice.get_at(index), &Time::zero(), None, None)
+}
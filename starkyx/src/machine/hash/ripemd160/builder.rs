@@ -0,0 +1,364 @@
+use super::air::RIPEMD160Air;
+use crate::chip::register::array::ArrayRegister;
+use crate::chip::register::bit::BitRegister;
+use crate::chip::register::element::ElementRegister;
+use crate::machine::builder::Builder;
+
+pub trait RIPEMD160Builder: Builder {
+    /// Computes RIPEMD-160 hashes, see [`RIPEMD160Air::ripemd160`].
+    fn ripemd160<R: RIPEMD160Air<Self>>(
+        &mut self,
+        padded_chunks: &[ArrayRegister<R::IntRegister>],
+        end_bits: &ArrayRegister<BitRegister>,
+        digest_bits: &ArrayRegister<BitRegister>,
+        digest_indices: &ArrayRegister<ElementRegister>,
+    ) -> Vec<R::DigestRegister> {
+        R::ripemd160(self, padded_chunks, end_bits, digest_bits, digest_indices)
+    }
+}
+
+impl<B: Builder> RIPEMD160Builder for B {}
+
+#[cfg(test)]
+mod tests {
+    use itertools::Itertools;
+    use plonky2::field::goldilocks_field::GoldilocksField;
+    use plonky2::iop::witness::{PartialWitness, WitnessWrite};
+    use plonky2::plonk::circuit_builder::CircuitBuilder;
+    use plonky2::plonk::circuit_data::CircuitConfig;
+    use plonky2::timed;
+    use plonky2::util::log2_ceil;
+    use plonky2::util::timing::TimingTree;
+    use serde::{Deserialize, Serialize};
+
+    use super::*;
+    use crate::chip::arithmetic::expression::ArithmeticExpression;
+    use crate::chip::register::Register;
+    use crate::chip::uint::operations::instruction::UintInstruction;
+    use crate::chip::uint::register::U32Register;
+    use crate::chip::uint::util::{u32_from_le_field_bytes, u32_to_le_field_bytes};
+    use crate::chip::AirParameters;
+    use crate::machine::bytes::builder::BytesBuilder;
+    use crate::machine::hash::ripemd160::pure::RIPEMD160Pure;
+    use crate::machine::hash::ripemd160::{CYCLE_LENGTH, INITIAL_HASH, RIPEMD160};
+    use crate::machine::hash::sha::algorithm::SHAPure;
+    use crate::machine::hash::sha::builder::SHABuilder;
+    use crate::machine::hash::sha::sha256::SHA256;
+    use crate::math::goldilocks::cubic::GoldilocksCubicParameters;
+    use crate::math::prelude::*;
+    use crate::plonky2::stark::config::{CurtaConfig, CurtaPoseidonGoldilocksConfig};
+    use crate::prelude::{AirWriter, AirWriterData};
+
+    #[derive(Clone, Debug, Serialize, Deserialize)]
+    pub struct RIPEMD160Test;
+
+    impl AirParameters for RIPEMD160Test {
+        type Field = GoldilocksField;
+        type CubicParams = GoldilocksCubicParameters;
+        type Instruction = UintInstruction;
+
+        const NUM_FREE_COLUMNS: usize = 436;
+        const EXTENDED_COLUMNS: usize = 984;
+    }
+
+    #[derive(Clone, Debug, Serialize, Deserialize)]
+    pub struct HASH160Test;
+
+    impl AirParameters for HASH160Test {
+        type Field = GoldilocksField;
+        type CubicParams = GoldilocksCubicParameters;
+        type Instruction = UintInstruction;
+
+        const NUM_FREE_COLUMNS: usize = 854;
+        const EXTENDED_COLUMNS: usize = 1896;
+    }
+
+    #[test]
+    fn test_ripemd160() {
+        type C = CurtaPoseidonGoldilocksConfig;
+        type Config = <C as CurtaConfig<2>>::GenericConfig;
+
+        let _ = env_logger::builder().is_test(true).try_init();
+        let mut timing = TimingTree::new("test_ripemd160", log::Level::Debug);
+
+        // Messages of one, two and four chunks, whose compressions do not fill the trace.
+        let long_msg = b"1234567890".repeat(20);
+        let messages: [(&[u8], &str); 4] = [
+            (b"", "9c1185a5c5e9fc54612808977ee8f548b2258d31"),
+            (b"abc", "8eb208f7e05d987a9b044a8e98c6b087f15a0bfc"),
+            (
+                b"12345678901234567890123456789012345678901234567890123456789012345678901234567890",
+                "9b752e45573d4b39f4dbd3323cab82bf63326bfb",
+            ),
+            (&long_msg, "9000bce5188ba938042023968efaa81e711ec5b1"),
+        ];
+
+        let padded_chunks_values = messages
+            .iter()
+            .flat_map(|(msg, _)| {
+                RIPEMD160::pad(msg)
+                    .chunks_exact(16)
+                    .map(|chunk| chunk.to_vec())
+                    .collect_vec()
+            })
+            .collect_vec();
+        let end_chunks = messages
+            .iter()
+            .scan(0, |num_chunks, (msg, _)| {
+                *num_chunks += RIPEMD160::num_chunks(msg);
+                Some(*num_chunks - 1)
+            })
+            .collect_vec();
+        let num_compressions = padded_chunks_values.len();
+
+        // Build the stark.
+        let mut builder = BytesBuilder::<RIPEMD160Test>::new();
+        let padded_chunks = (0..num_compressions)
+            .map(|_| builder.alloc_array_public::<U32Register>(16))
+            .collect_vec();
+        let end_bits = builder.alloc_array_public::<BitRegister>(num_compressions);
+        let digest_bits = builder.alloc_array_public::<BitRegister>(num_compressions);
+        let digest_indices = builder.alloc_array_public::<ElementRegister>(messages.len());
+        let digests = builder.ripemd160::<RIPEMD160>(
+            &padded_chunks,
+            &end_bits,
+            &digest_bits,
+            &digest_indices,
+        );
+
+        let num_rows = 1 << log2_ceil(CYCLE_LENGTH * num_compressions);
+        let stark = builder.build::<C, 2>(num_rows);
+
+        // Build the recursive circuit.
+        let config_rec = CircuitConfig::standard_recursion_config();
+        let mut recursive_builder = CircuitBuilder::<GoldilocksField, 2>::new(config_rec);
+
+        let (proof_target, public_input) =
+            stark.add_virtual_proof_with_pis_target(&mut recursive_builder);
+        stark.verify_circuit(&mut recursive_builder, &proof_target, &public_input);
+
+        let rec_data = recursive_builder.build::<Config>();
+
+        // Write trace.
+        let mut writer_data = AirWriterData::new(&stark.air_data, num_rows);
+        let mut writer = writer_data.public_writer();
+
+        let mut state = INITIAL_HASH;
+        for (i, (chunk, register)) in padded_chunks_values
+            .iter()
+            .zip_eq(padded_chunks.iter())
+            .enumerate()
+        {
+            writer.write_array(
+                register,
+                chunk
+                    .iter()
+                    .map(|word| u32_to_le_field_bytes::<GoldilocksField>(*word)),
+            );
+            state = RIPEMD160::compress(&state, chunk.as_slice().try_into().unwrap());
+            let is_end = end_chunks.contains(&i);
+            if let Some(position) = end_chunks.iter().position(|&end| end == i) {
+                writer.write(
+                    &digest_indices.get(position),
+                    &GoldilocksField::from_canonical_usize(i),
+                );
+                writer.write_array(
+                    &digests[position].as_array(),
+                    state.map(u32_to_le_field_bytes::<GoldilocksField>),
+                );
+                state = INITIAL_HASH;
+            }
+            writer.write(
+                &end_bits.get(i),
+                &GoldilocksField::from_canonical_usize(is_end as usize),
+            );
+            writer.write(
+                &digest_bits.get(i),
+                &GoldilocksField::from_canonical_usize(is_end as usize),
+            );
+        }
+
+        timed!(timing, "write input", {
+            stark.air_data.write_global_instructions(&mut writer);
+
+            for mut chunk in writer_data.chunks(num_rows) {
+                for i in 0..num_rows {
+                    let mut writer = chunk.window_writer(i);
+                    stark.air_data.write_trace_instructions(&mut writer);
+                }
+            }
+        });
+
+        // Compare the expected hashes with the digests.
+        let writer = writer_data.public_writer();
+        for (digest, (_, expected)) in digests.iter().zip_eq(messages.iter()) {
+            let digest = writer
+                .read_array::<_, 5>(&digest.as_array())
+                .map(|word| u32_from_le_field_bytes(&word));
+            assert_eq!(digest, RIPEMD160::decode(expected));
+        }
+
+        let (trace, public) = (writer_data.trace, writer_data.public);
+
+        let proof = timed!(
+            timing,
+            "generate stark proof",
+            stark.prove(&trace, &public, &mut timing).unwrap()
+        );
+
+        stark.verify(proof.clone(), &public).unwrap();
+
+        let mut pw = PartialWitness::new();
+
+        pw.set_target_arr(&public_input, &public).unwrap();
+        stark
+            .set_proof_target(&mut pw, &proof_target, proof)
+            .unwrap();
+
+        let rec_proof = timed!(
+            timing,
+            "generate recursive proof",
+            rec_data.prove(pw).unwrap()
+        );
+        rec_data.verify(rec_proof).unwrap();
+
+        timing.print();
+    }
+
+    #[test]
+    fn test_hash160() {
+        type C = CurtaPoseidonGoldilocksConfig;
+
+        let _ = env_logger::builder().is_test(true).try_init();
+        let mut timing = TimingTree::new("test_hash160", log::Level::Debug);
+
+        // Compressed public keys, whose SHA-256 digests fit in one RIPEMD-160 chunk.
+        let public_keys = [(0x02u8, 0x11u8), (0x03, 0x22), (0x02, 0x33)]
+            .map(|(prefix, byte)| [vec![prefix], vec![byte; 32]].concat());
+        let expected_digests = [
+            "adfce54f529b2154e3c361bbe3f7d41db0635717",
+            "5fa71d913f0497f8218d2892ec3f50e5faac8430",
+            "5eb9b5e445db673f0ed8935d18cd205b214e5187",
+        ];
+        let num_keys = public_keys.len();
+
+        // Build the stark, where each key takes a SHA-256 chunk and a RIPEMD-160 chunk. Both chips
+        // take 256 rows.
+        let mut builder = BytesBuilder::<HASH160Test>::new();
+        let sha_chunks = (0..num_keys)
+            .map(|_| builder.alloc_array_public::<U32Register>(16))
+            .collect_vec();
+        let ripemd_chunks = (0..num_keys)
+            .map(|_| builder.alloc_array_public::<U32Register>(16))
+            .collect_vec();
+        let bits = builder.alloc_array_public::<BitRegister>(num_keys);
+        let sha_indices = builder.alloc_array_public::<ElementRegister>(num_keys);
+        let ripemd_indices = builder.alloc_array_public::<ElementRegister>(num_keys);
+        let sha_digests = builder.sha::<SHA256, 64>(&sha_chunks, &bits, &bits, sha_indices);
+        let digests = builder.ripemd160::<RIPEMD160>(&ripemd_chunks, &bits, &bits, &ripemd_indices);
+
+        // The RIPEMD-160 message is the SHA-256 digest, whose big-endian words are read as
+        // little-endian words, followed by the padding of a 32-byte message.
+        for (sha_digest, chunk) in sha_digests.iter().zip_eq(ripemd_chunks.iter()) {
+            let sha_words: ArrayRegister<U32Register> = (*sha_digest).into();
+            for (i, word) in chunk.iter().enumerate() {
+                let bytes = word.to_le_bytes();
+                match i {
+                    0..=7 => {
+                        let sha_bytes = sha_words.get(i).to_le_bytes();
+                        for k in 0..4 {
+                            builder.assert_equal(&bytes.get(k), &sha_bytes.get(3 - k));
+                        }
+                    }
+                    _ => {
+                        let padding = match i {
+                            8 => 0x80,
+                            14 => 32 * 8,
+                            _ => 0,
+                        };
+                        builder.assert_expressions_equal(
+                            word.expr(),
+                            ArithmeticExpression::from_constant_vec(
+                                u32_to_le_field_bytes(padding).to_vec(),
+                            ),
+                        );
+                    }
+                }
+            }
+        }
+
+        let num_rows = 1 << log2_ceil(CYCLE_LENGTH * num_keys);
+        assert_eq!(num_rows, 1 << log2_ceil(64 * num_keys));
+        let stark = builder.build::<C, 2>(num_rows);
+
+        // Write trace.
+        let mut writer_data = AirWriterData::new(&stark.air_data, num_rows);
+        let mut writer = writer_data.public_writer();
+
+        for (i, public_key) in public_keys.iter().enumerate() {
+            let sha_chunk = SHA256::pad(public_key);
+            let sha_digest =
+                SHA256::process(SHA256::INITIAL_HASH, &SHA256::pre_process(&sha_chunk));
+            let ripemd_chunk = RIPEMD160::pad(
+                &sha_digest
+                    .iter()
+                    .flat_map(|word| word.to_be_bytes())
+                    .collect_vec(),
+            );
+            let digest = RIPEMD160::compress(&INITIAL_HASH, ripemd_chunk[..].try_into().unwrap());
+
+            writer.write_array(
+                &sha_chunks[i],
+                sha_chunk
+                    .iter()
+                    .map(|word| u32_to_le_field_bytes::<GoldilocksField>(*word)),
+            );
+            writer.write_array(
+                &ripemd_chunks[i],
+                ripemd_chunk
+                    .iter()
+                    .map(|word| u32_to_le_field_bytes::<GoldilocksField>(*word)),
+            );
+            writer.write(&bits.get(i), &GoldilocksField::ONE);
+            writer.write(
+                &sha_indices.get(i),
+                &GoldilocksField::from_canonical_usize(i),
+            );
+            writer.write(
+                &ripemd_indices.get(i),
+                &GoldilocksField::from_canonical_usize(i),
+            );
+            let sha_words: ArrayRegister<U32Register> = sha_digests[i].into();
+            writer.write_array(
+                &sha_words,
+                sha_digest.map(u32_to_le_field_bytes::<GoldilocksField>),
+            );
+            writer.write_array(
+                &digests[i].as_array(),
+                digest.map(u32_to_le_field_bytes::<GoldilocksField>),
+            );
+        }
+
+        stark.air_data.write_global_instructions(&mut writer);
+        for mut chunk in writer_data.chunks(num_rows) {
+            for i in 0..num_rows {
+                let mut writer = chunk.window_writer(i);
+                stark.air_data.write_trace_instructions(&mut writer);
+            }
+        }
+
+        // Compare the expected hashes with the digests.
+        let writer = writer_data.public_writer();
+        for (digest, expected) in digests.iter().zip_eq(expected_digests) {
+            let digest = writer
+                .read_array::<_, 5>(&digest.as_array())
+                .map(|word| u32_from_le_field_bytes(&word));
+            assert_eq!(digest, RIPEMD160::decode(expected));
+        }
+
+        let (trace, public) = (writer_data.trace, writer_data.public);
+
+        let proof = stark.prove(&trace, &public, &mut timing).unwrap();
+        stark.verify(proof, &public).unwrap();
+    }
+}
@@ -0,0 +1,70 @@
+//! The RIPEMD-160 hash function.
+//!
+//! RIPEMD-160 pads the message as SHA-256 does, but with little-endian words and length, and
+//! compresses its blocks of 16 words into a state of 5 words. The compression function runs two
+//! parallel lines of 80 steps, in five rounds of 16 steps, which differ by their order of the
+//! message words, their rotations, their round constants and their order of the boolean functions.
+
+use serde::{Deserialize, Serialize};
+
+pub mod air;
+pub mod builder;
+pub mod pure;
+pub mod register;
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct RIPEMD160;
+
+/// The number of rows of a compression in the AIR, one per step.
+const CYCLE_LENGTH: usize = 80;
+const NUM_ROUNDS: usize = 5;
+const ROUND_LENGTH: usize = 16;
+const MSG_ARRAY_SIZE: usize = 16;
+const STATE_SIZE: usize = 5;
+
+pub const INITIAL_HASH: [u32; STATE_SIZE] =
+    [0x67452301, 0xefcdab89, 0x98badcfe, 0x10325476, 0xc3d2e1f0];
+
+/// The round constants of the left line.
+const K_LEFT: [u32; NUM_ROUNDS] = [0x00000000, 0x5a827999, 0x6ed9eba1, 0x8f1bbcdc, 0xa953fd4e];
+/// The round constants of the right line.
+const K_RIGHT: [u32; NUM_ROUNDS] = [0x50a28be6, 0x5c4dd124, 0x6d703ef3, 0x7a6d76e9, 0x00000000];
+
+/// The indices of the message words of the steps of the left line.
+const R_LEFT: [usize; CYCLE_LENGTH] = [
+    0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15, //
+    7, 4, 13, 1, 10, 6, 15, 3, 12, 0, 9, 5, 2, 14, 11, 8, //
+    3, 10, 14, 4, 9, 15, 8, 1, 2, 7, 0, 6, 13, 11, 5, 12, //
+    1, 9, 11, 10, 0, 8, 12, 4, 13, 3, 7, 15, 14, 5, 6, 2, //
+    4, 0, 5, 9, 7, 12, 2, 10, 14, 1, 3, 8, 11, 6, 15, 13,
+];
+
+/// The indices of the message words of the steps of the right line.
+const R_RIGHT: [usize; CYCLE_LENGTH] = [
+    5, 14, 7, 0, 9, 2, 11, 4, 13, 6, 15, 8, 1, 10, 3, 12, //
+    6, 11, 3, 7, 0, 13, 5, 10, 14, 15, 8, 12, 4, 9, 1, 2, //
+    15, 5, 1, 3, 7, 14, 6, 9, 11, 8, 12, 2, 10, 0, 4, 13, //
+    8, 6, 4, 1, 3, 11, 15, 0, 5, 12, 2, 13, 9, 7, 10, 14, //
+    12, 15, 10, 4, 1, 5, 8, 7, 6, 2, 13, 14, 0, 3, 9, 11,
+];
+
+/// The left rotations of the steps of the left line.
+const S_LEFT: [u32; CYCLE_LENGTH] = [
+    11, 14, 15, 12, 5, 8, 7, 9, 11, 13, 14, 15, 6, 7, 9, 8, //
+    7, 6, 8, 13, 11, 9, 7, 15, 7, 12, 15, 9, 11, 7, 13, 12, //
+    11, 13, 6, 7, 14, 9, 13, 15, 14, 8, 13, 6, 5, 12, 7, 5, //
+    11, 12, 14, 15, 14, 15, 9, 8, 9, 14, 5, 6, 8, 6, 5, 12, //
+    9, 15, 5, 11, 6, 8, 13, 12, 5, 12, 13, 14, 11, 8, 5, 6,
+];
+
+/// The left rotations of the steps of the right line.
+const S_RIGHT: [u32; CYCLE_LENGTH] = [
+    8, 9, 9, 11, 13, 15, 15, 5, 7, 7, 8, 11, 14, 14, 12, 6, //
+    9, 13, 15, 7, 12, 8, 9, 11, 7, 7, 12, 7, 6, 15, 13, 11, //
+    9, 7, 15, 11, 8, 6, 6, 14, 12, 13, 5, 14, 13, 13, 7, 5, //
+    15, 5, 8, 11, 14, 14, 6, 14, 6, 9, 12, 9, 12, 5, 15, 8, //
+    8, 5, 12, 9, 12, 5, 14, 6, 8, 13, 6, 5, 15, 13, 11, 11,
+];
+
+/// The number of bits of the rotations, which are less than 16.
+const NUM_ROTATION_BITS: usize = 4;
@@ -0,0 +1,173 @@
+use super::{
+    INITIAL_HASH, K_LEFT, K_RIGHT, MSG_ARRAY_SIZE, NUM_ROUNDS, RIPEMD160, ROUND_LENGTH, R_LEFT,
+    R_RIGHT, STATE_SIZE, S_LEFT, S_RIGHT,
+};
+use crate::machine::hash::HashPureInteger;
+
+impl HashPureInteger for RIPEMD160 {
+    type Integer = u32;
+}
+
+pub trait RIPEMD160Pure: HashPureInteger {
+    /// Pads `msg` to a multiple of 64 bytes, as little-endian words.
+    fn pad(msg: &[u8]) -> Vec<Self::Integer>;
+
+    /// The number of blocks of the padded message.
+    fn num_chunks(msg: &[u8]) -> usize {
+        (msg.len() + 8) / 64 + 1
+    }
+
+    /// The compression function, returning the next state.
+    fn compress(
+        state: &[Self::Integer; STATE_SIZE],
+        block: &[Self::Integer; MSG_ARRAY_SIZE],
+    ) -> [Self::Integer; STATE_SIZE];
+
+    /// The 20-byte hash of `msg`.
+    fn hash(msg: &[u8]) -> [Self::Integer; STATE_SIZE];
+
+    /// Decodes a digest encoded as a hexadecimal string to its words.
+    fn decode(digest: &str) -> [Self::Integer; STATE_SIZE];
+}
+
+/// The boolean function of the given round, in the order of the left line.
+pub(crate) fn f(round: usize, x: u32, y: u32, z: u32) -> u32 {
+    match round {
+        0 => x ^ y ^ z,
+        1 => (x & y) | (!x & z),
+        2 => (x | !y) ^ z,
+        3 => (x & z) | (y & !z),
+        4 => x ^ (y | !z),
+        _ => unreachable!("RIPEMD-160 has {} rounds", NUM_ROUNDS),
+    }
+}
+
+/// A step of a line, updating the working variables `[a, b, c, d, e]`.
+fn step(vars: &mut [u32; STATE_SIZE], f: u32, x: u32, k: u32, s: u32) {
+    let [a, b, c, d, e] = *vars;
+    let t = a
+        .wrapping_add(f)
+        .wrapping_add(x)
+        .wrapping_add(k)
+        .rotate_left(s)
+        .wrapping_add(e);
+    *vars = [e, t, b, c.rotate_left(10), d];
+}
+
+impl RIPEMD160Pure for RIPEMD160 {
+    fn pad(msg: &[u8]) -> Vec<Self::Integer> {
+        let mut padded_msg = msg.to_vec();
+        padded_msg.push(1 << 7);
+        let padlen = (55 - msg.len() as isize).rem_euclid(64) as usize;
+        padded_msg.extend_from_slice(&vec![0u8; padlen]);
+        padded_msg.extend_from_slice(&((msg.len() * 8) as u64).to_le_bytes());
+
+        padded_msg
+            .chunks_exact(4)
+            .map(|slice| u32::from_le_bytes(slice.try_into().unwrap()))
+            .collect()
+    }
+
+    fn compress(
+        state: &[Self::Integer; STATE_SIZE],
+        block: &[Self::Integer; MSG_ARRAY_SIZE],
+    ) -> [Self::Integer; STATE_SIZE] {
+        let mut left = *state;
+        let mut right = *state;
+        for j in 0..NUM_ROUNDS * ROUND_LENGTH {
+            let round = j / ROUND_LENGTH;
+            let f_left = f(round, left[1], left[2], left[3]);
+            step(
+                &mut left,
+                f_left,
+                block[R_LEFT[j]],
+                K_LEFT[round],
+                S_LEFT[j],
+            );
+            let f_right = f(NUM_ROUNDS - 1 - round, right[1], right[2], right[3]);
+            step(
+                &mut right,
+                f_right,
+                block[R_RIGHT[j]],
+                K_RIGHT[round],
+                S_RIGHT[j],
+            );
+        }
+
+        [
+            state[1].wrapping_add(left[2]).wrapping_add(right[3]),
+            state[2].wrapping_add(left[3]).wrapping_add(right[4]),
+            state[3].wrapping_add(left[4]).wrapping_add(right[0]),
+            state[4].wrapping_add(left[0]).wrapping_add(right[1]),
+            state[0].wrapping_add(left[1]).wrapping_add(right[2]),
+        ]
+    }
+
+    fn hash(msg: &[u8]) -> [Self::Integer; STATE_SIZE] {
+        Self::pad(msg)
+            .chunks_exact(MSG_ARRAY_SIZE)
+            .fold(INITIAL_HASH, |state, block| {
+                Self::compress(&state, block.try_into().unwrap())
+            })
+    }
+
+    fn decode(digest: &str) -> [Self::Integer; STATE_SIZE] {
+        hex::decode(digest)
+            .unwrap()
+            .chunks_exact(4)
+            .map(|x| u32::from_le_bytes(x.try_into().unwrap()))
+            .collect::<Vec<_>>()
+            .try_into()
+            .unwrap()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ripemd160_hash() {
+        let million_a = vec![b'a'; 1_000_000];
+        let test_vectors: [(&[u8], &str); 8] = [
+            (b"", "9c1185a5c5e9fc54612808977ee8f548b2258d31"),
+            (b"a", "0bdc9d2d256b3ee9daae347be6f4dc835a467ffe"),
+            (b"abc", "8eb208f7e05d987a9b044a8e98c6b087f15a0bfc"),
+            (
+                b"message digest",
+                "5d0689ef49d2fae572b881b123a85ffa21595f36",
+            ),
+            (
+                b"abcdefghijklmnopqrstuvwxyz",
+                "f71c27109c692c1b56bbdceb5b9d2865b3708dbc",
+            ),
+            (
+                b"abcdbcdecdefdefgefghfghighijhijkijkljklmklmnlmnomnopnopq",
+                "12a053384a9c0c88e405a06c27dcf49ada62eb2b",
+            ),
+            (
+                b"12345678901234567890123456789012345678901234567890123456789012345678901234567890",
+                "9b752e45573d4b39f4dbd3323cab82bf63326bfb",
+            ),
+            (&million_a, "52783243c1697bdbe16d37f97f68f08325dc1528"),
+        ];
+
+        for (msg, digest) in test_vectors {
+            assert_eq!(RIPEMD160::hash(msg), RIPEMD160::decode(digest));
+        }
+    }
+
+    #[test]
+    fn test_ripemd160_padding() {
+        for len in [0, 1, 55, 56, 63, 64, 119, 120] {
+            let msg = vec![0xab; len];
+            let padded_msg = RIPEMD160::pad(&msg);
+            assert_eq!(
+                padded_msg.len(),
+                RIPEMD160::num_chunks(&msg) * MSG_ARRAY_SIZE
+            );
+            assert_eq!(padded_msg[padded_msg.len() - 2], (len * 8) as u32);
+            assert_eq!(padded_msg[padded_msg.len() - 1], 0);
+        }
+    }
+}
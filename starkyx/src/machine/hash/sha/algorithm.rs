@@ -13,6 +13,7 @@ use crate::chip::register::bit::BitRegister;
 use crate::chip::register::element::ElementRegister;
 use crate::chip::register::{Register, RegisterSerializable};
 use crate::machine::builder::Builder;
+use crate::machine::hash::batch::{BatchSchedule, HashBatch};
 use crate::machine::hash::sha::data::{SHAMemory, SHAPublicData, SHATraceData};
 use crate::machine::hash::{HashDigest, HashIntConversion, HashPureInteger};
 use crate::math::prelude::*;
@@ -27,17 +28,51 @@ pub trait SHAPure<const CYCLE_LENGTH: usize>:
 {
     const INITIAL_HASH: [Self::Integer; 8];
     const ROUND_CONSTANTS: [Self::Integer; CYCLE_LENGTH];
+    /// The number of words of the final state in the digest.
+    const DIGEST_LENGTH: usize = 8;
 
     /// Pad a byte message to a vector of `Self::Integer` values.
     fn pad(msg: &[u8]) -> Vec<Self::Integer>;
 
+    /// The number of 16-word chunks of the padded message.
+    fn num_chunks(msg: &[u8]) -> usize {
+        Self::pad(msg).len() / 16
+    }
+
+    /// Pad a byte message to exactly `num_chunks` chunks of 16 words.
+    ///
+    /// This is the variable-length mode, in which messages of different lengths occupy the same
+    /// number of chunks. The message is padded as usual and followed by chunks of zeros, whose
+    /// compression is ignored. The digest is the state after the chunk of index
+    /// `Self::num_chunks(msg) - 1`, which is flagged by the digest bits rather than the end bits.
+    ///
+    /// Panics if the padded message does not fit in `num_chunks` chunks.
+    fn pad_to_chunks(msg: &[u8], num_chunks: usize) -> Vec<Self::Integer> {
+        let mut padded_msg = Self::pad(msg);
+        assert!(
+            padded_msg.len() <= num_chunks * 16,
+            "The padded message has {} chunks, more than {}",
+            padded_msg.len() / 16,
+            num_chunks
+        );
+        padded_msg.resize(num_chunks * 16, Self::Integer::zero());
+        padded_msg
+    }
+
     /// Pre-process a chunk of `Self::Integer` values.
     fn pre_process(chunk: &[Self::Integer]) -> [Self::Integer; CYCLE_LENGTH];
 
     /// Process a chunk of `Self::Integer` values.
     fn process(hash: [Self::Integer; 8], w: &[Self::Integer; CYCLE_LENGTH]) -> [Self::Integer; 8];
 
+    /// The compression function, which updates the state `hash` with a chunk of 16 words.
+    fn compress(hash: [Self::Integer; 8], chunk: &[Self::Integer]) -> [Self::Integer; 8] {
+        Self::process(hash, &Self::pre_process(chunk))
+    }
+
     /// Decode a digest encoded as a string to a vector of `Self::Integer` values.
+    ///
+    /// The words of the state which are not part of the digest are set to zero.
     fn decode(digest: &str) -> [Self::Integer; 8];
 }
 
@@ -76,10 +111,13 @@ pub trait SHAir<B: Builder, const CYCLE_LENGTH: usize>:
         round_constant: Self::IntRegister,
     ) -> Vec<Self::IntRegister>;
 
+    /// Frees the digests from the state memory. A digest in the trace is freed from the first
+    /// row, with the multiplicity `first_row`.
     fn load_state(
         builder: &mut B,
-        hash_state_public: &[Self::StateVariable],
+        hash_state: &[Self::StateVariable],
         digest_indices: ArrayRegister<ElementRegister>,
+        first_row: Option<ElementRegister>,
     ) -> Self::StatePointer;
 
     fn store_state(
@@ -96,6 +134,11 @@ pub trait SHAir<B: Builder, const CYCLE_LENGTH: usize>:
         vars_next: &[Self::IntRegister],
     ) -> Self::StateVariable;
 
+    /// Computes the hashes of messages given by their padded chunks.
+    ///
+    /// The chunks are usually public. A chunk may also be a trace register, such as one holding a
+    /// private key, whose value in the first row is hashed. Such a chunk must have the same value
+    /// in all rows, and the rows of the trace must be written in order.
     fn sha(
         builder: &mut B,
         padded_chunks: &[ArrayRegister<Self::IntRegister>],
@@ -103,15 +146,190 @@ pub trait SHAir<B: Builder, const CYCLE_LENGTH: usize>:
         digest_bits: &ArrayRegister<BitRegister>,
         digest_indices: ArrayRegister<ElementRegister>,
     ) -> Vec<Self::StateVariable> {
+        // Initialize the initial hash and set it to the constant value.
+        let initial_hash = builder
+            .constant_array::<Self::IntRegister>(&Self::INITIAL_HASH.map(Self::int_to_field_value));
+        Self::sha_from_midstate(
+            builder,
+            padded_chunks,
+            end_bits,
+            digest_bits,
+            digest_indices,
+            initial_hash,
+        )
+    }
+
+    /// Computes the hashes of messages starting from the state `midstate` instead of the
+    /// initial hash.
+    ///
+    /// The midstate is the state after the chunks of a prefix of the messages, so that the proof
+    /// shows that the chunks extend that prefix. A midstate is exported by hashing the chunks of
+    /// the prefix without padding and setting the digest bit of the last one, whose digest is
+    /// then the full state. The midstate must be public and is shared by all messages.
+    fn sha_from_midstate(
+        builder: &mut B,
+        padded_chunks: &[ArrayRegister<Self::IntRegister>],
+        end_bits: &ArrayRegister<BitRegister>,
+        digest_bits: &ArrayRegister<BitRegister>,
+        digest_indices: ArrayRegister<ElementRegister>,
+        midstate: ArrayRegister<Self::IntRegister>,
+    ) -> Vec<Self::StateVariable> {
+        let digests = (0..digest_indices.len())
+            .map(|_| builder.alloc_public::<Self::StateVariable>())
+            .collect::<Vec<_>>();
+        Self::sha_with_digests(
+            builder,
+            padded_chunks,
+            end_bits,
+            digest_bits,
+            digest_indices,
+            midstate,
+            &digests,
+        );
+        digests
+    }
+
+    /// Computes the hashes of messages starting from `midstate` into the registers `digests`.
+    ///
+    /// A digest may be a trace register, which keeps it out of the public inputs. As for a chunk
+    /// in the trace, the value of such a digest in the first row is the digest, and it must have
+    /// the same value in all rows. Its value is not computed by the chip and must be written with
+    /// the other inputs of the trace.
+    fn sha_with_digests(
+        builder: &mut B,
+        padded_chunks: &[ArrayRegister<Self::IntRegister>],
+        end_bits: &ArrayRegister<BitRegister>,
+        digest_bits: &ArrayRegister<BitRegister>,
+        digest_indices: ArrayRegister<ElementRegister>,
+        midstate: ArrayRegister<Self::IntRegister>,
+        digests: &[Self::StateVariable],
+    ) {
+        assert!(!midstate.is_trace(), "The midstate must be public");
+        assert_eq!(midstate.len(), 8);
+        assert_eq!(digests.len(), digest_indices.len());
         let data = Self::data(
             builder,
             padded_chunks,
             end_bits,
             digest_bits,
             digest_indices,
+            midstate,
+            digests,
         );
         let w_i = Self::preprocessing(builder, &data);
-        Self::processing(builder, w_i, &data)
+        Self::processing(builder, w_i, &data, digests)
+    }
+
+    /// Computes the compressions of the chunks `chunks` with the states `states`.
+    ///
+    /// Each compression takes a state and a chunk of 16 words, which are hashed without padding,
+    /// and returns the updated state, as given by [`SHAPure::compress`]. This is the building
+    /// block of constructions which chain compressions themselves, such as HMAC or Merkle trees.
+    /// The states must be public.
+    fn sha_compress(
+        builder: &mut B,
+        states: &[ArrayRegister<Self::IntRegister>],
+        chunks: &[ArrayRegister<Self::IntRegister>],
+    ) -> Vec<Self::StateVariable> {
+        let outputs = (0..chunks.len())
+            .map(|_| builder.alloc_public::<Self::StateVariable>())
+            .collect::<Vec<_>>();
+        Self::sha_compress_with_outputs(builder, states, chunks, &outputs);
+        outputs
+    }
+
+    /// Computes the compressions of the chunks `chunks` with the states `states` into the
+    /// registers `outputs`.
+    ///
+    /// A state may be the output of another compression, which chains the two compressions in the
+    /// same chip. The states must be public, while an output may be in the trace, as a digest of
+    /// [`SHAir::sha_with_digests`].
+    fn sha_compress_with_outputs(
+        builder: &mut B,
+        states: &[ArrayRegister<Self::IntRegister>],
+        chunks: &[ArrayRegister<Self::IntRegister>],
+        outputs: &[Self::StateVariable],
+    ) {
+        assert_eq!(states.len(), chunks.len());
+        assert_eq!(outputs.len(), chunks.len());
+        for state in states {
+            assert!(!state.is_trace(), "The states must be public");
+            assert_eq!(state.len(), 8);
+        }
+
+        // Each chunk is a message of its own, whose digest is the state after it.
+        let bits = builder.constant_array::<BitRegister>(&vec![B::Field::ONE; chunks.len()]);
+        let digest_indices = builder.constant_array::<ElementRegister>(
+            &(0..chunks.len())
+                .map(B::Field::from_canonical_usize)
+                .collect::<Vec<_>>(),
+        );
+        let initial_hash = builder
+            .constant_array::<Self::IntRegister>(&Self::INITIAL_HASH.map(Self::int_to_field_value));
+        let mut data = Self::data(
+            builder,
+            chunks,
+            &bits,
+            &bits,
+            digest_indices,
+            initial_hash,
+            outputs,
+        );
+        data.chunk_state = Some(Self::load_chunk_state(builder, states, &data));
+        let w_i = Self::preprocessing(builder, &data);
+        Self::processing(builder, w_i, &data, outputs);
+    }
+
+    /// Computes the hashes of a batch of messages given by their padded chunks, each message
+    /// taking as many chunks as it needs.
+    ///
+    /// The chunks of the messages are placed one after the other in the table, whose rows record
+    /// the index of their message, see [`HashBatch`]. The digest of each message is found by its
+    /// index in the batch.
+    fn sha_batch(
+        builder: &mut B,
+        messages: &[Vec<ArrayRegister<Self::IntRegister>>],
+    ) -> HashBatch<Self::StateVariable> {
+        let schedule = BatchSchedule::new(messages.iter().map(|chunks| chunks.len()).collect());
+        let padded_chunks = messages.concat();
+        let to_field = |bits: Vec<bool>| {
+            bits.into_iter()
+                .map(|bit| B::Field::from_canonical_usize(bit as usize))
+                .collect::<Vec<_>>()
+        };
+        let end_bits = builder.constant_array::<BitRegister>(&to_field(schedule.end_bits()));
+        let digest_bits = builder.constant_array::<BitRegister>(&to_field(schedule.output_bits()));
+        let digest_indices = builder.constant_array::<ElementRegister>(
+            &schedule
+                .output_indices()
+                .into_iter()
+                .map(B::Field::from_canonical_usize)
+                .collect::<Vec<_>>(),
+        );
+        let initial_hash = builder
+            .constant_array::<Self::IntRegister>(&Self::INITIAL_HASH.map(Self::int_to_field_value));
+        let outputs = (0..messages.len())
+            .map(|_| builder.alloc_public::<Self::StateVariable>())
+            .collect::<Vec<_>>();
+
+        let data = Self::data(
+            builder,
+            &padded_chunks,
+            &end_bits,
+            &digest_bits,
+            digest_indices,
+            initial_hash,
+            &outputs,
+        );
+        let message_id = schedule.load_message_id(builder, data.trace.process_id, CYCLE_LENGTH);
+        let w_i = Self::preprocessing(builder, &data);
+        Self::processing(builder, w_i, &data, &outputs);
+
+        HashBatch {
+            schedule,
+            outputs,
+            message_id,
+        }
     }
 
     fn data(
@@ -120,6 +338,8 @@ pub trait SHAir<B: Builder, const CYCLE_LENGTH: usize>:
         end_bits: &ArrayRegister<BitRegister>,
         digest_bits: &ArrayRegister<BitRegister>,
         digest_indices: ArrayRegister<ElementRegister>,
+        initial_hash: ArrayRegister<Self::IntRegister>,
+        digests: &[Self::StateVariable],
     ) -> SHAData<Self::IntRegister, CYCLE_LENGTH> {
         assert_eq!(padded_chunks.len(), end_bits.len());
         let num_real_rounds = padded_chunks.len();
@@ -130,6 +350,7 @@ pub trait SHAir<B: Builder, const CYCLE_LENGTH: usize>:
         let degree_log = log2_ceil(num_real_rounds * CYCLE_LENGTH);
         assert!(degree_log < 31, "AIR degree is too large");
         debug!("AIR degree after padding: {}", 1 << degree_log);
+        builder.api().require_rows("SHA", 1 << degree_log);
         let num_dummy_rounds = (1 << degree_log) / CYCLE_LENGTH + 1 - num_real_rounds;
         // Keep track of the last round length to know how many dummy reads to add.
         let length_last_round = (1 << degree_log) % CYCLE_LENGTH;
@@ -139,10 +360,6 @@ pub trait SHAir<B: Builder, const CYCLE_LENGTH: usize>:
         let num_round_element = builder.constant(&B::Field::from_canonical_usize(num_rounds));
         let num_round_minus_one = builder.constant(&B::Field::from_canonical_usize(num_rounds - 1));
 
-        // Initialize the initial hash and set it to the constant value.
-        let initial_hash = builder
-            .constant_array::<Self::IntRegister>(&Self::INITIAL_HASH.map(Self::int_to_field_value));
-
         // Initialize the round constants and set them to the constant value.
         let round_constant_values = builder.constant_array::<Self::IntRegister>(
             &Self::ROUND_CONSTANTS.map(Self::int_to_field_value),
@@ -231,13 +448,29 @@ pub trait SHAir<B: Builder, const CYCLE_LENGTH: usize>:
                 + length_last_round * 5,
         ));
 
+        // Chunks in the trace, such as those holding private keys, are written to memory once,
+        // from their values in the first row. Digests in the trace are freed in the same way.
+        let has_trace_values = padded_chunks
+            .iter()
+            .any(|padded_chunk| padded_chunk.is_trace())
+            || digests.iter().any(|digest| digest.is_trace());
+        let first_row = has_trace_values.then(|| {
+            let first_row = builder.alloc::<ElementRegister>();
+            builder.set_to_expression_first_row(&first_row, B::Field::ONE.into());
+            builder.set_to_expression_transition(&first_row.next(), B::Field::ZERO.into());
+            first_row
+        });
         for (i, padded_chunk) in padded_chunks.iter().enumerate() {
+            let multiplicity = match padded_chunk.is_trace() {
+                true => first_row,
+                false => None,
+            };
             for (j, word) in padded_chunk.iter().enumerate().take(16) {
                 builder.store(
                     &w.get(CYCLE_LENGTH * i + j),
                     word,
                     &Time::zero(),
-                    None,
+                    multiplicity,
                     None,
                     None,
                 );
@@ -391,6 +624,7 @@ pub trait SHAir<B: Builder, const CYCLE_LENGTH: usize>:
             is_preprocessing,
             process_id,
             cycle_end_bit,
+            first_row,
             index,
             is_dummy,
         };
@@ -407,10 +641,56 @@ pub trait SHAir<B: Builder, const CYCLE_LENGTH: usize>:
             public,
             trace,
             memory,
+            chunk_state: None,
             degree: 1 << degree_log,
         }
     }
 
+    /// Loads the state of the chunk of the current row, given the states of the chunks.
+    ///
+    /// Each word of the states is stored in a slice at the index of its chunk, and read in every
+    /// row of the cycle of the chunk. The dummy rounds read the initial hash.
+    fn load_chunk_state(
+        builder: &mut B,
+        states: &[ArrayRegister<Self::IntRegister>],
+        data: &SHAData<Self::IntRegister, CYCLE_LENGTH>,
+    ) -> Vec<Self::IntRegister> {
+        let num_rounds = data.degree / CYCLE_LENGTH + 1;
+        let length_last_round = data.degree % CYCLE_LENGTH;
+        let reg_cycle_length = builder.constant(&B::Field::from_canonical_usize(CYCLE_LENGTH));
+        let reg_last_length = builder.constant(&B::Field::from_canonical_usize(length_last_round));
+        let initial_hash = data.public.initial_hash;
+
+        (0..8)
+            .map(|j| {
+                let word = builder.uninit_slice();
+                for i in 0..num_rounds {
+                    let value = states
+                        .get(i)
+                        .map_or(initial_hash.get(j), |state| state.get(j));
+                    let multiplicity = match i == num_rounds - 1 {
+                        true => reg_last_length,
+                        false => reg_cycle_length,
+                    };
+                    builder.store(
+                        &word.get(i),
+                        value,
+                        &Time::zero(),
+                        Some(multiplicity),
+                        None,
+                        None,
+                    );
+                }
+                builder.load(
+                    &word.get_at(data.trace.process_id),
+                    &Time::zero(),
+                    None,
+                    None,
+                )
+            })
+            .collect()
+    }
+
     fn preprocessing(
         builder: &mut B,
         data: &SHAData<Self::IntRegister, CYCLE_LENGTH>,
@@ -474,12 +754,14 @@ pub trait SHAir<B: Builder, const CYCLE_LENGTH: usize>:
         builder: &mut B,
         w_i: Self::IntRegister,
         data: &SHAData<Self::IntRegister, CYCLE_LENGTH>,
-    ) -> Vec<Self::StateVariable> {
-        let num_digests = data.public.digest_indices.len();
-        let hash_state_public = (0..num_digests)
-            .map(|_| builder.alloc_public::<Self::StateVariable>())
-            .collect::<Vec<_>>();
-        let state_ptr = Self::load_state(builder, &hash_state_public, data.public.digest_indices);
+        hash_state: &[Self::StateVariable],
+    ) {
+        let state_ptr = Self::load_state(
+            builder,
+            hash_state,
+            data.public.digest_indices,
+            data.trace.first_row,
+        );
 
         let index = data.trace.index;
         let initial_hash = data.public.initial_hash;
@@ -492,14 +774,21 @@ pub trait SHAir<B: Builder, const CYCLE_LENGTH: usize>:
             None,
         );
 
+        // The state at the start of the chunk of the current row and of the next row, which is
+        // the initial hash unless each chunk has its own state.
+        let (init, init_next): (Vec<_>, Vec<_>) = match &data.chunk_state {
+            Some(chunk_state) => chunk_state.iter().map(|h| (*h, h.next())).unzip(),
+            None => initial_hash.iter().map(|h| (h, h)).unzip(),
+        };
+
         // Initialize working variables
         let state = builder.alloc_array::<Self::IntRegister>(8);
-        for (h, h_init) in state.iter().zip(initial_hash.iter()) {
+        for (h, h_init) in state.iter().zip(init.iter()) {
             builder.set_to_expression_first_row(&h, h_init.expr());
         }
         // Initialize working variables and set them to the inital hash in the first row.
         let vars = builder.alloc_array::<Self::IntRegister>(8);
-        for (v, h_init) in vars.iter().zip(initial_hash.iter()) {
+        for (v, h_init) in vars.iter().zip(init.iter()) {
             builder.set_to_expression_first_row(&v, h_init.expr());
         }
 
@@ -539,7 +828,7 @@ pub trait SHAir<B: Builder, const CYCLE_LENGTH: usize>:
         for ((((var, h), init), var_next), h_next) in vars
             .iter()
             .zip(state.iter())
-            .zip(initial_hash.iter())
+            .zip(init_next.iter())
             .zip(vars_next.iter())
             .zip(state_next_arr.iter())
         {
@@ -556,7 +845,5 @@ pub trait SHAir<B: Builder, const CYCLE_LENGTH: usize>:
                         * bit.expr(),
             );
         }
-
-        hash_state_public
     }
 }
@@ -1,8 +1,12 @@
 use super::algorithm::SHAir;
+use super::outputs::{SHADigestSchedule, SHAPublicDigests};
 use crate::chip::register::array::ArrayRegister;
 use crate::chip::register::bit::BitRegister;
 use crate::chip::register::element::ElementRegister;
+use crate::chip::register::Register;
 use crate::machine::builder::Builder;
+use crate::machine::hash::batch::HashBatch;
+use crate::math::prelude::*;
 
 pub trait SHABuilder: Builder {
     fn sha<S: SHAir<Self, CYCLE_LENGTH>, const CYCLE_LENGTH: usize>(
@@ -14,6 +18,124 @@ pub trait SHABuilder: Builder {
     ) -> Vec<S::StateVariable> {
         S::sha(self, padded_chunks, end_bits, digest_bits, digest_indices)
     }
+
+    /// Computes SHA hashes from a midstate, see [`SHAir::sha_from_midstate`].
+    fn sha_from_midstate<S: SHAir<Self, CYCLE_LENGTH>, const CYCLE_LENGTH: usize>(
+        &mut self,
+        padded_chunks: &[ArrayRegister<S::IntRegister>],
+        end_bits: &ArrayRegister<BitRegister>,
+        digest_bits: &ArrayRegister<BitRegister>,
+        digest_indices: ArrayRegister<ElementRegister>,
+        midstate: ArrayRegister<S::IntRegister>,
+    ) -> Vec<S::StateVariable> {
+        S::sha_from_midstate(
+            self,
+            padded_chunks,
+            end_bits,
+            digest_bits,
+            digest_indices,
+            midstate,
+        )
+    }
+
+    /// Computes SHA hashes from a midstate into the given digest registers, see
+    /// [`SHAir::sha_with_digests`].
+    fn sha_with_digests<S: SHAir<Self, CYCLE_LENGTH>, const CYCLE_LENGTH: usize>(
+        &mut self,
+        padded_chunks: &[ArrayRegister<S::IntRegister>],
+        end_bits: &ArrayRegister<BitRegister>,
+        digest_bits: &ArrayRegister<BitRegister>,
+        digest_indices: ArrayRegister<ElementRegister>,
+        midstate: ArrayRegister<S::IntRegister>,
+        digests: &[S::StateVariable],
+    ) {
+        S::sha_with_digests(
+            self,
+            padded_chunks,
+            end_bits,
+            digest_bits,
+            digest_indices,
+            midstate,
+            digests,
+        )
+    }
+
+    /// Computes the compressions of chunks with the given states, see [`SHAir::sha_compress`].
+    fn sha_compress<S: SHAir<Self, CYCLE_LENGTH>, const CYCLE_LENGTH: usize>(
+        &mut self,
+        states: &[ArrayRegister<S::IntRegister>],
+        chunks: &[ArrayRegister<S::IntRegister>],
+    ) -> Vec<S::StateVariable> {
+        S::sha_compress(self, states, chunks)
+    }
+
+    /// Computes the compressions of chunks with the given states into the given output registers,
+    /// see [`SHAir::sha_compress_with_outputs`].
+    fn sha_compress_with_outputs<S: SHAir<Self, CYCLE_LENGTH>, const CYCLE_LENGTH: usize>(
+        &mut self,
+        states: &[ArrayRegister<S::IntRegister>],
+        chunks: &[ArrayRegister<S::IntRegister>],
+        outputs: &[S::StateVariable],
+    ) {
+        S::sha_compress_with_outputs(self, states, chunks, outputs)
+    }
+
+    /// Computes the SHA hashes of a batch of messages, see [`SHAir::sha_batch`].
+    fn sha_batch<S: SHAir<Self, CYCLE_LENGTH>, const CYCLE_LENGTH: usize>(
+        &mut self,
+        messages: &[Vec<ArrayRegister<S::IntRegister>>],
+    ) -> HashBatch<S::StateVariable> {
+        S::sha_batch(self, messages)
+    }
+
+    /// Computes the SHA hashes of the messages laid out by `schedule`, and exposes the digests of
+    /// the messages `selected` as the output `name`, see [`super::outputs`].
+    ///
+    /// The output is an array of global values holding the selected digests in the order of
+    /// `selected`. The end bits, digest bits and digest indices are constants given by
+    /// `schedule`. The digests of all messages are public and are written by the prover, before
+    /// the global instructions.
+    fn sha_public_digests<S: SHAir<Self, CYCLE_LENGTH>, const CYCLE_LENGTH: usize>(
+        &mut self,
+        name: &str,
+        padded_chunks: &[ArrayRegister<S::IntRegister>],
+        schedule: &SHADigestSchedule,
+        selected: &[usize],
+    ) -> SHAPublicDigests<S::StateVariable> {
+        assert_eq!(padded_chunks.len(), schedule.num_chunks());
+        assert!(!selected.is_empty(), "Output {} has no digests", name);
+        let to_field = |bits: Vec<bool>| {
+            bits.into_iter()
+                .map(|bit| Self::Field::from_canonical_usize(bit as usize))
+                .collect::<Vec<_>>()
+        };
+        let end_bits = self.constant_array::<BitRegister>(&to_field(schedule.end_bits()));
+        let digest_bits = self.constant_array::<BitRegister>(&to_field(schedule.digest_bits()));
+        let digest_indices = self.constant_array::<ElementRegister>(
+            &schedule
+                .digest_indices()
+                .into_iter()
+                .map(Self::Field::from_canonical_usize)
+                .collect::<Vec<_>>(),
+        );
+        let digests = S::sha(self, padded_chunks, &end_bits, &digest_bits, digest_indices);
+
+        let outputs = self
+            .api()
+            .alloc_array_global::<S::StateVariable>(selected.len());
+        for (output, message) in outputs.iter().zip(selected.iter()) {
+            assert!(
+                *message < schedule.num_messages(),
+                "There is no message {} in the schedule",
+                message
+            );
+            self.api()
+                .set_to_expression_public(&output, digests[*message].expr());
+        }
+        self.api().register_output(name, &outputs);
+
+        SHAPublicDigests { digests, outputs }
+    }
 }
 
 impl<B: Builder> SHABuilder for B {}
@@ -28,7 +150,6 @@ pub mod test_utils {
     use plonky2::plonk::circuit_builder::CircuitBuilder;
     use plonky2::plonk::circuit_data::CircuitConfig;
     use plonky2::timed;
-    use plonky2::util::log2_ceil;
     use plonky2::util::timing::TimingTree;
 
     use super::*;
@@ -40,7 +161,7 @@ pub mod test_utils {
     use crate::math::goldilocks::cubic::GoldilocksCubicParameters;
     use crate::math::prelude::*;
     use crate::plonky2::stark::config::{CurtaConfig, CurtaPoseidonGoldilocksConfig};
-    use crate::plonky2::Plonky2Air;
+    use crate::plonky2::{Plonky2Air, ProverAir};
 
     pub fn test_sha<
         'a,
@@ -57,27 +178,79 @@ pub mod test_utils {
         L::Instruction: UintInstructions,
         S: SHAir<BytesBuilder<L>, CYCLE_LENGTH>,
         Chip<L>: Plonky2Air<GoldilocksField, 2>,
+        Chip<L>: ProverAir<GoldilocksField, CurtaPoseidonGoldilocksConfig, 2>,
+        S::Integer: PartialEq + Eq + Debug,
+    {
+        test_sha_variable::<L, S, _, _, CYCLE_LENGTH>(
+            messages.into_iter().map(|msg| (msg, S::num_chunks(msg))),
+            expected_digests,
+        )
+    }
+
+    /// Tests the hashes of messages, each padded to the given number of chunks.
+    pub fn test_sha_variable<
+        'a,
+        L,
+        S,
+        I: IntoIterator<Item = (&'a [u8], usize)>,
+        J: IntoIterator<Item = &'a str>,
+        const CYCLE_LENGTH: usize,
+    >(
+        messages: I,
+        expected_digests: J,
+    ) where
+        L: AirParameters<Field = GoldilocksField, CubicParams = GoldilocksCubicParameters>,
+        L::Instruction: UintInstructions,
+        S: SHAir<BytesBuilder<L>, CYCLE_LENGTH>,
+        Chip<L>: Plonky2Air<GoldilocksField, 2>,
+        Chip<L>: ProverAir<GoldilocksField, CurtaPoseidonGoldilocksConfig, 2>,
+        S::Integer: PartialEq + Eq + Debug,
+    {
+        test_sha_chunks::<L, S, _, _, CYCLE_LENGTH>(
+            None,
+            messages.into_iter().map(|(msg, num_chunks)| {
+                (S::pad_to_chunks(msg, num_chunks), S::num_chunks(msg) - 1)
+            }),
+            expected_digests.into_iter().map(S::decode),
+        )
+    }
+
+    /// Tests the hashes of messages given by their chunks and the index of their digest chunk,
+    /// starting from `midstate` if given and from the initial hash otherwise.
+    pub fn test_sha_chunks<
+        L,
+        S,
+        I: IntoIterator<Item = (Vec<S::Integer>, usize)>,
+        J: IntoIterator<Item = [S::Integer; 8]>,
+        const CYCLE_LENGTH: usize,
+    >(
+        midstate: Option<[S::Integer; 8]>,
+        messages: I,
+        expected_digests: J,
+    ) where
+        L: AirParameters<Field = GoldilocksField, CubicParams = GoldilocksCubicParameters>,
+        L::Instruction: UintInstructions,
+        S: SHAir<BytesBuilder<L>, CYCLE_LENGTH>,
+        Chip<L>: Plonky2Air<GoldilocksField, 2>,
+        Chip<L>: ProverAir<GoldilocksField, CurtaPoseidonGoldilocksConfig, 2>,
         S::Integer: PartialEq + Eq + Debug,
     {
         type C = CurtaPoseidonGoldilocksConfig;
         type Config = <C as CurtaConfig<2>>::GenericConfig;
 
-        let mut end_bits_values = Vec::new();
-        let mut num_messages = 0;
-        let padded_chunks_values = messages
-            .into_iter()
-            .flat_map(|msg| {
-                num_messages += 1;
-                let padded_msg = S::pad(msg);
-                let num_chunks = padded_msg.len() / 16;
-                end_bits_values.extend_from_slice(&vec![GoldilocksField::ZERO; num_chunks - 1]);
-                end_bits_values.push(GoldilocksField::ONE);
-                padded_msg
-            })
-            .collect::<Vec<_>>();
+        let (padded_msgs, digest_chunks): (Vec<_>, Vec<_>) = messages.into_iter().unzip();
+        let schedule = SHADigestSchedule::variable(
+            padded_msgs
+                .iter()
+                .map(|padded_msg| padded_msg.len() / 16)
+                .zip(digest_chunks),
+        );
+        let padded_chunks_values = padded_msgs.concat();
+        let end_bits_values = schedule.end_bits();
+        let digest_bits_values = schedule.digest_bits();
 
-        assert_eq!(end_bits_values.len() * 16, padded_chunks_values.len());
-        let num_rounds = end_bits_values.len();
+        assert_eq!(schedule.num_chunks() * 16, padded_chunks_values.len());
+        let num_rounds = schedule.num_chunks();
         let _ = env_logger::builder().is_test(true).try_init();
         let mut timing = TimingTree::new("test_sha", log::Level::Debug);
 
@@ -87,12 +260,26 @@ pub mod test_utils {
             .map(|_| builder.alloc_array_public::<S::IntRegister>(16))
             .collect::<Vec<_>>();
         let end_bits = builder.alloc_array_public::<BitRegister>(num_rounds);
-        let digest_indices = builder.alloc_array_public(num_messages);
-        let hash_state =
-            builder.sha::<S, CYCLE_LENGTH>(&padded_chunks, &end_bits, &end_bits, digest_indices);
+        let digest_bits = builder.alloc_array_public::<BitRegister>(num_rounds);
+        let digest_indices = builder.alloc_array_public(schedule.num_messages());
+        let midstate_register = midstate.map(|_| builder.alloc_array_public(8));
+        let hash_state = match midstate_register {
+            Some(register) => builder.sha_from_midstate::<S, CYCLE_LENGTH>(
+                &padded_chunks,
+                &end_bits,
+                &digest_bits,
+                digest_indices,
+                register,
+            ),
+            None => builder.sha::<S, CYCLE_LENGTH>(
+                &padded_chunks,
+                &end_bits,
+                &digest_bits,
+                digest_indices,
+            ),
+        };
 
-        let num_rows_degree = log2_ceil(CYCLE_LENGTH * num_rounds);
-        let num_rows = 1 << num_rows_degree;
+        let num_rows = schedule.num_rows(CYCLE_LENGTH);
         let stark = builder.build::<C, 2>(num_rows);
 
         // Build the recursive circuit.
@@ -109,14 +296,20 @@ pub mod test_utils {
         let mut writer_data = AirWriterData::new(&stark.air_data, num_rows);
         let mut writer = writer_data.public_writer();
 
-        let mut current_state = S::INITIAL_HASH;
+        let initial_state = midstate.unwrap_or(S::INITIAL_HASH);
+        if let Some(register) = midstate_register {
+            writer.write_array(&register, initial_state.map(S::int_to_field_value));
+        }
+
+        for (register, index) in digest_indices.iter().zip_eq(schedule.digest_indices()) {
+            writer.write(&register, &GoldilocksField::from_canonical_usize(index));
+        }
+
+        let mut current_state = initial_state;
         let mut hash_iter = hash_state.iter();
-        let mut digest_indices_iter = digest_indices.iter();
-        for (i, (((message, register), end_bit), end_bit_value)) in padded_chunks_values
+        for (i, (message, register)) in padded_chunks_values
             .chunks_exact(16)
             .zip_eq(padded_chunks.iter())
-            .zip_eq(end_bits.iter())
-            .zip_eq(end_bits_values.iter())
             .enumerate()
         {
             writer.write_array(register, message.iter().map(|x| S::int_to_field_value(*x)));
@@ -124,18 +317,23 @@ pub mod test_utils {
             let pre_processed = S::pre_process(message);
             current_state = S::process(current_state, &pre_processed);
             let state = current_state.map(S::int_to_field_value);
-            if *end_bit_value == GoldilocksField::ONE {
-                writer.write(
-                    &digest_indices_iter.next().unwrap(),
-                    &GoldilocksField::from_canonical_usize(i),
-                );
+            if digest_bits_values[i] {
                 let h: S::StateVariable = *hash_iter.next().unwrap();
                 let array: ArrayRegister<_> = h.into();
                 writer.write_array(&array, &state);
-                current_state = S::INITIAL_HASH;
+            }
+            if end_bits_values[i] {
+                current_state = initial_state;
             }
 
-            writer.write(&end_bit, end_bit_value);
+            writer.write(
+                &end_bits.get(i),
+                &GoldilocksField::from_canonical_usize(end_bits_values[i] as usize),
+            );
+            writer.write(
+                &digest_bits.get(i),
+                &GoldilocksField::from_canonical_usize(digest_bits_values[i] as usize),
+            );
         }
 
         timed!(timing, "write input", {
@@ -156,8 +354,7 @@ pub mod test_utils {
             let digest = writer
                 .read_array::<_, 8>(&array)
                 .map(|x| S::field_value_to_int(&x));
-            let expected_digest = S::decode(expected);
-            assert_eq!(digest, expected_digest);
+            assert_eq!(digest[..S::DIGEST_LENGTH], expected[..S::DIGEST_LENGTH]);
         }
 
         let (trace, public) = (writer_data.trace, writer_data.public);
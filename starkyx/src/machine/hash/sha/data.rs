@@ -7,6 +7,9 @@ pub struct SHAData<T, const LENGTH: usize> {
     pub public: SHAPublicData<T>,
     pub trace: SHATraceData<LENGTH>,
     pub memory: SHAMemory<T>,
+    /// The registers holding the state at the start of the chunk of the current row, when each
+    /// chunk is compressed from its own state rather than from the initial hash.
+    pub(crate) chunk_state: Option<Vec<T>>,
     pub degree: usize,
 }
 
@@ -20,6 +23,9 @@ pub struct SHATraceData<const LENGTH: usize> {
     pub(crate) is_preprocessing: BitRegister,
     pub(crate) process_id: ElementRegister,
     pub(crate) cycle_end_bit: BitRegister,
+    /// A register equal to one in the first row and zero in the others, if there are chunks or
+    /// digests in the trace.
+    pub(crate) first_row: Option<ElementRegister>,
     pub index: ElementRegister,
     pub is_dummy: BitRegister,
 }
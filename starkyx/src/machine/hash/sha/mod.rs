@@ -1,5 +1,7 @@
 pub mod algorithm;
 pub mod builder;
 pub mod data;
+pub mod outputs;
 pub mod sha256;
 pub mod sha512;
+pub mod stream;
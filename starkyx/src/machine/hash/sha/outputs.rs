@@ -0,0 +1,174 @@
+//! Digests of the SHA chips exposed as outputs of the proof.
+//!
+//! The digests computed by [`SHAir::sha`] are public registers, whose positions in the public
+//! inputs depend on every other public register of the AIR, and the chip expects the end bits,
+//! digest bits and digest indices of the messages as inputs. A [`SHADigestSchedule`] computes
+//! these from the number of chunks of each message, and [`SHABuilder::sha_public_digests`]
+//! sets them as constants and copies the digests of a selection of messages to an array of global
+//! values, named as an output of the STARK. The verifier reads the selected digests from the proof
+//! by that name with [`read_digests`], in the order in which they were selected.
+//!
+//! [`SHABuilder::sha_public_digests`]: super::builder::SHABuilder::sha_public_digests
+
+use core::ops::Range;
+
+use anyhow::{ensure, Result};
+use plonky2::util::log2_ceil;
+
+use super::algorithm::{SHAPure, SHAir};
+use crate::chip::register::array::ArrayRegister;
+use crate::machine::builder::Builder;
+use crate::plonky2::stark::outputs::ProofOutputs;
+
+/// The layout of the chunks of messages hashed by a SHA chip, and the positions of their digests.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SHADigestSchedule {
+    /// The number of chunks of each message.
+    chunk_counts: Vec<usize>,
+    /// The index of the digest chunk of each message among its chunks.
+    digest_chunks: Vec<usize>,
+}
+
+impl SHADigestSchedule {
+    /// A schedule of messages with the given numbers of chunks, whose digests are the states
+    /// after their last chunks.
+    pub fn new(chunk_counts: Vec<usize>) -> Self {
+        Self::variable(
+            chunk_counts
+                .into_iter()
+                .map(|num_chunks| (num_chunks, num_chunks - 1)),
+        )
+    }
+
+    /// A schedule of messages given by their numbers of chunks and the indices of their digest
+    /// chunks, as in the variable-length mode of [`SHAPure::pad_to_chunks`].
+    pub fn variable(messages: impl IntoIterator<Item = (usize, usize)>) -> Self {
+        let (chunk_counts, digest_chunks): (Vec<_>, Vec<_>) = messages.into_iter().unzip();
+        assert!(
+            !chunk_counts.is_empty(),
+            "A schedule needs at least one message"
+        );
+        for (num_chunks, digest_chunk) in chunk_counts.iter().zip(digest_chunks.iter()) {
+            assert!(
+                digest_chunk < num_chunks,
+                "The digest chunk {} is not one of the {} chunks of the message",
+                digest_chunk,
+                num_chunks
+            );
+        }
+        Self {
+            chunk_counts,
+            digest_chunks,
+        }
+    }
+
+    /// The schedule of byte messages, each padded to its own number of chunks.
+    pub fn from_messages<S: SHAPure<CYCLE_LENGTH>, const CYCLE_LENGTH: usize>(
+        messages: &[&[u8]],
+    ) -> Self {
+        Self::new(messages.iter().map(|msg| S::num_chunks(msg)).collect())
+    }
+
+    pub fn num_messages(&self) -> usize {
+        self.chunk_counts.len()
+    }
+
+    pub fn num_chunks(&self) -> usize {
+        self.chunk_counts.iter().sum()
+    }
+
+    /// The number of rows of a table whose chunks take `cycle_length` rows.
+    pub fn num_rows(&self, cycle_length: usize) -> usize {
+        1 << log2_ceil(cycle_length * self.num_chunks())
+    }
+
+    /// The indices of the chunks of the message `message`.
+    pub fn chunk_range(&self, message: usize) -> Range<usize> {
+        let start = self.chunk_counts[..message].iter().sum();
+        start..start + self.chunk_counts[message]
+    }
+
+    /// The end bit of each chunk, which is set at the last chunk of each message.
+    pub fn end_bits(&self) -> Vec<bool> {
+        self.chunk_counts
+            .iter()
+            .flat_map(|num_chunks| (0..*num_chunks).map(move |i| i == num_chunks - 1))
+            .collect()
+    }
+
+    /// The digest bit of each chunk, which is set at the digest chunk of each message.
+    pub fn digest_bits(&self) -> Vec<bool> {
+        self.chunk_counts
+            .iter()
+            .zip(self.digest_chunks.iter())
+            .flat_map(|(num_chunks, digest_chunk)| {
+                (0..*num_chunks).map(move |i| i == *digest_chunk)
+            })
+            .collect()
+    }
+
+    /// The index of the digest chunk of each message among all chunks, which are the digest
+    /// indices taken by [`SHAir::sha`].
+    pub fn digest_indices(&self) -> Vec<usize> {
+        (0..self.num_messages())
+            .map(|message| self.chunk_range(message).start + self.digest_chunks[message])
+            .collect()
+    }
+}
+
+/// The digests of the messages of a schedule, see `SHABuilder::sha_public_digests`.
+#[derive(Debug, Clone)]
+pub struct SHAPublicDigests<D> {
+    /// The digests of all messages, as public registers written by the prover.
+    pub digests: Vec<D>,
+    /// The digests of the selected messages, in the order of the selection, as global registers.
+    pub outputs: ArrayRegister<D>,
+}
+
+/// Reads the digests of the output `name` of a proof, in the order in which they were selected
+/// by `SHABuilder::sha_public_digests`.
+pub fn read_digests<B: Builder, S: SHAir<B, CYCLE_LENGTH>, const CYCLE_LENGTH: usize>(
+    outputs: &ProofOutputs<B::Field>,
+    name: &str,
+) -> Result<Vec<[S::Integer; 8]>> {
+    let words = outputs.values::<S::IntRegister>(name)?;
+    ensure!(
+        words.len() % 8 == 0,
+        "Output {} has {} words, which is not a number of digests",
+        name,
+        words.len()
+    );
+    Ok(words
+        .chunks_exact(8)
+        .map(|digest| core::array::from_fn(|i| S::field_value_to_int(&digest[i])))
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sha_digest_schedule() {
+        let schedule = SHADigestSchedule::variable([(1, 0), (5, 3), (2, 1)]);
+        assert_eq!(schedule.num_messages(), 3);
+        assert_eq!(schedule.num_chunks(), 8);
+        assert_eq!(schedule.num_rows(64), 512);
+        assert_eq!(schedule.num_rows(80), 1024);
+        assert_eq!(schedule.chunk_range(2), 6..8);
+        assert_eq!(
+            schedule.end_bits(),
+            vec![true, false, false, false, false, true, false, true]
+        );
+        assert_eq!(
+            schedule.digest_bits(),
+            vec![true, false, false, false, true, false, false, true]
+        );
+        assert_eq!(schedule.digest_indices(), vec![0, 4, 7]);
+
+        // Without padding chunks, the digest chunks are the last ones.
+        let schedule = SHADigestSchedule::new(vec![1, 5, 2]);
+        assert_eq!(schedule.end_bits(), schedule.digest_bits());
+        assert_eq!(schedule.digest_indices(), vec![0, 5, 7]);
+    }
+}
@@ -5,7 +5,7 @@ use crate::chip::memory::time::Time;
 use crate::chip::register::array::ArrayRegister;
 use crate::chip::register::bit::BitRegister;
 use crate::chip::register::element::ElementRegister;
-use crate::chip::register::Register;
+use crate::chip::register::{Register, RegisterSerializable};
 use crate::chip::uint::operations::instruction::UintInstructions;
 use crate::chip::uint::register::{U32Register, U64Register};
 use crate::chip::uint::util::{u32_from_le_field_bytes, u32_to_le_field_bytes};
@@ -55,14 +55,26 @@ where
 
     fn load_state(
         builder: &mut BytesBuilder<L>,
-        hash_state_public: &[Self::StateVariable],
+        hash_state: &[Self::StateVariable],
         digest_indices: ArrayRegister<ElementRegister>,
+        first_row: Option<ElementRegister>,
     ) -> Self::StatePointer {
         let state_ptr = builder.uninit_slice();
 
-        for (i, h_slice) in digest_indices.iter().zip(hash_state_public.iter()) {
+        for (i, h_slice) in digest_indices.iter().zip(hash_state.iter()) {
+            let multiplicity = h_slice
+                .is_trace()
+                .then(|| first_row.expect("Digests in the trace are freed from the first row"));
             for (j, h) in h_slice.split().iter().enumerate() {
-                builder.free(&state_ptr.get(j), *h, &Time::from_element(i));
+                match multiplicity {
+                    Some(m) => builder.free_with_multiplicity(
+                        &state_ptr.get(j),
+                        *h,
+                        &Time::from_element(i),
+                        m,
+                    ),
+                    None => builder.free(&state_ptr.get(j), *h, &Time::from_element(i)),
+                }
             }
         }
 
@@ -201,13 +213,26 @@ where
 mod tests {
     use core::iter;
 
+    use anyhow::Result;
     use plonky2::field::goldilocks_field::GoldilocksField;
+    use plonky2::util::timing::TimingTree;
+    use rand::Rng;
     use serde::{Deserialize, Serialize};
 
     use super::*;
     use crate::chip::uint::operations::instruction::UintInstruction;
-    use crate::machine::hash::sha::builder::test_utils::test_sha;
+    use crate::machine::hash::sha::algorithm::SHAPure;
+    use crate::machine::hash::sha::builder::test_utils::{
+        test_sha, test_sha_chunks, test_sha_variable,
+    };
+    use crate::machine::hash::sha::builder::SHABuilder;
+    use crate::machine::hash::sha::outputs::{read_digests, SHADigestSchedule};
+    use crate::machine::hash::sha::sha256::INITIAL_HASH;
     use crate::math::goldilocks::cubic::GoldilocksCubicParameters;
+    use crate::math::prelude::*;
+    use crate::plonky2::stark::config::CurtaPoseidonGoldilocksConfig;
+    use crate::plonky2::stark::outputs::{AirOutputs, ProofOutputs};
+    use crate::prelude::{AirWriter, AirWriterData};
 
     #[derive(Clone, Debug, Serialize, Deserialize)]
     pub struct SHA256Test;
@@ -272,4 +297,351 @@ mod tests {
             ],
         );
     }
+
+    #[test]
+    fn test_sha256_variable_length() {
+        let short_msg = b"abc";
+        let short_expected_digest =
+            "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad";
+        let long_msg = (0..200).collect::<Vec<u8>>();
+        let long_expected_digest =
+            "1901da1c9f699b48f6b2636e65cbf73abf99d0441ef67f5c540a42f7051dec6f";
+        let empty_expected_digest =
+            "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855";
+
+        // Every message occupies the same number of chunks.
+        let max_chunks = 4;
+        test_sha_variable::<SHA256Test, SHA256, _, _, 64>(
+            [
+                (short_msg.as_slice(), max_chunks),
+                (long_msg.as_slice(), max_chunks),
+                (b"".as_slice(), max_chunks),
+            ],
+            [
+                short_expected_digest,
+                long_expected_digest,
+                empty_expected_digest,
+            ],
+        );
+    }
+
+    #[test]
+    fn test_sha256_midstate() {
+        let msg = (0..200).collect::<Vec<u8>>();
+        let expected_digest = "1901da1c9f699b48f6b2636e65cbf73abf99d0441ef67f5c540a42f7051dec6f";
+
+        // Split the padded message after its first two chunks, the prefix.
+        let padded_msg = SHA256::pad(&msg);
+        let (prefix, suffix) = padded_msg.split_at(2 * 16);
+        let midstate = prefix.chunks_exact(16).fold(INITIAL_HASH, |state, chunk| {
+            SHA256::process(state, &SHA256::pre_process(chunk))
+        });
+
+        // The state after the prefix is exported as the digest of its last chunk, along with the
+        // hash of another message.
+        let other_msg = b"abc";
+        test_sha_chunks::<SHA256Test, SHA256, _, _, 64>(
+            None,
+            [
+                (prefix.to_vec(), 1),
+                (SHA256::pad(other_msg), SHA256::num_chunks(other_msg) - 1),
+            ],
+            [
+                midstate,
+                SHA256::decode("ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"),
+            ],
+        );
+
+        // Hashing the suffix from the midstate gives the hash of the whole message.
+        test_sha_chunks::<SHA256Test, SHA256, _, _, 64>(
+            Some(midstate),
+            [(suffix.to_vec(), 1)],
+            [SHA256::decode(expected_digest)],
+        );
+    }
+
+    #[derive(Clone, Debug, Serialize, Deserialize)]
+    pub struct SHA256BatchTest;
+
+    impl AirParameters for SHA256BatchTest {
+        type Field = GoldilocksField;
+        type CubicParams = GoldilocksCubicParameters;
+
+        type Instruction = UintInstruction;
+
+        const NUM_FREE_COLUMNS: usize = 419;
+        const EXTENDED_COLUMNS: usize = 918;
+    }
+
+    #[test]
+    fn test_sha256_batch() {
+        type F = GoldilocksField;
+        type C = CurtaPoseidonGoldilocksConfig;
+
+        let _ = env_logger::builder().is_test(true).try_init();
+        let mut timing = TimingTree::new("test_sha256_batch", log::Level::Debug);
+
+        // Messages of one, four and one chunks, which take six chunks instead of twelve.
+        let long_msg = (0..200).collect::<Vec<u8>>();
+        let messages: [(&[u8], &str); 3] = [
+            (
+                b"abc".as_slice(),
+                "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad",
+            ),
+            (
+                long_msg.as_slice(),
+                "1901da1c9f699b48f6b2636e65cbf73abf99d0441ef67f5c540a42f7051dec6f",
+            ),
+            (
+                b"".as_slice(),
+                "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855",
+            ),
+        ];
+
+        // Build the stark.
+        let mut builder = BytesBuilder::<SHA256BatchTest>::new();
+        let padded_chunks = messages
+            .iter()
+            .map(|(msg, _)| {
+                (0..SHA256::num_chunks(msg))
+                    .map(|_| builder.alloc_array_public::<U32Register>(16))
+                    .collect::<Vec<_>>()
+            })
+            .collect::<Vec<_>>();
+        let batch = builder.sha_batch::<SHA256, 64>(&padded_chunks);
+
+        let num_rows = batch.schedule.num_rows(64);
+        assert_eq!(num_rows, 1 << 9);
+        let stark = builder.build::<C, 2>(num_rows);
+
+        // Write trace.
+        let mut writer_data = AirWriterData::new(&stark.air_data, num_rows);
+        let mut writer = writer_data.public_writer();
+
+        for (i, ((msg, expected), registers)) in
+            messages.iter().zip(padded_chunks.iter()).enumerate()
+        {
+            let padded_msg = SHA256::pad(msg);
+            for (register, chunk) in registers.iter().zip(padded_msg.chunks_exact(16)) {
+                writer.write_array(register, chunk.iter().map(|w| u32_to_le_field_bytes(*w)));
+            }
+            writer.write_array(
+                &batch.digest(i).as_array(),
+                SHA256::decode(expected).map(u32_to_le_field_bytes),
+            );
+        }
+
+        stark.air_data.write_global_instructions(&mut writer);
+
+        // Each row holds the index of its message.
+        let row_message_ids = batch.schedule.row_message_ids(64);
+        for mut chunk in writer_data.chunks(num_rows) {
+            for (i, message_id) in row_message_ids.iter().enumerate() {
+                let mut writer = chunk.window_writer(i);
+                stark.air_data.write_trace_instructions(&mut writer);
+                assert_eq!(
+                    writer.read(&batch.message_id),
+                    F::from_canonical_usize(*message_id)
+                );
+            }
+        }
+
+        let (trace, public) = (writer_data.trace, writer_data.public);
+
+        let proof = stark.prove(&trace, &public, &mut timing).unwrap();
+        stark.verify(proof, &public).unwrap();
+    }
+
+    #[derive(Clone, Debug, Serialize, Deserialize)]
+    pub struct SHA256CompressTest;
+
+    impl AirParameters for SHA256CompressTest {
+        type Field = GoldilocksField;
+        type CubicParams = GoldilocksCubicParameters;
+
+        type Instruction = UintInstruction;
+
+        const NUM_FREE_COLUMNS: usize = 451;
+        const EXTENDED_COLUMNS: usize = 960;
+    }
+
+    #[test]
+    fn test_sha256_compress() {
+        type C = CurtaPoseidonGoldilocksConfig;
+
+        let _ = env_logger::builder().is_test(true).try_init();
+        let mut timing = TimingTree::new("test_sha256_compress", log::Level::Debug);
+
+        // The compressions of the chunks of a padded message, chained from the initial hash, and
+        // a compression of a random chunk with a random state.
+        let long_msg = (0..200).collect::<Vec<u8>>();
+        let expected_digest = "1901da1c9f699b48f6b2636e65cbf73abf99d0441ef67f5c540a42f7051dec6f";
+        let padded_msg = SHA256::pad(&long_msg);
+        let num_chained = padded_msg.len() / 16;
+        let mut rng = rand::thread_rng();
+        let random_state: [u32; 8] = rng.gen();
+        let random_chunk: [u32; 16] = rng.gen();
+        let chunk_values = padded_msg
+            .chunks_exact(16)
+            .chain(iter::once(random_chunk.as_slice()))
+            .collect::<Vec<_>>();
+
+        // Build the stark.
+        let mut builder = BytesBuilder::<SHA256CompressTest>::new();
+        let outputs = (0..=num_chained)
+            .map(|_| builder.alloc_public::<SHA256DigestRegister>())
+            .collect::<Vec<_>>();
+        let chunks = (0..=num_chained)
+            .map(|_| builder.alloc_array_public::<U32Register>(16))
+            .collect::<Vec<_>>();
+        let initial_hash =
+            builder.constant_array::<U32Register>(&INITIAL_HASH.map(u32_to_le_field_bytes));
+        let random_state_register = builder.alloc_array_public::<U32Register>(8);
+        let states = iter::once(initial_hash)
+            .chain(
+                outputs[..num_chained - 1]
+                    .iter()
+                    .map(|output| output.as_array()),
+            )
+            .chain(iter::once(random_state_register))
+            .collect::<Vec<_>>();
+        builder.sha_compress_with_outputs::<SHA256, 64>(&states, &chunks, &outputs);
+
+        let num_rows = 1 << 9;
+        let stark = builder.build::<C, 2>(num_rows);
+
+        // Write trace.
+        let mut writer_data = AirWriterData::new(&stark.air_data, num_rows);
+        let mut writer = writer_data.public_writer();
+
+        writer.write_array(
+            &random_state_register,
+            random_state.map(u32_to_le_field_bytes),
+        );
+        let mut state = INITIAL_HASH;
+        let mut output_values = Vec::new();
+        for chunk in padded_msg.chunks_exact(16) {
+            state = SHA256::compress(state, chunk);
+            output_values.push(state);
+        }
+        assert_eq!(state, SHA256::decode(expected_digest));
+        output_values.push(SHA256::compress(random_state, &random_chunk));
+
+        for ((register, chunk), (output, value)) in chunks
+            .iter()
+            .zip(chunk_values.iter())
+            .zip(outputs.iter().zip(output_values.iter()))
+        {
+            writer.write_array(register, chunk.iter().map(|w| u32_to_le_field_bytes(*w)));
+            writer.write_array(&output.as_array(), value.map(u32_to_le_field_bytes));
+        }
+
+        stark.air_data.write_global_instructions(&mut writer);
+        for mut chunk in writer_data.chunks(num_rows) {
+            for i in 0..num_rows {
+                let mut writer = chunk.window_writer(i);
+                stark.air_data.write_trace_instructions(&mut writer);
+            }
+        }
+
+        let (trace, public) = (writer_data.trace, writer_data.public);
+
+        let proof = stark.prove(&trace, &public, &mut timing).unwrap();
+        stark.verify(proof, &public).unwrap();
+    }
+
+    #[test]
+    fn test_sha256_public_digests() {
+        type C = CurtaPoseidonGoldilocksConfig;
+        type B = BytesBuilder<SHA256Test>;
+
+        let _ = env_logger::builder().is_test(true).try_init();
+        let mut timing = TimingTree::new("test_sha256_public_digests", log::Level::Debug);
+
+        // The second message is padded to five chunks, with its digest after the fourth one.
+        let long_msg = (0..200).collect::<Vec<u8>>();
+        let messages: [(&[u8], usize, &str); 3] = [
+            (
+                b"abc".as_slice(),
+                1,
+                "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad",
+            ),
+            (
+                long_msg.as_slice(),
+                5,
+                "1901da1c9f699b48f6b2636e65cbf73abf99d0441ef67f5c540a42f7051dec6f",
+            ),
+            (
+                b"".as_slice(),
+                1,
+                "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855",
+            ),
+        ];
+        let schedule = SHADigestSchedule::variable(
+            messages
+                .iter()
+                .map(|(msg, num_chunks, _)| (*num_chunks, SHA256::num_chunks(msg) - 1)),
+        );
+        assert_eq!(schedule.digest_indices(), vec![0, 4, 6]);
+        let selected = [2, 0];
+
+        // Build the stark.
+        let mut builder = B::new();
+        let padded_chunks = (0..schedule.num_chunks())
+            .map(|_| builder.alloc_array_public::<U32Register>(16))
+            .collect::<Vec<_>>();
+        let sha_digests = builder.sha_public_digests::<SHA256, 64>(
+            "digests",
+            &padded_chunks,
+            &schedule,
+            &selected,
+        );
+
+        let num_rows = schedule.num_rows(64);
+        let stark = builder.build::<C, 2>(num_rows);
+
+        // Write trace.
+        let mut writer_data = AirWriterData::new(&stark.air_data, num_rows);
+        let mut writer = writer_data.public_writer();
+
+        for (i, (msg, num_chunks, expected)) in messages.iter().enumerate() {
+            let padded_msg = SHA256::pad_to_chunks(msg, *num_chunks);
+            for (register, chunk) in padded_chunks[schedule.chunk_range(i)]
+                .iter()
+                .zip(padded_msg.chunks_exact(16))
+            {
+                writer.write_array(register, chunk.iter().map(|w| u32_to_le_field_bytes(*w)));
+            }
+            writer.write_array(
+                &sha_digests.digests[i].as_array(),
+                SHA256::decode(expected).map(u32_to_le_field_bytes),
+            );
+        }
+
+        stark.air_data.write_global_instructions(&mut writer);
+        for mut chunk in writer_data.chunks(num_rows) {
+            for i in 0..num_rows {
+                let mut writer = chunk.window_writer(i);
+                stark.air_data.write_trace_instructions(&mut writer);
+            }
+        }
+
+        let (trace, public) = (writer_data.trace, writer_data.public);
+
+        let proof = stark.prove(&trace, &public, &mut timing).unwrap();
+        stark.verify(proof.clone(), &public).unwrap();
+
+        // The verifier reads the selected digests by the name of the output.
+        struct Digests(Vec<[u32; 8]>);
+        impl AirOutputs<GoldilocksField> for Digests {
+            fn from_outputs(outputs: &ProofOutputs<GoldilocksField>) -> Result<Self> {
+                read_digests::<B, SHA256, 64>(outputs, "digests").map(Digests)
+            }
+        }
+        let Digests(digests) = proof.outputs::<Digests, _>(&stark.stark).unwrap();
+        assert_eq!(
+            digests,
+            selected.map(|message| SHA256::decode(messages[message].2))
+        );
+    }
 }
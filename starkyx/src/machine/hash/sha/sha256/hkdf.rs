@@ -0,0 +1,378 @@
+//! HKDF over HMAC-SHA256, with private input keying material.
+//!
+//! HKDF-Extract computes the pseudorandom key `PRK = HMAC(salt, IKM)`, and HKDF-Expand the blocks
+//! `T(i) = HMAC(PRK, T(i - 1) || info || i)` of the output keying material, where `T(0)` is empty.
+//! The salt and the info are parameters of the gadget. The input keying material is given by
+//! chunks in the trace, and the pseudorandom key and the inner digests of all the HMACs are
+//! private digests, so that the output keying material is the only public value which depends on
+//! the input keying material.
+
+use serde::{Deserialize, Serialize};
+
+use super::hmac::{
+    digest_to_bytes, hmac_key_block, hmac_message_chunks, hmac_sha256, HMACChunks,
+    HMACSHA256Register, DIGEST_BYTES, IPAD, OPAD,
+};
+use super::register::SHA256DigestRegister;
+use crate::chip::register::array::ArrayRegister;
+use crate::chip::register::{Register, RegisterSerializable};
+use crate::chip::uint::operations::instruction::UintInstructions;
+use crate::chip::uint::register::U32Register;
+use crate::chip::uint::util::u32_to_le_field_bytes;
+use crate::chip::AirParameters;
+use crate::machine::builder::Builder;
+use crate::machine::bytes::builder::BytesBuilder;
+
+/// The HMACs of an HKDF derivation.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HKDFSHA256Register {
+    /// The HMAC of HKDF-Extract, whose digest is the private pseudorandom key.
+    pub extract: HMACSHA256Register,
+    /// The HMACs of HKDF-Expand, whose digests are the public blocks `T(1), ..., T(N)`.
+    pub expand: Vec<HMACSHA256Register>,
+}
+
+pub trait HKDFSHA256Builder: Builder {
+    /// Derives output keying material of `len_in_bytes` bytes from private input keying
+    /// material, with the salt `salt` and the info `info`.
+    ///
+    /// The input keying material of each derivation is given by trace registers holding the
+    /// padded chunks of the inner hash of HKDF-Extract after the chunk of the salt, see
+    /// [`hmac_message_chunks`], whose values are written in the first row only. They are copied
+    /// to the other rows and marked as secret.
+    ///
+    /// Returns the HMACs of each derivation. Their private digests must be written in the first
+    /// row and their public digests before the global instructions, as given by
+    /// [`hkdf_sha256_digests`].
+    fn hkdf_sha256(
+        &mut self,
+        ikm_chunks: &[Vec<ArrayRegister<U32Register>>],
+        salt: &[u8],
+        info: &[u8],
+        len_in_bytes: usize,
+    ) -> Vec<HKDFSHA256Register>;
+}
+
+impl<L: AirParameters> HKDFSHA256Builder for BytesBuilder<L>
+where
+    L::Instruction: UintInstructions,
+{
+    fn hkdf_sha256(
+        &mut self,
+        ikm_chunks: &[Vec<ArrayRegister<U32Register>>],
+        salt: &[u8],
+        info: &[u8],
+        len_in_bytes: usize,
+    ) -> Vec<HKDFSHA256Register> {
+        let num_blocks = hkdf_num_blocks(len_in_bytes);
+        let mut hmacs = HMACChunks::new(self);
+
+        // The salt is public, so its key pads are constant.
+        let salt_block = hmac_key_block(salt);
+        let salt_pads = [IPAD, OPAD].map(|pad| {
+            self.constant_array::<U32Register>(
+                &salt_block.map(|word| u32_to_le_field_bytes(word ^ pad)),
+            )
+        });
+
+        let paddings = (1..=num_blocks)
+            .map(|i| hkdf_expand_padding(info, i))
+            .collect::<Vec<_>>();
+
+        let derivations = ikm_chunks
+            .iter()
+            .map(|chunks| {
+                for chunk in chunks.iter() {
+                    assert!(
+                        chunk.is_trace(),
+                        "The input keying material must be in the trace"
+                    );
+                    for word in chunk.iter() {
+                        self.set_to_expression_transition(&word.next(), word.expr());
+                    }
+                    self.api.mark_secret(chunk);
+                }
+                let prk = HMACChunks::alloc_private_digest(self);
+                let extract = hmacs.push(self, &salt_pads, chunks, prk);
+                let prk_pads = hmacs.key_pads(self, &prk.as_array());
+
+                // The message of `T(i)` starts with `T(i - 1)` for `i > 1`, so the first chunk of
+                // the message holds the previous block, and the rest of the message is constant.
+                let mut message_chunks = Vec::with_capacity(num_blocks);
+                for (i, padding) in paddings.iter().enumerate() {
+                    let mut chunks = padding
+                        .chunks_exact(16)
+                        .map(|chunk| {
+                            self.constant_array::<U32Register>(
+                                &chunk
+                                    .iter()
+                                    .map(|word| u32_to_le_field_bytes(*word))
+                                    .collect::<Vec<_>>(),
+                            )
+                        })
+                        .collect::<Vec<_>>();
+                    if i > 0 {
+                        let first_chunk = self.alloc_array_public::<U32Register>(16);
+                        for (word, value) in first_chunk
+                            .get_subarray(8..16)
+                            .iter()
+                            .zip(chunks[0].get_subarray(8..16).iter())
+                        {
+                            self.api.set_to_expression_public(&word, value.expr());
+                        }
+                        chunks[0] = first_chunk;
+                    }
+                    message_chunks.push(chunks);
+                }
+
+                let expand = (0..num_blocks)
+                    .map(|i| {
+                        let digest = match message_chunks.get(i + 1) {
+                            Some(next_chunks) => {
+                                SHA256DigestRegister::from_array(next_chunks[0].get_subarray(0..8))
+                            }
+                            None => self.alloc_public::<SHA256DigestRegister>(),
+                        };
+                        hmacs.push(self, &prk_pads, &message_chunks[i], digest)
+                    })
+                    .collect();
+
+                HKDFSHA256Register { extract, expand }
+            })
+            .collect();
+
+        hmacs.hash(self);
+        derivations
+    }
+}
+
+/// The number `N` of blocks of output keying material of `len_in_bytes` bytes.
+pub fn hkdf_num_blocks(len_in_bytes: usize) -> usize {
+    let num_blocks = len_in_bytes.div_ceil(DIGEST_BYTES);
+    assert!(
+        (1..=255).contains(&num_blocks),
+        "The output must have between 1 and 255 blocks"
+    );
+    num_blocks
+}
+
+/// The padded chunks of the inner hash of `T(i)` after the chunk of the key, with zeros in place
+/// of `T(i - 1)` for `i > 1`.
+fn hkdf_expand_padding(info: &[u8], i: usize) -> Vec<u32> {
+    let mut msg = match i {
+        1 => Vec::new(),
+        _ => vec![0u8; DIGEST_BYTES],
+    };
+    msg.extend_from_slice(info);
+    msg.push(i as u8);
+    hmac_message_chunks(&msg)
+}
+
+/// The inner digests and the digests of the HMACs of HKDF-Extract and of HKDF-Expand.
+#[allow(clippy::type_complexity)]
+pub fn hkdf_sha256_digests(
+    salt: &[u8],
+    ikm: &[u8],
+    info: &[u8],
+    len_in_bytes: usize,
+) -> (([u32; 8], [u32; 8]), Vec<([u32; 8], [u32; 8])>) {
+    let num_blocks = hkdf_num_blocks(len_in_bytes);
+    let extract = hmac_sha256(salt, ikm);
+    let prk = digest_to_bytes(extract.1);
+
+    let mut expand: Vec<([u32; 8], [u32; 8])> = Vec::with_capacity(num_blocks);
+    for i in 1..=num_blocks {
+        let mut msg = match expand.last() {
+            None => Vec::new(),
+            Some((_, t_prev)) => digest_to_bytes(*t_prev),
+        };
+        msg.extend_from_slice(info);
+        msg.push(i as u8);
+        expand.push(hmac_sha256(&prk, &msg));
+    }
+
+    (extract, expand)
+}
+
+/// HKDF with HMAC-SHA256, returning the first `len_in_bytes` bytes of output keying material.
+pub fn hkdf_sha256(salt: &[u8], ikm: &[u8], info: &[u8], len_in_bytes: usize) -> Vec<u8> {
+    let (_, expand) = hkdf_sha256_digests(salt, ikm, info, len_in_bytes);
+    let mut okm = expand
+        .into_iter()
+        .flat_map(|(_, t)| digest_to_bytes(t))
+        .collect::<Vec<_>>();
+    okm.truncate(len_in_bytes);
+    okm
+}
+
+#[cfg(test)]
+mod tests {
+    use plonky2::field::goldilocks_field::GoldilocksField;
+    use plonky2::timed;
+    use plonky2::util::log2_ceil;
+    use plonky2::util::timing::TimingTree;
+
+    use super::*;
+    use crate::chip::uint::operations::instruction::UintInstruction;
+    use crate::math::goldilocks::cubic::GoldilocksCubicParameters;
+    use crate::plonky2::stark::config::CurtaPoseidonGoldilocksConfig;
+    use crate::prelude::{AirWriter, AirWriterData};
+
+    #[derive(Clone, Debug, Serialize, Deserialize)]
+    pub struct HKDFSHA256Test;
+
+    impl AirParameters for HKDFSHA256Test {
+        type Field = GoldilocksField;
+        type CubicParams = GoldilocksCubicParameters;
+        type Instruction = UintInstruction;
+
+        const NUM_FREE_COLUMNS: usize = 1251;
+        const EXTENDED_COLUMNS: usize = 2832;
+    }
+
+    fn to_field_bytes(digest: [u32; 8]) -> [GoldilocksField; 32] {
+        let bytes = digest
+            .iter()
+            .flat_map(|word| u32_to_le_field_bytes::<GoldilocksField>(*word))
+            .collect::<Vec<_>>();
+        bytes.try_into().unwrap()
+    }
+
+    #[test]
+    fn test_hkdf_sha256_pure() {
+        // Test cases 1 and 3 of RFC 5869.
+        let ikm = [0x0b; 22];
+        let salt = (0x00..=0x0c).collect::<Vec<u8>>();
+        let info = (0xf0..=0xf9).collect::<Vec<u8>>();
+
+        let ((_, prk), _) = hkdf_sha256_digests(&salt, &ikm, &info, 42);
+        assert_eq!(
+            hex::encode(digest_to_bytes(prk)),
+            "077709362c2e32df0ddc3f0dc47bba6390b6c73bb50f9c3122ec844ad7c2b3e5"
+        );
+        assert_eq!(
+            hex::encode(hkdf_sha256(&salt, &ikm, &info, 42)),
+            "3cb25f25faacd57a90434f64d0362f2a2d2d0a90cf1a5a4c5db02d56ecc4c5bf34007208d5b887185865"
+        );
+
+        let ((_, prk), _) = hkdf_sha256_digests(b"", &ikm, b"", 42);
+        assert_eq!(
+            hex::encode(digest_to_bytes(prk)),
+            "19ef24a32c717b167f33a91d6f648bdf96596776afdb6377ac434c1c293ccb04"
+        );
+        assert_eq!(
+            hex::encode(hkdf_sha256(b"", &ikm, b"", 42)),
+            "8da4e775a563c18f715f802a063c5a31b8a11f5c5ee1879ec3454e5f3c738d2d9d201395faa4b61a96c8"
+        );
+    }
+
+    #[test]
+    fn test_hkdf_sha256() {
+        type C = CurtaPoseidonGoldilocksConfig;
+
+        let _ = env_logger::builder().is_test(true).try_init();
+        let mut timing = TimingTree::new("test_hkdf_sha256", log::Level::Debug);
+
+        // The parameters of test case 1 of RFC 5869, with a second input keying material.
+        let salt = (0x00..=0x0c).collect::<Vec<u8>>();
+        let info = (0xf0..=0xf9).collect::<Vec<u8>>();
+        let len_in_bytes = 42;
+        let ikms: [&[u8]; 2] = [&[0x0b; 22], b"a private input keying material"];
+
+        // Build the stark.
+        let mut builder = BytesBuilder::<HKDFSHA256Test>::new();
+        let ikm_chunk_values = ikms
+            .iter()
+            .map(|ikm| hmac_message_chunks(ikm))
+            .collect::<Vec<_>>();
+        let ikm_chunks = ikm_chunk_values
+            .iter()
+            .map(|values| {
+                (0..values.len() / 16)
+                    .map(|_| builder.alloc_array::<U32Register>(16))
+                    .collect::<Vec<_>>()
+            })
+            .collect::<Vec<_>>();
+        let derivations = builder.hkdf_sha256(&ikm_chunks, &salt, &info, len_in_bytes);
+
+        let num_chunks = ikm_chunks
+            .iter()
+            .map(|chunks| chunks.len() + 3 + 4 * hkdf_num_blocks(len_in_bytes))
+            .sum::<usize>();
+        let num_rows = 1 << log2_ceil(64 * num_chunks);
+        let stark = builder.build::<C, 2>(num_rows);
+
+        // Write trace.
+        let mut writer_data = AirWriterData::new(&stark.air_data, num_rows);
+        let mut writer = writer_data.public_writer();
+
+        let digest_values = ikms
+            .iter()
+            .map(|ikm| hkdf_sha256_digests(&salt, ikm, &info, len_in_bytes))
+            .collect::<Vec<_>>();
+        for (derivation, (_, expand)) in derivations.iter().zip(digest_values.iter()) {
+            for (hmac, (_, t)) in derivation.expand.iter().zip(expand.iter()) {
+                writer.write(&hmac.digest, &to_field_bytes(*t));
+            }
+        }
+
+        timed!(timing, "write input", {
+            stark.air_data.write_global_instructions(&mut writer);
+
+            for mut chunk in writer_data.chunks(num_rows) {
+                for i in 0..num_rows {
+                    let mut writer = chunk.window_writer(i);
+                    // The private inputs are written in the first row and copied to the others.
+                    if i == 0 {
+                        for (((chunks, values), derivation), (extract, expand)) in ikm_chunks
+                            .iter()
+                            .zip(ikm_chunk_values.iter())
+                            .zip(derivations.iter())
+                            .zip(digest_values.iter())
+                        {
+                            for (register, chunk) in chunks.iter().zip(values.chunks_exact(16)) {
+                                writer.write_array(
+                                    register,
+                                    chunk.iter().map(|word| u32_to_le_field_bytes(*word)),
+                                );
+                            }
+                            let (inner_digest, prk) = extract;
+                            writer.write(
+                                &derivation.extract.inner_digest,
+                                &to_field_bytes(*inner_digest),
+                            );
+                            writer.write(&derivation.extract.digest, &to_field_bytes(*prk));
+                            for (hmac, (inner_digest, _)) in
+                                derivation.expand.iter().zip(expand.iter())
+                            {
+                                writer.write(&hmac.inner_digest, &to_field_bytes(*inner_digest));
+                            }
+                        }
+                    }
+                    stark.air_data.write_trace_instructions(&mut writer);
+                }
+            }
+        });
+
+        let (trace, public) = (writer_data.trace, writer_data.public);
+
+        let proof = timed!(
+            timing,
+            "generate stark proof",
+            stark.prove(&trace, &public, &mut timing).unwrap()
+        );
+
+        stark.verify(proof, &public).unwrap();
+
+        for (ikm, (_, expand)) in ikms.iter().zip(digest_values.iter()) {
+            let okm = expand
+                .iter()
+                .flat_map(|(_, t)| digest_to_bytes(*t))
+                .take(len_in_bytes)
+                .collect::<Vec<_>>();
+            assert_eq!(okm, hkdf_sha256(&salt, ikm, &info, len_in_bytes));
+        }
+
+        timing.print();
+    }
+}
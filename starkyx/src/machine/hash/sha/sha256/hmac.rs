@@ -0,0 +1,577 @@
+//! HMAC over SHA-256, with private keys.
+//!
+//! The HMAC of a message `m` with a key `K` is `H((K' ^ opad) || H((K' ^ ipad) || m))`, where `K'`
+//! is the key block, the key padded with zeros to 64 bytes, or its hash if it is longer. Each of
+//! the two hashes starts with a chunk derived from the key block, which the gadget computes in
+//! the trace, so that the key is not a public input of the proof.
+
+use serde::{Deserialize, Serialize};
+
+use super::register::SHA256DigestRegister;
+use super::SHA256;
+use crate::chip::register::array::ArrayRegister;
+use crate::chip::register::bit::BitRegister;
+use crate::chip::register::element::ElementRegister;
+use crate::chip::register::{Register, RegisterSerializable};
+use crate::chip::uint::operations::instruction::UintInstructions;
+use crate::chip::uint::register::U32Register;
+use crate::chip::uint::util::u32_to_le_field_bytes;
+use crate::chip::AirParameters;
+use crate::machine::builder::Builder;
+use crate::machine::bytes::builder::BytesBuilder;
+use crate::machine::hash::sha::algorithm::SHAPure;
+use crate::machine::hash::sha::builder::SHABuilder;
+use crate::math::prelude::*;
+
+/// The number of bytes of a chunk, and of the key block.
+const BLOCK_BYTES: usize = 64;
+/// The number of bytes of a digest.
+pub(crate) const DIGEST_BYTES: usize = 32;
+/// The words xored into the key block for the inner and the outer hash.
+pub(crate) const IPAD: u32 = 0x36363636;
+pub(crate) const OPAD: u32 = 0x5c5c5c5c;
+/// The padding of the outer hash after the inner digest, for a message of 96 bytes.
+const OUTER_PADDING: [u32; 8] = [0x80000000, 0, 0, 0, 0, 0, 0, 768];
+
+/// The digests of an HMAC computation.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct HMACSHA256Register {
+    /// The digest of the inner hash, `H((K' ^ ipad) || m)`.
+    pub inner_digest: SHA256DigestRegister,
+    /// The HMAC of the message.
+    pub digest: SHA256DigestRegister,
+}
+
+pub trait HMACSHA256Builder: Builder {
+    /// Computes the HMACs of messages with private keys.
+    ///
+    /// Each key is a trace register of 16 words holding the key block, see [`hmac_key_block`],
+    /// whose value is written in the first row only. The key is copied to the other rows and its
+    /// registers are marked as secret. The message of the same index is given by the padded
+    /// chunks of its inner hash after the chunk of the key, see [`hmac_message_chunks`].
+    ///
+    /// Returns the inner digests and the HMACs, which are public and must be written before the
+    /// global instructions, as given by [`hmac_sha256`].
+    fn hmac_sha256(
+        &mut self,
+        keys: &[ArrayRegister<U32Register>],
+        message_chunks: &[Vec<ArrayRegister<U32Register>>],
+    ) -> Vec<HMACSHA256Register>;
+}
+
+impl<L: AirParameters> HMACSHA256Builder for BytesBuilder<L>
+where
+    L::Instruction: UintInstructions,
+{
+    fn hmac_sha256(
+        &mut self,
+        keys: &[ArrayRegister<U32Register>],
+        message_chunks: &[Vec<ArrayRegister<U32Register>>],
+    ) -> Vec<HMACSHA256Register> {
+        assert_eq!(keys.len(), message_chunks.len());
+        let ipad = self.constant::<U32Register>(&u32_to_le_field_bytes(IPAD));
+        let opad = self.constant::<U32Register>(&u32_to_le_field_bytes(OPAD));
+        let outer_padding =
+            self.constant_array::<U32Register>(&OUTER_PADDING.map(u32_to_le_field_bytes));
+
+        // The chunks of the inner and the outer hash of each message, in that order.
+        let mut padded_chunks = Vec::new();
+        let mut outer_chunks = Vec::new();
+        let mut end_bit_values = Vec::new();
+        for (key, chunks) in keys.iter().zip(message_chunks.iter()) {
+            assert!(key.is_trace(), "The key must be a trace register");
+            assert_eq!(key.len(), 16);
+            let inner_key = self.alloc_array::<U32Register>(16);
+            let outer_key = self.alloc_array::<U32Register>(16);
+            for ((word, inner_word), outer_word) in
+                key.iter().zip(inner_key.iter()).zip(outer_key.iter())
+            {
+                self.set_to_expression_transition(&word.next(), word.expr());
+                self.api
+                    .set_bitwise_xor(&word, &ipad, &inner_word, &mut self.operations);
+                self.api
+                    .set_bitwise_xor(&word, &opad, &outer_word, &mut self.operations);
+            }
+            for register in [key, &inner_key, &outer_key] {
+                self.api.mark_secret(register);
+            }
+
+            padded_chunks.push(inner_key);
+            padded_chunks.extend(chunks.iter().copied());
+            end_bit_values.push(false);
+            end_bit_values.extend((0..chunks.len()).map(|i| i == chunks.len() - 1));
+
+            // The outer chunk is the inner digest followed by the padding. The words of the digest
+            // are written as the inner digest of the HMAC, since the chunk is read from the public
+            // values before the digests of the hashes are.
+            let outer_chunk = self.alloc_array_public::<U32Register>(16);
+            for (word, padding) in outer_chunk
+                .get_subarray(8..16)
+                .iter()
+                .zip(outer_padding.iter())
+            {
+                self.api.set_to_expression_public(&word, padding.expr());
+            }
+            padded_chunks.extend([outer_key, outer_chunk]);
+            end_bit_values.extend([false, true]);
+            outer_chunks.push(outer_chunk);
+        }
+
+        let end_bits = self.constant_array::<BitRegister>(
+            &end_bit_values
+                .iter()
+                .map(|bit| Self::Field::from_canonical_usize(*bit as usize))
+                .collect::<Vec<_>>(),
+        );
+        let digest_indices = self.constant_array::<ElementRegister>(
+            &end_bit_values
+                .iter()
+                .enumerate()
+                .filter(|(_, bit)| **bit)
+                .map(|(i, _)| Self::Field::from_canonical_usize(i))
+                .collect::<Vec<_>>(),
+        );
+        let digests = self.sha::<SHA256, 64>(&padded_chunks, &end_bits, &end_bits, digest_indices);
+
+        digests
+            .chunks_exact(2)
+            .zip(outer_chunks)
+            .map(|(digests, outer_chunk)| {
+                let inner_digest = SHA256DigestRegister::from_array(outer_chunk.get_subarray(0..8));
+                for (word, value) in digests[0].iter().zip(inner_digest.iter()) {
+                    self.api.set_to_expression_public(&word, value.expr());
+                }
+                HMACSHA256Register {
+                    inner_digest,
+                    digest: digests[1],
+                }
+            })
+            .collect()
+    }
+}
+
+/// HMAC computations with private keys and private inner digests, hashed by a single instance of
+/// the SHA-256 chip.
+///
+/// The chunk of the outer hash of each computation is in the trace, and holds the inner digest
+/// followed by the padding. The keys and the messages of a computation may be derived from the
+/// private digests of others, since the digests are written with the other inputs of the trace.
+pub(crate) struct HMACChunks {
+    initial_hash: ArrayRegister<U32Register>,
+    ipad: U32Register,
+    opad: U32Register,
+    outer_padding: ArrayRegister<U32Register>,
+    padded_chunks: Vec<ArrayRegister<U32Register>>,
+    end_bits: Vec<bool>,
+    digests: Vec<SHA256DigestRegister>,
+}
+
+impl HMACChunks {
+    pub(crate) fn new<L: AirParameters>(builder: &mut BytesBuilder<L>) -> Self {
+        Self {
+            initial_hash: builder
+                .constant_array::<U32Register>(&SHA256::INITIAL_HASH.map(u32_to_le_field_bytes)),
+            ipad: builder.constant::<U32Register>(&u32_to_le_field_bytes(IPAD)),
+            opad: builder.constant::<U32Register>(&u32_to_le_field_bytes(OPAD)),
+            outer_padding: builder
+                .constant_array::<U32Register>(&OUTER_PADDING.map(u32_to_le_field_bytes)),
+            padded_chunks: Vec::new(),
+            end_bits: Vec::new(),
+            digests: Vec::new(),
+        }
+    }
+
+    /// The chunks `K' ^ ipad` and `K' ^ opad` of the private key block `K'`, whose first words
+    /// are given by `key` and the others are zero.
+    ///
+    /// The key must have the same value in all rows. The chunks are marked as secret.
+    pub(crate) fn key_pads<L: AirParameters>(
+        &self,
+        builder: &mut BytesBuilder<L>,
+        key: &ArrayRegister<U32Register>,
+    ) -> [ArrayRegister<U32Register>; 2]
+    where
+        L::Instruction: UintInstructions,
+    {
+        assert!(key.is_trace(), "The key must be a trace register");
+        assert!(key.len() <= 16);
+        [self.ipad, self.opad].map(|pad| {
+            let key_pad = builder.alloc_array::<U32Register>(16);
+            for (i, word) in key_pad.iter().enumerate() {
+                match i < key.len() {
+                    true => builder.api.set_bitwise_xor(
+                        &key.get(i),
+                        &pad,
+                        &word,
+                        &mut builder.operations,
+                    ),
+                    false => builder.set_to_expression(&word, pad.expr()),
+                }
+            }
+            builder.api.mark_secret(&key_pad);
+            key_pad
+        })
+    }
+
+    /// Allocates a private digest, which is copied to all rows and marked as secret.
+    pub(crate) fn alloc_private_digest<L: AirParameters>(
+        builder: &mut BytesBuilder<L>,
+    ) -> SHA256DigestRegister {
+        let digest = builder.alloc_array::<U32Register>(8);
+        Self::set_private(builder, &digest);
+        SHA256DigestRegister::from_array(digest)
+    }
+
+    /// Allocates a chunk in the trace holding a private digest followed by the padding of a
+    /// message of 96 bytes. This is the chunk of an outer hash, and the chunk of an inner hash
+    /// whose message is a digest.
+    pub(crate) fn alloc_digest_chunk<L: AirParameters>(
+        &self,
+        builder: &mut BytesBuilder<L>,
+    ) -> (ArrayRegister<U32Register>, SHA256DigestRegister) {
+        let chunk = builder.alloc_array::<U32Register>(16);
+        for (word, padding) in chunk
+            .get_subarray(8..16)
+            .iter()
+            .zip(self.outer_padding.iter())
+        {
+            builder.set_to_expression(&word, padding.expr());
+        }
+        let digest = chunk.get_subarray(0..8);
+        Self::set_private(builder, &digest);
+        (chunk, SHA256DigestRegister::from_array(digest))
+    }
+
+    fn set_private<L: AirParameters>(
+        builder: &mut BytesBuilder<L>,
+        digest: &ArrayRegister<U32Register>,
+    ) {
+        for word in digest.iter() {
+            builder.set_to_expression_transition(&word.next(), word.expr());
+        }
+        builder.api.mark_secret(digest);
+    }
+
+    /// Adds the HMAC of a message with the key given by its pads, see [`Self::key_pads`].
+    ///
+    /// The message is given by the padded chunks of its inner hash after the chunk of the key,
+    /// and the HMAC is written to `digest`, which is either public or a private digest.
+    pub(crate) fn push<L: AirParameters>(
+        &mut self,
+        builder: &mut BytesBuilder<L>,
+        key_pads: &[ArrayRegister<U32Register>; 2],
+        message_chunks: &[ArrayRegister<U32Register>],
+        digest: SHA256DigestRegister,
+    ) -> HMACSHA256Register {
+        let [inner_key, outer_key] = *key_pads;
+        let (outer_chunk, inner_digest) = self.alloc_digest_chunk(builder);
+
+        self.padded_chunks.push(inner_key);
+        self.padded_chunks.extend_from_slice(message_chunks);
+        self.padded_chunks.extend([outer_key, outer_chunk]);
+        let num_chunks = message_chunks.len();
+        self.end_bits
+            .extend((0..=num_chunks).map(|i| i == num_chunks));
+        self.end_bits.extend([false, true]);
+        self.digests.extend([inner_digest, digest]);
+
+        HMACSHA256Register {
+            inner_digest,
+            digest,
+        }
+    }
+
+    /// Hashes the chunks of the computations with the SHA-256 chip.
+    pub(crate) fn hash<L: AirParameters>(self, builder: &mut BytesBuilder<L>)
+    where
+        L::Instruction: UintInstructions,
+    {
+        let end_bits = builder.constant_array::<BitRegister>(
+            &self
+                .end_bits
+                .iter()
+                .map(|bit| L::Field::from_canonical_usize(*bit as usize))
+                .collect::<Vec<_>>(),
+        );
+        let digest_indices = builder.constant_array::<ElementRegister>(
+            &self
+                .end_bits
+                .iter()
+                .enumerate()
+                .filter(|(_, bit)| **bit)
+                .map(|(i, _)| L::Field::from_canonical_usize(i))
+                .collect::<Vec<_>>(),
+        );
+        builder.sha_with_digests::<SHA256, 64>(
+            &self.padded_chunks,
+            &end_bits,
+            &end_bits,
+            digest_indices,
+            self.initial_hash,
+            &self.digests,
+        );
+    }
+}
+
+/// The key block of `key`, as big-endian words.
+pub fn hmac_key_block(key: &[u8]) -> [u32; 16] {
+    let mut key_block = match key.len() > BLOCK_BYTES {
+        true => sha256_chunks(SHA256::pad(key))
+            .iter()
+            .flat_map(|word| word.to_be_bytes())
+            .collect::<Vec<_>>(),
+        false => key.to_vec(),
+    };
+    key_block.resize(BLOCK_BYTES, 0);
+    core::array::from_fn(|i| u32::from_be_bytes(key_block[4 * i..4 * i + 4].try_into().unwrap()))
+}
+
+/// The padded chunks of the inner hash of `msg` which follow the chunk of the key.
+pub fn hmac_message_chunks(msg: &[u8]) -> Vec<u32> {
+    let mut inner_msg = vec![0u8; BLOCK_BYTES];
+    inner_msg.extend_from_slice(msg);
+    SHA256::pad(&inner_msg)[16..].to_vec()
+}
+
+/// The inner digest and the HMAC of `msg` with the key `key`.
+pub fn hmac_sha256(key: &[u8], msg: &[u8]) -> ([u32; 8], [u32; 8]) {
+    let key_block = hmac_key_block(key);
+    let inner_digest = sha256_chunks(
+        key_block
+            .iter()
+            .map(|word| word ^ IPAD)
+            .chain(hmac_message_chunks(msg)),
+    );
+    let digest = sha256_chunks(
+        key_block
+            .iter()
+            .map(|word| word ^ OPAD)
+            .chain(inner_digest)
+            .chain(OUTER_PADDING),
+    );
+    (inner_digest, digest)
+}
+
+/// The bytes of a digest given by big-endian words.
+pub(crate) fn digest_to_bytes(digest: [u32; 8]) -> Vec<u8> {
+    digest.iter().flat_map(|word| word.to_be_bytes()).collect()
+}
+
+/// The state after the compression of the chunks of `padded_msg` from the initial hash.
+fn sha256_chunks<I: IntoIterator<Item = u32>>(padded_msg: I) -> [u32; 8] {
+    let padded_msg = padded_msg.into_iter().collect::<Vec<_>>();
+    padded_msg
+        .chunks_exact(16)
+        .fold(SHA256::INITIAL_HASH, |state, chunk| {
+            SHA256::process(state, &SHA256::pre_process(chunk))
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use plonky2::field::goldilocks_field::GoldilocksField;
+    use plonky2::iop::witness::{PartialWitness, WitnessWrite};
+    use plonky2::plonk::circuit_builder::CircuitBuilder;
+    use plonky2::plonk::circuit_data::CircuitConfig;
+    use plonky2::timed;
+    use plonky2::util::log2_ceil;
+    use plonky2::util::timing::TimingTree;
+
+    use super::*;
+    use crate::chip::uint::operations::instruction::UintInstruction;
+    use crate::math::goldilocks::cubic::GoldilocksCubicParameters;
+    use crate::plonky2::stark::config::{CurtaConfig, CurtaPoseidonGoldilocksConfig};
+    use crate::prelude::{AirWriter, AirWriterData};
+
+    #[derive(Clone, Debug, Serialize, Deserialize)]
+    pub struct HMACSHA256Test;
+
+    impl AirParameters for HMACSHA256Test {
+        type Field = GoldilocksField;
+        type CubicParams = GoldilocksCubicParameters;
+        type Instruction = UintInstruction;
+
+        const NUM_FREE_COLUMNS: usize = 803;
+        const EXTENDED_COLUMNS: usize = 2400;
+    }
+
+    fn encode(digest: [u32; 8]) -> String {
+        hex::encode(
+            digest
+                .iter()
+                .flat_map(|word| word.to_be_bytes())
+                .collect::<Vec<_>>(),
+        )
+    }
+
+    #[test]
+    fn test_hmac_sha256_pure() {
+        // Test cases 1, 2, 6 and 7 of RFC 4231.
+        let long_key = vec![0xaa; 131];
+        let test_vectors: [(&[u8], &[u8], &str); 4] = [
+            (
+                &[0x0b; 20],
+                b"Hi There",
+                "b0344c61d8db38535ca8afceaf0bf12b881dc200c9833da726e9376c2e32cff7",
+            ),
+            (
+                b"Jefe",
+                b"what do ya want for nothing?",
+                "5bdcc146bf60754e6a042426089575c75a003f089d2739839dec58b964ec3843",
+            ),
+            (
+                &long_key,
+                b"Test Using Larger Than Block-Size Key - Hash Key First",
+                "60e431591ee0b67f0d8a26aacbf5b77f8e0bc6213728c5140546040f0ee37f54",
+            ),
+            (
+                &long_key,
+                b"This is a test using a larger than block-size key and a larger than block-size \
+                  data. The key needs to be hashed before being used by the HMAC algorithm.",
+                "9b09ffa71b942fcb27635fbcd5b0e944bfdc63644f0713938a7f51535c3a35e2",
+            ),
+        ];
+
+        for (key, msg, expected) in test_vectors {
+            let (_, digest) = hmac_sha256(key, msg);
+            assert_eq!(encode(digest), expected);
+        }
+    }
+
+    #[test]
+    fn test_hmac_sha256() {
+        type C = CurtaPoseidonGoldilocksConfig;
+        type Config = <C as CurtaConfig<2>>::GenericConfig;
+
+        let _ = env_logger::builder().is_test(true).try_init();
+        let mut timing = TimingTree::new("test_hmac_sha256", log::Level::Debug);
+
+        // Test cases 1 and 2 of RFC 4231.
+        let messages: [(&[u8], &[u8], &str); 2] = [
+            (
+                &[0x0b; 20],
+                b"Hi There",
+                "b0344c61d8db38535ca8afceaf0bf12b881dc200c9833da726e9376c2e32cff7",
+            ),
+            (
+                b"Jefe",
+                b"what do ya want for nothing?",
+                "5bdcc146bf60754e6a042426089575c75a003f089d2739839dec58b964ec3843",
+            ),
+        ];
+
+        // Build the stark.
+        let mut builder = BytesBuilder::<HMACSHA256Test>::new();
+        let keys = messages
+            .iter()
+            .map(|_| builder.alloc_array::<U32Register>(16))
+            .collect::<Vec<_>>();
+        let message_chunk_values = messages
+            .iter()
+            .map(|(_, msg, _)| hmac_message_chunks(msg))
+            .collect::<Vec<_>>();
+        let message_chunks = message_chunk_values
+            .iter()
+            .map(|values| {
+                (0..values.len() / 16)
+                    .map(|_| builder.alloc_array_public::<U32Register>(16))
+                    .collect::<Vec<_>>()
+            })
+            .collect::<Vec<_>>();
+        let hmacs = builder.hmac_sha256(&keys, &message_chunks);
+
+        let num_chunks = message_chunks
+            .iter()
+            .map(|chunks| chunks.len() + 3)
+            .sum::<usize>();
+        let num_rows = 1 << log2_ceil(64 * num_chunks);
+        let stark = builder.build::<C, 2>(num_rows);
+
+        // Build the recursive circuit.
+        let config_rec = CircuitConfig::standard_recursion_config();
+        let mut recursive_builder = CircuitBuilder::<GoldilocksField, 2>::new(config_rec);
+
+        let (proof_target, public_input) =
+            stark.add_virtual_proof_with_pis_target(&mut recursive_builder);
+        stark.verify_circuit(&mut recursive_builder, &proof_target, &public_input);
+
+        let rec_data = recursive_builder.build::<Config>();
+
+        // Write trace.
+        let mut writer_data = AirWriterData::new(&stark.air_data, num_rows);
+        let mut writer = writer_data.public_writer();
+
+        for (((key, msg, expected), (chunks, values)), hmac) in messages
+            .iter()
+            .zip(message_chunks.iter().zip(message_chunk_values.iter()))
+            .zip(hmacs.iter())
+        {
+            for (register, chunk) in chunks.iter().zip(values.chunks_exact(16)) {
+                writer.write_array(
+                    register,
+                    chunk.iter().map(|word| u32_to_le_field_bytes(*word)),
+                );
+            }
+            let (inner_digest, digest) = hmac_sha256(key, msg);
+            assert_eq!(encode(digest), *expected);
+            writer.write(&hmac.inner_digest, &to_field_bytes(inner_digest));
+            writer.write(&hmac.digest, &to_field_bytes(digest));
+        }
+
+        timed!(timing, "write input", {
+            stark.air_data.write_global_instructions(&mut writer);
+
+            for mut chunk in writer_data.chunks(num_rows) {
+                for i in 0..num_rows {
+                    let mut writer = chunk.window_writer(i);
+                    // The keys are written in the first row and copied to the others.
+                    if i == 0 {
+                        for (key, (key_value, _, _)) in keys.iter().zip(messages.iter()) {
+                            writer.write_array(
+                                key,
+                                hmac_key_block(key_value)
+                                    .iter()
+                                    .map(|word| u32_to_le_field_bytes(*word)),
+                            );
+                        }
+                    }
+                    stark.air_data.write_trace_instructions(&mut writer);
+                }
+            }
+        });
+
+        let (trace, public) = (writer_data.trace, writer_data.public);
+
+        let proof = timed!(
+            timing,
+            "generate stark proof",
+            stark.prove(&trace, &public, &mut timing).unwrap()
+        );
+
+        stark.verify(proof.clone(), &public).unwrap();
+
+        let mut pw = PartialWitness::new();
+
+        pw.set_target_arr(&public_input, &public).unwrap();
+        stark
+            .set_proof_target(&mut pw, &proof_target, proof)
+            .unwrap();
+
+        let rec_proof = timed!(
+            timing,
+            "generate recursive proof",
+            rec_data.prove(pw).unwrap()
+        );
+        rec_data.verify(rec_proof).unwrap();
+
+        timing.print();
+    }
+
+    fn to_field_bytes(digest: [u32; 8]) -> [GoldilocksField; 32] {
+        let bytes = digest
+            .iter()
+            .flat_map(|word| u32_to_le_field_bytes::<GoldilocksField>(*word))
+            .collect::<Vec<_>>();
+        bytes.try_into().unwrap()
+    }
+}
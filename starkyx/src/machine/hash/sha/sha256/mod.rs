@@ -1,6 +1,10 @@
 use serde::{Deserialize, Serialize};
 
 pub mod air;
+pub mod hkdf;
+pub mod hmac;
+pub mod padding;
+pub mod pbkdf2;
 pub mod pure;
 pub mod register;
 
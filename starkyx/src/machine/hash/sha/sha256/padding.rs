@@ -0,0 +1,256 @@
+use crate::chip::arithmetic::expression::ArithmeticExpression;
+use crate::chip::register::array::ArrayRegister;
+use crate::chip::register::bit::BitRegister;
+use crate::chip::register::element::ElementRegister;
+use crate::chip::register::Register;
+use crate::chip::uint::register::U32Register;
+use crate::machine::builder::Builder;
+use crate::math::prelude::*;
+
+const CHUNK_BYTES: usize = 64;
+/// The number of bytes of the length at the end of the padding.
+const LENGTH_BYTES: usize = 8;
+/// The offset in its chunk of the length at the end of the padding.
+const LENGTH_OFFSET: usize = CHUNK_BYTES - LENGTH_BYTES;
+
+/// The mask of the bytes of a message of `length` bytes padded to `num_chunks` chunks, whose
+/// `p`-th value is `true` iff `p < length`.
+///
+/// The mask covers the positions `0..64 * num_chunks - 9`, since the padding takes at least 9
+/// bytes, the byte `0x80` and the length.
+pub fn message_mask(length: usize, num_chunks: usize) -> Vec<bool> {
+    let mask_len = num_chunks * CHUNK_BYTES - LENGTH_BYTES - 1;
+    assert!(
+        length <= mask_len,
+        "A message of {} bytes does not fit in {} chunks",
+        length,
+        num_chunks
+    );
+    (0..mask_len).map(|p| p < length).collect()
+}
+
+pub trait SHA256PaddingBuilder: Builder {
+    /// Constrains `padded_chunks` to be the padding of a message of variable length, and returns
+    /// its length in bytes.
+    ///
+    /// The length is given by `message_mask`, whose `p`-th bit is set iff the byte `p` of the
+    /// chunks belongs to the message, see [`message_mask`]. The message is followed by the byte
+    /// `0x80` and zeros, up to the end of the digest chunk, whose bit is set in `digest_bits` and
+    /// whose last 8 bytes are the length of the message in bits. The chunks after the digest
+    /// chunk are zero, as in [`SHAPure::pad_to_chunks`].
+    ///
+    /// All registers are public and the constraints are global constraints, so the same circuit
+    /// accepts messages of any length up to the number of chunks.
+    ///
+    /// [`SHAPure::pad_to_chunks`]: crate::machine::hash::sha::algorithm::SHAPure::pad_to_chunks
+    fn sha256_assert_padding(
+        &mut self,
+        padded_chunks: &[ArrayRegister<U32Register>],
+        digest_bits: &ArrayRegister<BitRegister>,
+        message_mask: &ArrayRegister<BitRegister>,
+    ) -> ElementRegister {
+        let num_chunks = padded_chunks.len();
+        assert_eq!(digest_bits.len(), num_chunks);
+        assert_eq!(
+            message_mask.len(),
+            num_chunks * CHUNK_BYTES - LENGTH_BYTES - 1
+        );
+
+        // The mask extended by ones before the chunks and zeros after its end.
+        let mask = |p: isize| -> ArithmeticExpression<Self::Field> {
+            if p < 0 {
+                ArithmeticExpression::one()
+            } else if (p as usize) < message_mask.len() {
+                message_mask.get(p as usize).expr()
+            } else {
+                ArithmeticExpression::zero()
+            }
+        };
+
+        // The mask is a sequence of ones followed by zeros.
+        for (p, bit) in message_mask.iter().enumerate() {
+            self.assert_expression_zero(bit.expr() * bit.not_expr());
+            self.assert_expression_zero(
+                bit.expr() * (ArithmeticExpression::one() - mask(p as isize - 1)),
+            );
+        }
+        let length = self.public_expression::<ElementRegister>(
+            message_mask
+                .iter()
+                .fold(ArithmeticExpression::zero(), |acc, bit| acc + bit.expr()),
+        );
+
+        for (c, (chunk, digest_bit)) in padded_chunks.iter().zip(digest_bits.iter()).enumerate() {
+            assert_eq!(chunk.len(), 16);
+            let start = (c * CHUNK_BYTES) as isize;
+
+            // The digest chunk is the one where the length fits after the message and the byte
+            // `0x80`, that is `64 * c - 8 <= length < 64 * c + 56`.
+            self.assert_expressions_equal(
+                digest_bit.expr(),
+                mask(start - LENGTH_BYTES as isize - 1) - mask(start + LENGTH_OFFSET as isize - 1),
+            );
+
+            // The words are big-endian and their bytes are stored in little-endian order.
+            for (i, word) in chunk.iter().enumerate() {
+                let bytes = word.to_le_bytes();
+                for k in 0..4 {
+                    let offset = 4 * i + k;
+                    let p = start + offset as isize;
+                    let is_padding_start = mask(p - 1) - mask(p);
+                    let padding_constraint = (ArithmeticExpression::one() - mask(p))
+                        * bytes.get(3 - k).expr()
+                        - is_padding_start * Self::Field::from_canonical_u32(0x80);
+                    if offset < LENGTH_OFFSET {
+                        self.assert_expression_zero(padding_constraint);
+                    } else {
+                        self.assert_expression_zero(digest_bit.not_expr() * padding_constraint);
+                    }
+                }
+            }
+
+            // In the digest chunk, the last two words are the length in bits, which is less than
+            // `2^32` so that the first of them is zero.
+            self.assert_expression_zero(digest_bit.expr() * word_expr(&chunk.get(14)));
+            self.assert_expression_zero(
+                digest_bit.expr()
+                    * (word_expr(&chunk.get(15))
+                        - length.expr() * Self::Field::from_canonical_u32(8)),
+            );
+        }
+
+        length
+    }
+}
+
+impl<B: Builder> SHA256PaddingBuilder for B {}
+
+/// The value of a word from its little-endian bytes.
+fn word_expr<F: Field>(word: &U32Register) -> ArithmeticExpression<F> {
+    word.to_le_bytes()
+        .iter()
+        .enumerate()
+        .fold(ArithmeticExpression::zero(), |acc, (j, byte)| {
+            acc + byte.expr() * F::from_canonical_u32(1 << (8 * j))
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use plonky2::field::goldilocks_field::GoldilocksField;
+    use plonky2::util::timing::TimingTree;
+    use serde::{Deserialize, Serialize};
+
+    use super::*;
+    use crate::chip::uint::util::u32_to_le_field_bytes;
+    use crate::chip::AirParameters;
+    use crate::machine::hash::sha::algorithm::SHAPure;
+    use crate::machine::hash::sha::sha256::SHA256;
+    use crate::machine::stark::builder::StarkBuilder;
+    use crate::math::goldilocks::cubic::GoldilocksCubicParameters;
+    use crate::plonky2::stark::config::CurtaPoseidonGoldilocksConfig;
+    use crate::prelude::{AirWriter, AirWriterData, EmptyInstruction};
+
+    #[derive(Clone, Debug, Serialize, Deserialize)]
+    pub struct SHA256PaddingTest;
+
+    impl AirParameters for SHA256PaddingTest {
+        type Field = GoldilocksField;
+        type CubicParams = GoldilocksCubicParameters;
+        type Instruction = EmptyInstruction<GoldilocksField>;
+
+        const NUM_FREE_COLUMNS: usize = 1;
+        const EXTENDED_COLUMNS: usize = 3;
+    }
+
+    #[test]
+    fn test_message_mask() {
+        assert_eq!(message_mask(0, 1).len(), 55);
+        assert!(message_mask(0, 1).iter().all(|bit| !bit));
+        assert_eq!(message_mask(55, 1), vec![true; 55]);
+        assert_eq!(message_mask(3, 2).iter().filter(|bit| **bit).count(), 3);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_message_mask_too_long() {
+        message_mask(56, 1);
+    }
+
+    #[test]
+    fn test_sha256_padding() {
+        type F = GoldilocksField;
+        type L = SHA256PaddingTest;
+        type C = CurtaPoseidonGoldilocksConfig;
+
+        let _ = env_logger::builder().is_test(true).try_init();
+        let mut timing = TimingTree::new("test_sha256_padding", log::Level::Debug);
+
+        // Lengths at the boundaries of the digest chunk, all padded to the same number of chunks.
+        let num_chunks = 3;
+        let lengths = [0, 3, 55, 56, 63, 64, 119, 120, 183];
+
+        // Build the stark.
+        let mut builder = StarkBuilder::<L>::new();
+        let messages = lengths
+            .iter()
+            .map(|_| {
+                let padded_chunks = (0..num_chunks)
+                    .map(|_| builder.alloc_array_public::<U32Register>(16))
+                    .collect::<Vec<_>>();
+                let digest_bits = builder.alloc_array_public::<BitRegister>(num_chunks);
+                let mask = builder.alloc_array_public::<BitRegister>(num_chunks * 64 - 9);
+                let length = builder.sha256_assert_padding(&padded_chunks, &digest_bits, &mask);
+                (padded_chunks, digest_bits, mask, length)
+            })
+            .collect::<Vec<_>>();
+
+        let num_rows = 1 << 5;
+        let stark = builder.build::<C, 2>(num_rows);
+
+        // Write trace.
+        let mut writer_data = AirWriterData::new(&stark.air_data, num_rows);
+        let mut writer = writer_data.public_writer();
+
+        for (&len, (padded_chunks, digest_bits, mask, _)) in lengths.iter().zip(messages.iter()) {
+            let msg = (1..=len).map(|i| i as u8).collect::<Vec<_>>();
+            let padded_msg = SHA256::pad_to_chunks(&msg, num_chunks);
+            for (register, chunk) in padded_chunks.iter().zip(padded_msg.chunks_exact(16)) {
+                writer.write_array(
+                    register,
+                    chunk.iter().map(|word| u32_to_le_field_bytes::<F>(*word)),
+                );
+            }
+            let digest_chunk = SHA256::num_chunks(&msg) - 1;
+            for i in 0..num_chunks {
+                writer.write(
+                    &digest_bits.get(i),
+                    &F::from_canonical_usize((i == digest_chunk) as usize),
+                );
+            }
+            writer.write_array(
+                mask,
+                message_mask(len, num_chunks)
+                    .into_iter()
+                    .map(|bit| F::from_canonical_usize(bit as usize)),
+            );
+        }
+
+        stark.air_data.write_global_instructions(&mut writer);
+        for (&len, (.., length)) in lengths.iter().zip(messages.iter()) {
+            assert_eq!(writer.read(length), F::from_canonical_usize(len));
+        }
+
+        for mut chunk in writer_data.chunks(num_rows) {
+            for i in 0..num_rows {
+                let mut writer = chunk.window_writer(i);
+                stark.air_data.write_trace_instructions(&mut writer);
+            }
+        }
+
+        let (trace, public) = (writer_data.trace, writer_data.public);
+
+        let proof = stark.prove(&trace, &public, &mut timing).unwrap();
+        stark.verify(proof, &public).unwrap();
+    }
+}
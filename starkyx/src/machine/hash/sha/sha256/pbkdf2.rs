@@ -0,0 +1,373 @@
+//! PBKDF2 with HMAC-SHA256, with private passwords and a bounded number of iterations.
+//!
+//! The block of index `i` of the derived key is `T_i = U_1 ^ ... ^ U_c`, where
+//! `U_1 = HMAC(P, S || INT(i))` and `U_j = HMAC(P, U_(j - 1))`. The number of iterations `c` is
+//! a parameter of the gadget. Each iteration takes four chunks of the SHA-256 chip and its own
+//! trace registers, so the number of iterations is bounded by the size of the trace.
+//!
+//! The password is a private key, and the digests `U_j` and the inner digests of their HMACs are
+//! private digests, so that the block is the only public value which depends on the password.
+
+use serde::{Deserialize, Serialize};
+
+use super::hmac::{
+    digest_to_bytes, hmac_message_chunks, hmac_sha256, HMACChunks, HMACSHA256Register, DIGEST_BYTES,
+};
+use super::register::SHA256DigestRegister;
+use crate::chip::register::array::ArrayRegister;
+use crate::chip::register::{Register, RegisterSerializable};
+use crate::chip::uint::operations::instruction::UintInstructions;
+use crate::chip::uint::register::U32Register;
+use crate::chip::AirParameters;
+use crate::machine::builder::Builder;
+use crate::machine::bytes::builder::BytesBuilder;
+
+/// The registers of the derivation of a block with PBKDF2.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PBKDF2SHA256Register {
+    /// The HMACs of the iterations, whose digests are the private `U_1, ..., U_c`.
+    pub iterations: Vec<HMACSHA256Register>,
+    /// The block of the derived key.
+    pub digest: SHA256DigestRegister,
+}
+
+pub trait PBKDF2SHA256Builder: Builder {
+    /// Derives blocks of keys with PBKDF2 from private passwords, with `iterations` iterations.
+    ///
+    /// Each password is a trace register of 16 words holding its key block, see
+    /// [`super::hmac::hmac_key_block`], whose value is written in the first row only. The
+    /// password is copied to the other rows and its registers are marked as secret. The salt of
+    /// the same index is given by the padded chunks of the inner hash of `U_1` after the chunk of
+    /// the key, see [`pbkdf2_salt_chunks`].
+    ///
+    /// Returns the registers of each block. The private digests of the iterations must be
+    /// written in the first row and the blocks before the global instructions, as given by
+    /// [`pbkdf2_sha256_digests`].
+    fn pbkdf2_sha256(
+        &mut self,
+        passwords: &[ArrayRegister<U32Register>],
+        salt_chunks: &[Vec<ArrayRegister<U32Register>>],
+        iterations: usize,
+    ) -> Vec<PBKDF2SHA256Register>;
+}
+
+impl<L: AirParameters> PBKDF2SHA256Builder for BytesBuilder<L>
+where
+    L::Instruction: UintInstructions,
+{
+    fn pbkdf2_sha256(
+        &mut self,
+        passwords: &[ArrayRegister<U32Register>],
+        salt_chunks: &[Vec<ArrayRegister<U32Register>>],
+        iterations: usize,
+    ) -> Vec<PBKDF2SHA256Register> {
+        assert_eq!(passwords.len(), salt_chunks.len());
+        assert!(iterations > 0, "PBKDF2 needs at least one iteration");
+        let mut hmacs = HMACChunks::new(self);
+
+        let blocks = passwords
+            .iter()
+            .zip(salt_chunks.iter())
+            .map(|(password, chunks)| {
+                assert!(password.is_trace(), "The password must be a trace register");
+                assert_eq!(password.len(), 16);
+                for word in password.iter() {
+                    self.set_to_expression_transition(&word.next(), word.expr());
+                }
+                self.api.mark_secret(password);
+                let key_pads = hmacs.key_pads(self, password);
+
+                // The message of `U_(j + 1)` is `U_j`, which is the first half of its chunk.
+                let mut message_chunks = chunks.clone();
+                let mut hmac_iterations = Vec::with_capacity(iterations);
+                for j in 1..=iterations {
+                    let (next_chunk, digest) = match j < iterations {
+                        true => {
+                            let (chunk, digest) = hmacs.alloc_digest_chunk(self);
+                            (Some(chunk), digest)
+                        }
+                        false => (None, HMACChunks::alloc_private_digest(self)),
+                    };
+                    hmac_iterations.push(hmacs.push(self, &key_pads, &message_chunks, digest));
+                    message_chunks = next_chunk.into_iter().collect();
+                }
+
+                // The xor of the digests is computed in the trace and equal to the public block.
+                let mut xor = hmac_iterations[0].digest;
+                for hmac in hmac_iterations[1..].iter() {
+                    let next_xor = self.alloc_array::<U32Register>(8);
+                    for ((a, b), result) in xor.iter().zip(hmac.digest.iter()).zip(next_xor.iter())
+                    {
+                        self.api
+                            .set_bitwise_xor(&a, &b, &result, &mut self.operations);
+                    }
+                    self.api.mark_secret(&next_xor);
+                    xor = SHA256DigestRegister::from_array(next_xor);
+                }
+                let digest = self.alloc_public::<SHA256DigestRegister>();
+                for (word, value) in xor.iter().zip(digest.iter()) {
+                    self.assert_equal(&word, &value);
+                }
+
+                PBKDF2SHA256Register {
+                    iterations: hmac_iterations,
+                    digest,
+                }
+            })
+            .collect();
+
+        hmacs.hash(self);
+        blocks
+    }
+}
+
+/// The padded chunks of the inner hash of `U_1` for the block of index `block_index`, starting
+/// from one, after the chunk of the key.
+pub fn pbkdf2_salt_chunks(salt: &[u8], block_index: u32) -> Vec<u32> {
+    let mut msg = salt.to_vec();
+    msg.extend_from_slice(&block_index.to_be_bytes());
+    hmac_message_chunks(&msg)
+}
+
+/// The inner digests and the digests `U_1, ..., U_c` of the HMACs of the iterations of the block
+/// of index `block_index`.
+pub fn pbkdf2_sha256_digests(
+    password: &[u8],
+    salt: &[u8],
+    block_index: u32,
+    iterations: usize,
+) -> Vec<([u32; 8], [u32; 8])> {
+    assert!(iterations > 0, "PBKDF2 needs at least one iteration");
+    let mut msg = salt.to_vec();
+    msg.extend_from_slice(&block_index.to_be_bytes());
+
+    let mut digests = Vec::with_capacity(iterations);
+    for _ in 0..iterations {
+        let (inner_digest, digest) = hmac_sha256(password, &msg);
+        digests.push((inner_digest, digest));
+        msg = digest_to_bytes(digest);
+    }
+    digests
+}
+
+/// The block of index `block_index` of PBKDF2 with HMAC-SHA256.
+pub fn pbkdf2_sha256_block(
+    password: &[u8],
+    salt: &[u8],
+    block_index: u32,
+    iterations: usize,
+) -> [u32; 8] {
+    pbkdf2_sha256_digests(password, salt, block_index, iterations)
+        .into_iter()
+        .fold([0; 8], |block, (_, digest)| {
+            core::array::from_fn(|i| block[i] ^ digest[i])
+        })
+}
+
+/// PBKDF2 with HMAC-SHA256, returning a derived key of `dk_len` bytes.
+pub fn pbkdf2_sha256(password: &[u8], salt: &[u8], iterations: usize, dk_len: usize) -> Vec<u8> {
+    let num_blocks = dk_len.div_ceil(DIGEST_BYTES) as u32;
+    let mut dk = (1..=num_blocks)
+        .flat_map(|i| digest_to_bytes(pbkdf2_sha256_block(password, salt, i, iterations)))
+        .collect::<Vec<_>>();
+    dk.truncate(dk_len);
+    dk
+}
+
+#[cfg(test)]
+mod tests {
+    use plonky2::field::goldilocks_field::GoldilocksField;
+    use plonky2::timed;
+    use plonky2::util::log2_ceil;
+    use plonky2::util::timing::TimingTree;
+
+    use super::*;
+    use crate::chip::uint::operations::instruction::UintInstruction;
+    use crate::chip::uint::util::u32_to_le_field_bytes;
+    use crate::machine::hash::sha::sha256::hmac::hmac_key_block;
+    use crate::math::goldilocks::cubic::GoldilocksCubicParameters;
+    use crate::plonky2::stark::config::CurtaPoseidonGoldilocksConfig;
+    use crate::prelude::{AirWriter, AirWriterData};
+
+    #[derive(Clone, Debug, Serialize, Deserialize)]
+    pub struct PBKDF2SHA256Test;
+
+    impl AirParameters for PBKDF2SHA256Test {
+        type Field = GoldilocksField;
+        type CubicParams = GoldilocksCubicParameters;
+        type Instruction = UintInstruction;
+
+        const NUM_FREE_COLUMNS: usize = 1315;
+        const EXTENDED_COLUMNS: usize = 3600;
+    }
+
+    fn to_field_bytes(digest: [u32; 8]) -> [GoldilocksField; 32] {
+        let bytes = digest
+            .iter()
+            .flat_map(|word| u32_to_le_field_bytes::<GoldilocksField>(*word))
+            .collect::<Vec<_>>();
+        bytes.try_into().unwrap()
+    }
+
+    #[test]
+    fn test_pbkdf2_sha256_pure() {
+        let test_vectors: [(&[u8], &[u8], usize, &str); 4] = [
+            (
+                b"password",
+                b"salt",
+                1,
+                "120fb6cffcf8b32c43e7225256c4f837a86548c92ccc35480805987cb70be17b",
+            ),
+            (
+                b"password",
+                b"salt",
+                2,
+                "ae4d0c95af6b46d32d0adff928f06dd02a303f8ef3c251dfd6e2d85a95474c43",
+            ),
+            (
+                b"password",
+                b"salt",
+                4096,
+                "c5e478d59288c841aa530db6845c4c8d962893a001ce4e11a4963873aa98134a",
+            ),
+            (
+                b"passwordPASSWORDpassword",
+                b"saltSALTsaltSALTsaltSALTsaltSALTsalt",
+                4096,
+                "348c89dbcbd32b2f32d814b8116e84cf2b17347ebc1800181c4e2a1fb8dd53e1c635518c7dac47e9",
+            ),
+        ];
+
+        for (password, salt, iterations, expected) in test_vectors {
+            let dk = pbkdf2_sha256(password, salt, iterations, expected.len() / 2);
+            assert_eq!(hex::encode(dk), expected);
+        }
+
+        // The test vector of section 11 of RFC 7914.
+        assert_eq!(
+            hex::encode(pbkdf2_sha256(b"passwd", b"salt", 1, 64)),
+            "55ac046e56e3089fec1691c22544b605f94185216dde0465e68b9d57c20dacbc\
+             49ca9cccf179b645991664b39d77ef317c71b845b1e30bd509112041d3a19783"
+        );
+    }
+
+    #[test]
+    fn test_pbkdf2_sha256() {
+        type C = CurtaPoseidonGoldilocksConfig;
+
+        let _ = env_logger::builder().is_test(true).try_init();
+        let mut timing = TimingTree::new("test_pbkdf2_sha256", log::Level::Debug);
+
+        let iterations = 2;
+        let inputs: [(&[u8], &[u8], &str); 2] = [
+            (
+                b"password",
+                b"salt",
+                "ae4d0c95af6b46d32d0adff928f06dd02a303f8ef3c251dfd6e2d85a95474c43",
+            ),
+            (b"a private password", b"a public salt", ""),
+        ];
+
+        // Build the stark.
+        let mut builder = BytesBuilder::<PBKDF2SHA256Test>::new();
+        let passwords = inputs
+            .iter()
+            .map(|_| builder.alloc_array::<U32Register>(16))
+            .collect::<Vec<_>>();
+        let salt_chunk_values = inputs
+            .iter()
+            .map(|(_, salt, _)| pbkdf2_salt_chunks(salt, 1))
+            .collect::<Vec<_>>();
+        let salt_chunks = salt_chunk_values
+            .iter()
+            .map(|values| {
+                (0..values.len() / 16)
+                    .map(|_| builder.alloc_array_public::<U32Register>(16))
+                    .collect::<Vec<_>>()
+            })
+            .collect::<Vec<_>>();
+        let blocks = builder.pbkdf2_sha256(&passwords, &salt_chunks, iterations);
+
+        let num_chunks = salt_chunks
+            .iter()
+            .map(|chunks| chunks.len() + 3 + 4 * (iterations - 1))
+            .sum::<usize>();
+        let num_rows = 1 << log2_ceil(64 * num_chunks);
+        let stark = builder.build::<C, 2>(num_rows);
+
+        // Write trace.
+        let mut writer_data = AirWriterData::new(&stark.air_data, num_rows);
+        let mut writer = writer_data.public_writer();
+
+        let digest_values = inputs
+            .iter()
+            .map(|(password, salt, _)| pbkdf2_sha256_digests(password, salt, 1, iterations))
+            .collect::<Vec<_>>();
+        for ((((password, salt, expected), (chunks, values)), block), digests) in inputs
+            .iter()
+            .zip(salt_chunks.iter().zip(salt_chunk_values.iter()))
+            .zip(blocks.iter())
+            .zip(digest_values.iter())
+        {
+            for (register, chunk) in chunks.iter().zip(values.chunks_exact(16)) {
+                writer.write_array(
+                    register,
+                    chunk.iter().map(|word| u32_to_le_field_bytes(*word)),
+                );
+            }
+            let value = digests.iter().fold([0; 8], |block, (_, digest)| {
+                core::array::from_fn(|i| block[i] ^ digest[i])
+            });
+            assert_eq!(value, pbkdf2_sha256_block(password, salt, 1, iterations));
+            if !expected.is_empty() {
+                assert_eq!(hex::encode(digest_to_bytes(value)), *expected);
+            }
+            writer.write(&block.digest, &to_field_bytes(value));
+        }
+
+        timed!(timing, "write input", {
+            stark.air_data.write_global_instructions(&mut writer);
+
+            for mut chunk in writer_data.chunks(num_rows) {
+                for i in 0..num_rows {
+                    let mut writer = chunk.window_writer(i);
+                    // The private inputs are written in the first row and copied to the others.
+                    if i == 0 {
+                        for (((password, (password_value, _, _)), block), digests) in passwords
+                            .iter()
+                            .zip(inputs.iter())
+                            .zip(blocks.iter())
+                            .zip(digest_values.iter())
+                        {
+                            writer.write_array(
+                                password,
+                                hmac_key_block(password_value)
+                                    .iter()
+                                    .map(|word| u32_to_le_field_bytes(*word)),
+                            );
+                            for (hmac, (inner_digest, digest)) in
+                                block.iterations.iter().zip(digests.iter())
+                            {
+                                writer.write(&hmac.inner_digest, &to_field_bytes(*inner_digest));
+                                writer.write(&hmac.digest, &to_field_bytes(*digest));
+                            }
+                        }
+                    }
+                    stark.air_data.write_trace_instructions(&mut writer);
+                }
+            }
+        });
+
+        let (trace, public) = (writer_data.trace, writer_data.public);
+
+        let proof = timed!(
+            timing,
+            "generate stark proof",
+            stark.prove(&trace, &public, &mut timing).unwrap()
+        );
+
+        stark.verify(proof, &public).unwrap();
+
+        timing.print();
+    }
+}
@@ -1,11 +1,11 @@
 use super::register::SHA512DigestRegister;
-use super::SHA512;
+use super::SHA512Variant;
 use crate::chip::memory::pointer::slice::Slice;
 use crate::chip::memory::time::Time;
 use crate::chip::register::array::ArrayRegister;
 use crate::chip::register::bit::BitRegister;
 use crate::chip::register::element::ElementRegister;
-use crate::chip::register::Register;
+use crate::chip::register::{Register, RegisterSerializable};
 use crate::chip::uint::operations::instruction::UintInstructions;
 use crate::chip::uint::register::U64Register;
 use crate::chip::uint::util::{u64_from_le_field_bytes, u64_to_le_field_bytes};
@@ -15,12 +15,12 @@ use crate::machine::bytes::builder::BytesBuilder;
 use crate::machine::hash::sha::algorithm::SHAir;
 use crate::machine::hash::{HashDigest, HashIntConversion, HashInteger};
 
-impl<B: Builder> HashInteger<B> for SHA512 {
+impl<B: Builder, V: SHA512Variant> HashInteger<B> for V {
     type Value = <U64Register as Register>::Value<B::Field>;
     type IntRegister = U64Register;
 }
 
-impl<B: Builder> HashIntConversion<B> for SHA512 {
+impl<B: Builder, V: SHA512Variant> HashIntConversion<B> for V {
     fn int_to_field_value(int: Self::Integer) -> Self::Value {
         u64_to_le_field_bytes(int)
     }
@@ -30,11 +30,11 @@ impl<B: Builder> HashIntConversion<B> for SHA512 {
     }
 }
 
-impl<B: Builder> HashDigest<B> for SHA512 {
+impl<B: Builder, V: SHA512Variant> HashDigest<B> for V {
     type DigestRegister = SHA512DigestRegister;
 }
 
-impl<L: AirParameters> SHAir<BytesBuilder<L>, 80> for SHA512
+impl<L: AirParameters, V: SHA512Variant> SHAir<BytesBuilder<L>, 80> for V
 where
     L::Instruction: UintInstructions,
 {
@@ -59,14 +59,26 @@ where
 
     fn load_state(
         builder: &mut BytesBuilder<L>,
-        hash_state_public: &[Self::StateVariable],
+        hash_state: &[Self::StateVariable],
         digest_indices: ArrayRegister<ElementRegister>,
+        first_row: Option<ElementRegister>,
     ) -> Self::StatePointer {
         let state_ptr = builder.uninit_slice();
 
-        for (i, h_slice) in digest_indices.iter().zip(hash_state_public.iter()) {
+        for (i, h_slice) in digest_indices.iter().zip(hash_state.iter()) {
+            let multiplicity = h_slice
+                .is_trace()
+                .then(|| first_row.expect("Digests in the trace are freed from the first row"));
             for (j, h) in h_slice.iter().enumerate() {
-                builder.free(&state_ptr.get(j), h, &Time::from_element(i));
+                match multiplicity {
+                    Some(m) => builder.free_with_multiplicity(
+                        &state_ptr.get(j),
+                        h,
+                        &Time::from_element(i),
+                        m,
+                    ),
+                    None => builder.free(&state_ptr.get(j), h, &Time::from_element(i)),
+                }
             }
         }
 
@@ -207,8 +219,9 @@ mod tests {
 
     use crate::chip::uint::operations::instruction::UintInstruction;
     use crate::chip::AirParameters;
-    use crate::machine::hash::sha::builder::test_utils::test_sha;
-    use crate::machine::hash::sha::sha512::SHA512;
+    use crate::machine::hash::sha::algorithm::SHAPure;
+    use crate::machine::hash::sha::builder::test_utils::{test_sha, test_sha_variable};
+    use crate::machine::hash::sha::sha512::{SHA384, SHA512, SHA512_256};
     use crate::math::goldilocks::cubic::GoldilocksCubicParameters;
 
     #[derive(Clone, Debug, Serialize, Deserialize)]
@@ -272,4 +285,78 @@ mod tests {
             ],
         );
     }
+
+    #[test]
+    fn test_sha384_and_sha512_256() {
+        let short_msg = b"plonky2";
+        let long_msg = hex::decode("35c323757c20640a294345c89c0bfcebe3d554fdb0c7b7a0bdb72222c531b1ecf7ec1c43f4de9d49556de87b86b26a98942cb078486fdb44de38b80864c3973153756363696e6374204c616273").unwrap();
+        let messages = [short_msg.as_slice(), long_msg.as_slice()];
+
+        test_sha::<SHA512Test, SHA384, _, _, 80>(
+            messages,
+            [
+                "7e4cc34d59b11b2c7db390417a3722112c5c2522d2bc6aab414eca71daf3db08b90bdd9781e374bd2946c926e4e36f31",
+                "39efc652dc1fbdb1db7b39f447ced380e2ebc1e3e3ffff5b45d4b349e0844c0e243c306d2dcc0b52436f06838f10b45a",
+            ],
+        );
+        test_sha::<SHA512Test, SHA512_256, _, _, 80>(
+            messages,
+            [
+                "f897d1b844ca01eff99cb7f1784abe151bde2ddad7786d3c980bdd25032eb03d",
+                "c8edf3df5ccaa7a262ead572ae4bc9ff373390869b3790a426bf318b5e456e51",
+            ],
+        );
+    }
+
+    #[test]
+    fn test_sha512_pad_to_chunks() {
+        let msg = (0..200).collect::<Vec<u8>>();
+        assert_eq!(SHA512::num_chunks(b""), 1);
+        assert_eq!(SHA512::num_chunks(&msg), 2);
+
+        let padded = SHA512::pad_to_chunks(&msg, 4);
+        assert_eq!(padded.len(), 4 * 16);
+        assert_eq!(padded[..32], SHA512::pad(&msg));
+        assert!(padded[32..].iter().all(|w| *w == 0));
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_sha512_pad_to_too_few_chunks() {
+        SHA512::pad_to_chunks(&[0u8; 112], 1);
+    }
+
+    #[test]
+    fn test_sha512_variable_length() {
+        let short_msg = b"plonky2";
+        let short_expected_digest = "7c6159dd615db8c15bc76e23d36106e77464759979a0fcd1366e531f552cfa0852dbf5c832f00bb279cbc945b44a132bff3ed0028259813b6a07b57326e88c87";
+        let long_msg = (0..200).collect::<Vec<u8>>();
+        let long_expected_digest = "986058e9895e2c2ab8f9e8cbdf801db12a44842a56a91d5a4e87b1fc98b293722c4664142e42c3c551ff898646268cd92b84ed230b8c94bed7798d4f27cd7465";
+        let empty_expected_digest = "cf83e1357eefb8bdf1542850d66d8007d620e4050b5715dc83f4a921d36ce9ce47d0d13c5d85f2b0ff8318d2877eec2f63b931bd47417a81a538327af927da3e";
+
+        // Every message occupies the same number of chunks.
+        let max_chunks = 3;
+        test_sha_variable::<SHA512Test, SHA512, _, _, 80>(
+            [
+                (short_msg.as_slice(), max_chunks),
+                (long_msg.as_slice(), max_chunks),
+                (b"".as_slice(), max_chunks),
+                (long_msg.as_slice(), max_chunks),
+            ],
+            [
+                short_expected_digest,
+                long_expected_digest,
+                empty_expected_digest,
+                long_expected_digest,
+            ],
+        );
+
+        test_sha_variable::<SHA512Test, SHA384, _, _, 80>(
+            [(long_msg.as_slice(), 2), (long_msg.as_slice(), max_chunks)],
+            [
+                "7ea4bb2534c67036f49de7beb5fe8a2478df04ff3fef40a9cd4923999a590e9912df1297217ce1a021aa2fb1013498b8",
+                "7ea4bb2534c67036f49de7beb5fe8a2478df04ff3fef40a9cd4923999a590e9912df1297217ce1a021aa2fb1013498b8",
+            ],
+        );
+    }
 }
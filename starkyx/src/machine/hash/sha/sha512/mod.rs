@@ -1,12 +1,54 @@
+//! The SHA-512 family of hash functions.
+//!
+//! SHA-384 and SHA-512/256 use the compression function of SHA-512 with different initial hash
+//! values, and their digests are the first words of the final state.
+
+use core::fmt::Debug;
+
+use serde::de::DeserializeOwned;
 use serde::{Deserialize, Serialize};
 
 pub mod air;
 pub mod pure;
 pub mod register;
 
+/// A hash function of the SHA-512 family.
+pub trait SHA512Variant:
+    Debug + Clone + Copy + 'static + Serialize + DeserializeOwned + Send + Sync
+{
+    /// The initial hash value.
+    const IV: [u64; 8];
+    /// The number of 64-bit words of the final state in the digest.
+    const DIGEST_WORDS: usize;
+}
+
 #[derive(Clone, Copy, Debug, Serialize, Deserialize)]
 pub struct SHA512;
 
+/// SHA-384, whose digest is the first 6 words of the state.
+#[derive(Clone, Copy, Debug, Serialize, Deserialize)]
+pub struct SHA384;
+
+/// SHA-512/256, whose digest is the first 4 words of the state.
+#[derive(Clone, Copy, Debug, Serialize, Deserialize)]
+#[allow(non_camel_case_types)]
+pub struct SHA512_256;
+
+impl SHA512Variant for SHA512 {
+    const IV: [u64; 8] = INITIAL_HASH;
+    const DIGEST_WORDS: usize = 8;
+}
+
+impl SHA512Variant for SHA384 {
+    const IV: [u64; 8] = SHA384_INITIAL_HASH;
+    const DIGEST_WORDS: usize = 6;
+}
+
+impl SHA512Variant for SHA512_256 {
+    const IV: [u64; 8] = SHA512_256_INITIAL_HASH;
+    const DIGEST_WORDS: usize = 4;
+}
+
 pub(crate) const ROUND_CONSTANTS: [u64; 80] = [
     0x428a2f98d728ae22,
     0x7137449123ef65cd,
@@ -100,3 +142,25 @@ pub(crate) const INITIAL_HASH: [u64; 8] = [
     0x1f83d9abfb41bd6b,
     0x5be0cd19137e2179,
 ];
+
+pub(crate) const SHA384_INITIAL_HASH: [u64; 8] = [
+    0xcbbb9d5dc1059ed8,
+    0x629a292a367cd507,
+    0x9159015a3070dd17,
+    0x152fecd8f70e5939,
+    0x67332667ffc00b31,
+    0x8eb44a8768581511,
+    0xdb0c2e0d64f98fa7,
+    0x47b5481dbefa4fa4,
+];
+
+pub(crate) const SHA512_256_INITIAL_HASH: [u64; 8] = [
+    0x22312194fc2bf72c,
+    0x9f555fa3c84c64c2,
+    0x2393b86b6f53b151,
+    0x963877195940eabd,
+    0x96283ee2a88effe3,
+    0xbe5e1e2553863992,
+    0x2b0199fc2c85b8aa,
+    0x0eb72ddc81c52ca2,
+];
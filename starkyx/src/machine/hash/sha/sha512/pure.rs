@@ -1,14 +1,15 @@
-use super::{INITIAL_HASH, ROUND_CONSTANTS, SHA512};
+use super::{SHA512Variant, ROUND_CONSTANTS};
 use crate::machine::hash::sha::algorithm::SHAPure;
 use crate::machine::hash::HashPureInteger;
 
-impl HashPureInteger for SHA512 {
+impl<V: SHA512Variant> HashPureInteger for V {
     type Integer = u64;
 }
 
-impl SHAPure<80> for SHA512 {
-    const INITIAL_HASH: [Self::Integer; 8] = INITIAL_HASH;
+impl<V: SHA512Variant> SHAPure<80> for V {
+    const INITIAL_HASH: [Self::Integer; 8] = V::IV;
     const ROUND_CONSTANTS: [Self::Integer; 80] = ROUND_CONSTANTS;
+    const DIGEST_LENGTH: usize = V::DIGEST_WORDS;
 
     fn pad(msg: &[u8]) -> Vec<Self::Integer> {
         let mut padded_msg = Vec::new();
@@ -67,13 +68,14 @@ impl SHAPure<80> for SHA512 {
     }
 
     fn decode(digest: &str) -> [Self::Integer; 8] {
-        hex::decode(digest)
+        let mut words = hex::decode(digest)
             .unwrap()
             .chunks_exact(8)
             .map(|x| u64::from_be_bytes(x.try_into().unwrap()))
-            .collect::<Vec<_>>()
-            .try_into()
-            .unwrap()
+            .collect::<Vec<_>>();
+        assert_eq!(words.len(), V::DIGEST_WORDS, "Invalid digest length");
+        words.resize(8, 0);
+        words.try_into().unwrap()
     }
 }
 
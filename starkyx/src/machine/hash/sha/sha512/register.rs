@@ -52,6 +52,11 @@ impl SHA512DigestRegister {
         self.0.iter()
     }
 
+    /// The first `num_words` words of the state, which are the digest of the truncated variants.
+    pub fn truncate(&self, num_words: usize) -> ArrayRegister<U64Register> {
+        self.0.get_subarray(0..num_words)
+    }
+
     pub fn from_array(array: ArrayRegister<U64Register>) -> Self {
         assert_eq!(array.len(), 8);
         Self(array)
@@ -0,0 +1,244 @@
+use core::marker::PhantomData;
+
+use plonky2::util::log2_ceil;
+
+use super::algorithm::SHAir;
+use crate::chip::arithmetic::expression::ArithmeticExpression;
+use crate::chip::register::array::ArrayRegister;
+use crate::chip::register::bit::BitRegister;
+use crate::chip::register::element::ElementRegister;
+use crate::chip::register::{Register, RegisterSerializable, RegisterSized};
+use crate::chip::uint::bytes::register::ByteRegister;
+use crate::machine::builder::Builder;
+use crate::math::prelude::*;
+
+/// A streaming interface to the SHA AIR, which lays out the chunks of many messages in one trace.
+///
+/// The bytes of a message are given by calls to [`HashMachine::absorb`], and the message is
+/// closed by [`HashMachine::finalize`], which pads it into chunks. The chunks of all messages are
+/// placed one after the other, so that a trace of `HashMachine::num_rows` rows hashes all of them,
+/// and [`HashMachine::digests`] returns the digests of the messages in the order they were closed.
+///
+/// The lengths of the messages are fixed by the circuit. The bytes must be public registers, and
+/// the digests are public registers to be written before the global instructions.
+#[derive(Debug, Clone)]
+pub struct HashMachine<S, const CYCLE_LENGTH: usize> {
+    /// The bytes of the message which is not closed yet.
+    bytes: Vec<ByteRegister>,
+    padded_chunks: Vec<ArrayRegister<ElementRegister>>,
+    end_bits: Vec<bool>,
+    _marker: PhantomData<S>,
+}
+
+impl<S, const CYCLE_LENGTH: usize> HashMachine<S, CYCLE_LENGTH> {
+    pub fn new() -> Self {
+        Self {
+            bytes: Vec::new(),
+            padded_chunks: Vec::new(),
+            end_bits: Vec::new(),
+            _marker: PhantomData,
+        }
+    }
+
+    /// Appends `bytes` to the current message.
+    pub fn absorb(&mut self, bytes: impl IntoIterator<Item = ByteRegister>) {
+        for byte in bytes {
+            assert!(!byte.is_trace(), "The bytes must be public");
+            self.bytes.push(byte);
+        }
+    }
+
+    /// Closes the current message and returns the index of its digest.
+    ///
+    /// The message is padded into chunks whose words are the bytes of the message in big-endian
+    /// order, followed by the padding, which is set to constants.
+    pub fn finalize<B: Builder>(&mut self, builder: &mut B) -> usize
+    where
+        S: SHAir<B, CYCLE_LENGTH>,
+    {
+        let bytes = core::mem::take(&mut self.bytes);
+        let word_size = S::IntRegister::size_of();
+
+        // The padding of a message of zeros of the same length, whose cells are the bytes of
+        // each word in little-endian order.
+        let padded_msg = S::pad(&vec![0u8; bytes.len()]);
+        let num_chunks = padded_msg.len() / 16;
+        for (i, chunk) in padded_msg.chunks_exact(16).enumerate() {
+            let padded_chunk = builder.alloc_array_public::<S::IntRegister>(16);
+            let cells =
+                ArrayRegister::<ElementRegister>::from_register_unsafe(*padded_chunk.register());
+            for (k, word) in chunk.iter().enumerate() {
+                let value = S::int_to_field_value(*word);
+                for (j, padding) in S::IntRegister::align(&value).iter().enumerate() {
+                    let cell = cells.get(k * word_size + j);
+                    let position = (16 * i + k) * word_size + word_size - 1 - j;
+                    let expression = match bytes.get(position) {
+                        Some(byte) => byte.expr(),
+                        None => ArithmeticExpression::from_constant(*padding),
+                    };
+                    builder.api().set_to_expression_public(&cell, expression);
+                }
+            }
+            self.padded_chunks.push(cells);
+            self.end_bits.push(i == num_chunks - 1);
+        }
+
+        self.end_bits.iter().filter(|bit| **bit).count() - 1
+    }
+
+    /// The number of chunks of the closed messages.
+    pub fn num_chunks(&self) -> usize {
+        self.padded_chunks.len()
+    }
+
+    /// The number of rows of a trace hashing the closed messages.
+    pub fn num_rows(&self) -> usize {
+        1 << log2_ceil(CYCLE_LENGTH * self.num_chunks())
+    }
+
+    /// Hashes the closed messages, and returns their digests.
+    pub fn digests<B: Builder>(self, builder: &mut B) -> Vec<S::StateVariable>
+    where
+        S: SHAir<B, CYCLE_LENGTH>,
+    {
+        assert!(self.bytes.is_empty(), "The last message is not finalized");
+        assert!(!self.padded_chunks.is_empty(), "No message to hash");
+        let padded_chunks = self
+            .padded_chunks
+            .iter()
+            .map(|cells| ArrayRegister::from_register_unsafe(*cells.register()))
+            .collect::<Vec<_>>();
+        let end_bits = builder.constant_array::<BitRegister>(
+            &self
+                .end_bits
+                .iter()
+                .map(|bit| B::Field::from_canonical_usize(*bit as usize))
+                .collect::<Vec<_>>(),
+        );
+        let digest_indices = builder.constant_array::<ElementRegister>(
+            &self
+                .end_bits
+                .iter()
+                .enumerate()
+                .filter(|(_, bit)| **bit)
+                .map(|(i, _)| B::Field::from_canonical_usize(i))
+                .collect::<Vec<_>>(),
+        );
+        S::sha(
+            builder,
+            &padded_chunks,
+            &end_bits,
+            &end_bits,
+            digest_indices,
+        )
+    }
+}
+
+impl<S, const CYCLE_LENGTH: usize> Default for HashMachine<S, CYCLE_LENGTH> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use plonky2::field::goldilocks_field::GoldilocksField;
+    use plonky2::util::timing::TimingTree;
+    use serde::{Deserialize, Serialize};
+
+    use super::*;
+    use crate::chip::uint::operations::instruction::UintInstruction;
+    use crate::chip::uint::util::u32_to_le_field_bytes;
+    use crate::chip::AirParameters;
+    use crate::machine::bytes::builder::BytesBuilder;
+    use crate::machine::hash::sha::algorithm::SHAPure;
+    use crate::machine::hash::sha::sha256::SHA256;
+    use crate::math::goldilocks::cubic::GoldilocksCubicParameters;
+    use crate::plonky2::stark::config::CurtaPoseidonGoldilocksConfig;
+    use crate::prelude::{AirWriter, AirWriterData};
+
+    #[derive(Clone, Debug, Serialize, Deserialize)]
+    pub struct HashMachineTest;
+
+    impl AirParameters for HashMachineTest {
+        type Field = GoldilocksField;
+        type CubicParams = GoldilocksCubicParameters;
+
+        type Instruction = UintInstruction;
+
+        const NUM_FREE_COLUMNS: usize = 418;
+        const EXTENDED_COLUMNS: usize = 912;
+    }
+
+    #[test]
+    fn test_hash_machine_sha256() {
+        type F = GoldilocksField;
+        type C = CurtaPoseidonGoldilocksConfig;
+
+        let _ = env_logger::builder().is_test(true).try_init();
+        let mut timing = TimingTree::new("test_hash_machine_sha256", log::Level::Debug);
+
+        // Messages given by several calls to `absorb`, with their digests.
+        let long_msg = (0..200).collect::<Vec<u8>>();
+        let messages: [(Vec<&[u8]>, &str); 3] = [
+            (
+                vec![b"a".as_slice(), b"bc".as_slice()],
+                "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad",
+            ),
+            (
+                vec![&long_msg[..100], &long_msg[100..]],
+                "1901da1c9f699b48f6b2636e65cbf73abf99d0441ef67f5c540a42f7051dec6f",
+            ),
+            (
+                vec![],
+                "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855",
+            ),
+        ];
+
+        // Build the stark.
+        let mut builder = BytesBuilder::<HashMachineTest>::new();
+        let mut machine = HashMachine::<SHA256, 64>::new();
+        let mut byte_registers = Vec::new();
+        for (parts, _) in messages.iter() {
+            for part in parts.iter() {
+                let bytes = builder.alloc_array_public::<ByteRegister>(part.len());
+                machine.absorb(bytes.iter());
+                byte_registers.push((bytes, *part));
+            }
+            machine.finalize(&mut builder);
+        }
+        let num_rows = machine.num_rows();
+        assert_eq!(num_rows, 1 << 9);
+        let digests = machine.digests(&mut builder);
+
+        let stark = builder.build::<C, 2>(num_rows);
+
+        // Write trace.
+        let mut writer_data = AirWriterData::new(&stark.air_data, num_rows);
+        let mut writer = writer_data.public_writer();
+
+        for (register, bytes) in byte_registers.iter() {
+            writer.write_array(register, bytes.iter().map(|b| F::from_canonical_u8(*b)));
+        }
+        for (digest, (_, expected)) in digests.iter().zip(messages.iter()) {
+            writer.write_array(
+                &digest.as_array(),
+                SHA256::decode(expected).map(u32_to_le_field_bytes),
+            );
+        }
+
+        stark.air_data.write_global_instructions(&mut writer);
+
+        for mut chunk in writer_data.chunks(num_rows) {
+            for i in 0..num_rows {
+                let mut writer = chunk.window_writer(i);
+                stark.air_data.write_trace_instructions(&mut writer);
+            }
+        }
+
+        let (trace, public) = (writer_data.trace, writer_data.public);
+
+        let proof = stark.prove(&trace, &public, &mut timing).unwrap();
+        stark.verify(proof, &public).unwrap();
+    }
+}
@@ -0,0 +1,497 @@
+use itertools::Itertools;
+use log::debug;
+use plonky2::field::goldilocks_field::GoldilocksField;
+use plonky2::util::log2_ceil;
+
+use crate::chip::arithmetic::expression::ArithmeticExpression;
+use crate::chip::memory::pointer::slice::Slice;
+use crate::chip::memory::time::Time;
+use crate::chip::register::array::ArrayRegister;
+use crate::chip::register::bit::BitRegister;
+use crate::chip::register::element::ElementRegister;
+use crate::chip::register::Register;
+use crate::machine::builder::Builder;
+use crate::machine::hash::poseidon2::air::{load_row_value, Poseidon2Air};
+use crate::machine::hash::poseidon2::{CAPACITY, DIGEST_LENGTH};
+use crate::math::prelude::*;
+
+pub trait MerkleBuilder: Builder<Field = GoldilocksField> {
+    /// Verifies the authentication paths of `leaves` against `root`.
+    ///
+    /// The path of `leaves[i]` is given by the siblings `siblings[i * depth..(i + 1) * depth]`,
+    /// from the leaf to the root, and the bits `index_bits[i * depth..(i + 1) * depth]` of the
+    /// index of the leaf, where `depth = siblings.len() / leaves.len()`. The root, the leaves and
+    /// the paths are public values.
+    ///
+    /// Each row hashes a node with its sibling, so that the paths take `siblings.len()` rows. The
+    /// first node of a path is its leaf and the others are the outputs of the previous rows, which
+    /// are passed on through memory, and the outputs of the last rows of the paths are checked
+    /// against the root. A node is hashed with a single permutation, which requires the rate to be
+    /// twice the digest length, as for
+    /// [`Poseidon2Goldilocks12`](crate::machine::hash::poseidon2::Poseidon2Goldilocks12).
+    fn verify_merkle_paths<P: Poseidon2Air<Self, WIDTH>, const WIDTH: usize>(
+        &mut self,
+        root: &ArrayRegister<ElementRegister>,
+        leaves: &[ArrayRegister<ElementRegister>],
+        siblings: &[ArrayRegister<ElementRegister>],
+        index_bits: &ArrayRegister<BitRegister>,
+    ) {
+        assert_eq!(
+            P::RATE,
+            2 * DIGEST_LENGTH,
+            "The rate must be twice the digest length"
+        );
+        assert_eq!(root.len(), DIGEST_LENGTH);
+        let num_paths = leaves.len();
+        assert!(num_paths > 0, "There must be at least one path");
+        let depth = siblings.len() / num_paths;
+        assert!(depth > 0, "The paths must not be empty");
+        assert_eq!(siblings.len(), num_paths * depth);
+        assert_eq!(index_bits.len(), num_paths * depth);
+
+        let num_real_rows = num_paths * depth;
+        debug!("AIR degree before padding: {}", num_real_rows);
+        let degree_log = log2_ceil(num_real_rows);
+        assert!(degree_log < 31, "AIR degree is too large");
+        debug!("AIR degree after padding: {}", 1 << degree_log);
+        self.api().require_rows("Merkle paths", 1 << degree_log);
+        // The rows after the real ones hash zero nodes and their outputs are ignored.
+        let num_rows = 1 << degree_log;
+        let clk = self.clk();
+
+        let zero = self.constant::<ElementRegister>(&GoldilocksField::ZERO);
+        let one = self.constant::<ElementRegister>(&GoldilocksField::ONE);
+        let zero_bit = self.constant::<BitRegister>(&GoldilocksField::ZERO);
+
+        // Load the sibling and the index bit of the current row.
+        let sibling = (0..DIGEST_LENGTH)
+            .map(|j| {
+                let values = siblings
+                    .iter()
+                    .map(|sibling| {
+                        assert_eq!(sibling.len(), DIGEST_LENGTH);
+                        sibling.get(j)
+                    })
+                    .collect_vec();
+                load_row_value(self, &values, zero, num_rows, clk)
+            })
+            .collect_vec();
+        let bit = load_row_value(
+            self,
+            &index_bits.iter().collect_vec(),
+            zero_bit,
+            num_rows,
+            clk,
+        );
+
+        // The multiplicities of the output as the node of the next row, and as the root.
+        let is_inner_values = (0..num_real_rows)
+            .map(|row| if row % depth == depth - 1 { zero } else { one })
+            .collect_vec();
+        let is_inner = load_row_value(self, &is_inner_values, zero, num_rows, clk);
+        let is_last_values = (0..num_real_rows)
+            .map(|row| if row % depth == depth - 1 { one } else { zero })
+            .collect_vec();
+        let is_last = load_row_value(self, &is_last_values, zero, num_rows, clk);
+
+        // Store the leaves as the nodes of the first rows of the paths, and load the node of the
+        // current row.
+        let nodes = (0..DIGEST_LENGTH)
+            .map(|_| self.uninit_slice())
+            .collect_vec();
+        for (i, leaf) in leaves.iter().enumerate() {
+            assert_eq!(leaf.len(), DIGEST_LENGTH);
+            for (slice, element) in nodes.iter().zip(leaf.iter()) {
+                self.store(
+                    &slice.get(i * depth),
+                    element,
+                    &Time::zero(),
+                    None,
+                    None,
+                    None,
+                );
+            }
+        }
+        for slice in nodes.iter() {
+            for row in num_real_rows..num_rows {
+                self.store(&slice.get(row), zero, &Time::zero(), None, None, None);
+            }
+        }
+        let node = nodes
+            .iter()
+            .map(|slice| self.load(&slice.get_at(clk), &Time::zero(), None, None))
+            .collect_vec();
+
+        // Hash the node with its sibling, in the order given by the index bit.
+        let left = node
+            .iter()
+            .zip(sibling.iter())
+            .map(|(node_j, sibling_j)| self.select(bit, sibling_j, node_j))
+            .collect_vec();
+        let right = node
+            .iter()
+            .zip(sibling.iter())
+            .map(|(node_j, sibling_j)| self.select(bit, node_j, sibling_j))
+            .collect_vec();
+        let input = left
+            .iter()
+            .chain(right.iter())
+            .map(|register| register.expr())
+            .chain((0..CAPACITY).map(|_| ArithmeticExpression::zero()))
+            .collect_vec();
+        let output = P::permutation(self, &input);
+
+        // Pass the output on as the node of the next row.
+        for (slice, element) in nodes.iter().zip(output.iter()) {
+            self.store(
+                &slice.get_at_shifted(clk, 1),
+                element,
+                &Time::zero(),
+                Some(is_inner),
+                None,
+                None,
+            );
+        }
+
+        // Store the outputs of the last rows and free them as the root at the end of each path.
+        let root_ptr = self.uninit_slice();
+        for (j, element) in output.iter().take(DIGEST_LENGTH).enumerate() {
+            self.store(
+                &root_ptr.get(j),
+                element,
+                &Time::from_element(clk),
+                Some(is_last),
+                None,
+                None,
+            );
+        }
+        for i in 0..num_paths {
+            let last_row = self.constant::<ElementRegister>(
+                &GoldilocksField::from_canonical_usize((i + 1) * depth - 1),
+            );
+            for (j, element) in root.iter().enumerate() {
+                self.free(&root_ptr.get(j), element, &Time::from_element(last_row));
+            }
+        }
+    }
+
+    /// Computes the root of the tree whose leaves are the digests of the chunks of the memory
+    /// region `region[start..start + num_leaves * P::RATE]`, see [`MerkleTree::from_elements`].
+    ///
+    /// The elements of the region are read once each, at the write time `last_write`, so they
+    /// must be stored with a multiplicity which accounts for this read. The number of leaves must
+    /// be a power of two, and the trace must have `2 * num_leaves` rows. The root is a public
+    /// value written by the prover.
+    ///
+    /// Each row computes one permutation. The first `num_leaves` rows hash the chunks of the
+    /// leaves, and the next ones hash the nodes of the tree level by level, the node of each row
+    /// being the parent of the nodes of two previous rows, which are passed on through memory.
+    /// The root is the output of the row before the last one, and the last row is a dummy row.
+    /// All rows read a chunk of the region, and the gadget stores zero chunks in
+    /// `region[start + num_leaves * P::RATE..start + 2 * num_leaves * P::RATE]` for the rows
+    /// which do not hash a leaf, so these indices of the slice must not be used otherwise.
+    ///
+    /// [`MerkleTree::from_elements`]: super::MerkleTree::from_elements
+    fn merkle_root_of_region<P: Poseidon2Air<Self, WIDTH>, const WIDTH: usize>(
+        &mut self,
+        region: &Slice<ElementRegister>,
+        start: usize,
+        num_leaves: usize,
+        last_write: &Time<Self::Field>,
+    ) -> ArrayRegister<ElementRegister> {
+        assert_eq!(
+            P::RATE,
+            2 * DIGEST_LENGTH,
+            "The rate must be twice the digest length"
+        );
+        assert!(
+            num_leaves.is_power_of_two(),
+            "The number of leaves must be a power of two"
+        );
+        let num_rows = 2 * num_leaves;
+        let region_end = start + num_leaves * P::RATE;
+        assert!(
+            start + num_rows * P::RATE <= i32::MAX as usize,
+            "The region is too large"
+        );
+        let clk = self.clk();
+
+        let zero = self.constant::<ElementRegister>(&GoldilocksField::ZERO);
+        let one = self.constant::<ElementRegister>(&GoldilocksField::ONE);
+        let two = self.constant::<ElementRegister>(&GoldilocksField::TWO);
+        let zero_bit = self.constant::<BitRegister>(&GoldilocksField::ZERO);
+        let one_bit = self.constant::<BitRegister>(&GoldilocksField::ONE);
+
+        // Load the chunk of the current row, which is a zero chunk after the leaves.
+        for i in region_end..start + num_rows * P::RATE {
+            self.store(&region.get(i), zero, last_write, None, None, None);
+        }
+        let chunk_index = self.expression::<ElementRegister>(
+            clk.expr() * GoldilocksField::from_canonical_usize(P::RATE),
+        );
+        let chunk = (0..P::RATE)
+            .map(|j| {
+                let ptr = region.get_at_shifted(chunk_index, (start + j) as i32);
+                self.load(&ptr, last_write, None, None)
+            })
+            .collect_vec();
+
+        // The node of the row `i` is stored at the index `num_rows + i` of the slices of the
+        // nodes, so that the children of the node of each row are at the indices `2 * clk` and
+        // `2 * clk + 1`. These are zero nodes for the rows of the leaves, and the last row reads
+        // the root and a zero node.
+        let nodes = (0..DIGEST_LENGTH)
+            .map(|_| self.uninit_slice())
+            .collect_vec();
+        for slice in nodes.iter() {
+            for i in (0..num_rows).chain([2 * num_rows - 1]) {
+                self.store(&slice.get(i), zero, &Time::zero(), None, None, None);
+            }
+        }
+        let child_index = self.expression::<ElementRegister>(clk.expr() * GoldilocksField::TWO);
+        let left = nodes
+            .iter()
+            .map(|slice| self.load(&slice.get_at(child_index), &Time::zero(), None, None))
+            .collect_vec();
+        let right = nodes
+            .iter()
+            .map(|slice| {
+                let ptr = slice.get_at_shifted(child_index, 1);
+                self.load(&ptr, &Time::zero(), None, None)
+            })
+            .collect_vec();
+
+        // Hash the chunk in the rows of the leaves, and the children in the other rows.
+        let is_leaf = load_row_value(self, &vec![one_bit; num_leaves], zero_bit, num_rows, clk);
+        let input = chunk
+            .iter()
+            .zip(left.iter().chain(right.iter()))
+            .map(|(chunk_j, child_j)| self.select(is_leaf, chunk_j, child_j).expr())
+            .chain((0..CAPACITY).map(|_| ArithmeticExpression::zero()))
+            .collect_vec();
+        let output = P::permutation(self, &input);
+
+        // Store the node of the current row, for its parent and also as the root in the row
+        // before the last one, and not in the last row.
+        let mut multiplicities = vec![one; num_rows];
+        multiplicities[num_rows - 2] = two;
+        multiplicities[num_rows - 1] = zero;
+        let multiplicity = load_row_value(self, &multiplicities, zero, num_rows, clk);
+        for (slice, element) in nodes.iter().zip(output.iter()) {
+            self.store(
+                &slice.get_at_shifted(clk, num_rows as i32),
+                element,
+                &Time::zero(),
+                Some(multiplicity),
+                None,
+                None,
+            );
+        }
+
+        // Free the root, which is the node of the row before the last one.
+        let root = self.alloc_array_public::<ElementRegister>(DIGEST_LENGTH);
+        for (slice, element) in nodes.iter().zip(root.iter()) {
+            self.free(&slice.get(2 * num_rows - 2), element, &Time::zero());
+        }
+        root
+    }
+}
+
+impl<B: Builder<Field = GoldilocksField>> MerkleBuilder for B {}
+
+#[cfg(test)]
+mod tests {
+    use plonky2::iop::witness::{PartialWitness, WitnessWrite};
+    use plonky2::plonk::circuit_builder::CircuitBuilder;
+    use plonky2::plonk::circuit_data::CircuitConfig;
+    use plonky2::timed;
+    use plonky2::util::timing::TimingTree;
+    use serde::{Deserialize, Serialize};
+
+    use super::*;
+    use crate::chip::AirParameters;
+    use crate::machine::hash::poseidon2::pure::Poseidon2Pure;
+    use crate::machine::hash::poseidon2::Poseidon2Goldilocks12;
+    use crate::machine::merkle::MerkleTree;
+    use crate::machine::stark::builder::StarkBuilder;
+    use crate::math::goldilocks::cubic::GoldilocksCubicParameters;
+    use crate::plonky2::stark::config::{CurtaConfig, CurtaPoseidonGoldilocksConfig};
+    use crate::prelude::{AirWriter, AirWriterData, EmptyInstruction};
+
+    #[derive(Clone, Debug, Serialize, Deserialize)]
+    pub struct MerkleTest;
+
+    impl AirParameters for MerkleTest {
+        type Field = GoldilocksField;
+        type CubicParams = GoldilocksCubicParameters;
+        type Instruction = EmptyInstruction<GoldilocksField>;
+
+        const NUM_FREE_COLUMNS: usize = 531;
+        const EXTENDED_COLUMNS: usize = 93;
+    }
+
+    #[derive(Clone, Debug, Serialize, Deserialize)]
+    pub struct MerkleRegionTest;
+
+    impl AirParameters for MerkleRegionTest {
+        type Field = GoldilocksField;
+        type CubicParams = GoldilocksCubicParameters;
+        type Instruction = EmptyInstruction<GoldilocksField>;
+
+        const NUM_FREE_COLUMNS: usize = 540;
+        const EXTENDED_COLUMNS: usize = 120;
+    }
+
+    #[test]
+    fn test_merkle_paths() {
+        type L = MerkleTest;
+        type P = Poseidon2Goldilocks12;
+        type C = CurtaPoseidonGoldilocksConfig;
+        type Config = <C as CurtaConfig<2>>::GenericConfig;
+
+        let _ = env_logger::builder().is_test(true).try_init();
+        let mut timing = TimingTree::new("test_merkle_paths", log::Level::Debug);
+
+        let depth = 5;
+        let leaves_values = (0..1 << depth)
+            .map(|_| [(); DIGEST_LENGTH].map(|_| GoldilocksField::rand()))
+            .collect_vec();
+        let tree = MerkleTree::<P, 12>::new(leaves_values.clone());
+        let indices = [0, 1, 6, 13, 17, 31, 31];
+
+        // Build the stark.
+        let mut builder = StarkBuilder::<L>::new();
+        let root = builder.alloc_array_public::<ElementRegister>(DIGEST_LENGTH);
+        let leaves = indices
+            .iter()
+            .map(|_| builder.alloc_array_public::<ElementRegister>(DIGEST_LENGTH))
+            .collect_vec();
+        let siblings = (0..indices.len() * depth)
+            .map(|_| builder.alloc_array_public::<ElementRegister>(DIGEST_LENGTH))
+            .collect_vec();
+        let index_bits = builder.alloc_array_public::<BitRegister>(indices.len() * depth);
+        builder.verify_merkle_paths::<P, 12>(&root, &leaves, &siblings, &index_bits);
+
+        let num_rows = 1 << log2_ceil(indices.len() * depth);
+        let stark = builder.build::<C, 2>(num_rows);
+
+        // Build the recursive circuit.
+        let config_rec = CircuitConfig::standard_recursion_config();
+        let mut recursive_builder = CircuitBuilder::<GoldilocksField, 2>::new(config_rec);
+
+        let (proof_target, public_input) =
+            stark.add_virtual_proof_with_pis_target(&mut recursive_builder);
+        stark.verify_circuit(&mut recursive_builder, &proof_target, &public_input);
+
+        let rec_data = recursive_builder.build::<Config>();
+
+        // Write trace.
+        let mut writer_data = AirWriterData::new(&stark.air_data, num_rows);
+        let mut writer = writer_data.public_writer();
+
+        writer.write_array(&root, tree.root());
+        for (i, (&index, leaf)) in indices.iter().zip(leaves.iter()).enumerate() {
+            writer.write_array(leaf, leaves_values[index]);
+            for (level, sibling) in tree.path(index).into_iter().enumerate() {
+                writer.write_array(&siblings[i * depth + level], sibling);
+                writer.write(
+                    &index_bits.get(i * depth + level),
+                    &GoldilocksField::from_canonical_usize((index >> level) & 1),
+                );
+            }
+        }
+
+        timed!(timing, "write input", {
+            stark.air_data.write_global_instructions(&mut writer);
+
+            for mut chunk in writer_data.chunks(num_rows) {
+                for i in 0..num_rows {
+                    let mut writer = chunk.window_writer(i);
+                    stark.air_data.write_trace_instructions(&mut writer);
+                }
+            }
+        });
+
+        let (trace, public) = (writer_data.trace, writer_data.public);
+
+        let proof = timed!(
+            timing,
+            "generate stark proof",
+            stark.prove(&trace, &public, &mut timing).unwrap()
+        );
+
+        stark.verify(proof.clone(), &public).unwrap();
+
+        let mut pw = PartialWitness::new();
+
+        pw.set_target_arr(&public_input, &public).unwrap();
+        stark
+            .set_proof_target(&mut pw, &proof_target, proof)
+            .unwrap();
+
+        let rec_proof = timed!(
+            timing,
+            "generate recursive proof",
+            rec_data.prove(pw).unwrap()
+        );
+        rec_data.verify(rec_proof).unwrap();
+
+        timing.print();
+    }
+
+    #[test]
+    fn test_merkle_root_of_region() {
+        type L = MerkleRegionTest;
+        type P = Poseidon2Goldilocks12;
+        type C = CurtaPoseidonGoldilocksConfig;
+
+        let _ = env_logger::builder().is_test(true).try_init();
+        let mut timing = TimingTree::new("test_merkle_root_of_region", log::Level::Debug);
+
+        let num_leaves = 16;
+        let num_elements = num_leaves * <P as Poseidon2Pure<12>>::RATE;
+        let values = (0..num_elements)
+            .map(|_| GoldilocksField::rand())
+            .collect_vec();
+        let tree = MerkleTree::<P, 12>::from_elements(&values);
+
+        // Build the stark, with the array in a region of memory.
+        let mut builder = StarkBuilder::<L>::new();
+        let array = builder.alloc_array_public::<ElementRegister>(num_elements);
+        let region = builder.initialize_slice(&array, &Time::zero(), None);
+        let root = builder.merkle_root_of_region::<P, 12>(&region, 0, num_leaves, &Time::zero());
+
+        let num_rows = 2 * num_leaves;
+        let stark = builder.build::<C, 2>(num_rows);
+
+        // Write trace.
+        let mut writer_data = AirWriterData::new(&stark.air_data, num_rows);
+        let mut writer = writer_data.public_writer();
+
+        writer.write_array(&array, &values);
+        writer.write_array(&root, tree.root());
+
+        timed!(timing, "write input", {
+            stark.air_data.write_global_instructions(&mut writer);
+
+            for mut chunk in writer_data.chunks(num_rows) {
+                for i in 0..num_rows {
+                    let mut writer = chunk.window_writer(i);
+                    stark.air_data.write_trace_instructions(&mut writer);
+                }
+            }
+        });
+
+        let (trace, public) = (writer_data.trace, writer_data.public);
+
+        let proof = timed!(
+            timing,
+            "generate stark proof",
+            stark.prove(&trace, &public, &mut timing).unwrap()
+        );
+
+        stark.verify(proof, &public).unwrap();
+
+        timing.print();
+    }
+}
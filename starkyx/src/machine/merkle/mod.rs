@@ -0,0 +1,129 @@
+//! Merkle tree inclusion proofs with the Poseidon2 hash.
+//!
+//! The nodes of a tree are the digests of the Poseidon2 sponge, and the parent of two nodes is
+//! their hash [`Poseidon2Pure::two_to_one`]. An authentication path is the list of siblings of
+//! the nodes from a leaf to the root, and the bits of the index of the leaf, least significant
+//! first, tell whether the node at each level is a left child (`0`) or a right child (`1`).
+//!
+//! The tree of an array of elements, such as a region of the memory of an AIR, has the digests of
+//! the chunks of the array as its leaves, so that its root commits to the array and its elements
+//! can be opened chunk by chunk.
+
+use core::marker::PhantomData;
+
+use plonky2::field::goldilocks_field::GoldilocksField;
+
+use crate::machine::hash::poseidon2::pure::Poseidon2Pure;
+use crate::machine::hash::poseidon2::DIGEST_LENGTH;
+
+pub mod builder;
+
+pub type MerkleDigest = [GoldilocksField; DIGEST_LENGTH];
+
+/// A Merkle tree with all of its layers, from the leaves to the root.
+#[derive(Debug, Clone)]
+pub struct MerkleTree<P, const WIDTH: usize> {
+    pub layers: Vec<Vec<MerkleDigest>>,
+    _marker: PhantomData<P>,
+}
+
+impl<P: Poseidon2Pure<WIDTH>, const WIDTH: usize> MerkleTree<P, WIDTH> {
+    /// Builds the tree of the given leaves, whose number must be a power of two.
+    pub fn new(leaves: Vec<MerkleDigest>) -> Self {
+        assert!(
+            leaves.len().is_power_of_two(),
+            "The number of leaves must be a power of two"
+        );
+        let mut layers = vec![leaves];
+        while layers.last().unwrap().len() > 1 {
+            let layer = layers
+                .last()
+                .unwrap()
+                .chunks_exact(2)
+                .map(|pair| P::two_to_one(pair[0], pair[1]))
+                .collect();
+            layers.push(layer);
+        }
+        Self {
+            layers,
+            _marker: PhantomData,
+        }
+    }
+
+    /// Builds the tree whose leaves are the digests of the chunks of `P::RATE` elements of
+    /// `elements`, whose number of chunks must be a power of two.
+    pub fn from_elements(elements: &[GoldilocksField]) -> Self {
+        assert_eq!(
+            elements.len() % P::RATE,
+            0,
+            "The number of elements must be a multiple of the rate"
+        );
+        Self::new(
+            elements
+                .chunks_exact(P::RATE)
+                .map(|chunk| P::hash_no_pad(chunk))
+                .collect(),
+        )
+    }
+
+    pub fn depth(&self) -> usize {
+        self.layers.len() - 1
+    }
+
+    pub fn root(&self) -> MerkleDigest {
+        self.layers.last().unwrap()[0]
+    }
+
+    /// The siblings of the nodes on the path from the leaf at `index` to the root.
+    pub fn path(&self, index: usize) -> Vec<MerkleDigest> {
+        self.layers[..self.depth()]
+            .iter()
+            .enumerate()
+            .map(|(level, layer)| layer[(index >> level) ^ 1])
+            .collect()
+    }
+}
+
+/// Computes the root of the tree from a leaf at `index` and its authentication path.
+pub fn merkle_root_from_path<P: Poseidon2Pure<WIDTH>, const WIDTH: usize>(
+    leaf: MerkleDigest,
+    index: usize,
+    siblings: &[MerkleDigest],
+) -> MerkleDigest {
+    siblings
+        .iter()
+        .enumerate()
+        .fold(leaf, |node, (level, sibling)| match (index >> level) & 1 {
+            0 => P::two_to_one(node, *sibling),
+            _ => P::two_to_one(*sibling, node),
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::machine::hash::poseidon2::Poseidon2Goldilocks12;
+    use crate::math::prelude::*;
+
+    #[test]
+    fn test_merkle_tree_paths() {
+        type P = Poseidon2Goldilocks12;
+        let leaves = (0..16)
+            .map(|_| [(); DIGEST_LENGTH].map(|_| GoldilocksField::rand()))
+            .collect::<Vec<_>>();
+        let tree = MerkleTree::<P, 12>::new(leaves.clone());
+        assert_eq!(tree.depth(), 4);
+
+        for (index, leaf) in leaves.iter().enumerate() {
+            let path = tree.path(index);
+            assert_eq!(
+                merkle_root_from_path::<P, 12>(*leaf, index, &path),
+                tree.root()
+            );
+            assert_ne!(
+                merkle_root_from_path::<P, 12>(*leaf, index ^ 1, &path),
+                tree.root()
+            );
+        }
+    }
+}
@@ -1,6 +1,17 @@
+#[cfg(feature = "aes")]
+pub mod aes;
+#[cfg(feature = "bitcoin")]
+pub mod bitcoin;
 pub mod builder;
 pub mod bytes;
+#[cfg(feature = "challenger")]
+pub mod challenger;
+#[cfg(feature = "ecc")]
 pub mod ec;
 pub mod emulated;
 pub mod hash;
+#[cfg(feature = "merkle")]
+pub mod merkle;
 pub mod stark;
+#[cfg(feature = "transcript")]
+pub mod transcript;
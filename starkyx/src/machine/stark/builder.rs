@@ -42,7 +42,10 @@ impl<L: AirParameters> StarkBuilder<L> {
 
         let config = StarkyConfig::<C, D>::standard_fast_config(num_rows);
         let (air, air_data) = api.build();
-        let stark = Starky::new(air);
+        if let Err(e) = air_data.check_num_rows(num_rows) {
+            panic!("{}", e);
+        }
+        let stark = Starky::from_chip(air);
 
         Stark {
             config,
@@ -1,31 +1,45 @@
 use anyhow::Result;
 use plonky2::field::extension::Extendable;
 use plonky2::hash::hash_types::RichField;
-use plonky2::iop::challenger::{Challenger, RecursiveChallenger};
+#[cfg(feature = "prover")]
+use plonky2::iop::challenger::Challenger;
 use plonky2::iop::target::Target;
 use plonky2::iop::witness::WitnessWrite;
 use plonky2::plonk::circuit_builder::CircuitBuilder;
+use plonky2::plonk::config::AlgebraicHasher;
+#[cfg(feature = "prover")]
 use plonky2::timed;
+#[cfg(feature = "prover")]
 use plonky2::util::timing::TimingTree;
 
+#[cfg(feature = "prover")]
 use crate::chip::table::log_derivative::entry::LogEntry;
+#[cfg(feature = "prover")]
 use crate::chip::table::lookup::table::LookupTable;
+#[cfg(feature = "prover")]
 use crate::chip::table::lookup::values::LookupValues;
 use crate::chip::trace::data::AirTraceData;
+#[cfg(feature = "prover")]
 use crate::chip::trace::writer::{InnerWriterData, TraceWriter};
 use crate::chip::{AirParameters, Chip};
 use crate::math::prelude::*;
+#[cfg(feature = "prover")]
 use crate::maybe_rayon::*;
 use crate::plonky2::stark::config::{CurtaConfig, StarkyConfig};
 use crate::plonky2::stark::proof::{
     StarkProof, StarkProofChallenges, StarkProofChallengesTarget, StarkProofTarget,
 };
+#[cfg(feature = "prover")]
 use crate::plonky2::stark::prover::{AirCommitment, StarkyProver};
 use crate::plonky2::stark::verifier::{
-    add_virtual_air_proof, set_air_proof_target, StarkyVerifier,
+    add_virtual_air_proof, set_air_proof_target, set_public_inputs_commitment_target,
+    StarkyVerifier,
 };
 use crate::plonky2::stark::Starky;
 use crate::plonky2::Plonky2Air;
+#[cfg(feature = "prover")]
+use crate::plonky2::ProverAir;
+#[cfg(feature = "prover")]
 use crate::trace::AirTrace;
 
 pub mod builder;
@@ -55,6 +69,7 @@ where
         element.as_canonical_u64() as usize
     }
 
+    #[cfg(feature = "prover")]
     fn generate_execution_trace(
         &self,
         execution_trace: &AirTrace<L::Field>,
@@ -110,10 +125,12 @@ where
         writer
     }
 
+    #[cfg(feature = "prover")]
     fn generate_extended_trace(&self, writer: &TraceWriter<L::Field>) {
         self.air_data.write_extended_trace(writer);
     }
 
+    #[cfg(feature = "prover")]
     fn generate_trace(
         &self,
         execution_trace: &AirTrace<L::Field>,
@@ -197,14 +214,18 @@ where
         }
     }
 
+    #[cfg(feature = "prover")]
     pub fn prove(
         &self,
         execution_trace: &AirTrace<L::Field>,
         public_values: &[L::Field],
         timing: &mut TimingTree,
-    ) -> Result<StarkProof<L::Field, C, D>> {
+    ) -> Result<StarkProof<L::Field, C, D>>
+    where
+        Chip<L>: ProverAir<L::Field, C, D>,
+    {
         // Initialize challenger.
-        let mut challenger = Challenger::new();
+        let mut challenger = self.config.challenger();
 
         // Generate stark commitment.
         let air_commitment = timed!(
@@ -236,7 +257,7 @@ where
         public_values: &[L::Field],
     ) -> StarkProofChallenges<L::Field, D> {
         // Initialize challenger.
-        let mut challenger = Challenger::<L::Field, C::Hasher>::new();
+        let mut challenger = self.config.challenger();
 
         // Observe public values.
         challenger.observe_elements(public_values);
@@ -255,7 +276,7 @@ where
         // Get all challenges.
         proof.air_proof.get_iop_challenges(
             &self.config,
-            self.config.degree_bits,
+            self.config.fri_degree_bits(),
             challenges.clone(),
             &mut challenger,
         )
@@ -266,11 +287,13 @@ where
         proof: StarkProof<L::Field, C, D>,
         public_values: &[L::Field],
     ) -> Result<()> {
+        StarkyVerifier::verify_public_inputs_commitment(&self.config, &proof, public_values)?;
         let challenges = self.get_challenges(&proof, public_values);
 
         let StarkProof {
             air_proof,
             global_values,
+            ..
         } = proof;
 
         StarkyVerifier::verify_with_challenges(
@@ -280,7 +303,8 @@ where
             public_values,
             &global_values,
             challenges,
-        )
+        )?;
+        Ok(())
     }
 
     pub fn add_virtual_proof_with_pis_target(
@@ -297,6 +321,10 @@ where
             StarkProofTarget {
                 air_proof,
                 global_values,
+                public_inputs_commitment: self
+                    .config
+                    .commit_public_inputs
+                    .then(|| builder.add_virtual_hash()),
             },
             public_inputs,
         )
@@ -309,7 +337,7 @@ where
         public_values: &[Target],
     ) -> StarkProofChallengesTarget<D> {
         // Initialize challenger.
-        let mut challenger = RecursiveChallenger::<L::Field, C::InnerHasher, D>::new(builder);
+        let mut challenger = self.config.recursive_challenger(builder);
 
         // Observe public values.
         challenger.observe_elements(public_values);
@@ -339,11 +367,20 @@ where
         builder: &mut CircuitBuilder<L::Field, D>,
         proof: &StarkProofTarget<D>,
         public_values: &[Target],
-    ) {
+    ) where
+        C::Hasher: AlgebraicHasher<L::Field>,
+    {
+        StarkyVerifier::verify_public_inputs_commitment_circuit(
+            builder,
+            &self.config,
+            proof,
+            public_values,
+        );
         let challenges = self.get_challenges_target(builder, proof, public_values);
         let StarkProofTarget {
             air_proof,
             global_values,
+            ..
         } = proof;
 
         StarkyVerifier::verify_with_challenges_circuit(
@@ -362,19 +399,24 @@ where
         witness: &mut W,
         proof_tagret: &StarkProofTarget<D>,
         proof: StarkProof<L::Field, C, D>,
-    ) -> Result<()> {
+    ) -> Result<()>
+    where
+        C::Hasher: AlgebraicHasher<L::Field>,
+    {
         let StarkProofTarget {
             air_proof,
             global_values,
+            ..
         } = proof_tagret;
 
         set_air_proof_target(witness, air_proof, &proof.air_proof)?;
+        set_public_inputs_commitment_target(witness, proof_tagret, &proof)?;
 
         witness.set_target_arr(global_values, &proof.global_values)
     }
 }
 
-#[cfg(test)]
+#[cfg(all(test, feature = "bigint"))]
 mod tests {
     use num::bigint::RandBigInt;
     use plonky2::field::goldilocks_field::GoldilocksField;
@@ -0,0 +1,343 @@
+use super::pure::{absorbed_lengths, reduce, KeccakTranscript, TranscriptOp};
+use super::{STATE_BYTES, STATE_LANES};
+use crate::chip::arithmetic::expression::ArithmeticExpression;
+use crate::chip::register::array::ArrayRegister;
+use crate::chip::register::element::ElementRegister;
+use crate::chip::register::Register;
+use crate::chip::trace::writer::AirWriter;
+use crate::chip::uint::bytes::register::ByteRegister;
+use crate::chip::uint::operations::instruction::UintInstructions;
+use crate::chip::uint::register::U64Register;
+use crate::chip::uint::util::u64_to_le_field_bytes;
+use crate::chip::AirParameters;
+use crate::machine::builder::Builder;
+use crate::machine::bytes::builder::BytesBuilder;
+use crate::machine::hash::batch::BatchSchedule;
+use crate::machine::hash::keccak::builder::KeccakBuilder;
+use crate::machine::hash::keccak::pure::KeccakSponge;
+use crate::machine::hash::keccak::{Keccak, CYCLE_LENGTH};
+use crate::math::prelude::*;
+
+/// A byte absorbed by the transcript.
+#[derive(Debug, Clone, Copy)]
+enum Observation {
+    Public(ByteRegister),
+    /// A constant, whose value is known before the global instructions are written.
+    Constant(ByteRegister, u8),
+}
+
+impl Observation {
+    fn register(&self) -> ByteRegister {
+        match self {
+            Observation::Public(register) | Observation::Constant(register, _) => *register,
+        }
+    }
+}
+
+/// A transcript whose challenges are those of the Keccak-256 transcript of a contract absorbing
+/// the same bytes, see [`super`].
+///
+/// The operations are recorded by [`AirKeccakTranscript::observe_bytes`] and
+/// [`AirKeccakTranscript::get_challenge`], and the hashes of the transcript are computed by
+/// [`AirKeccakTranscript::finalize`] with the Keccak chip, in a trace of
+/// `AirKeccakTranscript::num_rows` rows. The absorbed bytes must be public, and the challenges are
+/// public registers, which can be used in the constraints of the trace. They are written by
+/// [`AirKeccakTranscript::write_challenges`] once the absorbed bytes are written, before the
+/// global instructions.
+#[derive(Debug, Clone, Default)]
+pub struct AirKeccakTranscript {
+    ops: Vec<TranscriptOp>,
+    observations: Vec<Observation>,
+    challenges: Vec<ElementRegister>,
+    /// The states after each hash, set by [`AirKeccakTranscript::finalize`].
+    states: Vec<ArrayRegister<U64Register>>,
+}
+
+impl AirKeccakTranscript {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Absorbs the public bytes `bytes`.
+    pub fn observe_bytes(&mut self, bytes: impl IntoIterator<Item = ByteRegister>) {
+        for byte in bytes {
+            assert!(!byte.is_trace(), "The absorbed bytes must be public");
+            self.ops.push(TranscriptOp::Absorb);
+            self.observations.push(Observation::Public(byte));
+        }
+    }
+
+    /// Absorbs the constant bytes `bytes`, such as a domain separator.
+    pub fn observe_constant_bytes<B: Builder>(&mut self, builder: &mut B, bytes: &[u8]) {
+        for byte in bytes {
+            let register = builder.constant(&B::Field::from_canonical_u8(*byte));
+            self.ops.push(TranscriptOp::Absorb);
+            self.observations
+                .push(Observation::Constant(register, *byte));
+        }
+    }
+
+    /// Returns a public register holding the next challenge.
+    pub fn get_challenge<B: Builder>(&mut self, builder: &mut B) -> ElementRegister {
+        let challenge = builder.alloc_public();
+        self.ops.push(TranscriptOp::Challenge);
+        self.challenges.push(challenge);
+        challenge
+    }
+
+    pub fn get_n_challenges<B: Builder>(
+        &mut self,
+        builder: &mut B,
+        n: usize,
+    ) -> Vec<ElementRegister> {
+        (0..n).map(|_| self.get_challenge(builder)).collect()
+    }
+
+    /// The number of bytes of the message of each hash, the state followed by the absorbed bytes.
+    fn message_lengths(&self) -> Vec<usize> {
+        absorbed_lengths(&self.ops)
+            .into_iter()
+            .map(|length| STATE_BYTES + length)
+            .collect()
+    }
+
+    /// The schedule of the hashes in the Keccak chip, one message for each challenge.
+    pub fn schedule(&self) -> BatchSchedule {
+        let sponge = KeccakSponge::keccak256();
+        BatchSchedule::new(
+            self.message_lengths()
+                .into_iter()
+                .map(|length| length / sponge.rate + 1)
+                .collect(),
+        )
+    }
+
+    /// The number of rows of the trace, which must be the length of the trace of the AIR.
+    pub fn num_rows(&self) -> usize {
+        self.schedule().num_rows(CYCLE_LENGTH)
+    }
+
+    /// Constrains the challenges to be the reduced states of the transcript.
+    ///
+    /// The message of each hash is the state after the previous hash, or zero for the first one,
+    /// followed by the bytes absorbed since, which are set in the padded blocks of the message
+    /// by global instructions. The messages are hashed by a batch of the Keccak chip, whose
+    /// outputs are the states, and each challenge is set to the sum of the bytes of its state
+    /// weighted by the powers of `256`, which the field reduces modulo its order.
+    ///
+    /// This must be called once, after the last operation of the transcript.
+    pub fn finalize<L: AirParameters>(&mut self, builder: &mut BytesBuilder<L>)
+    where
+        L::Instruction: UintInstructions,
+    {
+        assert!(
+            self.states.is_empty(),
+            "The transcript is already finalized"
+        );
+        assert!(
+            !self.challenges.is_empty(),
+            "The transcript has no challenges"
+        );
+        let sponge = KeccakSponge::keccak256();
+        let schedule = self.schedule();
+        let messages = (0..schedule.num_messages())
+            .map(|message| {
+                schedule
+                    .chunk_range(message)
+                    .map(|_| builder.alloc_array_public::<U64Register>(sponge.rate_lanes()))
+                    .collect::<Vec<_>>()
+            })
+            .collect::<Vec<_>>();
+        let batch =
+            builder.keccak_sponge_batch::<Keccak>(&messages, &vec![1; messages.len()], STATE_LANES);
+
+        // Set the bytes of the padded messages.
+        let state_bytes = |state: Option<ArrayRegister<U64Register>>| {
+            (0..STATE_BYTES)
+                .map(|k| match state {
+                    Some(lanes) => lanes.get(k / 8).to_le_bytes().get(k % 8).expr(),
+                    None => ArithmeticExpression::from_constant(L::Field::ZERO),
+                })
+                .collect::<Vec<_>>()
+        };
+        let mut observations = self.observations.iter().map(|o| o.register());
+        for (message, (blocks, length)) in messages.iter().zip(self.message_lengths()).enumerate() {
+            let previous_state = message
+                .checked_sub(1)
+                .map(|previous| batch.digest(previous));
+            let padding = sponge
+                .pad(&vec![0u8; length])
+                .into_iter()
+                .flat_map(|lane| lane.to_le_bytes())
+                .collect::<Vec<_>>();
+            let values = state_bytes(previous_state)
+                .into_iter()
+                .chain(
+                    observations
+                        .by_ref()
+                        .take(length - STATE_BYTES)
+                        .map(|byte| byte.expr()),
+                )
+                .chain(padding[length..].iter().map(|byte| {
+                    ArithmeticExpression::from_constant(L::Field::from_canonical_u8(*byte))
+                }));
+            let bytes = blocks
+                .iter()
+                .flat_map(|block| block.iter())
+                .flat_map(|lane| lane.to_le_bytes());
+            for (byte, value) in bytes.zip(values) {
+                builder.set_to_expression(&byte, value);
+            }
+        }
+
+        // The challenges are the states, read as big-endian integers.
+        let base = L::Field::from_canonical_u32(1 << 8);
+        for (message, challenge) in self.challenges.iter().enumerate() {
+            let value = state_bytes(Some(batch.digest(message)))
+                .into_iter()
+                .fold(ArithmeticExpression::zero(), |acc, byte| acc * base + byte);
+            builder.set_to_expression(challenge, value);
+        }
+
+        self.states = batch.outputs;
+    }
+
+    /// Writes the states of the transcript and the values of the challenges, computed from the
+    /// values of the absorbed bytes.
+    pub fn write_challenges<W: AirWriter>(&self, writer: &mut W)
+    where
+        W::Field: PrimeField64,
+    {
+        assert!(
+            !self.states.is_empty(),
+            "The transcript must be finalized before writing its challenges"
+        );
+        let mut transcript = KeccakTranscript::new();
+        let mut observations = self.observations.iter();
+        let mut states = self.states.iter();
+        let mut challenges = self.challenges.iter();
+        for op in self.ops.iter() {
+            match op {
+                TranscriptOp::Absorb => {
+                    let byte = match observations.next().unwrap() {
+                        Observation::Public(register) => writer.read(register).as_canonical_u64(),
+                        Observation::Constant(_, value) => *value as u64,
+                    };
+                    transcript.absorb(&[byte as u8]);
+                }
+                TranscriptOp::Challenge => {
+                    let state = transcript.squeeze_digest();
+                    writer.write_array(
+                        states.next().unwrap(),
+                        state.chunks_exact(8).map(|lane| {
+                            u64_to_le_field_bytes(u64::from_le_bytes(lane.try_into().unwrap()))
+                        }),
+                    );
+                    writer.write(challenges.next().unwrap(), &reduce(&state));
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use plonky2::field::goldilocks_field::GoldilocksField;
+    use plonky2::timed;
+    use plonky2::util::timing::TimingTree;
+    use serde::{Deserialize, Serialize};
+
+    use super::*;
+    use crate::chip::uint::operations::instruction::UintInstruction;
+    use crate::math::goldilocks::cubic::GoldilocksCubicParameters;
+    use crate::plonky2::stark::config::CurtaPoseidonGoldilocksConfig;
+    use crate::prelude::AirWriterData;
+
+    #[derive(Clone, Debug, Serialize, Deserialize)]
+    pub struct KeccakTranscriptTest;
+
+    impl AirParameters for KeccakTranscriptTest {
+        type Field = GoldilocksField;
+        type CubicParams = GoldilocksCubicParameters;
+        type Instruction = UintInstruction;
+
+        const NUM_FREE_COLUMNS: usize = 2449;
+        const EXTENDED_COLUMNS: usize = 5688;
+    }
+
+    #[test]
+    fn test_air_keccak_transcript() {
+        type F = GoldilocksField;
+        type C = CurtaPoseidonGoldilocksConfig;
+
+        let _ = env_logger::builder().is_test(true).try_init();
+        let mut timing = TimingTree::new("test_air_keccak_transcript", log::Level::Debug);
+
+        // Rounds of a transcript, each absorbing a domain separator and some public bytes, and
+        // squeezing some challenges. The long rounds take two blocks.
+        let num_rounds = 6;
+        let mut builder = BytesBuilder::<KeccakTranscriptTest>::new();
+        let mut transcript = AirKeccakTranscript::new();
+        let mut rounds = Vec::new();
+        for round in 0..num_rounds {
+            let separator = format!("round {}", round).into_bytes();
+            transcript.observe_constant_bytes(&mut builder, &separator);
+            let bytes = builder.alloc_array_public::<ByteRegister>(50 * round + 1);
+            transcript.observe_bytes(bytes.iter());
+            let challenges = transcript.get_n_challenges(&mut builder, round % 3 + 1);
+            rounds.push((separator, bytes, challenges));
+        }
+        transcript.finalize(&mut builder);
+
+        let num_rows = transcript.num_rows();
+        let stark = builder.build::<C, 2>(num_rows);
+
+        // Write trace.
+        let mut writer_data = AirWriterData::new(&stark.air_data, num_rows);
+        let mut writer = writer_data.public_writer();
+
+        let byte_values = rounds
+            .iter()
+            .map(|(_, bytes, _)| {
+                let values = (0..bytes.len())
+                    .map(|_| rand::random::<u8>())
+                    .collect::<Vec<_>>();
+                writer.write_array(bytes, values.iter().map(|b| F::from_canonical_u8(*b)));
+                values
+            })
+            .collect::<Vec<_>>();
+        transcript.write_challenges(&mut writer);
+
+        // The challenges are those of the transcript of a contract.
+        let mut contract_transcript = KeccakTranscript::new();
+        for ((separator, _, challenges), values) in rounds.iter().zip(byte_values.iter()) {
+            contract_transcript.absorb(separator);
+            contract_transcript.absorb(values);
+            for challenge in challenges.iter() {
+                assert_eq!(writer.read(challenge), contract_transcript.squeeze::<F>());
+            }
+        }
+
+        timed!(timing, "write input", {
+            stark.air_data.write_global_instructions(&mut writer);
+
+            for mut chunk in writer_data.chunks(num_rows) {
+                for i in 0..num_rows {
+                    let mut writer = chunk.window_writer(i);
+                    stark.air_data.write_trace_instructions(&mut writer);
+                }
+            }
+        });
+
+        let (trace, public) = (writer_data.trace, writer_data.public);
+
+        let proof = timed!(
+            timing,
+            "generate stark proof",
+            stark.prove(&trace, &public, &mut timing).unwrap()
+        );
+        stark.verify(proof, &public).unwrap();
+
+        timing.print();
+    }
+}
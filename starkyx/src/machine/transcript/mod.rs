@@ -0,0 +1,35 @@
+//! A Fiat-Shamir transcript over Keccak-256, as computed by a contract on the EVM.
+//!
+//! The state of the transcript is a 32-byte digest, which is zero initially. The transcript
+//! buffers the bytes it absorbs, and a challenge hashes the state followed by the buffered bytes
+//! into the new state, which is read as a big-endian integer and reduced modulo the order of the
+//! field. A challenge without new bytes hashes the state alone, so that successive challenges
+//! differ. This is the transcript of the following Solidity code, where `P` is the order of the
+//! field, `0xffffffff00000001` for Goldilocks:
+//!
+//! ```solidity
+//! bytes32 state;
+//! bytes buffer;
+//!
+//! function absorb(bytes memory data) internal {
+//!     buffer = abi.encodePacked(buffer, data);
+//! }
+//!
+//! function squeeze() internal returns (uint256) {
+//!     state = keccak256(abi.encodePacked(state, buffer));
+//!     delete buffer;
+//!     return uint256(state) % P;
+//! }
+//! ```
+//!
+//! [`builder::AirKeccakTranscript`] records the operations of a transcript while building an AIR
+//! and computes its hashes with the Keccak chip, so that the challenges of protocols within the
+//! trace are those derived by a contract verifying the same protocol, without Poseidon.
+
+pub mod builder;
+pub mod pure;
+
+/// The number of bytes of the state, which is a Keccak-256 digest.
+pub const STATE_BYTES: usize = 32;
+/// The number of lanes of the state.
+const STATE_LANES: usize = STATE_BYTES / 8;
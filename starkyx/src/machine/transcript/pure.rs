@@ -0,0 +1,123 @@
+use super::STATE_BYTES;
+use crate::machine::hash::keccak::pure::KeccakSponge;
+use crate::math::prelude::*;
+
+/// An operation of the transcript.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TranscriptOp {
+    /// Absorbs the next byte.
+    Absorb,
+    /// Squeezes the next challenge.
+    Challenge,
+}
+
+/// The number of bytes absorbed by each hash of a transcript, given its operations.
+///
+/// Each challenge hashes the state with the bytes absorbed since the previous one. Bytes absorbed
+/// after the last challenge are never hashed, as they do not affect any challenge.
+pub fn absorbed_lengths(ops: &[TranscriptOp]) -> Vec<usize> {
+    let mut lengths = Vec::new();
+    let mut num_buffered = 0;
+    for op in ops {
+        match op {
+            TranscriptOp::Absorb => num_buffered += 1,
+            TranscriptOp::Challenge => {
+                lengths.push(num_buffered);
+                num_buffered = 0;
+            }
+        }
+    }
+    lengths
+}
+
+/// The transcript computed by a contract, see [`super`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct KeccakTranscript {
+    state: [u8; STATE_BYTES],
+    buffer: Vec<u8>,
+}
+
+impl KeccakTranscript {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn state(&self) -> [u8; STATE_BYTES] {
+        self.state
+    }
+
+    pub fn absorb(&mut self, bytes: &[u8]) {
+        self.buffer.extend_from_slice(bytes);
+    }
+
+    /// Hashes the state with the buffered bytes, and returns the new state.
+    pub fn squeeze_digest(&mut self) -> [u8; STATE_BYTES] {
+        let mut msg = self.state.to_vec();
+        msg.append(&mut self.buffer);
+        self.state = KeccakSponge::keccak256().hash(&msg).try_into().unwrap();
+        self.state
+    }
+
+    /// Hashes the state with the buffered bytes, and returns the new state as a field element.
+    pub fn squeeze<F: Field>(&mut self) -> F {
+        reduce(&self.squeeze_digest())
+    }
+}
+
+/// The big-endian integer of the bytes of a digest, reduced modulo the order of the field.
+pub fn reduce<F: Field>(digest: &[u8; STATE_BYTES]) -> F {
+    let base = F::from_canonical_u32(1 << 8);
+    digest.iter().fold(F::ZERO, |acc, byte| {
+        acc * base + F::from_canonical_u8(*byte)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use num::BigUint;
+    use plonky2::field::goldilocks_field::GoldilocksField;
+    use plonky2::field::types::Field64;
+
+    use super::*;
+
+    #[test]
+    fn test_keccak_transcript() {
+        type F = GoldilocksField;
+        let order = BigUint::from(F::ORDER);
+        let expected_challenge = |digest: &[u8; STATE_BYTES]| {
+            let value = BigUint::from_bytes_be(digest) % &order;
+            F::from_canonical_u64(value.try_into().unwrap())
+        };
+
+        // The first state is the hash of a zero word, `keccak256(abi.encode(0))`.
+        let mut transcript = KeccakTranscript::new();
+        let digest = transcript.clone().squeeze_digest();
+        assert_eq!(
+            hex::encode(digest),
+            "290decd9548b62a8d60345a988386fc84ba6bc95484008f6362f93160ef3e563"
+        );
+        assert_eq!(transcript.squeeze::<F>(), expected_challenge(&digest));
+
+        // The absorbed bytes follow the state, and a challenge without new bytes hashes the state.
+        let state = transcript.state();
+        transcript.absorb(b"ab");
+        transcript.absorb(b"c");
+        let digest = transcript.squeeze_digest();
+        let msg = [&state[..], b"abc"].concat();
+        assert_eq!(digest.to_vec(), KeccakSponge::keccak256().hash(&msg));
+        let next = KeccakSponge::keccak256().hash(&digest);
+        assert_eq!(
+            transcript.squeeze::<F>(),
+            expected_challenge(&next.try_into().unwrap())
+        );
+    }
+
+    #[test]
+    fn test_absorbed_lengths() {
+        use TranscriptOp::*;
+        let ops = [
+            Absorb, Absorb, Challenge, Challenge, Absorb, Challenge, Absorb,
+        ];
+        assert_eq!(absorbed_lengths(&ops), vec![2, 0, 1]);
+    }
+}
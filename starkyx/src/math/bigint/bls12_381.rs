@@ -0,0 +1,155 @@
+//! The scalar field of the BLS12-381 curve.
+
+use serde::{Deserialize, Serialize};
+
+use super::{Fp256, Fp256Parameters};
+
+/// The scalar field of BLS12-381, of order
+/// `52435875175126190479447740508185965837690552500527637822603658699938581184513`.
+pub type Bls12381Scalar = Fp256<Bls12381ScalarParameters>;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct Bls12381ScalarParameters;
+
+impl Fp256Parameters for Bls12381ScalarParameters {
+    const MODULUS: [u64; 4] = [
+        0xffffffff00000001,
+        0x53bda402fffe5bfe,
+        0x3339d80809a1d805,
+        0x73eda753299d7d48,
+    ];
+
+    const R: [u64; 4] = [
+        0x00000001fffffffe,
+        0x5884b7fa00034802,
+        0x998c4fefecbc4ff5,
+        0x1824b159acc5056f,
+    ];
+
+    const R2: [u64; 4] = [
+        0xc999e990f3f29c6d,
+        0x2b6cedcb87925c23,
+        0x05d314967254398f,
+        0x0748d9d99f59ff11,
+    ];
+
+    const INV: u64 = 0xfffffffeffffffff;
+
+    const GENERATOR: [u64; 4] = [7, 0, 0, 0];
+
+    const TWO_ADICITY: usize = 32;
+
+    const TWO_ADIC_ROOT_OF_UNITY: [u64; 4] = [
+        0x3829971f439f0d2b,
+        0xb63683508c2280b9,
+        0xd09b681922c813b4,
+        0x16a2a19edfe81f20,
+    ];
+}
+
+#[cfg(test)]
+mod tests {
+    use num::{BigUint, Num, One};
+
+    use super::*;
+    use crate::math::field::tests::{field_test, prime_field_test, test_field_arithmetic};
+    use crate::math::prelude::*;
+
+    type F = Bls12381Scalar;
+
+    test_field_arithmetic!(bls12_381_scalar, F);
+
+    fn modulus() -> BigUint {
+        BigUint::from_str_radix(
+            "73eda753299d7d483339d80809a1d80553bda402fffe5bfeffffffff00000001",
+            16,
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_bls12_381_scalar_field() {
+        for _ in 0..100 {
+            field_test::<F>();
+            prime_field_test::<F>();
+        }
+    }
+
+    #[test]
+    fn test_bls12_381_scalar_constants() {
+        let p = modulus();
+        assert_eq!(F::modulus(), p);
+        let r = (BigUint::one() << 256) % &p;
+        assert_eq!(F::ONE.0, F::from_biguint(&r).as_canonical_limbs());
+        assert_eq!(
+            F::from_canonical_limbs(Bls12381ScalarParameters::R2).to_biguint(),
+            (BigUint::one() << 512) % &p
+        );
+        let inv = Bls12381ScalarParameters::INV;
+        assert_eq!(
+            Bls12381ScalarParameters::MODULUS[0].wrapping_mul(inv),
+            u64::MAX
+        );
+
+        let root = F::primitive_root_of_unity(Bls12381ScalarParameters::TWO_ADICITY);
+        assert_eq!(root.two_pow(Bls12381ScalarParameters::TWO_ADICITY), F::ONE);
+        assert_ne!(
+            root.two_pow(Bls12381ScalarParameters::TWO_ADICITY - 1),
+            F::ONE
+        );
+    }
+
+    #[test]
+    fn test_bls12_381_scalar_generator() {
+        let p = modulus();
+        let order = &p - 1u32;
+        let factors = [
+            "2",
+            "3",
+            "11",
+            "19",
+            "10177",
+            "125527",
+            "859267",
+            "906349",
+            "2508409",
+            "2529403",
+            "52437899",
+            "254760293",
+        ];
+        let g = F::generator();
+        assert_eq!(g.pow_biguint(&order), F::ONE);
+        for q in factors {
+            let q = q.parse::<BigUint>().unwrap();
+            assert_ne!(g.pow_biguint(&(&order / q)), F::ONE);
+        }
+    }
+
+    #[test]
+    fn test_bls12_381_scalar_bytes() {
+        let a = F::rand();
+        let bytes = a.to_bytes_be();
+        let mut expected = a.to_biguint().to_bytes_be();
+        expected.splice(0..0, core::iter::repeat(0).take(32 - expected.len()));
+        assert_eq!(bytes.to_vec(), expected);
+        assert_eq!(F::try_from_bytes_be(&bytes), Some(a));
+
+        let mut p_bytes = [0u8; 32];
+        p_bytes.copy_from_slice(&modulus().to_bytes_be());
+        assert_eq!(F::try_from_bytes_be(&p_bytes), None);
+        assert_eq!(F::try_from_bytes_be(&[0xff; 32]), None);
+        assert_eq!(F::ONE.to_bytes_be()[31], 1);
+    }
+
+    #[test]
+    fn test_bls12_381_scalar_serde() {
+        let a = F::rand();
+        let bytes = bincode::serialize(&a).unwrap();
+        assert_eq!(bytes, bincode::serialize(&a.as_canonical_limbs()).unwrap());
+        let b: F = bincode::deserialize(&bytes).unwrap();
+        assert_eq!(a, b);
+
+        let unreduced = bincode::serialize(&Bls12381ScalarParameters::MODULUS).unwrap();
+        assert!(bincode::deserialize::<F>(&unreduced).is_err());
+    }
+}
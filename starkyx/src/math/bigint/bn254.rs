@@ -0,0 +1,126 @@
+//! The scalar field of the BN254 curve.
+
+use serde::{Deserialize, Serialize};
+
+use super::{Fp256, Fp256Parameters};
+
+/// The scalar field of BN254, of order
+/// `21888242871839275222246405745257275088548364400416034343698204186575808495617`.
+pub type Bn254Scalar = Fp256<Bn254ScalarParameters>;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct Bn254ScalarParameters;
+
+impl Fp256Parameters for Bn254ScalarParameters {
+    const MODULUS: [u64; 4] = [
+        0x43e1f593f0000001,
+        0x2833e84879b97091,
+        0xb85045b68181585d,
+        0x30644e72e131a029,
+    ];
+
+    const R: [u64; 4] = [
+        0xac96341c4ffffffb,
+        0x36fc76959f60cd29,
+        0x666ea36f7879462e,
+        0x0e0a77c19a07df2f,
+    ];
+
+    const R2: [u64; 4] = [
+        0x1bb8e645ae216da7,
+        0x53fe3ab1e35c59e3,
+        0x8c49833d53bb8085,
+        0x0216d0b17f4e44a5,
+    ];
+
+    const INV: u64 = 0xc2e1f593efffffff;
+
+    const GENERATOR: [u64; 4] = [5, 0, 0, 0];
+
+    const TWO_ADICITY: usize = 28;
+
+    const TWO_ADIC_ROOT_OF_UNITY: [u64; 4] = [
+        0x9bd61b6e725b19f0,
+        0x402d111e41112ed4,
+        0x00e0a7eb8ef62abc,
+        0x2a3c09f0a58a7e85,
+    ];
+}
+
+#[cfg(test)]
+mod tests {
+    use num::{BigUint, Num, One};
+
+    use super::*;
+    use crate::math::field::tests::{field_test, prime_field_test, test_field_arithmetic};
+    use crate::math::prelude::*;
+
+    type F = Bn254Scalar;
+
+    test_field_arithmetic!(bn254_scalar, F);
+
+    fn modulus() -> BigUint {
+        BigUint::from_str_radix(
+            "21888242871839275222246405745257275088548364400416034343698204186575808495617",
+            10,
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_bn254_scalar_field() {
+        for _ in 0..100 {
+            field_test::<F>();
+            prime_field_test::<F>();
+        }
+    }
+
+    #[test]
+    fn test_bn254_scalar_constants() {
+        let p = modulus();
+        assert_eq!(F::modulus(), p);
+        let r = (BigUint::one() << 256) % &p;
+        assert_eq!(F::ONE.0, F::from_biguint(&r).as_canonical_limbs());
+        assert_eq!(
+            F::from_canonical_limbs(Bn254ScalarParameters::R2).to_biguint(),
+            (BigUint::one() << 512) % &p
+        );
+        let inv = Bn254ScalarParameters::INV;
+        assert_eq!(
+            Bn254ScalarParameters::MODULUS[0].wrapping_mul(inv),
+            u64::MAX
+        );
+
+        let root = F::primitive_root_of_unity(Bn254ScalarParameters::TWO_ADICITY);
+        assert_eq!(root.two_pow(Bn254ScalarParameters::TWO_ADICITY), F::ONE);
+        assert_ne!(root.two_pow(Bn254ScalarParameters::TWO_ADICITY - 1), F::ONE);
+    }
+
+    #[test]
+    fn test_bn254_scalar_biguint_arithmetic() {
+        let p = modulus();
+        for _ in 0..100 {
+            let (a, b) = (F::rand(), F::rand());
+            let (a_int, b_int) = (a.to_biguint(), b.to_biguint());
+            assert_eq!((a + b).to_biguint(), (&a_int + &b_int) % &p);
+            assert_eq!((a - b).to_biguint(), (&a_int + &p - &b_int) % &p);
+            assert_eq!((a * b).to_biguint(), (&a_int * &b_int) % &p);
+            assert_eq!(F::from_biguint(&a_int), a);
+        }
+        let minus_one = F::from_biguint(&(&p - 1u32));
+        assert_eq!(minus_one, -F::ONE);
+        assert_eq!(minus_one * minus_one, F::ONE);
+    }
+
+    #[test]
+    fn test_bn254_scalar_serde() {
+        let a = F::rand();
+        let bytes = bincode::serialize(&a).unwrap();
+        assert_eq!(bytes, bincode::serialize(&a.as_canonical_limbs()).unwrap());
+        let b: F = bincode::deserialize(&bytes).unwrap();
+        assert_eq!(a, b);
+
+        let unreduced = bincode::serialize(&Bn254ScalarParameters::MODULUS).unwrap();
+        assert!(bincode::deserialize::<F>(&unreduced).is_err());
+    }
+}
@@ -0,0 +1,463 @@
+//! Prime fields of order less than `2^256`, in Montgomery form.
+//!
+//! `Fp256<P>` stores an element `a` as the four little-endian 64-bit limbs of `a * 2^256 mod p`,
+//! and multiplies with the CIOS Montgomery multiplication. The field is determined by the
+//! constants of `P: Fp256Parameters`, which can be computed from the modulus and a generator. The
+//! arithmetic does not branch on the values of the elements, so inversion by exponentiation runs
+//! in constant time.
+//!
+//! These fields are the host-side counterparts of the fields emulated by the non-native
+//! arithmetic chips. Elements are serialized as their canonical limbs, and convert to and from
+//! `BigUint` for the witness generation of the chips.
+
+pub mod bls12_381;
+pub mod bn254;
+pub mod secp256k1;
+
+use alloc::vec::Vec;
+use core::fmt::{self, Debug, Display, Formatter};
+use core::hash::Hash;
+use core::iter::{Product, Sum};
+use core::marker::PhantomData;
+use core::ops::{Add, AddAssign, Div, DivAssign, Mul, MulAssign, Neg, Sub, SubAssign};
+
+use num::BigUint;
+use rand::Rng;
+use serde::de::{DeserializeOwned, Error};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+use crate::math::prelude::*;
+
+/// The constants of a prime field of order less than `2^256`.
+pub trait Fp256Parameters:
+    'static
+    + Sized
+    + Copy
+    + Clone
+    + Send
+    + Sync
+    + PartialEq
+    + Eq
+    + Hash
+    + Debug
+    + Serialize
+    + DeserializeOwned
+{
+    /// The modulus `p`, as little-endian limbs.
+    const MODULUS: [u64; 4];
+
+    /// `2^256 mod p`, which is the Montgomery form of one.
+    const R: [u64; 4];
+
+    /// `2^512 mod p`, which converts canonical limbs to the Montgomery form.
+    const R2: [u64; 4];
+
+    /// `-p^{-1} mod 2^64`.
+    const INV: u64;
+
+    /// A generator of the multiplicative group, as canonical limbs.
+    const GENERATOR: [u64; 4];
+
+    /// The largest `s` such that `2^s` divides `p - 1`.
+    const TWO_ADICITY: usize;
+
+    /// A primitive `2^TWO_ADICITY`-th root of unity, as canonical limbs.
+    const TWO_ADIC_ROOT_OF_UNITY: [u64; 4];
+}
+
+/// An element of the prime field defined by `P`, in Montgomery form.
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Fp256<P: Fp256Parameters>([u64; 4], PhantomData<P>);
+
+/// `a + b + carry`, as the low limb and the carry.
+#[inline]
+const fn adc(a: u64, b: u64, carry: u64) -> (u64, u64) {
+    let t = a as u128 + b as u128 + carry as u128;
+    (t as u64, (t >> 64) as u64)
+}
+
+/// `a - b - borrow`, as the low limb and the borrow, which is `1` on underflow.
+#[inline]
+const fn sbb(a: u64, b: u64, borrow: u64) -> (u64, u64) {
+    let t = (a as u128).wrapping_sub(b as u128 + borrow as u128);
+    (t as u64, (t >> 127) as u64)
+}
+
+/// `a + b * c + carry`, as the low limb and the carry.
+#[inline]
+const fn mac(a: u64, b: u64, c: u64, carry: u64) -> (u64, u64) {
+    let t = a as u128 + b as u128 * c as u128 + carry as u128;
+    (t as u64, (t >> 64) as u64)
+}
+
+/// `a - b` and the final borrow.
+#[inline]
+fn sub_limbs(a: &[u64; 4], b: &[u64; 4]) -> ([u64; 4], u64) {
+    let mut result = [0; 4];
+    let mut borrow = 0;
+    for ((r, &a_i), &b_i) in result.iter_mut().zip(a.iter()).zip(b.iter()) {
+        (*r, borrow) = sbb(a_i, b_i, borrow);
+    }
+    (result, borrow)
+}
+
+/// `a` if `flag` is zero and `b` if `flag` is one, without branching on `flag`.
+#[inline]
+fn select_limbs(flag: u64, a: &[u64; 4], b: &[u64; 4]) -> [u64; 4] {
+    let mask = flag.wrapping_neg();
+    core::array::from_fn(|i| (a[i] & !mask) | (b[i] & mask))
+}
+
+/// `a + b` and the final carry.
+#[inline]
+fn add_limbs(a: &[u64; 4], b: &[u64; 4]) -> ([u64; 4], u64) {
+    let mut result = [0; 4];
+    let mut carry = 0;
+    for ((r, &a_i), &b_i) in result.iter_mut().zip(a.iter()).zip(b.iter()) {
+        (*r, carry) = adc(a_i, b_i, carry);
+    }
+    (result, carry)
+}
+
+impl<P: Fp256Parameters> Fp256<P> {
+    pub const ZERO: Self = Self([0; 4], PhantomData);
+    pub const ONE: Self = Self(P::R, PhantomData);
+
+    /// The element with canonical limbs `limbs`, which must be less than the modulus.
+    pub fn from_canonical_limbs(limbs: [u64; 4]) -> Self {
+        assert!(Self::is_canonical(&limbs), "Limbs are not reduced");
+        Self(Self::mont_mul(&limbs, &P::R2), PhantomData)
+    }
+
+    /// The canonical little-endian limbs of the element.
+    pub fn as_canonical_limbs(&self) -> [u64; 4] {
+        Self::mont_mul(&self.0, &[1, 0, 0, 0])
+    }
+
+    /// The element with the 32 big-endian bytes `bytes`, or `None` if their value is not less
+    /// than the modulus.
+    ///
+    /// This is the encoding of scalars in Ethereum consensus objects.
+    pub fn try_from_bytes_be(bytes: &[u8; 32]) -> Option<Self> {
+        let limbs: [u64; 4] = core::array::from_fn(|i| {
+            u64::from_be_bytes(bytes[24 - 8 * i..32 - 8 * i].try_into().unwrap())
+        });
+        Self::is_canonical(&limbs).then(|| Self::from_canonical_limbs(limbs))
+    }
+
+    /// The 32 big-endian bytes of the canonical representative of the element.
+    pub fn to_bytes_be(&self) -> [u8; 32] {
+        let limbs = self.as_canonical_limbs();
+        let mut bytes = [0u8; 32];
+        for (chunk, limb) in bytes.chunks_exact_mut(8).zip(limbs.iter().rev()) {
+            chunk.copy_from_slice(&limb.to_be_bytes());
+        }
+        bytes
+    }
+
+    /// A generator of the multiplicative group.
+    pub fn generator() -> Self {
+        Self::from_canonical_limbs(P::GENERATOR)
+    }
+
+    /// Raises `self` to the power with little-endian limbs `exponent`.
+    ///
+    /// The sequence of operations only depends on the exponent, not on `self`.
+    pub fn pow_limbs(&self, exponent: &[u64]) -> Self {
+        let mut result = Self::ONE;
+        for limb in exponent.iter().rev() {
+            for i in (0..64).rev() {
+                result = result.square();
+                if (limb >> i) & 1 == 1 {
+                    result *= *self;
+                }
+            }
+        }
+        result
+    }
+
+    /// `a` if `flag` is zero and `b` if `flag` is one, without branching on `flag`.
+    #[inline]
+    fn select(flag: u64, a: &Self, b: &Self) -> Self {
+        Self(select_limbs(flag, &a.0, &b.0), PhantomData)
+    }
+
+    fn u32_digits(limbs: &[u64; 4]) -> [u32; 8] {
+        core::array::from_fn(|i| (limbs[i / 2] >> (32 * (i % 2))) as u32)
+    }
+
+    fn is_canonical(limbs: &[u64; 4]) -> bool {
+        sub_limbs(limbs, &P::MODULUS).1 == 1
+    }
+
+    /// Subtracts the modulus from `limbs` if they are not reduced.
+    ///
+    /// `carry` is the bit `2^256` of the value, which is less than `2p`.
+    #[inline]
+    fn reduce_once(limbs: [u64; 4], carry: u64) -> [u64; 4] {
+        let (reduced, borrow) = sub_limbs(&limbs, &P::MODULUS);
+        select_limbs(carry | (borrow ^ 1), &limbs, &reduced)
+    }
+
+    /// The Montgomery product `a * b * 2^{-256} mod p`, with the CIOS method.
+    #[inline]
+    fn mont_mul(a: &[u64; 4], b: &[u64; 4]) -> [u64; 4] {
+        let modulus = P::MODULUS;
+        let mut t = [0u64; 6];
+        for &b_i in b.iter() {
+            let mut carry = 0;
+            for (t_j, &a_j) in t.iter_mut().zip(a.iter()) {
+                (*t_j, carry) = mac(*t_j, a_j, b_i, carry);
+            }
+            (t[4], t[5]) = adc(t[4], carry, 0);
+
+            let m = t[0].wrapping_mul(P::INV);
+            let (_, mut carry) = mac(t[0], m, modulus[0], 0);
+            for (j, &modulus_j) in modulus.iter().enumerate().skip(1) {
+                (t[j - 1], carry) = mac(t[j], m, modulus_j, carry);
+            }
+            (t[3], carry) = adc(t[4], carry, 0);
+            t[4] = t[5] + carry;
+        }
+        Self::reduce_once([t[0], t[1], t[2], t[3]], t[4])
+    }
+}
+
+impl<P: Fp256Parameters> Debug for Fp256<P> {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        Display::fmt(&self.to_biguint(), f)
+    }
+}
+
+impl<P: Fp256Parameters> Display for Fp256<P> {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        Display::fmt(&self.to_biguint(), f)
+    }
+}
+
+impl<P: Fp256Parameters> Default for Fp256<P> {
+    fn default() -> Self {
+        Self::ZERO
+    }
+}
+
+impl<P: Fp256Parameters> Serialize for Fp256<P> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.as_canonical_limbs().serialize(serializer)
+    }
+}
+
+impl<'de, P: Fp256Parameters> Deserialize<'de> for Fp256<P> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let limbs = <[u64; 4]>::deserialize(deserializer)?;
+        if !Self::is_canonical(&limbs) {
+            return Err(D::Error::custom("field element is not reduced"));
+        }
+        Ok(Self::from_canonical_limbs(limbs))
+    }
+}
+
+impl<P: Fp256Parameters> Add for Fp256<P> {
+    type Output = Self;
+
+    #[inline]
+    fn add(self, rhs: Self) -> Self {
+        let (sum, carry) = add_limbs(&self.0, &rhs.0);
+        Self(Self::reduce_once(sum, carry), PhantomData)
+    }
+}
+
+impl<P: Fp256Parameters> AddAssign for Fp256<P> {
+    #[inline]
+    fn add_assign(&mut self, rhs: Self) {
+        *self = *self + rhs;
+    }
+}
+
+impl<P: Fp256Parameters> Sub for Fp256<P> {
+    type Output = Self;
+
+    #[inline]
+    fn sub(self, rhs: Self) -> Self {
+        let (diff, borrow) = sub_limbs(&self.0, &rhs.0);
+        let (corrected, _) = add_limbs(&diff, &P::MODULUS);
+        Self(select_limbs(borrow, &diff, &corrected), PhantomData)
+    }
+}
+
+impl<P: Fp256Parameters> SubAssign for Fp256<P> {
+    #[inline]
+    fn sub_assign(&mut self, rhs: Self) {
+        *self = *self - rhs;
+    }
+}
+
+impl<P: Fp256Parameters> Neg for Fp256<P> {
+    type Output = Self;
+
+    #[inline]
+    fn neg(self) -> Self {
+        Self::ZERO - self
+    }
+}
+
+impl<P: Fp256Parameters> Mul for Fp256<P> {
+    type Output = Self;
+
+    #[inline]
+    fn mul(self, rhs: Self) -> Self {
+        Self(Self::mont_mul(&self.0, &rhs.0), PhantomData)
+    }
+}
+
+impl<P: Fp256Parameters> MulAssign for Fp256<P> {
+    #[inline]
+    fn mul_assign(&mut self, rhs: Self) {
+        *self = *self * rhs;
+    }
+}
+
+impl<P: Fp256Parameters> Div for Fp256<P> {
+    type Output = Self;
+
+    #[allow(clippy::suspicious_arithmetic_impl)]
+    fn div(self, rhs: Self) -> Self {
+        self * rhs.inverse()
+    }
+}
+
+impl<P: Fp256Parameters> DivAssign for Fp256<P> {
+    fn div_assign(&mut self, rhs: Self) {
+        *self = *self / rhs;
+    }
+}
+
+impl<P: Fp256Parameters> Sum for Fp256<P> {
+    fn sum<I: Iterator<Item = Self>>(iter: I) -> Self {
+        iter.fold(Self::ZERO, |acc, x| acc + x)
+    }
+}
+
+impl<P: Fp256Parameters> Product for Fp256<P> {
+    fn product<I: Iterator<Item = Self>>(iter: I) -> Self {
+        iter.fold(Self::ONE, |acc, x| acc * x)
+    }
+}
+
+impl<P: Fp256Parameters> Ring for Fp256<P> {
+    const ONE: Self = Self::ONE;
+    const ZERO: Self = Self::ZERO;
+}
+
+impl<P: Fp256Parameters> Field for Fp256<P> {
+    /// Inverts `self` as `self^{p - 2}`, with operations which do not depend on `self`.
+    fn try_inverse(&self) -> Option<Self> {
+        if self.is_zero() {
+            return None;
+        }
+        let (exponent, _) = sub_limbs(&P::MODULUS, &[2, 0, 0, 0]);
+        Some(self.pow_limbs(&exponent))
+    }
+
+    fn from_canonical_u8(n: u8) -> Self {
+        Self::from_canonical_u64(n as u64)
+    }
+    fn from_canonical_u16(n: u16) -> Self {
+        Self::from_canonical_u64(n as u64)
+    }
+    fn from_canonical_u32(n: u32) -> Self {
+        Self::from_canonical_u64(n as u64)
+    }
+    fn from_canonical_u64(n: u64) -> Self {
+        Self::from_canonical_limbs([n, 0, 0, 0])
+    }
+    fn from_canonical_usize(n: usize) -> Self {
+        Self::from_canonical_u64(n as u64)
+    }
+
+    fn from_noncanonical_biguint(n: BigUint) -> Self {
+        Self::from_biguint(&n)
+    }
+
+    fn from_biguint(n: &BigUint) -> Self {
+        let reduced = n % Self::modulus();
+        let mut limbs = [0; 4];
+        for (limb, digit) in limbs.iter_mut().zip(reduced.to_u64_digits()) {
+            *limb = digit;
+        }
+        Self::from_canonical_limbs(limbs)
+    }
+
+    fn primitive_root_of_unity(n_log: usize) -> Self {
+        assert!(
+            n_log <= P::TWO_ADICITY,
+            "The field has no roots of unity of order 2^{n_log}"
+        );
+        Self::from_canonical_limbs(P::TWO_ADIC_ROOT_OF_UNITY).two_pow(P::TWO_ADICITY - n_log)
+    }
+
+    fn two_adic_subgroup(n_log: usize) -> Vec<Self> {
+        Self::primitive_root_of_unity(n_log)
+            .powers()
+            .take(1 << n_log)
+            .collect()
+    }
+}
+
+impl<P: Fp256Parameters> PrimeField for Fp256<P> {
+    fn modulus() -> BigUint {
+        BigUint::from_slice(&Self::u32_digits(&P::MODULUS))
+    }
+
+    fn to_biguint(&self) -> BigUint {
+        BigUint::from_slice(&Self::u32_digits(&self.as_canonical_limbs()))
+    }
+
+    fn num_u64_limbs() -> usize {
+        4
+    }
+
+    fn to_u64_limbs(&self) -> Vec<u64> {
+        self.as_canonical_limbs().to_vec()
+    }
+
+    /// A square root of `self`, or `None` if `self` is not a square.
+    ///
+    /// Unlike the default, the Tonelli-Shanks iterations are fixed, so that the sequence of
+    /// operations only depends on the field.
+    fn sqrt(&self) -> Option<Self> {
+        let s = P::TWO_ADICITY;
+        // `p - 1 = 2^s * t` with `t` odd.
+        let t = (Self::modulus() - 1u32) >> s;
+        let t_plus_one_half: BigUint = (&t + 1u32) >> 1;
+
+        // The invariant is `x^2 = self * b`, where `b` has order dividing `2^i` at step `i`.
+        let mut x = self.pow_limbs(&t_plus_one_half.to_u64_digits());
+        let mut b = self.pow_limbs(&t.to_u64_digits());
+        let mut z = Self::from_canonical_limbs(P::TWO_ADIC_ROOT_OF_UNITY);
+        for i in (1..s).rev() {
+            // If `b` has order `2^i`, multiplying by `z^2` of order `2^i` decreases its order.
+            let flag = (b.two_pow(i - 1) != Self::ONE) as u64;
+            let z_squared = z.square();
+            x = Self::select(flag, &x, &(x * z));
+            b = Self::select(flag, &b, &(b * z_squared));
+            z = z_squared;
+        }
+
+        (x.square() == *self).then_some(x)
+    }
+}
+
+impl<P: Fp256Parameters> Sample for Fp256<P> {
+    /// Samples a uniform element by rejection sampling.
+    fn sample<R: Rng + ?Sized>(rng: &mut R) -> Self {
+        let mask = u64::MAX >> P::MODULUS[3].leading_zeros();
+        loop {
+            let mut limbs: [u64; 4] = rng.gen();
+            limbs[3] &= mask;
+            if Self::is_canonical(&limbs) {
+                return Self::from_canonical_limbs(limbs);
+            }
+        }
+    }
+}
@@ -0,0 +1,159 @@
+//! The base and scalar fields of the secp256k1 curve.
+
+use serde::{Deserialize, Serialize};
+
+use super::{Fp256, Fp256Parameters};
+
+/// The base field of secp256k1, of order `2^256 - 2^32 - 977`.
+pub type Secp256k1Base = Fp256<Secp256k1BaseParameters>;
+
+/// The scalar field of secp256k1, whose order is the order of the curve.
+pub type Secp256k1Scalar = Fp256<Secp256k1ScalarParameters>;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct Secp256k1BaseParameters;
+
+impl Fp256Parameters for Secp256k1BaseParameters {
+    const MODULUS: [u64; 4] = [
+        0xfffffffefffffc2f,
+        0xffffffffffffffff,
+        0xffffffffffffffff,
+        0xffffffffffffffff,
+    ];
+
+    const R: [u64; 4] = [0x00000001000003d1, 0, 0, 0];
+
+    const R2: [u64; 4] = [0x000007a2000e90a1, 0x0000000000000001, 0, 0];
+
+    const INV: u64 = 0xd838091dd2253531;
+
+    const GENERATOR: [u64; 4] = [3, 0, 0, 0];
+
+    const TWO_ADICITY: usize = 1;
+
+    /// The root `-1`.
+    const TWO_ADIC_ROOT_OF_UNITY: [u64; 4] = [
+        0xfffffffefffffc2e,
+        0xffffffffffffffff,
+        0xffffffffffffffff,
+        0xffffffffffffffff,
+    ];
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct Secp256k1ScalarParameters;
+
+impl Fp256Parameters for Secp256k1ScalarParameters {
+    const MODULUS: [u64; 4] = [
+        0xbfd25e8cd0364141,
+        0xbaaedce6af48a03b,
+        0xfffffffffffffffe,
+        0xffffffffffffffff,
+    ];
+
+    const R: [u64; 4] = [
+        0x402da1732fc9bebf,
+        0x4551231950b75fc4,
+        0x0000000000000001,
+        0x0000000000000000,
+    ];
+
+    const R2: [u64; 4] = [
+        0x896cf21467d7d140,
+        0x741496c20e7cf878,
+        0xe697f5e45bcd07c6,
+        0x9d671cd581c69bc5,
+    ];
+
+    const INV: u64 = 0x4b0dff665588b13f;
+
+    const GENERATOR: [u64; 4] = [7, 0, 0, 0];
+
+    const TWO_ADICITY: usize = 6;
+
+    const TWO_ADIC_ROOT_OF_UNITY: [u64; 4] = [
+        0x992f4b5402b052f2,
+        0x98bdeab680756045,
+        0xdf9879a3fbc483a8,
+        0x0c1dc060e7a91986,
+    ];
+}
+
+#[cfg(test)]
+mod tests {
+    use num::{BigUint, Num, One};
+
+    use super::*;
+    use crate::math::field::tests::{field_test, prime_field_test, test_field_arithmetic};
+    use crate::math::prelude::*;
+
+    test_field_arithmetic!(secp256k1_base, Secp256k1Base);
+    test_field_arithmetic!(secp256k1_scalar, Secp256k1Scalar);
+
+    fn check_constants<P: Fp256Parameters>(modulus: &str) {
+        let p = BigUint::from_str_radix(modulus, 16).unwrap();
+        assert_eq!(Fp256::<P>::modulus(), p);
+        let r = (BigUint::one() << 256) % &p;
+        assert_eq!(
+            Fp256::<P>::ONE.0,
+            Fp256::<P>::from_biguint(&r).as_canonical_limbs()
+        );
+        assert_eq!(
+            Fp256::<P>::from_canonical_limbs(P::R2).to_biguint(),
+            (BigUint::one() << 512) % &p
+        );
+        assert_eq!(P::MODULUS[0].wrapping_mul(P::INV), u64::MAX);
+
+        let root = Fp256::<P>::primitive_root_of_unity(P::TWO_ADICITY);
+        assert_eq!(root.two_pow(P::TWO_ADICITY), Fp256::<P>::ONE);
+        assert_ne!(root.two_pow(P::TWO_ADICITY - 1), Fp256::<P>::ONE);
+    }
+
+    fn check_sqrt<P: Fp256Parameters>() {
+        for _ in 0..20 {
+            let a = Fp256::<P>::rand();
+            let square = a.square();
+            let root = square.sqrt().unwrap();
+            assert!(root == a || root == -a);
+        }
+        // The generator is not a square.
+        assert_eq!(Fp256::<P>::generator().sqrt(), None);
+        assert_eq!(Fp256::<P>::ZERO.sqrt(), Some(Fp256::<P>::ZERO));
+    }
+
+    #[test]
+    fn test_secp256k1_base_field() {
+        for _ in 0..100 {
+            field_test::<Secp256k1Base>();
+            prime_field_test::<Secp256k1Base>();
+        }
+        check_constants::<Secp256k1BaseParameters>(
+            "fffffffffffffffffffffffffffffffffffffffffffffffffffffffefffffc2f",
+        );
+        check_sqrt::<Secp256k1BaseParameters>();
+    }
+
+    #[test]
+    fn test_secp256k1_scalar_field() {
+        for _ in 0..100 {
+            field_test::<Secp256k1Scalar>();
+            prime_field_test::<Secp256k1Scalar>();
+        }
+        check_constants::<Secp256k1ScalarParameters>(
+            "fffffffffffffffffffffffffffffffebaaedce6af48a03bbfd25e8cd0364141",
+        );
+        check_sqrt::<Secp256k1ScalarParameters>();
+    }
+
+    #[test]
+    fn test_secp256k1_generator_on_curve() {
+        let parse = |s: &str| Secp256k1Base::from_biguint(&BigUint::from_str_radix(s, 16).unwrap());
+        let x = parse("79be667ef9dcbbac55a06295ce870b07029bfcdb2dce28d959f2815b16f81798");
+        let y = parse("483ada7726a3c4655da4fbfc0e1108a8fd17b448a68554199c47d08ffb10d4b8");
+
+        let y_squared = x * x * x + Secp256k1Base::from_canonical_u8(7);
+        assert_eq!(y * y, y_squared);
+        let root = y_squared.sqrt().unwrap();
+        assert!(root == y || root == -y);
+    }
+}
@@ -1,3 +1,4 @@
+use alloc::vec::Vec;
 use core::hash::{Hash, Hasher};
 use core::iter::{Product, Sum};
 use core::marker::PhantomData;
@@ -249,6 +250,16 @@ impl<F: Field, P: CubicParameters<F>> Extension<F> for CubicExtension<F, P> {
 
 impl<F: Field, P: CubicParameters<F>> ExtensionField<F> for CubicExtension<F, P> {}
 
+impl<F: PrimeField, P: CubicParameters<F>> Frobenius for CubicExtension<F, P> {
+    /// Since `X^p = ORBIT[0]`, the image of `a + bX + cX^2` is
+    /// `a + b * ORBIT[0] + c * ORBIT[0]^2`.
+    fn frobenius(&self) -> Self {
+        let [a, b, c] = self.0.as_array();
+        let x_p = Self::ORBIT[0];
+        Self::from(a) + x_p * b + x_p * x_p * c
+    }
+}
+
 impl<F: Field, P: CubicParameters<F>> Field for CubicExtension<F, P> {
     fn try_inverse(&self) -> Option<Self> {
         self.try_inverse()
@@ -11,6 +11,7 @@ pub trait CubicParameters<F>:
 {
     /// The Galois orbit of the generator.
     ///
-    /// These are the roots of X^3 - X - 1 in the extension field not equal to X.
+    /// These are the roots of X^3 - X - 1 in the extension field not equal to X, in the order
+    /// `[X^p, X^{p^2}]` of their images under the Frobenius automorphism.
     const GALOIS_ORBIT: [CubicElement<F>; 2];
 }
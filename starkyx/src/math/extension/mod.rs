@@ -1,11 +1,17 @@
+use alloc::vec::Vec;
 use core::slice;
 
 use super::algebra::Algebra;
-use super::field::Field;
+use super::field::{Field, PrimeField};
 
 pub mod cubic;
+pub mod quadratic;
+pub mod quintic;
 
 pub use cubic::parameters::CubicParameters;
+pub use quadratic::parameters::QuadraticParameters;
+pub use quintic::parameters::QuinticParameters;
+
 /// A ring extension of a field with a fixed basis
 pub trait Extension<F: Field>: Algebra<F> {
     /// The dimension (i.e. degree) of the extension
@@ -16,6 +22,40 @@ pub trait Extension<F: Field>: Algebra<F> {
 
     /// The coefficients of the element with respect to the fixed basis
     fn as_base_slice(&self) -> &[F];
+
+    /// The element of the base field equal to `self`, if it lies in the base field
+    fn try_to_base(&self) -> Option<F> {
+        let (first, rest) = self.as_base_slice().split_first()?;
+        rest.iter().all(|c| *c == F::ZERO).then_some(*first)
+    }
+
+    /// The 64-bit limbs of the coefficients of the element, one coefficient after the other
+    fn to_base_u64_limbs(&self) -> Vec<u64>
+    where
+        F: PrimeField,
+    {
+        self.as_base_slice()
+            .iter()
+            .flat_map(|c| c.to_u64_limbs())
+            .collect()
+    }
+
+    /// An element from the 64-bit limbs of its coefficients, or `None` if a coefficient is not
+    /// reduced or the number of limbs does not match the dimension
+    fn try_from_base_u64_limbs(limbs: &[u64]) -> Option<Self>
+    where
+        F: PrimeField,
+    {
+        let num_limbs = F::num_u64_limbs();
+        if limbs.len() != Self::D * num_limbs {
+            return None;
+        }
+        let coefficients = limbs
+            .chunks(num_limbs)
+            .map(F::try_from_u64_limbs)
+            .collect::<Option<Vec<_>>>()?;
+        Some(Self::from_base_slice(&coefficients))
+    }
 }
 
 /// A ring extension of a field with a fixed basis
@@ -34,3 +74,25 @@ impl<F: Field> Extension<F> for F {
 }
 
 impl<F: Field> ExtensionField<F> for F {}
+
+/// The Frobenius automorphism `x -> x^p` of a field of characteristic `p`
+pub trait Frobenius: Field {
+    /// The image `self^p` of `self`
+    fn frobenius(&self) -> Self;
+
+    /// Applies the Frobenius automorphism `count` times, computing `self^{p^count}`
+    fn repeated_frobenius(&self, count: usize) -> Self {
+        (0..count).fold(*self, |x, _| x.frobenius())
+    }
+}
+
+/// The Frobenius automorphism of a prime field is the identity.
+impl<F: PrimeField> Frobenius for F {
+    fn frobenius(&self) -> Self {
+        *self
+    }
+
+    fn repeated_frobenius(&self, _count: usize) -> Self {
+        *self
+    }
+}
@@ -0,0 +1,292 @@
+use alloc::vec::Vec;
+use core::hash::{Hash, Hasher};
+use core::iter::{Product, Sum};
+use core::marker::PhantomData;
+use core::ops::{Add, AddAssign, Div, DivAssign, Mul, MulAssign, Neg, Sub, SubAssign};
+
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+
+use super::parameters::QuadraticParameters;
+use crate::math::prelude::*;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(bound = "")]
+pub struct QuadraticExtension<F: Field, P: QuadraticParameters<F>>(pub [F; 2], PhantomData<P>);
+
+impl<F: Field, P: QuadraticParameters<F>> QuadraticExtension<F, P> {
+    pub const ZERO: Self = Self::new([F::ZERO; 2]);
+    pub const ONE: Self = Self::from_base_field(F::ONE);
+
+    /// The generator `U` of the extension, with `U^2 = NONRESIDUE`.
+    pub const U: Self = Self::new([F::ZERO, F::ONE]);
+
+    pub const fn new(coefficients: [F; 2]) -> Self {
+        Self(coefficients, PhantomData)
+    }
+
+    pub const fn from_base_field(a: F) -> Self {
+        Self::new([a, F::ZERO])
+    }
+
+    #[inline]
+    pub fn from_slice(slice: &[F]) -> Self {
+        let mut array = [F::ZERO; 2];
+        array.copy_from_slice(slice);
+        Self::new(array)
+    }
+
+    #[inline]
+    pub fn base_field_array(&self) -> [F; 2] {
+        self.0
+    }
+
+    /// The conjugate `a - bU` of `a + bU`, which is its image under the non-trivial automorphism
+    /// fixing the base field.
+    #[inline]
+    pub fn conjugate(&self) -> Self {
+        Self::new([self.0[0], -self.0[1]])
+    }
+
+    /// The norm `(a + bU)(a - bU) = a^2 - NONRESIDUE * b^2`, which lies in the base field.
+    #[inline]
+    pub fn norm(&self) -> F {
+        let [a, b] = self.0;
+        a.square() - P::NONRESIDUE * b.square()
+    }
+
+    pub fn try_inverse(&self) -> Option<Self> {
+        let norm_inv = self.norm().try_inverse()?;
+        Some(self.conjugate() * norm_inv)
+    }
+
+    pub fn inverse(&self) -> Self {
+        self.try_inverse().expect("Cannot invert zero")
+    }
+}
+
+impl<F: Field, P: QuadraticParameters<F>> From<[F; 2]> for QuadraticExtension<F, P> {
+    fn from(value: [F; 2]) -> Self {
+        Self::new(value)
+    }
+}
+
+impl<F: Field, P: QuadraticParameters<F>> From<F> for QuadraticExtension<F, P> {
+    fn from(value: F) -> Self {
+        Self::from_base_field(value)
+    }
+}
+
+impl<F: Field, P: QuadraticParameters<F>> Add for QuadraticExtension<F, P> {
+    type Output = Self;
+
+    fn add(self, rhs: Self) -> Self::Output {
+        Self::new([self.0[0] + rhs.0[0], self.0[1] + rhs.0[1]])
+    }
+}
+
+impl<F: Field, P: QuadraticParameters<F>> Add<F> for QuadraticExtension<F, P> {
+    type Output = Self;
+
+    fn add(self, rhs: F) -> Self::Output {
+        Self::new([self.0[0] + rhs, self.0[1]])
+    }
+}
+
+impl<F: Field, P: QuadraticParameters<F>> Sub for QuadraticExtension<F, P> {
+    type Output = Self;
+
+    fn sub(self, rhs: Self) -> Self::Output {
+        Self::new([self.0[0] - rhs.0[0], self.0[1] - rhs.0[1]])
+    }
+}
+
+impl<F: Field, P: QuadraticParameters<F>> Sub<F> for QuadraticExtension<F, P> {
+    type Output = Self;
+
+    fn sub(self, rhs: F) -> Self::Output {
+        Self::new([self.0[0] - rhs, self.0[1]])
+    }
+}
+
+impl<F: Field, P: QuadraticParameters<F>> Mul for QuadraticExtension<F, P> {
+    type Output = Self;
+
+    fn mul(self, rhs: Self) -> Self::Output {
+        let [a0, a1] = self.0;
+        let [b0, b1] = rhs.0;
+        // `(a0 + a1 U)(b0 + b1 U) = (a0 b0 + NONRESIDUE a1 b1) + (a0 b1 + a1 b0) U`, where the
+        // middle coefficient is computed with a single product as in Karatsuba's method.
+        let a0_b0 = a0 * b0;
+        let a1_b1 = a1 * b1;
+        Self::new([
+            a0_b0 + P::NONRESIDUE * a1_b1,
+            (a0 + a1) * (b0 + b1) - a0_b0 - a1_b1,
+        ])
+    }
+}
+
+impl<F: Field, P: QuadraticParameters<F>> Mul<F> for QuadraticExtension<F, P> {
+    type Output = Self;
+
+    fn mul(self, rhs: F) -> Self::Output {
+        Self::new(self.0.map(|c| c * rhs))
+    }
+}
+
+impl<F: Field, P: QuadraticParameters<F>> Neg for QuadraticExtension<F, P> {
+    type Output = Self;
+
+    fn neg(self) -> Self::Output {
+        Self::new(self.0.map(|c| -c))
+    }
+}
+
+impl<'a, F: Field, P: QuadraticParameters<F>> Sum<&'a Self> for QuadraticExtension<F, P> {
+    fn sum<I: Iterator<Item = &'a Self>>(iter: I) -> Self {
+        iter.fold(Self::ZERO, |acc, x| acc + *x)
+    }
+}
+
+impl<F: Field, P: QuadraticParameters<F>> Sum for QuadraticExtension<F, P> {
+    fn sum<I: Iterator<Item = Self>>(iter: I) -> Self {
+        iter.fold(Self::ZERO, |acc, x| acc + x)
+    }
+}
+
+impl<'a, F: Field, P: QuadraticParameters<F>> Product<&'a Self> for QuadraticExtension<F, P> {
+    fn product<I: Iterator<Item = &'a Self>>(iter: I) -> Self {
+        iter.fold(Self::ONE, |acc, x| acc * *x)
+    }
+}
+
+impl<F: Field, P: QuadraticParameters<F>> Product for QuadraticExtension<F, P> {
+    fn product<I: Iterator<Item = Self>>(iter: I) -> Self {
+        iter.fold(Self::ONE, |acc, x| acc * x)
+    }
+}
+
+impl<F: Field, P: QuadraticParameters<F>> AddAssign for QuadraticExtension<F, P> {
+    fn add_assign(&mut self, rhs: Self) {
+        *self = *self + rhs;
+    }
+}
+
+impl<F: Field, P: QuadraticParameters<F>> MulAssign for QuadraticExtension<F, P> {
+    fn mul_assign(&mut self, rhs: Self) {
+        *self = *self * rhs;
+    }
+}
+
+impl<F: Field, P: QuadraticParameters<F>> MulAssign<F> for QuadraticExtension<F, P> {
+    fn mul_assign(&mut self, rhs: F) {
+        *self = *self * rhs;
+    }
+}
+
+impl<F: Field, P: QuadraticParameters<F>> SubAssign for QuadraticExtension<F, P> {
+    fn sub_assign(&mut self, rhs: Self) {
+        *self = *self - rhs;
+    }
+}
+
+impl<F: Field, P: QuadraticParameters<F>> Div for QuadraticExtension<F, P> {
+    type Output = Self;
+
+    #[allow(clippy::suspicious_arithmetic_impl)]
+    fn div(self, rhs: Self) -> Self::Output {
+        self * rhs.inverse()
+    }
+}
+
+impl<F: Field, P: QuadraticParameters<F>> DivAssign for QuadraticExtension<F, P> {
+    fn div_assign(&mut self, rhs: Self) {
+        *self = *self / rhs;
+    }
+}
+
+impl<F: Field + Sample, P: QuadraticParameters<F>> Sample for QuadraticExtension<F, P> {
+    fn sample<R: Rng + ?Sized>(rng: &mut R) -> Self {
+        Self::new([F::sample(rng), F::sample(rng)])
+    }
+}
+
+impl<F: Field, P: QuadraticParameters<F>> Default for QuadraticExtension<F, P> {
+    fn default() -> Self {
+        Self::ZERO
+    }
+}
+
+impl<F: Field, P: QuadraticParameters<F>> Hash for QuadraticExtension<F, P> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.0.hash(state);
+    }
+}
+
+impl<F: Field, P: QuadraticParameters<F>> Ring for QuadraticExtension<F, P> {
+    const ONE: Self = Self::ONE;
+    const ZERO: Self = Self::ZERO;
+}
+
+impl<F: Field, P: QuadraticParameters<F>> Algebra<F> for QuadraticExtension<F, P> {}
+
+impl<F: Field, P: QuadraticParameters<F>> Extension<F> for QuadraticExtension<F, P> {
+    const D: usize = 2;
+
+    fn as_base_slice(&self) -> &[F] {
+        &self.0
+    }
+
+    fn from_base_slice(elements: &[F]) -> Self {
+        Self::from_slice(elements)
+    }
+}
+
+impl<F: Field, P: QuadraticParameters<F>> ExtensionField<F> for QuadraticExtension<F, P> {}
+
+impl<F: Frobenius, P: QuadraticParameters<F>> Frobenius for QuadraticExtension<F, P> {
+    /// Since `U^p = FROBENIUS_COEFF * U`, the image of `a + bU` is
+    /// `a^p + b^p * FROBENIUS_COEFF * U`.
+    fn frobenius(&self) -> Self {
+        let [a, b] = self.0;
+        Self::new([a.frobenius(), b.frobenius() * P::FROBENIUS_COEFF])
+    }
+}
+
+impl<F: Field, P: QuadraticParameters<F>> Field for QuadraticExtension<F, P> {
+    fn try_inverse(&self) -> Option<Self> {
+        self.try_inverse()
+    }
+    fn from_canonical_u8(n: u8) -> Self {
+        Self::from_base_field(F::from_canonical_u8(n))
+    }
+    fn from_canonical_u16(n: u16) -> Self {
+        Self::from_base_field(F::from_canonical_u16(n))
+    }
+    fn from_canonical_u32(n: u32) -> Self {
+        Self::from_base_field(F::from_canonical_u32(n))
+    }
+    fn from_canonical_u64(n: u64) -> Self {
+        Self::from_base_field(F::from_canonical_u64(n))
+    }
+    fn from_canonical_usize(n: usize) -> Self {
+        Self::from_base_field(F::from_canonical_usize(n))
+    }
+
+    fn from_noncanonical_biguint(n: num::BigUint) -> Self {
+        Self::from_base_field(F::from_noncanonical_biguint(n))
+    }
+
+    /// The roots of unity of the base field. The extension has roots of larger two-adic order, but
+    /// these are not needed for domains of the size supported by the base field.
+    fn primitive_root_of_unity(n_log: usize) -> Self {
+        Self::from_base_field(F::primitive_root_of_unity(n_log))
+    }
+
+    fn two_adic_subgroup(n_log: usize) -> Vec<Self> {
+        F::two_adic_subgroup(n_log)
+            .into_iter()
+            .map(Self::from_base_field)
+            .collect()
+    }
+}
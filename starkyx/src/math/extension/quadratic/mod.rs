@@ -0,0 +1,7 @@
+//! The quadratic extension F[U]/(U^2 - NONRESIDUE) of a field which may itself be an extension.
+//!
+//! Towers of extensions, such as the degree 4, 6 and 8 extensions used by pairing-friendly
+//! constructions, are built by stacking quadratic extensions on top of each other.
+
+pub mod extension;
+pub mod parameters;
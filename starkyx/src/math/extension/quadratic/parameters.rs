@@ -0,0 +1,17 @@
+use core::fmt::Debug;
+
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+
+/// Parameters for the quadratic extension F[U]/(U^2 - NONRESIDUE)
+pub trait QuadraticParameters<F>:
+    'static + Sized + Copy + Clone + Send + Sync + PartialEq + Eq + Debug + Serialize + DeserializeOwned
+{
+    /// The constant `NONRESIDUE`, which is not a square in the base field.
+    const NONRESIDUE: F;
+
+    /// The constant `NONRESIDUE^((p - 1) / 2)`, where `p` is the characteristic of the field.
+    ///
+    /// The Frobenius automorphism maps `U` to `FROBENIUS_COEFF * U`.
+    const FROBENIUS_COEFF: F;
+}
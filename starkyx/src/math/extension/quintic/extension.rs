@@ -0,0 +1,319 @@
+use alloc::vec::Vec;
+use core::hash::{Hash, Hasher};
+use core::iter::{Product, Sum};
+use core::marker::PhantomData;
+use core::ops::{Add, AddAssign, Div, DivAssign, Mul, MulAssign, Neg, Sub, SubAssign};
+
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+
+use super::parameters::QuinticParameters;
+use crate::math::prelude::*;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(bound = "")]
+pub struct QuinticExtension<F: Field, P: QuinticParameters<F>>(pub [F; 5], PhantomData<P>);
+
+impl<F: Field, P: QuinticParameters<F>> QuinticExtension<F, P> {
+    pub const ZERO: Self = Self::new([F::ZERO; 5]);
+    pub const ONE: Self = Self::from_base_field(F::ONE);
+
+    /// A generator of the multiplicative group.
+    pub const GENERATOR: Self = Self::new(P::EXT_MULTIPLICATIVE_GROUP_GENERATOR);
+
+    pub const fn new(coefficients: [F; 5]) -> Self {
+        Self(coefficients, PhantomData)
+    }
+
+    pub const fn from_base_field(a: F) -> Self {
+        Self::new([a, F::ZERO, F::ZERO, F::ZERO, F::ZERO])
+    }
+
+    #[inline]
+    pub fn from_slice(slice: &[F]) -> Self {
+        let mut array = [F::ZERO; 5];
+        array.copy_from_slice(slice);
+        Self::new(array)
+    }
+
+    #[inline]
+    pub fn base_field_array(&self) -> [F; 5] {
+        self.0
+    }
+
+    #[inline]
+    fn in_base_field(&self) -> bool {
+        self.0[1..].iter().all(|c| *c == F::ZERO)
+    }
+
+    /// Applies the Frobenius automorphism `x -> x^p` `count` times.
+    ///
+    /// Since `X^p = DTH_ROOT * X`, the `i`-th coefficient is multiplied by `DTH_ROOT^{i * count}`.
+    pub fn repeated_frobenius(&self, count: usize) -> Self {
+        let z0 = P::DTH_ROOT.pow((count % 5) as u64);
+        let mut z = F::ONE;
+        let mut coefficients = self.0;
+        for c in coefficients.iter_mut() {
+            *c *= z;
+            z *= z0;
+        }
+        Self::new(coefficients)
+    }
+
+    pub fn try_inverse(&self) -> Option<Self> {
+        if *self == Self::ZERO {
+            return None;
+        }
+        // `f = x^{p + p^2 + p^3 + p^4}`, so that `x * f` is the norm of `x`, which lies in the base
+        // field.
+        let a_pow_p = self.repeated_frobenius(1);
+        let a_pow_p_p2 = a_pow_p * a_pow_p.repeated_frobenius(1);
+        let f = a_pow_p_p2 * a_pow_p_p2.repeated_frobenius(2);
+        let norm = *self * f;
+        debug_assert!(norm.in_base_field());
+
+        let norm_inv = norm.0[0].try_inverse()?;
+        Some(f * norm_inv)
+    }
+
+    pub fn inverse(&self) -> Self {
+        self.try_inverse().expect("Cannot invert zero")
+    }
+}
+
+impl<F: Field, P: QuinticParameters<F>> From<[F; 5]> for QuinticExtension<F, P> {
+    fn from(value: [F; 5]) -> Self {
+        Self::new(value)
+    }
+}
+
+impl<F: Field, P: QuinticParameters<F>> From<F> for QuinticExtension<F, P> {
+    fn from(value: F) -> Self {
+        Self::from_base_field(value)
+    }
+}
+
+impl<F: Field, P: QuinticParameters<F>> Add for QuinticExtension<F, P> {
+    type Output = Self;
+
+    fn add(self, rhs: Self) -> Self::Output {
+        let mut coefficients = self.0;
+        for (c, r) in coefficients.iter_mut().zip(rhs.0) {
+            *c += r;
+        }
+        Self::new(coefficients)
+    }
+}
+
+impl<F: Field, P: QuinticParameters<F>> Add<F> for QuinticExtension<F, P> {
+    type Output = Self;
+
+    fn add(self, rhs: F) -> Self::Output {
+        self + Self::from_base_field(rhs)
+    }
+}
+
+impl<F: Field, P: QuinticParameters<F>> Sub for QuinticExtension<F, P> {
+    type Output = Self;
+
+    fn sub(self, rhs: Self) -> Self::Output {
+        let mut coefficients = self.0;
+        for (c, r) in coefficients.iter_mut().zip(rhs.0) {
+            *c -= r;
+        }
+        Self::new(coefficients)
+    }
+}
+
+impl<F: Field, P: QuinticParameters<F>> Sub<F> for QuinticExtension<F, P> {
+    type Output = Self;
+
+    fn sub(self, rhs: F) -> Self::Output {
+        self - Self::from_base_field(rhs)
+    }
+}
+
+impl<F: Field, P: QuinticParameters<F>> Mul for QuinticExtension<F, P> {
+    type Output = Self;
+
+    fn mul(self, rhs: Self) -> Self::Output {
+        let mut coefficients = [F::ZERO; 5];
+        for (i, &a) in self.0.iter().enumerate() {
+            for (j, &b) in rhs.0.iter().enumerate() {
+                // `X^5 = W`.
+                if i + j < 5 {
+                    coefficients[i + j] += a * b;
+                } else {
+                    coefficients[i + j - 5] += P::W * a * b;
+                }
+            }
+        }
+        Self::new(coefficients)
+    }
+}
+
+impl<F: Field, P: QuinticParameters<F>> Mul<F> for QuinticExtension<F, P> {
+    type Output = Self;
+
+    fn mul(self, rhs: F) -> Self::Output {
+        Self::new(self.0.map(|c| c * rhs))
+    }
+}
+
+impl<F: Field, P: QuinticParameters<F>> Neg for QuinticExtension<F, P> {
+    type Output = Self;
+
+    fn neg(self) -> Self::Output {
+        Self::new(self.0.map(|c| -c))
+    }
+}
+
+impl<'a, F: Field, P: QuinticParameters<F>> Sum<&'a Self> for QuinticExtension<F, P> {
+    fn sum<I: Iterator<Item = &'a Self>>(iter: I) -> Self {
+        iter.fold(Self::ZERO, |acc, x| acc + *x)
+    }
+}
+
+impl<F: Field, P: QuinticParameters<F>> Sum for QuinticExtension<F, P> {
+    fn sum<I: Iterator<Item = Self>>(iter: I) -> Self {
+        iter.fold(Self::ZERO, |acc, x| acc + x)
+    }
+}
+
+impl<'a, F: Field, P: QuinticParameters<F>> Product<&'a Self> for QuinticExtension<F, P> {
+    fn product<I: Iterator<Item = &'a Self>>(iter: I) -> Self {
+        iter.fold(Self::ONE, |acc, x| acc * *x)
+    }
+}
+
+impl<F: Field, P: QuinticParameters<F>> Product for QuinticExtension<F, P> {
+    fn product<I: Iterator<Item = Self>>(iter: I) -> Self {
+        iter.fold(Self::ONE, |acc, x| acc * x)
+    }
+}
+
+impl<F: Field, P: QuinticParameters<F>> AddAssign for QuinticExtension<F, P> {
+    fn add_assign(&mut self, rhs: Self) {
+        *self = *self + rhs;
+    }
+}
+
+impl<F: Field, P: QuinticParameters<F>> MulAssign for QuinticExtension<F, P> {
+    fn mul_assign(&mut self, rhs: Self) {
+        *self = *self * rhs;
+    }
+}
+
+impl<F: Field, P: QuinticParameters<F>> MulAssign<F> for QuinticExtension<F, P> {
+    fn mul_assign(&mut self, rhs: F) {
+        *self = *self * rhs;
+    }
+}
+
+impl<F: Field, P: QuinticParameters<F>> SubAssign for QuinticExtension<F, P> {
+    fn sub_assign(&mut self, rhs: Self) {
+        *self = *self - rhs;
+    }
+}
+
+impl<F: Field, P: QuinticParameters<F>> Div for QuinticExtension<F, P> {
+    type Output = Self;
+
+    #[allow(clippy::suspicious_arithmetic_impl)]
+    fn div(self, rhs: Self) -> Self::Output {
+        self * rhs.inverse()
+    }
+}
+
+impl<F: Field, P: QuinticParameters<F>> DivAssign for QuinticExtension<F, P> {
+    fn div_assign(&mut self, rhs: Self) {
+        *self = *self / rhs;
+    }
+}
+
+impl<F: Field + Sample, P: QuinticParameters<F>> Sample for QuinticExtension<F, P> {
+    fn sample<R: Rng + ?Sized>(rng: &mut R) -> Self {
+        Self::new(core::array::from_fn(|_| F::sample(rng)))
+    }
+}
+
+impl<F: Field, P: QuinticParameters<F>> Default for QuinticExtension<F, P> {
+    fn default() -> Self {
+        Self::ZERO
+    }
+}
+
+impl<F: Field, P: QuinticParameters<F>> Hash for QuinticExtension<F, P> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.0.hash(state);
+    }
+}
+
+impl<F: Field, P: QuinticParameters<F>> Ring for QuinticExtension<F, P> {
+    const ONE: Self = Self::ONE;
+    const ZERO: Self = Self::ZERO;
+}
+
+impl<F: Field, P: QuinticParameters<F>> Algebra<F> for QuinticExtension<F, P> {}
+
+impl<F: Field, P: QuinticParameters<F>> Extension<F> for QuinticExtension<F, P> {
+    const D: usize = 5;
+
+    fn as_base_slice(&self) -> &[F] {
+        &self.0
+    }
+
+    fn from_base_slice(elements: &[F]) -> Self {
+        Self::from_slice(elements)
+    }
+}
+
+impl<F: Field, P: QuinticParameters<F>> ExtensionField<F> for QuinticExtension<F, P> {}
+
+impl<F: PrimeField, P: QuinticParameters<F>> Frobenius for QuinticExtension<F, P> {
+    fn frobenius(&self) -> Self {
+        QuinticExtension::repeated_frobenius(self, 1)
+    }
+
+    fn repeated_frobenius(&self, count: usize) -> Self {
+        QuinticExtension::repeated_frobenius(self, count)
+    }
+}
+
+impl<F: Field, P: QuinticParameters<F>> Field for QuinticExtension<F, P> {
+    fn try_inverse(&self) -> Option<Self> {
+        self.try_inverse()
+    }
+    fn from_canonical_u8(n: u8) -> Self {
+        Self::from_base_field(F::from_canonical_u8(n))
+    }
+    fn from_canonical_u16(n: u16) -> Self {
+        Self::from_base_field(F::from_canonical_u16(n))
+    }
+    fn from_canonical_u32(n: u32) -> Self {
+        Self::from_base_field(F::from_canonical_u32(n))
+    }
+    fn from_canonical_u64(n: u64) -> Self {
+        Self::from_base_field(F::from_canonical_u64(n))
+    }
+    fn from_canonical_usize(n: usize) -> Self {
+        Self::from_base_field(F::from_canonical_usize(n))
+    }
+
+    fn from_noncanonical_biguint(n: num::BigUint) -> Self {
+        Self::from_base_field(F::from_noncanonical_biguint(n))
+    }
+
+    /// The two-adic part of the multiplicative group of the extension is the one of the base
+    /// field, since `(p^5 - 1) / (p - 1)` is odd.
+    fn primitive_root_of_unity(n_log: usize) -> Self {
+        Self::from_base_field(F::primitive_root_of_unity(n_log))
+    }
+
+    fn two_adic_subgroup(n_log: usize) -> Vec<Self> {
+        F::two_adic_subgroup(n_log)
+            .into_iter()
+            .map(Self::from_base_field)
+            .collect()
+    }
+}
@@ -0,0 +1,4 @@
+//! The quintic extension field F[X]/(X^5 - W).
+
+pub mod extension;
+pub mod parameters;
@@ -0,0 +1,20 @@
+use core::fmt::Debug;
+
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+
+/// Parameters for the quintic extension F[X]/(X^5 - W)
+pub trait QuinticParameters<F>:
+    'static + Sized + Copy + Clone + Send + Sync + PartialEq + Eq + Debug + Serialize + DeserializeOwned
+{
+    /// The constant `W`, which is not a fifth power in the base field.
+    const W: F;
+
+    /// The primitive fifth root of unity `W^((p - 1) / 5)`.
+    ///
+    /// The Frobenius automorphism maps `X` to `DTH_ROOT * X`.
+    const DTH_ROOT: F;
+
+    /// A generator of the multiplicative group of the extension field.
+    const EXT_MULTIPLICATIVE_GROUP_GENERATOR: [F; 5];
+}
@@ -1,9 +1,12 @@
+use alloc::vec;
+use alloc::vec::Vec;
 use core::fmt::Debug;
 use core::hash::Hash;
 use core::iter::{Product, Sum};
 use core::ops::{Add, AddAssign, Div, DivAssign, Mul, MulAssign, Neg, Sub, SubAssign};
 
 use num::BigUint;
+#[cfg(feature = "std")]
 use rand::rngs::OsRng;
 use serde::de::DeserializeOwned;
 use serde::{Deserialize, Serialize};
@@ -73,6 +76,11 @@ pub trait Field:
     fn from_canonical_usize(n: usize) -> Self;
     fn from_noncanonical_biguint(n: BigUint) -> Self;
 
+    /// The element `n`, reduced modulo the characteristic of the field.
+    fn from_biguint(n: &BigUint) -> Self {
+        Self::from_noncanonical_biguint(n.clone())
+    }
+
     fn primitive_root_of_unity(n_log: usize) -> Self;
 
     fn two_adic_subgroup(n_log: usize) -> Vec<Self>;
@@ -81,6 +89,32 @@ pub trait Field:
         self.try_inverse().expect("Tried to invert zero")
     }
 
+    /// Inverts every element of `x` with a single inversion, using Montgomery's trick.
+    ///
+    /// Panics if any element of `x` is zero.
+    fn batch_multiplicative_inverse(x: &[Self]) -> Vec<Self> {
+        if x.is_empty() {
+            return Vec::new();
+        }
+        // `prefix[i]` is the product of `x[0..=i]`.
+        let mut prefix = Vec::with_capacity(x.len());
+        let mut acc = Self::ONE;
+        for &x_i in x {
+            acc *= x_i;
+            prefix.push(acc);
+        }
+
+        // `acc_inv` is the inverse of the product of `x[0..=i]` at step `i`.
+        let mut acc_inv = acc.inverse();
+        let mut inverses = vec![Self::ZERO; x.len()];
+        for i in (1..x.len()).rev() {
+            inverses[i] = acc_inv * prefix[i - 1];
+            acc_inv *= x[i];
+        }
+        inverses[0] = acc_inv;
+        inverses
+    }
+
     /// Returns `true` if `self` is zero.
     fn is_zero(&self) -> bool {
         *self == Self::ZERO
@@ -131,7 +165,116 @@ pub trait Field:
 }
 
 /// A finite field of the form `F_p` for some prime `p`.
-pub trait PrimeField: Field {}
+pub trait PrimeField: Field {
+    /// The order `p` of the field.
+    fn modulus() -> BigUint;
+
+    /// The canonical representative of `self` in `[0, p)`.
+    fn to_biguint(&self) -> BigUint;
+
+    /// The number of 64-bit limbs of the canonical representatives.
+    fn num_u64_limbs() -> usize {
+        Self::modulus().bits().div_ceil(64) as usize
+    }
+
+    /// The little-endian 64-bit limbs of the canonical representative of `self`.
+    ///
+    /// There are always `Self::num_u64_limbs()` limbs.
+    fn to_u64_limbs(&self) -> Vec<u64> {
+        let mut limbs = self.to_biguint().to_u64_digits();
+        limbs.resize(Self::num_u64_limbs(), 0);
+        limbs
+    }
+
+    /// The element with little-endian 64-bit limbs `limbs`, or `None` if the value of the limbs
+    /// is not less than `p`.
+    fn try_from_u64_limbs(limbs: &[u64]) -> Option<Self> {
+        let digits = limbs
+            .iter()
+            .flat_map(|limb| [*limb as u32, (limb >> 32) as u32])
+            .collect::<Vec<_>>();
+        let n = BigUint::from_slice(&digits);
+        if n >= Self::modulus() {
+            return None;
+        }
+        Some(Self::from_biguint(&n))
+    }
+
+    /// The number of bytes needed by `from_uniform_bytes`.
+    ///
+    /// These are `128` more bits than the size of the modulus, so that the reduction of uniform
+    /// bytes is within a statistical distance of `2^-128` of the uniform distribution.
+    fn uniform_bytes_len() -> usize {
+        (Self::modulus().bits() as usize + 128).div_ceil(8)
+    }
+
+    /// An element derived from uniformly random `bytes`, by reducing their little-endian value
+    /// modulo `p`.
+    ///
+    /// Panics if there are fewer than `Self::uniform_bytes_len()` bytes, since the reduction would
+    /// then be noticeably biased.
+    fn from_uniform_bytes(bytes: &[u8]) -> Self {
+        assert!(
+            bytes.len() >= Self::uniform_bytes_len(),
+            "At least {} uniform bytes are needed, found {}",
+            Self::uniform_bytes_len(),
+            bytes.len()
+        );
+        Self::from_biguint(&BigUint::from_bytes_le(bytes))
+    }
+
+    /// The Legendre symbol of `self`, which is `1` for non-zero squares, `-1` for non-squares and
+    /// `0` for zero.
+    fn legendre(&self) -> i8 {
+        let symbol = self.pow_biguint(&((Self::modulus() - 1u32) >> 1));
+        if symbol == Self::ZERO {
+            0
+        } else if symbol == Self::ONE {
+            1
+        } else {
+            -1
+        }
+    }
+
+    /// A square root of `self`, or `None` if `self` is not a square.
+    ///
+    /// This is the Tonelli-Shanks algorithm, using the primitive root of unity whose order is the
+    /// largest power of two dividing `p - 1`.
+    fn sqrt(&self) -> Option<Self> {
+        if self.is_zero() {
+            return Some(Self::ZERO);
+        }
+        if self.legendre() != 1 {
+            return None;
+        }
+        // `p - 1 = 2^s * t` with `t` odd.
+        let p_minus_one = Self::modulus() - 1u32;
+        let s = p_minus_one.trailing_zeros().unwrap() as usize;
+        let t = &p_minus_one >> s;
+
+        // The invariant is `x^2 = self * b`, where the order of `b` divides `2^m` and `z` has
+        // order `2^m`.
+        let mut x = self.pow_biguint(&((&t + 1u32) >> 1));
+        let mut b = self.pow_biguint(&t);
+        let mut z = Self::primitive_root_of_unity(s);
+        let mut m = s;
+        while b != Self::ONE {
+            // The order `2^i` of `b`, with `i < m`.
+            let mut i = 0;
+            let mut b_pow = b;
+            while b_pow != Self::ONE {
+                b_pow = b_pow.square();
+                i += 1;
+            }
+            let c = z.two_pow(m - i - 1);
+            z = c.square();
+            x *= c;
+            b *= z;
+            m = i;
+        }
+        Some(x)
+    }
+}
 
 /// A prime field of order less than `2^64`.
 pub trait PrimeField64: PrimeField + Serialize + for<'de> Deserialize<'de> {
@@ -167,18 +310,21 @@ pub trait Sample: Sized {
         R: rand::RngCore + ?Sized;
 
     /// Samples a single value using the [`OsRng`].
+    #[cfg(feature = "std")]
     #[inline]
     fn rand() -> Self {
         Self::sample(&mut OsRng)
     }
 
     /// Samples a [`Vec`] of values of length `n` using [`OsRng`].
+    #[cfg(feature = "std")]
     #[inline]
     fn rand_vec(n: usize) -> Vec<Self> {
         (0..n).map(|_| Self::rand()).collect()
     }
 
     /// Samples an array of values of length `N` using [`OsRng`].
+    #[cfg(feature = "std")]
     #[inline]
     fn rand_array<const N: usize>() -> [Self; N] {
         Self::rand_vec(N)
@@ -190,7 +336,159 @@ pub trait Sample: Sized {
 
 #[cfg(test)]
 pub mod tests {
+    use proptest::prelude::*;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
     use super::*;
+    use crate::math::extension::Frobenius;
+
+    /// Generates property tests of the arithmetic of the field `$field`.
+    ///
+    /// The field axioms, inversion and the Frobenius automorphism are tested for every field, given
+    /// its degree `$degree` over its prime field. Without a degree, `$field` is a prime field and the
+    /// reduction of the values around multiples of the modulus is tested as well.
+    macro_rules! test_field_arithmetic {
+        ($name:ident, $field:ty) => {
+            $crate::math::field::tests::test_field_arithmetic!(@field $name, $field, 1, {
+                #[test]
+                fn test_prime_field_reduction(
+                    k in prop_oneof![0u64..1 << 16, any::<u64>()],
+                    m in any::<u64>(),
+                ) {
+                    $crate::math::field::tests::prime_field_reduction::<$field>(k, m);
+                }
+            });
+        };
+        ($name:ident, $field:ty, $degree:expr) => {
+            $crate::math::field::tests::test_field_arithmetic!(@field $name, $field, $degree, {});
+        };
+        (@field $name:ident, $field:ty, $degree:expr, { $($extra:tt)* }) => {
+            mod $name {
+                use proptest::prelude::*;
+
+                use super::*;
+                use $crate::math::field::tests::{
+                    element, field_axioms, frobenius_properties, inverse_properties,
+                };
+
+                proptest! {
+                    #[test]
+                    fn test_field_axioms(
+                        a in element::<$field>(),
+                        b in element::<$field>(),
+                        c in element::<$field>(),
+                    ) {
+                        field_axioms(a, b, c);
+                    }
+
+                    #[test]
+                    fn test_inverse(a in element::<$field>(), b in element::<$field>()) {
+                        inverse_properties(a, b);
+                    }
+
+                    #[test]
+                    fn test_frobenius(a in element::<$field>(), b in element::<$field>()) {
+                        frobenius_properties(a, b, $degree);
+                    }
+
+                    $($extra)*
+                }
+            }
+        };
+    }
+
+    pub(crate) use test_field_arithmetic;
+
+    /// A strategy for elements of `F`, which are either uniformly random or one of the edge cases
+    /// `0`, `1` and `-1`.
+    pub fn element<F: Field + Sample>() -> impl Strategy<Value = F> {
+        prop_oneof![
+            1 => Just(F::ZERO),
+            1 => Just(F::ONE),
+            1 => Just(-F::ONE),
+            7 => any::<[u8; 32]>().prop_map(|seed| F::sample(&mut StdRng::from_seed(seed))),
+        ]
+    }
+
+    /// The ring axioms and the consistency of the derived operations.
+    pub fn field_axioms<F: Field>(a: F, b: F, c: F) {
+        assert_eq!(a + b, b + a);
+        assert_eq!((a + b) + c, a + (b + c));
+        assert_eq!(a * b, b * a);
+        assert_eq!((a * b) * c, a * (b * c));
+        assert_eq!(a * (b + c), a * b + a * c);
+        assert_eq!((a - b) + b, a);
+        assert_eq!(a + (-a), F::ZERO);
+        assert_eq!(a * F::ONE, a);
+        assert_eq!(a * F::ZERO, F::ZERO);
+        assert_eq!(a.square(), a * a);
+        assert_eq!((a + b).square(), a.square() + a * b + a * b + b.square());
+        assert_eq!(a.pow(3), a * a * a);
+        assert_eq!([a, b, c].into_iter().sum::<F>(), a + b + c);
+        assert_eq!([a, b, c].into_iter().product::<F>(), a * b * c);
+    }
+
+    /// Inversion and division, including the edge cases of zero and `-1`.
+    pub fn inverse_properties<F: Field>(a: F, b: F) {
+        match a.try_inverse() {
+            None => assert_eq!(a, F::ZERO),
+            Some(a_inv) => {
+                assert_eq!(a * a_inv, F::ONE);
+                assert_eq!(a_inv.inverse(), a);
+                assert_eq!((b / a) * a, b);
+            }
+        }
+        assert_eq!((-F::ONE).inverse(), -F::ONE);
+
+        let elements = [a, b, a * b, -a]
+            .into_iter()
+            .filter(|x| !x.is_zero())
+            .collect::<Vec<_>>();
+        for (x, x_inv) in elements
+            .iter()
+            .zip(F::batch_multiplicative_inverse(&elements))
+        {
+            assert_eq!(x.inverse(), x_inv);
+        }
+    }
+
+    /// The Frobenius automorphism is a ring homomorphism whose order divides `degree`.
+    pub fn frobenius_properties<F: Frobenius>(a: F, b: F, degree: usize) {
+        assert_eq!((a + b).frobenius(), a.frobenius() + b.frobenius());
+        assert_eq!((a * b).frobenius(), a.frobenius() * b.frobenius());
+        assert_eq!(a.repeated_frobenius(2), a.frobenius().frobenius());
+        assert_eq!(a.repeated_frobenius(degree), a);
+        assert_eq!(F::ONE.frobenius(), F::ONE);
+    }
+
+    /// The reduction of the values `m * p + k` and `p - k` around multiples of the modulus `p`, and
+    /// the inversion of the elements close to `p`.
+    pub fn prime_field_reduction<F: PrimeField>(k: u64, m: u64) {
+        let p = F::modulus();
+        let k_int = BigUint::from(k);
+        let k_elem = F::from_biguint(&k_int);
+        assert_eq!(F::from_biguint(&(&p * m + &k_int)), k_elem);
+        assert!(k_elem.to_biguint() < p);
+
+        if k_int < p {
+            assert_eq!(k_elem.to_biguint(), k_int);
+            let p_minus_k = F::from_biguint(&(&p - &k_int));
+            assert_eq!(p_minus_k, -k_elem);
+            if k != 0 {
+                assert_eq!(p_minus_k.to_biguint(), &p - &k_int);
+                assert_eq!(p_minus_k * p_minus_k.inverse(), F::ONE);
+                assert_eq!(p_minus_k.inverse(), -k_elem.inverse());
+            }
+        }
+
+        // The limbs of a value in `[p, 2p)` are rejected.
+        let mut limbs = (&p + &k_int % &p).to_u64_digits();
+        if limbs.len() <= F::num_u64_limbs() {
+            limbs.resize(F::num_u64_limbs(), 0);
+            assert_eq!(F::try_from_u64_limbs(&limbs), None);
+        }
+    }
 
     pub fn ring_test<F: Ring + Eq + Sample>() {
         let a = F::rand();
@@ -257,5 +555,54 @@ pub mod tests {
         if a != zero {
             assert_eq!(a * a.inverse(), one);
         }
+        // Test batch inversion
+        let elements = [a, b, c, a * b]
+            .into_iter()
+            .filter(|x| *x != zero)
+            .collect::<Vec<_>>();
+        let inverses = F::batch_multiplicative_inverse(&elements);
+        assert_eq!(inverses.len(), elements.len());
+        for (x, x_inv) in elements.iter().zip(inverses) {
+            assert_eq!(*x * x_inv, one);
+        }
+        assert!(F::batch_multiplicative_inverse(&[]).is_empty());
+    }
+
+    pub fn prime_field_test<F: PrimeField + Sample>() {
+        let a = F::rand();
+        let p = F::modulus();
+
+        // Test conversions to and from integers
+        let a_int = a.to_biguint();
+        assert!(a_int < p);
+        assert_eq!(F::from_biguint(&a_int), a);
+        assert_eq!(F::from_biguint(&(&a_int + &p)), a);
+        assert_eq!((-F::ONE).to_biguint(), &p - 1u32);
+
+        // Test conversions to and from limbs
+        let limbs = a.to_u64_limbs();
+        assert_eq!(limbs.len(), F::num_u64_limbs());
+        assert_eq!(F::try_from_u64_limbs(&limbs), Some(a));
+        let mut p_limbs = p.to_u64_digits();
+        p_limbs.resize(F::num_u64_limbs(), 0);
+        assert_eq!(F::try_from_u64_limbs(&p_limbs), None);
+
+        // Test sampling from uniform bytes
+        let mut bytes = (&a_int + &p).to_bytes_le();
+        bytes.resize(F::uniform_bytes_len(), 0);
+        assert_eq!(F::from_uniform_bytes(&bytes), a);
+
+        // Test square roots and the Legendre symbol
+        let square = a.square();
+        let root = square.sqrt().unwrap();
+        assert!(root == a || root == -a);
+        assert_eq!(F::ZERO.legendre(), 0);
+        if a != F::ZERO {
+            assert_eq!(square.legendre(), 1);
+        }
+        let two_adicity = (&p - 1u32).trailing_zeros().unwrap() as usize;
+        let non_square = F::primitive_root_of_unity(two_adicity);
+        assert_eq!(non_square.legendre(), -1);
+        assert_eq!(non_square.sqrt(), None);
     }
 }
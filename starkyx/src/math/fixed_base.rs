@@ -0,0 +1,108 @@
+//! Exponentiation of a fixed base with precomputed windowed tables.
+//!
+//! The exponent is split into windows of `window_bits` bits. For every window `i`, the table holds
+//! the powers `base^{d * 2^{i * window_bits}}` for all digits `d`, so that a power of the base is
+//! a product of one table entry per window, without any squaring.
+
+use alloc::vec::Vec;
+
+use super::field::Field;
+
+/// The powers of a fixed base, for exponents of up to `num_bits` bits.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FixedBaseTable<F> {
+    window_bits: usize,
+    num_bits: usize,
+    windows: Vec<Vec<F>>,
+}
+
+impl<F: Field> FixedBaseTable<F> {
+    /// Builds the table of `base` for exponents of up to `num_bits` bits.
+    pub fn new(base: F, num_bits: usize, window_bits: usize) -> Self {
+        assert!(
+            (1..=16).contains(&window_bits),
+            "The window size must be between 1 and 16 bits"
+        );
+        let num_windows = num_bits.div_ceil(window_bits);
+        let mut window_base = base;
+        let windows = (0..num_windows)
+            .map(|_| {
+                let window = window_base.powers().take(1 << window_bits).collect();
+                window_base = window_base.two_pow(window_bits);
+                window
+            })
+            .collect();
+        Self {
+            window_bits,
+            num_bits,
+            windows,
+        }
+    }
+
+    /// The base of the table.
+    pub fn base(&self) -> F {
+        self.windows.first().map_or(F::ONE, |window| window[1])
+    }
+
+    /// The maximal number of bits of the exponents.
+    pub fn num_bits(&self) -> usize {
+        self.num_bits
+    }
+
+    /// Computes `base^exponent`.
+    ///
+    /// Panics if the exponent has more than `num_bits` bits.
+    pub fn pow(&self, exponent: u64) -> F {
+        assert!(
+            self.num_bits >= 64 || exponent >> self.num_bits == 0,
+            "The exponent {exponent} has more than {} bits",
+            self.num_bits
+        );
+        let mask = (1 << self.window_bits) - 1;
+        self.windows
+            .iter()
+            .enumerate()
+            .take_while(|(i, _)| i * self.window_bits < 64)
+            .map(|(i, window)| window[((exponent >> (i * self.window_bits)) & mask) as usize])
+            .product()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use plonky2::field::goldilocks_field::GoldilocksField;
+    use rand::{thread_rng, Rng};
+
+    use super::*;
+    use crate::math::prelude::*;
+
+    #[test]
+    fn test_fixed_base_pow() {
+        type F = GoldilocksField;
+
+        let mut rng = thread_rng();
+        let base = F::rand();
+        for window_bits in [1, 3, 8] {
+            let table = FixedBaseTable::new(base, 64, window_bits);
+            assert_eq!(table.base(), base);
+            for exponent in [0, 1, 2, u64::MAX]
+                .into_iter()
+                .chain((0..20).map(|_| rng.gen()))
+            {
+                assert_eq!(table.pow(exponent), base.pow(exponent));
+            }
+        }
+
+        let table = FixedBaseTable::new(base, 20, 8);
+        assert_eq!(table.pow((1 << 20) - 1), base.pow((1 << 20) - 1));
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_fixed_base_exponent_too_large() {
+        type F = GoldilocksField;
+
+        let table = FixedBaseTable::new(F::TWO, 20, 8);
+        table.pow(1 << 20);
+    }
+}
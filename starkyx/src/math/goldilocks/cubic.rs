@@ -29,8 +29,11 @@ impl CubicParameters<GoldilocksField> for GoldilocksCubicParameters {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::math::field::tests::test_field_arithmetic;
     use crate::math::prelude::*;
 
+    test_field_arithmetic!(gf3, GF3, 3);
+
     #[test]
     fn test_gf3_add() {
         let num_tests = 100;
@@ -75,6 +78,15 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_gf3_frobenius() {
+        let p = GoldilocksField::order();
+        let a = GF3::rand();
+        assert_eq!(a.frobenius(), a.pow(p));
+        assert_eq!(a.repeated_frobenius(2), a.pow(p).pow(p));
+        assert_eq!(a.repeated_frobenius(3), a);
+    }
+
     #[test]
     fn test_gf3_inverse() {
         let num_tests = 100;
@@ -0,0 +1,108 @@
+//! Fused multiply-add operations on Goldilocks elements.
+//!
+//! A product of two elements is a 128-bit value that is reduced modulo `p`. Adding the
+//! accumulator to the unreduced product saves the reduction of the sum, so the ubiquitous
+//! `a * b + c` of constraint evaluation costs a single reduction. When one factor fits in 32 bits,
+//! the value has at most 96 bits and the cheaper 96-bit reduction is used.
+
+use plonky2::field::goldilocks_field::GoldilocksField;
+
+/// `2^64 mod p`.
+const EPSILON: u64 = 0xFFFF_FFFF;
+
+/// Fused `a * b + c` operations, reduced once.
+pub trait MultiplyAccumulate: Sized {
+    /// Computes `a * b + c`.
+    fn multiply_accumulate(a: Self, b: Self, c: Self) -> Self;
+
+    /// Computes `a * b + c` for a factor `b` of 32 bits.
+    fn multiply_accumulate_u32(a: Self, b: u32, c: Self) -> Self;
+}
+
+/// The element `x_lo + 2^64 * x_hi`, for a value of at most 96 bits.
+///
+/// The result is not necessarily canonical.
+#[inline]
+pub fn from_noncanonical_u96((x_lo, x_hi): (u64, u32)) -> GoldilocksField {
+    // `2^64 = EPSILON mod p`, and `x_hi * EPSILON < 2^64`.
+    let (sum, overflow) = x_lo.overflowing_add(x_hi as u64 * EPSILON);
+    // On overflow the sum has lost `2^64 = EPSILON`, and adding it back does not overflow since the
+    // sum is at most `2^64 - 2^33`.
+    GoldilocksField(sum.wrapping_add(EPSILON * overflow as u64))
+}
+
+/// The element `x`, for a value of at most 128 bits.
+///
+/// The result is not necessarily canonical.
+#[inline]
+pub fn from_noncanonical_u128(x: u128) -> GoldilocksField {
+    let x_lo = x as u64;
+    let x_hi = (x >> 64) as u64;
+    let x_hi_hi = x_hi >> 32;
+    let x_hi_lo = x_hi & EPSILON;
+
+    // `2^96 = -1 mod p`. On a borrow the difference has gained `2^64 = EPSILON`, which is
+    // subtracted back without underflow since the difference is then at least `2^64 - 2^32`.
+    let (t0, borrow) = x_lo.overflowing_sub(x_hi_hi);
+    let t0 = t0.wrapping_sub(EPSILON * borrow as u64);
+    let (sum, overflow) = t0.overflowing_add(x_hi_lo * EPSILON);
+    GoldilocksField(sum.wrapping_add(EPSILON * overflow as u64))
+}
+
+impl MultiplyAccumulate for GoldilocksField {
+    #[inline]
+    fn multiply_accumulate(a: Self, b: Self, c: Self) -> Self {
+        // `a * b + c < 2^128` for any representatives `a, b, c < 2^64`.
+        from_noncanonical_u128(a.0 as u128 * b.0 as u128 + c.0 as u128)
+    }
+
+    #[inline]
+    fn multiply_accumulate_u32(a: Self, b: u32, c: Self) -> Self {
+        // `a * b + c < 2^96` for any representatives `a, c < 2^64`.
+        let x = a.0 as u128 * b as u128 + c.0 as u128;
+        from_noncanonical_u96((x as u64, (x >> 64) as u32))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use plonky2::field::types::{Field, Sample};
+
+    use super::*;
+
+    type F = GoldilocksField;
+
+    const ORDER: u64 = 0xFFFF_FFFF_0000_0001;
+
+    #[test]
+    fn test_from_noncanonical() {
+        for (x_lo, x_hi) in [(0, 0), (u64::MAX, u32::MAX), (ORDER, 1), (5, u32::MAX)] {
+            let x = x_lo as u128 + ((x_hi as u128) << 64);
+            let expected = F::from_canonical_u64((x % ORDER as u128) as u64);
+            assert_eq!(from_noncanonical_u96((x_lo, x_hi)), expected);
+            assert_eq!(from_noncanonical_u128(x), expected);
+        }
+        for x in [u128::MAX, u128::MAX - ORDER as u128, 1 << 96, (1 << 96) - 1] {
+            let expected = F::from_canonical_u64((x % ORDER as u128) as u64);
+            assert_eq!(from_noncanonical_u128(x), expected);
+        }
+    }
+
+    #[test]
+    fn test_multiply_accumulate() {
+        let edge = [F::ZERO, F::ONE, F::NEG_ONE, F(EPSILON), F(u64::MAX)];
+        let elements = edge.into_iter().chain(F::rand_vec(20)).collect::<Vec<_>>();
+        for &a in elements.iter() {
+            for &b in elements.iter() {
+                for &c in elements.iter().take(8) {
+                    assert_eq!(F::multiply_accumulate(a, b, c), a * b + c);
+                }
+                let b_u32 = b.0 as u32;
+                assert_eq!(
+                    F::multiply_accumulate_u32(a, b_u32, b),
+                    a * F::from_canonical_u32(b_u32) + b
+                );
+            }
+        }
+    }
+}
@@ -1,4 +1,8 @@
 pub mod cubic;
+pub mod fused;
+pub mod montgomery;
+pub mod quintic;
+pub mod tower;
 
 // use plonky2::field::goldilocks_field::GoldilocksField;
 // use plonky2::field::types::PrimeField64 as PlonkyPrimeField64;
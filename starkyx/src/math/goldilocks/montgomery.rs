@@ -0,0 +1,198 @@
+//! Goldilocks elements in Montgomery form.
+//!
+//! An element `x` is represented by `x * 2^64 mod p`. The product of two representations is
+//! reduced by a Montgomery reduction, which for the Goldilocks prime only takes shifts and
+//! subtractions and is faster than `reduce128` on some CPUs. The conversions cost a reduction
+//! each, so this form only pays off in long chains of multiplications.
+//!
+//! The Montgomery product of a representation of `x` with an element `y` in canonical form is `x *
+//! y` in canonical form. Multiplying by a fixed element is therefore a single reduction, without
+//! converting the other factor, as in the evaluation of a polynomial at a fixed point.
+
+use core::ops::{Add, Mul, Neg, Sub};
+
+use plonky2::field::goldilocks_field::GoldilocksField;
+use plonky2::field::types::PrimeField64;
+
+/// The Goldilocks prime `p = 2^64 - 2^32 + 1`.
+const ORDER: u64 = 0xFFFF_FFFF_0000_0001;
+
+/// `2^64 mod p`.
+const EPSILON: u64 = 0xFFFF_FFFF;
+
+/// `2^128 mod p`, the Montgomery representation of `2^64`.
+const R2: u64 = 0xFFFF_FFFE_0000_0001;
+
+/// A Goldilocks element in Montgomery form.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct MontgomeryGoldilocks(u64);
+
+/// Computes `x * 2^{-64} mod p`.
+///
+/// The result is canonical for `x < p * 2^64`, in particular for the products of canonical values.
+#[inline]
+fn mont_red(x: u128) -> u64 {
+    let x_lo = x as u64;
+    let x_hi = (x >> 64) as u64;
+    // `x_lo * p^{-1} mod 2^64 = x_lo * (2^32 + 1) mod 2^64`, whose product with `p` is subtracted
+    // from `x` to clear its low 64 bits.
+    let (a, overflow) = x_lo.overflowing_add(x_lo << 32);
+    let b = a.wrapping_sub(a >> 32).wrapping_sub(overflow as u64);
+    let (r, underflow) = x_hi.overflowing_sub(b);
+    r.wrapping_sub(EPSILON * underflow as u64)
+}
+
+impl MontgomeryGoldilocks {
+    pub const ZERO: Self = Self(0);
+    pub const ONE: Self = Self(EPSILON);
+
+    pub fn from_goldilocks(x: GoldilocksField) -> Self {
+        Self(mont_red(x.to_canonical_u64() as u128 * R2 as u128))
+    }
+
+    pub fn to_goldilocks(self) -> GoldilocksField {
+        GoldilocksField(mont_red(self.0 as u128))
+    }
+
+    /// Multiplies `x` by `self`, with `x` and the product in canonical form.
+    #[inline]
+    pub fn mul_canonical(self, x: GoldilocksField) -> GoldilocksField {
+        GoldilocksField(mont_red(self.0 as u128 * x.to_canonical_u64() as u128))
+    }
+}
+
+impl From<GoldilocksField> for MontgomeryGoldilocks {
+    fn from(x: GoldilocksField) -> Self {
+        Self::from_goldilocks(x)
+    }
+}
+
+impl From<MontgomeryGoldilocks> for GoldilocksField {
+    fn from(x: MontgomeryGoldilocks) -> Self {
+        x.to_goldilocks()
+    }
+}
+
+impl Add for MontgomeryGoldilocks {
+    type Output = Self;
+
+    #[inline]
+    fn add(self, rhs: Self) -> Self {
+        let (sum, overflow) = self.0.overflowing_add(rhs.0);
+        // On overflow the sum has lost `2^64 = EPSILON`, and adding it back stays below `p`.
+        if overflow {
+            Self(sum + EPSILON)
+        } else if sum >= ORDER {
+            Self(sum - ORDER)
+        } else {
+            Self(sum)
+        }
+    }
+}
+
+impl Sub for MontgomeryGoldilocks {
+    type Output = Self;
+
+    #[inline]
+    fn sub(self, rhs: Self) -> Self {
+        let (diff, underflow) = self.0.overflowing_sub(rhs.0);
+        Self(diff.wrapping_sub(EPSILON * underflow as u64))
+    }
+}
+
+impl Neg for MontgomeryGoldilocks {
+    type Output = Self;
+
+    #[inline]
+    fn neg(self) -> Self {
+        Self::ZERO - self
+    }
+}
+
+impl Mul for MontgomeryGoldilocks {
+    type Output = Self;
+
+    #[inline]
+    fn mul(self, rhs: Self) -> Self {
+        Self(mont_red(self.0 as u128 * rhs.0 as u128))
+    }
+}
+
+/// Evaluates the polynomial with coefficients `coeffs` at the point `z` of the quadratic extension
+/// `F[X] / (X^2 - w)`, by Horner's rule.
+///
+/// The point is converted to Montgomery form once, after which every step of the chain costs four
+/// Montgomery reductions and no conversion.
+pub fn eval_quadratic(
+    coeffs: &[GoldilocksField],
+    z: [GoldilocksField; 2],
+    w: GoldilocksField,
+) -> [GoldilocksField; 2] {
+    let z_0 = MontgomeryGoldilocks::from(z[0]);
+    let z_1 = MontgomeryGoldilocks::from(z[1]);
+    let w_z_1 = MontgomeryGoldilocks::from(w) * z_1;
+
+    let mut acc = [GoldilocksField(0); 2];
+    for &c in coeffs.iter().rev() {
+        // `(a_0 + a_1 X) (z_0 + z_1 X) = (a_0 z_0 + w a_1 z_1) + (a_0 z_1 + a_1 z_0) X`.
+        acc = [
+            z_0.mul_canonical(acc[0]) + w_z_1.mul_canonical(acc[1]) + c,
+            z_1.mul_canonical(acc[0]) + z_0.mul_canonical(acc[1]),
+        ];
+    }
+    acc
+}
+
+#[cfg(test)]
+mod tests {
+    use plonky2::field::extension::quadratic::QuadraticExtension;
+    use plonky2::field::extension::{Extendable, FieldExtension};
+    use plonky2::field::polynomial::PolynomialCoeffs;
+    use plonky2::field::types::{Field, Sample};
+
+    use super::*;
+
+    type F = GoldilocksField;
+
+    #[test]
+    fn test_montgomery_arithmetic() {
+        let edge = [F::ZERO, F::ONE, F::NEG_ONE, F(EPSILON), F(ORDER - EPSILON)];
+        let elements = edge.into_iter().chain(F::rand_vec(100)).collect::<Vec<_>>();
+
+        assert_eq!(MontgomeryGoldilocks::ONE.to_goldilocks(), F::ONE);
+        for &x in elements.iter() {
+            let x_mont = MontgomeryGoldilocks::from(x);
+            assert_eq!(x_mont.to_goldilocks(), x);
+            assert_eq!((-x_mont).to_goldilocks(), -x);
+            for &y in elements.iter().take(20) {
+                let y_mont = MontgomeryGoldilocks::from(y);
+                assert_eq!((x_mont + y_mont).to_goldilocks(), x + y);
+                assert_eq!((x_mont - y_mont).to_goldilocks(), x - y);
+                assert_eq!((x_mont * y_mont).to_goldilocks(), x * y);
+                assert_eq!(x_mont.mul_canonical(y), x * y);
+            }
+        }
+    }
+
+    #[test]
+    fn test_noncanonical_input() {
+        let x = F(ORDER + 5);
+        assert_eq!(MontgomeryGoldilocks::from(x).to_goldilocks(), F(5));
+        assert_eq!(MontgomeryGoldilocks::ONE.mul_canonical(x), F(5));
+    }
+
+    #[test]
+    fn test_eval_quadratic() {
+        type E = QuadraticExtension<F>;
+
+        for len in [0, 1, 2, 17, 64] {
+            let coeffs = F::rand_vec(len);
+            let z = E::rand();
+            let expected = PolynomialCoeffs::new(coeffs.clone())
+                .to_extension::<2>()
+                .eval(z);
+            let value = eval_quadratic(&coeffs, z.to_basefield_array(), <F as Extendable<2>>::W);
+            assert_eq!(E::from_basefield_array(value), expected);
+        }
+    }
+}
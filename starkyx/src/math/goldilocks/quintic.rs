@@ -0,0 +1,118 @@
+use plonky2::field::goldilocks_field::GoldilocksField;
+use serde::{Deserialize, Serialize};
+
+use crate::math::extension::quintic::extension::QuinticExtension;
+use crate::math::extension::quintic::parameters::QuinticParameters;
+
+pub type GF5 = QuinticExtension<GoldilocksField, GoldilocksQuinticParameters>;
+
+/// Parameters for the quintic Goldilocks extension field F[X]/(X^5 - 3).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GoldilocksQuinticParameters;
+
+impl QuinticParameters<GoldilocksField> for GoldilocksQuinticParameters {
+    const W: GoldilocksField = GoldilocksField(3);
+
+    const DTH_ROOT: GoldilocksField = GoldilocksField(1041288259238279555);
+
+    /// The generator `X + 2`.
+    const EXT_MULTIPLICATIVE_GROUP_GENERATOR: [GoldilocksField; 5] = [
+        GoldilocksField(2),
+        GoldilocksField(1),
+        GoldilocksField(0),
+        GoldilocksField(0),
+        GoldilocksField(0),
+    ];
+}
+
+#[cfg(test)]
+mod tests {
+    use num::BigUint;
+
+    use super::*;
+    use crate::math::field::tests::{field_test, test_field_arithmetic};
+    use crate::math::prelude::*;
+
+    type F = GoldilocksField;
+
+    test_field_arithmetic!(gf5, GF5, 5);
+
+    #[test]
+    fn test_gf5_field() {
+        for _ in 0..100 {
+            field_test::<GF5>();
+        }
+    }
+
+    #[test]
+    fn test_gf5_parameters() {
+        let p = F::order();
+        let w = GoldilocksQuinticParameters::W;
+        let dth_root = GoldilocksQuinticParameters::DTH_ROOT;
+        assert_eq!(w.pow((p - 1) / 5), dth_root);
+        // `X^5 - W` is irreducible since `W` is not a fifth power.
+        assert_ne!(dth_root, F::ONE);
+        assert_eq!(dth_root.pow(5), F::ONE);
+
+        let x = GF5::new([F::ZERO, F::ONE, F::ZERO, F::ZERO, F::ZERO]);
+        assert_eq!(x.pow(5), GF5::from_base_field(w));
+    }
+
+    #[test]
+    fn test_gf5_frobenius() {
+        let p = F::order();
+        let a = GF5::rand();
+        assert_eq!(a.repeated_frobenius(1), a.pow(p));
+        assert_eq!(a.repeated_frobenius(2), a.pow(p).pow(p));
+        assert_eq!(a.repeated_frobenius(5), a);
+    }
+
+    #[test]
+    fn test_gf5_generator() {
+        let p = BigUint::from(F::order());
+        let order = p.pow(5) - 1u32;
+        let factors = [
+            "2",
+            "3",
+            "5",
+            "17",
+            "257",
+            "65537",
+            "45971",
+            "255006435240067831",
+            "280083648770327405561",
+            "7053197395277272939628824863222181",
+        ];
+        let g = GF5::GENERATOR;
+        assert_eq!(g.pow_biguint(&order), GF5::ONE);
+        for q in factors {
+            let q = q.parse::<BigUint>().unwrap();
+            assert_ne!(g.pow_biguint(&(&order / q)), GF5::ONE);
+        }
+    }
+
+    #[test]
+    fn test_gf5_conversions() {
+        let a = F::rand();
+        let a_ext = GF5::from(a);
+        assert_eq!(a_ext.try_to_base(), Some(a));
+        assert_eq!(GF5::GENERATOR.try_to_base(), None);
+
+        let b = GF5::rand();
+        let limbs = b.to_base_u64_limbs();
+        assert_eq!(limbs.len(), 5);
+        assert_eq!(GF5::try_from_base_u64_limbs(&limbs), Some(b));
+        assert_eq!(GF5::try_from_base_u64_limbs(&limbs[1..]), None);
+        let mut unreduced = limbs;
+        unreduced[2] = u64::MAX;
+        assert_eq!(GF5::try_from_base_u64_limbs(&unreduced), None);
+    }
+
+    #[test]
+    fn test_gf5_serde() {
+        let a = GF5::rand();
+        let bytes = bincode::serialize(&a).unwrap();
+        let b: GF5 = bincode::deserialize(&bytes).unwrap();
+        assert_eq!(a, b);
+    }
+}
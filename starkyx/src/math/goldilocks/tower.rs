@@ -0,0 +1,151 @@
+//! Towers of quadratic extensions of the Goldilocks field and its cubic extension.
+//!
+//! The extensions of degree 2, 4 and 8 are obtained by adjoining successive square roots:
+//! `U^2 = 7`, `V^2 = U` and `W^2 = V`. The extension of degree 6 is the quadratic extension
+//! `U^2 = 7` of the cubic extension [`GF3`].
+
+use plonky2::field::goldilocks_field::GoldilocksField;
+use serde::{Deserialize, Serialize};
+
+use super::cubic::GF3;
+use crate::math::extension::quadratic::extension::QuadraticExtension;
+use crate::math::extension::quadratic::parameters::QuadraticParameters;
+
+pub type GF2 = QuadraticExtension<GoldilocksField, GoldilocksQuadraticParameters>;
+pub type GF4 = QuadraticExtension<GF2, GoldilocksQuarticParameters>;
+pub type GF8 = QuadraticExtension<GF4, GoldilocksOcticParameters>;
+pub type GF6 = QuadraticExtension<GF3, GoldilocksSexticParameters>;
+
+/// Parameters for the quadratic Goldilocks extension field F[U]/(U^2 - 7).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GoldilocksQuadraticParameters;
+
+impl QuadraticParameters<GoldilocksField> for GoldilocksQuadraticParameters {
+    const NONRESIDUE: GoldilocksField = GoldilocksField(7);
+
+    /// `7^((p - 1) / 2) = -1`.
+    const FROBENIUS_COEFF: GoldilocksField = GoldilocksField(0xFFFF_FFFF_0000_0000);
+}
+
+/// Parameters for the quartic Goldilocks extension field GF2[V]/(V^2 - U).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GoldilocksQuarticParameters;
+
+impl QuadraticParameters<GF2> for GoldilocksQuarticParameters {
+    const NONRESIDUE: GF2 = GF2::U;
+
+    /// `U^((p - 1) / 2) = 7^((p - 1) / 4) = 2^48`, a primitive fourth root of unity.
+    const FROBENIUS_COEFF: GF2 = GF2::from_base_field(GoldilocksField(1 << 48));
+}
+
+/// Parameters for the octic Goldilocks extension field GF4[W]/(W^2 - V).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GoldilocksOcticParameters;
+
+impl QuadraticParameters<GF4> for GoldilocksOcticParameters {
+    const NONRESIDUE: GF4 = GF4::U;
+
+    /// `V^((p - 1) / 2) = 7^((p - 1) / 8)`, a primitive eighth root of unity.
+    const FROBENIUS_COEFF: GF4 =
+        GF4::from_base_field(GF2::from_base_field(GoldilocksField(18446744069397807105)));
+}
+
+/// Parameters for the sextic Goldilocks extension field GF3[U]/(U^2 - 7).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GoldilocksSexticParameters;
+
+impl QuadraticParameters<GF3> for GoldilocksSexticParameters {
+    /// Since the degree of `GF3` is odd, `7` is not a square in `GF3` either.
+    const NONRESIDUE: GF3 = GF3::from_base_field(GoldilocksField(7));
+
+    /// `7^((p - 1) / 2) = -1`.
+    const FROBENIUS_COEFF: GF3 = GF3::from_base_field(GoldilocksField(0xFFFF_FFFF_0000_0000));
+}
+
+#[cfg(test)]
+mod tests {
+    use num::BigUint;
+
+    use super::*;
+    use crate::math::field::tests::{field_test, test_field_arithmetic};
+    use crate::math::prelude::*;
+
+    type F = GoldilocksField;
+
+    test_field_arithmetic!(gf2, GF2, 2);
+    test_field_arithmetic!(gf4, GF4, 4);
+    test_field_arithmetic!(gf8, GF8, 8);
+    test_field_arithmetic!(gf6, GF6, 6);
+
+    fn frobenius_test<E: Frobenius + Sample>(degree: usize) {
+        let p = F::order();
+        let a = E::rand();
+        assert_eq!(a.frobenius(), a.pow(p));
+        assert_eq!(a.repeated_frobenius(2), a.pow(p).pow(p));
+        assert_eq!(a.repeated_frobenius(degree), a);
+        // A random element does not lie in the subfield of half the degree.
+        assert_ne!(a.repeated_frobenius(degree / 2), a);
+    }
+
+    /// Checks that `NONRESIDUE` is not a square in a base field with `p^degree` elements, and the
+    /// value of `FROBENIUS_COEFF`.
+    fn parameters_test<B: Field, P: QuadraticParameters<B>>(degree: u32) {
+        let p = F::order();
+        let order = BigUint::from(p).pow(degree);
+        let euler = P::NONRESIDUE.pow_biguint(&((order - 1u32) / 2u32));
+        assert_eq!(euler, -B::ONE);
+        assert_eq!(P::NONRESIDUE.pow((p - 1) / 2), P::FROBENIUS_COEFF);
+    }
+
+    #[test]
+    fn test_tower_fields() {
+        for _ in 0..100 {
+            field_test::<GF2>();
+            field_test::<GF4>();
+            field_test::<GF8>();
+            field_test::<GF6>();
+        }
+    }
+
+    #[test]
+    fn test_tower_parameters() {
+        parameters_test::<F, GoldilocksQuadraticParameters>(1);
+        parameters_test::<GF2, GoldilocksQuarticParameters>(2);
+        parameters_test::<GF4, GoldilocksOcticParameters>(4);
+        parameters_test::<GF3, GoldilocksSexticParameters>(3);
+
+        let w = GF8::U;
+        let v = GF4::U;
+        assert_eq!(w.square(), GF8::from_base_field(v));
+        assert_eq!(
+            w.pow(8),
+            GF8::from_base_field(GF4::from_base_field(GF2::from_base_field(
+                F::from_canonical_u8(7)
+            )))
+        );
+    }
+
+    #[test]
+    fn test_tower_frobenius() {
+        for _ in 0..10 {
+            frobenius_test::<GF2>(2);
+            frobenius_test::<GF4>(4);
+            frobenius_test::<GF8>(8);
+            frobenius_test::<GF6>(6);
+        }
+    }
+
+    #[test]
+    fn test_tower_serde() {
+        let a = GF8::rand();
+        let bytes = bincode::serialize(&a).unwrap();
+        assert_eq!(bytes.len(), 8 * 8);
+        let b: GF8 = bincode::deserialize(&bytes).unwrap();
+        assert_eq!(a, b);
+
+        let c = GF6::rand();
+        let bytes = bincode::serialize(&c).unwrap();
+        let d: GF6 = bincode::deserialize(&bytes).unwrap();
+        assert_eq!(c, d);
+    }
+}
@@ -0,0 +1,355 @@
+//! The circle group over the Mersenne-31 field and the circle FFT.
+//!
+//! The points `(x, y)` with `x^2 + y^2 = 1` form a cyclic group of order `p + 1 = 2^31`, with the
+//! group law `(x_0, y_0) * (x_1, y_1) = (x_0 x_1 - y_0 y_1, x_0 y_1 + x_1 y_0)`. Its subgroups
+//! of order `2^n` play the role of the two-adic subgroups of a smooth field.
+//!
+//! Polynomials are evaluated on a standard position coset `D_n = {g^{2i + 1}}`, where `g` is a
+//! generator of the subgroup of order `2^{n + 1}`. The circle FFT interpolates evaluations on
+//! `D_n` in the basis
+//!
+//! `b_j(x, y) = y^{j_0} x^{j_1} π(x)^{j_2} π^2(x)^{j_3} ...`,
+//!
+//! where `j_k` is the `k`-th bit of `j` and `π(x) = 2x^2 - 1` is the x-coordinate of the doubling
+//! map. The basis does not depend on the size of the domain, so a low-degree extension is the
+//! evaluation of the coefficients on a larger domain.
+
+use alloc::vec;
+use alloc::vec::Vec;
+use core::ops::Mul;
+
+use serde::{Deserialize, Serialize};
+
+use super::Mersenne31Field;
+use crate::math::prelude::*;
+
+/// The base two logarithm of the order of the circle group over the Mersenne-31 field.
+pub const CIRCLE_TWO_ADICITY: usize = 31;
+
+/// A point of the circle `x^2 + y^2 = 1`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(bound = "")]
+pub struct CirclePoint<F: Field> {
+    pub x: F,
+    pub y: F,
+}
+
+impl<F: Field> CirclePoint<F> {
+    /// The identity of the circle group.
+    pub const IDENTITY: Self = Self {
+        x: F::ONE,
+        y: F::ZERO,
+    };
+
+    pub const fn new(x: F, y: F) -> Self {
+        Self { x, y }
+    }
+
+    /// Returns `true` if the point lies on the circle.
+    pub fn is_on_circle(&self) -> bool {
+        self.x.square() + self.y.square() == F::ONE
+    }
+
+    /// The inverse of the point, which is its conjugate `(x, -y)`.
+    pub fn inverse(&self) -> Self {
+        Self {
+            x: self.x,
+            y: -self.y,
+        }
+    }
+
+    /// The square of the point, whose x-coordinate is `π(x) = 2x^2 - 1`.
+    pub fn square(&self) -> Self {
+        *self * *self
+    }
+
+    /// Raises the point to the power of `power`.
+    pub fn pow(&self, power: u64) -> Self {
+        let mut current = *self;
+        let mut product = Self::IDENTITY;
+        let n_bits = 64 - power.leading_zeros();
+        for j in 0..n_bits {
+            if (power >> j & 1) != 0 {
+                product = product * current;
+            }
+            current = current.square();
+        }
+        product
+    }
+
+    /// Evaluates the basis polynomials `b_j` at the point for `j < 2^log_n`.
+    pub fn basis(&self, log_n: usize) -> Vec<F> {
+        let mut basis = vec![F::ONE];
+        if log_n == 0 {
+            return basis;
+        }
+        basis.push(self.y);
+        let mut x = self.x;
+        for _ in 1..log_n {
+            let len = basis.len();
+            for j in 0..len {
+                basis.push(basis[j] * x);
+            }
+            x = double_x(x);
+        }
+        basis
+    }
+
+    /// Evaluates the polynomial with coefficients `coefficients` in the circle basis at the point.
+    pub fn evaluate(&self, coefficients: &[F]) -> F {
+        assert!(coefficients.len().is_power_of_two());
+        let log_n = coefficients.len().trailing_zeros() as usize;
+        self.basis(log_n)
+            .into_iter()
+            .zip(coefficients)
+            .map(|(b, &c)| b * c)
+            .sum()
+    }
+}
+
+/// The group law of the circle.
+impl<F: Field> Mul for CirclePoint<F> {
+    type Output = Self;
+
+    fn mul(self, rhs: Self) -> Self {
+        Self {
+            x: self.x * rhs.x - self.y * rhs.y,
+            y: self.x * rhs.y + rhs.x * self.y,
+        }
+    }
+}
+
+/// The x-coordinate `π(x) = 2x^2 - 1` of the square of a point with x-coordinate `x`.
+#[inline]
+fn double_x<F: Field>(x: F) -> F {
+    let x_squared = x.square();
+    x_squared + x_squared - F::ONE
+}
+
+impl CirclePoint<Mersenne31Field> {
+    /// A generator of the whole circle group.
+    pub const GENERATOR: Self = Self {
+        x: Mersenne31Field::new(2),
+        y: Mersenne31Field::new(1268011823),
+    };
+
+    /// A generator of the subgroup of order `2^log_order`.
+    pub fn generator(log_order: usize) -> Self {
+        assert!(log_order <= CIRCLE_TWO_ADICITY);
+        Self::GENERATOR.pow(1 << (CIRCLE_TWO_ADICITY - log_order))
+    }
+}
+
+/// The standard position coset `D_n` of size `2^n`.
+///
+/// The `i`-th point of the domain is `g^{2i + 1}`, where `g` generates the subgroup of order
+/// `2^{n + 1}`, so that the conjugate of the `i`-th point is the `(2^n - 1 - i)`-th point.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CircleDomain {
+    log_n: usize,
+}
+
+impl CircleDomain {
+    pub fn new(log_n: usize) -> Self {
+        assert!(log_n < CIRCLE_TWO_ADICITY, "The domain is too large");
+        Self { log_n }
+    }
+
+    pub fn log_n(&self) -> usize {
+        self.log_n
+    }
+
+    pub fn size(&self) -> usize {
+        1 << self.log_n
+    }
+
+    /// The points of the domain, in order.
+    pub fn points(&self) -> Vec<CirclePoint<Mersenne31Field>> {
+        Self::coset_points(self.log_n, self.size())
+    }
+
+    /// The first `len` points `g^{2i + 1}` of the standard position coset of size `2^log_n`.
+    fn coset_points(log_n: usize, len: usize) -> Vec<CirclePoint<Mersenne31Field>> {
+        let g = CirclePoint::generator(log_n + 1);
+        let step = g.square();
+        let mut point = g;
+        let mut points = Vec::with_capacity(len);
+        for _ in 0..len {
+            points.push(point);
+            point = point * step;
+        }
+        points
+    }
+
+    /// Interpolates `values` on the domain, returning the coefficients in the circle basis.
+    pub fn interpolate(&self, values: &[Mersenne31Field]) -> Vec<Mersenne31Field> {
+        assert_eq!(values.len(), self.size());
+        if self.log_n == 0 {
+            return values.to_vec();
+        }
+        let n = self.size();
+        let half = n / 2;
+        let two_inv = Mersenne31Field::TWO.inverse();
+        let points = Self::coset_points(self.log_n, half);
+
+        // Split `f(x, y) = f_0(x) + y f_1(x)` using the pairs of conjugate points.
+        let ys = points.iter().map(|p| p.y).collect::<Vec<_>>();
+        let y_invs = Mersenne31Field::batch_multiplicative_inverse(&ys);
+        let mut even = Vec::with_capacity(half);
+        let mut odd = Vec::with_capacity(half);
+        for (i, y_inv) in y_invs.into_iter().enumerate() {
+            let (v, v_conj) = (values[i], values[n - 1 - i]);
+            even.push((v + v_conj) * two_inv);
+            odd.push((v - v_conj) * two_inv * y_inv);
+        }
+
+        let xs = points.iter().map(|p| p.x).collect::<Vec<_>>();
+        let even = Self::interpolate_line(&xs, even);
+        let odd = Self::interpolate_line(&xs, odd);
+        even.into_iter()
+            .zip(odd)
+            .flat_map(|(e, o)| [e, o])
+            .collect()
+    }
+
+    /// Evaluates the polynomial with coefficients `coefficients` in the circle basis on the domain.
+    ///
+    /// There can be fewer coefficients than points, in which case the evaluations are a
+    /// low-degree extension of the polynomial.
+    pub fn evaluate(&self, coefficients: &[Mersenne31Field]) -> Vec<Mersenne31Field> {
+        assert!(coefficients.len() <= self.size());
+        let mut coefficients = coefficients.to_vec();
+        coefficients.resize(self.size(), Mersenne31Field::ZERO);
+        if self.log_n == 0 {
+            return coefficients;
+        }
+        let n = self.size();
+        let half = n / 2;
+        let points = Self::coset_points(self.log_n, half);
+        let xs = points.iter().map(|p| p.x).collect::<Vec<_>>();
+
+        let even = coefficients.iter().step_by(2).copied().collect();
+        let odd = coefficients.iter().skip(1).step_by(2).copied().collect();
+        let even = Self::evaluate_line(&xs, even);
+        let odd = Self::evaluate_line(&xs, odd);
+
+        let mut values = vec![Mersenne31Field::ZERO; n];
+        for (i, point) in points.iter().enumerate() {
+            values[i] = even[i] + point.y * odd[i];
+            values[n - 1 - i] = even[i] - point.y * odd[i];
+        }
+        values
+    }
+
+    /// Interpolates `values` on the x-coordinates `xs`, which satisfy `xs[len - 1 - i] = -xs[i]`.
+    ///
+    /// The coefficients are in the basis `x^{j_0} π(x)^{j_1} ...`.
+    fn interpolate_line(
+        xs: &[Mersenne31Field],
+        values: Vec<Mersenne31Field>,
+    ) -> Vec<Mersenne31Field> {
+        let n = values.len();
+        if n == 1 {
+            return values;
+        }
+        let half = n / 2;
+        let two_inv = Mersenne31Field::TWO.inverse();
+
+        let x_invs = Mersenne31Field::batch_multiplicative_inverse(&xs[..half]);
+        let mut even = Vec::with_capacity(half);
+        let mut odd = Vec::with_capacity(half);
+        for (i, x_inv) in x_invs.into_iter().enumerate() {
+            let (v, v_neg) = (values[i], values[n - 1 - i]);
+            even.push((v + v_neg) * two_inv);
+            odd.push((v - v_neg) * two_inv * x_inv);
+        }
+
+        let folded_xs = Self::fold_line(xs);
+        let even = Self::interpolate_line(&folded_xs, even);
+        let odd = Self::interpolate_line(&folded_xs, odd);
+        even.into_iter()
+            .zip(odd)
+            .flat_map(|(e, o)| [e, o])
+            .collect()
+    }
+
+    /// Evaluates the polynomial with coefficients `coefficients` on the x-coordinates `xs`.
+    fn evaluate_line(
+        xs: &[Mersenne31Field],
+        coefficients: Vec<Mersenne31Field>,
+    ) -> Vec<Mersenne31Field> {
+        let n = coefficients.len();
+        if n == 1 {
+            return coefficients;
+        }
+        let half = n / 2;
+        let folded_xs = Self::fold_line(xs);
+
+        let even = coefficients.iter().step_by(2).copied().collect();
+        let odd = coefficients.iter().skip(1).step_by(2).copied().collect();
+        let even = Self::evaluate_line(&folded_xs, even);
+        let odd = Self::evaluate_line(&folded_xs, odd);
+
+        let mut values = vec![Mersenne31Field::ZERO; n];
+        for (i, &x) in xs.iter().take(half).enumerate() {
+            values[i] = even[i] + x * odd[i];
+            values[n - 1 - i] = even[i] - x * odd[i];
+        }
+        values
+    }
+
+    /// The images `π(x)` of the first half of `xs`, which are the x-coordinates of the next layer.
+    fn fold_line(xs: &[Mersenne31Field]) -> Vec<Mersenne31Field> {
+        xs.iter().take(xs.len() / 2).map(|&x| double_x(x)).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    type F = Mersenne31Field;
+
+    #[test]
+    fn test_circle_group() {
+        let g = CirclePoint::GENERATOR;
+        assert!(g.is_on_circle());
+        assert_eq!(g.pow(1 << 30), CirclePoint::new(F::NEG_ONE, F::ZERO));
+        assert_eq!(g.pow(1 << 31), CirclePoint::IDENTITY);
+
+        for log_order in 0..8 {
+            let h = CirclePoint::generator(log_order);
+            assert!(h.is_on_circle());
+            assert_eq!(h.pow(1 << log_order), CirclePoint::IDENTITY);
+            assert_eq!(h * h.inverse(), CirclePoint::IDENTITY);
+        }
+    }
+
+    #[test]
+    fn test_circle_fft() {
+        for log_n in 0..8 {
+            let domain = CircleDomain::new(log_n);
+            let values = F::rand_vec(domain.size());
+
+            let coefficients = domain.interpolate(&values);
+            assert_eq!(domain.evaluate(&coefficients), values);
+
+            // The coefficients agree with a direct evaluation of the basis.
+            for (point, &value) in domain.points().iter().zip(values.iter()) {
+                assert_eq!(point.evaluate(&coefficients), value);
+            }
+
+            // A low-degree extension agrees with the polynomial on the larger domain.
+            let extended_domain = CircleDomain::new(log_n + 2);
+            let extension = extended_domain.evaluate(&coefficients);
+            let point = extended_domain.points()[1];
+            assert_eq!(extension[1], point.evaluate(&coefficients));
+            let mut extended_coefficients = extended_domain.interpolate(&extension);
+            assert!(extended_coefficients[domain.size()..]
+                .iter()
+                .all(|c| *c == F::ZERO));
+            extended_coefficients.truncate(domain.size());
+            assert_eq!(extended_coefficients, coefficients);
+        }
+    }
+}
@@ -0,0 +1,322 @@
+//! The Mersenne-31 prime field.
+//!
+//! The multiplicative group of this field has a two-adicity of one, so it has no large smooth
+//! subgroups on which to run a standard FFT. Instead, low-degree extensions are computed over
+//! subgroups of the circle `x^2 + y^2 = 1`, which has order `2^31` over this field. See the
+//! [`circle`] module for the circle domains and the circle FFT.
+//!
+//! The field implements the traits of [`crate::math`], and can be used anywhere a generic field
+//! is expected. The plonky2 prover and FRI still require a two-adic `RichField`, so proofs are not
+//! yet generated over this field. Proving over circle domains still needs:
+//!
+//! - an abstraction of the trace, quotient and LDE domains of the prover, which currently uses
+//!   the two-adic subgroups and cosets of plonky2,
+//! - a circle FRI, opening the committed polynomials at points of the circle,
+//! - an extension of this field from which the challenges are sampled.
+
+pub mod circle;
+
+use alloc::vec::Vec;
+use core::fmt::{self, Display, Formatter};
+use core::iter::{Product, Sum};
+use core::ops::{Add, AddAssign, Div, DivAssign, Mul, MulAssign, Neg, Sub, SubAssign};
+
+use num::BigUint;
+use rand::Rng;
+use serde::{Deserialize, Deserializer, Serialize};
+
+use crate::math::prelude::*;
+
+/// The prime `2^31 - 1`.
+pub const MERSENNE31_PRIME: u32 = (1 << 31) - 1;
+
+/// An element of the field of order `2^31 - 1`, stored in canonical form.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize)]
+pub struct Mersenne31Field(u32);
+
+impl Mersenne31Field {
+    pub const ZERO: Self = Self(0);
+    pub const ONE: Self = Self(1);
+    pub const TWO: Self = Self(2);
+    pub const NEG_ONE: Self = Self(MERSENNE31_PRIME - 1);
+
+    /// A generator of the multiplicative group.
+    pub const MULTIPLICATIVE_GENERATOR: Self = Self(7);
+
+    /// A new element from a value which must be less than `2^31 - 1`.
+    #[inline]
+    pub const fn new(value: u32) -> Self {
+        assert!(value < MERSENNE31_PRIME);
+        Self(value)
+    }
+
+    /// Reduces any `u64` modulo `2^31 - 1`, using `2^31 = 1`.
+    #[inline]
+    const fn reduce_u64(value: u64) -> u32 {
+        let p = MERSENNE31_PRIME as u64;
+        let folded = (value & p) + (value >> 31);
+        let folded = (folded & p) + (folded >> 31);
+        if folded >= p {
+            (folded - p) as u32
+        } else {
+            folded as u32
+        }
+    }
+}
+
+impl Display for Mersenne31Field {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        Display::fmt(&self.0, f)
+    }
+}
+
+impl<'de> Deserialize<'de> for Mersenne31Field {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let value = u32::deserialize(deserializer)?;
+        if value >= MERSENNE31_PRIME {
+            return Err(serde::de::Error::custom("The value is not reduced"));
+        }
+        Ok(Self(value))
+    }
+}
+
+impl Add for Mersenne31Field {
+    type Output = Self;
+
+    #[inline]
+    fn add(self, rhs: Self) -> Self {
+        let sum = self.0 + rhs.0;
+        if sum >= MERSENNE31_PRIME {
+            Self(sum - MERSENNE31_PRIME)
+        } else {
+            Self(sum)
+        }
+    }
+}
+
+impl AddAssign for Mersenne31Field {
+    #[inline]
+    fn add_assign(&mut self, rhs: Self) {
+        *self = *self + rhs;
+    }
+}
+
+impl Sub for Mersenne31Field {
+    type Output = Self;
+
+    #[inline]
+    fn sub(self, rhs: Self) -> Self {
+        if self.0 >= rhs.0 {
+            Self(self.0 - rhs.0)
+        } else {
+            Self(self.0 + MERSENNE31_PRIME - rhs.0)
+        }
+    }
+}
+
+impl SubAssign for Mersenne31Field {
+    #[inline]
+    fn sub_assign(&mut self, rhs: Self) {
+        *self = *self - rhs;
+    }
+}
+
+impl Neg for Mersenne31Field {
+    type Output = Self;
+
+    #[inline]
+    fn neg(self) -> Self {
+        Self::ZERO - self
+    }
+}
+
+impl Mul for Mersenne31Field {
+    type Output = Self;
+
+    #[inline]
+    fn mul(self, rhs: Self) -> Self {
+        Self(Self::reduce_u64(self.0 as u64 * rhs.0 as u64))
+    }
+}
+
+impl MulAssign for Mersenne31Field {
+    #[inline]
+    fn mul_assign(&mut self, rhs: Self) {
+        *self = *self * rhs;
+    }
+}
+
+impl Div for Mersenne31Field {
+    type Output = Self;
+
+    #[allow(clippy::suspicious_arithmetic_impl)]
+    fn div(self, rhs: Self) -> Self {
+        self * rhs.inverse()
+    }
+}
+
+impl DivAssign for Mersenne31Field {
+    fn div_assign(&mut self, rhs: Self) {
+        *self = *self / rhs;
+    }
+}
+
+impl Sum for Mersenne31Field {
+    fn sum<I: Iterator<Item = Self>>(iter: I) -> Self {
+        iter.fold(Self::ZERO, |acc, x| acc + x)
+    }
+}
+
+impl Product for Mersenne31Field {
+    fn product<I: Iterator<Item = Self>>(iter: I) -> Self {
+        iter.fold(Self::ONE, |acc, x| acc * x)
+    }
+}
+
+impl Ring for Mersenne31Field {
+    const ONE: Self = Self::ONE;
+    const ZERO: Self = Self::ZERO;
+}
+
+impl Field for Mersenne31Field {
+    fn try_inverse(&self) -> Option<Self> {
+        if self.is_zero() {
+            return None;
+        }
+        Some(self.pow(MERSENNE31_PRIME as u64 - 2))
+    }
+
+    fn from_canonical_u8(n: u8) -> Self {
+        Self(n as u32)
+    }
+    fn from_canonical_u16(n: u16) -> Self {
+        Self(n as u32)
+    }
+    fn from_canonical_u32(n: u32) -> Self {
+        debug_assert!(n < MERSENNE31_PRIME);
+        Self(n)
+    }
+    fn from_canonical_u64(n: u64) -> Self {
+        debug_assert!(n < MERSENNE31_PRIME as u64);
+        Self(n as u32)
+    }
+    fn from_canonical_usize(n: usize) -> Self {
+        Self::from_canonical_u64(n as u64)
+    }
+
+    fn from_noncanonical_biguint(n: BigUint) -> Self {
+        let reduced = n % BigUint::from(MERSENNE31_PRIME);
+        Self(reduced.to_u32_digits().first().copied().unwrap_or(0))
+    }
+
+    /// The multiplicative group has a two-adicity of one, so only roots of unity of order one and
+    /// two exist.
+    fn primitive_root_of_unity(n_log: usize) -> Self {
+        match n_log {
+            0 => Self::ONE,
+            1 => Self::NEG_ONE,
+            _ => panic!("The Mersenne-31 field has no roots of unity of order 2^{n_log}"),
+        }
+    }
+
+    fn two_adic_subgroup(n_log: usize) -> Vec<Self> {
+        Self::primitive_root_of_unity(n_log)
+            .powers()
+            .take(1 << n_log)
+            .collect()
+    }
+}
+
+impl PrimeField for Mersenne31Field {
+    fn modulus() -> BigUint {
+        BigUint::from(MERSENNE31_PRIME)
+    }
+
+    fn to_biguint(&self) -> BigUint {
+        BigUint::from(self.0)
+    }
+}
+
+impl PrimeField32 for Mersenne31Field {
+    fn as_canonical_u32(&self) -> u32 {
+        self.0
+    }
+}
+
+impl PrimeField64 for Mersenne31Field {
+    fn as_canonical_u64(&self) -> u64 {
+        self.0 as u64
+    }
+}
+
+impl Sample for Mersenne31Field {
+    fn sample<R: Rng + ?Sized>(rng: &mut R) -> Self {
+        Self(rng.gen_range(0..MERSENNE31_PRIME))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::math::field::tests::{field_test, prime_field_test, test_field_arithmetic};
+
+    type F = Mersenne31Field;
+
+    test_field_arithmetic!(mersenne31, F);
+
+    #[test]
+    fn test_mersenne31_field() {
+        for _ in 0..100 {
+            field_test::<F>();
+            prime_field_test::<F>();
+        }
+    }
+
+    #[test]
+    fn test_mersenne31_serialization() {
+        let a = F::rand();
+        let bytes = bincode::serialize(&a).unwrap();
+        assert_eq!(
+            bytes,
+            bincode::serialize(&(a.as_canonical_u64() as u32)).unwrap()
+        );
+        let b: F = bincode::deserialize(&bytes).unwrap();
+        assert_eq!(a, b);
+
+        let unreduced = bincode::serialize(&MERSENNE31_PRIME).unwrap();
+        assert!(bincode::deserialize::<F>(&unreduced).is_err());
+        let unreduced = bincode::serialize(&u32::MAX).unwrap();
+        assert!(bincode::deserialize::<F>(&unreduced).is_err());
+    }
+
+    #[test]
+    fn test_mersenne31_reduction() {
+        let p = MERSENNE31_PRIME as u64;
+        let a = F::rand();
+        let b = F::rand();
+        assert_eq!(
+            (a * b).as_canonical_u64(),
+            a.as_canonical_u64() * b.as_canonical_u64() % p
+        );
+        assert_eq!(F::NEG_ONE * F::NEG_ONE, F::ONE);
+        assert_eq!(F::new(MERSENNE31_PRIME - 1) + F::ONE, F::ZERO);
+        assert_eq!(F::ZERO - F::ONE, F::NEG_ONE);
+        assert_eq!(F::order(), p);
+        assert_eq!(
+            F::from_noncanonical_biguint(BigUint::from(u64::MAX)).as_canonical_u64(),
+            u64::MAX % p
+        );
+        assert_eq!(F::try_inverse(&F::ZERO), None);
+    }
+
+    #[test]
+    fn test_mersenne31_generator() {
+        let g = F::MULTIPLICATIVE_GENERATOR;
+        let order = MERSENNE31_PRIME as u64 - 1;
+        assert_eq!(g.pow(order), F::ONE);
+        for q in [2, 3, 7, 11, 31, 151, 331] {
+            assert_ne!(g.pow(order / q), F::ONE);
+        }
+        assert_eq!(F::two_adic_subgroup(1), vec![F::ONE, F::NEG_ONE]);
+    }
+}
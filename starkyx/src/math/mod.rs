@@ -1,7 +1,12 @@
 pub mod algebra;
+pub mod bigint;
 pub mod extension;
 pub mod field;
+pub mod fixed_base;
 pub mod goldilocks;
+pub mod mersenne31;
+pub mod monty31;
+pub mod strict;
 
 pub mod prelude {
     pub use super::algebra::*;
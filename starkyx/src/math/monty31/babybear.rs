@@ -0,0 +1,44 @@
+//! The BabyBear prime field, of order `2^31 - 2^27 + 1`.
+
+use super::{MontyField31, MontyParameters, MontyQuadratic};
+
+pub type BabyBearField = MontyField31<BabyBearParameters>;
+
+/// The quadratic extension of the BabyBear field.
+pub type BabyBearQuadratic = MontyQuadratic<BabyBearParameters>;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct BabyBearParameters;
+
+impl MontyParameters for BabyBearParameters {
+    const PRIME: u32 = 2013265921;
+
+    const MULTIPLICATIVE_GENERATOR: u32 = 31;
+
+    const QUADRATIC_NONRESIDUE: u32 = 11;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::math::field::tests::{field_test, prime_field_test, test_field_arithmetic};
+    use crate::math::monty31::tests::{monty_arithmetic_test, monty_parameters_test};
+
+    test_field_arithmetic!(babybear, BabyBearField);
+    test_field_arithmetic!(babybear_quadratic, BabyBearQuadratic, 2);
+
+    #[test]
+    fn test_babybear_field() {
+        for _ in 0..100 {
+            field_test::<BabyBearField>();
+            prime_field_test::<BabyBearField>();
+            field_test::<BabyBearQuadratic>();
+        }
+    }
+
+    #[test]
+    fn test_babybear_parameters() {
+        monty_parameters_test::<BabyBearParameters>();
+        monty_arithmetic_test::<BabyBearParameters>();
+    }
+}
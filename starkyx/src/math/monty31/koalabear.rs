@@ -0,0 +1,44 @@
+//! The KoalaBear prime field, of order `2^31 - 2^24 + 1`.
+
+use super::{MontyField31, MontyParameters, MontyQuadratic};
+
+pub type KoalaBearField = MontyField31<KoalaBearParameters>;
+
+/// The quadratic extension of the KoalaBear field.
+pub type KoalaBearQuadratic = MontyQuadratic<KoalaBearParameters>;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct KoalaBearParameters;
+
+impl MontyParameters for KoalaBearParameters {
+    const PRIME: u32 = 2130706433;
+
+    const MULTIPLICATIVE_GENERATOR: u32 = 3;
+
+    const QUADRATIC_NONRESIDUE: u32 = 3;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::math::field::tests::{field_test, prime_field_test, test_field_arithmetic};
+    use crate::math::monty31::tests::{monty_arithmetic_test, monty_parameters_test};
+
+    test_field_arithmetic!(koalabear, KoalaBearField);
+    test_field_arithmetic!(koalabear_quadratic, KoalaBearQuadratic, 2);
+
+    #[test]
+    fn test_koalabear_field() {
+        for _ in 0..100 {
+            field_test::<KoalaBearField>();
+            prime_field_test::<KoalaBearField>();
+            field_test::<KoalaBearQuadratic>();
+        }
+    }
+
+    #[test]
+    fn test_koalabear_parameters() {
+        monty_parameters_test::<KoalaBearParameters>();
+        monty_arithmetic_test::<KoalaBearParameters>();
+    }
+}
@@ -0,0 +1,413 @@
+//! Prime fields of order less than `2^31`, with elements in Montgomery form.
+//!
+//! A field is instantiated from a few constants given by [`MontyParameters`]: the prime, a
+//! generator of the multiplicative group and a quadratic non-residue. Everything else, including
+//! the Montgomery constants, the two-adicity, the roots of unity and the quadratic extension
+//! [`MontyQuadratic`], is derived from them.
+//!
+//! The Mersenne-31 prime is not handled by this template, since its special form admits a faster
+//! reduction than Montgomery's. See [`crate::math::mersenne31`].
+
+pub mod babybear;
+pub mod koalabear;
+
+use alloc::vec::Vec;
+use core::fmt::{self, Debug, Display, Formatter};
+use core::hash::Hash;
+use core::iter::{Product, Sum};
+use core::marker::PhantomData;
+use core::ops::{Add, AddAssign, Div, DivAssign, Mul, MulAssign, Neg, Sub, SubAssign};
+
+use num::BigUint;
+use rand::Rng;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+use crate::math::extension::quadratic::extension::QuadraticExtension;
+use crate::math::extension::quadratic::parameters::QuadraticParameters;
+use crate::math::prelude::*;
+
+/// The constants defining a prime field of order less than `2^31`.
+pub trait MontyParameters:
+    'static + Sized + Copy + Clone + Send + Sync + PartialEq + Eq + Hash + Debug
+{
+    /// The prime `p < 2^31`.
+    const PRIME: u32;
+
+    /// A generator of the multiplicative group, in canonical form.
+    const MULTIPLICATIVE_GENERATOR: u32;
+
+    /// A quadratic non-residue, in canonical form, which defines the quadratic extension.
+    const QUADRATIC_NONRESIDUE: u32;
+}
+
+/// An element of the field defined by `MP`, stored in Montgomery form `x * 2^32 mod p`.
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+pub struct MontyField31<MP: MontyParameters>(u32, PhantomData<MP>);
+
+/// The quadratic extension `F[U]/(U^2 - QUADRATIC_NONRESIDUE)` of a field [`MontyField31`].
+pub type MontyQuadratic<MP> = QuadraticExtension<MontyField31<MP>, MontyQuadraticParameters<MP>>;
+
+impl<MP: MontyParameters> MontyField31<MP> {
+    /// `p^{-1} mod 2^32`, computed by Newton's iteration, which doubles the number of correct bits
+    /// at each step starting from the three bits of `p^{-1} = p mod 8`.
+    const MONTY_MU: u32 = {
+        let mut inv = MP::PRIME;
+        let mut i = 0;
+        while i < 4 {
+            inv = inv.wrapping_mul(2u32.wrapping_sub(MP::PRIME.wrapping_mul(inv)));
+            i += 1;
+        }
+        inv
+    };
+
+    /// The largest `k` such that `2^k` divides `p - 1`.
+    pub const TWO_ADICITY: usize = (MP::PRIME - 1).trailing_zeros() as usize;
+
+    pub const ZERO: Self = Self::new(0);
+    pub const ONE: Self = Self::new(1);
+    pub const TWO: Self = Self::new(2);
+    pub const NEG_ONE: Self = Self::new(MP::PRIME - 1);
+
+    /// A generator of the multiplicative group.
+    pub const MULTIPLICATIVE_GENERATOR: Self = Self::new(MP::MULTIPLICATIVE_GENERATOR);
+
+    /// A new element from a value which must be less than `p`.
+    #[inline]
+    pub const fn new(value: u32) -> Self {
+        assert!(value < MP::PRIME);
+        Self(
+            (((value as u64) << 32) % MP::PRIME as u64) as u32,
+            PhantomData,
+        )
+    }
+
+    /// Computes `x * 2^{-32} mod p`, for `x < p * 2^32`.
+    #[inline]
+    const fn monty_reduce(x: u64) -> u32 {
+        // `t * p = x mod 2^32`, so the low half of `x - t * p` vanishes.
+        let t = (x as u32).wrapping_mul(Self::MONTY_MU) as u64 * MP::PRIME as u64;
+        let (diff, underflow) = x.overflowing_sub(t);
+        let hi = (diff >> 32) as u32;
+        if underflow {
+            hi.wrapping_add(MP::PRIME)
+        } else {
+            hi
+        }
+    }
+
+    /// The canonical representative of `self` in `[0, p)`.
+    #[inline]
+    pub const fn as_canonical(&self) -> u32 {
+        Self::monty_reduce(self.0 as u64)
+    }
+}
+
+impl<MP: MontyParameters> Default for MontyField31<MP> {
+    fn default() -> Self {
+        Self::ZERO
+    }
+}
+
+impl<MP: MontyParameters> Debug for MontyField31<MP> {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        Debug::fmt(&self.as_canonical(), f)
+    }
+}
+
+impl<MP: MontyParameters> Display for MontyField31<MP> {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        Display::fmt(&self.as_canonical(), f)
+    }
+}
+
+/// Elements are serialized in canonical form.
+impl<MP: MontyParameters> Serialize for MontyField31<MP> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.as_canonical().serialize(serializer)
+    }
+}
+
+impl<'de, MP: MontyParameters> Deserialize<'de> for MontyField31<MP> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let value = u32::deserialize(deserializer)?;
+        if value >= MP::PRIME {
+            return Err(serde::de::Error::custom("The value is not reduced"));
+        }
+        Ok(Self::new(value))
+    }
+}
+
+impl<MP: MontyParameters> Add for MontyField31<MP> {
+    type Output = Self;
+
+    #[inline]
+    fn add(self, rhs: Self) -> Self {
+        let sum = self.0 + rhs.0;
+        if sum >= MP::PRIME {
+            Self(sum - MP::PRIME, PhantomData)
+        } else {
+            Self(sum, PhantomData)
+        }
+    }
+}
+
+impl<MP: MontyParameters> AddAssign for MontyField31<MP> {
+    #[inline]
+    fn add_assign(&mut self, rhs: Self) {
+        *self = *self + rhs;
+    }
+}
+
+impl<MP: MontyParameters> Sub for MontyField31<MP> {
+    type Output = Self;
+
+    #[inline]
+    fn sub(self, rhs: Self) -> Self {
+        let (diff, underflow) = self.0.overflowing_sub(rhs.0);
+        if underflow {
+            Self(diff.wrapping_add(MP::PRIME), PhantomData)
+        } else {
+            Self(diff, PhantomData)
+        }
+    }
+}
+
+impl<MP: MontyParameters> SubAssign for MontyField31<MP> {
+    #[inline]
+    fn sub_assign(&mut self, rhs: Self) {
+        *self = *self - rhs;
+    }
+}
+
+impl<MP: MontyParameters> Neg for MontyField31<MP> {
+    type Output = Self;
+
+    #[inline]
+    fn neg(self) -> Self {
+        Self::ZERO - self
+    }
+}
+
+impl<MP: MontyParameters> Mul for MontyField31<MP> {
+    type Output = Self;
+
+    #[inline]
+    fn mul(self, rhs: Self) -> Self {
+        Self(
+            Self::monty_reduce(self.0 as u64 * rhs.0 as u64),
+            PhantomData,
+        )
+    }
+}
+
+impl<MP: MontyParameters> MulAssign for MontyField31<MP> {
+    #[inline]
+    fn mul_assign(&mut self, rhs: Self) {
+        *self = *self * rhs;
+    }
+}
+
+impl<MP: MontyParameters> Div for MontyField31<MP> {
+    type Output = Self;
+
+    #[allow(clippy::suspicious_arithmetic_impl)]
+    fn div(self, rhs: Self) -> Self {
+        self * rhs.inverse()
+    }
+}
+
+impl<MP: MontyParameters> DivAssign for MontyField31<MP> {
+    fn div_assign(&mut self, rhs: Self) {
+        *self = *self / rhs;
+    }
+}
+
+impl<MP: MontyParameters> Sum for MontyField31<MP> {
+    fn sum<I: Iterator<Item = Self>>(iter: I) -> Self {
+        iter.fold(Self::ZERO, |acc, x| acc + x)
+    }
+}
+
+impl<MP: MontyParameters> Product for MontyField31<MP> {
+    fn product<I: Iterator<Item = Self>>(iter: I) -> Self {
+        iter.fold(Self::ONE, |acc, x| acc * x)
+    }
+}
+
+impl<MP: MontyParameters> Ring for MontyField31<MP> {
+    const ONE: Self = Self::ONE;
+    const ZERO: Self = Self::ZERO;
+}
+
+impl<MP: MontyParameters> Field for MontyField31<MP> {
+    fn try_inverse(&self) -> Option<Self> {
+        if self.is_zero() {
+            return None;
+        }
+        Some(self.pow(MP::PRIME as u64 - 2))
+    }
+
+    fn from_canonical_u8(n: u8) -> Self {
+        Self::new(n as u32)
+    }
+    fn from_canonical_u16(n: u16) -> Self {
+        Self::new(n as u32)
+    }
+    fn from_canonical_u32(n: u32) -> Self {
+        Self::new(n)
+    }
+    fn from_canonical_u64(n: u64) -> Self {
+        debug_assert!(n < MP::PRIME as u64);
+        Self::new(n as u32)
+    }
+    fn from_canonical_usize(n: usize) -> Self {
+        Self::from_canonical_u64(n as u64)
+    }
+
+    fn from_noncanonical_biguint(n: BigUint) -> Self {
+        let reduced = n % BigUint::from(MP::PRIME);
+        Self::new(reduced.to_u32_digits().first().copied().unwrap_or(0))
+    }
+
+    fn primitive_root_of_unity(n_log: usize) -> Self {
+        assert!(
+            n_log <= Self::TWO_ADICITY,
+            "The field has no roots of unity of order 2^{n_log}"
+        );
+        Self::MULTIPLICATIVE_GENERATOR.pow(((MP::PRIME - 1) >> n_log) as u64)
+    }
+
+    fn two_adic_subgroup(n_log: usize) -> Vec<Self> {
+        Self::primitive_root_of_unity(n_log)
+            .powers()
+            .take(1 << n_log)
+            .collect()
+    }
+}
+
+impl<MP: MontyParameters> PrimeField for MontyField31<MP> {
+    fn modulus() -> BigUint {
+        BigUint::from(MP::PRIME)
+    }
+
+    fn to_biguint(&self) -> BigUint {
+        BigUint::from(self.as_canonical())
+    }
+}
+
+impl<MP: MontyParameters> PrimeField32 for MontyField31<MP> {
+    fn as_canonical_u32(&self) -> u32 {
+        self.as_canonical()
+    }
+}
+
+impl<MP: MontyParameters> PrimeField64 for MontyField31<MP> {
+    fn as_canonical_u64(&self) -> u64 {
+        self.as_canonical() as u64
+    }
+}
+
+impl<MP: MontyParameters> Sample for MontyField31<MP> {
+    fn sample<R: Rng + ?Sized>(rng: &mut R) -> Self {
+        Self::new(rng.gen_range(0..MP::PRIME))
+    }
+}
+
+/// The parameters of the quadratic extension [`MontyQuadratic`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(bound = "")]
+pub struct MontyQuadraticParameters<MP: MontyParameters>(PhantomData<MP>);
+
+impl<MP: MontyParameters> QuadraticParameters<MontyField31<MP>> for MontyQuadraticParameters<MP> {
+    const NONRESIDUE: MontyField31<MP> = MontyField31::new(MP::QUADRATIC_NONRESIDUE);
+
+    /// The Euler criterion `NONRESIDUE^((p - 1) / 2) = -1` for a non-residue.
+    const FROBENIUS_COEFF: MontyField31<MP> = MontyField31::NEG_ONE;
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use num::BigUint;
+
+    use super::*;
+
+    /// The prime factors of `n`, by trial division.
+    fn prime_factors(mut n: u32) -> Vec<u32> {
+        let mut factors = Vec::new();
+        let mut q = 2;
+        while q * q <= n {
+            if n % q == 0 {
+                factors.push(q);
+                while n % q == 0 {
+                    n /= q;
+                }
+            }
+            q += 1;
+        }
+        if n > 1 {
+            factors.push(n);
+        }
+        factors
+    }
+
+    /// Checks the constants of `MP` and the values derived from them.
+    pub fn monty_parameters_test<MP: MontyParameters>() {
+        let p = MP::PRIME;
+        assert!(p < 1 << 31);
+        assert!(prime_factors(p) == [p], "The modulus {p} is not prime");
+        assert_eq!(p.wrapping_mul(MontyField31::<MP>::MONTY_MU), 1);
+        assert_eq!(MontyField31::<MP>::ONE.as_canonical(), 1);
+        assert_eq!(MontyField31::<MP>::NEG_ONE.as_canonical(), p - 1);
+
+        // The generator has order exactly `p - 1`.
+        let g = MontyField31::<MP>::MULTIPLICATIVE_GENERATOR;
+        let order = (p - 1) as u64;
+        assert_eq!(g.pow(order), MontyField31::<MP>::ONE);
+        for q in prime_factors(p - 1) {
+            assert_ne!(g.pow(order / q as u64), MontyField31::<MP>::ONE);
+        }
+
+        // The roots of unity have the expected order.
+        let two_adicity = MontyField31::<MP>::TWO_ADICITY;
+        let root = MontyField31::<MP>::primitive_root_of_unity(two_adicity);
+        assert_eq!(root.two_pow(two_adicity), MontyField31::<MP>::ONE);
+        assert_eq!(root.two_pow(two_adicity - 1), MontyField31::<MP>::NEG_ONE);
+
+        // The non-residue is the smallest one, and defines the quadratic extension.
+        let nonresidue = MontyField31::<MP>::new(MP::QUADRATIC_NONRESIDUE);
+        assert_eq!(nonresidue.legendre(), -1);
+        assert!((2..MP::QUADRATIC_NONRESIDUE).all(|a| MontyField31::<MP>::new(a).legendre() == 1));
+        let u = MontyQuadratic::<MP>::U;
+        assert_eq!(
+            u.square(),
+            MontyQuadratic::<MP>::from_base_field(nonresidue)
+        );
+        let a = MontyQuadratic::<MP>::rand();
+        assert_eq!(a.frobenius(), a.pow_biguint(&BigUint::from(p)));
+    }
+
+    /// Checks the arithmetic against the reduction of the products of canonical representatives.
+    pub fn monty_arithmetic_test<MP: MontyParameters>() {
+        let p = MP::PRIME as u64;
+        let edge = [0, 1, 2, MP::PRIME - 2, MP::PRIME - 1].map(MontyField31::<MP>::new);
+        let elements = edge
+            .into_iter()
+            .chain(MontyField31::<MP>::rand_vec(20))
+            .collect::<Vec<_>>();
+        for &a in elements.iter() {
+            for &b in elements.iter() {
+                let (x, y) = (a.as_canonical_u64(), b.as_canonical_u64());
+                assert_eq!((a + b).as_canonical_u64(), (x + y) % p);
+                assert_eq!((a - b).as_canonical_u64(), (x + p - y) % p);
+                assert_eq!((a * b).as_canonical_u64(), x * y % p);
+            }
+        }
+
+        let a = MontyField31::<MP>::rand();
+        let bytes = bincode::serialize(&a).unwrap();
+        assert_eq!(bytes, a.as_canonical().to_le_bytes());
+        assert_eq!(bincode::deserialize::<MontyField31<MP>>(&bytes).unwrap(), a);
+        let unreduced = bincode::serialize(&MP::PRIME).unwrap();
+        assert!(bincode::deserialize::<MontyField31<MP>>(&unreduced).is_err());
+    }
+}
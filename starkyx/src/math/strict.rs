@@ -0,0 +1,368 @@
+//! Fields whose values are checked to be canonical.
+//!
+//! Some field implementations store values which are not reduced, such as the Goldilocks field,
+//! whose elements may be any `u64` and are only reduced on comparison. Arithmetic is oblivious to
+//! this, but hashing raw values, serializing them or converting them to integers is not, and a
+//! non-canonical value there is a subtle bug which only shows up on rare inputs.
+//!
+//! [`StrictField`] wraps a field and keeps every value in canonical form. In builds with debug
+//! assertions, constructing it from a non-canonical value, hashing or serializing panics, and
+//! deserialization always rejects non-canonical values.
+
+use alloc::vec::Vec;
+use core::fmt::{self, Debug, Display, Formatter};
+use core::hash::{Hash, Hasher};
+use core::iter::{Product, Sum};
+use core::ops::{Add, AddAssign, Div, DivAssign, Mul, MulAssign, Neg, Sub, SubAssign};
+
+use num::BigUint;
+use plonky2::field::goldilocks_field::GoldilocksField;
+use rand::Rng;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+use super::bigint::{Fp256, Fp256Parameters};
+use super::mersenne31::Mersenne31Field;
+use super::monty31::{MontyField31, MontyParameters};
+use crate::math::prelude::*;
+
+/// A field whose stored representation of an element may not be the canonical one.
+pub trait CanonicalRepr: Field {
+    /// Whether the stored representation of `self` is the canonical one.
+    fn is_canonical(&self) -> bool;
+
+    /// The same element, stored in its canonical representation.
+    fn to_canonical(&self) -> Self;
+}
+
+impl CanonicalRepr for GoldilocksField {
+    #[inline]
+    fn is_canonical(&self) -> bool {
+        self.0 < 0xFFFF_FFFF_0000_0001
+    }
+
+    #[inline]
+    fn to_canonical(&self) -> Self {
+        GoldilocksField(self.as_canonical_u64())
+    }
+}
+
+/// Values are always reduced.
+impl CanonicalRepr for Mersenne31Field {
+    #[inline]
+    fn is_canonical(&self) -> bool {
+        true
+    }
+
+    #[inline]
+    fn to_canonical(&self) -> Self {
+        *self
+    }
+}
+
+/// Montgomery representations are always reduced.
+impl<MP: MontyParameters> CanonicalRepr for MontyField31<MP> {
+    #[inline]
+    fn is_canonical(&self) -> bool {
+        true
+    }
+
+    #[inline]
+    fn to_canonical(&self) -> Self {
+        *self
+    }
+}
+
+/// Montgomery representations are always reduced.
+impl<P: Fp256Parameters> CanonicalRepr for Fp256<P> {
+    #[inline]
+    fn is_canonical(&self) -> bool {
+        true
+    }
+
+    #[inline]
+    fn to_canonical(&self) -> Self {
+        *self
+    }
+}
+
+/// An element of `F` which is always stored in canonical form.
+#[derive(Clone, Copy, PartialEq, Eq, Default)]
+pub struct StrictField<F>(F);
+
+impl<F: CanonicalRepr> StrictField<F> {
+    /// Wraps `value`, which must be canonical.
+    ///
+    /// Panics on a non-canonical value if debug assertions are enabled. Use [`Self::reduce`] for
+    /// values which may legitimately not be reduced.
+    #[inline]
+    pub fn new(value: F) -> Self {
+        debug_assert!(value.is_canonical(), "Non-canonical value {value:?}");
+        Self(value)
+    }
+
+    /// Wraps the canonical representation of `value`.
+    #[inline]
+    pub fn reduce(value: F) -> Self {
+        Self(value.to_canonical())
+    }
+
+    /// The wrapped value, in canonical form.
+    #[inline]
+    pub fn into_inner(self) -> F {
+        self.0
+    }
+
+    #[inline]
+    fn check(&self) {
+        debug_assert!(self.0.is_canonical(), "Non-canonical value {:?}", self.0);
+    }
+}
+
+impl<F: CanonicalRepr> From<F> for StrictField<F> {
+    fn from(value: F) -> Self {
+        Self::new(value)
+    }
+}
+
+impl<F: Debug> Debug for StrictField<F> {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        Debug::fmt(&self.0, f)
+    }
+}
+
+impl<F: Display> Display for StrictField<F> {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        Display::fmt(&self.0, f)
+    }
+}
+
+impl<F: CanonicalRepr> Hash for StrictField<F> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.check();
+        self.0.hash(state);
+    }
+}
+
+impl<F: CanonicalRepr> Serialize for StrictField<F> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.check();
+        self.0.serialize(serializer)
+    }
+}
+
+/// Non-canonical values are rejected, whether debug assertions are enabled or not.
+impl<'de, F: CanonicalRepr> Deserialize<'de> for StrictField<F> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let value = F::deserialize(deserializer)?;
+        if !value.is_canonical() {
+            return Err(serde::de::Error::custom("The value is not canonical"));
+        }
+        Ok(Self(value))
+    }
+}
+
+impl<F: CanonicalRepr> Add for StrictField<F> {
+    type Output = Self;
+
+    #[inline]
+    fn add(self, rhs: Self) -> Self {
+        Self::reduce(self.0 + rhs.0)
+    }
+}
+
+impl<F: CanonicalRepr> AddAssign for StrictField<F> {
+    #[inline]
+    fn add_assign(&mut self, rhs: Self) {
+        *self = *self + rhs;
+    }
+}
+
+impl<F: CanonicalRepr> Sub for StrictField<F> {
+    type Output = Self;
+
+    #[inline]
+    fn sub(self, rhs: Self) -> Self {
+        Self::reduce(self.0 - rhs.0)
+    }
+}
+
+impl<F: CanonicalRepr> SubAssign for StrictField<F> {
+    #[inline]
+    fn sub_assign(&mut self, rhs: Self) {
+        *self = *self - rhs;
+    }
+}
+
+impl<F: CanonicalRepr> Neg for StrictField<F> {
+    type Output = Self;
+
+    #[inline]
+    fn neg(self) -> Self {
+        Self::reduce(-self.0)
+    }
+}
+
+impl<F: CanonicalRepr> Mul for StrictField<F> {
+    type Output = Self;
+
+    #[inline]
+    fn mul(self, rhs: Self) -> Self {
+        Self::reduce(self.0 * rhs.0)
+    }
+}
+
+impl<F: CanonicalRepr> MulAssign for StrictField<F> {
+    #[inline]
+    fn mul_assign(&mut self, rhs: Self) {
+        *self = *self * rhs;
+    }
+}
+
+impl<F: CanonicalRepr> Div for StrictField<F> {
+    type Output = Self;
+
+    #[inline]
+    fn div(self, rhs: Self) -> Self {
+        Self::reduce(self.0 / rhs.0)
+    }
+}
+
+impl<F: CanonicalRepr> DivAssign for StrictField<F> {
+    #[inline]
+    fn div_assign(&mut self, rhs: Self) {
+        *self = *self / rhs;
+    }
+}
+
+impl<F: CanonicalRepr> Sum for StrictField<F> {
+    fn sum<I: Iterator<Item = Self>>(iter: I) -> Self {
+        iter.fold(Self::ZERO, |acc, x| acc + x)
+    }
+}
+
+impl<F: CanonicalRepr> Product for StrictField<F> {
+    fn product<I: Iterator<Item = Self>>(iter: I) -> Self {
+        iter.fold(Self::ONE, |acc, x| acc * x)
+    }
+}
+
+/// The constants of the field are assumed to be canonical.
+impl<F: CanonicalRepr> Ring for StrictField<F> {
+    const ONE: Self = Self(F::ONE);
+    const ZERO: Self = Self(F::ZERO);
+}
+
+impl<F: CanonicalRepr> Field for StrictField<F> {
+    fn try_inverse(&self) -> Option<Self> {
+        self.0.try_inverse().map(Self::reduce)
+    }
+
+    fn from_canonical_u8(n: u8) -> Self {
+        Self::new(F::from_canonical_u8(n))
+    }
+    fn from_canonical_u16(n: u16) -> Self {
+        Self::new(F::from_canonical_u16(n))
+    }
+    fn from_canonical_u32(n: u32) -> Self {
+        Self::new(F::from_canonical_u32(n))
+    }
+    fn from_canonical_u64(n: u64) -> Self {
+        Self::new(F::from_canonical_u64(n))
+    }
+    fn from_canonical_usize(n: usize) -> Self {
+        Self::new(F::from_canonical_usize(n))
+    }
+
+    fn from_noncanonical_biguint(n: BigUint) -> Self {
+        Self::reduce(F::from_noncanonical_biguint(n))
+    }
+
+    fn primitive_root_of_unity(n_log: usize) -> Self {
+        Self::reduce(F::primitive_root_of_unity(n_log))
+    }
+
+    fn two_adic_subgroup(n_log: usize) -> Vec<Self> {
+        F::two_adic_subgroup(n_log)
+            .into_iter()
+            .map(Self::reduce)
+            .collect()
+    }
+}
+
+impl<F: CanonicalRepr + PrimeField> PrimeField for StrictField<F> {
+    fn modulus() -> BigUint {
+        F::modulus()
+    }
+
+    fn to_biguint(&self) -> BigUint {
+        self.check();
+        self.0.to_biguint()
+    }
+}
+
+impl<F: CanonicalRepr + PrimeField64> PrimeField64 for StrictField<F> {
+    fn as_canonical_u64(&self) -> u64 {
+        self.check();
+        self.0.as_canonical_u64()
+    }
+}
+
+impl<F: CanonicalRepr + Sample> Sample for StrictField<F> {
+    fn sample<R: Rng + ?Sized>(rng: &mut R) -> Self {
+        Self::reduce(F::sample(rng))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::math::field::tests::{field_test, prime_field_test, test_field_arithmetic};
+
+    type F = GoldilocksField;
+    type S = StrictField<GoldilocksField>;
+
+    const ORDER: u64 = 0xFFFF_FFFF_0000_0001;
+
+    test_field_arithmetic!(strict_goldilocks, S);
+
+    #[test]
+    fn test_strict_field() {
+        for _ in 0..100 {
+            field_test::<S>();
+            prime_field_test::<S>();
+        }
+    }
+
+    #[test]
+    fn test_strict_reduce() {
+        let x = F(ORDER + 5);
+        assert!(!x.is_canonical());
+        let x_strict = S::reduce(x);
+        assert_eq!(x_strict.into_inner().0, 5);
+
+        // Sums of canonical values may not be reduced in the underlying field.
+        let a = S::new(F(ORDER - 1));
+        let b = S::new(F(ORDER - 2));
+        assert!((a + b).into_inner().is_canonical());
+        assert!((a * b).into_inner().is_canonical());
+        assert!((-S::ONE).into_inner().is_canonical());
+    }
+
+    #[test]
+    fn test_strict_serde() {
+        let a = S::rand();
+        let bytes = bincode::serialize(&a).unwrap();
+        assert_eq!(bincode::deserialize::<S>(&bytes).unwrap(), a);
+
+        let unreduced = bincode::serialize(&F(ORDER + 1)).unwrap();
+        assert!(bincode::deserialize::<S>(&unreduced).is_err());
+        assert!(bincode::deserialize::<F>(&unreduced).is_ok());
+    }
+
+    #[test]
+    #[cfg(debug_assertions)]
+    #[should_panic(expected = "Non-canonical value")]
+    fn test_strict_noncanonical() {
+        S::new(F(ORDER + 1));
+    }
+}
@@ -0,0 +1,65 @@
+use core::fmt::Debug;
+
+use plonky2::field::extension::Extendable;
+use plonky2::hash::hash_types::RichField;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+
+use super::parser::global::{GlobalRecursiveStarkParser, GlobalStarkParser};
+use super::parser::{RecursiveStarkParser, StarkParser};
+use super::stark::config::CurtaConfig;
+use crate::air::RAir;
+
+/// an air that can generate constraints for the Starky proving system.
+pub trait StarkyAir<F: RichField + Extendable<D>, const D: usize>:
+    for<'a> RAir<StarkParser<'a, F, F::Extension, F::Extension, D, D>>
+    + for<'a> RAir<GlobalStarkParser<'a, F, F, F, D, 1>>
+    + 'static
+    + Debug
+    + Send
+    + Sync
+    + Serialize
+    + DeserializeOwned
+{
+}
+
+/// an air whose constraints the prover of configuration `C` evaluates on the packed field
+/// `C::Packing`.
+pub trait ProverAir<F: RichField + Extendable<D>, C: CurtaConfig<D, F = F>, const D: usize>:
+    StarkyAir<F, D> + for<'a> RAir<StarkParser<'a, F, F, C::Packing, D, 1>>
+{
+}
+
+/// an air that can be verified recursively inside a Plonky2 circuit.
+pub trait Plonky2Air<F: RichField + Extendable<D>, const D: usize>:
+    StarkyAir<F, D>
+    + for<'a> RAir<RecursiveStarkParser<'a, F, D>>
+    + for<'a> RAir<GlobalRecursiveStarkParser<'a, F, D>>
+{
+}
+
+impl<F: RichField + Extendable<D>, const D: usize, T> StarkyAir<F, D> for T where
+    T: for<'a> RAir<StarkParser<'a, F, F::Extension, F::Extension, D, D>>
+        + for<'a> RAir<GlobalStarkParser<'a, F, F, F, D, 1>>
+        + 'static
+        + Debug
+        + Send
+        + Sync
+        + Serialize
+        + DeserializeOwned
+{
+}
+
+impl<F: RichField + Extendable<D>, C: CurtaConfig<D, F = F>, const D: usize, T> ProverAir<F, C, D>
+    for T
+where
+    T: StarkyAir<F, D> + for<'a> RAir<StarkParser<'a, F, F, C::Packing, D, 1>>,
+{
+}
+
+impl<F: RichField + Extendable<D>, const D: usize, T> Plonky2Air<F, D> for T where
+    T: StarkyAir<F, D>
+        + for<'a> RAir<RecursiveStarkParser<'a, F, D>>
+        + for<'a> RAir<GlobalRecursiveStarkParser<'a, F, D>>
+{
+}
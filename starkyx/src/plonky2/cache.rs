@@ -0,0 +1,111 @@
+//! A bounded cache of precomputed tables.
+//!
+//! Tables such as the roots of unity of an FFT are built once per field and size, and shared by
+//! every later computation of that size. The cache keeps a bounded number of tables, and evicts
+//! the least recently used one when it is full. An evicted table remains valid for the callers
+//! still holding it.
+
+use core::any::{Any, TypeId};
+use std::sync::{Arc, Mutex};
+
+/// The maximal number of tables kept by `TABLES`.
+pub const MAX_CACHED_TABLES: usize = 32;
+
+/// The tables shared by the NTTs and the field arithmetic.
+pub static TABLES: TableCache = TableCache::new(MAX_CACHED_TABLES);
+
+type Entry = ((TypeId, usize), Arc<dyn Any + Send + Sync>);
+
+/// A cache of at most `capacity` tables, keyed by their type and a size.
+#[derive(Debug)]
+pub struct TableCache {
+    capacity: usize,
+    /// The entries, from the least to the most recently used.
+    entries: Mutex<Vec<Entry>>,
+}
+
+impl TableCache {
+    pub const fn new(capacity: usize) -> Self {
+        assert!(capacity > 0);
+        Self {
+            capacity,
+            entries: Mutex::new(Vec::new()),
+        }
+    }
+
+    /// The table of type `T` and size `size`, built with `build` if it is not in the cache.
+    pub fn get_or_insert_with<T, B>(&self, size: usize, build: B) -> Arc<T>
+    where
+        T: Any + Send + Sync,
+        B: FnOnce() -> T,
+    {
+        let key = (TypeId::of::<T>(), size);
+        if let Some(table) = self.get(key) {
+            return table.downcast().unwrap();
+        }
+        // The table is built outside of the lock, so a concurrent call may build it too, in which
+        // case the first table inserted is kept.
+        let table: Arc<dyn Any + Send + Sync> = Arc::new(build());
+        let mut entries = self.entries.lock().unwrap();
+        let table = match entries.iter().position(|(k, _)| *k == key) {
+            Some(i) => Self::touch(&mut entries, i),
+            None => {
+                if entries.len() == self.capacity {
+                    entries.remove(0);
+                }
+                entries.push((key, table.clone()));
+                table
+            }
+        };
+        table.downcast().unwrap()
+    }
+
+    /// The number of tables in the cache.
+    pub fn len(&self) -> usize {
+        self.entries.lock().unwrap().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    fn get(&self, key: (TypeId, usize)) -> Option<Arc<dyn Any + Send + Sync>> {
+        let mut entries = self.entries.lock().unwrap();
+        let i = entries.iter().position(|(k, _)| *k == key)?;
+        Some(Self::touch(&mut entries, i))
+    }
+
+    /// Moves the entry at `i` to the most recently used position and returns its table.
+    fn touch(entries: &mut Vec<Entry>, i: usize) -> Arc<dyn Any + Send + Sync> {
+        let entry = entries.remove(i);
+        let table = entry.1.clone();
+        entries.push(entry);
+        table
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_table_cache() {
+        let cache = TableCache::new(2);
+        let a = cache.get_or_insert_with(1, || vec![1u64]);
+        assert!(Arc::ptr_eq(&a, &cache.get_or_insert_with(1, || vec![0u64])));
+        // Tables of other types or sizes have their own entries.
+        let b = cache.get_or_insert_with(1, || vec![1u32]);
+        assert_eq!(*b, vec![1u32]);
+        assert_eq!(cache.len(), 2);
+
+        // The least recently used table is evicted, and remains valid for its holders.
+        let _ = cache.get_or_insert_with(1, || vec![1u64]);
+        let c = cache.get_or_insert_with(2, || vec![2u64]);
+        assert_eq!(cache.len(), 2);
+        assert_eq!(*c, vec![2u64]);
+        assert!(Arc::ptr_eq(&a, &cache.get_or_insert_with(1, || vec![0u64])));
+        let b_rebuilt = cache.get_or_insert_with(1, || vec![2u32]);
+        assert!(!Arc::ptr_eq(&b, &b_rebuilt));
+        assert_eq!(*b, vec![1u32]);
+    }
+}
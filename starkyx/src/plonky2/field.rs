@@ -1,10 +1,69 @@
+use alloc::vec::Vec;
+#[cfg(feature = "std")]
+use std::sync::Arc;
+
 use plonky2::field::types::{
     Field as Plonky2Field, PrimeField as Plonky2PrimeField, PrimeField64 as Plonky2PrimeField64,
     Sample as Plonky2Sample,
 };
 
+#[cfg(feature = "std")]
+use super::cache::TABLES;
+#[cfg(feature = "std")]
+use crate::math::fixed_base::FixedBaseTable;
 use crate::math::prelude::*;
 
+/// The number of bits of the windows of the generator tables.
+#[cfg(feature = "std")]
+const GENERATOR_WINDOW_BITS: usize = 8;
+
+/// The windowed power tables of the generators of a plonky2 field.
+#[cfg(feature = "std")]
+#[derive(Debug)]
+struct GeneratorTables<F> {
+    /// The powers of `MULTIPLICATIVE_GROUP_GENERATOR`.
+    generator: FixedBaseTable<F>,
+    /// The powers of `POWER_OF_TWO_GENERATOR`, whose order is `2^TWO_ADICITY`.
+    two_adic_generator: FixedBaseTable<F>,
+}
+
+/// The generator tables of `F`, built if they are not cached.
+#[cfg(feature = "std")]
+fn generator_tables<F: Plonky2Field>() -> Arc<GeneratorTables<F>> {
+    TABLES.get_or_insert_with(0, || GeneratorTables {
+        generator: FixedBaseTable::new(
+            F::MULTIPLICATIVE_GROUP_GENERATOR,
+            64,
+            GENERATOR_WINDOW_BITS,
+        ),
+        two_adic_generator: FixedBaseTable::new(
+            F::POWER_OF_TWO_GENERATOR,
+            F::TWO_ADICITY,
+            GENERATOR_WINDOW_BITS,
+        ),
+    })
+}
+
+/// Computes `F::MULTIPLICATIVE_GROUP_GENERATOR^exponent` from a precomputed table.
+#[cfg(feature = "std")]
+pub fn generator_pow<F: Plonky2Field>(exponent: u64) -> F {
+    generator_tables::<F>().generator.pow(exponent)
+}
+
+/// Computes `w^exponent` for the primitive root of unity `w` of order `2^n_log`, from a
+/// precomputed table.
+#[cfg(feature = "std")]
+pub fn root_of_unity_pow<F: Plonky2Field>(n_log: usize, exponent: u64) -> F {
+    assert!(
+        n_log <= F::TWO_ADICITY,
+        "The field has no roots of unity of order 2^{n_log}"
+    );
+    // `w = g^{2^{TWO_ADICITY - n_log}}`, and the powers of `g` are taken modulo its order.
+    let shift = F::TWO_ADICITY - n_log;
+    let exponent = (exponent << shift) & ((1 << F::TWO_ADICITY) - 1);
+    generator_tables::<F>().two_adic_generator.pow(exponent)
+}
+
 impl<F: Plonky2Field> Ring for F {
     const ONE: Self = F::ONE;
     const ZERO: Self = F::ZERO;
@@ -14,6 +73,9 @@ impl<F: Plonky2Field> Field for F {
     fn try_inverse(&self) -> Option<Self> {
         Some(self.inverse())
     }
+    fn batch_multiplicative_inverse(x: &[Self]) -> Vec<Self> {
+        <F as Plonky2Field>::batch_multiplicative_inverse(x)
+    }
     fn from_canonical_u8(n: u8) -> Self {
         F::from_canonical_u8(n)
     }
@@ -34,12 +96,21 @@ impl<F: Plonky2Field> Field for F {
         F::from_noncanonical_biguint(n)
     }
 
+    #[cfg(feature = "std")]
+    fn primitive_root_of_unity(n_log: usize) -> Self {
+        root_of_unity_pow(n_log, 1)
+    }
+
+    #[cfg(not(feature = "std"))]
     fn primitive_root_of_unity(n_log: usize) -> Self {
         F::primitive_root_of_unity(n_log)
     }
 
     fn two_adic_subgroup(n_log: usize) -> Vec<Self> {
-        F::two_adic_subgroup(n_log)
+        <Self as Field>::primitive_root_of_unity(n_log)
+            .powers()
+            .take(1 << n_log)
+            .collect()
     }
 }
 
@@ -52,10 +123,114 @@ impl<F: Plonky2Sample> Sample for F {
     }
 }
 
-impl<F: Plonky2PrimeField> PrimeField for F {}
+impl<F: Plonky2PrimeField> PrimeField for F {
+    fn modulus() -> num::BigUint {
+        <F as Plonky2Field>::order()
+    }
+
+    fn to_biguint(&self) -> num::BigUint {
+        self.to_canonical_biguint()
+    }
+}
 
 impl<F: Plonky2PrimeField64> PrimeField64 for F {
     fn as_canonical_u64(&self) -> u64 {
         self.to_canonical_u64()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use num::BigUint;
+    use plonky2::field::goldilocks_field::GoldilocksField;
+    use proptest::prelude::*;
+
+    use super::{generator_pow, root_of_unity_pow};
+    use crate::math::field::tests::{element, field_test, prime_field_test, test_field_arithmetic};
+    use crate::math::goldilocks::montgomery::MontgomeryGoldilocks;
+    use crate::math::prelude::*;
+
+    /// The Goldilocks prime.
+    const ORDER: u64 = 0xFFFF_FFFF_0000_0001;
+
+    test_field_arithmetic!(goldilocks, GoldilocksField);
+
+    proptest! {
+        /// The non-canonical representatives in `[ORDER, 2^64)` behave as their reductions.
+        #[test]
+        fn test_goldilocks_noncanonical(x in ORDER..=u64::MAX, b in element::<GoldilocksField>()) {
+            let a = GoldilocksField(x);
+            let a_canonical = GoldilocksField(x - ORDER);
+            assert_eq!(a, a_canonical);
+            assert_eq!(a.as_canonical_u64(), x - ORDER);
+            assert_eq!(a.to_biguint(), BigUint::from(x - ORDER));
+            assert_eq!(a + b, a_canonical + b);
+            assert_eq!(b + a, b + a_canonical);
+            assert_eq!(a - b, a_canonical - b);
+            assert_eq!(b - a, b - a_canonical);
+            assert_eq!(-a, -a_canonical);
+            assert_eq!(a * b, a_canonical * b);
+            assert_eq!(a.square(), a_canonical.square());
+            assert_eq!(a.try_inverse(), a_canonical.try_inverse());
+            assert_eq!(
+                MontgomeryGoldilocks::from(a),
+                MontgomeryGoldilocks::from(a_canonical)
+            );
+        }
+    }
+
+    #[test]
+    fn test_goldilocks_field() {
+        for _ in 0..100 {
+            field_test::<GoldilocksField>();
+            prime_field_test::<GoldilocksField>();
+        }
+    }
+
+    #[test]
+    fn test_goldilocks_from_uniform_bytes() {
+        let bytes = [0xffu8; 24];
+        assert_eq!(GoldilocksField::uniform_bytes_len(), 24);
+        let expected =
+            (BigUint::from_bytes_le(&bytes) % GoldilocksField::modulus()).to_u64_digits()[0];
+        assert_eq!(
+            GoldilocksField::from_uniform_bytes(&bytes),
+            GoldilocksField::from_canonical_u64(expected)
+        );
+    }
+
+    #[test]
+    fn test_goldilocks_generator_tables() {
+        use plonky2::field::types::Field as Plonky2Field;
+
+        type F = GoldilocksField;
+
+        for n_log in 0..=F::TWO_ADICITY {
+            let root = <F as Field>::primitive_root_of_unity(n_log);
+            assert_eq!(root, <F as Plonky2Field>::primitive_root_of_unity(n_log));
+            for exponent in [0, 1, 5, (1 << n_log) - 1, 1 << n_log, u64::MAX] {
+                assert_eq!(
+                    root_of_unity_pow::<F>(n_log, exponent),
+                    Field::pow(&root, exponent)
+                );
+            }
+        }
+        assert_eq!(
+            <F as Field>::two_adic_subgroup(4),
+            <F as Plonky2Field>::two_adic_subgroup(4)
+        );
+
+        for exponent in [0, 1, 7, 1 << 40, u64::MAX] {
+            assert_eq!(
+                generator_pow::<F>(exponent),
+                Field::pow(&F::MULTIPLICATIVE_GROUP_GENERATOR, exponent)
+            );
+        }
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_goldilocks_from_short_bytes() {
+        GoldilocksField::from_uniform_bytes(&[0u8; 8]);
+    }
+}
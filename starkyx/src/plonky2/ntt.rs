@@ -0,0 +1,119 @@
+//! Radix-2 number-theoretic transforms with cached twiddle tables.
+//!
+//! The FFTs of plonky2 compute the table of roots of unity of the domain on every call unless a
+//! table is given. A prover commits to many batches of polynomials of the same degree, so the
+//! tables are built once per field and size, on first use, and kept in the bounded cache of
+//! [`super::cache`] for the later transforms of that size.
+
+use std::sync::Arc;
+
+use plonky2::field::fft::{fft_root_table, fft_with_options, ifft_with_options, FftRootTable};
+use plonky2::field::polynomial::{PolynomialCoeffs, PolynomialValues};
+use plonky2::field::types::Field;
+use plonky2::util::log2_strict;
+
+use super::cache::TABLES;
+
+/// The table of roots of unity for transforms of size `2^lg_n`, built if it is not cached.
+pub fn root_table<F: Field>(lg_n: usize) -> Arc<FftRootTable<F>> {
+    TABLES.get_or_insert_with(lg_n, || fft_root_table::<F>(1 << lg_n))
+}
+
+/// The evaluations of `coeffs` on the subgroup of order `coeffs.len()`.
+pub fn ntt<F: Field>(coeffs: PolynomialCoeffs<F>) -> PolynomialValues<F> {
+    let table = root_table::<F>(log2_strict(coeffs.len()));
+    fft_with_options(coeffs, None, Some(&table))
+}
+
+/// The coefficients of the polynomial with evaluations `values` on the subgroup of order
+/// `values.len()`.
+pub fn intt<F: Field>(values: PolynomialValues<F>) -> PolynomialCoeffs<F> {
+    let table = root_table::<F>(log2_strict(values.len()));
+    ifft_with_options(values, None, Some(&table))
+}
+
+/// The coefficients of the polynomial with evaluations `values` on the coset `shift * H` of the
+/// subgroup `H` of order `values.len()`.
+pub fn coset_intt<F: Field>(values: PolynomialValues<F>, shift: F) -> PolynomialCoeffs<F> {
+    let mut coeffs = intt(values);
+    for (c, s) in coeffs.coeffs.iter_mut().zip(shift.inverse().powers()) {
+        *c *= s;
+    }
+    coeffs
+}
+
+/// The low-degree extension of `coeffs` by a factor of `2^rate_bits`, evaluated on the coset
+/// `shift * H` of the subgroup `H` of order `coeffs.len() << rate_bits`.
+pub fn coset_lde<F: Field>(
+    coeffs: &PolynomialCoeffs<F>,
+    rate_bits: usize,
+    shift: F,
+) -> PolynomialValues<F> {
+    let lg_n = log2_strict(coeffs.len()) + rate_bits;
+    let table = root_table::<F>(lg_n);
+    coeffs
+        .lde(rate_bits)
+        .coset_fft_with_options(shift, Some(rate_bits), Some(&table))
+}
+
+#[cfg(test)]
+mod tests {
+    use plonky2::field::goldilocks_field::GoldilocksField;
+    use plonky2::field::types::Sample;
+
+    use super::*;
+
+    type F = GoldilocksField;
+
+    fn random_coeffs(n: usize) -> PolynomialCoeffs<F> {
+        PolynomialCoeffs::new(F::rand_vec(n))
+    }
+
+    #[test]
+    fn test_root_table_cache() {
+        let table = root_table::<F>(5);
+        assert!(Arc::ptr_eq(&table, &root_table::<F>(5)));
+        assert_eq!(*table, fft_root_table::<F>(1 << 5));
+        assert!(!Arc::ptr_eq(&table, &root_table::<F>(6)));
+    }
+
+    #[test]
+    fn test_ntt_naive() {
+        for lg_n in 0..6 {
+            let n = 1 << lg_n;
+            let coeffs = random_coeffs(n);
+            let values = ntt(coeffs.clone());
+            let g = F::primitive_root_of_unity(lg_n);
+            for (x, value) in g.powers().zip(values.values.iter()) {
+                assert_eq!(coeffs.eval(x), *value);
+            }
+        }
+    }
+
+    #[test]
+    fn test_ntt_round_trip() {
+        for lg_n in 0..8 {
+            let coeffs = random_coeffs(1 << lg_n);
+            assert_eq!(intt(ntt(coeffs.clone())), coeffs);
+
+            let shift = F::coset_shift();
+            let values = coeffs.coset_fft(shift);
+            assert_eq!(coset_intt(values, shift), coeffs);
+        }
+    }
+
+    #[test]
+    fn test_coset_lde() {
+        let lg_n = 4;
+        let rate_bits = 2;
+        let coeffs = random_coeffs(1 << lg_n);
+        let shift = F::coset_shift();
+
+        let lde = coset_lde(&coeffs, rate_bits, shift);
+        assert_eq!(lde.len(), 1 << (lg_n + rate_bits));
+        let g = F::primitive_root_of_unity(lg_n + rate_bits);
+        for (x, value) in g.powers().zip(lde.values.iter()) {
+            assert_eq!(coeffs.eval(shift * x), *value);
+        }
+    }
+}
@@ -0,0 +1,408 @@
+//! Vectorized Goldilocks arithmetic on aarch64.
+//!
+//! `PackedGoldilocks` holds `WIDTH` Goldilocks elements in a single NEON register. Additions and
+//! subtractions reduce their inputs to canonical form, so a lane may hold another representative
+//! than the scalar `GoldilocksField` would, but the results are always equal as field elements.
+//!
+//! The type implements plonky2's `PackedField`, and is the packing with which the prover evaluates
+//! the constraints of a Goldilocks trace, see `GoldilocksPacking`. It is only available with the
+//! `simd` feature.
+//!
+//! This module is untested: no automated build runs on aarch64, so its tests must be run on an
+//! aarch64 machine, or under QEMU, whenever it is changed. On x86-64, plonky2 already packs
+//! Goldilocks elements in AVX2 or AVX-512 registers when the target supports them, e.g. with
+//! `RUSTFLAGS="-C target-cpu=native"`.
+
+use core::fmt::{self, Debug, Formatter};
+use core::iter::{Product, Sum};
+use core::mem::transmute;
+use core::ops::{Add, AddAssign, Div, Mul, MulAssign, Neg, Sub, SubAssign};
+
+use plonky2::field::goldilocks_field::GoldilocksField;
+use plonky2::field::ops::Square;
+use plonky2::field::packed::PackedField;
+use plonky2::field::types::Field;
+
+mod neon;
+use neon as backend;
+
+/// The number of Goldilocks elements in a `PackedGoldilocks`.
+pub const WIDTH: usize = backend::WIDTH;
+
+/// The order of the Goldilocks field, `2^64 - 2^32 + 1`.
+const ORDER: u64 = 0xFFFF_FFFF_0000_0001;
+
+/// `2^64 - ORDER = 2^32 - 1`.
+const EPSILON: u64 = 0xFFFF_FFFF;
+
+/// `WIDTH` Goldilocks elements, operated on in a single vector register.
+#[derive(Clone, Copy)]
+#[repr(transparent)]
+pub struct PackedGoldilocks(pub [GoldilocksField; WIDTH]);
+
+impl PackedGoldilocks {
+    #[inline]
+    fn new(x: backend::Vector) -> Self {
+        unsafe { transmute(x) }
+    }
+
+    #[inline]
+    fn get(&self) -> backend::Vector {
+        unsafe { transmute(*self) }
+    }
+}
+
+impl Debug for PackedGoldilocks {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        Debug::fmt(&self.0, f)
+    }
+}
+
+impl Default for PackedGoldilocks {
+    #[inline]
+    fn default() -> Self {
+        Self::ZEROS
+    }
+}
+
+impl From<GoldilocksField> for PackedGoldilocks {
+    #[inline]
+    fn from(x: GoldilocksField) -> Self {
+        Self([x; WIDTH])
+    }
+}
+
+impl Add for PackedGoldilocks {
+    type Output = Self;
+
+    #[inline]
+    fn add(self, rhs: Self) -> Self {
+        Self::new(unsafe { backend::add(self.get(), rhs.get()) })
+    }
+}
+
+impl Add<GoldilocksField> for PackedGoldilocks {
+    type Output = Self;
+
+    #[inline]
+    fn add(self, rhs: GoldilocksField) -> Self {
+        self + Self::from(rhs)
+    }
+}
+
+impl Add<PackedGoldilocks> for GoldilocksField {
+    type Output = PackedGoldilocks;
+
+    #[inline]
+    fn add(self, rhs: PackedGoldilocks) -> PackedGoldilocks {
+        PackedGoldilocks::from(self) + rhs
+    }
+}
+
+impl AddAssign for PackedGoldilocks {
+    #[inline]
+    fn add_assign(&mut self, rhs: Self) {
+        *self = *self + rhs;
+    }
+}
+
+impl AddAssign<GoldilocksField> for PackedGoldilocks {
+    #[inline]
+    fn add_assign(&mut self, rhs: GoldilocksField) {
+        *self = *self + rhs;
+    }
+}
+
+impl Sub for PackedGoldilocks {
+    type Output = Self;
+
+    #[inline]
+    fn sub(self, rhs: Self) -> Self {
+        Self::new(unsafe { backend::sub(self.get(), rhs.get()) })
+    }
+}
+
+impl Sub<GoldilocksField> for PackedGoldilocks {
+    type Output = Self;
+
+    #[inline]
+    fn sub(self, rhs: GoldilocksField) -> Self {
+        self - Self::from(rhs)
+    }
+}
+
+impl Sub<PackedGoldilocks> for GoldilocksField {
+    type Output = PackedGoldilocks;
+
+    #[inline]
+    fn sub(self, rhs: PackedGoldilocks) -> PackedGoldilocks {
+        PackedGoldilocks::from(self) - rhs
+    }
+}
+
+impl SubAssign for PackedGoldilocks {
+    #[inline]
+    fn sub_assign(&mut self, rhs: Self) {
+        *self = *self - rhs;
+    }
+}
+
+impl SubAssign<GoldilocksField> for PackedGoldilocks {
+    #[inline]
+    fn sub_assign(&mut self, rhs: GoldilocksField) {
+        *self = *self - rhs;
+    }
+}
+
+impl Neg for PackedGoldilocks {
+    type Output = Self;
+
+    #[inline]
+    fn neg(self) -> Self {
+        Self::ZEROS - self
+    }
+}
+
+impl Mul for PackedGoldilocks {
+    type Output = Self;
+
+    #[inline]
+    fn mul(self, rhs: Self) -> Self {
+        Self::new(unsafe { backend::mul(self.get(), rhs.get()) })
+    }
+}
+
+impl Mul<GoldilocksField> for PackedGoldilocks {
+    type Output = Self;
+
+    #[inline]
+    fn mul(self, rhs: GoldilocksField) -> Self {
+        self * Self::from(rhs)
+    }
+}
+
+impl Mul<PackedGoldilocks> for GoldilocksField {
+    type Output = PackedGoldilocks;
+
+    #[inline]
+    fn mul(self, rhs: PackedGoldilocks) -> PackedGoldilocks {
+        PackedGoldilocks::from(self) * rhs
+    }
+}
+
+impl MulAssign for PackedGoldilocks {
+    #[inline]
+    fn mul_assign(&mut self, rhs: Self) {
+        *self = *self * rhs;
+    }
+}
+
+impl MulAssign<GoldilocksField> for PackedGoldilocks {
+    #[inline]
+    fn mul_assign(&mut self, rhs: GoldilocksField) {
+        *self = *self * rhs;
+    }
+}
+
+impl Div<GoldilocksField> for PackedGoldilocks {
+    type Output = Self;
+
+    #[allow(clippy::suspicious_arithmetic_impl)]
+    #[inline]
+    fn div(self, rhs: GoldilocksField) -> Self {
+        self * rhs.inverse()
+    }
+}
+
+impl Square for PackedGoldilocks {
+    #[inline]
+    fn square(&self) -> Self {
+        *self * *self
+    }
+}
+
+impl Sum for PackedGoldilocks {
+    fn sum<I: Iterator<Item = Self>>(iter: I) -> Self {
+        iter.fold(Self::ZEROS, |acc, x| acc + x)
+    }
+}
+
+impl Product for PackedGoldilocks {
+    fn product<I: Iterator<Item = Self>>(iter: I) -> Self {
+        iter.fold(Self::ONES, |acc, x| acc * x)
+    }
+}
+
+unsafe impl PackedField for PackedGoldilocks {
+    type Scalar = GoldilocksField;
+
+    const WIDTH: usize = WIDTH;
+    const ZEROS: Self = Self([GoldilocksField::ZERO; WIDTH]);
+    const ONES: Self = Self([GoldilocksField::ONE; WIDTH]);
+
+    #[inline]
+    fn from_slice(slice: &[GoldilocksField]) -> &Self {
+        assert_eq!(slice.len(), WIDTH);
+        unsafe { &*slice.as_ptr().cast() }
+    }
+
+    #[inline]
+    fn from_slice_mut(slice: &mut [GoldilocksField]) -> &mut Self {
+        assert_eq!(slice.len(), WIDTH);
+        unsafe { &mut *slice.as_mut_ptr().cast() }
+    }
+
+    #[inline]
+    fn as_slice(&self) -> &[GoldilocksField] {
+        &self.0
+    }
+
+    #[inline]
+    fn as_slice_mut(&mut self) -> &mut [GoldilocksField] {
+        &mut self.0
+    }
+
+    fn interleave(&self, other: Self, block_len: usize) -> (Self, Self) {
+        assert!(block_len.is_power_of_two() && block_len <= WIDTH);
+        if block_len == WIDTH {
+            return (*self, other);
+        }
+        let (a, b) = (self.0, other.0);
+        let (mut res_0, mut res_1) = (a, b);
+        for i in (0..WIDTH).step_by(2 * block_len) {
+            for j in 0..block_len {
+                res_0[i + j] = a[i + j];
+                res_0[i + block_len + j] = b[i + j];
+                res_1[i + j] = a[i + block_len + j];
+                res_1[i + block_len + j] = b[i + block_len + j];
+            }
+        }
+        (Self(res_0), Self(res_1))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use plonky2::field::types::Sample;
+
+    use super::*;
+    use crate::air::fibonacci::FibonacciAir;
+    use crate::plonky2::stark::config::{
+        CurtaConfig, CurtaPoseidonGoldilocksConfig, PoseidonGoldilocksStarkConfig,
+    };
+    use crate::plonky2::stark::prover::StarkyProver;
+    use crate::plonky2::stark::verifier::StarkyVerifier;
+    use crate::plonky2::stark::Starky;
+    use crate::trace::generator::ConstantGenerator;
+
+    type F = GoldilocksField;
+
+    fn random_packed() -> PackedGoldilocks {
+        PackedGoldilocks(F::rand_array())
+    }
+
+    /// Values next to multiples of `2^32` and to the order, including non-canonical ones.
+    fn edge_cases() -> Vec<PackedGoldilocks> {
+        let values = [F(ORDER - 1), F(ORDER + 5), F(u64::MAX), F(EPSILON << 32)];
+        values
+            .chunks(WIDTH)
+            .map(|chunk| PackedGoldilocks(core::array::from_fn(|i| chunk[i % chunk.len()])))
+            .collect()
+    }
+
+    fn check_lanes(
+        x: PackedGoldilocks,
+        y: PackedGoldilocks,
+        packed_op: fn(PackedGoldilocks, PackedGoldilocks) -> PackedGoldilocks,
+        scalar_op: fn(F, F) -> F,
+    ) {
+        let result = packed_op(x, y);
+        for i in 0..WIDTH {
+            assert_eq!(result.0[i], scalar_op(x.0[i], y.0[i]));
+        }
+    }
+
+    #[test]
+    fn test_packed_goldilocks_arithmetic() {
+        let mut inputs = vec![(random_packed(), random_packed())];
+        for edge in edge_cases() {
+            inputs.push((edge, random_packed()));
+            inputs.push((random_packed(), edge));
+            for other in edge_cases() {
+                inputs.push((edge, other));
+            }
+        }
+        for (x, y) in inputs {
+            check_lanes(x, y, |x, y| x + y, |x, y| x + y);
+            check_lanes(x, y, |x, y| x - y, |x, y| x - y);
+            check_lanes(x, y, |x, y| x * y, |x, y| x * y);
+            check_lanes(x, y, |x, _| -x, |x, _| -x);
+            check_lanes(x, y, |x, _| x.square(), |x, _| x * x);
+        }
+    }
+
+    #[test]
+    fn test_packed_goldilocks_interleave() {
+        let x = PackedGoldilocks(core::array::from_fn(|i| F(i as u64)));
+        let y = PackedGoldilocks(core::array::from_fn(|i| F((WIDTH + i) as u64)));
+
+        let mut block_len = 1;
+        while block_len <= WIDTH {
+            let (a, b) = x.interleave(y, block_len);
+            for i in 0..WIDTH {
+                let (block, offset) = (i / block_len, i % block_len);
+                // Even blocks of the output come from `x` and odd blocks from `y`.
+                let (source, pair) = if block % 2 == 0 {
+                    (&x, block)
+                } else {
+                    (&y, block - 1)
+                };
+                assert_eq!(a.0[i], source.0[pair * block_len + offset]);
+                let other = (pair + 1) * block_len + offset;
+                let expected_b = if block_len == WIDTH {
+                    y.0[i]
+                } else {
+                    source.0[other]
+                };
+                assert_eq!(b.0[i], expected_b);
+            }
+            block_len *= 2;
+        }
+    }
+
+    #[test]
+    fn test_fibonacci_stark_packed_goldilocks() {
+        type C = CurtaPoseidonGoldilocksConfig;
+        type SC = PoseidonGoldilocksStarkConfig;
+
+        let num_rows = 1 << 5usize;
+        let stark = Starky::<FibonacciAir>::new(FibonacciAir::new());
+
+        let public_inputs = [
+            F::ZERO,
+            F::ONE,
+            FibonacciAir::fibonacci(num_rows - 1, F::ZERO, F::ONE),
+        ];
+        let trace_generator =
+            ConstantGenerator::new(FibonacciAir::generate_trace(F::ZERO, F::ONE, num_rows));
+        let config = SC::standard_fast_config(num_rows);
+
+        // The prover packs Goldilocks elements in NEON registers by default.
+        assert_eq!(
+            core::any::TypeId::of::<<C as CurtaConfig<2>>::Packing>(),
+            core::any::TypeId::of::<PackedGoldilocks>()
+        );
+        let proof =
+            StarkyProver::<F, C, 2>::prove(&config, &stark, &trace_generator, &public_inputs)
+                .unwrap();
+        let scalar_proof = StarkyProver::<F, C, 2>::prove_with_packing::<_, _, F>(
+            &config,
+            &stark,
+            &trace_generator,
+            &public_inputs,
+        )
+        .unwrap();
+        assert_eq!(proof, scalar_proof);
+        StarkyVerifier::verify(&config, &stark, proof, &public_inputs).unwrap();
+    }
+}
@@ -0,0 +1,93 @@
+//! The NEON backend of `PackedGoldilocks`.
+//!
+//! Two Goldilocks elements are held in a 128-bit register. NEON has no 64-bit multiplication, so
+//! products are computed from four widening 32-bit multiplications per lane, and reduced with a
+//! vectorized `reduce128`.
+
+use core::arch::aarch64::*;
+
+use super::{EPSILON, ORDER};
+
+/// The number of Goldilocks elements in a NEON register.
+pub(super) const WIDTH: usize = 2;
+
+pub(super) type Vector = uint64x2_t;
+
+#[inline]
+unsafe fn splat(x: u64) -> uint64x2_t {
+    vdupq_n_u64(x)
+}
+
+/// Reduces every lane to its canonical representative in `[0, ORDER)`.
+#[inline]
+unsafe fn canonicalize(x: uint64x2_t) -> uint64x2_t {
+    let in_range = vcgtq_u64(splat(ORDER), x);
+    // `x - ORDER = x + EPSILON` modulo `2^64`.
+    vaddq_u64(x, vbicq_u64(splat(EPSILON), in_range))
+}
+
+#[inline]
+pub(super) unsafe fn add(x: uint64x2_t, y: uint64x2_t) -> uint64x2_t {
+    let (x, y) = (canonicalize(x), canonicalize(y));
+    let sum = vaddq_u64(x, y);
+    // On overflow the sum has lost `2^64 = EPSILON` modulo `ORDER`. Since both inputs are
+    // canonical, adding it back can not overflow again.
+    let overflow = vcgtq_u64(x, sum);
+    vaddq_u64(sum, vandq_u64(overflow, splat(EPSILON)))
+}
+
+#[inline]
+pub(super) unsafe fn sub(x: uint64x2_t, y: uint64x2_t) -> uint64x2_t {
+    let (x, y) = (canonicalize(x), canonicalize(y));
+    let diff = vsubq_u64(x, y);
+    // On underflow the difference has gained `2^64 = EPSILON` modulo `ORDER`.
+    let underflow = vcgtq_u64(y, x);
+    vsubq_u64(diff, vandq_u64(underflow, splat(EPSILON)))
+}
+
+/// The 128-bit products of the lanes of `x` and `y`, as their high and low 64 bits.
+#[inline]
+unsafe fn mul64_64(x: uint64x2_t, y: uint64x2_t) -> (uint64x2_t, uint64x2_t) {
+    let low_mask = splat(EPSILON);
+    let (x_lo, x_hi) = (vmovn_u64(x), vshrn_n_u64::<32>(x));
+    let (y_lo, y_hi) = (vmovn_u64(y), vshrn_n_u64::<32>(y));
+
+    let mul_ll = vmull_u32(x_lo, y_lo);
+    let mul_lh = vmull_u32(x_lo, y_hi);
+    let mul_hl = vmull_u32(x_hi, y_lo);
+    let mul_hh = vmull_u32(x_hi, y_hi);
+
+    // The middle 32 bits, together with the carries into the high half.
+    let mid = vaddq_u64(
+        vshrq_n_u64::<32>(mul_ll),
+        vaddq_u64(vandq_u64(mul_lh, low_mask), vandq_u64(mul_hl, low_mask)),
+    );
+    let lo = vorrq_u64(vandq_u64(mul_ll, low_mask), vshlq_n_u64::<32>(mid));
+    let hi = vaddq_u64(
+        vaddq_u64(mul_hh, vshrq_n_u64::<32>(mid)),
+        vaddq_u64(vshrq_n_u64::<32>(mul_lh), vshrq_n_u64::<32>(mul_hl)),
+    );
+    (hi, lo)
+}
+
+/// Reduces the 128-bit values `hi * 2^64 + lo` modulo `ORDER`, as the scalar `reduce128`.
+#[inline]
+unsafe fn reduce128(hi: uint64x2_t, lo: uint64x2_t) -> uint64x2_t {
+    let epsilon = splat(EPSILON);
+    let hi_hi = vshrq_n_u64::<32>(hi);
+
+    // `2^96 = -1` modulo `ORDER`.
+    let borrow = vcgtq_u64(hi_hi, lo);
+    let t0 = vsubq_u64(vsubq_u64(lo, hi_hi), vandq_u64(borrow, epsilon));
+    // `2^64 = EPSILON` modulo `ORDER`, applied to the low 32 bits of `hi`.
+    let t1 = vmull_u32(vmovn_u64(hi), vdup_n_u32(EPSILON as u32));
+    let t2 = vaddq_u64(t0, t1);
+    let carry = vcgtq_u64(t0, t2);
+    vaddq_u64(t2, vandq_u64(carry, epsilon))
+}
+
+#[inline]
+pub(super) unsafe fn mul(x: uint64x2_t, y: uint64x2_t) -> uint64x2_t {
+    let (hi, lo) = mul64_64(x, y);
+    reduce128(hi, lo)
+}
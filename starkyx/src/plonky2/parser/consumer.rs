@@ -1,13 +1,32 @@
 use alloc::vec;
+use core::any::Any;
 use core::marker::PhantomData;
 
 use plonky2::field::extension::Extendable;
+use plonky2::field::goldilocks_field::GoldilocksField;
 use plonky2::field::packed::PackedField;
 use plonky2::hash::hash_types::RichField;
 use plonky2::iop::ext_target::ExtensionTarget;
 use plonky2::iop::target::Target;
 use plonky2::plonk::circuit_builder::CircuitBuilder;
 
+use crate::math::goldilocks::fused::MultiplyAccumulate;
+
+/// Folds the constraints of an AIR into a random linear combination for each challenge.
+pub trait ConstraintFolder<P: PackedField> {
+    /// Add one constraint on all rows.
+    fn constraint(&mut self, constraint: P);
+
+    /// Add one constraint valid on all rows except the last.
+    fn constraint_transition(&mut self, constraint: P);
+
+    /// Add one constraint which only applies to the first row of the trace.
+    fn constraint_first_row(&mut self, constraint: P);
+
+    /// Add one constraint which only applies to the last row of the trace.
+    fn constraint_last_row(&mut self, constraint: P);
+}
+
 pub struct ConstraintConsumer<P: PackedField> {
     /// Random values used to combine multiple constraints into one.
     alphas: Vec<P::Scalar>,
@@ -56,6 +75,19 @@ impl<P: PackedField> ConstraintConsumer<P> {
 
     /// Add one constraint on all rows.
     pub fn constraint(&mut self, constraint: P) {
+        // Over Goldilocks, `acc * alpha + constraint` is computed with a single reduction.
+        if let Some(&constraint) = (&constraint as &dyn Any).downcast_ref::<GoldilocksField>() {
+            for (alpha, acc) in self.alphas.iter().zip(&mut self.constraint_accs) {
+                let alpha = (alpha as &dyn Any)
+                    .downcast_ref::<GoldilocksField>()
+                    .unwrap();
+                let acc = (acc as &mut dyn Any)
+                    .downcast_mut::<GoldilocksField>()
+                    .unwrap();
+                *acc = GoldilocksField::multiply_accumulate(*acc, *alpha, constraint);
+            }
+            return;
+        }
         for (&alpha, acc) in self.alphas.iter().zip(&mut self.constraint_accs) {
             *acc *= alpha;
             *acc += constraint;
@@ -75,6 +107,87 @@ impl<P: PackedField> ConstraintConsumer<P> {
     }
 }
 
+impl<P: PackedField> ConstraintFolder<P> for ConstraintConsumer<P> {
+    fn constraint(&mut self, constraint: P) {
+        ConstraintConsumer::constraint(self, constraint);
+    }
+
+    fn constraint_transition(&mut self, constraint: P) {
+        ConstraintConsumer::constraint_transition(self, constraint);
+    }
+
+    fn constraint_first_row(&mut self, constraint: P) {
+        ConstraintConsumer::constraint_first_row(self, constraint);
+    }
+
+    fn constraint_last_row(&mut self, constraint: P) {
+        ConstraintConsumer::constraint_last_row(self, constraint);
+    }
+}
+
+/// A `ConstraintConsumer` for a number of challenges `N` known at compile time.
+///
+/// The accumulators are held in an array, so that the compiler can unroll the folding of each
+/// constraint for AIRs whose configuration is fixed.
+pub struct FixedConstraintConsumer<P: PackedField, const N: usize> {
+    /// Random values used to combine multiple constraints into one.
+    alphas: [P::Scalar; N],
+
+    /// Running sums of constraints that have been emitted so far, scaled by powers of alpha.
+    constraint_accs: [P; N],
+
+    /// The evaluation of `X - g^(n-1)`.
+    z_last: P,
+
+    /// The evaluation of the Lagrange basis polynomial of the first row.
+    lagrange_basis_first: P,
+
+    /// The evaluation of the Lagrange basis polynomial of the last row.
+    lagrange_basis_last: P,
+}
+
+impl<P: PackedField, const N: usize> FixedConstraintConsumer<P, N> {
+    pub fn new(
+        alphas: [P::Scalar; N],
+        z_last: P,
+        lagrange_basis_first: P,
+        lagrange_basis_last: P,
+    ) -> Self {
+        Self {
+            alphas,
+            constraint_accs: [P::ZEROS; N],
+            z_last,
+            lagrange_basis_first,
+            lagrange_basis_last,
+        }
+    }
+
+    pub fn accumulators(self) -> [P; N] {
+        self.constraint_accs
+    }
+}
+
+impl<P: PackedField, const N: usize> ConstraintFolder<P> for FixedConstraintConsumer<P, N> {
+    fn constraint(&mut self, constraint: P) {
+        for i in 0..N {
+            self.constraint_accs[i] *= self.alphas[i];
+            self.constraint_accs[i] += constraint;
+        }
+    }
+
+    fn constraint_transition(&mut self, constraint: P) {
+        self.constraint(constraint * self.z_last);
+    }
+
+    fn constraint_first_row(&mut self, constraint: P) {
+        self.constraint(constraint * self.lagrange_basis_first);
+    }
+
+    fn constraint_last_row(&mut self, constraint: P) {
+        self.constraint(constraint * self.lagrange_basis_last);
+    }
+}
+
 pub struct RecursiveConstraintConsumer<F: RichField + Extendable<D>, const D: usize> {
     /// A random value used to combine multiple constraints into one.
     alphas: Vec<Target>,
@@ -24,6 +24,8 @@ where
     pub(crate) global_vars: &'a [P],
     pub(crate) public_vars: &'a [P],
     pub(crate) challenges: &'a [P],
+    /// Whether all the constraints evaluated so far are satisfied.
+    pub(crate) is_satisfied: bool,
 }
 
 pub struct GlobalRecursiveStarkParser<'a, F: RichField + Extendable<D>, const D: usize> {
@@ -69,19 +71,19 @@ where
     }
 
     fn constraint(&mut self, constraint: Self::Var) {
-        assert_eq!(constraint.as_slice(), P::ZEROS.as_slice());
+        self.is_satisfied &= constraint.as_slice() == P::ZEROS.as_slice();
     }
 
     fn constraint_transition(&mut self, constraint: Self::Var) {
-        assert_eq!(constraint.as_slice(), P::ZEROS.as_slice());
+        self.is_satisfied &= constraint.as_slice() == P::ZEROS.as_slice();
     }
 
     fn constraint_first_row(&mut self, constraint: Self::Var) {
-        assert_eq!(constraint.as_slice(), P::ZEROS.as_slice());
+        self.is_satisfied &= constraint.as_slice() == P::ZEROS.as_slice();
     }
 
     fn constraint_last_row(&mut self, constraint: Self::Var) {
-        assert_eq!(constraint.as_slice(), P::ZEROS.as_slice());
+        self.is_satisfied &= constraint.as_slice() == P::ZEROS.as_slice();
     }
 
     fn add(&mut self, a: Self::Var, b: Self::Var) -> Self::Var {
@@ -7,13 +7,19 @@ use plonky2::hash::hash_types::RichField;
 use plonky2::iop::ext_target::ExtensionTarget;
 use plonky2::plonk::circuit_builder::CircuitBuilder;
 
-use self::consumer::{ConstraintConsumer, RecursiveConstraintConsumer};
+use self::consumer::{
+    ConstraintConsumer, ConstraintFolder, FixedConstraintConsumer, RecursiveConstraintConsumer,
+};
 use crate::air::extension::cubic::CubicParser;
 use crate::air::parser::AirParser;
 use crate::math::extension::cubic::parameters::CubicParameters;
 use crate::polynomial::parser::PolynomialParser;
 
-pub struct StarkParser<'a, F, FE, P, const D: usize, const D2: usize>
+/// A parser evaluating the constraints of an AIR on packed values.
+///
+/// The constraints are folded by `CC`, which is a `ConstraintConsumer` unless a specialization
+/// for a fixed number of challenges is used.
+pub struct StarkParser<'a, F, FE, P, const D: usize, const D2: usize, CC = ConstraintConsumer<P>>
 where
     F: RichField + Extendable<D>,
     FE: FieldExtension<D2, BaseField = F>,
@@ -24,9 +30,21 @@ where
     pub(crate) global_vars: &'a [P],
     pub(crate) public_vars: &'a [P],
     pub(crate) challenges: &'a [P],
-    pub(crate) consumer: &'a mut ConstraintConsumer<P>,
+    pub(crate) consumer: &'a mut CC,
 }
 
+/// A `StarkParser` over the extension field which folds the constraints for `N` challenges known
+/// at compile time.
+pub type FixedStarkParser<'a, F, const D: usize, const N: usize> = StarkParser<
+    'a,
+    F,
+    <F as Extendable<D>>::Extension,
+    <F as Extendable<D>>::Extension,
+    D,
+    D,
+    FixedConstraintConsumer<<F as Extendable<D>>::Extension, N>,
+>;
+
 pub struct RecursiveStarkParser<'a, F: RichField + Extendable<D>, const D: usize> {
     pub(crate) builder: &'a mut CircuitBuilder<F, D>,
     pub(crate) local_vars: &'a [ExtensionTarget<D>],
@@ -37,11 +55,13 @@ pub struct RecursiveStarkParser<'a, F: RichField + Extendable<D>, const D: usize
     pub(crate) consumer: &'a mut RecursiveConstraintConsumer<F, D>,
 }
 
-impl<'a, F, FE, P, const D: usize, const D2: usize> AirParser for StarkParser<'a, F, FE, P, D, D2>
+impl<'a, F, FE, P, const D: usize, const D2: usize, CC> AirParser
+    for StarkParser<'a, F, FE, P, D, D2, CC>
 where
     F: RichField + Extendable<D>,
     FE: FieldExtension<D2, BaseField = F>,
     P: PackedField<Scalar = FE>,
+    CC: ConstraintFolder<P>,
 {
     type Field = F;
     type Var = P;
@@ -118,21 +138,23 @@ where
     }
 }
 
-impl<'a, F, FE, P, const D: usize, const D2: usize> PolynomialParser
-    for StarkParser<'a, F, FE, P, D, D2>
+impl<'a, F, FE, P, const D: usize, const D2: usize, CC> PolynomialParser
+    for StarkParser<'a, F, FE, P, D, D2, CC>
 where
     F: RichField + Extendable<D>,
     FE: FieldExtension<D2, BaseField = F>,
     P: PackedField<Scalar = FE>,
+    CC: ConstraintFolder<P>,
 {
 }
 
-impl<'a, F, FE, E: CubicParameters<F>, P, const D: usize, const D2: usize> CubicParser<E>
-    for StarkParser<'a, F, FE, P, D, D2>
+impl<'a, F, FE, E: CubicParameters<F>, P, const D: usize, const D2: usize, CC> CubicParser<E>
+    for StarkParser<'a, F, FE, P, D, D2, CC>
 where
     F: RichField + Extendable<D>,
     FE: FieldExtension<D2, BaseField = F>,
     P: PackedField<Scalar = FE>,
+    CC: ConstraintFolder<P>,
 {
 }
 
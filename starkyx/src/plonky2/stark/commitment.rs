@@ -0,0 +1,93 @@
+//! Commitments to a trace which is proven later.
+//!
+//! A `TraceCommitment` contains the Merkle caps of every round of a trace and a digest which binds
+//! them to the configuration, the public inputs and the global values. It can be published as soon
+//! as the trace is generated, and a proof generated later by `StarkyProver::prove_from_commitment`
+//! from the same committed polynomials opens exactly these caps, so the trace is never committed
+//! to twice.
+
+use plonky2::field::extension::Extendable;
+use plonky2::hash::hash_types::{HashOut, RichField};
+use plonky2::hash::merkle_tree::MerkleCap;
+use plonky2::plonk::config::Hasher;
+use serde::{Deserialize, Serialize};
+
+use super::config::{CurtaConfig, StarkyConfig};
+use super::proof::StarkProof;
+use super::verifier::VerificationError;
+
+/// The Merkle caps of a committed trace, bound to its statement by a digest.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(bound = "")]
+pub struct TraceCommitment<F: RichField + Extendable<D>, C: CurtaConfig<D, F = F>, const D: usize> {
+    /// Merkle caps of LDEs of trace values for each round.
+    pub trace_caps: Vec<MerkleCap<F, C::Hasher>>,
+    pub global_values: Vec<F>,
+    /// The digest of the configuration, the public inputs, the global values and the caps.
+    pub digest: HashOut<F>,
+}
+
+impl<F: RichField + Extendable<D>, C: CurtaConfig<D, F = F>, const D: usize>
+    TraceCommitment<F, C, D>
+{
+    /// A commitment to the trace with caps `trace_caps` for `public_inputs`.
+    pub fn new(
+        config: &StarkyConfig<C, D>,
+        trace_caps: Vec<MerkleCap<F, C::Hasher>>,
+        global_values: Vec<F>,
+        public_inputs: &[F],
+    ) -> Self {
+        let digest = Self::compute_digest(config, &trace_caps, &global_values, public_inputs);
+        Self {
+            trace_caps,
+            global_values,
+            digest,
+        }
+    }
+
+    /// The digest binding `trace_caps` and `global_values` to `config` and `public_inputs`.
+    pub fn compute_digest(
+        config: &StarkyConfig<C, D>,
+        trace_caps: &[MerkleCap<F, C::Hasher>],
+        global_values: &[F],
+        public_inputs: &[F],
+    ) -> HashOut<F> {
+        let mut elements = config
+            .digest()
+            .into_iter()
+            .map(F::from_canonical_u8)
+            .collect::<Vec<_>>();
+        elements.push(F::from_canonical_usize(public_inputs.len()));
+        elements.extend_from_slice(public_inputs);
+        elements.push(F::from_canonical_usize(global_values.len()));
+        elements.extend_from_slice(global_values);
+        for cap in trace_caps {
+            elements.extend(cap.flatten());
+        }
+        C::InnerHasher::hash_no_pad(&elements)
+    }
+
+    /// Checks that the digest of the commitment is for `config` and `public_inputs`.
+    pub fn check(
+        &self,
+        config: &StarkyConfig<C, D>,
+        public_inputs: &[F],
+    ) -> Result<(), VerificationError> {
+        let digest =
+            Self::compute_digest(config, &self.trace_caps, &self.global_values, public_inputs);
+        if digest != self.digest {
+            return Err(VerificationError::TraceCommitmentMismatch);
+        }
+        Ok(())
+    }
+
+    /// Checks that `proof` opens the committed trace.
+    pub fn check_proof(&self, proof: &StarkProof<F, C, D>) -> Result<(), VerificationError> {
+        if proof.air_proof.trace_caps != self.trace_caps
+            || proof.global_values != self.global_values
+        {
+            return Err(VerificationError::TraceCommitmentMismatch);
+        }
+        Ok(())
+    }
+}
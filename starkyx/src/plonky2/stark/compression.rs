@@ -0,0 +1,399 @@
+//! Compression of STARK proofs.
+//!
+//! The FRI query rounds of a proof open many leaves of the same Merkle trees, so that the Merkle
+//! authentication paths of different rounds share a lot of nodes. A compressed proof only keeps
+//! the siblings which can not be computed from the leaves and the siblings already present in the
+//! proof. The missing siblings are recomputed from the query indices, which are derived from the
+//! transcript during verification.
+
+use std::collections::HashMap;
+
+use plonky2::field::extension::{Extendable, FieldExtension};
+use plonky2::fri::proof::{FriChallenges, FriProof};
+use plonky2::hash::hash_types::RichField;
+use plonky2::hash::merkle_proofs::MerkleProof;
+use plonky2::plonk::config::Hasher;
+use serde::{Deserialize, Serialize};
+
+use super::config::{CurtaConfig, StarkyConfig};
+use super::proof::{ProofShapeError, StarkProof};
+use super::verifier::VerificationError;
+use super::Starky;
+use crate::air::RAirData;
+
+/// A STARK proof whose FRI query rounds have their redundant Merkle siblings removed.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(bound = "")]
+pub struct CompressedStarkProof<
+    F: RichField + Extendable<D>,
+    C: CurtaConfig<D, F = F>,
+    const D: usize,
+> {
+    /// The proof with the Merkle authentication paths of the FRI query rounds compressed.
+    pub(crate) proof: StarkProof<F, C, D>,
+}
+
+impl<F: RichField + Extendable<D>, C: CurtaConfig<D, F = F>, const D: usize> StarkProof<F, C, D> {
+    /// Compresses the proof by removing the Merkle siblings shared between FRI query rounds.
+    ///
+    /// The query indices are derived from the transcript, which depends on the `stark` and on the
+    /// public inputs.
+    pub fn compress<A: RAirData>(
+        &self,
+        config: &StarkyConfig<C, D>,
+        stark: &Starky<A>,
+        public_inputs: &[F],
+    ) -> CompressedStarkProof<F, C, D> {
+        let challenges =
+            self.get_challenges(config, stark, public_inputs, config.fri_degree_bits());
+        let mut proof = self.clone();
+        map_fri_merkle_proofs(
+            config,
+            &mut proof.air_proof.opening_proof,
+            &challenges.fri_challenges,
+            |_, indices, proofs, height, cap_height| {
+                Ok(compress_merkle_proofs(indices, proofs, height, cap_height))
+            },
+        )
+        .expect("The proof does not have the shape of the configuration");
+        CompressedStarkProof { proof }
+    }
+}
+
+impl<F: RichField + Extendable<D>, C: CurtaConfig<D, F = F>, const D: usize>
+    CompressedStarkProof<F, C, D>
+{
+    /// Recovers the full proof.
+    ///
+    /// Returns an error if the compressed proof does not have the shape expected by the `stark`
+    /// and the configuration.
+    pub fn decompress<A: RAirData>(
+        self,
+        config: &StarkyConfig<C, D>,
+        stark: &Starky<A>,
+        public_inputs: &[F],
+    ) -> Result<StarkProof<F, C, D>, VerificationError> {
+        self.proof.validate_shape(stark, config)?;
+        let challenges =
+            self.proof
+                .get_challenges(config, stark, public_inputs, config.fri_degree_bits());
+        self.decompress_with_challenges(config, &challenges.fri_challenges)
+    }
+
+    /// Recovers the full proof given the FRI challenges of the transcript.
+    pub(crate) fn decompress_with_challenges(
+        self,
+        config: &StarkyConfig<C, D>,
+        fri_challenges: &FriChallenges<F, D>,
+    ) -> Result<StarkProof<F, C, D>, VerificationError> {
+        let mut proof = self.proof;
+        map_fri_merkle_proofs(
+            config,
+            &mut proof.air_proof.opening_proof,
+            fri_challenges,
+            |leaves, indices, proofs, height, cap_height| {
+                decompress_merkle_proofs::<F, C::Hasher>(
+                    leaves, indices, proofs, height, cap_height,
+                )
+            },
+        )?;
+        Ok(proof)
+    }
+
+    /// The proof with compressed Merkle paths, from which the FRI challenges can be computed.
+    pub(crate) fn inner(&self) -> &StarkProof<F, C, D> {
+        &self.proof
+    }
+}
+
+/// Applies `f` to the Merkle proofs of each tree opened in the FRI query rounds.
+///
+/// The function `f` takes the leaves, their indices, the Merkle proofs, the height and the cap
+/// height of the tree, and returns the new Merkle proofs. Returns an error if the number of query
+/// rounds, initial trees or commit phase layers of the proof does not match the configuration.
+fn map_fri_merkle_proofs<F, C, G, const D: usize>(
+    config: &StarkyConfig<C, D>,
+    opening_proof: &mut FriProof<F, C::Hasher, D>,
+    fri_challenges: &FriChallenges<F, D>,
+    mut f: G,
+) -> Result<(), ProofShapeError>
+where
+    F: RichField + Extendable<D>,
+    C: CurtaConfig<D, F = F>,
+    G: FnMut(
+        &[Vec<F>],
+        &[usize],
+        &[MerkleProof<F, C::Hasher>],
+        usize,
+        usize,
+    ) -> Result<Vec<MerkleProof<F, C::Hasher>>, ProofShapeError>,
+{
+    let fri_params = config.fri_params();
+    let cap_height = config.fri_config.cap_height;
+    let lde_bits = fri_params.lde_bits();
+    let rounds = &mut opening_proof.query_round_proofs;
+    let indices = &fri_challenges.fri_query_indices;
+    ProofShapeError::check(ProofShapeError::NumQueryRounds, indices.len(), rounds.len())?;
+    if rounds.is_empty() {
+        return Ok(());
+    }
+
+    let num_oracles = rounds[0].initial_trees_proof.evals_proofs.len();
+    let num_layers = fri_params.reduction_arity_bits.len();
+    for round in rounds.iter() {
+        ProofShapeError::check(
+            ProofShapeError::NumInitialTrees,
+            num_oracles,
+            round.initial_trees_proof.evals_proofs.len(),
+        )?;
+        ProofShapeError::check(ProofShapeError::NumFriLayers, num_layers, round.steps.len())?;
+    }
+
+    // The initial trees are opened at the query indices.
+    for oracle in 0..num_oracles {
+        let (leaves, proofs): (Vec<_>, Vec<_>) = rounds
+            .iter()
+            .map(|round| round.initial_trees_proof.evals_proofs[oracle].clone())
+            .unzip();
+        let new_proofs = f(&leaves, indices, &proofs, lde_bits, cap_height)?;
+        for (round, proof) in rounds.iter_mut().zip(new_proofs) {
+            round.initial_trees_proof.evals_proofs[oracle].1 = proof;
+        }
+    }
+
+    // The trees of the commit phase are opened at the cosets of the folded query indices.
+    let mut step_indices = indices.clone();
+    let mut height = lde_bits;
+    for (i, arity_bits) in fri_params.reduction_arity_bits.iter().enumerate() {
+        for index in step_indices.iter_mut() {
+            *index >>= arity_bits;
+        }
+        height -= arity_bits;
+        let (leaves, proofs): (Vec<_>, Vec<_>) = rounds
+            .iter()
+            .map(|round| {
+                let step = &round.steps[i];
+                let leaf = step
+                    .evals
+                    .iter()
+                    .flat_map(|e| e.to_basefield_array())
+                    .collect::<Vec<_>>();
+                (leaf, step.merkle_proof.clone())
+            })
+            .unzip();
+        let new_proofs = f(&leaves, &step_indices, &proofs, height, cap_height)?;
+        for (round, proof) in rounds.iter_mut().zip(new_proofs) {
+            round.steps[i].merkle_proof = proof;
+        }
+    }
+    Ok(())
+}
+
+/// Removes the siblings of the Merkle proofs that are on the path of another leaf or that
+/// appear earlier in the list of proofs.
+fn compress_merkle_proofs<F: RichField, H: Hasher<F>>(
+    indices: &[usize],
+    proofs: &[MerkleProof<F, H>],
+    height: usize,
+    cap_height: usize,
+) -> Vec<MerkleProof<F, H>> {
+    proofs
+        .iter()
+        .zip(kept_siblings(indices, height, cap_height))
+        .map(|(proof, kept)| MerkleProof {
+            siblings: proof
+                .siblings
+                .iter()
+                .zip(kept)
+                .filter_map(|(sibling, keep)| keep.then_some(*sibling))
+                .collect(),
+        })
+        .collect()
+}
+
+/// Whether each sibling on the paths of the leaves at `indices` is kept by a compressed proof,
+/// that is, whether it is neither on the path of a leaf nor kept by an earlier proof.
+fn kept_siblings(indices: &[usize], height: usize, cap_height: usize) -> Vec<Vec<bool>> {
+    let num_leaves = 1 << height;
+
+    // Nodes are indexed with the root at index 1, such that the children of the node at index `i`
+    // are at indices `2i` and `2i + 1`.
+    let mut known = vec![false; 2 * num_leaves];
+    for &i in indices {
+        // The path from a leaf to the cap is known.
+        for j in 0..(height - cap_height) {
+            known[(i + num_leaves) >> j] = true;
+        }
+    }
+
+    indices
+        .iter()
+        .map(|&i| {
+            (0..(height - cap_height))
+                .map(|j| {
+                    let sibling_index = ((i + num_leaves) >> j) ^ 1;
+                    let keep = !known[sibling_index];
+                    known[sibling_index] = true;
+                    keep
+                })
+                .collect()
+        })
+        .collect()
+}
+
+/// Recovers the Merkle proofs compressed by `compress_merkle_proofs`.
+///
+/// Returns an error if the number of compressed proofs or of their siblings does not match the
+/// indices of the leaves.
+fn decompress_merkle_proofs<F: RichField, H: Hasher<F>>(
+    leaves: &[Vec<F>],
+    indices: &[usize],
+    compressed_proofs: &[MerkleProof<F, H>],
+    height: usize,
+    cap_height: usize,
+) -> Result<Vec<MerkleProof<F, H>>, ProofShapeError> {
+    let path_length = height - cap_height;
+    let num_leaves = 1 << height;
+
+    ProofShapeError::check(
+        ProofShapeError::NumQueryRounds,
+        indices.len(),
+        compressed_proofs.len(),
+    )?;
+    ProofShapeError::check(ProofShapeError::NumQueryRounds, indices.len(), leaves.len())?;
+    for (proof, kept) in compressed_proofs
+        .iter()
+        .zip(kept_siblings(indices, height, cap_height))
+    {
+        ProofShapeError::check(
+            ProofShapeError::MerkleProofLength,
+            kept.into_iter().filter(|keep| *keep).count(),
+            proof.siblings.len(),
+        )?;
+    }
+
+    // The nodes of the tree computed so far.
+    let mut seen = HashMap::new();
+    for (&i, leaf) in indices.iter().zip(leaves) {
+        seen.insert(i + num_leaves, H::hash_or_noop(leaf));
+    }
+
+    // Fill the nodes from the bottom of the tree to the top, taking the siblings from the
+    // compressed proofs whenever they are not known. The lengths of the compressed proofs are
+    // checked above, so that they have exactly one sibling for each missing node.
+    let mut siblings = compressed_proofs
+        .iter()
+        .map(|proof| proof.siblings.iter())
+        .collect::<Vec<_>>();
+    for layer in 0..path_length {
+        for (&i, proof_siblings) in indices.iter().zip(siblings.iter_mut()) {
+            let index = (i + num_leaves) >> layer;
+            let current = seen[&index];
+            let sibling = *seen.entry(index ^ 1).or_insert_with(|| {
+                *proof_siblings
+                    .next()
+                    .expect("The lengths of the compressed proofs are checked")
+            });
+            let parent = if index & 1 == 0 {
+                H::two_to_one(current, sibling)
+            } else {
+                H::two_to_one(sibling, current)
+            };
+            seen.insert(index >> 1, parent);
+        }
+    }
+
+    Ok(indices
+        .iter()
+        .map(|&i| {
+            let index = i + num_leaves;
+            let siblings = (0..path_length)
+                .map(|layer| seen[&((index >> layer) ^ 1)])
+                .collect();
+            MerkleProof { siblings }
+        })
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use plonky2::field::goldilocks_field::GoldilocksField;
+
+    use super::*;
+    use crate::air::fibonacci::FibonacciAir;
+    use crate::math::prelude::*;
+    use crate::plonky2::stark::config::PoseidonGoldilocksStarkConfig;
+    use crate::plonky2::stark::prover::StarkyProver;
+    use crate::plonky2::stark::verifier::StarkyVerifier;
+    use crate::trace::generator::ConstantGenerator;
+
+    #[test]
+    fn test_compressed_fibonacci_stark() {
+        type F = GoldilocksField;
+        type SC = PoseidonGoldilocksStarkConfig;
+
+        let num_rows = 1 << 10usize;
+        let stark = Starky::<FibonacciAir>::new(FibonacciAir::new());
+
+        let public_inputs = [
+            F::ZERO,
+            F::ONE,
+            FibonacciAir::fibonacci(num_rows - 1, F::ZERO, F::ONE),
+        ];
+
+        let trace = FibonacciAir::generate_trace(F::ZERO, F::ONE, num_rows);
+        let trace_generator = ConstantGenerator::new(trace);
+
+        let config = SC::standard_fast_config(num_rows);
+
+        let proof = StarkyProver::prove(&config, &stark, &trace_generator, &public_inputs).unwrap();
+        let compressed = proof.compress(&config, &stark, &public_inputs);
+
+        let proof_size = bincode::serialize(&proof).unwrap().len();
+        let compressed_size = bincode::serialize(&compressed).unwrap().len();
+        assert!(compressed_size < proof_size);
+
+        let decompressed = compressed
+            .clone()
+            .decompress(&config, &stark, &public_inputs)
+            .unwrap();
+        assert_eq!(decompressed, proof);
+
+        StarkyVerifier::verify_compressed(&config, &stark, compressed.clone(), &public_inputs)
+            .unwrap();
+
+        // Compressed proofs with an extra sibling or a missing query round are rejected.
+        let mut extra_sibling = compressed.clone();
+        let sibling = extra_sibling.proof.air_proof.trace_caps[0].0[0];
+        extra_sibling
+            .proof
+            .air_proof
+            .opening_proof
+            .query_round_proofs[0]
+            .initial_trees_proof
+            .evals_proofs[0]
+            .1
+            .siblings
+            .push(sibling);
+        assert!(matches!(
+            StarkyVerifier::verify_compressed(&config, &stark, extra_sibling, &public_inputs),
+            Err(VerificationError::MalformedProof(
+                ProofShapeError::MerkleProofLength { .. }
+            ))
+        ));
+
+        let mut missing_round = compressed;
+        missing_round
+            .proof
+            .air_proof
+            .opening_proof
+            .query_round_proofs
+            .pop();
+        assert!(matches!(
+            StarkyVerifier::verify_compressed(&config, &stark, missing_round, &public_inputs),
+            Err(VerificationError::MalformedProof(
+                ProofShapeError::NumQueryRounds { .. }
+            ))
+        ));
+    }
+}
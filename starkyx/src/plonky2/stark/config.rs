@@ -1,18 +1,32 @@
 use core::fmt::Debug;
 
 use plonky2::field::extension::{Extendable, FieldExtension};
-use plonky2::field::polynomial::PolynomialValues;
+#[cfg(not(all(feature = "simd", target_arch = "aarch64", target_feature = "neon")))]
+use plonky2::field::goldilocks_field::GoldilocksField;
+#[cfg(not(all(feature = "simd", target_arch = "aarch64", target_feature = "neon")))]
+use plonky2::field::packable::Packable;
+use plonky2::field::packed::PackedField;
+use plonky2::field::polynomial::{PolynomialCoeffs, PolynomialValues};
 use plonky2::fri::oracle::PolynomialBatch;
 use plonky2::fri::reduction_strategies::FriReductionStrategy;
 use plonky2::fri::{FriConfig, FriParams};
-use plonky2::hash::hash_types::RichField;
-use plonky2::plonk::config::{AlgebraicHasher, GenericConfig, PoseidonGoldilocksConfig};
+use plonky2::hash::hash_types::{HashOut, RichField};
+use plonky2::iop::challenger::{Challenger, RecursiveChallenger};
+use plonky2::plonk::circuit_builder::CircuitBuilder;
+use plonky2::plonk::config::{
+    AlgebraicHasher, GenericConfig, GenericHashOut, Hasher, KeccakGoldilocksConfig,
+    PoseidonGoldilocksConfig,
+};
 use plonky2::util::log2_strict;
 use plonky2::util::timing::TimingTree;
 use serde::de::DeserializeOwned;
 use serde::{Deserialize, Serialize};
 
+use super::fri_arity::FriArityStrategy;
+#[cfg(feature = "keccak")]
+use super::keccak::{KeccakSpongeGoldilocksConfig, KeccakSpongeHash};
 use crate::maybe_rayon::*;
+use crate::plonky2::ntt;
 use crate::trace::AirTrace;
 use crate::utils::serde::{deserialize_fri_config, serialize_fri_config};
 
@@ -21,7 +35,12 @@ pub trait CurtaConfig<const D: usize>:
 {
     type F: RichField + Extendable<D>;
     type FE: FieldExtension<D, BaseField = Self::F>;
-    type Hasher: AlgebraicHasher<Self::F>;
+    /// The hasher of the Merkle trees and of the transcript of proofs.
+    ///
+    /// Recursive verification requires this to be an `AlgebraicHasher`. Configurations of proofs
+    /// which are only verified natively can use a faster hasher, such as Keccak.
+    type Hasher: Hasher<Self::F>;
+    /// The hasher used in-circuit and for the commitments to public inputs.
     type InnerHasher: AlgebraicHasher<Self::F>;
     type GenericConfig: GenericConfig<
         D,
@@ -30,8 +49,21 @@ pub trait CurtaConfig<const D: usize>:
         Hasher = Self::Hasher,
         InnerHasher = Self::InnerHasher,
     >;
+    /// The packed field on which the prover evaluates the constraints.
+    ///
+    /// The packing only affects the speed of the prover, the proofs are the same for any packing.
+    type Packing: PackedField<Scalar = Self::F>;
 }
 
+/// The packing of Goldilocks elements used by the prover.
+///
+/// This is the packing chosen by plonky2, except with the `simd` feature on aarch64, where the
+/// elements are packed in NEON registers.
+#[cfg(not(all(feature = "simd", target_arch = "aarch64", target_feature = "neon")))]
+pub type GoldilocksPacking = <GoldilocksField as Packable>::Packing;
+#[cfg(all(feature = "simd", target_arch = "aarch64", target_feature = "neon"))]
+pub type GoldilocksPacking = crate::plonky2::packed::PackedGoldilocks;
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct StarkyConfig<C, const D: usize> {
     pub security_bits: usize,
@@ -49,6 +81,37 @@ pub struct StarkyConfig<C, const D: usize> {
     #[serde(deserialize_with = "deserialize_fri_config")]
     pub fri_config: FriConfig,
 
+    /// Whether the trace polynomials are blinded before committing.
+    ///
+    /// When set, each trace polynomial `t(X)` is replaced by `t(X) + Z_H(X) * r(X)` for a random
+    /// polynomial `r(X)` and the Merkle leaves are salted, so that the proof reveals nothing about
+    /// the witness. The committed polynomials then have degree less than `2 * num_rows`.
+    #[serde(default)]
+    pub zero_knowledge: bool,
+
+    /// A salt observed at the start of the Fiat-Shamir transcript.
+    ///
+    /// Proofs of the same statement with different salts have independent challenges, which
+    /// domain-separates the proofs of different applications and prevents their replay.
+    #[serde(default)]
+    pub transcript_salt: Option<[u8; 32]>,
+
+    /// Whether proofs carry a hash commitment to their public inputs.
+    ///
+    /// The commitment is checked by the verifier, so that applications which only need to bind
+    /// the proof to its public inputs, such as on-chain verifiers, can pass the digest around
+    /// instead of the full public input vector.
+    #[serde(default)]
+    pub commit_public_inputs: bool,
+
+    /// The strategy for the FRI folding arities, which replaces the reduction strategy of
+    /// `fri_config` when set.
+    ///
+    /// Unlike the reduction strategy of `fri_config`, the arities of a strategy are computed for
+    /// the degree of the traces of the configuration.
+    #[serde(default)]
+    pub fri_arity_strategy: Option<FriArityStrategy>,
+
     _marker: core::marker::PhantomData<C>,
 }
 
@@ -56,24 +119,217 @@ impl<C: CurtaConfig<D>, const D: usize> StarkyConfig<C, D> {
     /// A typical configuration with a rate of 2, resulting in fast but large proofs.
     /// Targets ~100 bit conjectured security.
     pub fn standard_fast_config(num_rows: usize) -> Self {
-        let degree_bits = log2_strict(num_rows);
-        Self {
-            security_bits: 100,
-            num_challenges: 2,
-            degree_bits,
-            fri_config: FriConfig {
+        Self::from_fri_config(
+            num_rows,
+            FriConfig {
                 rate_bits: 1,
                 cap_height: 4,
                 proof_of_work_bits: 16,
                 reduction_strategy: FriReductionStrategy::ConstantArityBits(4, 5),
                 num_query_rounds: 84,
             },
+        )
+    }
+
+    /// A configuration with a rate of 8, resulting in small proofs at the cost of a slower prover.
+    /// Targets ~100 bit conjectured security.
+    pub fn standard_100_bit_security(num_rows: usize) -> Self {
+        Self::from_fri_config(
+            num_rows,
+            FriConfig {
+                rate_bits: 3,
+                cap_height: 4,
+                proof_of_work_bits: 16,
+                reduction_strategy: FriReductionStrategy::ConstantArityBits(4, 5),
+                num_query_rounds: 28,
+            },
+        )
+    }
+
+    /// A configuration with a rate of 2 and fewer query rounds than `standard_fast_config`.
+    /// Targets ~80 bit conjectured security.
+    pub fn fast_80_bit(num_rows: usize) -> Self {
+        Self::from_fri_config(
+            num_rows,
+            FriConfig {
+                rate_bits: 1,
+                cap_height: 4,
+                proof_of_work_bits: 16,
+                reduction_strategy: FriReductionStrategy::ConstantArityBits(4, 5),
+                num_query_rounds: 64,
+            },
+        )
+    }
+
+    /// A configuration for `num_rows` rows with the given FRI parameters.
+    ///
+    /// The security level is set to the conjectured security of the FRI parameters.
+    pub fn from_fri_config(num_rows: usize, fri_config: FriConfig) -> Self {
+        let degree_bits = log2_strict(num_rows);
+        let mut config = Self {
+            security_bits: 0,
+            num_challenges: 2,
+            degree_bits,
+            fri_config,
+            zero_knowledge: false,
+            transcript_salt: None,
+            commit_public_inputs: false,
+            fri_arity_strategy: None,
             _marker: core::marker::PhantomData,
+        };
+        config.security_bits = config.conjectured_security_bits();
+        config
+    }
+
+    /// Returns the same configuration with `bits` bits of proof-of-work grinding.
+    ///
+    /// Each bit of grinding adds a bit of conjectured security, at the cost of doubling the work
+    /// of the prover to find the proof-of-work witness.
+    pub fn with_proof_of_work_bits(mut self, bits: u32) -> Self {
+        self.fri_config.proof_of_work_bits = bits;
+        self.security_bits = self.conjectured_security_bits();
+        self
+    }
+
+    /// The conjectured security of the FRI parameters, in bits.
+    ///
+    /// Every query round contributes `rate_bits` bits of security and the proof-of-work grinding
+    /// contributes `proof_of_work_bits` bits.
+    pub fn conjectured_security_bits(&self) -> usize {
+        self.fri_config.rate_bits * self.fri_config.num_query_rounds
+            + self.fri_config.proof_of_work_bits as usize
+    }
+
+    /// Returns the same configuration with zero-knowledge blinding enabled.
+    pub fn with_zero_knowledge(self) -> Self {
+        let num_rows = 1 << self.degree_bits;
+        let config = Self {
+            zero_knowledge: true,
+            ..self
+        };
+        assert!(
+            2 * config.num_blinding_coefficients() <= num_rows,
+            "Zero-knowledge blinding requires at least {} rows, got {}",
+            (4 * config.num_blinding_coefficients()).next_power_of_two(),
+            num_rows
+        );
+        config
+    }
+
+    /// Returns the same configuration with Merkle caps of height `cap_height`.
+    ///
+    /// Larger caps make the Merkle paths shorter, at the cost of larger caps in the proof.
+    pub fn with_cap_height(mut self, cap_height: usize) -> Self {
+        self.fri_config.cap_height = cap_height;
+        self
+    }
+
+    /// Returns the same configuration with the transcript salted by `salt`.
+    pub fn with_transcript_salt(mut self, salt: [u8; 32]) -> Self {
+        self.transcript_salt = Some(salt);
+        self
+    }
+
+    /// Returns the same configuration with a commitment to the public inputs in every proof.
+    pub fn with_public_inputs_commitment(mut self) -> Self {
+        self.commit_public_inputs = true;
+        self
+    }
+
+    /// Returns the same configuration with the FRI folding arities given by `strategy`.
+    ///
+    /// The arities trade the size of the proof against the cost of the verifier, in particular of
+    /// the recursive verifier, which grows with the arity of each layer.
+    pub fn with_fri_arity_strategy(mut self, strategy: FriArityStrategy) -> Self {
+        self.fri_arity_strategy = Some(strategy);
+        self
+    }
+
+    /// The commitment to `public_inputs` included in the proofs, if any.
+    pub fn public_inputs_commitment(&self, public_inputs: &[C::F]) -> Option<HashOut<C::F>> {
+        self.commit_public_inputs
+            .then(|| C::InnerHasher::hash_no_pad(public_inputs))
+    }
+
+    /// The transcript salt as field elements, one for every four bytes in little-endian order.
+    pub fn transcript_salt_elements(&self) -> Vec<C::F> {
+        self.transcript_salt
+            .iter()
+            .flat_map(|salt| salt.chunks_exact(4))
+            .map(|chunk| C::F::from_canonical_u32(u32::from_le_bytes(chunk.try_into().unwrap())))
+            .collect()
+    }
+
+    /// A new challenger which has observed the transcript salt.
+    pub fn challenger(&self) -> Challenger<C::F, C::Hasher> {
+        let mut challenger = Challenger::new();
+        challenger.observe_elements(&self.transcript_salt_elements());
+        challenger
+    }
+
+    /// A new recursive challenger which has observed the transcript salt.
+    pub fn recursive_challenger(
+        &self,
+        builder: &mut CircuitBuilder<C::F, D>,
+    ) -> RecursiveChallenger<C::F, C::InnerHasher, D> {
+        let mut challenger = RecursiveChallenger::new(builder);
+        let salt = self
+            .transcript_salt_elements()
+            .into_iter()
+            .map(|element| builder.constant(element))
+            .collect::<Vec<_>>();
+        challenger.observe_elements(&salt);
+        challenger
+    }
+
+    /// The number of random coefficients of the blinding polynomial `r(X)`.
+    ///
+    /// Every trace polynomial is opened at `zeta` and `g * zeta`, and every FRI query reveals its
+    /// value at a point `x` of the LDE domain, as well as at `g * x` through the quotient. Taking
+    /// `r(X)` with at least that many coefficients makes all the revealed values uniformly random.
+    pub fn num_blinding_coefficients(&self) -> usize {
+        if self.zero_knowledge {
+            2 * (self.fri_config.num_query_rounds + 1)
+        } else {
+            0
         }
     }
 
+    /// The number of bits in the degree bound of the committed polynomials.
+    ///
+    /// This is equal to `degree_bits`, unless the trace is blinded in which case the committed
+    /// polynomials have degree less than `2^{degree_bits + 1}`.
+    pub fn fri_degree_bits(&self) -> usize {
+        self.degree_bits + self.zero_knowledge as usize
+    }
+
+    /// A digest of the configuration, used to check that a proof was generated with it.
+    pub fn digest(&self) -> Vec<u8> {
+        let bytes = bincode::serialize(self).expect("Failed to serialize the configuration");
+        let elements = bytes
+            .into_iter()
+            .map(C::F::from_canonical_u8)
+            .collect::<Vec<_>>();
+        C::Hasher::hash_no_pad(&elements).to_bytes()
+    }
+
     pub fn fri_params(&self) -> FriParams {
-        self.fri_config.fri_params(self.degree_bits, false)
+        let degree_bits = self.fri_degree_bits();
+        match self.fri_arity_strategy {
+            Some(strategy) => {
+                let arity_bits = strategy.reduction_arity_bits(
+                    degree_bits,
+                    self.fri_config.rate_bits,
+                    self.fri_config.cap_height,
+                );
+                let fri_config = FriConfig {
+                    reduction_strategy: FriReductionStrategy::Fixed(arity_bits),
+                    ..self.fri_config.clone()
+                };
+                fri_config.fri_params(degree_bits, self.zero_knowledge)
+            }
+            None => self.fri_config.fri_params(degree_bits, self.zero_knowledge),
+        }
     }
 
     pub fn commit(
@@ -89,12 +345,55 @@ impl<C: CurtaConfig<D>, const D: usize> StarkyConfig<C, D> {
 
         let rate_bits = self.fri_config.rate_bits;
         let cap_height = self.fri_config.cap_height;
-        PolynomialBatch::<C::F, C::GenericConfig, D>::from_values(
-            trace_cols, rate_bits, false, cap_height, timing, None,
+
+        let root_table = ntt::root_table::<C::F>(self.fri_degree_bits() + rate_bits);
+
+        if !self.zero_knowledge {
+            return PolynomialBatch::<C::F, C::GenericConfig, D>::from_values(
+                trace_cols,
+                rate_bits,
+                false,
+                cap_height,
+                timing,
+                Some(&root_table),
+            );
+        }
+
+        let blinded_cols = trace_cols
+            .into_par_iter()
+            .map(|values| self.blind(values))
+            .collect::<Vec<_>>();
+        PolynomialBatch::<C::F, C::GenericConfig, D>::from_coeffs(
+            blinded_cols,
+            rate_bits,
+            true,
+            cap_height,
+            timing,
+            Some(&root_table),
         )
     }
+
+    /// Interpolates the column `values` and adds a random multiple of the vanishing polynomial
+    /// `Z_H(X) = X^n - 1` of the trace domain.
+    ///
+    /// The resulting polynomial agrees with the column on the trace domain and has degree less
+    /// than `2n`.
+    fn blind(&self, values: PolynomialValues<C::F>) -> PolynomialCoeffs<C::F> {
+        let num_rows = 1 << self.degree_bits;
+        debug_assert_eq!(values.len(), num_rows);
+
+        let mut coeffs = ntt::intt(values).coeffs;
+        coeffs.resize(2 * num_rows, C::F::ZERO);
+        for i in 0..self.num_blinding_coefficients() {
+            let r = C::F::rand();
+            coeffs[i] -= r;
+            coeffs[num_rows + i] += r;
+        }
+        PolynomialCoeffs::new(coeffs)
+    }
 }
 
+/// Poseidon over the Goldilocks field, with challenges in its quadratic extension (`D = 2`).
 #[derive(Debug, Copy, Clone, Eq, PartialEq, Serialize, Deserialize)]
 pub struct CurtaPoseidonGoldilocksConfig;
 
@@ -104,6 +403,49 @@ impl CurtaConfig<2> for CurtaPoseidonGoldilocksConfig {
     type Hasher = <PoseidonGoldilocksConfig as GenericConfig<2>>::Hasher;
     type InnerHasher = <PoseidonGoldilocksConfig as GenericConfig<2>>::InnerHasher;
     type GenericConfig = PoseidonGoldilocksConfig;
+    type Packing = GoldilocksPacking;
 }
 
 pub type PoseidonGoldilocksStarkConfig = StarkyConfig<CurtaPoseidonGoldilocksConfig, 2>;
+
+/// Keccak commitments over the Goldilocks field, with challenges in its quadratic extension
+/// (`D = 2`).
+///
+/// Proofs with this configuration are faster to generate, but can not be verified recursively,
+/// see `CurtaKeccakSpongeGoldilocksConfig` for Keccak commitments which can.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Serialize, Deserialize)]
+pub struct CurtaKeccakGoldilocksConfig;
+
+impl CurtaConfig<2> for CurtaKeccakGoldilocksConfig {
+    type F = <KeccakGoldilocksConfig as GenericConfig<2>>::F;
+    type FE = <KeccakGoldilocksConfig as GenericConfig<2>>::FE;
+    type Hasher = <KeccakGoldilocksConfig as GenericConfig<2>>::Hasher;
+    type InnerHasher = <KeccakGoldilocksConfig as GenericConfig<2>>::InnerHasher;
+    type GenericConfig = KeccakGoldilocksConfig;
+    type Packing = GoldilocksPacking;
+}
+
+pub type KeccakGoldilocksStarkConfig = StarkyConfig<CurtaKeccakGoldilocksConfig, 2>;
+
+/// Keccak commitments over the Goldilocks field which can be verified recursively, with
+/// challenges in its quadratic extension (`D = 2`).
+///
+/// The Merkle trees and the transcript are hashed with the sponge over the Keccak permutation of
+/// plonky2, which the recursive verifier computes in-circuit, see [`KeccakSpongeHash`]. Proofs are
+/// faster to generate than with Poseidon, but much more expensive to verify recursively.
+#[cfg(feature = "keccak")]
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Serialize, Deserialize)]
+pub struct CurtaKeccakSpongeGoldilocksConfig;
+
+#[cfg(feature = "keccak")]
+impl CurtaConfig<2> for CurtaKeccakSpongeGoldilocksConfig {
+    type F = <KeccakSpongeGoldilocksConfig as GenericConfig<2>>::F;
+    type FE = <KeccakSpongeGoldilocksConfig as GenericConfig<2>>::FE;
+    type Hasher = KeccakSpongeHash;
+    type InnerHasher = KeccakSpongeHash;
+    type GenericConfig = KeccakSpongeGoldilocksConfig;
+    type Packing = GoldilocksPacking;
+}
+
+#[cfg(feature = "keccak")]
+pub type KeccakSpongeGoldilocksStarkConfig = StarkyConfig<CurtaKeccakSpongeGoldilocksConfig, 2>;
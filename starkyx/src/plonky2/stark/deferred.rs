@@ -0,0 +1,238 @@
+//! Proofs with a deferred FRI argument.
+//!
+//! A `PartialStarkProof` contains the commitments of a proof and the openings of its polynomials
+//! at the challenge point of its own transcript, but no FRI argument. The openings of many partial
+//! proofs are then proven by a single FRI argument in an `AggregatedStarkProof`, which opens the
+//! polynomials of every proof at its own challenge points. This is much cheaper than a FRI
+//! argument for each proof when aggregating many tables.
+//!
+//! The FRI challenges of the aggregated proof are drawn from a transcript which observes the
+//! public inputs, global values, commitments, challenge points and openings of all the partial
+//! proofs, so that none of them can be changed after the FRI argument is generated.
+
+use core::iter::once;
+
+use plonky2::field::extension::Extendable;
+use plonky2::fri::proof::{FriChallenges, FriProof};
+use plonky2::fri::structure::{FriInstanceInfo, FriOpenings};
+use plonky2::hash::hash_types::RichField;
+use plonky2::hash::merkle_tree::MerkleCap;
+use plonky2::iop::challenger::Challenger;
+use serde::{Deserialize, Serialize};
+
+use super::config::{CurtaConfig, StarkyConfig};
+use super::proof::{ProofShapeError, StarkOpeningSet};
+use super::Starky;
+use crate::air::RAirData;
+
+/// The commitments and openings of a proof whose FRI argument is deferred.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(bound = "")]
+pub struct PartialStarkProof<F: RichField + Extendable<D>, C: CurtaConfig<D, F = F>, const D: usize>
+{
+    /// Merkle caps of LDEs of trace values for each round.
+    pub trace_caps: Vec<MerkleCap<F, C::Hasher>>,
+    /// Merkle cap of LDEs of the quotient polynomials.
+    pub quotient_polys_cap: MerkleCap<F, C::Hasher>,
+    /// Purported values of each polynomial at the challenge point.
+    pub openings: StarkOpeningSet<F, D>,
+    pub global_values: Vec<F>,
+}
+
+/// The challenges of a partial proof, drawn from its own transcript.
+#[derive(Debug, Clone)]
+pub struct PartialStarkProofChallenges<F: RichField + Extendable<D>, const D: usize> {
+    /// Random values used to combine STARK constraints.
+    pub stark_alphas: Vec<F>,
+    /// Random values of the AIR rounds.
+    pub stark_betas: Vec<F>,
+    /// Point at which the STARK polynomials are opened.
+    pub stark_zeta: F::Extension,
+}
+
+impl<F: RichField + Extendable<D>, C: CurtaConfig<D, F = F>, const D: usize>
+    PartialStarkProof<F, C, D>
+{
+    /// Checks that the proof has the shape expected by `stark`.
+    pub fn validate_shape<A: RAirData>(
+        &self,
+        stark: &Starky<A>,
+        config: &StarkyConfig<C, D>,
+    ) -> Result<(), ProofShapeError> {
+        let air = stark.air();
+        let cap_height = config.fri_config.cap_height;
+
+        ProofShapeError::check(
+            ProofShapeError::NumTraceCaps,
+            air.num_rounds(),
+            self.trace_caps.len(),
+        )?;
+        for cap in self.trace_caps.iter().chain(once(&self.quotient_polys_cap)) {
+            ProofShapeError::check(ProofShapeError::CapHeight, cap_height, cap.height())?;
+        }
+        ProofShapeError::check(
+            ProofShapeError::NumGlobalValues,
+            air.num_global_values(),
+            self.global_values.len(),
+        )?;
+        ProofShapeError::check(
+            ProofShapeError::NumLocalValues,
+            air.num_columns(),
+            self.openings.local_values.len(),
+        )?;
+        ProofShapeError::check(
+            ProofShapeError::NumNextValues,
+            stark.next_columns().len(),
+            self.openings.next_values.len(),
+        )?;
+        self.openings.validate_quotient_chunks(stark, config)?;
+        // Extra opening points are not supported in partial proofs.
+        ProofShapeError::check(
+            ProofShapeError::NumExtraOpenings,
+            0,
+            self.openings.extra_openings.len(),
+        )?;
+        Ok(())
+    }
+
+    /// Computes the challenges of the proof on its own transcript, as in `StarkProof`.
+    pub(crate) fn get_challenges<A: RAirData>(
+        &self,
+        config: &StarkyConfig<C, D>,
+        stark: &Starky<A>,
+        public_inputs: &[F],
+    ) -> PartialStarkProofChallenges<F, D> {
+        let mut challenger = config.challenger();
+
+        challenger.observe_elements(public_inputs);
+
+        let mut stark_betas = vec![];
+        for (round, cap) in stark.air().round_data().iter().zip(self.trace_caps.iter()) {
+            let (id_0, id_1) = round.global_values_range;
+            challenger.observe_elements(&self.global_values[id_0..id_1]);
+            challenger.observe_cap(cap);
+            stark_betas.extend(challenger.get_n_challenges(round.num_challenges));
+        }
+
+        let stark_alphas = challenger.get_n_challenges(config.num_challenges);
+        challenger.observe_cap(&self.quotient_polys_cap);
+        let stark_zeta = challenger.get_extension_challenge::<D>();
+
+        PartialStarkProofChallenges {
+            stark_alphas,
+            stark_betas,
+            stark_zeta,
+        }
+    }
+}
+
+/// Partial proofs of several STARKs with a single FRI argument for all their openings.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(bound = "")]
+pub struct AggregatedStarkProof<
+    F: RichField + Extendable<D>,
+    C: CurtaConfig<D, F = F>,
+    const D: usize,
+> {
+    pub proofs: Vec<PartialStarkProof<F, C, D>>,
+    /// A batch FRI argument for the openings of all the proofs.
+    pub opening_proof: FriProof<F, C::Hasher, D>,
+}
+
+impl<F: RichField + Extendable<D>, C: CurtaConfig<D, F = F>, const D: usize>
+    AggregatedStarkProof<F, C, D>
+{
+    /// The number of aggregated proofs.
+    pub fn num_proofs(&self) -> usize {
+        self.proofs.len()
+    }
+
+    /// The challenger of the FRI argument, which has observed the public inputs, the global
+    /// values, the commitments, the challenge point `zeta` and the openings of each of the partial
+    /// proofs.
+    pub(crate) fn challenger(
+        config: &StarkyConfig<C, D>,
+        proofs: &[PartialStarkProof<F, C, D>],
+        public_inputs: &[&[F]],
+        zetas: &[F::Extension],
+    ) -> Challenger<F, C::Hasher> {
+        let mut challenger = config.challenger();
+        for ((proof, public_inputs), zeta) in proofs.iter().zip(public_inputs).zip(zetas) {
+            challenger.observe_elements(public_inputs);
+            challenger.observe_elements(&proof.global_values);
+            for cap in proof
+                .trace_caps
+                .iter()
+                .chain(once(&proof.quotient_polys_cap))
+            {
+                challenger.observe_cap(cap);
+            }
+            challenger.observe_extension_element(zeta);
+            challenger.observe_openings(&proof.openings.to_fri_openings());
+        }
+        challenger
+    }
+
+    /// The FRI challenges of the proof, for the public inputs and challenge points of its partial
+    /// proofs.
+    pub(crate) fn fri_challenges(
+        &self,
+        config: &StarkyConfig<C, D>,
+        public_inputs: &[&[F]],
+        zetas: &[F::Extension],
+    ) -> FriChallenges<F, D> {
+        let FriProof {
+            commit_phase_merkle_caps,
+            final_poly,
+            pow_witness,
+            ..
+        } = &self.opening_proof;
+
+        Self::challenger(config, &self.proofs, public_inputs, zetas)
+            .fri_challenges::<C::GenericConfig, D>(
+                commit_phase_merkle_caps,
+                final_poly,
+                *pow_witness,
+                config.fri_degree_bits(),
+                &config.fri_config,
+            )
+    }
+
+    /// The FRI instance of the aggregated proof, in which the polynomials of the proof of each of
+    /// `starks` are opened at its challenge point `zeta` and at `zeta * g`.
+    ///
+    /// The oracles of each proof are its trace rounds followed by its quotient polynomials, in the
+    /// order of `starks`.
+    pub fn fri_instance<A: RAirData>(
+        config: &StarkyConfig<C, D>,
+        starks: &[&Starky<A>],
+        zetas: &[F::Extension],
+    ) -> FriInstanceInfo<F, D> {
+        let g = F::primitive_root_of_unity(config.degree_bits);
+        let mut oracles = vec![];
+        let mut batches = vec![];
+        for (stark, &zeta) in starks.iter().zip(zetas) {
+            let instance = Starky::batch_fri_instance(&[*stark], zeta, g, config);
+            let offset = oracles.len();
+            oracles.extend(instance.oracles);
+            batches.extend(instance.batches.into_iter().map(|mut batch| {
+                for polynomial in batch.polynomials.iter_mut() {
+                    polynomial.oracle_index += offset;
+                }
+                batch
+            }));
+        }
+        FriInstanceInfo { oracles, batches }
+    }
+
+    /// The FRI openings of the aggregated proof, in the order of `fri_instance`.
+    pub(crate) fn fri_openings(&self) -> FriOpenings<F, D> {
+        FriOpenings {
+            batches: self
+                .proofs
+                .iter()
+                .flat_map(|proof| proof.openings.to_fri_openings().batches)
+                .collect(),
+        }
+    }
+}
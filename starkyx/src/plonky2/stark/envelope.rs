@@ -0,0 +1,147 @@
+//! Versioned envelopes for the long-term storage of proofs.
+//!
+//! A `ProofEnvelope` records the AIR and the configuration which a proof was generated with, so
+//! that stored proofs can be matched with the circuit that verifies them and proofs of a different
+//! AIR or configuration are rejected before verification.
+
+use plonky2::field::extension::Extendable;
+use plonky2::hash::hash_types::RichField;
+use plonky2::plonk::config::{GenericHashOut, Hasher};
+use serde::{Deserialize, Serialize};
+
+use super::config::{CurtaConfig, StarkyConfig};
+use super::proof::StarkProof;
+use super::verifier::VerificationError;
+use super::Starky;
+
+/// The version of the envelope format.
+pub const PROOF_ENVELOPE_VERSION: u16 = 1;
+
+/// A proof together with the identifiers of the AIR and the configuration that produced it.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(bound = "")]
+pub struct ProofEnvelope<F: RichField + Extendable<D>, C: CurtaConfig<D, F = F>, const D: usize> {
+    /// The version of the envelope format.
+    pub version: u16,
+    /// The digest of the STARK which the proof is for.
+    pub air_id: Vec<u8>,
+    /// The digest of the configuration used to generate the proof.
+    pub config_digest: Vec<u8>,
+    pub proof: StarkProof<F, C, D>,
+}
+
+impl<F: RichField + Extendable<D>, C: CurtaConfig<D, F = F>, const D: usize>
+    ProofEnvelope<F, C, D>
+{
+    /// Wraps a proof of `stark` generated with `config`.
+    pub fn new<A: Serialize>(
+        config: &StarkyConfig<C, D>,
+        stark: &Starky<A>,
+        proof: StarkProof<F, C, D>,
+    ) -> Self {
+        Self {
+            version: PROOF_ENVELOPE_VERSION,
+            air_id: Self::air_id(stark),
+            config_digest: config.digest(),
+            proof,
+        }
+    }
+
+    /// The identifier of `stark`, which is a digest of its serialization.
+    pub fn air_id<A: Serialize>(stark: &Starky<A>) -> Vec<u8> {
+        let bytes = bincode::serialize(stark).expect("Failed to serialize the STARK");
+        let elements = bytes
+            .into_iter()
+            .map(F::from_canonical_u8)
+            .collect::<Vec<_>>();
+        C::Hasher::hash_no_pad(&elements).to_bytes()
+    }
+
+    /// Checks that the envelope is of the current version and that the proof was generated for
+    /// `stark` with `config`.
+    pub fn check<A: Serialize>(
+        &self,
+        config: &StarkyConfig<C, D>,
+        stark: &Starky<A>,
+    ) -> Result<(), VerificationError> {
+        if self.version != PROOF_ENVELOPE_VERSION {
+            return Err(VerificationError::UnsupportedEnvelopeVersion {
+                expected: PROOF_ENVELOPE_VERSION,
+                found: self.version,
+            });
+        }
+        if self.air_id != Self::air_id(stark) {
+            return Err(VerificationError::AirIdMismatch);
+        }
+        if self.config_digest != config.digest() {
+            return Err(VerificationError::ConfigDigestMismatch);
+        }
+        Ok(())
+    }
+
+    pub fn into_proof(self) -> StarkProof<F, C, D> {
+        self.proof
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use plonky2::field::goldilocks_field::GoldilocksField;
+
+    use super::*;
+    use crate::air::fibonacci::FibonacciAir;
+    use crate::math::prelude::*;
+    use crate::plonky2::stark::config::{
+        CurtaPoseidonGoldilocksConfig, PoseidonGoldilocksStarkConfig,
+    };
+    use crate::plonky2::stark::prover::StarkyProver;
+    use crate::plonky2::stark::verifier::StarkyVerifier;
+    use crate::trace::generator::ConstantGenerator;
+
+    #[test]
+    fn test_proof_envelope() {
+        type F = GoldilocksField;
+        type SC = PoseidonGoldilocksStarkConfig;
+        type Envelope = ProofEnvelope<F, CurtaPoseidonGoldilocksConfig, 2>;
+
+        let num_rows = 1 << 5usize;
+        let stark = Starky::<FibonacciAir>::new(FibonacciAir::new());
+
+        let public_inputs = [
+            F::ZERO,
+            F::ONE,
+            FibonacciAir::fibonacci(num_rows - 1, F::ZERO, F::ONE),
+        ];
+        let trace_generator =
+            ConstantGenerator::new(FibonacciAir::generate_trace(F::ZERO, F::ONE, num_rows));
+        let config = SC::standard_fast_config(num_rows);
+
+        let proof = StarkyProver::prove(&config, &stark, &trace_generator, &public_inputs).unwrap();
+        let envelope = Envelope::new(&config, &stark, proof);
+
+        let bytes = bincode::serialize(&envelope).unwrap();
+        let envelope: Envelope = bincode::deserialize(&bytes).unwrap();
+        StarkyVerifier::verify_envelope(&config, &stark, envelope.clone(), &public_inputs).unwrap();
+
+        // Envelopes of a different AIR, configuration or version are rejected.
+        let other_stark = Starky::new(FibonacciAir::new()).with_compressed_openings::<F>();
+        assert_eq!(
+            envelope.check(&config, &other_stark),
+            Err(VerificationError::AirIdMismatch)
+        );
+        let other_config = SC::standard_fast_config(num_rows).with_proof_of_work_bits(17);
+        assert_eq!(
+            envelope.check(&other_config, &stark),
+            Err(VerificationError::ConfigDigestMismatch)
+        );
+        let mut other_version = envelope;
+        other_version.version += 1;
+        assert_eq!(
+            StarkyVerifier::verify_envelope(&config, &stark, other_version, &public_inputs),
+            Err(VerificationError::UnsupportedEnvelopeVersion {
+                expected: PROOF_ENVELOPE_VERSION,
+                found: PROOF_ENVELOPE_VERSION + 1,
+            })
+        );
+    }
+}
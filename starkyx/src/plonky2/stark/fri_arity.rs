@@ -0,0 +1,221 @@
+//! Strategies for the sequence of FRI folding arities.
+//!
+//! Larger arities give fewer FRI layers, and so fewer Merkle caps and paths in the proof, at the
+//! cost of more openings per query and a more expensive recursive verifier. The strategy of a
+//! configuration determines the arities for the degree of its traces, so that the same strategy
+//! can be used for traces of any length.
+
+use serde::{Deserialize, Serialize};
+
+/// The sequence of arities with which the FRI polynomial is folded.
+///
+/// All arities are given as the number of bits of the arity. No layer is folded below a Merkle
+/// tree of `2^cap_height` leaves.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum FriArityStrategy {
+    /// Folds with an arity of `2^arity_bits` until the degree is at most `2^final_poly_bits`.
+    Constant {
+        arity_bits: usize,
+        final_poly_bits: usize,
+    },
+    /// Folds to a degree of `2^final_poly_bits` in as few layers of arity at most
+    /// `2^max_arity_bits` as possible, with arities which are as even as possible and in
+    /// decreasing order.
+    Decreasing {
+        max_arity_bits: usize,
+        final_poly_bits: usize,
+    },
+    /// Folds with an arity of `2^max_arity_bits`, and a smaller arity in the last layer, so that
+    /// the final polynomial has degree exactly `2^final_poly_bits`.
+    FinalPolyDegree {
+        max_arity_bits: usize,
+        final_poly_bits: usize,
+    },
+}
+
+impl FriArityStrategy {
+    /// The number of bits of the arity of each layer, for polynomials of degree `2^degree_bits`.
+    pub fn reduction_arity_bits(
+        &self,
+        degree_bits: usize,
+        rate_bits: usize,
+        cap_height: usize,
+    ) -> Vec<usize> {
+        match *self {
+            Self::Constant {
+                arity_bits,
+                final_poly_bits,
+            } => {
+                assert!(arity_bits > 0, "The arity must be at least 2");
+                let mut degree_bits = degree_bits;
+                let mut result = Vec::new();
+                while degree_bits > final_poly_bits
+                    && degree_bits >= arity_bits
+                    && degree_bits + rate_bits >= cap_height + arity_bits
+                {
+                    result.push(arity_bits);
+                    degree_bits -= arity_bits;
+                }
+                result
+            }
+            Self::Decreasing {
+                max_arity_bits,
+                final_poly_bits,
+            } => {
+                assert!(max_arity_bits > 0, "The arity must be at least 2");
+                let total =
+                    Self::total_reduction_bits(degree_bits, rate_bits, cap_height, final_poly_bits);
+                let num_layers = (total + max_arity_bits - 1) / max_arity_bits;
+                (0..num_layers)
+                    .map(|i| total / num_layers + (i < total % num_layers) as usize)
+                    .collect()
+            }
+            Self::FinalPolyDegree {
+                max_arity_bits,
+                final_poly_bits,
+            } => {
+                assert!(max_arity_bits > 0, "The arity must be at least 2");
+                let total =
+                    Self::total_reduction_bits(degree_bits, rate_bits, cap_height, final_poly_bits);
+                let mut result = vec![max_arity_bits; total / max_arity_bits];
+                if total % max_arity_bits != 0 {
+                    result.push(total % max_arity_bits);
+                }
+                result
+            }
+        }
+    }
+
+    /// The number of bits by which the degree is reduced, so that the final polynomial has degree
+    /// `2^final_poly_bits` and the last layer still has at least `2^cap_height` leaves.
+    fn total_reduction_bits(
+        degree_bits: usize,
+        rate_bits: usize,
+        cap_height: usize,
+        final_poly_bits: usize,
+    ) -> usize {
+        degree_bits
+            .saturating_sub(final_poly_bits)
+            .min((degree_bits + rate_bits).saturating_sub(cap_height))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use plonky2::field::goldilocks_field::GoldilocksField;
+    use plonky2::iop::witness::{PartialWitness, WitnessWrite};
+    use plonky2::plonk::circuit_builder::CircuitBuilder;
+    use plonky2::plonk::circuit_data::CircuitConfig;
+
+    use super::*;
+    use crate::air::fibonacci::FibonacciAir;
+    use crate::math::prelude::*;
+    use crate::plonky2::stark::config::{
+        CurtaConfig, CurtaPoseidonGoldilocksConfig, PoseidonGoldilocksStarkConfig,
+    };
+    use crate::plonky2::stark::gadget::StarkGadget;
+    use crate::plonky2::stark::prover::StarkyProver;
+    use crate::plonky2::stark::verifier::{set_stark_proof_target, StarkyVerifier};
+    use crate::plonky2::stark::Starky;
+    use crate::trace::generator::ConstantGenerator;
+
+    #[test]
+    fn test_fri_arity_sequences() {
+        let constant = FriArityStrategy::Constant {
+            arity_bits: 4,
+            final_poly_bits: 5,
+        };
+        assert_eq!(constant.reduction_arity_bits(20, 1, 4), vec![4, 4, 4, 4]);
+        assert_eq!(constant.reduction_arity_bits(5, 1, 4), vec![]);
+
+        let decreasing = FriArityStrategy::Decreasing {
+            max_arity_bits: 4,
+            final_poly_bits: 5,
+        };
+        assert_eq!(decreasing.reduction_arity_bits(20, 1, 4), vec![4, 4, 4, 3]);
+        assert_eq!(decreasing.reduction_arity_bits(18, 1, 4), vec![4, 3, 3, 3]);
+
+        let final_poly = FriArityStrategy::FinalPolyDegree {
+            max_arity_bits: 4,
+            final_poly_bits: 0,
+        };
+        assert_eq!(final_poly.reduction_arity_bits(14, 1, 0), vec![4, 4, 4, 2]);
+        // The last layer keeps at least `2^cap_height` leaves.
+        assert_eq!(final_poly.reduction_arity_bits(14, 1, 4), vec![4, 4, 3]);
+    }
+
+    #[test]
+    fn test_fibonacci_stark_fri_arity_strategies() {
+        type F = GoldilocksField;
+        type C = CurtaPoseidonGoldilocksConfig;
+        type SC = PoseidonGoldilocksStarkConfig;
+
+        let num_rows = 1 << 10usize;
+        let stark = Starky::<FibonacciAir>::new(FibonacciAir::new());
+
+        let public_inputs = [
+            F::ZERO,
+            F::ONE,
+            FibonacciAir::fibonacci(num_rows - 1, F::ZERO, F::ONE),
+        ];
+        let trace_generator =
+            ConstantGenerator::new(FibonacciAir::generate_trace(F::ZERO, F::ONE, num_rows));
+
+        let strategies = [
+            FriArityStrategy::Constant {
+                arity_bits: 2,
+                final_poly_bits: 3,
+            },
+            FriArityStrategy::Decreasing {
+                max_arity_bits: 3,
+                final_poly_bits: 2,
+            },
+            FriArityStrategy::FinalPolyDegree {
+                max_arity_bits: 4,
+                final_poly_bits: 1,
+            },
+        ];
+
+        for strategy in strategies {
+            let config = SC::standard_fast_config(num_rows).with_fri_arity_strategy(strategy);
+            let expected_arities = strategy.reduction_arity_bits(
+                config.fri_degree_bits(),
+                config.fri_config.rate_bits,
+                config.fri_config.cap_height,
+            );
+            assert_eq!(config.fri_params().reduction_arity_bits, expected_arities);
+
+            let proof =
+                StarkyProver::<F, C, 2>::prove(&config, &stark, &trace_generator, &public_inputs)
+                    .unwrap();
+            assert_eq!(
+                proof.air_proof.opening_proof.commit_phase_merkle_caps.len(),
+                expected_arities.len()
+            );
+            StarkyVerifier::verify(&config, &stark, proof.clone(), &public_inputs).unwrap();
+
+            // A proof with different arities does not verify.
+            let default_config = SC::standard_fast_config(num_rows);
+            assert!(
+                StarkyVerifier::verify(&default_config, &stark, proof.clone(), &public_inputs)
+                    .is_err()
+            );
+
+            // Verify the proof recursively.
+            let config_rec = CircuitConfig::standard_recursion_config();
+            let mut builder = CircuitBuilder::<F, 2>::new(config_rec);
+            let virtual_proof = builder.add_virtual_stark_proof(&stark, &config);
+            let public_input_targets = builder.add_virtual_targets(public_inputs.len());
+            builder.verify_stark_proof(&config, &stark, &virtual_proof, &public_input_targets);
+
+            let mut pw = PartialWitness::new();
+            pw.set_target_arr(&public_input_targets, &public_inputs)
+                .unwrap();
+            set_stark_proof_target(&mut pw, &virtual_proof, &proof).unwrap();
+
+            let data = builder.build::<<C as CurtaConfig<2>>::GenericConfig>();
+            let recursive_proof = data.prove(pw).unwrap();
+            data.verify(recursive_proof).unwrap();
+        }
+    }
+}
@@ -2,6 +2,7 @@ use plonky2::field::extension::Extendable;
 use plonky2::hash::hash_types::RichField;
 use plonky2::iop::target::Target;
 use plonky2::plonk::circuit_builder::CircuitBuilder;
+use plonky2::plonk::config::AlgebraicHasher;
 
 use super::config::{CurtaConfig, StarkyConfig};
 use super::proof::StarkProofTarget;
@@ -35,6 +36,8 @@ pub trait StarkGadget<
 
 impl<F: RichField + Extendable<D>, C: CurtaConfig<D, F = F, FE = F::Extension>, const D: usize>
     StarkGadget<F, C, D> for CircuitBuilder<F, D>
+where
+    C::Hasher: AlgebraicHasher<F>,
 {
     fn add_virtual_stark_proof<A>(
         &mut self,
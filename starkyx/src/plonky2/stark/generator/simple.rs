@@ -7,6 +7,7 @@ use plonky2::iop::generator::{GeneratedValues, SimpleGenerator};
 use plonky2::iop::target::Target;
 use plonky2::iop::witness::{PartitionWitness, Witness};
 use plonky2::plonk::circuit_data::CommonCircuitData;
+use plonky2::plonk::config::AlgebraicHasher;
 use plonky2::util::serialization::{Buffer, IoResult};
 use serde::{Deserialize, Serialize};
 
@@ -18,7 +19,7 @@ use crate::chip::trace::generator::ArithmeticGenerator;
 use crate::chip::{AirParameters, Chip};
 use crate::plonky2::stark::config::CurtaConfig;
 use crate::plonky2::stark::Starky;
-use crate::plonky2::Plonky2Air;
+use crate::plonky2::{Plonky2Air, ProverAir};
 use crate::utils::serde::{BufferRead, BufferWrite};
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -62,8 +63,9 @@ impl<L: AirParameters, C, const D: usize> SimpleGenerator<L::Field, D>
     for SimpleStarkWitnessGenerator<L, C, D>
 where
     L::Field: RichField + Extendable<D>,
-    Chip<L>: Plonky2Air<L::Field, D>,
+    Chip<L>: Plonky2Air<L::Field, D> + ProverAir<L::Field, C, D>,
     C: CurtaConfig<D, F = L::Field>,
+    C::Hasher: AlgebraicHasher<L::Field>,
 {
     fn id(&self) -> String {
         Self::id()
@@ -0,0 +1,337 @@
+//! Pluggable search of the proof-of-work witness of the FRI argument.
+//!
+//! Configurations with many grinding bits spend a significant part of the proving time searching
+//! for the proof-of-work witness at the end of the FRI commit phase. The search is abstracted by
+//! the `PowGrinder` trait, so that it can be run on all the cores of the prover or offloaded to
+//! dedicated hardware. `prove_openings_with_grinder` is equivalent to
+//! `PolynomialBatch::prove_openings`, except for the witness search.
+
+use itertools::Itertools;
+use plonky2::field::extension::{flatten, unflatten, Extendable};
+use plonky2::field::polynomial::{PolynomialCoeffs, PolynomialValues};
+use plonky2::fri::oracle::PolynomialBatch;
+use plonky2::fri::proof::{FriInitialTreeProof, FriProof, FriQueryRound, FriQueryStep};
+use plonky2::fri::structure::{FriBatchInfo, FriInstanceInfo};
+use plonky2::fri::{FriConfig, FriParams};
+use plonky2::hash::hash_types::RichField;
+use plonky2::hash::merkle_tree::MerkleTree;
+use plonky2::iop::challenger::Challenger;
+use plonky2::plonk::config::{GenericConfig, Hasher};
+use plonky2::plonk::plonk_common::reduce_with_powers;
+use plonky2::timed;
+use plonky2::util::reducing::ReducingFactor;
+use plonky2::util::reverse_index_bits_in_place;
+use plonky2::util::timing::TimingTree;
+
+use crate::maybe_rayon::*;
+
+/// A search for the proof-of-work witness of the FRI argument.
+pub trait PowGrinder<F: RichField, H: Hasher<F>> {
+    /// Returns a witness `w` such that the challenge drawn by `challenger` after observing `w` has
+    /// at least `min_leading_zeros` leading zeros.
+    fn grind(&self, challenger: &Challenger<F, H>, min_leading_zeros: u32) -> F;
+}
+
+/// Searches the witnesses in increasing order on the current thread.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SerialGrinder;
+
+/// Searches the witnesses on all the threads of the rayon pool.
+///
+/// Without the `parallel` feature, the search runs on the current thread.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ParallelGrinder;
+
+/// Whether `candidate` is a valid proof-of-work witness for the transcript of `challenger`.
+pub fn is_pow_witness<F: RichField, H: Hasher<F>>(
+    challenger: &Challenger<F, H>,
+    candidate: F,
+    min_leading_zeros: u32,
+) -> bool {
+    let mut challenger = challenger.clone();
+    challenger.observe_element(candidate);
+    challenger
+        .get_challenge()
+        .to_canonical_u64()
+        .leading_zeros()
+        >= min_leading_zeros
+}
+
+impl<F: RichField, H: Hasher<F>> PowGrinder<F, H> for SerialGrinder {
+    fn grind(&self, challenger: &Challenger<F, H>, min_leading_zeros: u32) -> F {
+        (0..=F::NEG_ONE.to_canonical_u64())
+            .map(F::from_canonical_u64)
+            .find(|&candidate| is_pow_witness(challenger, candidate, min_leading_zeros))
+            .expect("Proof of work failed. This is highly unlikely!")
+    }
+}
+
+impl<F: RichField, H: Hasher<F>> PowGrinder<F, H> for ParallelGrinder
+where
+    Challenger<F, H>: Sync,
+{
+    fn grind(&self, challenger: &Challenger<F, H>, min_leading_zeros: u32) -> F {
+        (0..=F::NEG_ONE.to_canonical_u64())
+            .into_par_iter()
+            .find_any(|&candidate| {
+                is_pow_witness(
+                    challenger,
+                    F::from_canonical_u64(candidate),
+                    min_leading_zeros,
+                )
+            })
+            .map(F::from_canonical_u64)
+            .expect("Proof of work failed. This is highly unlikely!")
+    }
+}
+
+/// Proves the openings of `instance` with a FRI argument, searching the proof-of-work witness
+/// with `grinder`.
+pub fn prove_openings_with_grinder<F, C, const D: usize>(
+    instance: &FriInstanceInfo<F, D>,
+    oracles: &[&PolynomialBatch<F, C, D>],
+    challenger: &mut Challenger<F, C::Hasher>,
+    fri_params: &FriParams,
+    grinder: &dyn PowGrinder<F, C::Hasher>,
+    timing: &mut TimingTree,
+) -> FriProof<F, C::Hasher, D>
+where
+    F: RichField + Extendable<D>,
+    C: GenericConfig<D, F = F>,
+{
+    let alpha = challenger.get_extension_challenge::<D>();
+    let mut alpha = ReducingFactor::new(alpha);
+
+    // The final polynomial is `sum_i alpha^(k_i) (F_i(X) - F_i(z_i))/(X - z_i)`, where `F_i` is
+    // the combination of the polynomials of the batch opened at `z_i`, as in `prove_openings`.
+    let mut final_poly = PolynomialCoeffs::empty();
+    for FriBatchInfo { point, polynomials } in &instance.batches {
+        let polys_coeff = polynomials
+            .iter()
+            .map(|fri_poly| &oracles[fri_poly.oracle_index].polynomials[fri_poly.polynomial_index]);
+        let composition_poly = alpha.reduce_polys_base(polys_coeff);
+        let quotient = composition_poly.divide_by_linear(*point);
+        alpha.shift_poly(&mut final_poly);
+        final_poly += quotient;
+    }
+    // Multiply the final polynomial by `X`, so that it has the maximum degree for which the
+    // low-degree test passes.
+    final_poly.coeffs.insert(0, F::Extension::ZERO);
+
+    let lde_final_poly = final_poly.lde(fri_params.config.rate_bits);
+    let lde_final_values = timed!(
+        timing,
+        log::Level::Debug,
+        "perform final FFT",
+        lde_final_poly.coset_fft(F::coset_shift().into())
+    );
+
+    let initial_merkle_trees = oracles
+        .iter()
+        .map(|oracle| &oracle.merkle_tree)
+        .collect::<Vec<_>>();
+    let n = lde_final_values.len();
+
+    let (trees, final_coeffs) = timed!(
+        timing,
+        log::Level::Debug,
+        "fold codewords in the commitment phase",
+        fri_committed_trees::<F, C, D>(lde_final_poly, lde_final_values, challenger, fri_params)
+    );
+
+    let pow_witness = timed!(
+        timing,
+        log::Level::Debug,
+        "find proof-of-work witness",
+        fri_proof_of_work(challenger, &fri_params.config, grinder)
+    );
+
+    let query_round_proofs = challenger
+        .get_n_challenges(fri_params.config.num_query_rounds)
+        .into_par_iter()
+        .map(|rand| {
+            let x_index = rand.to_canonical_u64() as usize % n;
+            fri_prover_query_round::<F, C, D>(&initial_merkle_trees, &trees, x_index, fri_params)
+        })
+        .collect();
+
+    FriProof {
+        commit_phase_merkle_caps: trees.iter().map(|t| t.cap.clone()).collect(),
+        query_round_proofs,
+        final_poly: final_coeffs,
+        pow_witness,
+    }
+}
+
+type FriCommittedTrees<F, C, const D: usize> = (
+    Vec<MerkleTree<F, <C as GenericConfig<D>>::Hasher>>,
+    PolynomialCoeffs<<F as Extendable<D>>::Extension>,
+);
+
+fn fri_committed_trees<F, C, const D: usize>(
+    mut coeffs: PolynomialCoeffs<F::Extension>,
+    mut values: PolynomialValues<F::Extension>,
+    challenger: &mut Challenger<F, C::Hasher>,
+    fri_params: &FriParams,
+) -> FriCommittedTrees<F, C, D>
+where
+    F: RichField + Extendable<D>,
+    C: GenericConfig<D, F = F>,
+{
+    let mut trees = Vec::with_capacity(fri_params.reduction_arity_bits.len());
+
+    let mut shift = F::MULTIPLICATIVE_GROUP_GENERATOR;
+    for arity_bits in &fri_params.reduction_arity_bits {
+        let arity = 1 << arity_bits;
+
+        reverse_index_bits_in_place(&mut values.values);
+        let chunked_values = values
+            .values
+            .par_chunks(arity)
+            .map(|chunk: &[F::Extension]| flatten(chunk))
+            .collect();
+        let tree = MerkleTree::<F, C::Hasher>::new(chunked_values, fri_params.config.cap_height);
+
+        challenger.observe_cap(&tree.cap);
+        trees.push(tree);
+
+        let beta = challenger.get_extension_challenge::<D>();
+        // `P(x) = sum_{i<r} x^i * P_i(x^r)` becomes `sum_{i<r} beta^i * P_i(x)`.
+        coeffs = PolynomialCoeffs::new(
+            coeffs
+                .coeffs
+                .par_chunks_exact(arity)
+                .map(|chunk| reduce_with_powers(chunk, beta))
+                .collect::<Vec<_>>(),
+        );
+        shift = shift.exp_u64(arity as u64);
+        values = coeffs.coset_fft(shift.into())
+    }
+
+    // The coefficients being removed here are always zero.
+    coeffs
+        .coeffs
+        .truncate(coeffs.len() >> fri_params.config.rate_bits);
+
+    challenger.observe_extension_elements(&coeffs.coeffs);
+    (trees, coeffs)
+}
+
+fn fri_proof_of_work<F: RichField, H: Hasher<F>>(
+    challenger: &mut Challenger<F, H>,
+    config: &FriConfig,
+    grinder: &dyn PowGrinder<F, H>,
+) -> F {
+    let min_leading_zeros = config.proof_of_work_bits + (64 - F::order().bits()) as u32;
+    let pow_witness = grinder.grind(challenger, min_leading_zeros);
+
+    // Observe the witness on the transcript, checking that the grinder found a valid one.
+    challenger.observe_element(pow_witness);
+    let pow_response = challenger.get_challenge();
+    assert!(
+        pow_response.to_canonical_u64().leading_zeros() >= min_leading_zeros,
+        "Invalid proof-of-work witness"
+    );
+    pow_witness
+}
+
+fn fri_prover_query_round<F, C, const D: usize>(
+    initial_merkle_trees: &[&MerkleTree<F, C::Hasher>],
+    trees: &[MerkleTree<F, C::Hasher>],
+    mut x_index: usize,
+    fri_params: &FriParams,
+) -> FriQueryRound<F, C::Hasher, D>
+where
+    F: RichField + Extendable<D>,
+    C: GenericConfig<D, F = F>,
+{
+    let initial_proof = initial_merkle_trees
+        .iter()
+        .map(|t| (t.get(x_index).to_vec(), t.prove(x_index)))
+        .collect::<Vec<_>>();
+    let steps = trees
+        .iter()
+        .zip_eq(&fri_params.reduction_arity_bits)
+        .map(|(tree, &arity_bits)| {
+            let evals = unflatten(tree.get(x_index >> arity_bits));
+            let merkle_proof = tree.prove(x_index >> arity_bits);
+            x_index >>= arity_bits;
+            FriQueryStep {
+                evals,
+                merkle_proof,
+            }
+        })
+        .collect();
+    FriQueryRound {
+        initial_trees_proof: FriInitialTreeProof {
+            evals_proofs: initial_proof,
+        },
+        steps,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use plonky2::field::goldilocks_field::GoldilocksField;
+
+    use super::*;
+    use crate::air::fibonacci::FibonacciAir;
+    use crate::plonky2::stark::config::{
+        CurtaPoseidonGoldilocksConfig, PoseidonGoldilocksStarkConfig,
+    };
+    use crate::plonky2::stark::prover::StarkyProver;
+    use crate::plonky2::stark::verifier::StarkyVerifier;
+    use crate::plonky2::stark::Starky;
+    use crate::trace::generator::ConstantGenerator;
+
+    #[test]
+    fn test_plonky2_fibonacci_stark_grinders() {
+        type F = GoldilocksField;
+        type C = CurtaPoseidonGoldilocksConfig;
+        type SC = PoseidonGoldilocksStarkConfig;
+
+        let num_rows = 1 << 5usize;
+        let stark = Starky::<FibonacciAir>::new(FibonacciAir::new());
+
+        let public_inputs = [
+            F::ZERO,
+            F::ONE,
+            FibonacciAir::fibonacci(num_rows - 1, F::ZERO, F::ONE),
+        ];
+        let trace_generator =
+            ConstantGenerator::new(FibonacciAir::generate_trace(F::ZERO, F::ONE, num_rows));
+        let config = SC::standard_fast_config(num_rows);
+
+        let serial_proof = StarkyProver::<F, C, 2>::prove_with_grinder(
+            &config,
+            &stark,
+            &trace_generator,
+            &public_inputs,
+            &SerialGrinder,
+        )
+        .unwrap();
+        StarkyVerifier::verify(&config, &stark, serial_proof.clone(), &public_inputs).unwrap();
+
+        let parallel_proof = StarkyProver::<F, C, 2>::prove_with_grinder(
+            &config,
+            &stark,
+            &trace_generator,
+            &public_inputs,
+            &ParallelGrinder,
+        )
+        .unwrap();
+        StarkyVerifier::verify(&config, &stark, parallel_proof.clone(), &public_inputs).unwrap();
+
+        // The trace is not blinded, so the grinders only change the witness and the queries.
+        assert_eq!(
+            serial_proof
+                .air_proof
+                .opening_proof
+                .commit_phase_merkle_caps,
+            parallel_proof
+                .air_proof
+                .opening_proof
+                .commit_phase_merkle_caps
+        );
+    }
+}
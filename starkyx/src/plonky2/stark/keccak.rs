@@ -0,0 +1,381 @@
+//! A hasher over the Keccak permutation of plonky2 which can be verified recursively.
+//!
+//! The Keccak hasher of plonky2 hashes the bytes of field elements with Keccak-256 into digests
+//! of bytes, which the recursive verifier of plonky2 can not handle, as it only verifies Merkle
+//! proofs and transcripts of hashers whose digests are field elements. [`KeccakSpongeHash`]
+//! instead hashes and compresses with the sponge over `KeccakPermutation`, the permutation of
+//! the Keccak challenger of plonky2, in the same way as the Poseidon hasher does over the Poseidon
+//! permutation. A state of 12 elements is permuted by hashing its bytes with Keccak-256, and
+//! the new state is read from the digest and its iterated hashes.
+//!
+//! In a circuit, the permutation is computed by [`keccak_permutation_circuit`] on the bits of
+//! the state. This makes the Merkle caps and the transcript of proofs committed with this hasher
+//! verifiable by the recursive verifier, at a cost of about 40,000 arithmetic gates per
+//! permutation.
+
+use core::array::from_fn;
+
+use plonky2::field::extension::quadratic::QuadraticExtension;
+use plonky2::field::extension::Extendable;
+use plonky2::field::goldilocks_field::GoldilocksField;
+use plonky2::field::types::{Field, Field64};
+use plonky2::hash::hash_types::{HashOut, RichField};
+use plonky2::hash::hashing::{compress, hash_n_to_hash_no_pad, PlonkyPermutation};
+use plonky2::hash::keccak::KeccakPermutation;
+use plonky2::iop::target::{BoolTarget, Target};
+use plonky2::plonk::circuit_builder::CircuitBuilder;
+use plonky2::plonk::config::{AlgebraicHasher, GenericConfig, Hasher};
+use serde::{Deserialize, Serialize};
+
+use crate::machine::hash::keccak::{pi_index, ROTATIONS, ROUND_CONSTANTS, STATE_SIZE};
+
+/// The number of elements of the state of the permutation.
+const WIDTH: usize = 12;
+/// The number of elements absorbed or squeezed by a permutation.
+const RATE: usize = 8;
+/// The number of elements of a digest.
+const NUM_HASH_OUT_ELTS: usize = 4;
+/// The rate of Keccak-256 in lanes.
+const KECCAK256_RATE_LANES: usize = 17;
+/// The number of lanes of a Keccak-256 digest.
+const KECCAK256_DIGEST_LANES: usize = 4;
+
+/// A lane of the Keccak state, as its bits in little-endian order.
+type Lane = [BoolTarget; 64];
+
+/// The hasher of the sponge over the Keccak permutation of plonky2.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Serialize, Deserialize)]
+pub struct KeccakSpongeHash;
+
+impl<F: RichField> Hasher<F> for KeccakSpongeHash {
+    const HASH_SIZE: usize = NUM_HASH_OUT_ELTS * 8;
+    type Hash = HashOut<F>;
+    type Permutation = KeccakPermutation<F>;
+
+    fn hash_no_pad(input: &[F]) -> Self::Hash {
+        hash_n_to_hash_no_pad::<F, Self::Permutation>(input)
+    }
+
+    fn two_to_one(left: Self::Hash, right: Self::Hash) -> Self::Hash {
+        compress::<F, Self::Permutation>(left, right)
+    }
+}
+
+impl<F: RichField> AlgebraicHasher<F> for KeccakSpongeHash {
+    type AlgebraicPermutation = KeccakPermutationTarget;
+
+    fn permute_swapped<const D: usize>(
+        inputs: Self::AlgebraicPermutation,
+        swap: BoolTarget,
+        builder: &mut CircuitBuilder<F, D>,
+    ) -> Self::AlgebraicPermutation
+    where
+        F: RichField + Extendable<D>,
+    {
+        let mut state = inputs.state;
+        for i in 0..NUM_HASH_OUT_ELTS {
+            let (left, right) = (state[i], state[NUM_HASH_OUT_ELTS + i]);
+            state[i] = builder.select(swap, right, left);
+            state[NUM_HASH_OUT_ELTS + i] = builder.select(swap, left, right);
+        }
+        KeccakPermutationTarget {
+            state: keccak_permutation_circuit(builder, &state),
+        }
+    }
+}
+
+/// The state of the Keccak permutation in a circuit.
+///
+/// As for the Poseidon permutation of targets, the state is only permuted by
+/// [`KeccakSpongeHash::permute_swapped`], which needs the circuit builder.
+#[derive(Debug, Copy, Clone, Default, Eq, PartialEq)]
+pub struct KeccakPermutationTarget {
+    state: [Target; WIDTH],
+}
+
+impl AsRef<[Target]> for KeccakPermutationTarget {
+    fn as_ref(&self) -> &[Target] {
+        &self.state
+    }
+}
+
+impl PlonkyPermutation<Target> for KeccakPermutationTarget {
+    const RATE: usize = RATE;
+    const WIDTH: usize = WIDTH;
+
+    fn new<I: IntoIterator<Item = Target>>(elts: I) -> Self {
+        let mut perm = Self::default();
+        perm.set_from_iter(elts, 0);
+        perm
+    }
+
+    fn set_elt(&mut self, elt: Target, idx: usize) {
+        self.state[idx] = elt;
+    }
+
+    fn set_from_slice(&mut self, elts: &[Target], start_idx: usize) {
+        self.state[start_idx..start_idx + elts.len()].copy_from_slice(elts);
+    }
+
+    fn set_from_iter<I: IntoIterator<Item = Target>>(&mut self, elts: I, start_idx: usize) {
+        for (s, e) in self.state[start_idx..].iter_mut().zip(elts) {
+            *s = e;
+        }
+    }
+
+    fn permute(&mut self) {
+        panic!("Call `permute_swapped()` instead of `permute()`");
+    }
+
+    fn squeeze(&self) -> &[Target] {
+        &self.state[..RATE]
+    }
+}
+
+/// Computes the Keccak permutation of plonky2 of `state` in a circuit.
+///
+/// The bytes of the canonical representatives of the elements are hashed with Keccak-256, and
+/// the digest is hashed again until there are enough 64-bit words for a new state. The native
+/// permutation skips the words which are not canonical representatives of field elements, which
+/// happens with a probability of about `2^-32` per word for the Goldilocks field. The circuit
+/// asserts instead that the first words are canonical, so it has no witness for these rare
+/// states.
+pub fn keccak_permutation_circuit<F: RichField + Extendable<D>, const D: usize>(
+    builder: &mut CircuitBuilder<F, D>,
+    state: &[Target],
+) -> [Target; WIDTH] {
+    assert_eq!(state.len(), WIDTH);
+    let mut message = state
+        .iter()
+        .map(|x| canonical_bits(builder, *x))
+        .collect::<Vec<_>>();
+    let mut words = Vec::with_capacity(WIDTH);
+    while words.len() < WIDTH {
+        let digest = keccak256_circuit(builder, &message);
+        words.extend(digest);
+        message = digest.to_vec();
+    }
+    from_fn(|i| element_from_bits(builder, &words[i]))
+}
+
+/// The bits of the canonical representative of `x`.
+fn canonical_bits<F: RichField + Extendable<D>, const D: usize>(
+    builder: &mut CircuitBuilder<F, D>,
+    x: Target,
+) -> Lane {
+    let (low, high) = builder.split_low_high(x, 32, 64);
+    let mut bits = builder.split_le(low, 32);
+    bits.extend(builder.split_le(high, 32));
+    assert_at_most(builder, &bits, F::ORDER - 1);
+    bits.try_into().unwrap()
+}
+
+/// The field element of the little-endian `bits`, which must be a canonical representative.
+fn element_from_bits<F: RichField + Extendable<D>, const D: usize>(
+    builder: &mut CircuitBuilder<F, D>,
+    bits: &Lane,
+) -> Target {
+    assert_at_most(builder, bits, F::ORDER - 1);
+    let low = builder.le_sum(bits[..32].iter());
+    let high = builder.le_sum(bits[32..].iter());
+    builder.mul_const_add(F::from_canonical_u64(1 << 32), high, low)
+}
+
+/// Asserts that the integer of the little-endian `bits` is at most `bound`.
+fn assert_at_most<F: RichField + Extendable<D>, const D: usize>(
+    builder: &mut CircuitBuilder<F, D>,
+    bits: &[BoolTarget],
+    bound: u64,
+) {
+    // Whether the most significant bits seen so far are equal to those of `bound`.
+    let mut prefix_equal = builder.one();
+    for (i, bit) in bits.iter().enumerate().rev() {
+        let prefix_and_bit = builder.mul(prefix_equal, bit.target);
+        if (bound >> i) & 1 == 1 {
+            prefix_equal = prefix_and_bit;
+        } else {
+            builder.assert_zero(prefix_and_bit);
+        }
+    }
+}
+
+/// The Keccak-256 digest of a message of whole lanes which fits in a single block.
+fn keccak256_circuit<F: RichField + Extendable<D>, const D: usize>(
+    builder: &mut CircuitBuilder<F, D>,
+    message: &[Lane],
+) -> [Lane; KECCAK256_DIGEST_LANES] {
+    assert!(message.len() < KECCAK256_RATE_LANES);
+    let zero = builder._false();
+    let one = builder._true();
+    let mut state = [[zero; 64]; STATE_SIZE];
+    state[..message.len()].copy_from_slice(message);
+    // The padding of Keccak-256, the byte `0x01` after the message and the bit `0x80` at the end
+    // of the block.
+    state[message.len()][0] = one;
+    state[KECCAK256_RATE_LANES - 1][63] = one;
+    keccak_f_circuit(builder, &mut state);
+    from_fn(|i| state[i])
+}
+
+/// The Keccak-f[1600] permutation on the bits of the lanes, see `keccak_f`.
+fn keccak_f_circuit<F: RichField + Extendable<D>, const D: usize>(
+    builder: &mut CircuitBuilder<F, D>,
+    state: &mut [Lane; STATE_SIZE],
+) {
+    for round_constant in ROUND_CONSTANTS {
+        // theta
+        let c = (0..5)
+            .map(|x| {
+                (1..5).fold(state[x], |acc, y| {
+                    xor_lanes(builder, &acc, &state[x + 5 * y])
+                })
+            })
+            .collect::<Vec<_>>();
+        let d = (0..5)
+            .map(|x| xor_lanes(builder, &c[(x + 4) % 5], &rotate_left(&c[(x + 1) % 5], 1)))
+            .collect::<Vec<_>>();
+        for (i, lane) in state.iter_mut().enumerate() {
+            *lane = xor_lanes(builder, lane, &d[i % 5]);
+        }
+
+        // rho and pi
+        let mut b = *state;
+        for (i, lane) in state.iter().enumerate() {
+            b[pi_index(i)] = rotate_left(lane, ROTATIONS[i]);
+        }
+
+        // chi
+        for (i, lane) in state.iter_mut().enumerate() {
+            let (x, y) = (i % 5, i / 5);
+            let (b_1, b_2) = (&b[(x + 1) % 5 + 5 * y], &b[(x + 2) % 5 + 5 * y]);
+            let not_and: Lane = from_fn(|z| and_not(builder, b_1[z], b_2[z]));
+            *lane = xor_lanes(builder, &b[i], &not_and);
+        }
+
+        // iota
+        for (z, bit) in state[0].iter_mut().enumerate() {
+            if (round_constant >> z) & 1 == 1 {
+                *bit = builder.not(*bit);
+            }
+        }
+    }
+}
+
+fn rotate_left(lane: &Lane, rotation: u32) -> Lane {
+    from_fn(|z| lane[(z + 64 - rotation as usize) % 64])
+}
+
+fn xor_lanes<F: RichField + Extendable<D>, const D: usize>(
+    builder: &mut CircuitBuilder<F, D>,
+    a: &Lane,
+    b: &Lane,
+) -> Lane {
+    from_fn(|z| xor(builder, a[z], b[z]))
+}
+
+/// `a ^ b = a + b - 2ab`.
+fn xor<F: RichField + Extendable<D>, const D: usize>(
+    builder: &mut CircuitBuilder<F, D>,
+    a: BoolTarget,
+    b: BoolTarget,
+) -> BoolTarget {
+    let sum = builder.add(a.target, b.target);
+    let xor = builder.arithmetic(-F::TWO, F::ONE, a.target, b.target, sum);
+    BoolTarget::new_unsafe(xor)
+}
+
+/// `!a & b = b - ab`.
+fn and_not<F: RichField + Extendable<D>, const D: usize>(
+    builder: &mut CircuitBuilder<F, D>,
+    a: BoolTarget,
+    b: BoolTarget,
+) -> BoolTarget {
+    let and_not = builder.arithmetic(-F::ONE, F::ONE, a.target, b.target, b.target);
+    BoolTarget::new_unsafe(and_not)
+}
+
+/// The plonky2 configuration over the Goldilocks field with [`KeccakSpongeHash`] for the Merkle
+/// trees and the transcript.
+#[derive(Debug, Copy, Clone, Default, Eq, PartialEq, Serialize, Deserialize)]
+pub struct KeccakSpongeGoldilocksConfig;
+
+impl GenericConfig<2> for KeccakSpongeGoldilocksConfig {
+    type F = GoldilocksField;
+    type FE = QuadraticExtension<Self::F>;
+    type Hasher = KeccakSpongeHash;
+    type InnerHasher = KeccakSpongeHash;
+}
+
+#[cfg(test)]
+mod tests {
+    use plonky2::field::types::Sample;
+    use plonky2::hash::merkle_proofs::MerkleProofTarget;
+    use plonky2::hash::merkle_tree::MerkleTree;
+    use plonky2::iop::witness::{PartialWitness, WitnessWrite};
+    use plonky2::plonk::circuit_data::CircuitConfig;
+    use plonky2::plonk::config::PoseidonGoldilocksConfig;
+
+    use super::*;
+
+    #[test]
+    fn test_keccak_permutation_circuit() {
+        type F = GoldilocksField;
+
+        let mut builder = CircuitBuilder::<F, 2>::new(CircuitConfig::standard_recursion_config());
+        let input = builder.add_virtual_targets(WIDTH);
+        let output = keccak_permutation_circuit(&mut builder, &input);
+        builder.register_public_inputs(&output);
+        let data = builder.build::<PoseidonGoldilocksConfig>();
+
+        let state = (0..WIDTH).map(|_| F::rand()).collect::<Vec<_>>();
+        let mut permutation = KeccakPermutation::new(state.iter().copied());
+        permutation.permute();
+
+        let mut pw = PartialWitness::new();
+        pw.set_target_arr(&input, &state).unwrap();
+        let proof = data.prove(pw).unwrap();
+        assert_eq!(proof.public_inputs, permutation.as_ref());
+        data.verify(proof).unwrap();
+    }
+
+    #[test]
+    fn test_keccak_sponge_merkle_proof_circuit() {
+        type F = GoldilocksField;
+        type H = KeccakSpongeHash;
+
+        // Two leaves of a digest each, compressed into the root, with the leaf on the right
+        // swapped before the permutation.
+        let leaves = (0..2)
+            .map(|_| {
+                (0..NUM_HASH_OUT_ELTS)
+                    .map(|_| F::rand())
+                    .collect::<Vec<_>>()
+            })
+            .collect::<Vec<_>>();
+        let tree = MerkleTree::<F, H>::new(leaves.clone(), 0);
+        let leaf_index = 1;
+        let proof = tree.prove(leaf_index);
+
+        let mut builder = CircuitBuilder::<F, 2>::new(CircuitConfig::standard_recursion_config());
+        let leaf = builder.add_virtual_targets(NUM_HASH_OUT_ELTS);
+        let index = builder.add_virtual_target();
+        let index_bits = builder.split_le(index, 1);
+        let cap = builder.add_virtual_cap(0);
+        let proof_target = MerkleProofTarget {
+            siblings: builder.add_virtual_hashes(proof.siblings.len()),
+        };
+        builder.verify_merkle_proof_to_cap::<H>(leaf.clone(), &index_bits, &cap, &proof_target);
+        let data = builder.build::<PoseidonGoldilocksConfig>();
+
+        let mut pw = PartialWitness::new();
+        pw.set_target_arr(&leaf, &leaves[leaf_index]).unwrap();
+        pw.set_target(index, F::from_canonical_usize(leaf_index))
+            .unwrap();
+        pw.set_cap_target(&cap, &tree.cap).unwrap();
+        for (target, sibling) in proof_target.siblings.iter().zip(proof.siblings.iter()) {
+            pw.set_hash_target(*target, *sibling).unwrap();
+        }
+        let proof = data.prove(pw).unwrap();
+        data.verify(proof).unwrap();
+    }
+}
@@ -13,23 +13,79 @@ use plonky2::plonk::circuit_builder::CircuitBuilder;
 use serde::{Deserialize, Serialize};
 
 use self::config::{CurtaConfig, StarkyConfig};
-use crate::air::RAirData;
+use self::outputs::OutputLayout;
+use crate::air::usage::{next_row_columns, ColumnUsageParser};
+use crate::air::{RAir, RAirData};
+use crate::math::prelude::*;
 
+pub mod commitment;
+pub mod compression;
 pub mod config;
+pub mod deferred;
+pub mod envelope;
+pub mod fri_arity;
 pub mod gadget;
+#[cfg(feature = "prover")]
 pub mod generator;
+#[cfg(feature = "prover")]
+pub mod grinding;
+#[cfg(feature = "keccak")]
+pub mod keccak;
+pub mod outputs;
 pub mod proof;
+#[cfg(feature = "prover")]
 pub mod prover;
+pub mod shared;
+#[cfg(feature = "prover")]
+pub mod spill;
+pub mod stream;
+#[cfg(feature = "prover")]
+pub mod table;
 pub mod verifier;
+pub mod versions;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Starky<A> {
     pub air: A,
+    /// The named outputs of the AIR in the global values.
+    #[serde(default)]
+    pub output_layout: OutputLayout,
+    /// The columns opened at the next row, or `None` if all the columns are opened.
+    #[serde(default)]
+    pub next_row_columns: Option<Vec<usize>>,
 }
 
 impl<A> Starky<A> {
     pub fn new(air: A) -> Self {
-        Self { air }
+        Self {
+            air,
+            output_layout: OutputLayout::new(),
+            next_row_columns: None,
+        }
+    }
+
+    /// Returns the same `Starky` opening at the next row only the columns whose next row values
+    /// are used by the constraints.
+    ///
+    /// This shrinks the proofs of wide traces in which most columns are only constrained on the
+    /// local row. The values of the columns which are not opened are never read by the verifier.
+    pub fn with_compressed_openings<F: Field>(self) -> Self
+    where
+        A: RAir<ColumnUsageParser<F>>,
+    {
+        let next_row_columns = next_row_columns::<F, A>(&self.air);
+        Self {
+            next_row_columns: Some(next_row_columns),
+            ..self
+        }
+    }
+
+    /// Returns the same `Starky` with the outputs given by `output_layout`.
+    pub fn with_output_layout(self, output_layout: OutputLayout) -> Self {
+        Self {
+            output_layout,
+            ..self
+        }
     }
 }
 
@@ -38,6 +94,38 @@ impl<A> Starky<A> {
         &self.air
     }
 
+    /// The number of chunks the quotient polynomial of each challenge is split into.
+    ///
+    /// The chunks have the degree bound of the committed trace polynomials. Without zero knowledge
+    /// this is `n`, and the quotient, of degree `(d - 1)n` for constraints of degree `d`, needs
+    /// `d - 1` chunks. With zero knowledge the trace is blinded to degree `2n`, so the quotient has
+    /// degree `2dn - n` and needs `d` chunks of degree `2n`.
+    pub fn num_quotient_chunks<
+        F: RichField + Extendable<D>,
+        C: CurtaConfig<D, F = F>,
+        const D: usize,
+    >(
+        &self,
+        config: &StarkyConfig<C, D>,
+    ) -> usize
+    where
+        A: RAirData,
+    {
+        self.num_quotient_chunks_with_blinding(config.zero_knowledge)
+    }
+
+    /// The number of quotient chunks when the trace is blinded if `blinding` is set.
+    pub fn num_quotient_chunks_with_blinding(&self, blinding: bool) -> usize
+    where
+        A: RAirData,
+    {
+        if blinding {
+            self.air().constraint_degree().max(1)
+        } else {
+            self.air().quotient_degree_factor()
+        }
+    }
+
     pub fn num_quotient_polys<
         F: RichField + Extendable<D>,
         C: CurtaConfig<D, F = F>,
@@ -49,46 +137,107 @@ impl<A> Starky<A> {
     where
         A: RAirData,
     {
-        self.air().quotient_degree_factor() * config.num_challenges
+        self.num_quotient_chunks(config) * config.num_challenges
+    }
+
+    /// The columns opened at the next row, in increasing order.
+    pub fn next_columns(&self) -> Vec<usize>
+    where
+        A: RAirData,
+    {
+        self.next_row_columns
+            .clone()
+            .unwrap_or_else(|| (0..self.air().num_columns()).collect())
+    }
+
+    /// Places the opened `next_values` at their columns, filling the columns which are not opened
+    /// with `zero`.
+    pub fn expand_next_values<T: Copy>(&self, next_values: &[T], zero: T) -> Vec<T>
+    where
+        A: RAirData,
+    {
+        let mut values = vec![zero; self.air().num_columns()];
+        for (&column, &value) in self.next_columns().iter().zip(next_values) {
+            values[column] = value;
+        }
+        values
     }
 
     /// Computes the FRI instance used to prove this Stark.
+    ///
+    /// The trace polynomials are opened at `zeta`, `zeta * g` and at each of the `extra_points`.
     pub fn fri_instance<F: RichField + Extendable<D>, C: CurtaConfig<D, F = F>, const D: usize>(
         &self,
         zeta: F::Extension,
         g: F,
+        extra_points: &[F::Extension],
+        config: &StarkyConfig<C, D>,
+    ) -> FriInstanceInfo<F, D>
+    where
+        A: RAirData,
+    {
+        let mut instance = Self::batch_fri_instance(&[self], zeta, g, config);
+        let trace_info = instance.batches[0].polynomials[..self.air().num_columns()].to_vec();
+        instance
+            .batches
+            .extend(extra_points.iter().map(|&point| FriBatchInfo {
+                point,
+                polynomials: trace_info.clone(),
+            }));
+        instance
+    }
+
+    /// Computes the FRI instance used to prove several Starks with a single FRI argument.
+    ///
+    /// The oracles of each Stark are its trace rounds followed by its quotient polynomials, in the
+    /// order of `starks`.
+    pub fn batch_fri_instance<
+        F: RichField + Extendable<D>,
+        C: CurtaConfig<D, F = F>,
+        const D: usize,
+    >(
+        starks: &[&Self],
+        zeta: F::Extension,
+        g: F,
         config: &StarkyConfig<C, D>,
     ) -> FriInstanceInfo<F, D>
     where
         A: RAirData,
     {
         let mut oracles = vec![];
-        let mut trace_info: Vec<FriPolynomialInfo> = vec![];
+        let mut zeta_info: Vec<FriPolynomialInfo> = vec![];
+        let mut zeta_next_info: Vec<FriPolynomialInfo> = vec![];
 
-        for round in self.air().round_data() {
-            let length = round.num_columns;
-            let round_info = FriPolynomialInfo::from_range(oracles.len(), 0..length);
-            trace_info.extend(round_info);
+        for stark in starks {
+            let mut trace_info: Vec<FriPolynomialInfo> = vec![];
+            for round in stark.air().round_data() {
+                let length = round.num_columns;
+                let round_info = FriPolynomialInfo::from_range(oracles.len(), 0..length);
+                trace_info.extend(round_info);
+                oracles.push(FriOracleInfo {
+                    num_polys: length,
+                    blinding: config.zero_knowledge,
+                });
+            }
+
+            let num_quotient_polys = stark.num_quotient_polys(config);
+            let quotient_info = FriPolynomialInfo::from_range(oracles.len(), 0..num_quotient_polys);
             oracles.push(FriOracleInfo {
-                num_polys: length,
-                blinding: false,
+                num_polys: num_quotient_polys,
+                blinding: config.zero_knowledge,
             });
-        }
 
-        let num_quotient_polys = self.air().quotient_degree_factor() * config.num_challenges;
-        let quotient_info = FriPolynomialInfo::from_range(oracles.len(), 0..num_quotient_polys);
-        oracles.push(FriOracleInfo {
-            num_polys: num_quotient_polys,
-            blinding: false,
-        });
+            zeta_info.extend(trace_info.iter().cloned().chain(quotient_info));
+            zeta_next_info.extend(stark.next_columns().into_iter().map(|i| trace_info[i]));
+        }
 
         let zeta_batch = FriBatchInfo {
             point: zeta,
-            polynomials: [trace_info.clone(), quotient_info].concat(),
+            polynomials: zeta_info,
         };
         let zeta_next_batch = FriBatchInfo {
             point: zeta.scalar_mul(g),
-            polynomials: trace_info,
+            polynomials: zeta_next_info,
         };
 
         let batches = vec![zeta_batch, zeta_next_batch];
@@ -105,6 +254,7 @@ impl<A> Starky<A> {
         builder: &mut CircuitBuilder<F, D>,
         zeta: ExtensionTarget<D>,
         g: F,
+        extra_points: &[ExtensionTarget<D>],
         config: &StarkyConfig<C, D>,
     ) -> FriInstanceInfoTarget<D>
     where
@@ -119,15 +269,15 @@ impl<A> Starky<A> {
             trace_info.extend(round_info);
             oracles.push(FriOracleInfo {
                 num_polys: length,
-                blinding: false,
+                blinding: config.zero_knowledge,
             });
         }
 
-        let num_quotient_polys = self.air().quotient_degree_factor() * config.num_challenges;
+        let num_quotient_polys = self.num_quotient_polys(config);
         let quotient_info = FriPolynomialInfo::from_range(oracles.len(), 0..num_quotient_polys);
         oracles.push(FriOracleInfo {
             num_polys: num_quotient_polys,
-            blinding: false,
+            blinding: config.zero_knowledge,
         });
 
         let zeta_batch = FriBatchInfoTarget {
@@ -138,10 +288,21 @@ impl<A> Starky<A> {
         let zeta_next = builder.mul_const_extension(g, zeta);
         let zeta_next_batch = FriBatchInfoTarget {
             point: zeta_next,
-            polynomials: trace_info,
+            polynomials: self
+                .next_columns()
+                .into_iter()
+                .map(|i| trace_info[i])
+                .collect(),
         };
 
-        let batches = vec![zeta_batch, zeta_next_batch];
+        let extra_batches = extra_points.iter().map(|&point| FriBatchInfoTarget {
+            point,
+            polynomials: trace_info.clone(),
+        });
+        let batches = [zeta_batch, zeta_next_batch]
+            .into_iter()
+            .chain(extra_batches)
+            .collect();
         FriInstanceInfoTarget { oracles, batches }
     }
 }
@@ -151,6 +312,7 @@ pub(crate) mod tests {
     use core::fmt::Debug;
 
     use plonky2::field::goldilocks_field::GoldilocksField;
+    use plonky2::fri::oracle::PolynomialBatch;
     use plonky2::iop::witness::{PartialWitness, WitnessWrite};
     use plonky2::plonk::circuit_data::CircuitConfig;
     use plonky2::plonk::config::AlgebraicHasher;
@@ -163,11 +325,18 @@ pub(crate) mod tests {
     use crate::chip::builder::tests::ArithmeticGenerator;
     use crate::chip::{AirParameters, Chip};
     use crate::math::prelude::*;
-    use crate::plonky2::stark::config::PoseidonGoldilocksStarkConfig;
+    use crate::plonky2::stark::config::{
+        CurtaPoseidonGoldilocksConfig, KeccakGoldilocksStarkConfig, PoseidonGoldilocksStarkConfig,
+    };
     use crate::plonky2::stark::gadget::StarkGadget;
+    use crate::plonky2::stark::proof::ProofShapeError;
     use crate::plonky2::stark::prover::StarkyProver;
-    use crate::plonky2::stark::verifier::StarkyVerifier;
-    use crate::plonky2::{Plonky2Air, StarkyAir};
+    use crate::plonky2::stark::shared::SharedInputsProof;
+    use crate::plonky2::stark::verifier::{
+        add_virtual_stark_proof_with_extra_points, set_stark_proof_target, StarkyVerifier,
+        VerificationError,
+    };
+    use crate::plonky2::{Plonky2Air, ProverAir};
     use crate::trace::generator::{ConstantGenerator, TraceGenerator};
 
     /// Generate the proof and verify as a stark
@@ -184,7 +353,7 @@ pub(crate) mod tests {
         public_inputs: &[F],
     ) where
         A: 'static + Debug + Send + Sync,
-        A: StarkyAir<F, D>,
+        A: ProverAir<F, C, D>,
         T: TraceGenerator<F, A>,
         T::Error: Into<anyhow::Error>,
     {
@@ -208,7 +377,7 @@ pub(crate) mod tests {
         public_inputs: &[F],
     ) where
         C::Hasher: AlgebraicHasher<F>,
-        Chip<L>: Plonky2Air<F, D>,
+        Chip<L>: Plonky2Air<F, D> + ProverAir<F, C, D>,
     {
         let config_rec = CircuitConfig::standard_recursion_config();
         let mut builder = CircuitBuilder::<F, D>::new(config_rec);
@@ -264,4 +433,1086 @@ pub(crate) mod tests {
         // Generate proof and verify as a stark
         test_starky(&stark, &config, &trace_generator, &public_inputs);
     }
+
+    #[test]
+    fn test_plonky2_fibonacci_stark_keccak() {
+        type F = GoldilocksField;
+        type SC = KeccakGoldilocksStarkConfig;
+
+        let num_rows = 1 << 5usize;
+        let stark = Starky::<FibonacciAir>::new(FibonacciAir::new());
+
+        let public_inputs = [
+            F::ZERO,
+            F::ONE,
+            FibonacciAir::fibonacci(num_rows - 1, F::ZERO, F::ONE),
+        ];
+        let trace_generator =
+            ConstantGenerator::new(FibonacciAir::generate_trace(F::ZERO, F::ONE, num_rows));
+
+        // The commitments are hashed with Keccak while the public inputs commitment still uses
+        // the inner hasher.
+        let config = SC::standard_fast_config(num_rows).with_public_inputs_commitment();
+        test_starky(&stark, &config, &trace_generator, &public_inputs);
+    }
+
+    #[cfg(feature = "keccak")]
+    #[test]
+    fn test_plonky2_fibonacci_stark_keccak_sponge() {
+        use crate::plonky2::stark::config::KeccakSpongeGoldilocksStarkConfig;
+
+        type F = GoldilocksField;
+        type SC = KeccakSpongeGoldilocksStarkConfig;
+
+        let num_rows = 1 << 5usize;
+        let stark = Starky::<FibonacciAir>::new(FibonacciAir::new());
+
+        let public_inputs = [
+            F::ZERO,
+            F::ONE,
+            FibonacciAir::fibonacci(num_rows - 1, F::ZERO, F::ONE),
+        ];
+        let trace_generator =
+            ConstantGenerator::new(FibonacciAir::generate_trace(F::ZERO, F::ONE, num_rows));
+
+        // The commitments and the public inputs commitment are both hashed with the Keccak sponge.
+        let config = SC::standard_fast_config(num_rows).with_public_inputs_commitment();
+        test_starky(&stark, &config, &trace_generator, &public_inputs);
+    }
+
+    #[test]
+    fn test_plonky2_fibonacci_stark_spill() {
+        type F = GoldilocksField;
+        type C = CurtaPoseidonGoldilocksConfig;
+        type SC = PoseidonGoldilocksStarkConfig;
+
+        let num_rows = 1 << 5usize;
+        let stark = Starky::<FibonacciAir>::new(FibonacciAir::new());
+
+        let public_inputs = [
+            F::ZERO,
+            F::ONE,
+            FibonacciAir::fibonacci(num_rows - 1, F::ZERO, F::ONE),
+        ];
+        let trace_generator =
+            ConstantGenerator::new(FibonacciAir::generate_trace(F::ZERO, F::ONE, num_rows));
+        let config = SC::standard_fast_config(num_rows);
+
+        let spill_dir =
+            std::env::temp_dir().join(format!("starkyx-spill-{:016x}", rand::random::<u64>()));
+        std::fs::create_dir_all(&spill_dir).unwrap();
+        let proof = StarkyProver::<F, C, 2>::prove_with_spill(
+            &config,
+            &stark,
+            &trace_generator,
+            &public_inputs,
+            &spill_dir,
+        )
+        .unwrap();
+        StarkyVerifier::verify(&config, &stark, proof, &public_inputs).unwrap();
+
+        // The spill files are removed once the rounds are read back.
+        assert_eq!(std::fs::read_dir(&spill_dir).unwrap().count(), 0);
+        std::fs::remove_dir(&spill_dir).unwrap();
+    }
+
+    #[test]
+    fn test_plonky2_fibonacci_stark_cap_height() {
+        type F = GoldilocksField;
+        type C = CurtaPoseidonGoldilocksConfig;
+        type SC = PoseidonGoldilocksStarkConfig;
+
+        let num_rows = 1 << 5usize;
+        let stark = Starky::<FibonacciAir>::new(FibonacciAir::new());
+
+        let public_inputs = [
+            F::ZERO,
+            F::ONE,
+            FibonacciAir::fibonacci(num_rows - 1, F::ZERO, F::ONE),
+        ];
+        let trace_generator =
+            ConstantGenerator::new(FibonacciAir::generate_trace(F::ZERO, F::ONE, num_rows));
+        let config = SC::standard_fast_config(num_rows);
+
+        // Proofs with larger and smaller caps verify against the same configuration.
+        for cap_height in [2, config.fri_config.cap_height + 1] {
+            let prover_config = config.clone().with_cap_height(cap_height);
+            let proof = StarkyProver::<F, C, 2>::prove(
+                &prover_config,
+                &stark,
+                &trace_generator,
+                &public_inputs,
+            )
+            .unwrap();
+            assert_eq!(proof.degree_bits, config.degree_bits);
+            assert_eq!(proof.cap_height, cap_height);
+            StarkyVerifier::verify(&config, &stark, proof, &public_inputs).unwrap();
+        }
+
+        let mut proof =
+            StarkyProver::<F, C, 2>::prove(&config, &stark, &trace_generator, &public_inputs)
+                .unwrap();
+        proof.cap_height = 10;
+        assert_eq!(
+            StarkyVerifier::verify(&config, &stark, proof, &public_inputs),
+            Err(VerificationError::IncompatibleCapHeight {
+                max: config.fri_degree_bits() + config.fri_config.rate_bits,
+                found: 10
+            })
+        );
+    }
+
+    #[test]
+    fn test_plonky2_fibonacci_stark_challenges() {
+        type F = GoldilocksField;
+        type C = CurtaPoseidonGoldilocksConfig;
+        type SC = PoseidonGoldilocksStarkConfig;
+
+        let num_rows = 1 << 5usize;
+        let stark = Starky::<FibonacciAir>::new(FibonacciAir::new());
+
+        let public_inputs = [
+            F::ZERO,
+            F::ONE,
+            FibonacciAir::fibonacci(num_rows - 1, F::ZERO, F::ONE),
+        ];
+        let trace_generator =
+            ConstantGenerator::new(FibonacciAir::generate_trace(F::ZERO, F::ONE, num_rows));
+        let config = SC::standard_fast_config(num_rows);
+        let proof =
+            StarkyProver::<F, C, 2>::prove(&config, &stark, &trace_generator, &public_inputs)
+                .unwrap();
+
+        // The replayed transcript is deterministic and accepted by the verifier.
+        let challenges = proof.challenges(&config, &stark, &public_inputs).unwrap();
+        let replayed = proof.challenges(&config, &stark, &public_inputs).unwrap();
+        assert_eq!(challenges.stark_alphas.len(), config.num_challenges);
+        assert_eq!(challenges.stark_alphas, replayed.stark_alphas);
+        assert_eq!(challenges.stark_zeta, replayed.stark_zeta);
+        assert_eq!(
+            challenges.fri_challenges.fri_query_indices,
+            replayed.fri_challenges.fri_query_indices
+        );
+        StarkyVerifier::verify_with_challenges(
+            &config,
+            &stark,
+            proof.air_proof.clone(),
+            &public_inputs,
+            &proof.global_values,
+            challenges,
+        )
+        .unwrap();
+
+        // The public inputs are part of the transcript.
+        let mut other_inputs = public_inputs;
+        other_inputs[2] += F::ONE;
+        let other = proof.challenges(&config, &stark, &other_inputs).unwrap();
+        assert_ne!(other.stark_zeta, replayed.stark_zeta);
+
+        let mut malformed = proof;
+        malformed.degree_bits += 1;
+        assert!(malformed
+            .challenges(&config, &stark, &public_inputs)
+            .is_err());
+    }
+
+    #[test]
+    fn test_plonky2_fibonacci_stark_two_phase_verification() {
+        type F = GoldilocksField;
+        type C = CurtaPoseidonGoldilocksConfig;
+        type SC = PoseidonGoldilocksStarkConfig;
+
+        let num_rows = 1 << 5usize;
+        let stark = Starky::<FibonacciAir>::new(FibonacciAir::new());
+
+        let public_inputs = [
+            F::ZERO,
+            F::ONE,
+            FibonacciAir::fibonacci(num_rows - 1, F::ZERO, F::ONE),
+        ];
+        let trace_generator =
+            ConstantGenerator::new(FibonacciAir::generate_trace(F::ZERO, F::ONE, num_rows));
+        let config = SC::standard_fast_config(num_rows);
+        let proof =
+            StarkyProver::<F, C, 2>::prove(&config, &stark, &trace_generator, &public_inputs)
+                .unwrap();
+
+        let challenges = proof.challenges(&config, &stark, &public_inputs).unwrap();
+        StarkyVerifier::verify_constraints_at_zeta(
+            &config,
+            &stark,
+            &proof,
+            &public_inputs,
+            &challenges,
+        )
+        .unwrap();
+        StarkyVerifier::verify_fri(&config, &stark, &proof, &challenges).unwrap();
+
+        // Inconsistent openings are rejected by the first phase.
+        let mut bad_openings = proof.clone();
+        bad_openings.air_proof.openings.local_values[0] += <F as Extendable<2>>::Extension::ONE;
+        let challenges = bad_openings
+            .challenges(&config, &stark, &public_inputs)
+            .unwrap();
+        assert!(matches!(
+            StarkyVerifier::verify_constraints_at_zeta(
+                &config,
+                &stark,
+                &bad_openings,
+                &public_inputs,
+                &challenges,
+            ),
+            Err(VerificationError::QuotientMismatch { .. })
+        ));
+
+        // A forged FRI argument passes the first phase and is rejected by the second.
+        let mut bad_fri = proof;
+        bad_fri.air_proof.opening_proof.final_poly.coeffs[0] +=
+            <F as Extendable<2>>::Extension::ONE;
+        let challenges = bad_fri.challenges(&config, &stark, &public_inputs).unwrap();
+        StarkyVerifier::verify_constraints_at_zeta(
+            &config,
+            &stark,
+            &bad_fri,
+            &public_inputs,
+            &challenges,
+        )
+        .unwrap();
+        assert!(matches!(
+            StarkyVerifier::verify_fri(&config, &stark, &bad_fri, &challenges),
+            Err(VerificationError::InvalidFriProof(_))
+        ));
+    }
+
+    #[test]
+    fn test_plonky2_fibonacci_stark_verify_batch() {
+        type F = GoldilocksField;
+        type C = CurtaPoseidonGoldilocksConfig;
+        type SC = PoseidonGoldilocksStarkConfig;
+
+        let num_rows = 1 << 5usize;
+        let stark = Starky::<FibonacciAir>::new(FibonacciAir::new());
+        let config = SC::standard_fast_config(num_rows);
+
+        let inputs = (0..4u64)
+            .map(|i| {
+                let x0 = F::from_canonical_u64(i);
+                [
+                    x0,
+                    F::ONE,
+                    FibonacciAir::fibonacci(num_rows - 1, x0, F::ONE),
+                ]
+            })
+            .collect::<Vec<_>>();
+        let mut proofs = inputs
+            .iter()
+            .enumerate()
+            .map(|(i, public_inputs)| {
+                let trace_generator = ConstantGenerator::new(FibonacciAir::generate_trace(
+                    public_inputs[0],
+                    F::ONE,
+                    num_rows,
+                ));
+                // Proofs with different cap heights are verified with different parameters.
+                let prover_config = config.clone().with_cap_height(i % 2);
+                let proof = StarkyProver::<F, C, 2>::prove(
+                    &prover_config,
+                    &stark,
+                    &trace_generator,
+                    public_inputs,
+                )
+                .unwrap();
+                (proof, public_inputs.as_slice())
+            })
+            .collect::<Vec<_>>();
+
+        let results = StarkyVerifier::verify_batch(&config, &stark, &proofs);
+        assert_eq!(results, vec![Ok(()); 4]);
+
+        // Invalid proofs are reported at their index without affecting the others.
+        proofs[1].1 = inputs[0].as_slice();
+        proofs[2].0.degree_bits += 1;
+        let results = StarkyVerifier::verify_batch(&config, &stark, &proofs);
+        assert_eq!(results[0], Ok(()));
+        assert!(results[1].is_err());
+        assert!(matches!(
+            results[2],
+            Err(VerificationError::DegreeBitsMismatch { .. })
+        ));
+        assert_eq!(results[3], Ok(()));
+    }
+
+    #[test]
+    fn test_plonky2_fibonacci_stark_fixed_verifier() {
+        type F = GoldilocksField;
+        type C = CurtaPoseidonGoldilocksConfig;
+        type SC = PoseidonGoldilocksStarkConfig;
+
+        let num_rows = 1 << 5usize;
+        let stark = Starky::<FibonacciAir>::new(FibonacciAir::new());
+
+        let public_inputs = [
+            F::ZERO,
+            F::ONE,
+            FibonacciAir::fibonacci(num_rows - 1, F::ZERO, F::ONE),
+        ];
+        let trace_generator =
+            ConstantGenerator::new(FibonacciAir::generate_trace(F::ZERO, F::ONE, num_rows));
+        let config = SC::standard_fast_config(num_rows);
+        let proof =
+            StarkyProver::<F, C, 2>::prove(&config, &stark, &trace_generator, &public_inputs)
+                .unwrap();
+
+        StarkyVerifier::verify_fixed::<_, 2, 2>(&config, &stark, proof.clone(), &public_inputs)
+            .unwrap();
+
+        // The specialization rejects the same proofs as the generic verifier.
+        let mut bad_openings = proof.clone();
+        bad_openings.air_proof.openings.local_values[1] += <F as Extendable<2>>::Extension::ONE;
+        assert_eq!(
+            StarkyVerifier::verify_fixed::<_, 2, 2>(
+                &config,
+                &stark,
+                bad_openings.clone(),
+                &public_inputs
+            ),
+            StarkyVerifier::verify(&config, &stark, bad_openings, &public_inputs)
+        );
+
+        // AIRs and configurations of a different shape are rejected.
+        assert_eq!(
+            StarkyVerifier::verify_fixed::<_, 3, 2>(&config, &stark, proof.clone(), &public_inputs),
+            Err(VerificationError::SpecializationMismatch)
+        );
+        assert_eq!(
+            StarkyVerifier::verify_fixed::<_, 2, 3>(&config, &stark, proof, &public_inputs),
+            Err(VerificationError::SpecializationMismatch)
+        );
+    }
+
+    #[test]
+    fn test_plonky2_fibonacci_stark_security_presets() {
+        type F = GoldilocksField;
+        type SC = PoseidonGoldilocksStarkConfig;
+
+        let num_rows = 1 << 5usize;
+        let air = FibonacciAir::new();
+        let stark = Starky::<FibonacciAir>::new(air);
+
+        let public_inputs = [
+            F::ZERO,
+            F::ONE,
+            FibonacciAir::fibonacci(num_rows - 1, F::ZERO, F::ONE),
+        ];
+
+        let trace = FibonacciAir::generate_trace(F::ZERO, F::ONE, num_rows);
+        let trace_generator = ConstantGenerator::new(trace);
+
+        let standard = SC::standard_100_bit_security(num_rows);
+        assert_eq!(standard.conjectured_security_bits(), 100);
+        test_starky(&stark, &standard, &trace_generator, &public_inputs);
+
+        let fast = SC::fast_80_bit(num_rows);
+        assert_eq!(fast.conjectured_security_bits(), 80);
+        test_starky(&stark, &fast, &trace_generator, &public_inputs);
+
+        let grinding = SC::fast_80_bit(num_rows).with_proof_of_work_bits(20);
+        assert_eq!(grinding.security_bits, 84);
+        test_starky(&stark, &grinding, &trace_generator, &public_inputs);
+    }
+
+    #[test]
+    fn test_plonky2_fibonacci_stark_transcript_salt() {
+        type F = GoldilocksField;
+        type C = CurtaPoseidonGoldilocksConfig;
+        type SC = PoseidonGoldilocksStarkConfig;
+
+        let num_rows = 1 << 5usize;
+        let air = FibonacciAir::new();
+        let stark = Starky::<FibonacciAir>::new(air);
+
+        let public_inputs = [
+            F::ZERO,
+            F::ONE,
+            FibonacciAir::fibonacci(num_rows - 1, F::ZERO, F::ONE),
+        ];
+
+        let trace = FibonacciAir::generate_trace(F::ZERO, F::ONE, num_rows);
+        let trace_generator = ConstantGenerator::new(trace);
+
+        let config = SC::standard_fast_config(num_rows).with_transcript_salt([7u8; 32]);
+        let proof =
+            StarkyProver::<F, C, 2>::prove(&config, &stark, &trace_generator, &public_inputs)
+                .unwrap();
+        StarkyVerifier::verify(&config, &stark, proof.clone(), &public_inputs).unwrap();
+
+        // The proof does not verify without the salt or with a different salt.
+        let unsalted = SC::standard_fast_config(num_rows);
+        assert!(StarkyVerifier::verify(&unsalted, &stark, proof.clone(), &public_inputs).is_err());
+        let other_salt = SC::standard_fast_config(num_rows).with_transcript_salt([8u8; 32]);
+        assert!(
+            StarkyVerifier::verify(&other_salt, &stark, proof.clone(), &public_inputs).is_err()
+        );
+
+        // Verify the salted proof recursively.
+        let config_rec = CircuitConfig::standard_recursion_config();
+        let mut builder = CircuitBuilder::<F, 2>::new(config_rec);
+        let virtual_proof = builder.add_virtual_stark_proof(&stark, &config);
+        let public_input_targets = builder.add_virtual_targets(public_inputs.len());
+        builder.verify_stark_proof(&config, &stark, &virtual_proof, &public_input_targets);
+
+        let mut pw = PartialWitness::new();
+        for (&pi_t, &pi) in public_input_targets.iter().zip(public_inputs.iter()) {
+            pw.set_target(pi_t, pi).unwrap();
+        }
+        set_stark_proof_target(&mut pw, &virtual_proof, &proof).unwrap();
+
+        let data = builder.build::<<C as CurtaConfig<2>>::GenericConfig>();
+        let recursive_proof = data.prove(pw).unwrap();
+        data.verify(recursive_proof).unwrap();
+    }
+
+    #[test]
+    fn test_plonky2_fibonacci_stark_public_inputs_commitment() {
+        type F = GoldilocksField;
+        type C = CurtaPoseidonGoldilocksConfig;
+        type SC = PoseidonGoldilocksStarkConfig;
+
+        let num_rows = 1 << 5usize;
+        let stark = Starky::<FibonacciAir>::new(FibonacciAir::new());
+
+        let public_inputs = [
+            F::ZERO,
+            F::ONE,
+            FibonacciAir::fibonacci(num_rows - 1, F::ZERO, F::ONE),
+        ];
+        let trace_generator =
+            ConstantGenerator::new(FibonacciAir::generate_trace(F::ZERO, F::ONE, num_rows));
+
+        let config = SC::standard_fast_config(num_rows).with_public_inputs_commitment();
+        let proof =
+            StarkyProver::<F, C, 2>::prove(&config, &stark, &trace_generator, &public_inputs)
+                .unwrap();
+        let commitment = config.public_inputs_commitment(&public_inputs).unwrap();
+        assert_eq!(proof.public_inputs_commitment, Some(commitment));
+        assert_eq!(proof.public_inputs_digest().unwrap().len(), 32);
+        StarkyVerifier::verify(&config, &stark, proof.clone(), &public_inputs).unwrap();
+
+        // A commitment to other public inputs is rejected.
+        let mut wrong_commitment = proof.clone();
+        wrong_commitment.public_inputs_commitment =
+            config.public_inputs_commitment(&[F::ONE, F::ONE, F::ONE]);
+        assert_eq!(
+            StarkyVerifier::verify(&config, &stark, wrong_commitment, &public_inputs),
+            Err(VerificationError::PublicInputsCommitmentMismatch)
+        );
+
+        // The commitment can not be dropped from the proof.
+        let mut no_commitment = proof.clone();
+        no_commitment.public_inputs_commitment = None;
+        assert_eq!(
+            StarkyVerifier::verify(&config, &stark, no_commitment, &public_inputs),
+            Err(VerificationError::MalformedProof(
+                ProofShapeError::NumPublicInputsCommitments {
+                    expected: 1,
+                    found: 0
+                }
+            ))
+        );
+
+        // Verify the proof recursively, exposing only the commitment.
+        let config_rec = CircuitConfig::standard_recursion_config();
+        let mut builder = CircuitBuilder::<F, 2>::new(config_rec);
+        let virtual_proof = builder.add_virtual_stark_proof(&stark, &config);
+        let public_input_targets = builder.add_virtual_targets(public_inputs.len());
+        builder.verify_stark_proof(&config, &stark, &virtual_proof, &public_input_targets);
+        builder.register_public_inputs(&virtual_proof.public_inputs_commitment.unwrap().elements);
+
+        let mut pw = PartialWitness::new();
+        for (&pi_t, &pi) in public_input_targets.iter().zip(public_inputs.iter()) {
+            pw.set_target(pi_t, pi).unwrap();
+        }
+        set_stark_proof_target(&mut pw, &virtual_proof, &proof).unwrap();
+
+        let data = builder.build::<<C as CurtaConfig<2>>::GenericConfig>();
+        let recursive_proof = data.prove(pw).unwrap();
+        assert_eq!(recursive_proof.public_inputs, commitment.elements);
+        data.verify(recursive_proof).unwrap();
+    }
+
+    #[test]
+    fn test_plonky2_fibonacci_stark_zero_knowledge() {
+        type F = GoldilocksField;
+        type SC = PoseidonGoldilocksStarkConfig;
+
+        let num_rows = 1 << 9usize;
+        let air = FibonacciAir::new();
+        let stark = Starky::<FibonacciAir>::new(air);
+
+        let public_inputs = [
+            F::ZERO,
+            F::ONE,
+            FibonacciAir::fibonacci(num_rows - 1, F::ZERO, F::ONE),
+        ];
+
+        let trace = FibonacciAir::generate_trace(F::ZERO, F::ONE, num_rows);
+        let trace_generator = ConstantGenerator::new(trace);
+
+        let config = SC::standard_fast_config(num_rows).with_zero_knowledge();
+
+        // Generate proof and verify as a stark
+        test_starky(&stark, &config, &trace_generator, &public_inputs);
+    }
+
+    #[test]
+    fn test_plonky2_fibonacci_stark_extra_openings() {
+        type F = GoldilocksField;
+        type FE = <F as Extendable<2>>::Extension;
+        type SC = PoseidonGoldilocksStarkConfig;
+
+        let num_rows = 1 << 5usize;
+        let stark = Starky::<FibonacciAir>::new(FibonacciAir::new());
+
+        let public_inputs = [
+            F::ZERO,
+            F::ONE,
+            FibonacciAir::fibonacci(num_rows - 1, F::ZERO, F::ONE),
+        ];
+        let trace_generator =
+            ConstantGenerator::new(FibonacciAir::generate_trace(F::ZERO, F::ONE, num_rows));
+        let config = SC::standard_fast_config(num_rows);
+
+        // The prover rejects repeated extra points and points of the LDE domain.
+        let g = F::primitive_root_of_unity(config.degree_bits);
+        let last_row = FE::from_basefield(g.exp_u64(num_rows as u64 - 1));
+        let lde_point = FE::from_basefield(
+            F::coset_shift() * F::primitive_root_of_unity(config.fri_params().lde_bits()),
+        );
+        for extra_points in [[last_row, last_row], [last_row, lde_point]] {
+            assert!(StarkyProver::prove_with_extra_points(
+                &config,
+                &stark,
+                &trace_generator,
+                &public_inputs,
+                &extra_points,
+            )
+            .is_err());
+        }
+
+        // Open the trace at the last row.
+        let proof = StarkyProver::prove_with_extra_points(
+            &config,
+            &stark,
+            &trace_generator,
+            &public_inputs,
+            &[last_row],
+        )
+        .unwrap();
+
+        let openings = &proof.air_proof.openings;
+        assert_eq!(openings.extra_openings.len(), 1);
+        let last_values = openings.extra_values(last_row).unwrap();
+        assert_eq!(last_values[1], FE::from_basefield(public_inputs[2]));
+
+        StarkyVerifier::verify(&config, &stark, proof.clone(), &public_inputs).unwrap();
+
+        // Verify the proof recursively, with targets for the extra opening.
+        type C = CurtaPoseidonGoldilocksConfig;
+        let config_rec = CircuitConfig::standard_recursion_config();
+        let mut builder = CircuitBuilder::<F, 2>::new(config_rec);
+        let virtual_proof =
+            add_virtual_stark_proof_with_extra_points(&mut builder, &stark, &config, 1);
+        let public_input_targets = builder.add_virtual_targets(public_inputs.len());
+        builder.verify_stark_proof(&config, &stark, &virtual_proof, &public_input_targets);
+
+        let mut pw = PartialWitness::new();
+        for (&pi_t, &pi) in public_input_targets.iter().zip(public_inputs.iter()) {
+            pw.set_target(pi_t, pi).unwrap();
+        }
+        set_stark_proof_target(&mut pw, &virtual_proof, &proof).unwrap();
+
+        let data = builder.build::<<C as CurtaConfig<2>>::GenericConfig>();
+        let recursive_proof = data.prove(pw).unwrap();
+        data.verify(recursive_proof).unwrap();
+    }
+
+    #[test]
+    fn test_plonky2_fibonacci_stark_shared_transcript() {
+        type F = GoldilocksField;
+        type SC = PoseidonGoldilocksStarkConfig;
+
+        let stark = Starky::<FibonacciAir>::new(FibonacciAir::new());
+        let num_rows = [1 << 5usize, 1 << 6usize];
+
+        let initial_values = [(F::ZERO, F::ONE), (F::TWO, F::from_canonical_u8(3))];
+        let public_inputs = initial_values
+            .iter()
+            .zip(num_rows)
+            .map(|(&(x_0, x_1), n)| [x_0, x_1, FibonacciAir::fibonacci(n - 1, x_0, x_1)])
+            .collect::<Vec<_>>();
+        let trace_generators = initial_values
+            .iter()
+            .zip(num_rows)
+            .map(|(&(x_0, x_1), n)| {
+                ConstantGenerator::new(FibonacciAir::generate_trace(x_0, x_1, n))
+            })
+            .collect::<Vec<_>>();
+        let configs = num_rows.map(SC::standard_fast_config);
+
+        let configs = configs.iter().collect::<Vec<_>>();
+        let starks = [&stark, &stark];
+        let generators = trace_generators.iter().collect::<Vec<_>>();
+        let inputs = public_inputs
+            .iter()
+            .map(|x| x.as_slice())
+            .collect::<Vec<_>>();
+        let proofs = StarkyProver::prove_all(&configs, &starks, &generators, &inputs).unwrap();
+
+        StarkyVerifier::verify_all(&configs, &starks, &proofs, &inputs).unwrap();
+
+        // The second proof is bound to the transcript of the first one.
+        assert!(StarkyVerifier::verify(configs[1], &stark, proofs[1].clone(), inputs[1]).is_err());
+        assert!(StarkyVerifier::verify_all(
+            &configs[1..],
+            &starks[1..],
+            &proofs[1..],
+            &inputs[1..]
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn test_plonky2_fibonacci_stark_shared_inputs() {
+        type F = GoldilocksField;
+        type C = CurtaPoseidonGoldilocksConfig;
+        type SC = PoseidonGoldilocksStarkConfig;
+
+        let stark = Starky::<FibonacciAir>::new(FibonacciAir::new());
+        let num_rows = [1 << 5usize, 1 << 6usize];
+
+        let initial_values = [(F::ZERO, F::ONE), (F::TWO, F::from_canonical_u8(3))];
+        let public_inputs = initial_values
+            .iter()
+            .zip(num_rows)
+            .map(|(&(x_0, x_1), n)| [x_0, x_1, FibonacciAir::fibonacci(n - 1, x_0, x_1)])
+            .collect::<Vec<_>>();
+        let trace_generators = initial_values
+            .iter()
+            .zip(num_rows)
+            .map(|(&(x_0, x_1), n)| {
+                ConstantGenerator::new(FibonacciAir::generate_trace(x_0, x_1, n))
+            })
+            .collect::<Vec<_>>();
+        let configs = num_rows.map(SC::standard_fast_config);
+
+        let configs = configs.iter().collect::<Vec<_>>();
+        let starks = [&stark, &stark];
+        let generators = trace_generators.iter().collect::<Vec<_>>();
+        let inputs = public_inputs
+            .iter()
+            .map(|x| x.as_slice())
+            .collect::<Vec<_>>();
+        let shared_inputs = [F::from_canonical_u8(7), F::from_canonical_u8(11)];
+        let proof =
+            StarkyProver::prove_shared(&configs, &starks, &generators, &inputs, &shared_inputs)
+                .unwrap();
+        assert_eq!(proof.num_proofs(), 2);
+
+        StarkyVerifier::verify_shared(&configs, &starks, &proof, &inputs, &shared_inputs).unwrap();
+
+        // The proofs are bound to the shared inputs, even if the commitment is replaced.
+        let other_shared_inputs = [F::from_canonical_u8(7), F::from_canonical_u8(12)];
+        assert_eq!(
+            StarkyVerifier::verify_shared(&configs, &starks, &proof, &inputs, &other_shared_inputs),
+            Err(VerificationError::SharedInputsCommitmentMismatch)
+        );
+        let mut rebound = proof.clone();
+        rebound.shared_inputs_commitment =
+            SharedInputsProof::<F, C, 2>::commit(&other_shared_inputs);
+        assert!(StarkyVerifier::verify_shared(
+            &configs,
+            &starks,
+            &rebound,
+            &inputs,
+            &other_shared_inputs
+        )
+        .is_err());
+
+        // A proof of a different statement can not be mixed in.
+        let other_proof = StarkyProver::prove_shared(
+            &configs,
+            &starks,
+            &generators,
+            &inputs,
+            &other_shared_inputs,
+        )
+        .unwrap();
+        let mut mixed = proof.clone();
+        mixed.proofs[1] = other_proof.proofs[1].clone();
+        assert!(
+            StarkyVerifier::verify_shared(&configs, &starks, &mixed, &inputs, &shared_inputs)
+                .is_err()
+        );
+
+        // The proofs are not valid outside of the shared statement.
+        assert!(
+            StarkyVerifier::verify(configs[0], &stark, proof.proofs[0].clone(), inputs[0]).is_err()
+        );
+    }
+
+    #[test]
+    fn test_plonky2_malformed_proof_shape() {
+        type F = GoldilocksField;
+        type SC = PoseidonGoldilocksStarkConfig;
+
+        let num_rows = 1 << 5usize;
+        let stark = Starky::<FibonacciAir>::new(FibonacciAir::new());
+
+        let public_inputs = [
+            F::ZERO,
+            F::ONE,
+            FibonacciAir::fibonacci(num_rows - 1, F::ZERO, F::ONE),
+        ];
+        let trace_generator =
+            ConstantGenerator::new(FibonacciAir::generate_trace(F::ZERO, F::ONE, num_rows));
+        let config = SC::standard_fast_config(num_rows);
+
+        let proof = StarkyProver::prove(&config, &stark, &trace_generator, &public_inputs).unwrap();
+        assert_eq!(proof.validate_shape(&stark, &config), Ok(()));
+
+        let mut missing_cap = proof.clone();
+        missing_cap.air_proof.trace_caps.pop();
+        assert_eq!(
+            missing_cap.validate_shape(&stark, &config),
+            Err(ProofShapeError::NumTraceCaps {
+                expected: 1,
+                found: 0
+            })
+        );
+        assert_eq!(
+            StarkyVerifier::verify(&config, &stark, missing_cap, &public_inputs),
+            Err(VerificationError::MalformedProof(
+                ProofShapeError::NumTraceCaps {
+                    expected: 1,
+                    found: 0
+                }
+            ))
+        );
+
+        // The quotient openings have one group per challenge, each with one chunk per unit of the
+        // quotient degree.
+        let openings = &proof.air_proof.openings;
+        assert_eq!(openings.quotient_chunks.len(), config.num_challenges);
+        assert!(openings
+            .quotient_chunks
+            .iter()
+            .all(|chunks| chunks.len() == stark.num_quotient_chunks(&config)));
+
+        let mut missing_group = proof.clone();
+        missing_group.air_proof.openings.quotient_chunks.pop();
+        assert_eq!(
+            StarkyVerifier::verify(&config, &stark, missing_group, &public_inputs),
+            Err(VerificationError::MalformedProof(
+                ProofShapeError::NumQuotientGroups {
+                    expected: config.num_challenges,
+                    found: config.num_challenges - 1
+                }
+            ))
+        );
+
+        let mut extra_chunk = proof.clone();
+        let chunk = extra_chunk.air_proof.openings.quotient_chunks[0][0];
+        extra_chunk.air_proof.openings.quotient_chunks[0].push(chunk);
+        assert_eq!(
+            extra_chunk.validate_shape(&stark, &config),
+            Err(ProofShapeError::NumQuotientPolys {
+                expected: stark.num_quotient_chunks(&config),
+                found: stark.num_quotient_chunks(&config) + 1
+            })
+        );
+
+        let mut extra_global = proof;
+        extra_global.global_values.push(F::ONE);
+        assert!(matches!(
+            extra_global.validate_shape(&stark, &config),
+            Err(ProofShapeError::NumGlobalValues { .. })
+        ));
+        assert!(StarkyVerifier::verify(&config, &stark, extra_global, &public_inputs).is_err());
+    }
+
+    #[test]
+    fn test_plonky2_verification_errors() {
+        type F = GoldilocksField;
+        type SC = PoseidonGoldilocksStarkConfig;
+
+        let num_rows = 1 << 5usize;
+        let stark = Starky::<FibonacciAir>::new(FibonacciAir::new());
+
+        let public_inputs = [
+            F::ZERO,
+            F::ONE,
+            FibonacciAir::fibonacci(num_rows - 1, F::ZERO, F::ONE),
+        ];
+        let trace_generator =
+            ConstantGenerator::new(FibonacciAir::generate_trace(F::ZERO, F::ONE, num_rows));
+        let config = SC::standard_fast_config(num_rows);
+
+        let proof = StarkyProver::prove(&config, &stark, &trace_generator, &public_inputs).unwrap();
+
+        // A valid proof of a false statement.
+        let wrong_inputs = [F::ZERO, F::ONE, F::ZERO];
+        assert!(matches!(
+            StarkyVerifier::verify(&config, &stark, proof.clone(), &wrong_inputs),
+            Err(VerificationError::QuotientMismatch { .. })
+        ));
+
+        // A proof checked against the configuration of a longer trace.
+        let long_config = SC::standard_fast_config(2 * num_rows);
+        assert_eq!(
+            StarkyVerifier::verify(&long_config, &stark, proof.clone(), &public_inputs),
+            Err(VerificationError::DegreeBitsMismatch {
+                expected: 6,
+                found: 5
+            })
+        );
+
+        // A proof with a tampered FRI argument.
+        let mut bad_fri = proof;
+        bad_fri.air_proof.opening_proof.pow_witness += F::ONE;
+        assert!(matches!(
+            StarkyVerifier::verify(&config, &stark, bad_fri, &public_inputs),
+            Err(VerificationError::InvalidFriProof(_))
+        ));
+    }
+
+    #[test]
+    fn test_plonky2_fibonacci_batch_stark() {
+        type F = GoldilocksField;
+        type SC = PoseidonGoldilocksStarkConfig;
+
+        let num_rows = 1 << 5usize;
+        let stark = Starky::<FibonacciAir>::new(FibonacciAir::new());
+
+        let initial_values = [(F::ZERO, F::ONE), (F::TWO, F::from_canonical_u8(3))];
+        let public_inputs = initial_values
+            .iter()
+            .map(|&(x_0, x_1)| [x_0, x_1, FibonacciAir::fibonacci(num_rows - 1, x_0, x_1)])
+            .collect::<Vec<_>>();
+        let trace_generators = initial_values
+            .iter()
+            .map(|&(x_0, x_1)| {
+                ConstantGenerator::new(FibonacciAir::generate_trace(x_0, x_1, num_rows))
+            })
+            .collect::<Vec<_>>();
+
+        let config = SC::standard_fast_config(num_rows);
+
+        let starks = [&stark, &stark];
+        let generators = trace_generators.iter().collect::<Vec<_>>();
+        let inputs = public_inputs
+            .iter()
+            .map(|x| x.as_slice())
+            .collect::<Vec<_>>();
+        let proof = StarkyProver::prove_batch(&config, &starks, &generators, &inputs).unwrap();
+        assert_eq!(proof.num_starks(), 2);
+
+        StarkyVerifier::verify_batch_proof(&config, &starks, proof, &inputs).unwrap();
+    }
+
+    #[test]
+    fn test_plonky2_fibonacci_deferred_fri() {
+        type F = GoldilocksField;
+        type C = CurtaPoseidonGoldilocksConfig;
+        type SC = PoseidonGoldilocksStarkConfig;
+
+        let num_rows = 1 << 5usize;
+        let stark = Starky::<FibonacciAir>::new(FibonacciAir::new());
+        let compressed_stark = Starky::new(FibonacciAir::new()).with_compressed_openings::<F>();
+        let starks = [&stark, &compressed_stark, &stark];
+
+        let initial_values = [
+            (F::ZERO, F::ONE),
+            (F::TWO, F::from_canonical_u8(3)),
+            (F::ONE, F::ONE),
+        ];
+        let public_inputs = initial_values
+            .iter()
+            .map(|&(x_0, x_1)| [x_0, x_1, FibonacciAir::fibonacci(num_rows - 1, x_0, x_1)])
+            .collect::<Vec<_>>();
+        let inputs = public_inputs
+            .iter()
+            .map(|x| x.as_slice())
+            .collect::<Vec<_>>();
+
+        let config = SC::standard_fast_config(num_rows);
+
+        // Each proof is generated on its own, without a FRI argument.
+        let deferred_proofs = starks
+            .iter()
+            .zip(initial_values.iter())
+            .zip(inputs.iter())
+            .map(|((stark, &(x_0, x_1)), public_inputs)| {
+                let trace_generator =
+                    ConstantGenerator::new(FibonacciAir::generate_trace(x_0, x_1, num_rows));
+                StarkyProver::<F, C, 2>::prove_deferred(
+                    &config,
+                    stark,
+                    &trace_generator,
+                    public_inputs,
+                )
+                .unwrap()
+            })
+            .collect::<Vec<_>>();
+        assert_ne!(deferred_proofs[0].zeta, deferred_proofs[1].zeta);
+
+        let proof = StarkyProver::aggregate(&config, &starks, &deferred_proofs).unwrap();
+        assert_eq!(proof.num_proofs(), 3);
+        StarkyVerifier::verify_aggregated(&config, &starks, &proof, &inputs).unwrap();
+
+        // The proofs are bound to their own public inputs.
+        let swapped_inputs = [inputs[1], inputs[0], inputs[2]];
+        assert!(
+            StarkyVerifier::verify_aggregated(&config, &starks, &proof, &swapped_inputs).is_err()
+        );
+
+        // The FRI challenges depend on the public inputs and the challenge points of each proof.
+        let zetas = deferred_proofs
+            .iter()
+            .map(|proof| proof.zeta)
+            .collect::<Vec<_>>();
+        let fri_alpha = proof.fri_challenges(&config, &inputs, &zetas).fri_alpha;
+        assert_ne!(
+            proof
+                .fri_challenges(&config, &swapped_inputs, &zetas)
+                .fri_alpha,
+            fri_alpha
+        );
+        let swapped_zetas = [zetas[1], zetas[0], zetas[2]];
+        assert_ne!(
+            proof
+                .fri_challenges(&config, &inputs, &swapped_zetas)
+                .fri_alpha,
+            fri_alpha
+        );
+
+        // Openings can not be changed after the FRI argument is generated.
+        let mut bad_proof = proof;
+        bad_proof.proofs[2].openings.local_values[0] += <F as Extendable<2>>::Extension::ONE;
+        assert!(StarkyVerifier::verify_aggregated(&config, &starks, &bad_proof, &inputs).is_err());
+    }
+
+    #[test]
+    fn test_plonky2_fibonacci_stark_commit_then_prove() {
+        type F = GoldilocksField;
+        type C = CurtaPoseidonGoldilocksConfig;
+        type SC = PoseidonGoldilocksStarkConfig;
+
+        let num_rows = 1 << 5usize;
+        let stark = Starky::<FibonacciAir>::new(FibonacciAir::new());
+
+        let public_inputs = [
+            F::ZERO,
+            F::ONE,
+            FibonacciAir::fibonacci(num_rows - 1, F::ZERO, F::ONE),
+        ];
+        let trace_generator =
+            ConstantGenerator::new(FibonacciAir::generate_trace(F::ZERO, F::ONE, num_rows));
+        let config = SC::standard_fast_config(num_rows);
+
+        let (commitment, air_commitment) =
+            StarkyProver::<F, C, 2>::commit(&config, &stark, &trace_generator, &public_inputs)
+                .unwrap();
+        commitment.check(&config, &public_inputs).unwrap();
+
+        let proof = StarkyProver::<F, C, 2>::prove_from_commitment(&config, &stark, air_commitment)
+            .unwrap();
+        assert_eq!(proof.air_proof.trace_caps, commitment.trace_caps);
+        StarkyVerifier::verify_from_commitment(
+            &config,
+            &stark,
+            &commitment,
+            proof.clone(),
+            &public_inputs,
+        )
+        .unwrap();
+
+        // The commitment is bound to the public inputs.
+        let other_inputs = [F::ONE, F::ONE, F::ONE];
+        assert_eq!(
+            commitment.check(&config, &other_inputs),
+            Err(VerificationError::TraceCommitmentMismatch)
+        );
+
+        // A proof of another trace does not open the commitment.
+        let other_generator =
+            ConstantGenerator::new(FibonacciAir::generate_trace(F::ONE, F::ONE, num_rows));
+        let other_public_inputs = [
+            F::ONE,
+            F::ONE,
+            FibonacciAir::fibonacci(num_rows - 1, F::ONE, F::ONE),
+        ];
+        let other_proof =
+            StarkyProver::<F, C, 2>::prove(&config, &stark, &other_generator, &other_public_inputs)
+                .unwrap();
+        assert_eq!(
+            StarkyVerifier::verify_from_commitment(
+                &config,
+                &stark,
+                &commitment,
+                other_proof,
+                &public_inputs,
+            ),
+            Err(VerificationError::TraceCommitmentMismatch)
+        );
+    }
+
+    #[test]
+    fn test_plonky2_fibonacci_stark_zeroize_commitments() {
+        type F = GoldilocksField;
+        type C = CurtaPoseidonGoldilocksConfig;
+        type SC = PoseidonGoldilocksStarkConfig;
+
+        let num_rows = 1 << 5usize;
+        let stark = Starky::<FibonacciAir>::new(FibonacciAir::new());
+
+        let public_inputs = [
+            F::ZERO,
+            F::ONE,
+            FibonacciAir::fibonacci(num_rows - 1, F::ZERO, F::ONE),
+        ];
+        let trace_generator =
+            ConstantGenerator::new(FibonacciAir::generate_trace(F::ZERO, F::ONE, num_rows));
+        let config = SC::standard_fast_config(num_rows);
+
+        let is_zero = |batch: &PolynomialBatch<F, <C as CurtaConfig<2>>::GenericConfig, 2>| {
+            batch
+                .polynomials
+                .iter()
+                .all(|p| p.coeffs.iter().all(|x| *x == F::ZERO))
+                && batch
+                    .merkle_tree
+                    .leaves
+                    .iter()
+                    .all(|leaf| leaf.iter().all(|x| *x == F::ZERO))
+        };
+
+        let (_, mut air_commitment) =
+            StarkyProver::<F, C, 2>::commit(&config, &stark, &trace_generator, &public_inputs)
+                .unwrap();
+        assert!(!air_commitment.trace_commitments.iter().all(is_zero));
+        air_commitment.zeroize();
+        assert!(air_commitment.trace_commitments.iter().all(is_zero));
+
+        let mut deferred_proof = StarkyProver::<F, C, 2>::prove_deferred(
+            &config,
+            &stark,
+            &trace_generator,
+            &public_inputs,
+        )
+        .unwrap();
+        assert!(!deferred_proof.trace_commitments.iter().all(is_zero));
+        deferred_proof.zeroize();
+        assert!(deferred_proof.trace_commitments.iter().all(is_zero));
+        assert!(is_zero(&deferred_proof.quotient_commitment));
+    }
 }
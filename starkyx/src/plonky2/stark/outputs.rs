@@ -0,0 +1,231 @@
+//! Typed extraction of the outputs of a proof.
+//!
+//! The outputs of a STARK are named ranges of its global values. Their layout is recorded when
+//! building the AIR and stored in the `Starky`, so that applications can read the outputs of a
+//! proof by name instead of by their index in the global values.
+
+use alloc::collections::BTreeMap;
+
+use anyhow::{anyhow, ensure, Result};
+use plonky2::field::extension::Extendable;
+use plonky2::hash::hash_types::RichField;
+use serde::{Deserialize, Serialize};
+
+use super::config::CurtaConfig;
+use super::proof::StarkProof;
+use super::Starky;
+use crate::chip::register::Register;
+use crate::math::prelude::*;
+
+/// The positions of the named outputs in the global values of a STARK.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct OutputLayout {
+    outputs: BTreeMap<String, (usize, usize)>,
+}
+
+impl OutputLayout {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds the output `name` at the global values `start..end`.
+    pub fn insert(&mut self, name: &str, start: usize, end: usize) {
+        let previous = self.outputs.insert(name.to_string(), (start, end));
+        assert!(previous.is_none(), "Output {} is already defined", name);
+    }
+
+    /// The range of global values of the output `name`.
+    pub fn get(&self, name: &str) -> Option<(usize, usize)> {
+        self.outputs.get(name).copied()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.outputs.is_empty()
+    }
+
+    /// The names of the outputs.
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.outputs.keys().map(|name| name.as_str())
+    }
+}
+
+/// The global values of a proof together with the layout of its outputs.
+#[derive(Debug, Clone, Copy)]
+pub struct ProofOutputs<'a, F> {
+    layout: &'a OutputLayout,
+    global_values: &'a [F],
+}
+
+impl<'a, F: Field> ProofOutputs<'a, F> {
+    pub fn new(layout: &'a OutputLayout, global_values: &'a [F]) -> Self {
+        Self {
+            layout,
+            global_values,
+        }
+    }
+
+    /// The values of the output `name`.
+    pub fn get(&self, name: &str) -> Result<&'a [F]> {
+        let (start, end) = self
+            .layout
+            .get(name)
+            .ok_or_else(|| anyhow!("Unknown output {}", name))?;
+        ensure!(
+            end <= self.global_values.len(),
+            "Output {} is out of the range of the global values",
+            name
+        );
+        Ok(&self.global_values[start..end])
+    }
+
+    /// The value of the output `name`, read as a value of the register type `T`.
+    pub fn value<T: Register>(&self, name: &str) -> Result<T::Value<F>> {
+        let values = self.get(name)?;
+        ensure!(
+            values.len() == T::size_of(),
+            "Output {} has {} values, expected {}",
+            name,
+            values.len(),
+            T::size_of()
+        );
+        Ok(T::value_from_slice(values))
+    }
+
+    /// The values of the output `name`, read as an array of values of the register type `T`.
+    pub fn values<T: Register>(&self, name: &str) -> Result<Vec<T::Value<F>>> {
+        let values = self.get(name)?;
+        ensure!(
+            values.len() % T::size_of() == 0,
+            "Output {} has {} values, which is not a multiple of {}",
+            name,
+            values.len(),
+            T::size_of()
+        );
+        Ok(values
+            .chunks_exact(T::size_of())
+            .map(T::value_from_slice)
+            .collect())
+    }
+}
+
+/// A typed view of the outputs of a STARK.
+pub trait AirOutputs<F: Field>: Sized {
+    fn from_outputs(outputs: &ProofOutputs<F>) -> Result<Self>;
+}
+
+impl<F: RichField + Extendable<D>, C: CurtaConfig<D, F = F>, const D: usize> StarkProof<F, C, D> {
+    /// Reads the outputs of the proof of `stark` from its global values.
+    pub fn outputs<O: AirOutputs<F>, A>(&self, stark: &Starky<A>) -> Result<O> {
+        O::from_outputs(&ProofOutputs::new(
+            &stark.output_layout,
+            &self.global_values,
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde::{Deserialize, Serialize};
+
+    use super::*;
+    use crate::chip::builder::tests::*;
+    use crate::chip::builder::AirBuilder;
+    use crate::chip::register::element::ElementRegister;
+    use crate::chip::AirParameters;
+    use crate::plonky2::stark::config::CurtaPoseidonGoldilocksConfig;
+    use crate::plonky2::stark::prover::StarkyProver;
+    use crate::plonky2::stark::verifier::StarkyVerifier;
+
+    #[derive(Debug, Clone, Serialize, Deserialize)]
+    pub struct OutputsTest;
+
+    impl AirParameters for OutputsTest {
+        type Field = GoldilocksField;
+        type CubicParams = GoldilocksCubicParameters;
+        type Instruction = EmptyInstruction<GoldilocksField>;
+
+        const NUM_FREE_COLUMNS: usize = 2;
+    }
+
+    #[derive(Debug, PartialEq, Eq)]
+    struct TestOutputs {
+        sum: GoldilocksField,
+        pair: Vec<GoldilocksField>,
+    }
+
+    impl AirOutputs<GoldilocksField> for TestOutputs {
+        fn from_outputs(outputs: &ProofOutputs<GoldilocksField>) -> Result<Self> {
+            Ok(Self {
+                sum: outputs.value::<ElementRegister>("sum")?,
+                pair: outputs.values::<ElementRegister>("pair")?,
+            })
+        }
+    }
+
+    #[test]
+    fn test_proof_outputs() {
+        type F = GoldilocksField;
+        type L = OutputsTest;
+        type C = CurtaPoseidonGoldilocksConfig;
+        type SC = PoseidonGoldilocksStarkConfig;
+
+        let mut builder = AirBuilder::<L>::new();
+        let a = builder.alloc::<ElementRegister>();
+        builder.set_to_expression(&a, F::ONE.into());
+        let x = builder.alloc_public::<ElementRegister>();
+        let y = builder.alloc_public::<ElementRegister>();
+        // An unnamed global value shifts the outputs away from the start of the global values.
+        let padding = builder.alloc_global::<ElementRegister>();
+        let sum = builder.alloc_global::<ElementRegister>();
+        let pair = builder.alloc_array_global::<ElementRegister>(2);
+        builder.set_to_expression_public(&padding, x.expr());
+        builder.set_to_expression_public(&sum, x.expr() + y.expr());
+        builder.set_to_expression_public(&pair.get(0), x.expr() * y.expr());
+        builder.set_to_expression_public(&pair.get(1), x.expr() - y.expr());
+        builder.register_output("sum", &sum);
+        builder.register_output("pair", &pair);
+
+        let (air, trace_data) = builder.build();
+        let num_rows = 1 << 5;
+        let generator = ArithmeticGenerator::<L>::new(trace_data, num_rows);
+
+        let writer = generator.new_writer();
+        let x_value = F::from_canonical_u8(5);
+        let y_value = F::from_canonical_u8(3);
+        writer.write(&x, &x_value, 0);
+        writer.write(&y, &y_value, 0);
+        writer.write_global_instructions(&generator.air_data);
+        for i in 0..num_rows {
+            writer.write_row_instructions(&generator.air_data, i);
+        }
+
+        let stark = Starky::from_chip(air);
+        assert_eq!(
+            stark.output_layout.names().collect::<Vec<_>>(),
+            ["pair", "sum"]
+        );
+        let config = SC::standard_fast_config(num_rows);
+        let public = writer.public().unwrap().clone();
+        let proof = StarkyProver::<F, C, 2>::prove(&config, &stark, &generator, &public).unwrap();
+        StarkyVerifier::verify(&config, &stark, proof.clone(), &public).unwrap();
+
+        let outputs = proof.outputs::<TestOutputs, _>(&stark).unwrap();
+        assert_eq!(
+            outputs,
+            TestOutputs {
+                sum: x_value + y_value,
+                pair: vec![x_value * y_value, x_value - y_value],
+            }
+        );
+
+        // Missing outputs are reported instead of reading the wrong global values.
+        struct MissingOutputs;
+        impl AirOutputs<F> for MissingOutputs {
+            fn from_outputs(outputs: &ProofOutputs<F>) -> Result<Self> {
+                outputs.get("product")?;
+                Ok(Self)
+            }
+        }
+        assert!(proof.outputs::<MissingOutputs, _>(&stark).is_err());
+    }
+}
@@ -0,0 +1,917 @@
+pub mod shape;
+pub mod testing;
+
+use core::iter::once;
+
+use itertools::Itertools;
+use plonky2::field::extension::{Extendable, FieldExtension};
+use plonky2::field::goldilocks_field::GoldilocksField;
+use plonky2::field::polynomial::PolynomialCoeffs;
+use plonky2::field::types::{Field, Field64, PrimeField64};
+use plonky2::fri::oracle::PolynomialBatch;
+use plonky2::fri::proof::{FriChallenges, FriChallengesTarget, FriProof, FriProofTarget};
+use plonky2::fri::structure::{
+    FriOpeningBatch, FriOpeningBatchTarget, FriOpenings, FriOpeningsTarget,
+};
+use plonky2::hash::hash_types::{HashOut, HashOutTarget, MerkleCapTarget, RichField};
+use plonky2::hash::merkle_tree::MerkleCap;
+use plonky2::iop::challenger::{Challenger, RecursiveChallenger};
+use plonky2::iop::ext_target::ExtensionTarget;
+use plonky2::iop::target::Target;
+use plonky2::plonk::circuit_builder::CircuitBuilder;
+use plonky2::plonk::config::{GenericConfig, GenericHashOut};
+use serde::{Deserialize, Serialize};
+
+use super::config::{CurtaConfig, StarkyConfig};
+use super::Starky;
+use crate::air::{RAir, RAirData};
+use crate::math::goldilocks::montgomery;
+use crate::maybe_rayon::*;
+use crate::plonky2::parser::RecursiveStarkParser;
+use crate::utils::serde::{
+    deserialize_extension_target, deserialize_extension_target_groups,
+    deserialize_extension_targets, deserialize_fri_proof_target, deserialize_merkle_cap_target,
+    deserialize_merkle_cap_targets, deserialize_option_hash_out_target, serialize_extension_target,
+    serialize_extension_target_groups, serialize_extension_targets, serialize_fri_proof_target,
+    serialize_merkle_cap_target, serialize_merkle_cap_targets, serialize_option_hash_out_target,
+};
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(bound = "")]
+pub struct AirProof<F: RichField + Extendable<D>, C: CurtaConfig<D, F = F>, const D: usize> {
+    /// Merkle cap of LDEs of trace values for each round.
+    ///
+    /// The trace of each round after the first, such as the auxiliary columns of lookup and
+    /// permutation arguments, is generated with the challenges drawn after the previous rounds.
+    /// All the rounds are opened together at the challenge points.
+    pub trace_caps: Vec<MerkleCap<F, C::Hasher>>,
+    /// Merkle cap of LDEs of the quotient polynomials.
+    pub quotient_polys_cap: MerkleCap<F, C::Hasher>,
+    /// Purported values of each polynomial at the challenge point.
+    pub openings: StarkOpeningSet<F, D>,
+    /// A batch FRI argument for all openings.
+    pub opening_proof: FriProof<F, C::Hasher, D>,
+}
+
+impl<F: RichField + Extendable<D>, C: CurtaConfig<D, F = F>, const D: usize> AirProof<F, C, D> {
+    /// Checks that the proof has the shape expected by `stark`.
+    pub fn validate_shape<A: RAirData>(
+        &self,
+        stark: &Starky<A>,
+        config: &StarkyConfig<C, D>,
+        global_values: &[F],
+    ) -> Result<(), ProofShapeError> {
+        let air = stark.air();
+        let cap_height = config.fri_config.cap_height;
+
+        let AirProof {
+            trace_caps,
+            quotient_polys_cap,
+            openings,
+            opening_proof,
+        } = self;
+
+        let StarkOpeningSet {
+            local_values,
+            next_values,
+            extra_openings,
+            ..
+        } = openings;
+
+        ProofShapeError::check(
+            ProofShapeError::NumTraceCaps,
+            air.num_rounds(),
+            trace_caps.len(),
+        )?;
+        for cap in trace_caps.iter().chain(once(quotient_polys_cap)) {
+            ProofShapeError::check(ProofShapeError::CapHeight, cap_height, cap.height())?;
+        }
+        ProofShapeError::check(
+            ProofShapeError::NumGlobalValues,
+            air.num_global_values(),
+            global_values.len(),
+        )?;
+        ProofShapeError::check(
+            ProofShapeError::NumLocalValues,
+            air.num_columns(),
+            local_values.len(),
+        )?;
+        ProofShapeError::check(
+            ProofShapeError::NumNextValues,
+            stark.next_columns().len(),
+            next_values.len(),
+        )?;
+        openings.validate_quotient_chunks(stark, config)?;
+        for opening in extra_openings.iter() {
+            ProofShapeError::check(
+                ProofShapeError::NumExtraValues,
+                air.num_columns(),
+                opening.values.len(),
+            )?;
+        }
+
+        let query_rounds = &opening_proof.query_round_proofs;
+        ProofShapeError::check(
+            ProofShapeError::NumQueryRounds,
+            config.fri_config.num_query_rounds,
+            query_rounds.len(),
+        )?;
+        ProofShapeError::check(
+            ProofShapeError::NumFriLayers,
+            config.fri_params().reduction_arity_bits.len(),
+            opening_proof.commit_phase_merkle_caps.len(),
+        )?;
+        // The trace of each round and the quotient polynomials are opened in every query round,
+        // followed by every layer of the commit phase.
+        for round in query_rounds.iter() {
+            ProofShapeError::check(
+                ProofShapeError::NumInitialTrees,
+                air.num_rounds() + 1,
+                round.initial_trees_proof.evals_proofs.len(),
+            )?;
+            ProofShapeError::check(
+                ProofShapeError::NumFriLayers,
+                opening_proof.commit_phase_merkle_caps.len(),
+                round.steps.len(),
+            )?;
+        }
+
+        Ok(())
+    }
+
+    pub fn get_iop_challenges(
+        &self,
+        config: &StarkyConfig<C, D>,
+        degree_bits: usize,
+        air_challenges: Vec<F>,
+        challenger: &mut Challenger<F, C::Hasher>,
+    ) -> StarkProofChallenges<F, D> {
+        let AirProof {
+            quotient_polys_cap,
+            openings,
+            opening_proof:
+                FriProof {
+                    commit_phase_merkle_caps,
+                    final_poly,
+                    pow_witness,
+                    ..
+                },
+            ..
+        } = &self;
+
+        let num_challenges = config.num_challenges;
+        let stark_alphas = challenger.get_n_challenges(num_challenges);
+
+        challenger.observe_cap(quotient_polys_cap);
+        let stark_zeta = challenger.get_extension_challenge::<D>();
+
+        challenger.observe_extension_elements(&openings.extra_points());
+        challenger.observe_openings(&openings.to_fri_openings());
+
+        StarkProofChallenges {
+            stark_alphas,
+            stark_betas: air_challenges,
+            stark_zeta,
+            fri_challenges: challenger.fri_challenges::<C::GenericConfig, D>(
+                commit_phase_merkle_caps,
+                final_poly,
+                *pow_witness,
+                degree_bits,
+                &config.fri_config,
+            ),
+        }
+    }
+}
+
+/// The ways in which a proof can fail to have the shape expected by a STARK.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ProofShapeError {
+    NumTraceCaps { expected: usize, found: usize },
+    CapHeight { expected: usize, found: usize },
+    NumGlobalValues { expected: usize, found: usize },
+    NumLocalValues { expected: usize, found: usize },
+    NumNextValues { expected: usize, found: usize },
+    NumQuotientGroups { expected: usize, found: usize },
+    NumQuotientPolys { expected: usize, found: usize },
+    NumExtraValues { expected: usize, found: usize },
+    NumQueryRounds { expected: usize, found: usize },
+    NumFriLayers { expected: usize, found: usize },
+    NumInitialTrees { expected: usize, found: usize },
+    NumLeafValues { expected: usize, found: usize },
+    MerkleProofLength { expected: usize, found: usize },
+    NumFriStepValues { expected: usize, found: usize },
+    FinalPolyLength { expected: usize, found: usize },
+    NumBatchEntries { expected: usize, found: usize },
+    NumExtraOpenings { expected: usize, found: usize },
+    NumPublicInputsCommitments { expected: usize, found: usize },
+}
+
+impl ProofShapeError {
+    pub(crate) fn check(
+        error: impl FnOnce(usize, usize) -> Self,
+        expected: usize,
+        found: usize,
+    ) -> Result<(), Self> {
+        if expected == found {
+            Ok(())
+        } else {
+            Err(error(expected, found))
+        }
+    }
+}
+
+impl core::fmt::Display for ProofShapeError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        let (name, expected, found) = match *self {
+            Self::NumTraceCaps { expected, found } => ("number of trace caps", expected, found),
+            Self::CapHeight { expected, found } => ("Merkle cap height", expected, found),
+            Self::NumGlobalValues { expected, found } => {
+                ("number of global values", expected, found)
+            }
+            Self::NumLocalValues { expected, found } => ("number of local values", expected, found),
+            Self::NumNextValues { expected, found } => ("number of next values", expected, found),
+            Self::NumQuotientGroups { expected, found } => {
+                ("number of quotient chunk groups", expected, found)
+            }
+            Self::NumQuotientPolys { expected, found } => {
+                ("number of quotient chunks for a challenge", expected, found)
+            }
+            Self::NumExtraValues { expected, found } => (
+                "number of values at an extra opening point",
+                expected,
+                found,
+            ),
+            Self::NumQueryRounds { expected, found } => {
+                ("number of FRI query rounds", expected, found)
+            }
+            Self::NumFriLayers { expected, found } => ("number of FRI layers", expected, found),
+            Self::NumInitialTrees { expected, found } => (
+                "number of initial trees in a FRI query round",
+                expected,
+                found,
+            ),
+            Self::NumLeafValues { expected, found } => (
+                "number of values in a leaf of a FRI initial tree",
+                expected,
+                found,
+            ),
+            Self::MerkleProofLength { expected, found } => {
+                ("length of a Merkle proof", expected, found)
+            }
+            Self::NumFriStepValues { expected, found } => {
+                ("number of values in a FRI query step", expected, found)
+            }
+            Self::FinalPolyLength { expected, found } => {
+                ("length of the FRI final polynomial", expected, found)
+            }
+            Self::NumBatchEntries { expected, found } => {
+                ("number of STARKs in the batch proof", expected, found)
+            }
+            Self::NumExtraOpenings { expected, found } => {
+                ("number of extra opening points", expected, found)
+            }
+            Self::NumPublicInputsCommitments { expected, found } => {
+                ("number of public input commitments", expected, found)
+            }
+        };
+        write!(
+            f,
+            "Invalid proof shape: {} is {}, expected {}",
+            name, found, expected
+        )
+    }
+}
+
+impl std::error::Error for ProofShapeError {}
+
+/// A proof of a STARK computation.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(bound = "")]
+pub struct StarkProof<F: RichField + Extendable<D>, C: CurtaConfig<D, F = F>, const D: usize> {
+    pub air_proof: AirProof<F, C, D>,
+    pub global_values: Vec<F>,
+    /// The number of bits in the length of the trace.
+    pub degree_bits: usize,
+    /// The height of the Merkle caps of the proof, which may differ from the height of the
+    /// verifier's configuration.
+    pub cap_height: usize,
+    /// A hash of the public inputs, if the configuration commits to them.
+    #[serde(default)]
+    pub public_inputs_commitment: Option<HashOut<F>>,
+}
+
+impl<F: RichField + Extendable<D>, C: CurtaConfig<D, F = F>, const D: usize> StarkProof<F, C, D> {
+    /// Checks that the proof has the shape expected by `stark`.
+    ///
+    /// This should be called on untrusted proofs before computing the challenges, which assumes
+    /// that the proof is well formed.
+    pub fn validate_shape<A: RAirData>(
+        &self,
+        stark: &Starky<A>,
+        config: &StarkyConfig<C, D>,
+    ) -> Result<(), ProofShapeError> {
+        ProofShapeError::check(
+            ProofShapeError::NumPublicInputsCommitments,
+            config.commit_public_inputs as usize,
+            self.public_inputs_commitment.is_some() as usize,
+        )?;
+        self.air_proof
+            .validate_shape(stark, config, &self.global_values)
+    }
+
+    /// The bytes of the commitment to the public inputs, if the proof has one.
+    pub fn public_inputs_digest(&self) -> Option<Vec<u8>> {
+        self.public_inputs_commitment.map(|hash| hash.to_bytes())
+    }
+
+    /// Recover the number of bits in the degree bound of the committed polynomials from the Merkle
+    /// paths of a STARK proof and a STARK config.
+    pub fn recover_degree_bits(&self, config: &StarkyConfig<C, D>) -> usize {
+        let initial_merkle_proof = &self.air_proof.opening_proof.query_round_proofs[0]
+            .initial_trees_proof
+            .evals_proofs[0]
+            .1;
+        let lde_bits = self.cap_height + initial_merkle_proof.siblings.len();
+        lde_bits - config.fri_config.rate_bits
+    }
+
+    pub fn get_iop_challenges(
+        &self,
+        config: &StarkyConfig<C, D>,
+        degree_bits: usize,
+        air_challenges: Vec<F>,
+        challenger: &mut Challenger<F, C::Hasher>,
+    ) -> StarkProofChallenges<F, D> {
+        self.air_proof
+            .get_iop_challenges(config, degree_bits, air_challenges, challenger)
+    }
+
+    pub(crate) fn get_challenges<A: RAirData>(
+        &self,
+        config: &StarkyConfig<C, D>,
+        stark: &Starky<A>,
+        public_inputs: &[F],
+        degree_bits: usize,
+    ) -> StarkProofChallenges<F, D> {
+        let mut challenger = config.challenger();
+        self.get_challenges_with_challenger(
+            config,
+            stark,
+            public_inputs,
+            degree_bits,
+            &mut challenger,
+        )
+    }
+
+    /// Computes the challenges of the proof on the transcript held by `challenger`, which may
+    /// already contain the transcripts of other proofs.
+    ///
+    /// The challenger observes the public inputs, the global values and trace caps of each round,
+    /// the quotient cap, the openings and the FRI commitments of the proof, in the order of the
+    /// prover. After the call, it holds the transcript of the proof, so that the challenges of
+    /// the proofs of `StarkyProver::prove_all` are recovered by calling this method on each proof
+    /// in turn with the same challenger, starting from the challenger of the first configuration.
+    /// The proof is expected to have been checked with `validate_shape`.
+    pub fn get_challenges_with_challenger<A: RAirData>(
+        &self,
+        config: &StarkyConfig<C, D>,
+        stark: &Starky<A>,
+        public_inputs: &[F],
+        degree_bits: usize,
+        challenger: &mut Challenger<F, C::Hasher>,
+    ) -> StarkProofChallenges<F, D> {
+        let StarkProof {
+            air_proof: AirProof { trace_caps, .. },
+            global_values,
+            ..
+        } = &self;
+
+        // Observe public inputs
+        challenger.observe_elements(public_inputs);
+
+        let mut challenges = vec![];
+        for (round, cap) in stark.air().round_data().iter().zip_eq(trace_caps.iter()) {
+            let (id_0, id_1) = round.global_values_range;
+            challenger.observe_elements(&global_values[id_0..id_1]);
+            challenger.observe_cap(cap);
+            let round_challenges = challenger.get_n_challenges(round.num_challenges);
+            challenges.extend(round_challenges);
+        }
+
+        self.get_iop_challenges(config, degree_bits, challenges, challenger)
+    }
+}
+
+/// A proof of several STARK computations sharing a single batch FRI argument.
+///
+/// The traces of all the STARKs are committed on the same transcript and all polynomials are
+/// opened at the same point, so that a single FRI proof covers every opening. This is much smaller
+/// than a `StarkProof` for each of the STARKs when proving many small tables.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(bound = "")]
+pub struct BatchStarkProof<F: RichField + Extendable<D>, C: CurtaConfig<D, F = F>, const D: usize> {
+    /// Merkle caps of LDEs of trace values for each STARK and each round.
+    pub trace_caps: Vec<Vec<MerkleCap<F, C::Hasher>>>,
+    /// Merkle caps of LDEs of the quotient polynomials of each STARK.
+    pub quotient_polys_caps: Vec<MerkleCap<F, C::Hasher>>,
+    /// Purported values of the polynomials of each STARK at the challenge point.
+    pub openings: Vec<StarkOpeningSet<F, D>>,
+    /// A batch FRI argument for the openings of all the STARKs.
+    pub opening_proof: FriProof<F, C::Hasher, D>,
+    /// The global values of each STARK.
+    pub global_values: Vec<Vec<F>>,
+}
+
+impl<F: RichField + Extendable<D>, C: CurtaConfig<D, F = F>, const D: usize>
+    BatchStarkProof<F, C, D>
+{
+    /// The number of STARKs in the batch.
+    pub fn num_starks(&self) -> usize {
+        self.openings.len()
+    }
+
+    /// The FRI openings of a batch, in the order of `Starky::batch_fri_instance`.
+    pub(crate) fn fri_openings(openings: &[StarkOpeningSet<F, D>]) -> FriOpenings<F, D> {
+        let zeta_batch = FriOpeningBatch {
+            values: openings
+                .iter()
+                .flat_map(|opening| opening.local_values.iter().chain(opening.quotient_values()))
+                .copied()
+                .collect::<Vec<_>>(),
+        };
+        let zeta_next_batch = FriOpeningBatch {
+            values: openings
+                .iter()
+                .flat_map(|opening| opening.next_values.iter())
+                .copied()
+                .collect::<Vec<_>>(),
+        };
+        FriOpenings {
+            batches: vec![zeta_batch, zeta_next_batch],
+        }
+    }
+
+    pub(crate) fn get_challenges<A: RAirData>(
+        &self,
+        config: &StarkyConfig<C, D>,
+        starks: &[&Starky<A>],
+        public_inputs: &[&[F]],
+    ) -> BatchStarkProofChallenges<F, D> {
+        let mut challenger = config.challenger();
+
+        let mut stark_betas = vec![];
+        for (((stark, trace_caps), global_values), public_inputs) in starks
+            .iter()
+            .zip_eq(self.trace_caps.iter())
+            .zip_eq(self.global_values.iter())
+            .zip_eq(public_inputs.iter())
+        {
+            // Observe public inputs
+            challenger.observe_elements(public_inputs);
+
+            let mut challenges = vec![];
+            for (round, cap) in stark.air().round_data().iter().zip_eq(trace_caps.iter()) {
+                let (id_0, id_1) = round.global_values_range;
+                challenger.observe_elements(&global_values[id_0..id_1]);
+                challenger.observe_cap(cap);
+                let round_challenges = challenger.get_n_challenges(round.num_challenges);
+                challenges.extend(round_challenges);
+            }
+            stark_betas.push(challenges);
+        }
+
+        let stark_alphas = challenger.get_n_challenges(config.num_challenges);
+
+        for cap in self.quotient_polys_caps.iter() {
+            challenger.observe_cap(cap);
+        }
+        let stark_zeta = challenger.get_extension_challenge::<D>();
+
+        challenger.observe_openings(&Self::fri_openings(&self.openings));
+
+        let FriProof {
+            commit_phase_merkle_caps,
+            final_poly,
+            pow_witness,
+            ..
+        } = &self.opening_proof;
+
+        BatchStarkProofChallenges {
+            stark_alphas,
+            stark_betas,
+            stark_zeta,
+            fri_challenges: challenger.fri_challenges::<C::GenericConfig, D>(
+                commit_phase_merkle_caps,
+                final_poly,
+                *pow_witness,
+                config.fri_degree_bits(),
+                &config.fri_config,
+            ),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AirProofTarget<const D: usize> {
+    #[serde(serialize_with = "serialize_merkle_cap_targets")]
+    #[serde(deserialize_with = "deserialize_merkle_cap_targets")]
+    pub trace_caps: Vec<MerkleCapTarget>,
+    #[serde(serialize_with = "serialize_merkle_cap_target")]
+    #[serde(deserialize_with = "deserialize_merkle_cap_target")]
+    pub quotient_polys_cap: MerkleCapTarget,
+    pub openings: StarkOpeningSetTarget<D>,
+    #[serde(serialize_with = "serialize_fri_proof_target")]
+    #[serde(deserialize_with = "deserialize_fri_proof_target")]
+    pub opening_proof: FriProofTarget<D>,
+}
+
+impl<const D: usize> AirProofTarget<D> {
+    pub fn get_iop_challenges_target<F: RichField + Extendable<D>, C: CurtaConfig<D, F = F>>(
+        &self,
+        builder: &mut CircuitBuilder<F, D>,
+        config: &StarkyConfig<C, D>,
+        air_challengs: Vec<Target>,
+        challenger: &mut RecursiveChallenger<F, C::InnerHasher, D>,
+    ) -> StarkProofChallengesTarget<D> {
+        let AirProofTarget {
+            quotient_polys_cap,
+            openings,
+            opening_proof:
+                FriProofTarget {
+                    commit_phase_merkle_caps,
+                    final_poly,
+                    pow_witness,
+                    ..
+                },
+            ..
+        } = &self;
+
+        let num_challenges = config.num_challenges;
+
+        let stark_alphas = challenger.get_n_challenges(builder, num_challenges);
+
+        challenger.observe_cap(quotient_polys_cap);
+        let stark_zeta = challenger.get_extension_challenge(builder);
+
+        challenger.observe_extension_elements(&openings.extra_points());
+        challenger.observe_openings(&openings.to_fri_openings());
+
+        StarkProofChallengesTarget {
+            stark_alphas,
+            stark_betas: air_challengs,
+            stark_zeta,
+            fri_challenges: challenger.fri_challenges(
+                builder,
+                commit_phase_merkle_caps,
+                final_poly,
+                *pow_witness,
+                &config.fri_config,
+            ),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct StarkProofTarget<const D: usize> {
+    pub air_proof: AirProofTarget<D>,
+    pub global_values: Vec<Target>,
+    #[serde(serialize_with = "serialize_option_hash_out_target")]
+    #[serde(deserialize_with = "deserialize_option_hash_out_target")]
+    pub public_inputs_commitment: Option<HashOutTarget>,
+}
+
+impl<const D: usize> StarkProofTarget<D> {
+    /// Recover the length of the trace from a STARK proof and a STARK config.
+    pub fn recover_degree_bits<F: RichField + Extendable<D>, C: CurtaConfig<D, F = F>>(
+        &self,
+        config: &StarkyConfig<C, D>,
+    ) -> usize {
+        let initial_merkle_proof = &self.air_proof.opening_proof.query_round_proofs[0]
+            .initial_trees_proof
+            .evals_proofs[0]
+            .1;
+        let lde_bits = config.fri_config.cap_height + initial_merkle_proof.siblings.len();
+        lde_bits - config.fri_config.rate_bits
+    }
+
+    pub fn get_iop_challenges_target<F: RichField + Extendable<D>, C: CurtaConfig<D, F = F>>(
+        &self,
+        builder: &mut CircuitBuilder<F, D>,
+        config: &StarkyConfig<C, D>,
+        air_challengs: Vec<Target>,
+        challenger: &mut RecursiveChallenger<F, C::InnerHasher, D>,
+    ) -> StarkProofChallengesTarget<D> {
+        self.air_proof
+            .get_iop_challenges_target(builder, config, air_challengs, challenger)
+    }
+
+    pub fn get_challenges_target<
+        F: RichField + Extendable<D>,
+        A: for<'a> RAir<RecursiveStarkParser<'a, F, D>>,
+        C: CurtaConfig<D, F = F>,
+    >(
+        &self,
+        builder: &mut CircuitBuilder<F, D>,
+        config: &StarkyConfig<C, D>,
+        public_inputs: &[Target],
+        stark: &Starky<A>,
+    ) -> StarkProofChallengesTarget<D> {
+        let StarkProofTarget {
+            air_proof: AirProofTarget { trace_caps, .. },
+            global_values,
+            ..
+        } = &self;
+
+        let mut challenger = config.recursive_challenger(builder);
+
+        // Observe public inputs
+        challenger.observe_elements(public_inputs);
+
+        let mut challenges = vec![];
+        for (round, cap) in stark.air().round_data().iter().zip(trace_caps.iter()) {
+            let (id_0, id_1) = round.global_values_range;
+            challenger.observe_elements(&global_values[id_0..id_1]);
+            challenger.observe_cap(cap);
+            let round_challenges = challenger.get_n_challenges(builder, round.num_challenges);
+            challenges.extend(round_challenges);
+        }
+
+        self.get_iop_challenges_target(builder, config, challenges, &mut challenger)
+    }
+}
+
+/// The Fiat-Shamir challenges of a STARK proof.
+#[derive(Debug)]
+pub struct StarkProofChallenges<F: RichField + Extendable<D>, const D: usize> {
+    /// Random values used to combine STARK constraints.
+    pub stark_alphas: Vec<F>,
+
+    /// Random values that can be used by the STARK for any purpose.
+    pub stark_betas: Vec<F>,
+
+    /// Point at which the STARK polynomials are opened.
+    pub stark_zeta: F::Extension,
+
+    /// Challenges of the FRI argument, including the query indices.
+    pub fri_challenges: FriChallenges<F, D>,
+}
+
+pub struct BatchStarkProofChallenges<F: RichField + Extendable<D>, const D: usize> {
+    /// Random values used to combine the constraints of all the STARKs.
+    pub stark_alphas: Vec<F>,
+
+    /// Random values used by each STARK for any purpose.
+    pub stark_betas: Vec<Vec<F>>,
+
+    /// Point at which the polynomials of all the STARKs are opened.
+    pub stark_zeta: F::Extension,
+
+    pub fri_challenges: FriChallenges<F, D>,
+}
+
+pub struct StarkProofChallengesTarget<const D: usize> {
+    pub stark_alphas: Vec<Target>,
+    pub stark_betas: Vec<Target>,
+    pub stark_zeta: ExtensionTarget<D>,
+    pub fri_challenges: FriChallengesTarget<D>,
+}
+
+/// Purported values of each polynomial at the challenge point.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(bound = "")]
+pub struct StarkOpeningSet<F: RichField + Extendable<D>, const D: usize> {
+    pub local_values: Vec<F::Extension>,
+    /// Values of the columns opened at the next row, given by `Starky::next_columns`.
+    pub next_values: Vec<F::Extension>,
+    /// Values of the quotient chunks, grouped by constraint challenge.
+    ///
+    /// The quotient polynomial `t(X)` of each challenge has degree less than `d * N`, where `d` is
+    /// the quotient degree factor of the AIR and `N` is the degree bound of the committed
+    /// polynomials. It is committed as `d` chunks `t_0, ..., t_{d-1}` of degree less than `N`,
+    /// with `t(X) = t_0(X) + t_1(X) * X^N + ... + t_{d-1}(X) * X^{(d-1)N}`.
+    pub quotient_chunks: Vec<Vec<F::Extension>>,
+    /// Values of the trace polynomials at additional points.
+    #[serde(default)]
+    pub extra_openings: Vec<PointOpening<F, D>>,
+}
+
+/// Purported values of the trace polynomials at a point other than `zeta` and `zeta * g`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(bound = "")]
+pub struct PointOpening<F: RichField + Extendable<D>, const D: usize> {
+    pub point: F::Extension,
+    pub values: Vec<F::Extension>,
+}
+
+impl<F: RichField + Extendable<D>, const D: usize> StarkOpeningSet<F, D> {
+    pub fn new<A: RAirData, C: GenericConfig<D, F = F>>(
+        stark: &Starky<A>,
+        zeta: F::Extension,
+        g: F,
+        trace_commitments: &[PolynomialBatch<F, C, D>],
+        quotient_commitment: &PolynomialBatch<F, C, D>,
+    ) -> Self {
+        Self::new_with_extra_points(stark, zeta, g, &[], trace_commitments, quotient_commitment)
+    }
+
+    /// Opens the trace polynomials at `extra_points` in addition to `zeta` and `zeta * g`.
+    pub fn new_with_extra_points<A: RAirData, C: GenericConfig<D, F = F>>(
+        stark: &Starky<A>,
+        zeta: F::Extension,
+        g: F,
+        extra_points: &[F::Extension],
+        trace_commitments: &[PolynomialBatch<F, C, D>],
+        quotient_commitment: &PolynomialBatch<F, C, D>,
+    ) -> Self {
+        let eval_commitment = |z: F::Extension, c: &PolynomialBatch<F, C, D>| {
+            c.polynomials
+                .par_iter()
+                .map(|p| eval_extension(p, z))
+                .collect::<Vec<_>>()
+        };
+        let zeta_next = zeta.scalar_mul(g);
+
+        let local_values = trace_commitments
+            .par_iter()
+            .flat_map(|trace| eval_commitment(zeta, trace))
+            .collect::<Vec<_>>();
+        let trace_polys = trace_commitments
+            .iter()
+            .flat_map(|trace| trace.polynomials.iter())
+            .collect::<Vec<_>>();
+        let next_values = stark
+            .next_columns()
+            .par_iter()
+            .map(|&column| eval_extension(trace_polys[column], zeta_next))
+            .collect::<Vec<_>>();
+        let quotient_chunks = eval_commitment(zeta, quotient_commitment)
+            .chunks(stark.num_quotient_chunks_with_blinding(quotient_commitment.blinding))
+            .map(|chunk| chunk.to_vec())
+            .collect();
+        let extra_openings = extra_points
+            .iter()
+            .map(|&point| PointOpening {
+                point,
+                values: trace_commitments
+                    .iter()
+                    .flat_map(|trace| eval_commitment(point, trace))
+                    .collect(),
+            })
+            .collect();
+        Self {
+            local_values,
+            next_values,
+            quotient_chunks,
+            extra_openings,
+        }
+    }
+
+    /// The values of all the quotient chunks, in the order in which they are committed.
+    pub fn quotient_values(&self) -> impl Iterator<Item = &F::Extension> {
+        self.quotient_chunks.iter().flatten()
+    }
+
+    /// Checks that there is a group of quotient chunks for every constraint challenge, each with
+    /// as many chunks as the quotient is split into.
+    pub fn validate_quotient_chunks<A: RAirData, C: CurtaConfig<D, F = F>>(
+        &self,
+        stark: &Starky<A>,
+        config: &StarkyConfig<C, D>,
+    ) -> Result<(), ProofShapeError> {
+        ProofShapeError::check(
+            ProofShapeError::NumQuotientGroups,
+            config.num_challenges,
+            self.quotient_chunks.len(),
+        )?;
+        let num_quotient_chunks = stark.num_quotient_chunks(config);
+        for chunks in self.quotient_chunks.iter() {
+            ProofShapeError::check(
+                ProofShapeError::NumQuotientPolys,
+                num_quotient_chunks,
+                chunks.len(),
+            )?;
+        }
+        Ok(())
+    }
+
+    /// The additional points at which the trace polynomials are opened.
+    pub fn extra_points(&self) -> Vec<F::Extension> {
+        self.extra_openings.iter().map(|o| o.point).collect()
+    }
+
+    /// The values of the trace polynomials at `point`, if it is one of the additional points.
+    pub fn extra_values(&self, point: F::Extension) -> Option<&[F::Extension]> {
+        self.extra_openings
+            .iter()
+            .find(|o| o.point == point)
+            .map(|o| o.values.as_slice())
+    }
+
+    pub(crate) fn to_fri_openings(&self) -> FriOpenings<F, D> {
+        let zeta_batch = FriOpeningBatch {
+            values: self
+                .local_values
+                .iter()
+                .chain(self.quotient_values())
+                .copied()
+                .collect::<Vec<_>>(),
+        };
+        let zeta_next_batch = FriOpeningBatch {
+            values: self.next_values.to_vec(),
+        };
+        let extra_batches = self.extra_openings.iter().map(|o| FriOpeningBatch {
+            values: o.values.clone(),
+        });
+        FriOpenings {
+            batches: [zeta_batch, zeta_next_batch]
+                .into_iter()
+                .chain(extra_batches)
+                .collect(),
+        }
+    }
+}
+
+/// Evaluates the base field polynomial `poly` at the extension point `z`.
+///
+/// Over the quadratic extension of Goldilocks, the Horner chain is computed in Montgomery form.
+fn eval_extension<F: RichField + Extendable<D>, const D: usize>(
+    poly: &PolynomialCoeffs<F>,
+    z: F::Extension,
+) -> F::Extension {
+    if D != 2 || F::ORDER != GoldilocksField::ORDER {
+        return poly.to_extension::<D>().eval(z);
+    }
+    let coeffs = poly
+        .coeffs
+        .iter()
+        .map(|c| GoldilocksField(c.to_canonical_u64()))
+        .collect::<Vec<_>>();
+    let z = z.to_basefield_array();
+    let value = montgomery::eval_quadratic(
+        &coeffs,
+        [0, 1].map(|i| GoldilocksField(z[i].to_canonical_u64())),
+        GoldilocksField(F::W.to_canonical_u64()),
+    );
+    F::Extension::from_basefield_array(core::array::from_fn(|i| F::from_canonical_u64(value[i].0)))
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct StarkOpeningSetTarget<const D: usize> {
+    #[serde(serialize_with = "serialize_extension_targets")]
+    #[serde(deserialize_with = "deserialize_extension_targets")]
+    pub local_values: Vec<ExtensionTarget<D>>,
+    #[serde(serialize_with = "serialize_extension_targets")]
+    #[serde(deserialize_with = "deserialize_extension_targets")]
+    pub next_values: Vec<ExtensionTarget<D>>,
+    /// Targets of the values of the quotient chunks, grouped by constraint challenge.
+    #[serde(serialize_with = "serialize_extension_target_groups")]
+    #[serde(deserialize_with = "deserialize_extension_target_groups")]
+    pub quotient_chunks: Vec<Vec<ExtensionTarget<D>>>,
+    #[serde(default)]
+    pub extra_openings: Vec<PointOpeningTarget<D>>,
+}
+
+/// The targets of the values of the trace polynomials at an additional point.
+///
+/// The point is a witness of the circuit. The verifier circuit constrains it to lie outside of the
+/// LDE domain and to differ from the other extra points, and it is up to the caller to constrain
+/// its value otherwise.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PointOpeningTarget<const D: usize> {
+    #[serde(serialize_with = "serialize_extension_target")]
+    #[serde(deserialize_with = "deserialize_extension_target")]
+    pub point: ExtensionTarget<D>,
+    #[serde(serialize_with = "serialize_extension_targets")]
+    #[serde(deserialize_with = "deserialize_extension_targets")]
+    pub values: Vec<ExtensionTarget<D>>,
+}
+
+impl<const D: usize> StarkOpeningSetTarget<D> {
+    /// The additional points at which the trace polynomials are opened.
+    pub fn extra_points(&self) -> Vec<ExtensionTarget<D>> {
+        self.extra_openings.iter().map(|o| o.point).collect()
+    }
+
+    pub(crate) fn to_fri_openings(&self) -> FriOpeningsTarget<D> {
+        let zeta_batch = FriOpeningBatchTarget {
+            values: self
+                .local_values
+                .iter()
+                .chain(self.quotient_chunks.iter().flatten())
+                .copied()
+                .collect::<Vec<_>>(),
+        };
+        let zeta_next_batch = FriOpeningBatchTarget {
+            values: self.next_values.to_vec(),
+        };
+        let extra_batches = self.extra_openings.iter().map(|o| FriOpeningBatchTarget {
+            values: o.values.clone(),
+        });
+        FriOpeningsTarget {
+            batches: [zeta_batch, zeta_next_batch]
+                .into_iter()
+                .chain(extra_batches)
+                .collect(),
+        }
+    }
+}
@@ -0,0 +1,374 @@
+//! Proof targets with the shape of a proof.
+//!
+//! Setting a proof in the witness of a target of a different shape, such as a target for an AIR
+//! with a different number of columns, fails deep in the witness generation of plonky2 with
+//! errors which do not point to the mismatch. Instead, the targets can be created with the shape
+//! of a given proof, and a proof is checked against the shape of the target before it is set.
+
+use core::iter::once;
+
+use itertools::Itertools;
+use plonky2::field::extension::Extendable;
+use plonky2::fri::proof::{
+    FriInitialTreeProofTarget, FriProof, FriProofTarget, FriQueryRoundTarget, FriQueryStepTarget,
+};
+use plonky2::gadgets::polynomial::PolynomialCoeffsExtTarget;
+use plonky2::hash::hash_types::{MerkleCapTarget, RichField};
+use plonky2::hash::merkle_proofs::{MerkleProof, MerkleProofTarget};
+use plonky2::plonk::circuit_builder::CircuitBuilder;
+use plonky2::plonk::config::Hasher;
+use plonky2::util::log2_strict;
+
+use super::{
+    AirProof, AirProofTarget, PointOpeningTarget, ProofShapeError, StarkOpeningSetTarget,
+    StarkProof, StarkProofTarget,
+};
+use crate::plonky2::stark::config::CurtaConfig;
+
+impl<const D: usize> StarkProofTarget<D> {
+    /// Virtual targets with the same shape as `proof`.
+    pub fn from_proof<F, C>(builder: &mut CircuitBuilder<F, D>, proof: &StarkProof<F, C, D>) -> Self
+    where
+        F: RichField + Extendable<D>,
+        C: CurtaConfig<D, F = F>,
+    {
+        let AirProof {
+            trace_caps,
+            quotient_polys_cap,
+            openings,
+            opening_proof,
+        } = &proof.air_proof;
+
+        let openings = StarkOpeningSetTarget {
+            local_values: builder.add_virtual_extension_targets(openings.local_values.len()),
+            next_values: builder.add_virtual_extension_targets(openings.next_values.len()),
+            quotient_chunks: openings
+                .quotient_chunks
+                .iter()
+                .map(|chunks| builder.add_virtual_extension_targets(chunks.len()))
+                .collect(),
+            extra_openings: openings
+                .extra_openings
+                .iter()
+                .map(|opening| PointOpeningTarget {
+                    point: builder.add_virtual_extension_target(),
+                    values: builder.add_virtual_extension_targets(opening.values.len()),
+                })
+                .collect(),
+        };
+
+        let air_proof = AirProofTarget {
+            trace_caps: trace_caps
+                .iter()
+                .map(|cap| builder.add_virtual_cap(cap.height()))
+                .collect(),
+            quotient_polys_cap: builder.add_virtual_cap(quotient_polys_cap.height()),
+            openings,
+            opening_proof: fri_proof_target(builder, opening_proof),
+        };
+
+        Self {
+            air_proof,
+            global_values: builder.add_virtual_targets(proof.global_values.len()),
+            public_inputs_commitment: proof
+                .public_inputs_commitment
+                .map(|_| builder.add_virtual_hash()),
+        }
+    }
+
+    /// Checks that `proof` has the shape of the target, so that it can be set in a witness.
+    pub fn check_proof_shape<F, C>(
+        &self,
+        proof: &StarkProof<F, C, D>,
+    ) -> Result<(), ProofShapeError>
+    where
+        F: RichField + Extendable<D>,
+        C: CurtaConfig<D, F = F>,
+    {
+        let target = &self.air_proof;
+        let air_proof = &proof.air_proof;
+
+        ProofShapeError::check(
+            ProofShapeError::NumTraceCaps,
+            target.trace_caps.len(),
+            air_proof.trace_caps.len(),
+        )?;
+        for (target_cap, cap) in target
+            .trace_caps
+            .iter()
+            .chain(once(&target.quotient_polys_cap))
+            .zip(
+                air_proof
+                    .trace_caps
+                    .iter()
+                    .chain(once(&air_proof.quotient_polys_cap)),
+            )
+        {
+            ProofShapeError::check(
+                ProofShapeError::CapHeight,
+                cap_height(target_cap),
+                cap.height(),
+            )?;
+        }
+        ProofShapeError::check(
+            ProofShapeError::NumGlobalValues,
+            self.global_values.len(),
+            proof.global_values.len(),
+        )?;
+
+        let (target_openings, openings) = (&target.openings, &air_proof.openings);
+        ProofShapeError::check(
+            ProofShapeError::NumLocalValues,
+            target_openings.local_values.len(),
+            openings.local_values.len(),
+        )?;
+        ProofShapeError::check(
+            ProofShapeError::NumNextValues,
+            target_openings.next_values.len(),
+            openings.next_values.len(),
+        )?;
+        ProofShapeError::check(
+            ProofShapeError::NumQuotientGroups,
+            target_openings.quotient_chunks.len(),
+            openings.quotient_chunks.len(),
+        )?;
+        for (target_chunks, chunks) in target_openings
+            .quotient_chunks
+            .iter()
+            .zip(openings.quotient_chunks.iter())
+        {
+            ProofShapeError::check(
+                ProofShapeError::NumQuotientPolys,
+                target_chunks.len(),
+                chunks.len(),
+            )?;
+        }
+        ProofShapeError::check(
+            ProofShapeError::NumExtraOpenings,
+            target_openings.extra_openings.len(),
+            openings.extra_openings.len(),
+        )?;
+        for (target_opening, opening) in target_openings
+            .extra_openings
+            .iter()
+            .zip(openings.extra_openings.iter())
+        {
+            ProofShapeError::check(
+                ProofShapeError::NumExtraValues,
+                target_opening.values.len(),
+                opening.values.len(),
+            )?;
+        }
+        ProofShapeError::check(
+            ProofShapeError::NumPublicInputsCommitments,
+            self.public_inputs_commitment.is_some() as usize,
+            proof.public_inputs_commitment.is_some() as usize,
+        )?;
+
+        check_fri_proof_shape(&target.opening_proof, &air_proof.opening_proof)
+    }
+}
+
+fn cap_height(cap: &MerkleCapTarget) -> usize {
+    log2_strict(cap.0.len())
+}
+
+fn merkle_proof_target<F: RichField + Extendable<D>, H: Hasher<F>, const D: usize>(
+    builder: &mut CircuitBuilder<F, D>,
+    proof: &MerkleProof<F, H>,
+) -> MerkleProofTarget {
+    MerkleProofTarget {
+        siblings: builder.add_virtual_hashes(proof.siblings.len()),
+    }
+}
+
+fn fri_proof_target<F: RichField + Extendable<D>, H: Hasher<F>, const D: usize>(
+    builder: &mut CircuitBuilder<F, D>,
+    proof: &FriProof<F, H, D>,
+) -> FriProofTarget<D> {
+    let commit_phase_merkle_caps = proof
+        .commit_phase_merkle_caps
+        .iter()
+        .map(|cap| builder.add_virtual_cap(cap.height()))
+        .collect();
+    let query_round_proofs = proof
+        .query_round_proofs
+        .iter()
+        .map(|round| FriQueryRoundTarget {
+            initial_trees_proof: FriInitialTreeProofTarget {
+                evals_proofs: round
+                    .initial_trees_proof
+                    .evals_proofs
+                    .iter()
+                    .map(|(leaf, merkle_proof)| {
+                        (
+                            builder.add_virtual_targets(leaf.len()),
+                            merkle_proof_target(builder, merkle_proof),
+                        )
+                    })
+                    .collect(),
+            },
+            steps: round
+                .steps
+                .iter()
+                .map(|step| FriQueryStepTarget {
+                    evals: builder.add_virtual_extension_targets(step.evals.len()),
+                    merkle_proof: merkle_proof_target(builder, &step.merkle_proof),
+                })
+                .collect(),
+        })
+        .collect();
+
+    FriProofTarget {
+        commit_phase_merkle_caps,
+        query_round_proofs,
+        final_poly: PolynomialCoeffsExtTarget(
+            builder.add_virtual_extension_targets(proof.final_poly.len()),
+        ),
+        pow_witness: builder.add_virtual_target(),
+    }
+}
+
+fn check_fri_proof_shape<F: RichField + Extendable<D>, H: Hasher<F>, const D: usize>(
+    target: &FriProofTarget<D>,
+    proof: &FriProof<F, H, D>,
+) -> Result<(), ProofShapeError> {
+    ProofShapeError::check(
+        ProofShapeError::NumFriLayers,
+        target.commit_phase_merkle_caps.len(),
+        proof.commit_phase_merkle_caps.len(),
+    )?;
+    for (target_cap, cap) in target
+        .commit_phase_merkle_caps
+        .iter()
+        .zip(proof.commit_phase_merkle_caps.iter())
+    {
+        ProofShapeError::check(
+            ProofShapeError::CapHeight,
+            cap_height(target_cap),
+            cap.height(),
+        )?;
+    }
+    ProofShapeError::check(
+        ProofShapeError::NumQueryRounds,
+        target.query_round_proofs.len(),
+        proof.query_round_proofs.len(),
+    )?;
+    for (target_round, round) in target
+        .query_round_proofs
+        .iter()
+        .zip_eq(proof.query_round_proofs.iter())
+    {
+        let target_trees = &target_round.initial_trees_proof.evals_proofs;
+        let trees = &round.initial_trees_proof.evals_proofs;
+        ProofShapeError::check(
+            ProofShapeError::NumInitialTrees,
+            target_trees.len(),
+            trees.len(),
+        )?;
+        for ((target_leaf, target_path), (leaf, path)) in target_trees.iter().zip(trees.iter()) {
+            ProofShapeError::check(
+                ProofShapeError::NumLeafValues,
+                target_leaf.len(),
+                leaf.len(),
+            )?;
+            ProofShapeError::check(
+                ProofShapeError::MerkleProofLength,
+                target_path.siblings.len(),
+                path.siblings.len(),
+            )?;
+        }
+        ProofShapeError::check(
+            ProofShapeError::NumFriLayers,
+            target_round.steps.len(),
+            round.steps.len(),
+        )?;
+        for (target_step, step) in target_round.steps.iter().zip(round.steps.iter()) {
+            ProofShapeError::check(
+                ProofShapeError::NumFriStepValues,
+                target_step.evals.len(),
+                step.evals.len(),
+            )?;
+            ProofShapeError::check(
+                ProofShapeError::MerkleProofLength,
+                target_step.merkle_proof.siblings.len(),
+                step.merkle_proof.siblings.len(),
+            )?;
+        }
+    }
+    ProofShapeError::check(
+        ProofShapeError::FinalPolyLength,
+        target.final_poly.0.len(),
+        proof.final_poly.len(),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use plonky2::field::goldilocks_field::GoldilocksField;
+    use plonky2::iop::witness::{PartialWitness, WitnessWrite};
+    use plonky2::plonk::circuit_data::CircuitConfig;
+
+    use super::*;
+    use crate::air::fibonacci::FibonacciAir;
+    use crate::math::prelude::*;
+    use crate::plonky2::stark::config::{
+        CurtaPoseidonGoldilocksConfig, PoseidonGoldilocksStarkConfig,
+    };
+    use crate::plonky2::stark::gadget::StarkGadget;
+    use crate::plonky2::stark::prover::StarkyProver;
+    use crate::plonky2::stark::verifier::set_proof_target;
+    use crate::plonky2::stark::Starky;
+    use crate::trace::generator::ConstantGenerator;
+
+    #[test]
+    fn test_proof_target_from_proof() {
+        type F = GoldilocksField;
+        type C = CurtaPoseidonGoldilocksConfig;
+        type SC = PoseidonGoldilocksStarkConfig;
+
+        let stark = Starky::<FibonacciAir>::new(FibonacciAir::new());
+        let prove = |num_rows: usize| {
+            let public_inputs = vec![
+                F::ZERO,
+                F::ONE,
+                FibonacciAir::fibonacci(num_rows - 1, F::ZERO, F::ONE),
+            ];
+            let trace_generator =
+                ConstantGenerator::new(FibonacciAir::generate_trace(F::ZERO, F::ONE, num_rows));
+            let config = SC::standard_fast_config(num_rows);
+            let proof =
+                StarkyProver::<F, C, 2>::prove(&config, &stark, &trace_generator, &public_inputs)
+                    .unwrap();
+            (config, proof, public_inputs)
+        };
+
+        let (config, proof, public_inputs) = prove(1 << 5);
+
+        let config_rec = CircuitConfig::standard_recursion_config();
+        let mut builder = CircuitBuilder::<F, 2>::new(config_rec);
+        let virtual_proof = StarkProofTarget::from_proof(&mut builder, &proof);
+        let public_input_targets = builder.add_virtual_targets(public_inputs.len());
+        builder.verify_stark_proof(&config, &stark, &virtual_proof, &public_input_targets);
+
+        // A proof of a longer trace has longer Merkle proofs.
+        let (_, other_proof, _) = prove(1 << 6);
+        assert_eq!(
+            virtual_proof.check_proof_shape(&other_proof),
+            Err(ProofShapeError::MerkleProofLength {
+                expected: 2,
+                found: 3
+            })
+        );
+
+        let mut pw = PartialWitness::new();
+        assert!(set_proof_target(&mut pw, &virtual_proof, &other_proof).is_err());
+        pw.set_target_arr(&public_input_targets, &public_inputs)
+            .unwrap();
+        set_proof_target(&mut pw, &virtual_proof, &proof).unwrap();
+
+        let data = builder.build::<<C as CurtaConfig<2>>::GenericConfig>();
+        let recursive_proof = data.prove(pw).unwrap();
+        data.verify(recursive_proof).unwrap();
+    }
+}
@@ -0,0 +1,225 @@
+//! Mutation testing of proofs.
+//!
+//! Every value of a proof is bound by the verifier, either directly or through the transcript, so
+//! changing any of them must make a valid proof fail to verify. The functions of this module take
+//! a valid proof and change each of its commitments, openings, FRI values and global values in
+//! turn, which catches the values left unchecked by a change to the layout of the openings.
+
+use plonky2::field::extension::Extendable;
+use plonky2::field::types::Field;
+use plonky2::hash::hash_types::RichField;
+use plonky2::hash::merkle_tree::MerkleCap;
+use plonky2::plonk::config::{GenericHashOut, Hasher};
+
+use super::StarkProof;
+use crate::plonky2::stark::config::{CurtaConfig, StarkyConfig};
+use crate::plonky2::stark::verifier::StarkyVerifier;
+use crate::plonky2::stark::Starky;
+use crate::plonky2::StarkyAir;
+
+/// A proof in which a single value was changed.
+#[derive(Debug, Clone)]
+pub struct ProofMutation<F: RichField + Extendable<D>, C: CurtaConfig<D, F = F>, const D: usize> {
+    /// The location of the changed value in the proof.
+    pub description: String,
+    pub proof: StarkProof<F, C, D>,
+}
+
+fn mutate_cap<F: RichField, H: Hasher<F>>(cap: &mut MerkleCap<F, H>) {
+    let mut bytes = cap.0[0].to_bytes();
+    bytes[0] ^= 1;
+    cap.0[0] = H::Hash::from_bytes(&bytes);
+}
+
+/// Returns a copy of `proof` for each of its values, in which that value is changed.
+///
+/// All the commitments, openings and global values are changed, as well as the FRI commitments,
+/// the final polynomial, the proof-of-work witness and the first value of each Merkle opening of
+/// the first query round.
+pub fn mutations<F, C, const D: usize>(proof: &StarkProof<F, C, D>) -> Vec<ProofMutation<F, C, D>>
+where
+    F: RichField + Extendable<D>,
+    C: CurtaConfig<D, F = F>,
+{
+    let mut mutations = vec![];
+    let mut push = |description: String, mutate: &dyn Fn(&mut StarkProof<F, C, D>)| {
+        let mut proof = proof.clone();
+        mutate(&mut proof);
+        mutations.push(ProofMutation { description, proof });
+    };
+
+    let air_proof = &proof.air_proof;
+    let openings = &air_proof.openings;
+    let opening_proof = &air_proof.opening_proof;
+
+    for i in 0..air_proof.trace_caps.len() {
+        push(format!("trace cap {}", i), &|p| {
+            mutate_cap(&mut p.air_proof.trace_caps[i])
+        });
+    }
+    push("quotient cap".to_string(), &|p| {
+        mutate_cap(&mut p.air_proof.quotient_polys_cap)
+    });
+
+    for i in 0..openings.local_values.len() {
+        push(format!("local value {}", i), &|p| {
+            p.air_proof.openings.local_values[i] += F::Extension::ONE
+        });
+    }
+    for i in 0..openings.next_values.len() {
+        push(format!("next value {}", i), &|p| {
+            p.air_proof.openings.next_values[i] += F::Extension::ONE
+        });
+    }
+    for (i, chunks) in openings.quotient_chunks.iter().enumerate() {
+        for j in 0..chunks.len() {
+            push(format!("quotient chunk {} of challenge {}", j, i), &|p| {
+                p.air_proof.openings.quotient_chunks[i][j] += F::Extension::ONE
+            });
+        }
+    }
+    for (i, opening) in openings.extra_openings.iter().enumerate() {
+        push(format!("point of extra opening {}", i), &|p| {
+            p.air_proof.openings.extra_openings[i].point += F::Extension::ONE
+        });
+        for j in 0..opening.values.len() {
+            push(format!("value {} of extra opening {}", j, i), &|p| {
+                p.air_proof.openings.extra_openings[i].values[j] += F::Extension::ONE
+            });
+        }
+    }
+
+    for i in 0..opening_proof.commit_phase_merkle_caps.len() {
+        push(format!("FRI commit phase cap {}", i), &|p| {
+            mutate_cap(&mut p.air_proof.opening_proof.commit_phase_merkle_caps[i])
+        });
+    }
+    if !opening_proof.final_poly.coeffs.is_empty() {
+        push("FRI final polynomial".to_string(), &|p| {
+            p.air_proof.opening_proof.final_poly.coeffs[0] += F::Extension::ONE
+        });
+    }
+    push("proof-of-work witness".to_string(), &|p| {
+        p.air_proof.opening_proof.pow_witness += F::ONE
+    });
+    if let Some(round) = opening_proof.query_round_proofs.first() {
+        for (i, (leaf, _)) in round.initial_trees_proof.evals_proofs.iter().enumerate() {
+            if !leaf.is_empty() {
+                push(format!("FRI initial tree {} opening", i), &|p| {
+                    p.air_proof.opening_proof.query_round_proofs[0]
+                        .initial_trees_proof
+                        .evals_proofs[i]
+                        .0[0] += F::ONE
+                });
+            }
+        }
+        for i in 0..round.steps.len() {
+            push(format!("FRI query step {} opening", i), &|p| {
+                p.air_proof.opening_proof.query_round_proofs[0].steps[i].evals[0] +=
+                    F::Extension::ONE
+            });
+        }
+    }
+
+    for i in 0..proof.global_values.len() {
+        push(format!("global value {}", i), &|p| {
+            p.global_values[i] += F::ONE
+        });
+    }
+    if proof.public_inputs_commitment.is_some() {
+        push("public inputs commitment".to_string(), &|p| {
+            let commitment = p.public_inputs_commitment.as_mut().unwrap();
+            commitment.elements[0] += F::ONE
+        });
+    }
+
+    mutations
+}
+
+/// Returns the descriptions of the mutations of `proof` which are accepted by the verifier.
+///
+/// The result is empty for a sound verifier.
+pub fn accepted_mutations<A, F, C, const D: usize>(
+    config: &StarkyConfig<C, D>,
+    stark: &Starky<A>,
+    proof: &StarkProof<F, C, D>,
+    public_inputs: &[F],
+) -> Vec<String>
+where
+    A: StarkyAir<F, D>,
+    F: RichField + Extendable<D>,
+    C: CurtaConfig<D, F = F, FE = F::Extension>,
+{
+    mutations(proof)
+        .into_iter()
+        .filter(|mutation| {
+            StarkyVerifier::verify(config, stark, mutation.proof.clone(), public_inputs).is_ok()
+        })
+        .map(|mutation| mutation.description)
+        .collect()
+}
+
+/// Checks that `proof` verifies and that none of its mutations do.
+///
+/// # Panics
+/// Panics if the proof is rejected or if a mutation of the proof is accepted.
+pub fn assert_mutations_rejected<A, F, C, const D: usize>(
+    config: &StarkyConfig<C, D>,
+    stark: &Starky<A>,
+    proof: &StarkProof<F, C, D>,
+    public_inputs: &[F],
+) where
+    A: StarkyAir<F, D>,
+    F: RichField + Extendable<D>,
+    C: CurtaConfig<D, F = F, FE = F::Extension>,
+{
+    StarkyVerifier::verify(config, stark, proof.clone(), public_inputs)
+        .expect("The original proof must be valid");
+    let accepted = accepted_mutations(config, stark, proof, public_inputs);
+    assert!(
+        accepted.is_empty(),
+        "Mutated proofs were accepted: {}",
+        accepted.join(", ")
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use plonky2::field::goldilocks_field::GoldilocksField;
+
+    use super::*;
+    use crate::air::fibonacci::FibonacciAir;
+    use crate::plonky2::stark::config::{
+        CurtaPoseidonGoldilocksConfig, PoseidonGoldilocksStarkConfig,
+    };
+    use crate::plonky2::stark::prover::StarkyProver;
+    use crate::trace::generator::ConstantGenerator;
+
+    #[test]
+    fn test_proof_mutations_rejected() {
+        type F = GoldilocksField;
+        type C = CurtaPoseidonGoldilocksConfig;
+        type SC = PoseidonGoldilocksStarkConfig;
+
+        let num_rows = 1 << 5usize;
+        let stark = Starky::new(FibonacciAir::new());
+        let public_inputs = [
+            F::ZERO,
+            F::ONE,
+            FibonacciAir::fibonacci(num_rows - 1, F::ZERO, F::ONE),
+        ];
+        let trace_generator =
+            ConstantGenerator::new(FibonacciAir::generate_trace(F::ZERO, F::ONE, num_rows));
+
+        let config = SC::standard_fast_config(num_rows).with_public_inputs_commitment();
+        let proof =
+            StarkyProver::<F, C, 2>::prove(&config, &stark, &trace_generator, &public_inputs)
+                .unwrap();
+
+        let mutations = mutations(&proof);
+        assert!(mutations
+            .iter()
+            .any(|mutation| mutation.description == "proof-of-work witness"));
+        assert_mutations_rejected(&config, &stark, &proof, &public_inputs);
+    }
+}
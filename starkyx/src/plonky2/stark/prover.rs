@@ -2,10 +2,11 @@
 
 use core::fmt::Debug;
 use core::iter::once;
+use std::path::Path;
 
 use anyhow::{ensure, Result};
-use plonky2::field::extension::Extendable;
-use plonky2::field::packable::Packable;
+use itertools::Itertools;
+use plonky2::field::extension::{Extendable, FieldExtension};
 use plonky2::field::packed::PackedField;
 use plonky2::field::polynomial::{PolynomialCoeffs, PolynomialValues};
 use plonky2::field::types::Field;
@@ -13,21 +14,33 @@ use plonky2::field::zero_poly_coset::ZeroPolyOnCoset;
 use plonky2::fri::oracle::PolynomialBatch;
 use plonky2::hash::hash_types::RichField;
 use plonky2::iop::challenger::Challenger;
+use plonky2::plonk::config::GenericConfig;
 use plonky2::util::timing::TimingTree;
 use plonky2::util::{log2_ceil, transpose};
 
+use super::commitment::TraceCommitment;
 use super::config::{CurtaConfig, StarkyConfig};
+use super::deferred::{AggregatedStarkProof, PartialStarkProof};
+use super::grinding::{prove_openings_with_grinder, PowGrinder};
+use super::shared::SharedInputsProof;
+use super::spill::SpillFile;
 use super::Starky;
+use crate::air::RAir;
 use crate::maybe_rayon::*;
 use crate::plonky2::parser::consumer::ConstraintConsumer;
 use crate::plonky2::parser::StarkParser;
-use crate::plonky2::stark::proof::{AirProof, StarkOpeningSet, StarkProof};
-use crate::plonky2::StarkyAir;
+use crate::plonky2::stark::proof::{AirProof, BatchStarkProof, StarkOpeningSet, StarkProof};
+use crate::plonky2::{ntt, ProverAir, StarkyAir};
+use crate::trace::columns::ColumnTraceView;
 use crate::trace::generator::TraceGenerator;
 
 #[derive(Debug, Clone)]
 pub struct StarkyProver<F, C, const D: usize>(core::marker::PhantomData<(F, C)>);
 
+/// The committed rounds of a trace.
+///
+/// The polynomial batches hold the coefficients and the LDE of every column, including the secret
+/// registers, so they are overwritten with zeros when the commitment is dropped.
 #[derive(Debug)]
 pub struct AirCommitment<F: RichField + Extendable<D>, C: CurtaConfig<D, F = F>, const D: usize> {
     pub trace_commitments: Vec<PolynomialBatch<F, C::GenericConfig, D>>,
@@ -36,7 +49,94 @@ pub struct AirCommitment<F: RichField + Extendable<D>, C: CurtaConfig<D, F = F>,
     pub challenges: Vec<F>,
 }
 
-type P<F> = <F as Packable>::Packing;
+impl<F: RichField + Extendable<D>, C: CurtaConfig<D, F = F>, const D: usize>
+    AirCommitment<F, C, D>
+{
+    /// Overwrites the coefficients and the LDE leaves of the committed rounds with zeros.
+    pub fn zeroize(&mut self) {
+        self.trace_commitments.iter_mut().for_each(zeroize_batch);
+    }
+}
+
+impl<F: RichField + Extendable<D>, C: CurtaConfig<D, F = F>, const D: usize> Drop
+    for AirCommitment<F, C, D>
+{
+    fn drop(&mut self) {
+        self.zeroize();
+    }
+}
+
+/// A proof whose FRI argument is deferred, together with the committed polynomials which the
+/// FRI argument opens.
+///
+/// Deferred proofs are aggregated into a single FRI argument by `StarkyProver::aggregate`. As for
+/// an `AirCommitment`, the committed polynomials are overwritten with zeros when the proof is
+/// dropped.
+#[derive(Debug)]
+pub struct DeferredStarkProof<
+    F: RichField + Extendable<D>,
+    C: CurtaConfig<D, F = F>,
+    const D: usize,
+> {
+    pub trace_commitments: Vec<PolynomialBatch<F, C::GenericConfig, D>>,
+    pub quotient_commitment: PolynomialBatch<F, C::GenericConfig, D>,
+    /// The challenge point at which the polynomials are opened.
+    pub zeta: F::Extension,
+    pub openings: StarkOpeningSet<F, D>,
+    pub public_inputs: Vec<F>,
+    pub global_values: Vec<F>,
+}
+
+impl<F: RichField + Extendable<D>, C: CurtaConfig<D, F = F>, const D: usize>
+    DeferredStarkProof<F, C, D>
+{
+    /// The commitments and openings of the proof, without the committed polynomials.
+    pub fn partial_proof(&self) -> PartialStarkProof<F, C, D> {
+        PartialStarkProof {
+            trace_caps: self
+                .trace_commitments
+                .iter()
+                .map(|c| c.merkle_tree.cap.clone())
+                .collect(),
+            quotient_polys_cap: self.quotient_commitment.merkle_tree.cap.clone(),
+            openings: self.openings.clone(),
+            global_values: self.global_values.clone(),
+        }
+    }
+
+    /// Overwrites the coefficients and the LDE leaves of the committed polynomials with zeros.
+    pub fn zeroize(&mut self) {
+        self.trace_commitments.iter_mut().for_each(zeroize_batch);
+        zeroize_batch(&mut self.quotient_commitment);
+    }
+}
+
+impl<F: RichField + Extendable<D>, C: CurtaConfig<D, F = F>, const D: usize> Drop
+    for DeferredStarkProof<F, C, D>
+{
+    fn drop(&mut self) {
+        self.zeroize();
+    }
+}
+
+/// Overwrites the coefficients and the LDE leaves of `batch` with zeros.
+///
+/// The batch is passed to `black_box` afterwards so that the writes are not optimized away when
+/// the batch is dropped right after.
+fn zeroize_batch<F: RichField + Extendable<D>, G: GenericConfig<D, F = F>, const D: usize>(
+    batch: &mut PolynomialBatch<F, G, D>,
+) {
+    for polynomial in batch.polynomials.iter_mut() {
+        polynomial.coeffs.fill(F::ZERO);
+    }
+    for leaf in batch.merkle_tree.leaves.iter_mut() {
+        leaf.fill(F::ZERO);
+    }
+    core::hint::black_box(batch);
+}
+
+/// The number of points of the quotient domain whose LDE values are transposed at a time.
+const QUOTIENT_BLOCK_ROWS: usize = 1 << 10;
 
 impl<F, C, const D: usize> StarkyProver<F, C, D>
 where
@@ -55,6 +155,37 @@ where
         challenger: &mut Challenger<F, C::Hasher>,
         timing: &mut TimingTree,
     ) -> Result<AirCommitment<F, C, D>>
+    where
+        A: StarkyAir<F, D>,
+        T: TraceGenerator<F, A>,
+        T::Error: Into<anyhow::Error>,
+    {
+        Self::generate_trace_with_spill(
+            config,
+            stark,
+            public_inputs,
+            trace_generator,
+            None,
+            challenger,
+            timing,
+        )
+    }
+
+    /// Generates and commits to the trace of every round.
+    ///
+    /// If `spill_dir` is given, the polynomial batch of each round is appended to a file in it once
+    /// its cap has been observed, and the batches are read back after the last round. Spilling is
+    /// refused if the trace generator holds secret registers, which would otherwise be written to
+    /// disk.
+    pub fn generate_trace_with_spill<A, T>(
+        config: &StarkyConfig<C, D>,
+        stark: &Starky<A>,
+        public_inputs: &[F],
+        trace_generator: &T,
+        spill_dir: Option<&Path>,
+        challenger: &mut Challenger<F, C::Hasher>,
+        timing: &mut TimingTree,
+    ) -> Result<AirCommitment<F, C, D>>
     where
         A: StarkyAir<F, D>,
         T: TraceGenerator<F, A>,
@@ -66,13 +197,16 @@ where
         // Oberve public inputs
         challenger.observe_elements(public_inputs);
 
-        let rate_bits = config.fri_config.rate_bits;
-        let cap_height = config.fri_config.cap_height;
+        ensure!(
+            spill_dir.is_none() || !trace_generator.has_secrets(),
+            "The trace of a generator with secret registers cannot be spilled to disk"
+        );
 
         let mut trace_commitments = Vec::new();
+        let mut spill_file = spill_dir.map(SpillFile::create).transpose()?;
         for (r, round) in stark.air().round_data().iter().enumerate() {
             let (id_0, id_1) = round.global_values_range;
-            let round_trace = trace_generator
+            let mut round_trace = trace_generator
                 .generate_round(
                     stark.air(),
                     r,
@@ -82,24 +216,28 @@ where
                 )
                 .map_err(|e| e.into())?;
 
-            let trace_cols = round_trace
-                .as_columns()
-                .into_par_iter()
-                .map(PolynomialValues::from)
-                .collect::<Vec<_>>();
-
-            let commitment = PolynomialBatch::<F, C::GenericConfig, D>::from_values(
-                trace_cols, rate_bits, false, cap_height, timing, None,
-            );
+            let commitment = config.commit(&round_trace, timing);
+            // The values are no longer needed once committed to, and may contain secrets.
+            round_trace.zeroize();
             challenger.observe_elements(&global_values[id_0..id_1]);
             let cap = commitment.merkle_tree.cap.clone();
             challenger.observe_cap(&cap);
-            trace_commitments.push(commitment);
+            match spill_file.as_mut() {
+                Some(file) => file.spill(commitment)?,
+                None => trace_commitments.push(commitment),
+            }
 
             // Get the challenges for next round
             let round_challenges = challenger.get_n_challenges(round.num_challenges);
             challenges.extend(round_challenges);
         }
+        // Every round is committed to, so the secret values of the generator are no longer needed.
+        trace_generator.zeroize_secrets();
+        if let Some(file) = spill_file {
+            for batch in file.read_rounds()? {
+                trace_commitments.push(batch?);
+            }
+        }
 
         Ok(AirCommitment {
             trace_commitments,
@@ -109,76 +247,235 @@ where
         })
     }
 
-    pub fn prove_with_trace<A: StarkyAir<F, D>>(
+    pub fn prove_with_trace<A: ProverAir<F, C, D>>(
         config: &StarkyConfig<C, D>,
         stark: &Starky<A>,
         air_commitment: AirCommitment<F, C, D>,
         challenger: &mut Challenger<F, C::Hasher>,
         timing: &mut TimingTree,
     ) -> Result<StarkProof<F, C, D>> {
-        let AirCommitment {
-            trace_commitments,
-            public_inputs,
-            global_values,
-            challenges,
-        } = air_commitment;
+        Self::prove_with_trace_and_extra_points(
+            config,
+            stark,
+            air_commitment,
+            &[],
+            challenger,
+            timing,
+        )
+    }
+
+    /// Proves the trace, opening the trace polynomials at `extra_points` in addition to the
+    /// challenge points.
+    ///
+    /// Returns an error if the extra points are not distinct, or if one of them lies in the LDE
+    /// domain.
+    pub fn prove_with_trace_and_extra_points<A: ProverAir<F, C, D>>(
+        config: &StarkyConfig<C, D>,
+        stark: &Starky<A>,
+        air_commitment: AirCommitment<F, C, D>,
+        extra_points: &[F::Extension],
+        challenger: &mut Challenger<F, C::Hasher>,
+        timing: &mut TimingTree,
+    ) -> Result<StarkProof<F, C, D>> {
+        Self::prove_with_trace_and_grinder::<A, C::Packing>(
+            config,
+            stark,
+            air_commitment,
+            extra_points,
+            None,
+            challenger,
+            timing,
+        )
+    }
+
+    /// Proves the trace, searching the proof-of-work witness of the FRI argument with `grinder`
+    /// instead of the search of `PolynomialBatch::prove_openings` if one is given.
+    fn prove_with_trace_and_grinder<A, Q>(
+        config: &StarkyConfig<C, D>,
+        stark: &Starky<A>,
+        air_commitment: AirCommitment<F, C, D>,
+        extra_points: &[F::Extension],
+        grinder: Option<&dyn PowGrinder<F, C::Hasher>>,
+        challenger: &mut Challenger<F, C::Hasher>,
+        timing: &mut TimingTree,
+    ) -> Result<StarkProof<F, C, D>>
+    where
+        A: StarkyAir<F, D> + for<'a> RAir<StarkParser<'a, F, F, Q, D, 1>>,
+        Q: PackedField<Scalar = F>,
+    {
         let rate_bits = config.fri_config.rate_bits;
         let cap_height = config.fri_config.cap_height;
-        let degree = 1 << trace_commitments[0].degree_log;
         let degree_bits = config.degree_bits;
         let fri_params = config.fri_params();
         assert!(
-            fri_params.total_arities() <= degree_bits + rate_bits - cap_height,
+            fri_params.total_arities() <= config.fri_degree_bits() + rate_bits - cap_height,
             "FRI total reduction arity is too large.",
         );
 
-        let challenge_vars = challenges
-            .iter()
-            .map(|x| P::<F>::from(*x))
-            .collect::<Vec<_>>();
-        let global_vars = global_values
-            .iter()
-            .map(|x| P::<F>::from(*x))
-            .collect::<Vec<_>>();
-        let public_vars = public_inputs
+        // The committed polynomials are zeroized when `deferred` is dropped, once the openings are
+        // proven.
+        let deferred = Self::open_at_zeta::<A, Q>(
+            config,
+            stark,
+            air_commitment,
+            extra_points,
+            challenger,
+            timing,
+        )?;
+        let quotient_polys_cap = deferred.quotient_commitment.merkle_tree.cap.clone();
+        let g = F::primitive_root_of_unity(degree_bits);
+
+        let initial_merkle_trees = deferred
+            .trace_commitments
             .iter()
-            .map(|x| P::<F>::from(*x))
+            .chain(once(&deferred.quotient_commitment))
             .collect::<Vec<_>>();
-        let quotient_polys = Self::quotient_polys(
+
+        let fri_instance =
+            stark.fri_instance(deferred.zeta, g, &deferred.openings.extra_points(), config);
+        let opening_proof = match grinder {
+            Some(grinder) => prove_openings_with_grinder(
+                &fri_instance,
+                &initial_merkle_trees,
+                challenger,
+                &fri_params,
+                grinder,
+                timing,
+            ),
+            None => PolynomialBatch::prove_openings(
+                &fri_instance,
+                &initial_merkle_trees,
+                challenger,
+                &fri_params,
+                timing,
+            ),
+        };
+
+        let partial_proof = deferred.partial_proof();
+        ensure!(
+            partial_proof.trace_caps.len() == stark.air().round_data().len(),
+            "Number of trace commitments does not match"
+        );
+        Ok(StarkProof {
+            air_proof: AirProof {
+                trace_caps: partial_proof.trace_caps,
+                quotient_polys_cap,
+                openings: partial_proof.openings,
+                opening_proof,
+            },
+            global_values: partial_proof.global_values,
             degree_bits,
+            cap_height,
+            public_inputs_commitment: config.public_inputs_commitment(&deferred.public_inputs),
+        })
+    }
+
+    pub fn prove<A, T>(
+        config: &StarkyConfig<C, D>,
+        stark: &Starky<A>,
+        trace_generator: &T,
+        public_inputs: &[F],
+    ) -> Result<StarkProof<F, C, D>>
+    where
+        A: ProverAir<F, C, D>,
+        T: TraceGenerator<F, A>,
+        T::Error: Into<anyhow::Error>,
+    {
+        let mut challenger = config.challenger();
+        let mut timing = TimingTree::default();
+        let air_commitment = Self::generate_trace(
             config,
             stark,
-            &trace_commitments,
-            &challenge_vars,
-            &global_vars,
-            &public_vars,
-            challenger,
-        );
-        let quotient_degree_factor = stark.air().quotient_degree_factor();
-        let all_quotient_chunks = quotient_polys
-            .into_par_iter()
-            .flat_map(|mut quotient_poly| {
-                quotient_poly
-                    .trim_to_len(degree * quotient_degree_factor)
-                    .expect(
-                        "Quotient has failed, the vanishing polynomial is not divisible by Z_H",
-                    );
-                // Split quotient into degree-n chunks.
-                quotient_poly.chunks(degree)
-            })
-            .collect();
+            public_inputs,
+            trace_generator,
+            &mut challenger,
+            &mut timing,
+        )?;
 
-        let quotient_commitment = PolynomialBatch::<F, C::GenericConfig, D>::from_coeffs(
-            all_quotient_chunks,
-            rate_bits,
-            false,
-            config.fri_config.cap_height,
-            timing,
+        Self::prove_with_trace(config, stark, air_commitment, &mut challenger, &mut timing)
+    }
+
+    /// Proves the statement, evaluating the constraints on packed values of type `Q` instead of
+    /// the packing of the configuration.
+    ///
+    /// The proof is the same as the one of `prove`, e.g. when evaluating on the scalar field `F`.
+    pub fn prove_with_packing<A, T, Q>(
+        config: &StarkyConfig<C, D>,
+        stark: &Starky<A>,
+        trace_generator: &T,
+        public_inputs: &[F],
+    ) -> Result<StarkProof<F, C, D>>
+    where
+        A: StarkyAir<F, D> + for<'a> RAir<StarkParser<'a, F, F, Q, D, 1>>,
+        Q: PackedField<Scalar = F>,
+        T: TraceGenerator<F, A>,
+        T::Error: Into<anyhow::Error>,
+    {
+        let mut challenger = config.challenger();
+        let mut timing = TimingTree::default();
+        let air_commitment = Self::generate_trace(
+            config,
+            stark,
+            public_inputs,
+            trace_generator,
+            &mut challenger,
+            &mut timing,
+        )?;
+
+        Self::prove_with_trace_and_grinder::<A, Q>(
+            config,
+            stark,
+            air_commitment,
+            &[],
             None,
+            &mut challenger,
+            &mut timing,
+        )
+    }
+
+    /// Commits to the quotient polynomials and opens all the polynomials at the challenge point
+    /// `zeta`, which is everything in a proof but the FRI argument.
+    fn open_at_zeta<A, Q>(
+        config: &StarkyConfig<C, D>,
+        stark: &Starky<A>,
+        mut air_commitment: AirCommitment<F, C, D>,
+        extra_points: &[F::Extension],
+        challenger: &mut Challenger<F, C::Hasher>,
+        timing: &mut TimingTree,
+    ) -> Result<DeferredStarkProof<F, C, D>>
+    where
+        A: StarkyAir<F, D> + for<'a> RAir<StarkParser<'a, F, F, Q, D, 1>>,
+        Q: PackedField<Scalar = F>,
+    {
+        let degree_bits = config.degree_bits;
+
+        // The FRI argument can not open the polynomials at points of the LDE domain, and the
+        // verifier rejects repeated extra points.
+        ensure!(
+            extra_points.iter().all_unique(),
+            "Repeated extra opening point."
+        );
+        let lde_bits = config.fri_params().lde_bits();
+        let shift_inv = F::Extension::from_basefield(F::coset_shift().inverse());
+        ensure!(
+            extra_points
+                .iter()
+                .all(|&point| (point * shift_inv).exp_power_of_2(lde_bits) != F::Extension::ONE),
+            "Extra opening point is in the LDE domain."
         );
 
-        let quotient_polys_cap = quotient_commitment.merkle_tree.cap.clone();
-        challenger.observe_cap(&quotient_polys_cap);
+        let alphas = challenger.get_n_challenges(config.num_challenges);
+        let quotient_commitment = Self::commit_quotient::<A, Q>(
+            config,
+            stark,
+            &air_commitment.trace_commitments,
+            &air_commitment.challenges,
+            &air_commitment.global_values,
+            &air_commitment.public_inputs,
+            &alphas,
+            timing,
+        );
+        challenger.observe_cap(&quotient_commitment.merkle_tree.cap);
 
         let zeta = challenger.get_extension_challenge::<D>();
         // To avoid leaking witness data, we want to ensure that our opening locations, `zeta` and
@@ -189,53 +486,481 @@ where
             zeta.exp_power_of_2(degree_bits) != F::Extension::ONE,
             "Opening point is in the subgroup."
         );
-        let openings = StarkOpeningSet::new(zeta, g, &trace_commitments, &quotient_commitment);
+        let openings = StarkOpeningSet::new_with_extra_points(
+            stark,
+            zeta,
+            g,
+            extra_points,
+            &air_commitment.trace_commitments,
+            &quotient_commitment,
+        );
+        challenger.observe_extension_elements(&openings.extra_points());
         challenger.observe_openings(&openings.to_fri_openings());
 
-        let initial_merkle_trees = trace_commitments
+        Ok(DeferredStarkProof {
+            trace_commitments: core::mem::take(&mut air_commitment.trace_commitments),
+            quotient_commitment,
+            zeta,
+            openings,
+            public_inputs: core::mem::take(&mut air_commitment.public_inputs),
+            global_values: core::mem::take(&mut air_commitment.global_values),
+        })
+    }
+
+    /// Proves the statement, searching the proof-of-work witness of the FRI argument with
+    /// `grinder`.
+    pub fn prove_with_grinder<A, T>(
+        config: &StarkyConfig<C, D>,
+        stark: &Starky<A>,
+        trace_generator: &T,
+        public_inputs: &[F],
+        grinder: &dyn PowGrinder<F, C::Hasher>,
+    ) -> Result<StarkProof<F, C, D>>
+    where
+        A: ProverAir<F, C, D>,
+        T: TraceGenerator<F, A>,
+        T::Error: Into<anyhow::Error>,
+    {
+        let mut challenger = config.challenger();
+        let mut timing = TimingTree::default();
+        let air_commitment = Self::generate_trace(
+            config,
+            stark,
+            public_inputs,
+            trace_generator,
+            &mut challenger,
+            &mut timing,
+        )?;
+
+        Self::prove_with_trace_and_grinder::<A, C::Packing>(
+            config,
+            stark,
+            air_commitment,
+            &[],
+            Some(grinder),
+            &mut challenger,
+            &mut timing,
+        )
+    }
+
+    /// Proves the statement, spilling the committed rounds to files in `spill_dir` while the later
+    /// rounds are generated.
+    ///
+    /// This fails if the trace generator holds secret registers.
+    pub fn prove_with_spill<A, T>(
+        config: &StarkyConfig<C, D>,
+        stark: &Starky<A>,
+        trace_generator: &T,
+        public_inputs: &[F],
+        spill_dir: &Path,
+    ) -> Result<StarkProof<F, C, D>>
+    where
+        A: ProverAir<F, C, D>,
+        T: TraceGenerator<F, A>,
+        T::Error: Into<anyhow::Error>,
+    {
+        let mut challenger = config.challenger();
+        let mut timing = TimingTree::default();
+        let air_commitment = Self::generate_trace_with_spill(
+            config,
+            stark,
+            public_inputs,
+            trace_generator,
+            Some(spill_dir),
+            &mut challenger,
+            &mut timing,
+        )?;
+
+        Self::prove_with_trace(config, stark, air_commitment, &mut challenger, &mut timing)
+    }
+
+    /// Proves the statement, opening the trace polynomials at `extra_points` in addition to the
+    /// challenge points.
+    pub fn prove_with_extra_points<A, T>(
+        config: &StarkyConfig<C, D>,
+        stark: &Starky<A>,
+        trace_generator: &T,
+        public_inputs: &[F],
+        extra_points: &[F::Extension],
+    ) -> Result<StarkProof<F, C, D>>
+    where
+        A: ProverAir<F, C, D>,
+        T: TraceGenerator<F, A>,
+        T::Error: Into<anyhow::Error>,
+    {
+        let mut challenger = config.challenger();
+        let mut timing = TimingTree::default();
+        let air_commitment = Self::generate_trace(
+            config,
+            stark,
+            public_inputs,
+            trace_generator,
+            &mut challenger,
+            &mut timing,
+        )?;
+
+        Self::prove_with_trace_and_extra_points(
+            config,
+            stark,
+            air_commitment,
+            extra_points,
+            &mut challenger,
+            &mut timing,
+        )
+    }
+
+    /// Commits to the trace of every round without proving it.
+    ///
+    /// The returned `TraceCommitment` can be published right away, and the statement proven later
+    /// with `prove_from_commitment` from the returned polynomials.
+    pub fn commit<A, T>(
+        config: &StarkyConfig<C, D>,
+        stark: &Starky<A>,
+        trace_generator: &T,
+        public_inputs: &[F],
+    ) -> Result<(TraceCommitment<F, C, D>, AirCommitment<F, C, D>)>
+    where
+        A: StarkyAir<F, D>,
+        T: TraceGenerator<F, A>,
+        T::Error: Into<anyhow::Error>,
+    {
+        let mut challenger = config.challenger();
+        let mut timing = TimingTree::default();
+        let air_commitment = Self::generate_trace(
+            config,
+            stark,
+            public_inputs,
+            trace_generator,
+            &mut challenger,
+            &mut timing,
+        )?;
+        let trace_caps = air_commitment
+            .trace_commitments
+            .iter()
+            .map(|c| c.merkle_tree.cap.clone())
+            .collect();
+        let commitment = TraceCommitment::new(
+            config,
+            trace_caps,
+            air_commitment.global_values.clone(),
+            public_inputs,
+        );
+        Ok((commitment, air_commitment))
+    }
+
+    /// Proves a trace committed to by `commit`, without committing to it again.
+    ///
+    /// The transcript of the trace rounds is replayed from the committed caps, so the proof opens
+    /// the caps of the published `TraceCommitment`.
+    pub fn prove_from_commitment<A: ProverAir<F, C, D>>(
+        config: &StarkyConfig<C, D>,
+        stark: &Starky<A>,
+        air_commitment: AirCommitment<F, C, D>,
+    ) -> Result<StarkProof<F, C, D>> {
+        let mut challenger = config.challenger();
+        let mut timing = TimingTree::default();
+
+        challenger.observe_elements(&air_commitment.public_inputs);
+        let mut challenges = vec![];
+        for (round, commitment) in stark
+            .air()
+            .round_data()
+            .iter()
+            .zip_eq(air_commitment.trace_commitments.iter())
+        {
+            let (id_0, id_1) = round.global_values_range;
+            challenger.observe_elements(&air_commitment.global_values[id_0..id_1]);
+            challenger.observe_cap(&commitment.merkle_tree.cap);
+            challenges.extend(challenger.get_n_challenges(round.num_challenges));
+        }
+        ensure!(
+            challenges == air_commitment.challenges,
+            "The challenges of the commitment do not match its transcript"
+        );
+
+        Self::prove_with_trace(config, stark, air_commitment, &mut challenger, &mut timing)
+    }
+
+    /// Proves a sequence of STARKs on a single Fiat-Shamir transcript.
+    ///
+    /// Each proof is a regular `StarkProof`, but its challenges are drawn from the transcript of
+    /// all the preceding proofs, which binds every proof to the commitments of the earlier ones.
+    /// The proofs must be verified together with `StarkyVerifier::verify_all`.
+    pub fn prove_all<A, T>(
+        configs: &[&StarkyConfig<C, D>],
+        starks: &[&Starky<A>],
+        trace_generators: &[&T],
+        public_inputs: &[&[F]],
+    ) -> Result<Vec<StarkProof<F, C, D>>>
+    where
+        A: ProverAir<F, C, D>,
+        T: TraceGenerator<F, A>,
+        T::Error: Into<anyhow::Error>,
+    {
+        ensure!(
+            configs.len() == starks.len()
+                && trace_generators.len() == starks.len()
+                && public_inputs.len() == starks.len(),
+            "Number of configs, STARKs, trace generators and public inputs do not match"
+        );
+
+        // The transcript is salted with the salt of the first configuration.
+        let mut challenger = configs
+            .first()
+            .map_or_else(Challenger::new, |config| config.challenger());
+        let mut timing = TimingTree::default();
+        let mut proofs = Vec::with_capacity(starks.len());
+        for (((config, stark), trace_generator), public_inputs) in configs
+            .iter()
+            .zip(starks)
+            .zip(trace_generators)
+            .zip(public_inputs)
+        {
+            let air_commitment = Self::generate_trace(
+                config,
+                stark,
+                public_inputs,
+                *trace_generator,
+                &mut challenger,
+                &mut timing,
+            )?;
+            proofs.push(Self::prove_with_trace(
+                config,
+                stark,
+                air_commitment,
+                &mut challenger,
+                &mut timing,
+            )?);
+        }
+        Ok(proofs)
+    }
+
+    /// Proves several STARKs which are bound to the same `shared_inputs`.
+    ///
+    /// Every proof is generated on its own transcript, which starts with the commitment to the
+    /// shared inputs.
+    pub fn prove_shared<A, T>(
+        configs: &[&StarkyConfig<C, D>],
+        starks: &[&Starky<A>],
+        trace_generators: &[&T],
+        public_inputs: &[&[F]],
+        shared_inputs: &[F],
+    ) -> Result<SharedInputsProof<F, C, D>>
+    where
+        A: ProverAir<F, C, D>,
+        T: TraceGenerator<F, A>,
+        T::Error: Into<anyhow::Error>,
+    {
+        ensure!(
+            configs.len() == starks.len()
+                && trace_generators.len() == starks.len()
+                && public_inputs.len() == starks.len(),
+            "Number of configs, STARKs, trace generators and public inputs do not match"
+        );
+
+        let shared_inputs_commitment = SharedInputsProof::<F, C, D>::commit(shared_inputs);
+        let mut timing = TimingTree::default();
+        let mut proofs = Vec::with_capacity(starks.len());
+        for (((config, stark), trace_generator), public_inputs) in configs
+            .iter()
+            .zip(starks)
+            .zip(trace_generators)
+            .zip(public_inputs)
+        {
+            let mut challenger = SharedInputsProof::challenger(config, shared_inputs_commitment);
+            let air_commitment = Self::generate_trace(
+                config,
+                stark,
+                public_inputs,
+                *trace_generator,
+                &mut challenger,
+                &mut timing,
+            )?;
+            proofs.push(Self::prove_with_trace(
+                config,
+                stark,
+                air_commitment,
+                &mut challenger,
+                &mut timing,
+            )?);
+        }
+        Ok(SharedInputsProof {
+            shared_inputs_commitment,
+            proofs,
+        })
+    }
+
+    /// Proves several STARKs with a single FRI argument.
+    ///
+    /// The traces of all the STARKs are committed in sequence on the same transcript, after which
+    /// the quotient polynomials are computed with shared constraint challenges and all the
+    /// polynomials are opened at a common point `zeta`. All the STARKs must have the same number
+    /// of rows, which is given by `config`.
+    pub fn prove_batch<A, T>(
+        config: &StarkyConfig<C, D>,
+        starks: &[&Starky<A>],
+        trace_generators: &[&T],
+        public_inputs: &[&[F]],
+    ) -> Result<BatchStarkProof<F, C, D>>
+    where
+        A: ProverAir<F, C, D>,
+        T: TraceGenerator<F, A>,
+        T::Error: Into<anyhow::Error>,
+    {
+        ensure!(!starks.is_empty(), "No STARKs to prove");
+        ensure!(
+            starks.len() == trace_generators.len() && starks.len() == public_inputs.len(),
+            "Number of STARKs, trace generators and public inputs do not match"
+        );
+
+        let mut challenger = config.challenger();
+        let mut timing = TimingTree::default();
+
+        let air_commitments = starks
+            .iter()
+            .zip_eq(trace_generators.iter())
+            .zip_eq(public_inputs.iter())
+            .map(|((stark, trace_generator), public_inputs)| {
+                Self::generate_trace(
+                    config,
+                    stark,
+                    public_inputs,
+                    *trace_generator,
+                    &mut challenger,
+                    &mut timing,
+                )
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        for (stark, air_commitment) in starks.iter().zip(air_commitments.iter()) {
+            ensure!(
+                air_commitment.trace_commitments.len() == stark.air().round_data().len(),
+                "Number of trace commitments does not match"
+            );
+            ensure!(
+                air_commitment
+                    .trace_commitments
+                    .iter()
+                    .all(|c| c.degree_log == config.fri_degree_bits()),
+                "All traces in a batch proof must have the length given by the config"
+            );
+        }
+
+        let rate_bits = config.fri_config.rate_bits;
+        let cap_height = config.fri_config.cap_height;
+        let degree_bits = config.degree_bits;
+        let fri_params = config.fri_params();
+        assert!(
+            fri_params.total_arities() <= config.fri_degree_bits() + rate_bits - cap_height,
+            "FRI total reduction arity is too large.",
+        );
+
+        let alphas = challenger.get_n_challenges(config.num_challenges);
+        let quotient_commitments = starks
+            .iter()
+            .zip(air_commitments.iter())
+            .map(|(stark, air_commitment)| {
+                Self::commit_quotient::<A, C::Packing>(
+                    config,
+                    stark,
+                    &air_commitment.trace_commitments,
+                    &air_commitment.challenges,
+                    &air_commitment.global_values,
+                    &air_commitment.public_inputs,
+                    &alphas,
+                    &mut timing,
+                )
+            })
+            .collect::<Vec<_>>();
+        for quotient_commitment in quotient_commitments.iter() {
+            challenger.observe_cap(&quotient_commitment.merkle_tree.cap);
+        }
+
+        let zeta = challenger.get_extension_challenge::<D>();
+        // As in the single proof case, the opening point must not be in the subgroup `H`.
+        let g = F::primitive_root_of_unity(degree_bits);
+        ensure!(
+            zeta.exp_power_of_2(degree_bits) != F::Extension::ONE,
+            "Opening point is in the subgroup."
+        );
+        let openings = air_commitments
+            .iter()
+            .zip(starks.iter())
+            .zip(quotient_commitments.iter())
+            .map(|((air_commitment, stark), quotient_commitment)| {
+                StarkOpeningSet::new(
+                    stark,
+                    zeta,
+                    g,
+                    &air_commitment.trace_commitments,
+                    quotient_commitment,
+                )
+            })
+            .collect::<Vec<_>>();
+        challenger.observe_openings(&BatchStarkProof::<F, C, D>::fri_openings(&openings));
+
+        let initial_merkle_trees = air_commitments
             .iter()
-            .chain(once(&quotient_commitment))
+            .zip(quotient_commitments.iter())
+            .flat_map(|(air_commitment, quotient_commitment)| {
+                air_commitment
+                    .trace_commitments
+                    .iter()
+                    .chain(once(quotient_commitment))
+            })
             .collect::<Vec<_>>();
 
         let opening_proof = PolynomialBatch::prove_openings(
-            &stark.fri_instance(zeta, g, config),
+            &Starky::batch_fri_instance(starks, zeta, g, config),
             &initial_merkle_trees,
-            challenger,
+            &mut challenger,
             &fri_params,
-            timing,
+            &mut timing,
         );
 
-        let trace_caps = trace_commitments
+        let quotient_polys_caps = quotient_commitments
             .into_iter()
-            .map(|c| c.merkle_tree.cap)
-            .collect::<Vec<_>>();
-        ensure!(
-            trace_caps.len() == stark.air().round_data().len(),
-            "Number of trace commitments does not match"
-        );
-        Ok(StarkProof {
-            air_proof: AirProof {
-                trace_caps,
-                quotient_polys_cap,
-                openings,
-                opening_proof,
-            },
+            .map(|mut c| {
+                zeroize_batch(&mut c);
+                c.merkle_tree.cap
+            })
+            .collect();
+        // The trace polynomials are zeroized when the commitments are dropped.
+        let (trace_caps, global_values) = air_commitments
+            .iter()
+            .map(|air_commitment| {
+                let caps = air_commitment
+                    .trace_commitments
+                    .iter()
+                    .map(|c| c.merkle_tree.cap.clone())
+                    .collect::<Vec<_>>();
+                (caps, air_commitment.global_values.clone())
+            })
+            .unzip();
+
+        Ok(BatchStarkProof {
+            trace_caps,
+            quotient_polys_caps,
+            openings,
+            opening_proof,
             global_values,
         })
     }
 
-    pub fn prove<A, T>(
+    /// Proves the statement up to the openings at the challenge point, deferring the FRI argument
+    /// to `aggregate`.
+    pub fn prove_deferred<A, T>(
         config: &StarkyConfig<C, D>,
         stark: &Starky<A>,
         trace_generator: &T,
         public_inputs: &[F],
-    ) -> Result<StarkProof<F, C, D>>
+    ) -> Result<DeferredStarkProof<F, C, D>>
     where
-        A: StarkyAir<F, D>,
+        A: ProverAir<F, C, D>,
         T: TraceGenerator<F, A>,
         T::Error: Into<anyhow::Error>,
     {
-        let mut challenger = Challenger::<F, C::Hasher>::new();
+        let mut challenger = config.challenger();
         let mut timing = TimingTree::default();
         let air_commitment = Self::generate_trace(
             config,
@@ -246,114 +971,280 @@ where
             &mut timing,
         )?;
 
-        Self::prove_with_trace(config, stark, air_commitment, &mut challenger, &mut timing)
+        Self::open_at_zeta::<A, C::Packing>(
+            config,
+            stark,
+            air_commitment,
+            &[],
+            &mut challenger,
+            &mut timing,
+        )
+    }
+
+    /// Proves the openings of the deferred proofs of `starks` with a single FRI argument.
+    ///
+    /// Each proof keeps the challenge point of its own transcript, and the FRI argument opens the
+    /// polynomials of every proof at its own points. All the proofs must have been generated with
+    /// `config`.
+    pub fn aggregate<A>(
+        config: &StarkyConfig<C, D>,
+        starks: &[&Starky<A>],
+        deferred_proofs: &[DeferredStarkProof<F, C, D>],
+    ) -> Result<AggregatedStarkProof<F, C, D>>
+    where
+        A: StarkyAir<F, D>,
+    {
+        ensure!(!starks.is_empty(), "No proofs to aggregate");
+        ensure!(
+            starks.len() == deferred_proofs.len(),
+            "Number of STARKs and deferred proofs do not match"
+        );
+        ensure!(
+            deferred_proofs
+                .iter()
+                .flat_map(|proof| proof.trace_commitments.iter())
+                .all(|c| c.degree_log == config.fri_degree_bits()),
+            "All aggregated traces must have the length given by the config"
+        );
+
+        let fri_params = config.fri_params();
+        let proofs = deferred_proofs
+            .iter()
+            .map(DeferredStarkProof::partial_proof)
+            .collect::<Vec<_>>();
+        let public_inputs = deferred_proofs
+            .iter()
+            .map(|proof| proof.public_inputs.as_slice())
+            .collect::<Vec<_>>();
+        let zetas = deferred_proofs
+            .iter()
+            .map(|proof| proof.zeta)
+            .collect::<Vec<_>>();
+
+        let mut challenger =
+            AggregatedStarkProof::<F, C, D>::challenger(config, &proofs, &public_inputs, &zetas);
+        let initial_merkle_trees = deferred_proofs
+            .iter()
+            .flat_map(|proof| {
+                proof
+                    .trace_commitments
+                    .iter()
+                    .chain(once(&proof.quotient_commitment))
+            })
+            .collect::<Vec<_>>();
+        let opening_proof = PolynomialBatch::prove_openings(
+            &AggregatedStarkProof::<F, C, D>::fri_instance(config, starks, &zetas),
+            &initial_merkle_trees,
+            &mut challenger,
+            &fri_params,
+            &mut TimingTree::default(),
+        );
+
+        Ok(AggregatedStarkProof {
+            proofs,
+            opening_proof,
+        })
+    }
+
+    /// Computes the quotient polynomials of `stark` for the constraint challenges `alphas`,
+    /// splits them into chunks of the committed degree and commits to them.
+    #[allow(clippy::too_many_arguments)]
+    fn commit_quotient<A, Q>(
+        config: &StarkyConfig<C, D>,
+        stark: &Starky<A>,
+        trace_commitments: &[PolynomialBatch<F, C::GenericConfig, D>],
+        challenges: &[F],
+        global_values: &[F],
+        public_inputs: &[F],
+        alphas: &[F],
+        timing: &mut TimingTree,
+    ) -> PolynomialBatch<F, C::GenericConfig, D>
+    where
+        A: StarkyAir<F, D> + for<'a> RAir<StarkParser<'a, F, F, Q, D, 1>>,
+        Q: PackedField<Scalar = F>,
+    {
+        let degree = 1 << trace_commitments[0].degree_log;
+
+        let challenge_vars = challenges.iter().map(|x| Q::from(*x)).collect::<Vec<_>>();
+        let global_vars = global_values
+            .iter()
+            .map(|x| Q::from(*x))
+            .collect::<Vec<_>>();
+        let public_vars = public_inputs
+            .iter()
+            .map(|x| Q::from(*x))
+            .collect::<Vec<_>>();
+        let quotient_polys = Self::quotient_polys::<A, Q>(
+            config.degree_bits,
+            config,
+            stark,
+            trace_commitments,
+            &challenge_vars,
+            &global_vars,
+            &public_vars,
+            alphas,
+        );
+        let num_quotient_chunks = stark.num_quotient_chunks(config);
+        let all_quotient_chunks = quotient_polys
+            .into_par_iter()
+            .flat_map(|mut quotient_poly| {
+                quotient_poly
+                    .trim_to_len(degree * num_quotient_chunks)
+                    .expect(
+                        "Quotient has failed, the vanishing polynomial is not divisible by Z_H",
+                    );
+                // Split quotient into chunks of the degree bound of the committed trace.
+                quotient_poly.chunks(degree)
+            })
+            .collect();
+
+        PolynomialBatch::<F, C::GenericConfig, D>::from_coeffs(
+            all_quotient_chunks,
+            config.fri_config.rate_bits,
+            config.zero_knowledge,
+            config.fri_config.cap_height,
+            timing,
+            Some(&ntt::root_table(
+                trace_commitments[0].degree_log + config.fri_config.rate_bits,
+            )),
+        )
     }
 
     #[allow(clippy::too_many_arguments)]
-    fn quotient_polys<A>(
+    fn quotient_polys<A, Q>(
         degree_bits: usize,
         config: &StarkyConfig<C, D>,
         stark: &Starky<A>,
         trace_data: &[PolynomialBatch<F, C::GenericConfig, D>],
-        challenges_vars: &[P<F>],
-        global_vars: &[P<F>],
-        public_vars: &[P<F>],
-        challenger: &mut Challenger<F, C::Hasher>,
+        challenges_vars: &[Q],
+        global_vars: &[Q],
+        public_vars: &[Q],
+        alphas: &[F],
     ) -> Vec<PolynomialCoeffs<F>>
     where
-        A: StarkyAir<F, D>,
+        A: StarkyAir<F, D> + for<'a> RAir<StarkParser<'a, F, F, Q, D, 1>>,
+        Q: PackedField<Scalar = F>,
     {
-        let alphas = challenger.get_n_challenges(config.num_challenges);
         let degree = 1 << degree_bits;
         let rate_bits = config.fri_config.rate_bits;
 
-        let quotient_degree_bits = log2_ceil(stark.air().quotient_degree_factor());
+        let quotient_degree_bits = log2_ceil(stark.num_quotient_chunks(config));
         assert!(
             quotient_degree_bits <= rate_bits,
             "Having constraints of degree higher than the rate is not supported yet."
         );
         let step = 1 << (rate_bits - quotient_degree_bits);
+        // If the trace is blinded, the committed polynomials have degree bound `2n` and the LDE
+        // domain has an extra bit compared to the trace domain.
+        let extra_bits = config.fri_degree_bits() - degree_bits;
+        let lde_bits = quotient_degree_bits + extra_bits;
         // When opening the `Z`s polys at the "next" point, need to look at the point `next_step` steps away.
-        let next_step = 1 << quotient_degree_bits;
+        let next_step = 1 << lde_bits;
 
         // Evaluation of the first Lagrange polynomial on the LDE domain.
-        let lagrange_first =
-            PolynomialValues::selector(degree, 0).lde_onto_coset(quotient_degree_bits);
+        let lagrange_first = ntt::coset_lde(
+            &ntt::intt(PolynomialValues::selector(degree, 0)),
+            lde_bits,
+            F::coset_shift(),
+        );
         // Evaluation of the last Lagrange polynomial on the LDE domain.
-        let lagrange_last =
-            PolynomialValues::selector(degree, degree - 1).lde_onto_coset(quotient_degree_bits);
+        let lagrange_last = ntt::coset_lde(
+            &ntt::intt(PolynomialValues::selector(degree, degree - 1)),
+            lde_bits,
+            F::coset_shift(),
+        );
 
-        let z_h_on_coset = ZeroPolyOnCoset::<F>::new(degree_bits, quotient_degree_bits);
+        let z_h_on_coset = ZeroPolyOnCoset::<F>::new(degree_bits, lde_bits);
+        let size = degree << lde_bits;
 
-        // Retrieve the LDE values at index `i`.
-        let get_trace_values_packed = |i_start| -> Vec<P<F>> {
+        // The LDE values of a block of points of the quotient domain, transposed into packed
+        // columns so that the values of a batch of points are read with aligned loads. Only the
+        // blocks being evaluated are transposed, so the LDE is never copied as a whole.
+        let block_rows = QUOTIENT_BLOCK_ROWS.min(size);
+        let lde_block = |start: usize| -> Vec<ColumnTraceView<Q>> {
             trace_data
                 .iter()
-                .flat_map(|commitment| commitment.get_lde_values_packed(i_start, step))
+                .map(|commitment| {
+                    ColumnTraceView::<Q>::from_rows(block_rows, |r| {
+                        commitment.get_lde_values((start + r) % size, step)
+                    })
+                })
+                .collect()
+        };
+        // Retrieve the LDE values at row `r` of a block.
+        let get_trace_values_packed = |block: &[ColumnTraceView<Q>], r: usize| -> Vec<Q> {
+            block
+                .iter()
+                .flat_map(|columns| columns.packed_row(r))
                 .collect()
         };
         // Last element of the subgroup.
         let last = F::primitive_root_of_unity(degree_bits).inverse();
-        let size = degree << quotient_degree_bits;
         let coset = F::cyclic_subgroup_coset_known_order(
-            F::primitive_root_of_unity(degree_bits + quotient_degree_bits),
+            F::primitive_root_of_unity(degree_bits + lde_bits),
             F::coset_shift(),
             size,
         );
 
-        // We will step by `P::WIDTH`, and in each iteration, evaluate the quotient polynomial at
-        // a batch of `P::WIDTH` points.
+        // The blocks are evaluated in parallel. Within a block, we step by `P::WIDTH`, and in each
+        // iteration, evaluate the quotient polynomial at a batch of `P::WIDTH` points.
         let quotient_values = (0..size)
             .into_par_iter()
-            .step_by(P::<F>::WIDTH)
-            .flat_map_iter(|i_start| {
-                let i_next_start = (i_start + next_step) % size;
-                let i_range = i_start..i_start + P::<F>::WIDTH;
-
-                let x = *P::<F>::from_slice(&coset[i_range.clone()]);
-                let z_last = x - last;
-                let lagrange_basis_first =
-                    *P::<F>::from_slice(&lagrange_first.values[i_range.clone()]);
-                let lagrange_basis_last = *P::<F>::from_slice(&lagrange_last.values[i_range]);
-
-                let mut consumer = ConstraintConsumer::new(
-                    alphas.clone(),
-                    z_last,
-                    lagrange_basis_first,
-                    lagrange_basis_last,
-                );
-                let mut parser = StarkParser {
-                    local_vars: &get_trace_values_packed(i_start),
-                    next_vars: &get_trace_values_packed(i_next_start),
-                    global_vars,
-                    public_vars,
-                    challenges: challenges_vars,
-                    consumer: &mut consumer,
-                };
-
-                stark.air().eval(&mut parser);
-
-                let mut constraints_evals = consumer.accumulators();
-                // We divide the constraints evaluations by `Z_H(x)`.
-                let denominator_inv: P<F> = z_h_on_coset.eval_inverse_packed(i_start);
-
-                for eval in &mut constraints_evals {
-                    *eval *= denominator_inv;
-                }
+            .step_by(block_rows)
+            .flat_map_iter(|block_start| {
+                let local_block = lde_block(block_start);
+                let next_block = lde_block((block_start + next_step) % size);
+                let mut block_values = Vec::with_capacity(block_rows);
+                for i_start in (block_start..block_start + block_rows).step_by(Q::WIDTH) {
+                    let r = i_start - block_start;
+                    let i_range = i_start..i_start + Q::WIDTH;
 
-                let num_challenges = alphas.len();
+                    let x = *Q::from_slice(&coset[i_range.clone()]);
+                    let z_last = x - last;
+                    let lagrange_basis_first =
+                        *Q::from_slice(&lagrange_first.values[i_range.clone()]);
+                    let lagrange_basis_last = *Q::from_slice(&lagrange_last.values[i_range]);
 
-                (0..P::<F>::WIDTH).map(move |i| {
-                    (0..num_challenges)
-                        .map(|j| constraints_evals[j].as_slice()[i])
-                        .collect()
-                })
+                    let mut consumer = ConstraintConsumer::new(
+                        alphas.to_vec(),
+                        z_last,
+                        lagrange_basis_first,
+                        lagrange_basis_last,
+                    );
+                    let mut parser = StarkParser {
+                        local_vars: &get_trace_values_packed(&local_block, r),
+                        next_vars: &get_trace_values_packed(&next_block, r),
+                        global_vars,
+                        public_vars,
+                        challenges: challenges_vars,
+                        consumer: &mut consumer,
+                    };
+
+                    stark.air().eval(&mut parser);
+
+                    let mut constraints_evals = consumer.accumulators();
+                    // We divide the constraints evaluations by `Z_H(x)`.
+                    let denominator_inv: Q = z_h_on_coset.eval_inverse_packed(i_start);
+
+                    for eval in &mut constraints_evals {
+                        *eval *= denominator_inv;
+                    }
+
+                    let num_challenges = alphas.len();
+
+                    block_values.extend((0..Q::WIDTH).map(|i| {
+                        (0..num_challenges)
+                            .map(|j| constraints_evals[j].as_slice()[i])
+                            .collect::<Vec<_>>()
+                    }));
+                }
+                block_values
             })
             .collect::<Vec<_>>();
         transpose(&quotient_values)
             .into_par_iter()
             .map(PolynomialValues::new)
-            .map(|values| values.coset_ifft(F::coset_shift()))
+            .map(|values| ntt::coset_intt(values, F::coset_shift()))
             .collect()
     }
 }
@@ -0,0 +1,51 @@
+//! Proofs of a single statement split across several STARKs.
+//!
+//! The statement is bound by a commitment to its shared inputs, which is observed at the start of
+//! the transcript of every proof. A proof generated for some shared inputs therefore has different
+//! challenges, and fails to verify, for any other shared inputs, so the proofs of different AIRs
+//! can not be mixed between statements.
+
+use plonky2::field::extension::Extendable;
+use plonky2::hash::hash_types::{HashOut, RichField};
+use plonky2::iop::challenger::Challenger;
+use plonky2::plonk::config::Hasher;
+use serde::{Deserialize, Serialize};
+
+use super::config::{CurtaConfig, StarkyConfig};
+use super::proof::StarkProof;
+
+/// Proofs of several STARKs bound to the same shared inputs.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(bound = "")]
+pub struct SharedInputsProof<F: RichField + Extendable<D>, C: CurtaConfig<D, F = F>, const D: usize>
+{
+    /// The commitment to the shared inputs observed by every proof.
+    pub shared_inputs_commitment: HashOut<F>,
+    /// A proof for each of the STARKs, each on its own transcript.
+    pub proofs: Vec<StarkProof<F, C, D>>,
+}
+
+impl<F: RichField + Extendable<D>, C: CurtaConfig<D, F = F>, const D: usize>
+    SharedInputsProof<F, C, D>
+{
+    /// The commitment to `shared_inputs`.
+    pub fn commit(shared_inputs: &[F]) -> HashOut<F> {
+        C::InnerHasher::hash_no_pad(shared_inputs)
+    }
+
+    /// A new challenger for one of the proofs, which has observed the transcript salt of `config`
+    /// and the commitment to the shared inputs.
+    pub fn challenger(
+        config: &StarkyConfig<C, D>,
+        shared_inputs_commitment: HashOut<F>,
+    ) -> Challenger<F, C::Hasher> {
+        let mut challenger = config.challenger();
+        challenger.observe_hash::<C::InnerHasher>(shared_inputs_commitment);
+        challenger
+    }
+
+    /// The number of proofs.
+    pub fn num_proofs(&self) -> usize {
+        self.proofs.len()
+    }
+}
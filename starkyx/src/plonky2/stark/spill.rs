@@ -0,0 +1,216 @@
+//! Spilling of committed trace rounds to disk.
+//!
+//! Once the Merkle cap of a round has been observed, its polynomial batch is only needed again to
+//! compute the quotient polynomials and the openings. For AIRs with many large rounds, writing the
+//! committed batches to disk while the next rounds are generated keeps a single committed round in
+//! memory during the trace generation.
+//!
+//! Every batch is read back into memory before the quotient polynomials are computed, so spilling
+//! does not reduce the peak memory of the quotient and opening phases, which hold all the rounds.
+//!
+//! The spilled batches contain the coefficients and the LDE of every column, so the prover refuses
+//! to spill the trace of a generator holding secret registers.
+//!
+//! The rounds are appended to a single file, and streamed back in order once the last round is
+//! committed. Each vector of a batch is written as its length followed by its elements, so that it
+//! is read back into an allocation of the exact size, one element at a time.
+
+use std::fs::{self, File};
+use std::io::{BufReader, BufWriter, Read, Write};
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result};
+use plonky2::field::extension::Extendable;
+use plonky2::field::polynomial::PolynomialCoeffs;
+use plonky2::fri::oracle::PolynomialBatch;
+use plonky2::hash::hash_types::RichField;
+use plonky2::hash::merkle_tree::{MerkleCap, MerkleTree};
+use plonky2::plonk::config::GenericConfig;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+
+/// A file to which the polynomial batches of the committed rounds are appended.
+///
+/// The file is removed if it is dropped before being read back, and otherwise by the
+/// `SpilledRounds` reading it.
+#[derive(Debug)]
+pub struct SpillFile<F, C, const D: usize> {
+    path: Option<PathBuf>,
+    writer: BufWriter<File>,
+    num_rounds: usize,
+    _marker: core::marker::PhantomData<(F, C)>,
+}
+
+impl<F, C, const D: usize> SpillFile<F, C, D>
+where
+    F: RichField + Extendable<D>,
+    C: GenericConfig<D, F = F>,
+{
+    /// Creates a new spill file in `dir`.
+    pub fn create(dir: &Path) -> Result<Self> {
+        let path = dir.join(format!("starkyx-rounds-{:016x}.bin", rand::random::<u64>()));
+        let file = File::create(&path)
+            .with_context(|| format!("Failed to create spill file {}", path.display()))?;
+        Ok(Self {
+            path: Some(path),
+            writer: BufWriter::new(file),
+            num_rounds: 0,
+            _marker: core::marker::PhantomData,
+        })
+    }
+
+    /// Appends `batch` to the file and frees its memory.
+    pub fn spill(&mut self, batch: PolynomialBatch<F, C, D>) -> Result<()> {
+        let path = self.path.clone().expect("Spill file was already read");
+        self.write_batch(batch)
+            .with_context(|| format!("Failed to write spill file {}", path.display()))?;
+        self.num_rounds += 1;
+        Ok(())
+    }
+
+    /// Returns an iterator streaming the spilled rounds back in the order they were written.
+    pub fn read_rounds(mut self) -> Result<SpilledRounds<F, C, D>> {
+        let path = self.path.take().expect("Spill file was already read");
+        self.writer
+            .flush()
+            .with_context(|| format!("Failed to write spill file {}", path.display()))?;
+        let file = File::open(&path)
+            .with_context(|| format!("Failed to open spill file {}", path.display()))?;
+        Ok(SpilledRounds {
+            path: Some(path),
+            reader: BufReader::new(file),
+            remaining: self.num_rounds,
+            _marker: core::marker::PhantomData,
+        })
+    }
+
+    fn write_batch(&mut self, batch: PolynomialBatch<F, C, D>) -> Result<()> {
+        write_vec(&mut self.writer, &batch.polynomials)?;
+        drop(batch.polynomials);
+        write_vec(&mut self.writer, &batch.merkle_tree.leaves)?;
+        drop(batch.merkle_tree.leaves);
+        write_vec(&mut self.writer, &batch.merkle_tree.digests)?;
+        bincode::serialize_into(
+            &mut self.writer,
+            &(
+                &batch.merkle_tree.cap,
+                batch.degree_log,
+                batch.rate_bits,
+                batch.blinding,
+            ),
+        )?;
+        self.writer.flush()?;
+        Ok(())
+    }
+}
+
+impl<F, C, const D: usize> Drop for SpillFile<F, C, D> {
+    fn drop(&mut self) {
+        if let Some(path) = self.path.take() {
+            let _ = fs::remove_file(path);
+        }
+    }
+}
+
+/// The rounds of a spill file, read back one at a time.
+///
+/// The file is removed once all the rounds are read, or when the iterator is dropped.
+#[derive(Debug)]
+pub struct SpilledRounds<F, C, const D: usize> {
+    path: Option<PathBuf>,
+    reader: BufReader<File>,
+    remaining: usize,
+    _marker: core::marker::PhantomData<(F, C)>,
+}
+
+impl<F, C, const D: usize> SpilledRounds<F, C, D>
+where
+    F: RichField + Extendable<D>,
+    C: GenericConfig<D, F = F>,
+{
+    fn read_batch(&mut self) -> Result<PolynomialBatch<F, C, D>> {
+        let polynomials: Vec<PolynomialCoeffs<F>> = read_vec(&mut self.reader)?;
+        let leaves = read_vec(&mut self.reader)?;
+        let digests = read_vec(&mut self.reader)?;
+        let (cap, degree_log, rate_bits, blinding): (MerkleCap<F, C::Hasher>, usize, usize, bool) =
+            bincode::deserialize_from(&mut self.reader)?;
+        Ok(PolynomialBatch {
+            polynomials,
+            merkle_tree: MerkleTree {
+                leaves,
+                digests,
+                cap,
+            },
+            degree_log,
+            rate_bits,
+            blinding,
+        })
+    }
+
+    fn remove_file(&mut self) -> Result<()> {
+        if let Some(path) = self.path.take() {
+            fs::remove_file(&path)
+                .with_context(|| format!("Failed to remove spill file {}", path.display()))?;
+        }
+        Ok(())
+    }
+}
+
+impl<F, C, const D: usize> Iterator for SpilledRounds<F, C, D>
+where
+    F: RichField + Extendable<D>,
+    C: GenericConfig<D, F = F>,
+{
+    type Item = Result<PolynomialBatch<F, C, D>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.remaining == 0 {
+            return None;
+        }
+        self.remaining -= 1;
+        let path = self.path.clone()?;
+        let batch = self
+            .read_batch()
+            .with_context(|| format!("Failed to read spill file {}", path.display()));
+        // The file is removed after the last round, or after the first failure.
+        if batch.is_err() || self.remaining == 0 {
+            self.remaining = 0;
+            if let Err(e) = self.remove_file() {
+                return Some(batch.and(Err(e)));
+            }
+        }
+        Some(batch)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.remaining, Some(self.remaining))
+    }
+}
+
+impl<F, C, const D: usize> Drop for SpilledRounds<F, C, D> {
+    fn drop(&mut self) {
+        if let Some(path) = self.path.take() {
+            let _ = fs::remove_file(path);
+        }
+    }
+}
+
+/// Writes the length of `values` followed by its elements.
+fn write_vec<T: Serialize, W: Write>(writer: &mut W, values: &[T]) -> Result<()> {
+    bincode::serialize_into(&mut *writer, &(values.len() as u64))?;
+    for value in values {
+        bincode::serialize_into(&mut *writer, value)?;
+    }
+    Ok(())
+}
+
+/// Reads a vector written by `write_vec` into an allocation of its exact length.
+fn read_vec<T: DeserializeOwned, R: Read>(reader: &mut R) -> Result<Vec<T>> {
+    let len: u64 = bincode::deserialize_from(&mut *reader)?;
+    let len = usize::try_from(len)?;
+    let mut values = Vec::with_capacity(len);
+    for _ in 0..len {
+        values.push(bincode::deserialize_from(&mut *reader)?);
+    }
+    Ok(values)
+}
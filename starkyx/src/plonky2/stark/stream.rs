@@ -0,0 +1,439 @@
+//! Streaming serialization of STARK proofs.
+//!
+//! A serialized proof consists of a header followed by the proof body:
+//!
+//! - the magic bytes `PROOF_MAGIC`,
+//! - the format version as a little-endian `u16`,
+//! - the degree bits of the trace as a little-endian `u32`,
+//! - the length of the configuration digest as a `u8`, followed by the digest,
+//! - the length of the body as a little-endian `u64`, followed by the bincode encoding of the
+//! proof.
+//!
+//! The header can be read and checked against the expected configuration before reading the
+//! body, so that proofs generated for a different configuration are rejected early. The body is
+//! encoded and decoded directly from the stream, without buffering it in memory. Decoding reads at
+//! most the declared length of the body, which is at most `MAX_PROOF_BODY_LENGTH`, so that a
+//! crafted stream can not cause large allocations.
+//!
+//! The bincode encoding of the body uses fixed-width little-endian integers, with lengths and
+//! `usize` fields encoded as `u64`, so it does not depend on the endianness or the pointer width
+//! of the platform. Field elements may be represented by non-canonical integers, such as `p` for
+//! zero, so the body encodes `StarkProof::canonical`, in which every field element is its
+//! canonical representative. `StarkProof::digest` hashes this encoding, so that equal proofs have
+//! the same digest on every platform.
+
+use core::iter::once;
+use std::io::{Read, Write};
+
+use anyhow::{anyhow, ensure, Result};
+use bincode::Options;
+use plonky2::field::extension::{Extendable, FieldExtension};
+use plonky2::hash::hash_types::RichField;
+use plonky2::hash::merkle_proofs::MerkleProof;
+use plonky2::hash::merkle_tree::MerkleCap;
+use plonky2::plonk::config::{GenericHashOut, Hasher};
+
+use super::config::{CurtaConfig, StarkyConfig};
+use super::proof::StarkProof;
+
+/// The magic bytes at the start of a serialized proof.
+pub const PROOF_MAGIC: [u8; 4] = *b"SXPF";
+
+/// The version of the proof serialization format.
+pub const PROOF_FORMAT_VERSION: u16 = 4;
+
+/// The maximal length in bytes of the body of a serialized proof.
+pub const MAX_PROOF_BODY_LENGTH: u64 = 1 << 30;
+
+/// The header of a serialized proof.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ProofHeader {
+    /// The version of the serialization format.
+    pub version: u16,
+    /// The degree bits of the trace.
+    pub degree_bits: u32,
+    /// The digest of the configuration used to generate the proof.
+    pub config_digest: Vec<u8>,
+    /// The length in bytes of the proof body.
+    pub body_length: u64,
+}
+
+impl ProofHeader {
+    /// Writes the header to `writer`.
+    pub fn write_to<W: Write>(&self, writer: &mut W) -> Result<()> {
+        let digest_length = u8::try_from(self.config_digest.len())
+            .map_err(|_| anyhow!("Configuration digest is too long"))?;
+        writer.write_all(&PROOF_MAGIC)?;
+        writer.write_all(&self.version.to_le_bytes())?;
+        writer.write_all(&self.degree_bits.to_le_bytes())?;
+        writer.write_all(&[digest_length])?;
+        writer.write_all(&self.config_digest)?;
+        writer.write_all(&self.body_length.to_le_bytes())?;
+        Ok(())
+    }
+
+    /// Reads a header from `reader`, checking the magic bytes and the format version.
+    pub fn read_from<R: Read>(reader: &mut R) -> Result<Self> {
+        let mut magic = [0u8; 4];
+        reader.read_exact(&mut magic)?;
+        ensure!(magic == PROOF_MAGIC, "Invalid proof magic bytes");
+
+        let mut version = [0u8; 2];
+        reader.read_exact(&mut version)?;
+        let version = u16::from_le_bytes(version);
+        ensure!(
+            version == PROOF_FORMAT_VERSION,
+            "Unsupported proof format version {}, expected {}",
+            version,
+            PROOF_FORMAT_VERSION
+        );
+
+        let mut degree_bits = [0u8; 4];
+        reader.read_exact(&mut degree_bits)?;
+
+        let mut digest_length = [0u8; 1];
+        reader.read_exact(&mut digest_length)?;
+        let mut config_digest = vec![0u8; digest_length[0] as usize];
+        reader.read_exact(&mut config_digest)?;
+
+        let mut body_length = [0u8; 8];
+        reader.read_exact(&mut body_length)?;
+
+        Ok(Self {
+            version,
+            degree_bits: u32::from_le_bytes(degree_bits),
+            config_digest,
+            body_length: u64::from_le_bytes(body_length),
+        })
+    }
+
+    /// Checks that the header matches the configuration `config`.
+    pub fn check<C: CurtaConfig<D>, const D: usize>(
+        &self,
+        config: &StarkyConfig<C, D>,
+    ) -> Result<()> {
+        ensure!(
+            self.degree_bits as usize == config.degree_bits,
+            "Proof has degree bits {}, expected {}",
+            self.degree_bits,
+            config.degree_bits
+        );
+        ensure!(
+            self.config_digest == config.digest(),
+            "Proof was generated with a different configuration"
+        );
+        Ok(())
+    }
+}
+
+impl<F: RichField + Extendable<D>, C: CurtaConfig<D, F = F>, const D: usize> StarkProof<F, C, D> {
+    /// Writes the proof generated with `config` to `writer`.
+    pub fn write_to<W: Write>(&self, config: &StarkyConfig<C, D>, mut writer: W) -> Result<()> {
+        let proof = self.canonical();
+        let header = ProofHeader {
+            version: PROOF_FORMAT_VERSION,
+            degree_bits: u32::try_from(config.degree_bits)?,
+            config_digest: config.digest(),
+            body_length: body_options().serialized_size(&proof)?,
+        };
+        header.write_to(&mut writer)?;
+        body_options().serialize_into(&mut writer, &proof)?;
+        writer.flush()?;
+        Ok(())
+    }
+
+    /// Reads a proof written by `write_to` from `reader`.
+    ///
+    /// The header is checked against `config` before reading the proof body.
+    pub fn read_from<R: Read>(config: &StarkyConfig<C, D>, mut reader: R) -> Result<Self> {
+        let header = ProofHeader::read_from(&mut reader)?;
+        header.check(config)?;
+        ensure!(
+            header.body_length <= MAX_PROOF_BODY_LENGTH,
+            "Proof body of {} bytes exceeds the maximal length {}",
+            header.body_length,
+            MAX_PROOF_BODY_LENGTH
+        );
+
+        let mut body = reader.take(header.body_length);
+        let proof = body_options()
+            .with_limit(header.body_length)
+            .deserialize_from(&mut body)?;
+        ensure!(
+            body.limit() == 0,
+            "Proof body is shorter than its declared length"
+        );
+        Ok(proof)
+    }
+
+    /// The proof with every field element and hash in its canonical representation.
+    pub fn canonical(&self) -> Self {
+        let mut proof = self.clone();
+        let air_proof = &mut proof.air_proof;
+        for cap in air_proof
+            .trace_caps
+            .iter_mut()
+            .chain(once(&mut air_proof.quotient_polys_cap))
+        {
+            canonical_cap::<F, C::Hasher>(cap);
+        }
+
+        let openings = &mut air_proof.openings;
+        canonical_extension_slice::<F, D>(&mut openings.local_values);
+        canonical_extension_slice::<F, D>(&mut openings.next_values);
+        for chunks in openings.quotient_chunks.iter_mut() {
+            canonical_extension_slice::<F, D>(chunks);
+        }
+        for opening in openings.extra_openings.iter_mut() {
+            opening.point = canonical_extension::<F, D>(opening.point);
+            canonical_extension_slice::<F, D>(&mut opening.values);
+        }
+
+        let fri_proof = &mut air_proof.opening_proof;
+        for cap in fri_proof.commit_phase_merkle_caps.iter_mut() {
+            canonical_cap::<F, C::Hasher>(cap);
+        }
+        for round in fri_proof.query_round_proofs.iter_mut() {
+            for (evals, merkle_proof) in round.initial_trees_proof.evals_proofs.iter_mut() {
+                canonical_slice(evals);
+                canonical_merkle_proof::<F, C::Hasher>(merkle_proof);
+            }
+            for step in round.steps.iter_mut() {
+                canonical_extension_slice::<F, D>(&mut step.evals);
+                canonical_merkle_proof::<F, C::Hasher>(&mut step.merkle_proof);
+            }
+        }
+        canonical_extension_slice::<F, D>(&mut fri_proof.final_poly.coeffs);
+        fri_proof.pow_witness = canonical(fri_proof.pow_witness);
+
+        canonical_slice(&mut proof.global_values);
+        if let Some(commitment) = proof.public_inputs_commitment.as_mut() {
+            canonical_slice(&mut commitment.elements);
+        }
+        proof
+    }
+
+    /// The canonical encoding of the proof, which is the body written by `write_to`.
+    pub fn canonical_bytes(&self) -> Vec<u8> {
+        body_options()
+            .serialize(&self.canonical())
+            .expect("Failed to serialize the proof")
+    }
+
+    /// A digest of the canonical encoding of the proof.
+    pub fn digest(&self) -> Vec<u8> {
+        let elements = self
+            .canonical_bytes()
+            .into_iter()
+            .map(F::from_canonical_u8)
+            .collect::<Vec<_>>();
+        C::Hasher::hash_no_pad(&elements).to_bytes()
+    }
+}
+
+fn canonical<F: RichField>(value: F) -> F {
+    F::from_canonical_u64(value.to_canonical_u64())
+}
+
+fn canonical_slice<F: RichField>(values: &mut [F]) {
+    for value in values.iter_mut() {
+        *value = canonical(*value);
+    }
+}
+
+fn canonical_extension<F: RichField + Extendable<D>, const D: usize>(
+    value: F::Extension,
+) -> F::Extension {
+    F::Extension::from_basefield_array(value.to_basefield_array().map(canonical))
+}
+
+fn canonical_extension_slice<F: RichField + Extendable<D>, const D: usize>(
+    values: &mut [F::Extension],
+) {
+    for value in values.iter_mut() {
+        *value = canonical_extension::<F, D>(*value);
+    }
+}
+
+/// Hashes are canonicalized through their byte encoding, which encodes the canonical
+/// representatives of field elements.
+fn canonical_hash<F: RichField, H: Hasher<F>>(hash: &mut H::Hash) {
+    *hash = H::Hash::from_bytes(&hash.to_bytes());
+}
+
+fn canonical_cap<F: RichField, H: Hasher<F>>(cap: &mut MerkleCap<F, H>) {
+    for hash in cap.0.iter_mut() {
+        canonical_hash::<F, H>(hash);
+    }
+}
+
+fn canonical_merkle_proof<F: RichField, H: Hasher<F>>(proof: &mut MerkleProof<F, H>) {
+    for hash in proof.siblings.iter_mut() {
+        canonical_hash::<F, H>(hash);
+    }
+}
+
+/// The bincode options of the proof body, which are those of `bincode::serialize`.
+fn body_options() -> impl Options {
+    bincode::DefaultOptions::new()
+        .with_fixint_encoding()
+        .with_little_endian()
+        .allow_trailing_bytes()
+}
+
+#[cfg(test)]
+mod tests {
+    use plonky2::field::goldilocks_field::GoldilocksField;
+    use plonky2::field::types::Field64;
+
+    use super::*;
+    use crate::air::fibonacci::FibonacciAir;
+    use crate::math::prelude::*;
+    use crate::plonky2::stark::config::{
+        CurtaPoseidonGoldilocksConfig, PoseidonGoldilocksStarkConfig,
+    };
+    use crate::plonky2::stark::prover::StarkyProver;
+    use crate::plonky2::stark::verifier::StarkyVerifier;
+    use crate::plonky2::stark::Starky;
+    use crate::trace::generator::ConstantGenerator;
+
+    #[test]
+    fn test_proof_stream_round_trip() {
+        type F = GoldilocksField;
+        type SC = PoseidonGoldilocksStarkConfig;
+        type Proof = StarkProof<F, CurtaPoseidonGoldilocksConfig, 2>;
+
+        let num_rows = 1 << 5usize;
+        let stark = Starky::<FibonacciAir>::new(FibonacciAir::new());
+
+        let public_inputs = [
+            F::ZERO,
+            F::ONE,
+            FibonacciAir::fibonacci(num_rows - 1, F::ZERO, F::ONE),
+        ];
+        let trace_generator =
+            ConstantGenerator::new(FibonacciAir::generate_trace(F::ZERO, F::ONE, num_rows));
+        let config = SC::standard_fast_config(num_rows);
+
+        let proof = StarkyProver::prove(&config, &stark, &trace_generator, &public_inputs).unwrap();
+
+        let mut bytes = Vec::new();
+        proof.write_to(&config, &mut bytes).unwrap();
+
+        let header = ProofHeader::read_from(&mut bytes.as_slice()).unwrap();
+        assert_eq!(header.degree_bits, 5);
+        assert_eq!(header.config_digest, config.digest());
+
+        let read_proof = Proof::read_from(&config, bytes.as_slice()).unwrap();
+        assert_eq!(read_proof, proof);
+        StarkyVerifier::verify(&config, &stark, read_proof, &public_inputs).unwrap();
+
+        // Proofs for a different configuration are rejected from the header.
+        let other_config = SC::standard_fast_config(2 * num_rows);
+        assert!(Proof::read_from(&other_config, bytes.as_slice()).is_err());
+        let other_config = SC::standard_fast_config(num_rows).with_proof_of_work_bits(17);
+        assert!(Proof::read_from(&other_config, bytes.as_slice()).is_err());
+
+        // Truncated and corrupted streams are rejected.
+        let truncated = &bytes[..bytes.len() - 1];
+        assert!(Proof::read_from(&config, truncated).is_err());
+        let mut wrong_version = bytes.clone();
+        wrong_version[4] ^= 1;
+        assert!(Proof::read_from(&config, wrong_version.as_slice()).is_err());
+
+        // Bodies longer than the maximal length are rejected from the header, and length prefixes
+        // exceeding the declared length of the body are rejected before allocating.
+        let body_start = bytes.len() - proof.canonical_bytes().len();
+        let mut too_long = bytes.clone();
+        too_long[body_start - 8..body_start].copy_from_slice(&u64::MAX.to_le_bytes());
+        assert!(Proof::read_from(&config, too_long.as_slice()).is_err());
+        let mut huge_prefix = bytes.clone();
+        huge_prefix[body_start..body_start + 8].copy_from_slice(&(u64::MAX >> 8).to_le_bytes());
+        assert!(Proof::read_from(&config, huge_prefix.as_slice()).is_err());
+    }
+
+    #[test]
+    fn test_proof_digest() {
+        type F = GoldilocksField;
+        type SC = PoseidonGoldilocksStarkConfig;
+        type Proof = StarkProof<F, CurtaPoseidonGoldilocksConfig, 2>;
+
+        let num_rows = 1 << 5usize;
+        let stark = Starky::<FibonacciAir>::new(FibonacciAir::new());
+
+        let public_inputs = [
+            F::ZERO,
+            F::ONE,
+            FibonacciAir::fibonacci(num_rows - 1, F::ZERO, F::ONE),
+        ];
+        let trace_generator =
+            ConstantGenerator::new(FibonacciAir::generate_trace(F::ZERO, F::ONE, num_rows));
+        let config = SC::standard_fast_config(num_rows);
+
+        let proof = StarkyProver::prove(&config, &stark, &trace_generator, &public_inputs).unwrap();
+
+        // The canonical encoding is the body of the serialized proof.
+        let bytes = proof.canonical_bytes();
+        assert_eq!(bytes, bincode::serialize(&proof.canonical()).unwrap());
+        let mut stream = Vec::new();
+        proof.write_to(&config, &mut stream).unwrap();
+        assert!(stream.ends_with(&bytes));
+
+        // Lengths and `usize` fields are little-endian `u64`s on every platform. The encoding
+        // starts with the number of trace rounds and ends with the degree bits, the cap height
+        // and the tag of the missing public inputs commitment.
+        let num_rounds = proof.air_proof.trace_caps.len() as u64;
+        assert_eq!(bytes[..8], num_rounds.to_le_bytes());
+        let tail = [
+            &5u64.to_le_bytes()[..],
+            &(proof.cap_height as u64).to_le_bytes()[..],
+            &[0u8],
+        ]
+        .concat();
+        assert!(bytes.ends_with(&tail));
+
+        // The digest is preserved by serialization and changes with the proof.
+        let digest = proof.digest();
+        let read_proof: Proof = bincode::deserialize(&bytes).unwrap();
+        assert_eq!(read_proof.digest(), digest);
+        let mut other_proof = proof.clone();
+        other_proof.global_values.push(F::ONE);
+        assert_ne!(other_proof.digest(), digest);
+
+        // Field elements are encoded as their canonical representatives, so that proofs with
+        // non-canonical representations of the same elements have the same encoding.
+        let mut canonical_proof = proof.clone();
+        canonical_proof.global_values.push(F::ONE);
+        canonical_proof.air_proof.openings.local_values[0] = <F as Extendable<2>>::Extension::ONE;
+        let order = <F as Field64>::ORDER;
+        let mut non_canonical_proof = proof;
+        non_canonical_proof
+            .global_values
+            .push(GoldilocksField(order + 1));
+        non_canonical_proof.air_proof.openings.local_values[0] =
+            <F as Extendable<2>>::Extension::from_basefield_array([
+                GoldilocksField(order + 1),
+                GoldilocksField(order),
+            ]);
+        assert_eq!(non_canonical_proof, canonical_proof);
+        assert_eq!(
+            non_canonical_proof.canonical_bytes(),
+            canonical_proof.canonical_bytes()
+        );
+        assert_eq!(non_canonical_proof.digest(), canonical_proof.digest());
+
+        // The global value is encoded as a little-endian `u64` on every platform, right before
+        // the degree bits.
+        let bytes = non_canonical_proof.canonical_bytes();
+        let value_end = bytes.len() - tail.len();
+        assert_eq!(bytes[value_end - 8..value_end], 1u64.to_le_bytes());
+
+        // Written proofs are canonical, and read back as equal proofs.
+        let mut stream = Vec::new();
+        non_canonical_proof.write_to(&config, &mut stream).unwrap();
+        assert!(stream.ends_with(&bytes));
+        let read_proof = Proof::read_from(&config, stream.as_slice()).unwrap();
+        assert_eq!(read_proof, non_canonical_proof);
+        assert_eq!(bincode::serialize(&read_proof).unwrap(), bytes);
+    }
+}
@@ -0,0 +1,68 @@
+//! Lookup tables committed once and shared by many proofs.
+//!
+//! A fixed table, such as a byte or range table, is the same in every proof of an AIR. A
+//! `CommittedTable` holds the low-degree extension and the Merkle tree of such a table, so that
+//! every proof reuses them instead of committing to the table again. The verifier only needs the
+//! cap of the table, which it compares with the cap of the corresponding round of each proof.
+//!
+//! Since the table is public, reusing its commitment across proofs does not leak anything even if
+//! the commitment is blinded.
+
+use plonky2::field::extension::Extendable;
+use plonky2::fri::oracle::PolynomialBatch;
+use plonky2::hash::hash_types::RichField;
+use plonky2::hash::merkle_tree::{MerkleCap, MerkleTree};
+use plonky2::util::timing::TimingTree;
+
+use super::config::{CurtaConfig, StarkyConfig};
+use crate::trace::AirTrace;
+
+/// The commitment to a fixed table, reused by every proof that opens it.
+#[derive(Debug)]
+pub struct CommittedTable<F: RichField + Extendable<D>, C: CurtaConfig<D, F = F>, const D: usize> {
+    batch: PolynomialBatch<F, C::GenericConfig, D>,
+}
+
+impl<F: RichField + Extendable<D>, C: CurtaConfig<D, F = F>, const D: usize>
+    CommittedTable<F, C, D>
+{
+    /// Commits to `table` with the parameters of `config`.
+    pub fn new(config: &StarkyConfig<C, D>, table: &AirTrace<F>) -> Self {
+        Self::from_batch(config.commit(table, &mut TimingTree::default()))
+    }
+
+    /// A table from an existing commitment to it.
+    pub fn from_batch(batch: PolynomialBatch<F, C::GenericConfig, D>) -> Self {
+        Self { batch }
+    }
+
+    /// The cap of the table, which the verifier checks the proofs against.
+    pub fn cap(&self) -> &MerkleCap<F, C::Hasher> {
+        &self.batch.merkle_tree.cap
+    }
+
+    /// A copy of the committed polynomials and Merkle tree for a single proof.
+    ///
+    /// This is a copy of the memory of the commitment, without computing the low-degree extension
+    /// or hashing the leaves again.
+    pub fn batch(&self) -> PolynomialBatch<F, C::GenericConfig, D> {
+        let PolynomialBatch {
+            polynomials,
+            merkle_tree,
+            degree_log,
+            rate_bits,
+            blinding,
+        } = &self.batch;
+        PolynomialBatch {
+            polynomials: polynomials.clone(),
+            merkle_tree: MerkleTree {
+                leaves: merkle_tree.leaves.clone(),
+                digests: merkle_tree.digests.clone(),
+                cap: merkle_tree.cap.clone(),
+            },
+            degree_log: *degree_log,
+            rate_bits: *rate_bits,
+            blinding: *blinding,
+        }
+    }
+}
@@ -7,27 +7,185 @@ use plonky2::field::extension::{Extendable, FieldExtension};
 use plonky2::field::types::Field as Plonky2Field;
 use plonky2::fri::verifier::verify_fri_proof;
 use plonky2::fri::witness_util::set_fri_proof_target;
+use plonky2::fri::FriParams;
 use plonky2::hash::hash_types::RichField;
+use plonky2::iop::challenger::Challenger;
 use plonky2::iop::ext_target::ExtensionTarget;
 use plonky2::iop::target::Target;
 use plonky2::iop::witness::WitnessWrite;
 use plonky2::plonk::circuit_builder::CircuitBuilder;
 use plonky2::plonk::config::AlgebraicHasher;
-use plonky2::plonk::plonk_common::reduce_with_powers;
+use plonky2::plonk::plonk_common::{reduce_with_powers, salt_size};
+use plonky2::util::log2_ceil;
 use plonky2::util::reducing::ReducingFactorTarget;
 
+use super::commitment::TraceCommitment;
+use super::compression::CompressedStarkProof;
 use super::config::{CurtaConfig, StarkyConfig};
+use super::deferred::AggregatedStarkProof;
+use super::envelope::ProofEnvelope;
 use super::proof::{
-    AirProofTarget, StarkOpeningSet, StarkOpeningSetTarget, StarkProof, StarkProofChallenges,
-    StarkProofChallengesTarget, StarkProofTarget,
+    AirProofTarget, BatchStarkProof, PointOpeningTarget, ProofShapeError, StarkOpeningSet,
+    StarkOpeningSetTarget, StarkProof, StarkProofChallenges, StarkProofChallengesTarget,
+    StarkProofTarget,
 };
+use super::shared::SharedInputsProof;
+use super::versions::StarkyVersions;
 use super::Starky;
 use crate::air::{RAir, RAirData};
-use crate::plonky2::parser::consumer::{ConstraintConsumer, RecursiveConstraintConsumer};
+use crate::maybe_rayon::*;
+use crate::plonky2::parser::consumer::{
+    ConstraintConsumer, FixedConstraintConsumer, RecursiveConstraintConsumer,
+};
 use crate::plonky2::parser::global::{GlobalRecursiveStarkParser, GlobalStarkParser};
-use crate::plonky2::parser::{RecursiveStarkParser, StarkParser};
+use crate::plonky2::parser::{FixedStarkParser, RecursiveStarkParser, StarkParser};
 use crate::plonky2::stark::proof::AirProof;
 use crate::plonky2::{Plonky2Air, StarkyAir};
+use crate::polynomial::barycentric::BarycentricCoset;
+
+/// The reasons for which the verifier can reject a proof.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum VerificationError {
+    /// The proof does not have the shape expected by the STARK and the configuration.
+    MalformedProof(ProofShapeError),
+    /// The degree of the committed polynomials does not match the configuration.
+    DegreeBitsMismatch { expected: usize, found: usize },
+    /// The Merkle caps of the proof are higher than the trees of the configuration.
+    IncompatibleCapHeight { max: usize, found: usize },
+    /// The numbers of configurations, STARKs, proofs and public inputs do not match.
+    InputLengthMismatch,
+    /// The AIR or the configuration do not have the shape of a fixed-size specialization of the
+    /// verifier.
+    SpecializationMismatch,
+    /// A batch proof with no STARKs.
+    EmptyBatch,
+    /// The global values do not satisfy the global constraints.
+    InvalidGlobalValues,
+    /// The constraints evaluated at the opening point do not match the quotient polynomials.
+    QuotientMismatch { challenge: usize },
+    /// The quotient of the constraints has more chunks than the rate of the FRI configuration
+    /// allows to commit to.
+    UnsupportedConstraintDegree { degree: usize, rate_bits: usize },
+    /// The commitment to the public inputs does not match the public inputs.
+    PublicInputsCommitmentMismatch,
+    /// The commitment to the shared inputs of a statement does not match the shared inputs.
+    SharedInputsCommitmentMismatch,
+    /// The cap of a preprocessed round does not match the commitment to the fixed table.
+    PreprocessedCapMismatch,
+    /// The trace commitment does not match the statement or the trace opened by the proof.
+    TraceCommitmentMismatch,
+    /// The proof envelope is of an unsupported version.
+    UnsupportedEnvelopeVersion { expected: u16, found: u16 },
+    /// The proof envelope is for a different AIR.
+    AirIdMismatch,
+    /// The proof envelope was generated with a different configuration.
+    ConfigDigestMismatch,
+    /// An extra opening point appears more than once.
+    RepeatedExtraPoint,
+    /// An extra opening point is in the LDE domain.
+    ExtraPointInDomain,
+    /// The FRI proof is invalid.
+    InvalidFriProof(String),
+}
+
+impl From<ProofShapeError> for VerificationError {
+    fn from(error: ProofShapeError) -> Self {
+        Self::MalformedProof(error)
+    }
+}
+
+impl core::fmt::Display for VerificationError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::MalformedProof(error) => write!(f, "{}", error),
+            Self::DegreeBitsMismatch { expected, found } => write!(
+                f,
+                "Proof has degree bits {}, but the configuration has {}",
+                found, expected
+            ),
+            Self::IncompatibleCapHeight { max, found } => write!(
+                f,
+                "Proof has cap height {}, but the configuration allows at most {}",
+                found, max
+            ),
+            Self::InputLengthMismatch => write!(
+                f,
+                "Number of configs, STARKs, proofs and public inputs do not match"
+            ),
+            Self::SpecializationMismatch => write!(
+                f,
+                "AIR or configuration does not match the fixed-size specialization"
+            ),
+            Self::EmptyBatch => write!(f, "No STARKs to verify"),
+            Self::InvalidGlobalValues => write!(f, "Global constraints are not satisfied"),
+            Self::QuotientMismatch { challenge } => write!(
+                f,
+                "Mismatch between evaluation and opening of quotient polynomial for challenge {}",
+                challenge
+            ),
+            Self::UnsupportedConstraintDegree { degree, rate_bits } => write!(
+                f,
+                "Constraints of degree {} are not supported with rate bits {}",
+                degree, rate_bits
+            ),
+            Self::PublicInputsCommitmentMismatch => {
+                write!(f, "Public inputs do not match their commitment")
+            }
+            Self::SharedInputsCommitmentMismatch => {
+                write!(f, "Shared inputs do not match their commitment")
+            }
+            Self::PreprocessedCapMismatch => {
+                write!(f, "Preprocessed trace does not match the committed table")
+            }
+            Self::TraceCommitmentMismatch => {
+                write!(f, "Trace commitment does not match the proof")
+            }
+            Self::UnsupportedEnvelopeVersion { expected, found } => write!(
+                f,
+                "Unsupported proof envelope version {}, expected {}",
+                found, expected
+            ),
+            Self::AirIdMismatch => write!(f, "Proof was generated for a different AIR"),
+            Self::ConfigDigestMismatch => {
+                write!(f, "Proof was generated with a different configuration")
+            }
+            Self::RepeatedExtraPoint => write!(f, "Repeated extra opening point"),
+            Self::ExtraPointInDomain => write!(f, "Extra opening point is in the LDE domain"),
+            Self::InvalidFriProof(error) => write!(f, "Invalid FRI proof: {}", error),
+        }
+    }
+}
+
+impl std::error::Error for VerificationError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::MalformedProof(error) => Some(error),
+            _ => None,
+        }
+    }
+}
+
+impl<F, C, const D: usize> StarkProof<F, C, D>
+where
+    F: RichField + Extendable<D>,
+    C: CurtaConfig<D, F = F, FE = F::Extension>,
+{
+    /// Recomputes the Fiat-Shamir challenges of the proof from its transcript.
+    ///
+    /// These are the challenges used by `StarkyVerifier::verify`, which lets external tools replay
+    /// and inspect the transcript of a proof independently of the verifier.
+    pub fn challenges<A: RAirData>(
+        &self,
+        config: &StarkyConfig<C, D>,
+        stark: &Starky<A>,
+        public_inputs: &[F],
+    ) -> Result<StarkProofChallenges<F, D>, VerificationError> {
+        let config = &StarkyVerifier::compatible_config(config, self)?;
+        self.validate_shape(stark, config)?;
+        let degree_bits = StarkyVerifier::check_degree_bits(config, self)?;
+        Ok(self.get_challenges(config, stark, public_inputs, degree_bits))
+    }
+}
 
 #[derive(Debug, Clone)]
 pub struct StarkyVerifier<F, C, const D: usize>(core::marker::PhantomData<(F, C)>);
@@ -44,104 +202,382 @@ where
         public_inputs: &[F],
         global_values: &[F],
         challenges: StarkProofChallenges<F, D>,
-    ) -> Result<()>
+    ) -> Result<(), VerificationError>
     where
         A: StarkyAir<F, D>,
     {
-        let degree_bits = config.degree_bits;
-
         Self::validate_proof_shape(config, stark, &proof, global_values)?;
 
-        let StarkOpeningSet {
-            local_values,
-            next_values,
-            quotient_polys,
-        } = &proof.openings;
+        Self::check_constraints_at_zeta(
+            config,
+            stark,
+            &proof.openings,
+            public_inputs,
+            global_values,
+            &challenges.stark_alphas,
+            &challenges.stark_betas,
+            challenges.stark_zeta,
+        )?;
+        Self::check_fri_proof(config, stark, &proof, &challenges, &config.fri_params())
+    }
 
-        // Verify the global constraints
-        let mut global_parser = GlobalStarkParser {
-            global_vars: global_values,
-            public_vars: public_inputs,
-            challenges: &challenges.stark_betas,
-        };
-        stark.air().eval_global(&mut global_parser);
+    /// Checks the constraints of a proof at the opening point `zeta`, without any Merkle or FRI
+    /// verification.
+    ///
+    /// This is the first and cheapest phase of `verify`, which can be used to reject malformed or
+    /// inconsistent proofs before calling `verify_fri`. A proof is only valid if it passes both
+    /// phases with the challenges given by `StarkProof::challenges`.
+    pub fn verify_constraints_at_zeta<A>(
+        config: &StarkyConfig<C, D>,
+        stark: &Starky<A>,
+        proof: &StarkProof<F, C, D>,
+        public_inputs: &[F],
+        challenges: &StarkProofChallenges<F, D>,
+    ) -> Result<(), VerificationError>
+    where
+        A: StarkyAir<F, D>,
+    {
+        let config = &Self::compatible_config(config, proof)?;
+        Self::validate_proof_shape(config, stark, &proof.air_proof, &proof.global_values)?;
+        Self::verify_public_inputs_commitment(config, proof, public_inputs)?;
+        Self::check_constraints_at_zeta(
+            config,
+            stark,
+            &proof.air_proof.openings,
+            public_inputs,
+            &proof.global_values,
+            &challenges.stark_alphas,
+            &challenges.stark_betas,
+            challenges.stark_zeta,
+        )
+    }
 
-        let global_values_ext = global_values
-            .iter()
-            .map(|x| F::Extension::from_basefield(*x))
-            .collect::<Vec<_>>();
-        let public_inputs_ext = public_inputs
-            .iter()
-            .map(|x| F::Extension::from_basefield(*x))
-            .collect::<Vec<_>>();
-        let challenges_ext = challenges
-            .stark_betas
-            .into_iter()
-            .map(F::Extension::from_basefield)
-            .collect::<Vec<_>>();
+    /// Checks the FRI argument of a proof, which is the second phase of `verify` after
+    /// `verify_constraints_at_zeta`.
+    pub fn verify_fri<A>(
+        config: &StarkyConfig<C, D>,
+        stark: &Starky<A>,
+        proof: &StarkProof<F, C, D>,
+        challenges: &StarkProofChallenges<F, D>,
+    ) -> Result<(), VerificationError>
+    where
+        A: StarkyAir<F, D>,
+    {
+        let config = &Self::compatible_config(config, proof)?;
+        Self::validate_proof_shape(config, stark, &proof.air_proof, &proof.global_values)?;
+        Self::check_fri_proof(
+            config,
+            stark,
+            &proof.air_proof,
+            challenges,
+            &config.fri_params(),
+        )
+    }
 
-        let (l_0, l_last) = Self::eval_l_0_and_l_last(degree_bits, challenges.stark_zeta);
-        let last = F::primitive_root_of_unity(degree_bits).inverse();
-        let z_last = challenges.stark_zeta - last.into();
-        let mut consumer = ConstraintConsumer::<F::Extension>::new(
-            challenges
-                .stark_alphas
-                .iter()
-                .map(|&alpha| F::Extension::from_basefield(alpha))
-                .collect::<Vec<_>>(),
-            z_last,
-            l_0,
-            l_last,
-        );
+    /// A specialization of `verify_constraints_at_zeta` for an AIR with `COLUMNS` columns and a
+    /// configuration with `N` challenges.
+    ///
+    /// The constraint accumulators are held in arrays, which lets the compiler unroll and
+    /// vectorize the evaluation for AIRs whose shape is fixed in production. Returns
+    /// `VerificationError::SpecializationMismatch` if the AIR or the configuration do not match
+    /// the specialization.
+    pub fn verify_constraints_at_zeta_fixed<A, const COLUMNS: usize, const N: usize>(
+        config: &StarkyConfig<C, D>,
+        stark: &Starky<A>,
+        proof: &StarkProof<F, C, D>,
+        public_inputs: &[F],
+        challenges: &StarkProofChallenges<F, D>,
+    ) -> Result<(), VerificationError>
+    where
+        A: StarkyAir<F, D> + for<'a> RAir<FixedStarkParser<'a, F, D, N>>,
+    {
+        let config = &Self::compatible_config(config, proof)?;
+        Self::validate_proof_shape(config, stark, &proof.air_proof, &proof.global_values)?;
+        Self::verify_public_inputs_commitment(config, proof, public_inputs)?;
+        Self::check_constraints_at_zeta_fixed::<A, COLUMNS, N>(
+            config,
+            stark,
+            &proof.air_proof.openings,
+            public_inputs,
+            &proof.global_values,
+            &challenges.stark_alphas,
+            &challenges.stark_betas,
+            challenges.stark_zeta,
+        )
+    }
 
-        let mut parser = StarkParser {
-            local_vars: local_values,
-            next_vars: next_values,
-            global_vars: &global_values_ext,
-            public_vars: &public_inputs_ext,
-            challenges: &challenges_ext,
-            consumer: &mut consumer,
-        };
+    /// Verifies a proof with the constraint evaluation specialized for an AIR with `COLUMNS`
+    /// columns and a configuration with `N` challenges.
+    pub fn verify_fixed<A, const COLUMNS: usize, const N: usize>(
+        config: &StarkyConfig<C, D>,
+        stark: &Starky<A>,
+        proof: StarkProof<F, C, D>,
+        public_inputs: &[F],
+    ) -> Result<(), VerificationError>
+    where
+        A: StarkyAir<F, D> + for<'a> RAir<FixedStarkParser<'a, F, D, N>>,
+    {
+        let challenges = proof.challenges(config, stark, public_inputs)?;
+        Self::verify_constraints_at_zeta_fixed::<A, COLUMNS, N>(
+            config,
+            stark,
+            &proof,
+            public_inputs,
+            &challenges,
+        )?;
+        Self::verify_fri(config, stark, &proof, &challenges)
+    }
 
-        stark.air().eval(&mut parser);
-        let vanishing_polys_zeta = consumer.accumulators();
+    /// Checks the FRI argument for the openings of `proof`.
+    fn check_fri_proof<A>(
+        config: &StarkyConfig<C, D>,
+        stark: &Starky<A>,
+        proof: &AirProof<F, C, D>,
+        challenges: &StarkProofChallenges<F, D>,
+        fri_params: &FriParams,
+    ) -> Result<(), VerificationError>
+    where
+        A: StarkyAir<F, D>,
+    {
+        let degree_bits = config.degree_bits;
 
-        // Check each polynomial identity, of the form `vanishing(x) = Z_H(x) quotient(x)`, at zeta.
-        let zeta_pow_deg = challenges.stark_zeta.exp_power_of_2(degree_bits);
-        let z_h_zeta = zeta_pow_deg - F::Extension::ONE;
-
-        // `quotient_polys_zeta` holds `num_challenges * quotient_degree_factor` evaluations.
-        // Each chunk of `quotient_degree_factor` holds the evaluations of `t_0(zeta),...,t_{quotient_degree_factor-1}(zeta)`
-        // where the "real" quotient polynomial is `t(X) = t_0(X) + t_1(X)*X^n + t_2(X)*X^{2n} + ...`.
-        // So to reconstruct `t(zeta)` we can compute `reduce_with_powers(chunk, zeta^n)` for each
-        // `quotient_degree_factor`-sized chunk of the original evaluations.
-        let constraint_degree = stark.air().constraint_degree();
-        let quotient_degree_factor = 1.max(constraint_degree - 1);
-        for (i, chunk) in quotient_polys.chunks(quotient_degree_factor).enumerate() {
-            ensure!(
-                vanishing_polys_zeta[i] == z_h_zeta * reduce_with_powers(chunk, zeta_pow_deg),
-                "Mismatch between evaluation and opening of quotient polynomial"
-            );
+        // The FRI argument can not open the polynomials at points of the LDE domain.
+        let extra_points = proof.openings.extra_points();
+        if !extra_points.iter().all_unique() {
+            return Err(VerificationError::RepeatedExtraPoint);
+        }
+        let lde_bits = fri_params.lde_bits();
+        let shift_inv = F::Extension::from_basefield(F::coset_shift().inverse());
+        for point in extra_points.iter() {
+            if (*point * shift_inv).exp_power_of_2(lde_bits) == F::Extension::ONE {
+                return Err(VerificationError::ExtraPointInDomain);
+            }
         }
 
         let merkle_caps = proof
             .trace_caps
-            .into_iter()
-            .chain(once(proof.quotient_polys_cap))
+            .iter()
+            .chain(once(&proof.quotient_polys_cap))
+            .cloned()
             .collect::<Vec<_>>();
 
         verify_fri_proof::<F, C::GenericConfig, D>(
             &stark.fri_instance(
                 challenges.stark_zeta,
                 F::primitive_root_of_unity(degree_bits),
+                &extra_points,
                 config,
             ),
             &proof.openings.to_fri_openings(),
             &challenges.fri_challenges,
             &merkle_caps,
             &proof.opening_proof,
-            &config.fri_params(),
+            fri_params,
+        )
+        .map_err(|e| VerificationError::InvalidFriProof(e.to_string()))
+    }
+
+    /// Checks the constraints of `stark` at the opening point `zeta` against the openings of the
+    /// quotient polynomials.
+    #[allow(clippy::too_many_arguments)]
+    fn check_constraints_at_zeta<A>(
+        config: &StarkyConfig<C, D>,
+        stark: &Starky<A>,
+        openings: &StarkOpeningSet<F, D>,
+        public_inputs: &[F],
+        global_values: &[F],
+        stark_alphas: &[F],
+        stark_betas: &[F],
+        zeta: F::Extension,
+    ) -> Result<(), VerificationError>
+    where
+        A: StarkyAir<F, D>,
+    {
+        let (z_last, l_0, l_last) = Self::constraint_filters(config.degree_bits, zeta);
+        let mut consumer = ConstraintConsumer::<F::Extension>::new(
+            Self::to_extension(stark_alphas),
+            z_last,
+            l_0,
+            l_last,
+        );
+        Self::eval_constraints_at_zeta(
+            config,
+            stark,
+            openings,
+            public_inputs,
+            global_values,
+            stark_betas,
+            &mut consumer,
+        )?;
+        Self::check_quotients(
+            config,
+            zeta,
+            &consumer.accumulators(),
+            &openings.quotient_chunks,
+        )
+    }
+
+    /// A specialization of `check_constraints_at_zeta` for an AIR with `COLUMNS` columns and a
+    /// configuration with `N` challenges.
+    #[allow(clippy::too_many_arguments)]
+    fn check_constraints_at_zeta_fixed<A, const COLUMNS: usize, const N: usize>(
+        config: &StarkyConfig<C, D>,
+        stark: &Starky<A>,
+        openings: &StarkOpeningSet<F, D>,
+        public_inputs: &[F],
+        global_values: &[F],
+        stark_alphas: &[F],
+        stark_betas: &[F],
+        zeta: F::Extension,
+    ) -> Result<(), VerificationError>
+    where
+        A: StarkyAir<F, D> + for<'a> RAir<FixedStarkParser<'a, F, D, N>>,
+    {
+        if stark.air().num_columns() != COLUMNS
+            || config.num_challenges != N
+            || stark_alphas.len() != N
+        {
+            return Err(VerificationError::SpecializationMismatch);
+        }
+
+        let (z_last, l_0, l_last) = Self::constraint_filters(config.degree_bits, zeta);
+        let alphas = core::array::from_fn(|i| F::Extension::from_basefield(stark_alphas[i]));
+        let mut consumer =
+            FixedConstraintConsumer::<F::Extension, N>::new(alphas, z_last, l_0, l_last);
+        Self::eval_constraints_at_zeta(
+            config,
+            stark,
+            openings,
+            public_inputs,
+            global_values,
+            stark_betas,
+            &mut consumer,
         )?;
+        Self::check_quotients(
+            config,
+            zeta,
+            &consumer.accumulators(),
+            &openings.quotient_chunks,
+        )
+    }
+
+    /// Checks the global constraints and evaluates the constraints of `stark` at the opening
+    /// point into `consumer`.
+    fn eval_constraints_at_zeta<A, CC>(
+        config: &StarkyConfig<C, D>,
+        stark: &Starky<A>,
+        openings: &StarkOpeningSet<F, D>,
+        public_inputs: &[F],
+        global_values: &[F],
+        stark_betas: &[F],
+        consumer: &mut CC,
+    ) -> Result<(), VerificationError>
+    where
+        A: StarkyAir<F, D> + for<'a> RAir<StarkParser<'a, F, F::Extension, F::Extension, D, D, CC>>,
+    {
+        Self::check_global_constraints(config, stark, public_inputs, global_values, stark_betas)?;
+
+        let global_values_ext = Self::to_extension(global_values);
+        let public_inputs_ext = Self::to_extension(public_inputs);
+        let challenges_ext = Self::to_extension(stark_betas);
+
+        // The columns which are not opened at the next row are not read by the constraints.
+        let next_values = stark.expand_next_values(&openings.next_values, F::Extension::ZERO);
+        let mut parser = StarkParser {
+            local_vars: &openings.local_values,
+            next_vars: &next_values,
+            global_vars: &global_values_ext,
+            public_vars: &public_inputs_ext,
+            challenges: &challenges_ext,
+            consumer,
+        };
+
+        stark.air().eval(&mut parser);
+        Ok(())
+    }
+
+    /// Checks that the constraint degree is supported by the configuration and that the global
+    /// values satisfy the global constraints.
+    fn check_global_constraints<A>(
+        config: &StarkyConfig<C, D>,
+        stark: &Starky<A>,
+        public_inputs: &[F],
+        global_values: &[F],
+        stark_betas: &[F],
+    ) -> Result<(), VerificationError>
+    where
+        A: StarkyAir<F, D>,
+    {
+        let constraint_degree = stark.air().constraint_degree();
+        let num_quotient_chunks = stark.num_quotient_chunks(config);
+        let rate_bits = config.fri_config.rate_bits;
+        if log2_ceil(num_quotient_chunks) > rate_bits {
+            return Err(VerificationError::UnsupportedConstraintDegree {
+                degree: constraint_degree,
+                rate_bits,
+            });
+        }
+
+        let mut global_parser = GlobalStarkParser {
+            global_vars: global_values,
+            public_vars: public_inputs,
+            challenges: stark_betas,
+            is_satisfied: true,
+        };
+        stark.air().eval_global(&mut global_parser);
+        if !global_parser.is_satisfied {
+            return Err(VerificationError::InvalidGlobalValues);
+        }
+        Ok(())
+    }
+
+    fn to_extension(values: &[F]) -> Vec<F::Extension> {
+        values
+            .iter()
+            .map(|x| F::Extension::from_basefield(*x))
+            .collect()
+    }
+
+    /// The evaluations at `zeta` of `X - g^(n-1)`, `L_0` and `L_(n-1)`, which filter the
+    /// transition, first row and last row constraints.
+    fn constraint_filters(
+        degree_bits: usize,
+        zeta: F::Extension,
+    ) -> (F::Extension, F::Extension, F::Extension) {
+        let subgroup = BarycentricCoset::<F::Extension>::subgroup(degree_bits);
+        let last = subgroup.size() - 1;
+        let lagrange = subgroup.lagrange_basis(&[0, last], zeta);
+        let z_last = zeta - subgroup.point(last);
+        (z_last, lagrange[0], lagrange[1])
+    }
+
+    /// Checks each polynomial identity, of the form `vanishing(x) = Z_H(x) quotient(x)`, at zeta.
+    fn check_quotients(
+        config: &StarkyConfig<C, D>,
+        zeta: F::Extension,
+        vanishing_polys_zeta: &[F::Extension],
+        quotient_chunks: &[Vec<F::Extension>],
+    ) -> Result<(), VerificationError> {
+        let z_h_zeta =
+            BarycentricCoset::<F::Extension>::subgroup(config.degree_bits).vanishing(zeta);
+
+        // `quotient_chunks` holds a group of `k` evaluations for each challenge, where `k` is the
+        // number of quotient chunks. Each group holds the evaluations of
+        // `t_0(zeta),...,t_{k-1}(zeta)` where the "real" quotient polynomial is
+        // `t(X) = t_0(X) + t_1(X)*X^N + t_2(X)*X^{2N} + ...`.
+        // So to reconstruct `t(zeta)` we can compute `reduce_with_powers(chunks, zeta^N)` for each
+        // group. Here `N` is the degree bound of the committed polynomials, which is `2n` if the
+        // trace is blinded and `n` otherwise.
+        let zeta_pow_chunk_deg = zeta.exp_power_of_2(config.fri_degree_bits());
+        for (i, (vanishing_poly_zeta, chunks)) in vanishing_polys_zeta
+            .iter()
+            .zip_eq(quotient_chunks.iter())
+            .enumerate()
+        {
+            if *vanishing_poly_zeta != z_h_zeta * reduce_with_powers(chunks, zeta_pow_chunk_deg) {
+                return Err(VerificationError::QuotientMismatch { challenge: i });
+            }
+        }
         Ok(())
     }
 
@@ -150,16 +586,124 @@ where
         stark: &Starky<A>,
         proof: StarkProof<F, C, D>,
         public_inputs: &[F],
-    ) -> Result<()>
+    ) -> Result<(), VerificationError>
     where
         A: StarkyAir<F, D>,
     {
-        let degree_bits = proof.recover_degree_bits(config);
-        let challenges = proof.get_challenges(config, stark, public_inputs, degree_bits);
+        let challenges = proof.challenges(config, stark, public_inputs)?;
+        Self::verify_constraints_at_zeta(config, stark, &proof, public_inputs, &challenges)?;
+        Self::verify_fri(config, stark, &proof, &challenges)
+    }
+
+    /// Verifies a proof generated by `StarkyProver::prove_from_commitment`, checking that it
+    /// opens the trace committed to by `commitment` for `public_inputs`.
+    pub fn verify_from_commitment<A>(
+        config: &StarkyConfig<C, D>,
+        stark: &Starky<A>,
+        commitment: &TraceCommitment<F, C, D>,
+        proof: StarkProof<F, C, D>,
+        public_inputs: &[F],
+    ) -> Result<(), VerificationError>
+    where
+        A: StarkyAir<F, D>,
+    {
+        commitment.check(config, public_inputs)?;
+        commitment.check_proof(&proof)?;
+        Self::verify(config, stark, proof, public_inputs)
+    }
+
+    /// Verifies a proof stored in an envelope, after checking that the envelope is for `stark` and
+    /// `config`.
+    pub fn verify_envelope<A>(
+        config: &StarkyConfig<C, D>,
+        stark: &Starky<A>,
+        envelope: ProofEnvelope<F, C, D>,
+        public_inputs: &[F],
+    ) -> Result<(), VerificationError>
+    where
+        A: StarkyAir<F, D>,
+    {
+        envelope.check(config, stark)?;
+        Self::verify(config, stark, envelope.into_proof(), public_inputs)
+    }
+
+    /// Verifies a proof stored in an envelope with the layout of `versions` that the envelope was
+    /// generated for.
+    pub fn verify_versioned<A>(
+        config: &StarkyConfig<C, D>,
+        versions: &StarkyVersions<A, C, D>,
+        envelope: ProofEnvelope<F, C, D>,
+        public_inputs: &[F],
+    ) -> Result<(), VerificationError>
+    where
+        A: StarkyAir<F, D>,
+    {
+        let stark = versions
+            .find(&envelope.air_id)
+            .ok_or(VerificationError::AirIdMismatch)?;
+        Self::verify_envelope(config, stark, envelope, public_inputs)
+    }
+
+    /// The configuration `proof` is verified with, which is `config` with the cap height of the
+    /// proof.
+    ///
+    /// The proof must be of a trace of the length given by `config`, but it may use larger or
+    /// smaller Merkle caps than `config` as long as they fit in the LDE.
+    pub fn compatible_config(
+        config: &StarkyConfig<C, D>,
+        proof: &StarkProof<F, C, D>,
+    ) -> Result<StarkyConfig<C, D>, VerificationError> {
+        if proof.degree_bits != config.degree_bits {
+            return Err(VerificationError::DegreeBitsMismatch {
+                expected: config.degree_bits,
+                found: proof.degree_bits,
+            });
+        }
+        let max = config.fri_degree_bits() + config.fri_config.rate_bits;
+        if proof.cap_height > max {
+            return Err(VerificationError::IncompatibleCapHeight {
+                max,
+                found: proof.cap_height,
+            });
+        }
+        Ok(config.clone().with_cap_height(proof.cap_height))
+    }
+
+    /// Checks that the proof commits to `public_inputs` if the configuration requires it.
+    pub fn verify_public_inputs_commitment(
+        config: &StarkyConfig<C, D>,
+        proof: &StarkProof<F, C, D>,
+        public_inputs: &[F],
+    ) -> Result<(), VerificationError> {
+        if proof.public_inputs_commitment != config.public_inputs_commitment(public_inputs) {
+            return Err(VerificationError::PublicInputsCommitmentMismatch);
+        }
+        Ok(())
+    }
+
+    /// Verifies a proof whose Merkle paths were compressed by `StarkProof::compress`.
+    pub fn verify_compressed<A>(
+        config: &StarkyConfig<C, D>,
+        stark: &Starky<A>,
+        proof: CompressedStarkProof<F, C, D>,
+        public_inputs: &[F],
+    ) -> Result<(), VerificationError>
+    where
+        A: StarkyAir<F, D>,
+    {
+        let config = &Self::compatible_config(config, proof.inner())?;
+        proof.inner().validate_shape(stark, config)?;
+        Self::verify_public_inputs_commitment(config, proof.inner(), public_inputs)?;
+        // The degree can not be recovered from the compressed Merkle paths.
+        let challenges =
+            proof
+                .inner()
+                .get_challenges(config, stark, public_inputs, config.fri_degree_bits());
         let StarkProof {
             air_proof,
             global_values,
-        } = proof;
+            ..
+        } = proof.decompress_with_challenges(config, &challenges.fri_challenges)?;
         Self::verify_with_challenges(
             config,
             stark,
@@ -170,52 +714,375 @@ where
         )
     }
 
-    pub fn validate_proof_shape<A: RAirData>(
+    /// Verifies a sequence of proofs generated by `StarkyProver::prove_all` on a single
+    /// Fiat-Shamir transcript.
+    pub fn verify_all<A>(
+        configs: &[&StarkyConfig<C, D>],
+        starks: &[&Starky<A>],
+        proofs: &[StarkProof<F, C, D>],
+        public_inputs: &[&[F]],
+    ) -> Result<(), VerificationError>
+    where
+        A: StarkyAir<F, D>,
+    {
+        if configs.len() != proofs.len()
+            || starks.len() != proofs.len()
+            || public_inputs.len() != proofs.len()
+        {
+            return Err(VerificationError::InputLengthMismatch);
+        }
+
+        // The transcript is salted with the salt of the first configuration.
+        let mut challenger = configs
+            .first()
+            .map_or_else(Challenger::new, |config| config.challenger());
+        for (((config, stark), proof), public_inputs) in
+            configs.iter().zip(starks).zip(proofs).zip(public_inputs)
+        {
+            Self::verify_with_challenger(config, stark, proof, public_inputs, &mut challenger)?;
+        }
+        Ok(())
+    }
+
+    /// Verifies proofs generated by `StarkyProver::prove_shared`, checking that they are all
+    /// bound to `shared_inputs`.
+    pub fn verify_shared<A>(
+        configs: &[&StarkyConfig<C, D>],
+        starks: &[&Starky<A>],
+        proof: &SharedInputsProof<F, C, D>,
+        public_inputs: &[&[F]],
+        shared_inputs: &[F],
+    ) -> Result<(), VerificationError>
+    where
+        A: StarkyAir<F, D>,
+    {
+        let proofs = &proof.proofs;
+        if configs.len() != proofs.len()
+            || starks.len() != proofs.len()
+            || public_inputs.len() != proofs.len()
+        {
+            return Err(VerificationError::InputLengthMismatch);
+        }
+        if proof.shared_inputs_commitment != SharedInputsProof::<F, C, D>::commit(shared_inputs) {
+            return Err(VerificationError::SharedInputsCommitmentMismatch);
+        }
+
+        for (((config, stark), stark_proof), public_inputs) in
+            configs.iter().zip(starks).zip(proofs).zip(public_inputs)
+        {
+            let mut challenger =
+                SharedInputsProof::challenger(config, proof.shared_inputs_commitment);
+            Self::verify_with_challenger(
+                config,
+                stark,
+                stark_proof,
+                public_inputs,
+                &mut challenger,
+            )?;
+        }
+        Ok(())
+    }
+
+    /// Verifies many independent proofs of `stark` in parallel.
+    ///
+    /// The configuration and the FRI parameters are computed once for each shape of the proofs
+    /// and shared between them. The result at each index is that of the proof at the same
+    /// index, so that a single invalid proof does not reject the whole batch.
+    pub fn verify_batch<A>(
         config: &StarkyConfig<C, D>,
         stark: &Starky<A>,
-        proof: &AirProof<F, C, D>,
-        global_values: &[F],
-    ) -> Result<()> {
-        let fri_params = config.fri_params();
-        let cap_height = fri_params.config.cap_height;
+        proofs: &[(StarkProof<F, C, D>, &[F])],
+    ) -> Vec<Result<(), VerificationError>>
+    where
+        A: StarkyAir<F, D>,
+    {
+        let mut params = HashMap::new();
+        for (proof, _) in proofs {
+            params
+                .entry((proof.degree_bits, proof.cap_height))
+                .or_insert_with(|| {
+                    Self::compatible_config(config, proof).map(|config| {
+                        let fri_params = config.fri_params();
+                        (config, fri_params)
+                    })
+                });
+        }
 
-        let AirProof {
-            trace_caps,
-            quotient_polys_cap,
-            openings,
-            // The shape of the opening proof will be checked in the FRI verifier (see
-            // validate_fri_proof_shape), so we ignore it here.
-            opening_proof: _,
-        } = proof;
+        proofs
+            .par_iter()
+            .map(|(proof, public_inputs)| {
+                let (config, fri_params) = params[&(proof.degree_bits, proof.cap_height)]
+                    .as_ref()
+                    .map_err(Clone::clone)?;
+                Self::verify_with_params(config, fri_params, stark, proof, public_inputs)
+            })
+            .collect()
+    }
 
-        let StarkOpeningSet {
-            local_values,
-            next_values,
-            quotient_polys,
-        } = openings;
+    /// Verifies a proof with a configuration checked by `compatible_config` and its FRI
+    /// parameters.
+    fn verify_with_params<A>(
+        config: &StarkyConfig<C, D>,
+        fri_params: &FriParams,
+        stark: &Starky<A>,
+        proof: &StarkProof<F, C, D>,
+        public_inputs: &[F],
+    ) -> Result<(), VerificationError>
+    where
+        A: StarkyAir<F, D>,
+    {
+        proof.validate_shape(stark, config)?;
+        Self::verify_public_inputs_commitment(config, proof, public_inputs)?;
+        let degree_bits = Self::check_degree_bits(config, proof)?;
+        let challenges = proof.get_challenges(config, stark, public_inputs, degree_bits);
+        Self::check_constraints_at_zeta(
+            config,
+            stark,
+            &proof.air_proof.openings,
+            public_inputs,
+            &proof.global_values,
+            &challenges.stark_alphas,
+            &challenges.stark_betas,
+            challenges.stark_zeta,
+        )?;
+        Self::check_fri_proof(config, stark, &proof.air_proof, &challenges, fri_params)
+    }
 
-        for cap in trace_caps.iter() {
-            ensure!(cap.height() == cap_height);
+    /// Verifies a proof on the transcript held by `challenger`.
+    fn verify_with_challenger<A>(
+        config: &StarkyConfig<C, D>,
+        stark: &Starky<A>,
+        proof: &StarkProof<F, C, D>,
+        public_inputs: &[F],
+        challenger: &mut Challenger<F, C::Hasher>,
+    ) -> Result<(), VerificationError>
+    where
+        A: StarkyAir<F, D>,
+    {
+        let config = &Self::compatible_config(config, proof)?;
+        proof.validate_shape(stark, config)?;
+        Self::verify_public_inputs_commitment(config, proof, public_inputs)?;
+        let degree_bits = Self::check_degree_bits(config, proof)?;
+        let challenges = proof.get_challenges_with_challenger(
+            config,
+            stark,
+            public_inputs,
+            degree_bits,
+            challenger,
+        );
+        Self::verify_with_challenges(
+            config,
+            stark,
+            proof.air_proof.clone(),
+            public_inputs,
+            &proof.global_values,
+            challenges,
+        )
+    }
+
+    /// Verifies a proof of several STARKs sharing a single FRI argument.
+    pub fn verify_batch_proof<A>(
+        config: &StarkyConfig<C, D>,
+        starks: &[&Starky<A>],
+        proof: BatchStarkProof<F, C, D>,
+        public_inputs: &[&[F]],
+    ) -> Result<(), VerificationError>
+    where
+        A: StarkyAir<F, D>,
+    {
+        let num_starks = starks.len();
+        if num_starks == 0 {
+            return Err(VerificationError::EmptyBatch);
+        }
+        if public_inputs.len() != num_starks {
+            return Err(VerificationError::InputLengthMismatch);
+        }
+        for found in [
+            proof.trace_caps.len(),
+            proof.quotient_polys_caps.len(),
+            proof.openings.len(),
+            proof.global_values.len(),
+        ] {
+            ProofShapeError::check(ProofShapeError::NumBatchEntries, num_starks, found)?;
         }
-        ensure!(quotient_polys_cap.height() == cap_height);
-        ensure!(global_values.len() == stark.air().num_global_values());
-        ensure!(local_values.len() == stark.air().num_columns());
-        ensure!(next_values.len() == stark.air().num_columns());
-        ensure!(quotient_polys.len() == stark.num_quotient_polys(config));
 
-        Ok(())
+        let cap_height = config.fri_config.cap_height;
+        for (((stark, trace_caps), openings), global_values) in starks
+            .iter()
+            .zip(proof.trace_caps.iter())
+            .zip(proof.openings.iter())
+            .zip(proof.global_values.iter())
+        {
+            let air = stark.air();
+            ProofShapeError::check(
+                ProofShapeError::NumTraceCaps,
+                air.num_rounds(),
+                trace_caps.len(),
+            )?;
+            for cap in trace_caps.iter() {
+                ProofShapeError::check(ProofShapeError::CapHeight, cap_height, cap.height())?;
+            }
+            ProofShapeError::check(
+                ProofShapeError::NumGlobalValues,
+                air.num_global_values(),
+                global_values.len(),
+            )?;
+            ProofShapeError::check(
+                ProofShapeError::NumLocalValues,
+                air.num_columns(),
+                openings.local_values.len(),
+            )?;
+            ProofShapeError::check(
+                ProofShapeError::NumNextValues,
+                stark.next_columns().len(),
+                openings.next_values.len(),
+            )?;
+            openings.validate_quotient_chunks(stark, config)?;
+            // Extra opening points are not supported in batch proofs.
+            ProofShapeError::check(
+                ProofShapeError::NumExtraOpenings,
+                0,
+                openings.extra_openings.len(),
+            )?;
+        }
+        for cap in proof.quotient_polys_caps.iter() {
+            ProofShapeError::check(ProofShapeError::CapHeight, cap_height, cap.height())?;
+        }
+
+        let challenges = proof.get_challenges(config, starks, public_inputs);
+
+        for ((((stark, openings), public_inputs), global_values), stark_betas) in starks
+            .iter()
+            .zip(proof.openings.iter())
+            .zip(public_inputs.iter())
+            .zip(proof.global_values.iter())
+            .zip(challenges.stark_betas.iter())
+        {
+            Self::check_constraints_at_zeta(
+                config,
+                stark,
+                openings,
+                public_inputs,
+                global_values,
+                &challenges.stark_alphas,
+                stark_betas,
+                challenges.stark_zeta,
+            )?;
+        }
+
+        let merkle_caps = proof
+            .trace_caps
+            .into_iter()
+            .zip(proof.quotient_polys_caps)
+            .flat_map(|(trace_caps, quotient_polys_cap)| {
+                trace_caps.into_iter().chain(once(quotient_polys_cap))
+            })
+            .collect::<Vec<_>>();
+
+        verify_fri_proof::<F, C::GenericConfig, D>(
+            &Starky::batch_fri_instance(
+                starks,
+                challenges.stark_zeta,
+                F::primitive_root_of_unity(config.degree_bits),
+                config,
+            ),
+            &BatchStarkProof::<F, C, D>::fri_openings(&proof.openings),
+            &challenges.fri_challenges,
+            &merkle_caps,
+            &proof.opening_proof,
+            &config.fri_params(),
+        )
+        .map_err(|e| VerificationError::InvalidFriProof(e.to_string()))
     }
 
-    /// Evaluate the Lagrange polynomials `L_0` and `L_(n-1)` at a point `x`.
-    /// `L_0(x) = (x^n - 1)/(n * (x - 1))`
-    /// `L_(n-1)(x) = (x^n - 1)/(n * (g * x - 1))`, with `g` the first element of the subgroup.
-    fn eval_l_0_and_l_last<FE: Plonky2Field>(log_n: usize, x: FE) -> (FE, FE) {
-        let n = FE::from_canonical_usize(1 << log_n);
-        let g = FE::primitive_root_of_unity(log_n);
-        let z_x = x.exp_power_of_2(log_n) - FE::ONE;
-        let invs = FE::batch_multiplicative_inverse(&[n * (x - FE::ONE), n * (g * x - FE::ONE)]);
+    /// Verifies partial proofs of `starks` aggregated by `StarkyProver::aggregate`.
+    pub fn verify_aggregated<A>(
+        config: &StarkyConfig<C, D>,
+        starks: &[&Starky<A>],
+        proof: &AggregatedStarkProof<F, C, D>,
+        public_inputs: &[&[F]],
+    ) -> Result<(), VerificationError>
+    where
+        A: StarkyAir<F, D>,
+    {
+        if starks.is_empty() {
+            return Err(VerificationError::EmptyBatch);
+        }
+        if public_inputs.len() != starks.len() {
+            return Err(VerificationError::InputLengthMismatch);
+        }
+        ProofShapeError::check(
+            ProofShapeError::NumBatchEntries,
+            starks.len(),
+            proof.num_proofs(),
+        )?;
 
-        (z_x * invs[0], z_x * invs[1])
+        let mut zetas = Vec::with_capacity(starks.len());
+        for ((stark, partial_proof), public_inputs) in
+            starks.iter().zip(proof.proofs.iter()).zip(public_inputs)
+        {
+            partial_proof.validate_shape(stark, config)?;
+            let challenges = partial_proof.get_challenges(config, stark, public_inputs);
+            Self::check_constraints_at_zeta(
+                config,
+                stark,
+                &partial_proof.openings,
+                public_inputs,
+                &partial_proof.global_values,
+                &challenges.stark_alphas,
+                &challenges.stark_betas,
+                challenges.stark_zeta,
+            )?;
+            zetas.push(challenges.stark_zeta);
+        }
+
+        let merkle_caps = proof
+            .proofs
+            .iter()
+            .flat_map(|partial_proof| {
+                partial_proof
+                    .trace_caps
+                    .iter()
+                    .chain(once(&partial_proof.quotient_polys_cap))
+                    .cloned()
+            })
+            .collect::<Vec<_>>();
+
+        verify_fri_proof::<F, C::GenericConfig, D>(
+            &AggregatedStarkProof::<F, C, D>::fri_instance(config, starks, &zetas),
+            &proof.fri_openings(),
+            &proof.fri_challenges(config, public_inputs, &zetas),
+            &merkle_caps,
+            &proof.opening_proof,
+            &config.fri_params(),
+        )
+        .map_err(|e| VerificationError::InvalidFriProof(e.to_string()))
+    }
+
+    pub fn validate_proof_shape<A: RAirData>(
+        config: &StarkyConfig<C, D>,
+        stark: &Starky<A>,
+        proof: &AirProof<F, C, D>,
+        global_values: &[F],
+    ) -> Result<(), VerificationError> {
+        // The shape of the FRI query rounds will be checked in the FRI verifier (see
+        // validate_fri_proof_shape).
+        proof.validate_shape(stark, config, global_values)?;
+        Ok(())
+    }
+
+    /// Checks that the degree of the committed polynomials recovered from the Merkle proofs of
+    /// `proof` matches the configuration, and returns it.
+    fn check_degree_bits(
+        config: &StarkyConfig<C, D>,
+        proof: &StarkProof<F, C, D>,
+    ) -> Result<usize, VerificationError> {
+        let expected = config.fri_degree_bits();
+        let found = proof.recover_degree_bits(config);
+        if found != expected {
+            return Err(VerificationError::DegreeBitsMismatch { expected, found });
+        }
+        Ok(found)
     }
 
     pub fn verify_with_challenges_circuit<A>(
@@ -228,13 +1095,24 @@ where
         challenges: StarkProofChallengesTarget<D>,
     ) where
         A: Plonky2Air<F, D>,
+        C::Hasher: AlgebraicHasher<F>,
     {
         let StarkOpeningSetTarget {
             local_values,
             next_values,
-            quotient_polys,
+            quotient_chunks,
+            ..
         } = &proof.openings;
 
+        let num_quotient_chunks = stark.num_quotient_chunks(config);
+        assert!(
+            log2_ceil(num_quotient_chunks) <= config.fri_config.rate_bits,
+            "Constraints of degree {} are not supported with rate bits {}",
+            stark.air().constraint_degree(),
+            config.fri_config.rate_bits
+        );
+        assert_eq!(quotient_chunks.len(), config.num_challenges);
+
         let degree_bits = config.degree_bits;
 
         let one = builder.one_extension();
@@ -284,10 +1162,12 @@ where
             .map(|x| builder.convert_to_ext(*x))
             .collect::<Vec<_>>();
 
+        let zero = builder.zero_extension();
+        let next_values = stark.expand_next_values(next_values, zero);
         let mut parser = RecursiveStarkParser {
             builder,
             local_vars: local_values,
-            next_vars: next_values,
+            next_vars: &next_values,
             global_vars: &global_vals_ext,
             public_vars: &public_inputs_ext,
             challenges: &challenges_ext,
@@ -299,12 +1179,17 @@ where
         let vanishing_polys_zeta = consumer.accumulators();
 
         // Check each polynomial identity, of the form `vanishing(x) = Z_H(x) quotient(x)`, at zeta.
-        let mut scale = ReducingFactorTarget::new(zeta_pow_deg);
-        let quotient_degree_factor = stark.air().quotient_degree_factor();
-        for (i, chunk) in quotient_polys.chunks(quotient_degree_factor).enumerate() {
-            let recombined_quotient = scale.reduce(chunk, builder);
+        let zeta_pow_chunk_deg = if config.zero_knowledge {
+            builder.square_extension(zeta_pow_deg)
+        } else {
+            zeta_pow_deg
+        };
+        let mut scale = ReducingFactorTarget::new(zeta_pow_chunk_deg);
+        for (vanishing_poly_zeta, chunks) in vanishing_polys_zeta.iter().zip_eq(quotient_chunks) {
+            assert_eq!(chunks.len(), num_quotient_chunks);
+            let recombined_quotient = scale.reduce(chunks, builder);
             let computed_vanishing_poly = builder.mul_extension(z_h_zeta, recombined_quotient);
-            builder.connect_extension(vanishing_polys_zeta[i], computed_vanishing_poly);
+            builder.connect_extension(*vanishing_poly_zeta, computed_vanishing_poly);
         }
 
         let merkle_caps = proof
@@ -314,10 +1199,34 @@ where
             .chain(once(proof.quotient_polys_cap.clone()))
             .collect::<Vec<_>>();
 
+        // The FRI argument can not open the polynomials at points of the LDE domain, and every
+        // extra point is opened once, so for the extra points `x`, the product of `x^n - 1`, where
+        // `n` is the size of the LDE domain, and of the pairwise differences must be invertible.
+        let extra_points = proof.openings.extra_points();
+        if !extra_points.is_empty() {
+            let lde_bits = config.fri_params().lde_bits();
+            let shift_inv = builder
+                .constant_extension(F::Extension::from_basefield(F::coset_shift().inverse()));
+            let one = builder.one_extension();
+            let mut product = one;
+            for (i, &point) in extra_points.iter().enumerate() {
+                let shifted = builder.mul_extension(point, shift_inv);
+                let shifted_pow = builder.exp_power_of_2_extension(shifted, lde_bits);
+                let vanishing = builder.sub_extension(shifted_pow, one);
+                product = builder.mul_extension(product, vanishing);
+                for &other in extra_points[..i].iter() {
+                    let difference = builder.sub_extension(point, other);
+                    product = builder.mul_extension(product, difference);
+                }
+            }
+            builder.inverse_extension(product);
+        }
+
         let fri_instance = stark.fri_instance_target(
             builder,
             challenges.stark_zeta,
             F::primitive_root_of_unity(degree_bits),
+            &extra_points,
             config,
         );
         builder.verify_fri_proof::<C::GenericConfig>(
@@ -338,11 +1247,14 @@ where
         public_inputs: &[Target],
     ) where
         A: Plonky2Air<F, D>,
+        C::Hasher: AlgebraicHasher<F>,
     {
+        Self::verify_public_inputs_commitment_circuit(builder, config, proof, public_inputs);
         let challenges = proof.get_challenges_target(builder, config, public_inputs, stark);
         let StarkProofTarget {
             air_proof,
             global_values,
+            ..
         } = proof;
         Self::verify_with_challenges_circuit(
             builder,
@@ -355,6 +1267,25 @@ where
         )
     }
 
+    /// Connects the commitment of the proof to the hash of `public_inputs`, if the configuration
+    /// commits to the public inputs.
+    pub fn verify_public_inputs_commitment_circuit(
+        builder: &mut CircuitBuilder<F, D>,
+        config: &StarkyConfig<C, D>,
+        proof: &StarkProofTarget<D>,
+        public_inputs: &[Target],
+    ) {
+        assert_eq!(
+            proof.public_inputs_commitment.is_some(),
+            config.commit_public_inputs,
+            "The proof target does not match the public inputs commitment of the configuration"
+        );
+        if let Some(commitment) = proof.public_inputs_commitment {
+            let hash = builder.hash_n_to_hash_no_pad::<C::InnerHasher>(public_inputs.to_vec());
+            builder.connect_hashes(hash, commitment);
+        }
+    }
+
     fn eval_l_0_and_l_last_circuit(
         builder: &mut CircuitBuilder<F, D>,
         log_n: usize,
@@ -384,18 +1315,34 @@ pub fn add_virtual_air_proof<
     builder: &mut CircuitBuilder<F, D>,
     stark: &Starky<A>,
     config: &StarkyConfig<C, D>,
+) -> AirProofTarget<D> {
+    add_virtual_air_proof_with_extra_points(builder, stark, config, 0)
+}
+
+/// Virtual targets of a proof whose trace polynomials are opened at `num_extra_points` distinct
+/// points in addition to `zeta` and `zeta * g`.
+pub fn add_virtual_air_proof_with_extra_points<
+    F: RichField + Extendable<D>,
+    A: Plonky2Air<F, D>,
+    C: CurtaConfig<D, F = F>,
+    const D: usize,
+>(
+    builder: &mut CircuitBuilder<F, D>,
+    stark: &Starky<A>,
+    config: &StarkyConfig<C, D>,
+    num_extra_points: usize,
 ) -> AirProofTarget<D> {
     let fri_params = config.fri_params();
     let cap_height = fri_params.config.cap_height;
+    let salt = salt_size(fri_params.hiding);
 
     let num_leaves_per_oracle = stark
         .air()
         .round_data()
         .into_iter()
         .map(|x| x.num_columns)
-        .chain(once(
-            stark.air().quotient_degree_factor() * config.num_challenges,
-        ))
+        .chain(once(stark.num_quotient_polys(config)))
+        .map(|num_leaves| num_leaves + salt)
         .collect::<Vec<_>>();
 
     let num_rounds = stark.air().num_rounds();
@@ -406,7 +1353,7 @@ pub fn add_virtual_air_proof<
     AirProofTarget {
         trace_caps,
         quotient_polys_cap: builder.add_virtual_cap(cap_height),
-        openings: add_stark_opening_set_target(builder, stark, config),
+        openings: add_stark_opening_set_target(builder, stark, config, num_extra_points),
         opening_proof: builder.add_virtual_fri_proof(&num_leaves_per_oracle, &fri_params),
     }
 }
@@ -420,13 +1367,33 @@ pub fn add_virtual_stark_proof<
     builder: &mut CircuitBuilder<F, D>,
     stark: &Starky<A>,
     config: &StarkyConfig<C, D>,
+) -> StarkProofTarget<D> {
+    add_virtual_stark_proof_with_extra_points(builder, stark, config, 0)
+}
+
+/// Virtual targets of a proof generated by `StarkyProver::prove_with_extra_points` with
+/// `num_extra_points` distinct extra points.
+pub fn add_virtual_stark_proof_with_extra_points<
+    F: RichField + Extendable<D>,
+    A: Plonky2Air<F, D>,
+    C: CurtaConfig<D, F = F>,
+    const D: usize,
+>(
+    builder: &mut CircuitBuilder<F, D>,
+    stark: &Starky<A>,
+    config: &StarkyConfig<C, D>,
+    num_extra_points: usize,
 ) -> StarkProofTarget<D> {
     let num_global_values = stark.air().num_global_values();
     let global_values_target = builder.add_virtual_targets(num_global_values);
-    let air_proof = add_virtual_air_proof(builder, stark, config);
+    let air_proof =
+        add_virtual_air_proof_with_extra_points(builder, stark, config, num_extra_points);
     StarkProofTarget {
         air_proof,
         global_values: global_values_target,
+        public_inputs_commitment: config
+            .commit_public_inputs
+            .then(|| builder.add_virtual_hash()),
     }
 }
 
@@ -439,13 +1406,22 @@ pub(crate) fn add_stark_opening_set_target<
     builder: &mut CircuitBuilder<F, D>,
     stark: &Starky<A>,
     config: &StarkyConfig<C, D>,
+    num_extra_points: usize,
 ) -> StarkOpeningSetTarget<D> {
-    let num_challenges = config.num_challenges;
+    let num_quotient_chunks = stark.num_quotient_chunks(config);
+    let num_columns = stark.air().num_columns();
     StarkOpeningSetTarget {
-        local_values: builder.add_virtual_extension_targets(stark.air().num_columns()),
-        next_values: builder.add_virtual_extension_targets(stark.air().num_columns()),
-        quotient_polys: builder
-            .add_virtual_extension_targets(stark.air().quotient_degree_factor() * num_challenges),
+        local_values: builder.add_virtual_extension_targets(num_columns),
+        next_values: builder.add_virtual_extension_targets(stark.next_columns().len()),
+        quotient_chunks: (0..config.num_challenges)
+            .map(|_| builder.add_virtual_extension_targets(num_quotient_chunks))
+            .collect(),
+        extra_openings: (0..num_extra_points)
+            .map(|_| PointOpeningTarget {
+                point: builder.add_virtual_extension_target(),
+                values: builder.add_virtual_extension_targets(num_columns),
+            })
+            .collect(),
     }
 }
 
@@ -468,6 +1444,18 @@ where
     }
     witness.set_cap_target(&proof_target.quotient_polys_cap, &proof.quotient_polys_cap)?;
 
+    ensure!(
+        proof_target.openings.extra_openings.len() == proof.openings.extra_openings.len(),
+        "Number of extra opening points does not match"
+    );
+    for (target, opening) in proof_target
+        .openings
+        .extra_openings
+        .iter()
+        .zip(proof.openings.extra_openings.iter())
+    {
+        witness.set_extension_target(target.point, opening.point)?;
+    }
     witness.set_fri_openings(
         &proof_target.openings.to_fri_openings(),
         &proof.openings.to_fri_openings(),
@@ -496,5 +1484,45 @@ where
         witness.set_target(*target, *value)?;
     }
 
-    Ok(())
+    set_public_inputs_commitment_target(witness, proof_target, proof)
+}
+
+/// Sets `proof` in the witness of `proof_target`, after checking that the proof has the shape of
+/// the target.
+///
+/// Unlike `set_stark_proof_target`, a proof of a different shape is rejected with an error which
+/// names the mismatched part of the proof, such as the number of columns.
+pub fn set_proof_target<F, C: CurtaConfig<D, F = F>, W, const D: usize>(
+    witness: &mut W,
+    proof_target: &StarkProofTarget<D>,
+    proof: &StarkProof<F, C, D>,
+) -> Result<()>
+where
+    F: RichField + Extendable<D>,
+    C::Hasher: AlgebraicHasher<F>,
+    W: WitnessWrite<F>,
+{
+    proof_target.check_proof_shape(proof)?;
+    set_stark_proof_target(witness, proof_target, proof)
+}
+
+pub(crate) fn set_public_inputs_commitment_target<F, C: CurtaConfig<D, F = F>, W, const D: usize>(
+    witness: &mut W,
+    proof_target: &StarkProofTarget<D>,
+    proof: &StarkProof<F, C, D>,
+) -> Result<()>
+where
+    F: RichField + Extendable<D>,
+    W: WitnessWrite<F>,
+{
+    match (
+        proof_target.public_inputs_commitment,
+        proof.public_inputs_commitment,
+    ) {
+        (Some(target), Some(commitment)) => witness.set_hash_target(target, commitment),
+        (None, None) => Ok(()),
+        _ => Err(anyhow::anyhow!(
+            "Public inputs commitment of the proof does not match the target"
+        )),
+    }
 }
@@ -0,0 +1,121 @@
+//! Verification of proofs generated with older layouts of an AIR.
+//!
+//! When a chip changes, provers are not all upgraded at once. A verifier can register every
+//! layout of the chip that it still accepts in `StarkyVersions`, and the layout of each proof is
+//! then selected by the AIR identifier recorded in its `ProofEnvelope`.
+
+use core::marker::PhantomData;
+
+use serde::Serialize;
+
+use super::config::CurtaConfig;
+use super::envelope::ProofEnvelope;
+use super::Starky;
+
+/// The layouts of the same logical chip accepted by a verifier of proofs with configuration `C`.
+///
+/// The AIR identifier of each layout is computed when the layout is registered.
+#[derive(Debug, Clone)]
+pub struct StarkyVersions<A, C, const D: usize> {
+    starks: Vec<(Vec<u8>, Starky<A>)>,
+    _marker: PhantomData<C>,
+}
+
+impl<A: Serialize, C: CurtaConfig<D>, const D: usize> StarkyVersions<A, C, D> {
+    pub fn new() -> Self {
+        Self {
+            starks: Vec::new(),
+            _marker: PhantomData,
+        }
+    }
+
+    /// Registers a layout of the chip.
+    pub fn register(&mut self, stark: Starky<A>) {
+        let air_id = ProofEnvelope::<C::F, C, D>::air_id(&stark);
+        self.starks.push((air_id, stark));
+    }
+
+    pub fn len(&self) -> usize {
+        self.starks.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.starks.is_empty()
+    }
+
+    /// The registered layout with the AIR identifier `air_id`, if any.
+    pub fn find(&self, air_id: &[u8]) -> Option<&Starky<A>> {
+        self.starks
+            .iter()
+            .find(|(id, _)| id == air_id)
+            .map(|(_, stark)| stark)
+    }
+}
+
+impl<A: Serialize, C: CurtaConfig<D>, const D: usize> Default for StarkyVersions<A, C, D> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use plonky2::field::goldilocks_field::GoldilocksField;
+
+    use super::*;
+    use crate::air::fibonacci::FibonacciAir;
+    use crate::math::prelude::*;
+    use crate::plonky2::stark::config::{
+        CurtaPoseidonGoldilocksConfig, PoseidonGoldilocksStarkConfig,
+    };
+    use crate::plonky2::stark::prover::StarkyProver;
+    use crate::plonky2::stark::verifier::{StarkyVerifier, VerificationError};
+    use crate::trace::generator::ConstantGenerator;
+
+    #[test]
+    fn test_starky_versions() {
+        type F = GoldilocksField;
+        type SC = PoseidonGoldilocksStarkConfig;
+        type Envelope = ProofEnvelope<F, CurtaPoseidonGoldilocksConfig, 2>;
+
+        let num_rows = 1 << 5usize;
+        let public_inputs = [
+            F::ZERO,
+            F::ONE,
+            FibonacciAir::fibonacci(num_rows - 1, F::ZERO, F::ONE),
+        ];
+        let trace_generator =
+            ConstantGenerator::new(FibonacciAir::generate_trace(F::ZERO, F::ONE, num_rows));
+        let config = SC::standard_fast_config(num_rows);
+
+        // An old layout opening all the columns at the next row, and the current one.
+        let old_stark = Starky::<FibonacciAir>::new(FibonacciAir::new());
+        let new_stark = Starky::new(FibonacciAir::new()).with_compressed_openings::<F>();
+
+        let mut versions = StarkyVersions::default();
+        versions.register(new_stark.clone());
+        assert!(versions.find(&Envelope::air_id(&new_stark)).is_some());
+        assert!(versions.find(&Envelope::air_id(&old_stark)).is_none());
+        let old_proof =
+            StarkyProver::prove(&config, &old_stark, &trace_generator, &public_inputs).unwrap();
+        let old_envelope = Envelope::new(&config, &old_stark, old_proof);
+        assert_eq!(
+            StarkyVerifier::verify_versioned(
+                &config,
+                &versions,
+                old_envelope.clone(),
+                &public_inputs
+            ),
+            Err(VerificationError::AirIdMismatch)
+        );
+
+        // Once the old layout is registered, proofs of both layouts are accepted.
+        versions.register(old_stark);
+        assert_eq!(versions.len(), 2);
+        StarkyVerifier::verify_versioned(&config, &versions, old_envelope, &public_inputs).unwrap();
+        let new_proof =
+            StarkyProver::prove(&config, &new_stark, &trace_generator, &public_inputs).unwrap();
+        let new_envelope = Envelope::new(&config, &new_stark, new_proof);
+        StarkyVerifier::verify_versioned(&config, &versions, new_envelope, &public_inputs).unwrap();
+    }
+}
@@ -0,0 +1,202 @@
+//! Barycentric evaluation of polynomials given by their values on a multiplicative coset.
+//!
+//! On a coset `s * H` of a subgroup `H` of order `n`, the vanishing polynomial is
+//! `Z(X) = X^n - s^n` and the barycentric weight of the point `x_i = s * g^i` is
+//! `w_i = x_i / (n * s^n)`. The Lagrange polynomials are then `L_i(X) = Z(X) * w_i / (X - x_i)`,
+//! so evaluating any number of them, or an interpolant, outside of the coset costs a single batch
+//! inversion of the denominators `n * s^n * (X - x_i)`.
+
+use alloc::vec::Vec;
+
+use crate::math::prelude::*;
+
+/// The coset `shift * <generator>` of order `2^log_n`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BarycentricCoset<F> {
+    log_n: usize,
+    shift: F,
+    generator: F,
+    /// `n * shift^n`, the inverse of the weights up to the point.
+    weight_denominator: F,
+}
+
+impl<F: Field> BarycentricCoset<F> {
+    /// The coset `shift * H`, where `H` is the subgroup of order `2^log_n`.
+    pub fn new(log_n: usize, shift: F) -> Self {
+        let n = F::from_canonical_usize(1 << log_n);
+        Self {
+            log_n,
+            shift,
+            generator: F::primitive_root_of_unity(log_n),
+            weight_denominator: n * shift.two_pow(log_n),
+        }
+    }
+
+    /// The subgroup of order `2^log_n`.
+    pub fn subgroup(log_n: usize) -> Self {
+        Self::new(log_n, F::ONE)
+    }
+
+    /// The number of points of the coset.
+    #[inline]
+    pub fn size(&self) -> usize {
+        1 << self.log_n
+    }
+
+    /// The `i`-th point, `shift * g^i`.
+    #[inline]
+    pub fn point(&self, i: usize) -> F {
+        self.shift * self.generator.pow(i as u64)
+    }
+
+    /// An iterator over the points of the coset, in order.
+    pub fn points(&self) -> impl Iterator<Item = F> + '_ {
+        self.generator
+            .powers()
+            .take(self.size())
+            .map(|g_i| self.shift * g_i)
+    }
+
+    /// The barycentric weights of all the points, with a single inversion.
+    pub fn weights(&self) -> Vec<F> {
+        let inv = self.weight_denominator.inverse();
+        self.points().map(|x_i| x_i * inv).collect()
+    }
+
+    /// The vanishing polynomial of the coset at `x`.
+    #[inline]
+    pub fn vanishing(&self, x: F) -> F {
+        x.two_pow(self.log_n) - self.shift.two_pow(self.log_n)
+    }
+
+    /// The Lagrange polynomials of the points `indices` at `x`, with a single batch inversion.
+    pub fn lagrange_basis(&self, indices: &[usize], x: F) -> Vec<F> {
+        let z_x = self.vanishing(x);
+        let points = indices.iter().map(|&i| self.point(i)).collect::<Vec<_>>();
+        if z_x == F::ZERO {
+            // `x` is a point of the coset, where the Lagrange polynomials are indicators.
+            return points
+                .iter()
+                .map(|&x_i| if x_i == x { F::ONE } else { F::ZERO })
+                .collect();
+        }
+        let denominators = points
+            .iter()
+            .map(|&x_i| self.weight_denominator * (x - x_i))
+            .collect::<Vec<_>>();
+        F::batch_multiplicative_inverse(&denominators)
+            .into_iter()
+            .zip(points)
+            .map(|(inv, x_i)| z_x * x_i * inv)
+            .collect()
+    }
+
+    /// Evaluates at `x` the polynomial of degree less than `n` whose values on the points of the
+    /// coset are `values`, in `O(n)` operations and a single batch inversion.
+    ///
+    /// Panics if the number of values is not the size of the coset.
+    pub fn evaluate(&self, values: &[F], x: F) -> F {
+        assert_eq!(
+            values.len(),
+            self.size(),
+            "There must be one value for each point of the coset"
+        );
+        let z_x = self.vanishing(x);
+        let points = self.points().collect::<Vec<_>>();
+        if z_x == F::ZERO {
+            let i = points.iter().position(|&x_i| x_i == x).unwrap();
+            return values[i];
+        }
+        let denominators = points
+            .iter()
+            .map(|&x_i| self.weight_denominator * (x - x_i))
+            .collect::<Vec<_>>();
+        let sum = F::batch_multiplicative_inverse(&denominators)
+            .into_iter()
+            .zip(points)
+            .zip(values)
+            .map(|((inv, x_i), &value)| value * x_i * inv)
+            .sum::<F>();
+        z_x * sum
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use plonky2::field::goldilocks_field::GoldilocksField;
+
+    use super::*;
+
+    type F = GoldilocksField;
+
+    fn eval_coefficients(coefficients: &[F], x: F) -> F {
+        coefficients
+            .iter()
+            .rev()
+            .fold(F::ZERO, |acc, &c| acc * x + c)
+    }
+
+    #[test]
+    fn test_barycentric_evaluate() {
+        for log_n in [0, 1, 4] {
+            let coset = BarycentricCoset::new(log_n, F::rand());
+            let coefficients = F::rand_vec(coset.size());
+            let values = coset
+                .points()
+                .map(|x_i| eval_coefficients(&coefficients, x_i))
+                .collect::<Vec<_>>();
+
+            let x = F::rand();
+            assert_eq!(
+                coset.evaluate(&values, x),
+                eval_coefficients(&coefficients, x)
+            );
+            for (i, x_i) in coset.points().enumerate() {
+                assert_eq!(coset.point(i), x_i);
+                assert_eq!(coset.vanishing(x_i), F::ZERO);
+                assert_eq!(coset.evaluate(&values, x_i), values[i]);
+            }
+        }
+    }
+
+    #[test]
+    fn test_barycentric_weights() {
+        let coset = BarycentricCoset::new(3, F::rand());
+        let points = coset.points().collect::<Vec<_>>();
+        for (i, weight) in coset.weights().into_iter().enumerate() {
+            let product = points
+                .iter()
+                .enumerate()
+                .filter(|(j, _)| *j != i)
+                .map(|(_, &x_j)| points[i] - x_j)
+                .product::<F>();
+            assert_eq!(weight * product, F::ONE);
+        }
+    }
+
+    #[test]
+    fn test_lagrange_basis() {
+        let log_n = 4;
+        let subgroup = BarycentricCoset::<F>::subgroup(log_n);
+        let n = subgroup.size();
+        let x = F::rand();
+        let indices = [0, 5, n - 1];
+        let basis = subgroup.lagrange_basis(&indices, x);
+        for (&i, l_i) in indices.iter().zip(basis) {
+            let mut indicator = vec![F::ZERO; n];
+            indicator[i] = F::ONE;
+            assert_eq!(l_i, subgroup.evaluate(&indicator, x));
+        }
+
+        // `L_0(x) = Z(x) / (n * (x - 1))`.
+        let n_f = F::from_canonical_usize(n);
+        let l_0 = subgroup.vanishing(x) / (n_f * (x - F::ONE));
+        assert_eq!(subgroup.lagrange_basis(&[0], x), vec![l_0]);
+
+        let g = subgroup.point(1);
+        assert_eq!(
+            subgroup.lagrange_basis(&[0, 1, 2], g),
+            vec![F::ZERO, F::ONE, F::ZERO]
+        );
+    }
+}
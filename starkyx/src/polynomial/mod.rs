@@ -1,6 +1,9 @@
+pub mod barycentric;
 pub mod ops;
 pub mod parser;
 
+use alloc::vec;
+use alloc::vec::Vec;
 use core::fmt::Debug;
 use core::iter;
 use core::ops::{Add, AddAssign, Div, Mul, MulAssign, Neg, Sub};
@@ -8,8 +11,11 @@ use core::ops::{Add, AddAssign, Div, Mul, MulAssign, Neg, Sub};
 use num::BigUint;
 
 use self::ops::PolynomialOps;
+#[cfg(feature = "bigint")]
 use crate::chip::field::parameters::FieldParameters;
-use crate::chip::utils::{bigint_into_u16_digits, biguint_to_16_digits_field};
+#[cfg(feature = "bigint")]
+use crate::chip::utils::bigint_into_u16_digits;
+use crate::chip::utils::biguint_to_16_digits_field;
 use crate::math::prelude::*;
 
 /// A wrapper around a vector of elements to represent a polynomial.
@@ -281,6 +287,7 @@ pub fn get_powers<T>(x: T, one: T) -> PowersIter<T> {
     }
 }
 
+#[cfg(feature = "bigint")]
 pub fn to_u16_le_limbs_polynomial<F: Field, P: FieldParameters>(x: &BigUint) -> Polynomial<F> {
     let num_limbs = bigint_into_u16_digits(x, P::NB_LIMBS)
         .iter()
@@ -1,3 +1,5 @@
+use alloc::vec;
+
 use itertools::Itertools;
 
 use super::Polynomial;
@@ -0,0 +1,158 @@
+//! Column-major traces of packed values.
+//!
+//! The evaluation of the constraints reads all the columns of a few rows at a time. In row-major
+//! storage, the values of a column at consecutive rows are a full row apart, so packing them into
+//! a vector register takes a gather and misses the cache on wide tables. A [`ColumnTraceView`]
+//! stores every column contiguously as packed values, aligned as the packed type, so that the
+//! values of `P::WIDTH` consecutive rows of a column are a single aligned load.
+
+use core::slice::ChunksExact;
+
+use plonky2::field::packed::PackedField;
+
+use super::AirTrace;
+
+/// The columns of a trace, each stored contiguously as packed values of `P::WIDTH` rows.
+#[derive(Debug, Clone)]
+pub struct ColumnTraceView<P> {
+    values: Vec<P>,
+    width: usize,
+    height: usize,
+}
+
+impl<P: PackedField> ColumnTraceView<P> {
+    /// Transposes the `height` rows `row(0), .., row(height - 1)`.
+    ///
+    /// Panics if the height is not a multiple of `P::WIDTH` or if the rows have different lengths.
+    pub fn from_rows<R: AsRef<[P::Scalar]>>(
+        height: usize,
+        mut row: impl FnMut(usize) -> R,
+    ) -> Self {
+        assert_eq!(
+            height % P::WIDTH,
+            0,
+            "The height must be a multiple of the packing width {}",
+            P::WIDTH
+        );
+        if height == 0 {
+            return Self {
+                values: Vec::new(),
+                width: 0,
+                height,
+            };
+        }
+        let packed_height = height / P::WIDTH;
+        let first_row = row(0);
+        let width = first_row.as_ref().len();
+        let mut values = vec![P::ZEROS; width * packed_height];
+        let mut write_row = |r: usize, row: &[P::Scalar]| {
+            assert_eq!(row.len(), width, "Row {r} has the wrong length");
+            let (i, lane) = (r / P::WIDTH, r % P::WIDTH);
+            for (c, &value) in row.iter().enumerate() {
+                values[c * packed_height + i].as_slice_mut()[lane] = value;
+            }
+        };
+        write_row(0, first_row.as_ref());
+        for r in 1..height {
+            write_row(r, row(r).as_ref());
+        }
+        Self {
+            values,
+            width,
+            height,
+        }
+    }
+
+    /// Transposes the rows of `trace`.
+    pub fn from_trace(trace: &AirTrace<P::Scalar>) -> Self {
+        Self::from_rows(trace.height(), |r| trace.row(r))
+    }
+
+    /// The number of columns.
+    #[inline]
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    /// The number of rows.
+    #[inline]
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    /// The packed values of column `c`, whose `i`-th entry holds the rows `i * P::WIDTH` to
+    /// `(i + 1) * P::WIDTH - 1`.
+    #[inline]
+    pub fn column(&self, c: usize) -> &[P] {
+        let packed_height = self.height / P::WIDTH;
+        &self.values[c * packed_height..(c + 1) * packed_height]
+    }
+
+    /// An iterator over the packed values of the columns.
+    #[inline]
+    pub fn columns(&self) -> ChunksExact<'_, P> {
+        self.values.chunks_exact((self.height / P::WIDTH).max(1))
+    }
+
+    /// The value of column `c` at row `r`.
+    #[inline]
+    pub fn get(&self, c: usize, r: usize) -> P::Scalar {
+        self.column(c)[r / P::WIDTH].as_slice()[r % P::WIDTH]
+    }
+
+    /// The values of every column at the `P::WIDTH` rows starting from row `r`, wrapping around
+    /// the last row.
+    ///
+    /// When `r` is a multiple of `P::WIDTH`, each value is a single load from its column.
+    pub fn packed_row(&self, r: usize) -> impl Iterator<Item = P> + '_ {
+        let aligned = r % P::WIDTH == 0;
+        (0..self.width).map(move |c| {
+            if aligned {
+                self.column(c)[r / P::WIDTH]
+            } else {
+                let mut packed = P::ZEROS;
+                for (lane, value) in packed.as_slice_mut().iter_mut().enumerate() {
+                    *value = self.get(c, (r + lane) % self.height);
+                }
+                packed
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use plonky2::field::goldilocks_field::GoldilocksField;
+    use plonky2::field::packable::Packable;
+    use plonky2::field::types::Sample;
+
+    use super::*;
+
+    type F = GoldilocksField;
+    type P = <F as Packable>::Packing;
+
+    #[test]
+    fn test_column_trace_view() {
+        let width = 7;
+        let height = 16;
+        let trace = AirTrace::from_rows(F::rand_vec(width * height), width);
+        let columns = ColumnTraceView::<P>::from_trace(&trace);
+        assert_eq!(columns.width(), width);
+        assert_eq!(columns.height(), height);
+        assert_eq!(columns.columns().len(), width);
+
+        for r in 0..height {
+            for c in 0..width {
+                assert_eq!(columns.get(c, r), trace.row(r)[c]);
+            }
+            for (c, packed) in columns.packed_row(r).enumerate() {
+                for (lane, value) in packed.as_slice().iter().enumerate() {
+                    assert_eq!(*value, trace.row((r + lane) % height)[c]);
+                }
+            }
+        }
+        for column in columns.columns() {
+            assert_eq!(column.len(), height / P::WIDTH);
+        }
+    }
+}
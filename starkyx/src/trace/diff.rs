@@ -0,0 +1,172 @@
+//! Comparison of two traces of the same AIR.
+//!
+//! Diffing the traces generated before and after a change to a trace generator locates the
+//! columns whose values changed, and the first row at which each of them differs, which is
+//! usually where a regression in the witness generation starts.
+
+use core::fmt;
+
+use super::AirTrace;
+
+/// Names of ranges of columns, such as the registers of an AIR, used to report differences.
+#[derive(Debug, Clone, Default)]
+pub struct ColumnNames {
+    ranges: Vec<(String, usize, usize)>,
+    /// Ranges of columns whose values are never reported, such as secret registers.
+    secret_ranges: Vec<(usize, usize)>,
+}
+
+impl ColumnNames {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Names the columns in the range `(start, end)`, such as the range of a register.
+    pub fn insert(&mut self, name: impl Into<String>, range: (usize, usize)) {
+        let (start, end) = range;
+        assert!(start <= end, "Invalid column range {:?}", range);
+        self.ranges.push((name.into(), start, end));
+    }
+
+    /// Excludes the columns in the range `(start, end)` from the reported differences.
+    ///
+    /// The column ranges of the secret registers of a chip are given by
+    /// `AirTraceData::secret_columns`.
+    pub fn insert_secret(&mut self, range: (usize, usize)) {
+        let (start, end) = range;
+        assert!(start <= end, "Invalid column range {:?}", range);
+        self.secret_ranges.push(range);
+    }
+
+    /// Whether the values of `column` are secret.
+    pub fn is_secret(&self, column: usize) -> bool {
+        self.secret_ranges
+            .iter()
+            .any(|(start, end)| (*start..*end).contains(&column))
+    }
+
+    /// The name of `column`, with its offset in the named range if the range has more than one
+    /// column.
+    pub fn name(&self, column: usize) -> Option<String> {
+        self.ranges
+            .iter()
+            .find(|(_, start, end)| (*start..*end).contains(&column))
+            .map(|(name, start, end)| {
+                if end - start == 1 {
+                    name.clone()
+                } else {
+                    format!("{}[{}]", name, column - start)
+                }
+            })
+    }
+}
+
+/// The first cell of a column at which two traces differ.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ColumnDiff<T> {
+    pub column: usize,
+    /// The name of the column, if it was given.
+    pub name: Option<String>,
+    pub row: usize,
+    /// The value of the cell in the first trace.
+    pub left: T,
+    /// The value of the cell in the second trace.
+    pub right: T,
+}
+
+impl<T: fmt::Debug> fmt::Display for ColumnDiff<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.name {
+            Some(name) => write!(f, "column {} ({})", self.column, name)?,
+            None => write!(f, "column {}", self.column)?,
+        }
+        write!(
+            f,
+            " differs at row {}: {:?} != {:?}",
+            self.row, self.left, self.right
+        )
+    }
+}
+
+impl<T: Copy + PartialEq> AirTrace<T> {
+    /// Compares the trace with `other`, returning the first differing cell of every column that
+    /// differs, in the order of the columns.
+    ///
+    /// Only the rows which are in both traces are compared.
+    ///
+    /// # Panics
+    /// Panics if the traces do not have the same width.
+    pub fn diff(&self, other: &Self) -> Vec<ColumnDiff<T>> {
+        self.diff_with_names(other, &ColumnNames::new())
+    }
+
+    /// Like `diff`, naming the differing columns with `names` and skipping its secret columns.
+    pub fn diff_with_names(&self, other: &Self, names: &ColumnNames) -> Vec<ColumnDiff<T>> {
+        assert_eq!(
+            self.width, other.width,
+            "Traces of different widths can not be compared"
+        );
+        let mut first_diffs: Vec<Option<ColumnDiff<T>>> = vec![None; self.width];
+        for (row, (left_row, right_row)) in self.rows().zip(other.rows()).enumerate() {
+            for (column, (&left, &right)) in left_row.iter().zip(right_row).enumerate() {
+                if left != right && first_diffs[column].is_none() && !names.is_secret(column) {
+                    first_diffs[column] = Some(ColumnDiff {
+                        column,
+                        name: names.name(column),
+                        row,
+                        left,
+                        right,
+                    });
+                }
+            }
+        }
+        first_diffs.into_iter().flatten().collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use plonky2::field::goldilocks_field::GoldilocksField;
+
+    use super::*;
+    use crate::air::fibonacci::FibonacciAir;
+    use crate::math::prelude::*;
+
+    #[test]
+    fn test_trace_diff() {
+        type F = GoldilocksField;
+
+        let num_rows = 1 << 8;
+        let trace = FibonacciAir::generate_trace(F::ZERO, F::ONE, num_rows);
+        assert!(trace.diff(&trace).is_empty());
+
+        // Changing the second initial value changes the second column at the first row and the
+        // first column at the next one.
+        let other = FibonacciAir::generate_trace(F::ZERO, F::TWO, num_rows);
+        let diffs = other.diff(&trace);
+        assert_eq!(diffs.len(), 2);
+        assert_eq!(diffs[0].column, 0);
+        assert_eq!(diffs[0].row, 1);
+        assert_eq!(diffs[1].column, 1);
+        assert_eq!(diffs[1].row, 0);
+        assert_eq!((diffs[1].left, diffs[1].right), (F::TWO, F::ONE));
+
+        let mut names = ColumnNames::new();
+        names.insert("x", (0, 1));
+        names.insert("y", (1, 2));
+        let mut corrupted = trace.clone();
+        corrupted.row_mut(100)[1] += F::ONE;
+        corrupted.row_mut(200)[1] += F::ONE;
+        let diffs = trace.diff_with_names(&corrupted, &names);
+        assert_eq!(diffs.len(), 1);
+        assert_eq!(diffs[0].name.as_deref(), Some("y"));
+        assert_eq!(diffs[0].row, 100);
+        assert!(diffs[0]
+            .to_string()
+            .starts_with("column 1 (y) differs at row 100"));
+
+        // Differences in secret columns are not reported.
+        names.insert_secret((1, 2));
+        assert!(trace.diff_with_names(&corrupted, &names).is_empty());
+    }
+}
@@ -19,6 +19,16 @@ pub trait TraceGenerator<F, A>:
         global_values: &mut [F],
         public_inputs: &[F],
     ) -> Result<AirTrace<F>, Self::Error>;
+
+    /// Overwrites the values of the secret registers held by the generator with zeros.
+    ///
+    /// This is called by the prover once the last round of the trace is committed to.
+    fn zeroize_secrets(&self) {}
+
+    /// Whether the generator holds the values of secret registers.
+    fn has_secrets(&self) -> bool {
+        false
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -1,4 +1,8 @@
+pub mod columns;
+pub mod diff;
+#[cfg(feature = "prover")]
 pub mod generator;
+pub mod spot_check;
 pub mod view;
 pub mod window;
 pub mod window_parser;
@@ -84,6 +88,34 @@ impl<T> AirTrace<T> {
         }
     }
 
+    /// Overwrites the values of the columns in `columns` with `T::default()`.
+    ///
+    /// The trace is passed to `black_box` afterwards so that the writes are not optimized away
+    /// when the trace is dropped right after.
+    pub fn zeroize_columns(&mut self, columns: &[(usize, usize)])
+    where
+        T: Default + Copy,
+    {
+        if columns.is_empty() {
+            return;
+        }
+        for row in self.values.chunks_exact_mut(self.width) {
+            for &(start, end) in columns {
+                row[start..end].fill(T::default());
+            }
+        }
+        core::hint::black_box(&mut self.values);
+    }
+
+    /// Overwrites all the values of the trace with `T::default()`.
+    pub fn zeroize(&mut self)
+    where
+        T: Default + Copy,
+    {
+        self.values.fill(T::default());
+        core::hint::black_box(&mut self.values);
+    }
+
     #[inline]
     pub fn rows(&self) -> ChunksExact<'_, T> {
         self.values.chunks_exact(self.width)
@@ -0,0 +1,283 @@
+//! Evaluation of the constraints of an AIR on a subset of the rows of a trace.
+//!
+//! Checking a few rows of a generated trace is much faster than committing to it and generating a
+//! proof, which makes it suitable for smoke tests of very large traces. The rows are checked
+//! independently, so a failure is reported for every row and constraint that does not vanish.
+
+use core::fmt;
+
+use super::window::TraceWindow;
+use super::AirTrace;
+use crate::air::extension::cubic::CubicParser;
+use crate::air::parser::AirParser;
+use crate::air::RAir;
+use crate::math::prelude::*;
+use crate::polynomial::parser::PolynomialParser;
+
+/// The kind of a constraint, which determines the rows on which it applies.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConstraintKind {
+    All,
+    Transition,
+    FirstRow,
+    LastRow,
+    Global,
+}
+
+/// A constraint which does not vanish on a row of the trace.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConstraintFailure<F> {
+    /// The row of the trace, or `None` for global constraints.
+    pub row: Option<usize>,
+    /// The index of the constraint in the order of evaluation of the AIR.
+    pub index: usize,
+    pub kind: ConstraintKind,
+    /// The value of the constraint.
+    pub value: F,
+}
+
+impl<F: fmt::Debug> fmt::Display for ConstraintFailure<F> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.row {
+            Some(row) => write!(
+                f,
+                "{:?} constraint {} is {:?} at row {}",
+                self.kind, self.index, self.value, row
+            ),
+            None => write!(
+                f,
+                "{:?} constraint {} is {:?}",
+                self.kind, self.index, self.value
+            ),
+        }
+    }
+}
+
+/// A parser which records the constraints that do not vanish instead of panicking.
+#[derive(Debug, Clone)]
+pub struct SpotCheckParser<'a, F> {
+    window: TraceWindow<'a, F>,
+    challenge_slice: &'a [F],
+    global_slice: &'a [F],
+    public_slice: &'a [F],
+    is_global: bool,
+    num_constraints: usize,
+    failures: Vec<ConstraintFailure<F>>,
+}
+
+impl<'a, F: Field> SpotCheckParser<'a, F> {
+    pub fn new(
+        window: TraceWindow<'a, F>,
+        challenge_slice: &'a [F],
+        global_slice: &'a [F],
+        public_slice: &'a [F],
+    ) -> Self {
+        Self {
+            window,
+            challenge_slice,
+            global_slice,
+            public_slice,
+            is_global: false,
+            num_constraints: 0,
+            failures: Vec::new(),
+        }
+    }
+
+    /// A parser for the global constraints, which have no access to the trace.
+    pub fn new_global(
+        challenge_slice: &'a [F],
+        global_slice: &'a [F],
+        public_slice: &'a [F],
+    ) -> Self {
+        Self {
+            is_global: true,
+            ..Self::new(
+                TraceWindow::empty(),
+                challenge_slice,
+                global_slice,
+                public_slice,
+            )
+        }
+    }
+
+    /// The constraints that did not vanish.
+    pub fn failures(&self) -> &[ConstraintFailure<F>] {
+        &self.failures
+    }
+
+    pub fn into_failures(self) -> Vec<ConstraintFailure<F>> {
+        self.failures
+    }
+
+    fn check(&mut self, value: F, kind: ConstraintKind, applies: bool) {
+        let index = self.num_constraints;
+        self.num_constraints += 1;
+        if applies && value != F::ZERO {
+            let (row, kind) = if self.is_global {
+                (None, ConstraintKind::Global)
+            } else {
+                (Some(self.window.row), kind)
+            };
+            self.failures.push(ConstraintFailure {
+                row,
+                index,
+                kind,
+                value,
+            });
+        }
+    }
+}
+
+impl<'a, F: Field> AirParser for SpotCheckParser<'a, F> {
+    type Field = F;
+
+    type Var = F;
+
+    fn local_slice(&self) -> &[Self::Var] {
+        self.window.local_slice
+    }
+
+    fn next_slice(&self) -> &[Self::Var] {
+        self.window.next_slice
+    }
+
+    fn challenge_slice(&self) -> &[Self::Var] {
+        self.challenge_slice
+    }
+
+    fn global_slice(&self) -> &[Self::Var] {
+        self.global_slice
+    }
+
+    fn public_slice(&self) -> &[Self::Var] {
+        self.public_slice
+    }
+
+    fn constraint(&mut self, constraint: Self::Var) {
+        self.check(constraint, ConstraintKind::All, true);
+    }
+
+    fn constraint_transition(&mut self, constraint: Self::Var) {
+        let applies = !self.window.is_last_row;
+        self.check(constraint, ConstraintKind::Transition, applies);
+    }
+
+    fn constraint_first_row(&mut self, constraint: Self::Var) {
+        let applies = self.window.is_first_row;
+        self.check(constraint, ConstraintKind::FirstRow, applies);
+    }
+
+    fn constraint_last_row(&mut self, constraint: Self::Var) {
+        let applies = self.window.is_last_row;
+        self.check(constraint, ConstraintKind::LastRow, applies);
+    }
+
+    fn constant(&mut self, value: Self::Field) -> Self::Var {
+        value
+    }
+
+    fn add(&mut self, a: Self::Var, b: Self::Var) -> Self::Var {
+        a + b
+    }
+
+    fn sub(&mut self, a: Self::Var, b: Self::Var) -> Self::Var {
+        a - b
+    }
+
+    fn neg(&mut self, a: Self::Var) -> Self::Var {
+        -a
+    }
+
+    fn mul(&mut self, a: Self::Var, b: Self::Var) -> Self::Var {
+        a * b
+    }
+}
+
+impl<'a, F: Field> PolynomialParser for SpotCheckParser<'a, F> {}
+
+impl<'a, F: Field, E: CubicParameters<F>> CubicParser<E> for SpotCheckParser<'a, F> {}
+
+impl<F: Field> AirTrace<F> {
+    /// Evaluates the constraints of `air` on the given `rows` of the trace, and the global
+    /// constraints once.
+    ///
+    /// Returns every constraint which does not vanish, without committing to the trace.
+    pub fn check_rows<A>(
+        &self,
+        air: &A,
+        rows: impl IntoIterator<Item = usize>,
+        challenges: &[F],
+        global_values: &[F],
+        public_inputs: &[F],
+    ) -> Result<(), Vec<ConstraintFailure<F>>>
+    where
+        A: for<'a> RAir<SpotCheckParser<'a, F>>,
+    {
+        let mut parser = SpotCheckParser::new_global(challenges, global_values, public_inputs);
+        air.eval_global(&mut parser);
+        let mut failures = parser.into_failures();
+
+        for row in rows {
+            assert!(
+                row < self.height(),
+                "Row {} is out of the trace of height {}",
+                row,
+                self.height()
+            );
+            let window = self.window(row);
+            let mut parser = SpotCheckParser::new(window, challenges, global_values, public_inputs);
+            air.eval(&mut parser);
+            failures.extend(parser.into_failures());
+        }
+
+        if failures.is_empty() {
+            Ok(())
+        } else {
+            Err(failures)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use plonky2::field::goldilocks_field::GoldilocksField;
+
+    use super::*;
+    use crate::air::fibonacci::FibonacciAir;
+
+    #[test]
+    fn test_spot_check_rows() {
+        type F = GoldilocksField;
+
+        let num_rows = 1 << 16;
+        let air = FibonacciAir::new();
+        let public_inputs = [
+            F::ZERO,
+            F::ONE,
+            FibonacciAir::fibonacci(num_rows - 1, F::ZERO, F::ONE),
+        ];
+        let mut trace = FibonacciAir::generate_trace(F::ZERO, F::ONE, num_rows);
+
+        let rows = [0, 1, 1000, num_rows - 2, num_rows - 1];
+        trace
+            .check_rows(&air, rows, &[], &[], &public_inputs)
+            .unwrap();
+
+        // Corrupt a single row, which is only detected by the windows that contain it.
+        trace.row_mut(1000)[1] += F::ONE;
+        trace
+            .check_rows(&air, [0, 500, num_rows - 1], &[], &[], &public_inputs)
+            .unwrap();
+        let failures = trace
+            .check_rows(&air, [998, 999, 1000, 1001], &[], &[], &public_inputs)
+            .unwrap_err();
+        assert!(failures
+            .iter()
+            .all(|failure| failure.row == Some(999) || failure.row == Some(1000)));
+        assert!(failures
+            .iter()
+            .all(|failure| failure.kind == ConstraintKind::Transition));
+        assert!(failures.iter().any(|failure| failure.row == Some(999)));
+        assert!(failures.iter().any(|failure| failure.row == Some(1000)));
+    }
+}
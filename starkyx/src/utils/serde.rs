@@ -1,3 +1,6 @@
+use alloc::vec;
+use alloc::vec::Vec;
+
 use plonky2::fri::proof::FriProofTarget;
 use plonky2::hash::hash_types::{HashOutTarget, MerkleCapTarget};
 use plonky2::iop::ext_target::ExtensionTarget;
@@ -64,6 +67,26 @@ impl<'de> Deserialize<'de> for SerdeHashOut {
     }
 }
 
+pub fn serialize_option_hash_out_target<S>(
+    hash_out: &Option<HashOutTarget>,
+    serializer: S,
+) -> Result<S::Ok, S::Error>
+where
+    S: serde::Serializer,
+{
+    hash_out.map(SerdeHashOut).serialize(serializer)
+}
+
+pub fn deserialize_option_hash_out_target<'de, D>(
+    deserializer: D,
+) -> Result<Option<HashOutTarget>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    let hash_out = Option::<SerdeHashOut>::deserialize(deserializer)?;
+    Ok(hash_out.map(|x| x.0))
+}
+
 pub fn serialize_merkle_cap_target<S>(
     merkle_cap_target: &MerkleCapTarget,
     serializer: S,
@@ -146,7 +169,7 @@ where
     elements.serialize(serializer)
 }
 
-fn deserialize_extension_target<'de, D, const DEG: usize>(
+pub fn deserialize_extension_target<'de, D, const DEG: usize>(
     deserializer: D,
 ) -> Result<ExtensionTarget<DEG>, D::Error>
 where
@@ -202,6 +225,39 @@ where
     Ok(elements.into_iter().map(|x| x.0).collect::<Vec<_>>())
 }
 
+#[allow(clippy::ptr_arg)]
+pub fn serialize_extension_target_groups<S, const D: usize>(
+    extension_target_groups: &Vec<Vec<ExtensionTarget<D>>>,
+    serializer: S,
+) -> Result<S::Ok, S::Error>
+where
+    S: serde::Serializer,
+{
+    extension_target_groups
+        .iter()
+        .map(|group| {
+            group
+                .iter()
+                .map(|x| SerdeExtensionTarget(*x))
+                .collect::<Vec<_>>()
+        })
+        .collect::<Vec<_>>()
+        .serialize(serializer)
+}
+
+pub fn deserialize_extension_target_groups<'de, D, const DEG: usize>(
+    deserializer: D,
+) -> Result<Vec<Vec<ExtensionTarget<DEG>>>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    let groups = Vec::<Vec<SerdeExtensionTarget<DEG>>>::deserialize(deserializer)?;
+    Ok(groups
+        .into_iter()
+        .map(|group| group.into_iter().map(|x| x.0).collect())
+        .collect())
+}
+
 pub fn serialize_fri_proof_target<S, const D: usize>(
     fri_proof_target: &FriProofTarget<D>,
     serializer: S,
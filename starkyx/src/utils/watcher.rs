@@ -1,3 +1,7 @@
+use alloc::format;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+
 use anyhow::Result;
 use log::{log, Level};
 use plonky2::field::extension::Extendable;